@@ -0,0 +1,205 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+#![deny(
+    unsafe_code,
+    dead_code,
+    missing_docs,
+    unused_variables,
+    unused_mut,
+    unused_imports,
+    non_upper_case_globals,
+    non_camel_case_types,
+    non_snake_case
+)]
+#![cfg_attr(coverage_nightly, feature(coverage_attribute))]
+
+//! Procedural macros for zk-AluVM assembly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+use zkaluvm::gfa::{AsmLimit, Assembly};
+
+/// Assembles a program from its textual representation at compile time.
+///
+/// The macro accepts a string literal with the program source in the syntax of
+/// [`zkaluvm::gfa::Assembly`], supporting labels, macros, comments and directives. The source is
+/// parsed during the compilation, and any syntax error, as well as a violation of the limits
+/// declared with `.assert_size` and `.assert_complexity` directives, is reported as a compilation
+/// error.
+///
+/// The macro expands into an expression of `Vec<zkaluvm::gfa::Instr<zkaluvm::alu::LibId>>` type,
+/// matching the output of `zkaluvm::zk_aluasm!`.
+///
+/// # Example
+///
+/// ```
+/// use zkaluvm_macros::zk_aluasm_str;
+///
+/// let code = zk_aluasm_str!(
+///     r#"
+///     .assert_size < 32
+///
+///     routine START:
+///         put     E1, 0
+///         put     E2, 1
+///     again:
+///         add     E1, E2      ; increment E1
+///         fits    E1, 8.bits
+///         jif     CO, again
+///         ret
+///     "#
+/// );
+/// assert_eq!(code.len(), 7);
+/// ```
+///
+/// Errors are reported at compile time:
+///
+/// ```compile_fail
+/// use zkaluvm_macros::zk_aluasm_str;
+///
+/// let code = zk_aluasm_str!("put E1, 0\njmp missing_label");
+/// ```
+///
+/// ```compile_fail
+/// use zkaluvm_macros::zk_aluasm_str;
+///
+/// let code = zk_aluasm_str!(".assert_size < 4\nput E1, 0\nput E2, 0");
+/// ```
+#[proc_macro]
+pub fn zk_aluasm_str(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    if let Err(err) = assemble(&lit) {
+        return err.to_compile_error().into();
+    }
+
+    quote! {{
+        let asm: ::zkaluvm::gfa::Assembly = ::core::str::FromStr::from_str(#lit)
+            .expect("zk-AluVM assembly is validated at compile time");
+        asm.into_code()
+    }}
+    .into()
+}
+
+/// Assembles a program from its textual representation at compile time, producing its source
+/// map.
+///
+/// Works the same way as [`zk_aluasm_str!`], but expands into an expression of
+/// `(Vec<zkaluvm::gfa::Instr<zkaluvm::alu::LibId>>, zkaluvm::gfa::SourceMap)` type. Source lines
+/// in the map are counted from the beginning of the string literal, starting with 1.
+///
+/// # Example
+///
+/// ```
+/// use zkaluvm_macros::zk_aluasm_map;
+///
+/// let (code, map) = zk_aluasm_map!(
+///     r#"
+///     put     E1, 0
+///     add     E1, E1
+///     "#
+/// );
+/// assert_eq!(code.len(), 2);
+/// assert_eq!(map.line_at(2), Some(3));
+/// ```
+#[proc_macro]
+pub fn zk_aluasm_map(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    if let Err(err) = assemble(&lit) {
+        return err.to_compile_error().into();
+    }
+
+    quote! {{
+        let asm: ::zkaluvm::gfa::Assembly = ::core::str::FromStr::from_str(#lit)
+            .expect("zk-AluVM assembly is validated at compile time");
+        (asm.code, asm.source_map)
+    }}
+    .into()
+}
+
+/// Parses the assembly source and checks the limits it declares.
+fn assemble(lit: &LitStr) -> Result<Assembly, syn::Error> {
+    let source = lit.value();
+
+    let asm = Assembly::parse(&source).map_err(|err| {
+        let span = line_span(lit, err.line);
+        syn::Error::new(span, format!("invalid zk-AluVM assembly at {err}"))
+    })?;
+
+    if let Some(error) = asm
+        .violated_limits()
+        .into_iter()
+        .map(|limit| {
+            let val = limit.measure(&asm.code);
+            let msg = match limit {
+                AsmLimit::Size { max, inclusive } => {
+                    let op = if inclusive { "<=" } else { "<" };
+                    format!("program code size is {val} bytes, which violates `.assert_size {op} {max}`")
+                }
+                AsmLimit::Complexity { max, inclusive } => {
+                    let op = if inclusive { "<=" } else { "<" };
+                    format!("program complexity is {val}, which violates `.assert_complexity {op} {max}`")
+                }
+            };
+            syn::Error::new(lit.span(), msg)
+        })
+        .reduce(|mut acc, err| {
+            acc.combine(err);
+            acc
+        })
+    {
+        return Err(error);
+    }
+
+    Ok(asm)
+}
+
+/// Detects span of a specific source line inside the string literal.
+///
+/// Falls back to the span of the whole literal if the compiler doesn't support sub-spans, or if
+/// the literal contains escape sequences making the source offsets differ from the token ones.
+fn line_span(lit: &LitStr, line: usize) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let Some(start) = repr.find('"').map(|pos| pos + 1) else {
+        return lit.span();
+    };
+    if !repr.starts_with('r') && repr.contains('\\') {
+        return lit.span();
+    }
+    let source = lit.value();
+    let offset = source
+        .split('\n')
+        .take(line.saturating_sub(1))
+        .map(|l| l.len() + 1)
+        .sum::<usize>();
+    let len = source[offset.min(source.len())..]
+        .split('\n')
+        .next()
+        .map(str::len)
+        .unwrap_or_default();
+    token
+        .subspan(start + offset..start + offset + len)
+        .unwrap_or_else(|| lit.span())
+}
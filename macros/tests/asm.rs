@@ -0,0 +1,86 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use aluvm::LibId;
+use zkaluvm::gfa::Instr;
+use zkaluvm::zk_aluasm;
+use zkaluvm_macros::{zk_aluasm_map, zk_aluasm_str};
+
+#[test]
+fn same_as_zk_aluasm() {
+    let text: Vec<Instr<LibId>> = zk_aluasm_str!(
+        r#"
+        // same program as with zk_aluasm!
+        .assert_complexity <= 100_000_000
+        routine START:
+            put     E1, 0
+            put     E2, 0
+            mov     E3, E1
+            eq      E1, E2
+            chk     CO
+            neg     E3, E1
+            mul     E1, E2
+            fits    E1, 8.bits
+            jif     CO, +3
+            not     CO
+            fail    CK
+            jmp     START
+            ret
+        "#
+    );
+    let masm = zk_aluasm! {
+        proc START:
+            put     E1, 0;
+            put     E2, 0;
+            mov     E3, E1;
+            eq      E1, E2;
+            chk     CO;
+            neg     E3, E1;
+            mul     E1, E2;
+            fits    E1, 8.bits;
+            jif     CO, +3;
+            not     CO;
+            fail    CK;
+            jmp     0;
+            ret;
+    };
+    assert_eq!(text, masm);
+}
+
+#[test]
+fn labels() {
+    let code = zk_aluasm_str!("jmp end\nnop\nend:\nstop");
+    assert_eq!(code, zk_aluasm! { jmp 4; nop; stop; });
+}
+
+#[test]
+fn source_map() {
+    let (code, map) = zk_aluasm_map!("jmp end\nnop\n\nend:\nstop");
+    assert_eq!(code, zk_aluasm! { jmp 4; nop; stop; });
+    assert_eq!(
+        map.iter()
+            .map(|loc| (loc.pos, loc.line))
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (3, 2), (4, 5)]
+    );
+    assert_eq!(map.line_at(1), Some(1));
+}
@@ -0,0 +1,228 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! ASCII-armored text representation of zk-AluVM libraries.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use aluvm::{Lib, LibArmorError, LibId};
+use armor::{ArmorHeader, AsciiArmor, ASCII_ARMOR_CHECKSUM_SHA256, ASCII_ARMOR_ID};
+
+const ASCII_ARMOR_ISAE: &str = "ISA-Extensions";
+const ASCII_ARMOR_DEPENDENCY: &str = "Dependency";
+
+/// Errors decoding an ASCII-armored library.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ArmorError {
+    /// {0}
+    #[from]
+    #[from(armor::ArmorParseError)]
+    Lib(LibArmorError),
+
+    /// ASCII armor misses the checksum of the library data.
+    NoChecksum,
+
+    /// ASCII armor misses the library id.
+    NoId,
+
+    /// ASCII armor header '{0}' must have a single value.
+    MultipleValues(String),
+
+    /// unrecognized header '{0}' in the ASCII armor.
+    UnknownHeader(String),
+
+    /// ASCII armor declares library id {expected}, while the actual library id is {actual}.
+    IdMismatch {
+        /// Library id declared in the armor headers.
+        expected: String,
+        /// Id of the decoded library.
+        actual: LibId,
+    },
+
+    /// ASCII armor declares ISA extensions '{expected}', while the library uses '{actual}'.
+    IsaeMismatch {
+        /// ISA extensions declared in the armor headers.
+        expected: String,
+        /// ISA extensions used by the decoded library.
+        actual: String,
+    },
+
+    /// ASCII armor declares dependencies which don't match the libraries called by the library.
+    DependencyMismatch,
+}
+
+/// Encodes a library into ASCII-armored text.
+///
+/// The text starts with the headers providing the library id, the list of ISA extensions used by
+/// the library, the libraries it depends on and the SHA-256 checksum of the library data, followed
+/// by the strict-encoded library in Base85 encoding. It is suitable for pasting programs into
+/// issue trackers, configuration files or text fields of on-chain data, and can be decoded with
+/// [`parse_armored_lib`].
+pub fn armor_lib(lib: &Lib) -> String { lib.to_ascii_armored_string() }
+
+/// Decodes a library from its ASCII-armored text produced by [`armor_lib`].
+///
+/// Unlike [`AsciiArmor::from_ascii_armored_str`], requires the checksum to be present and checks
+/// that the library id, ISA extensions and dependencies declared in the headers match the decoded
+/// library. Text before and after the armor is ignored.
+pub fn parse_armored_lib(s: &str) -> Result<Lib, ArmorError> {
+    let lib = Lib::from_ascii_armored_str(s)?;
+
+    let first = format!("-----BEGIN {}-----", Lib::PLATE_TITLE);
+    let mut checksum = false;
+    let mut id = None;
+    let mut isae = None;
+    let mut deps = BTreeSet::new();
+    for line in s
+        .lines()
+        .skip_while(|line| *line != first)
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+    {
+        let header = line.parse::<ArmorHeader>().map_err(LibArmorError::from)?;
+        let [value] = header.values.as_slice() else {
+            return Err(ArmorError::MultipleValues(header.title));
+        };
+        match header.title.as_str() {
+            ASCII_ARMOR_CHECKSUM_SHA256 => checksum = true,
+            ASCII_ARMOR_ID => id = Some(value.clone()),
+            ASCII_ARMOR_ISAE => isae = Some(value.clone()),
+            ASCII_ARMOR_DEPENDENCY => {
+                deps.insert(value.clone());
+            }
+            _ => return Err(ArmorError::UnknownHeader(header.title)),
+        }
+    }
+
+    if !checksum {
+        return Err(ArmorError::NoChecksum);
+    }
+    let expected = id.ok_or(ArmorError::NoId)?;
+    let actual = lib.lib_id();
+    if expected != actual.to_string() {
+        return Err(ArmorError::IdMismatch { expected, actual });
+    }
+    let actual = lib.isae_string();
+    let expected = isae.unwrap_or_default();
+    if expected != actual {
+        return Err(ArmorError::IsaeMismatch { expected, actual });
+    }
+    if deps != lib.libs.iter().map(LibId::to_string).collect() {
+        return Err(ArmorError::DependencyMismatch);
+    }
+    Ok(lib)
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::borrow::ToOwned;
+
+    use aluvm::LibSite;
+
+    use super::*;
+    use crate::gfa::Assembly;
+
+    fn lib() -> Lib {
+        Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 0x1234_5678_9ABC_DEF0_1234_5678_9ABC_DEF0
+                mul     E1, E1
+                chk     CK
+                ret
+            "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let lib = lib();
+        let armor = armor_lib(&lib);
+        assert!(armor.starts_with("-----BEGIN ALUVM LIB-----\n"));
+        assert!(armor.contains(&format!("Id: {}\n", lib.lib_id())));
+        assert!(armor.contains("ISA-Extensions: GFA256\n"));
+        assert_eq!(parse_armored_lib(&armor), Ok(lib.clone()));
+        assert_eq!(parse_armored_lib(&format!("Program:\n\n{armor}\nEnd of program\n")), Ok(lib));
+    }
+
+    #[test]
+    fn dependencies() {
+        let dep = lib().lib_id();
+        let code = format!("call {}\nret", LibSite::new(dep, 0));
+        let lib = Assembly::parse(&code).unwrap().assemble().unwrap();
+        let armor = armor_lib(&lib);
+        assert!(armor.contains(&format!("Dependency: {dep}\n")));
+        assert_eq!(parse_armored_lib(&armor), Ok(lib));
+
+        let forged = armor.replace(&format!("Dependency: {dep}\n"), "");
+        assert_eq!(parse_armored_lib(&forged), Err(ArmorError::DependencyMismatch));
+    }
+
+    #[test]
+    fn tampered() {
+        let lib = lib();
+        let armor = armor_lib(&lib);
+        let header = |title: &str| {
+            armor
+                .lines()
+                .find(|line| line.starts_with(title))
+                .unwrap()
+                .to_owned()
+        };
+
+        let no_checksum = armor.replace(&format!("{}\n", header("Check-SHA256")), "");
+        assert_eq!(parse_armored_lib(&no_checksum), Err(ArmorError::NoChecksum));
+
+        let no_id = armor.replace(&format!("{}\n", header("Id")), "");
+        assert_eq!(parse_armored_lib(&no_id), Err(ArmorError::NoId));
+
+        let other = armor_lib(&Assembly::parse("stop").unwrap().assemble().unwrap());
+        let other_id = other.lines().find(|line| line.starts_with("Id")).unwrap();
+        let wrong_id = armor.replace(&header("Id"), other_id);
+        assert!(matches!(parse_armored_lib(&wrong_id), Err(ArmorError::IdMismatch { .. })));
+
+        let wrong_isae = armor.replace("ISA-Extensions: GFA256", "ISA-Extensions: ALU64");
+        assert_eq!(
+            parse_armored_lib(&wrong_isae),
+            Err(ArmorError::IsaeMismatch {
+                expected: s!("ALU64"),
+                actual: s!("GFA256")
+            })
+        );
+
+        let unknown = armor.replace("ISA-Extensions:", "Comment: x\nISA-Extensions:");
+        assert_eq!(parse_armored_lib(&unknown), Err(ArmorError::UnknownHeader(s!("Comment"))));
+
+        let payload = armor.lines().nth(5).unwrap();
+        let mut corrupted = payload.to_owned();
+        corrupted.replace_range(0..1, if payload.starts_with('0') { "1" } else { "0" });
+        assert!(matches!(parse_armored_lib(&armor.replace(payload, &corrupted)), Err(ArmorError::Lib(_))));
+    }
+}
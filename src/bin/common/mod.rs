@@ -0,0 +1,42 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Helpers shared by the command-line tools.
+
+use std::str::FromStr;
+
+use amplify::num::u256;
+use zkaluvm::{fe256, FIELD_ORDER_25519, FIELD_ORDER_SECP, FIELD_ORDER_STARK};
+
+/// Parses field order from a preset name or a hexadecimal value.
+pub fn parse_field(s: &str) -> Result<u256, String> {
+    match s {
+        "25519" => Ok(FIELD_ORDER_25519),
+        "secp" => Ok(FIELD_ORDER_SECP),
+        "stark" => Ok(FIELD_ORDER_STARK),
+        _ => s
+            .strip_prefix("0x")
+            .and_then(|hex| fe256::from_str(&format!("{hex}.fe")).ok())
+            .map(|fe| fe.to_u256())
+            .ok_or_else(|| format!("invalid field `{s}`")),
+    }
+}
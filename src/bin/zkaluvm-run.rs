@@ -0,0 +1,257 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+#![cfg_attr(coverage_nightly, feature(coverage_attribute), coverage(off))]
+
+//! Command-line runner for zk-AluVM libraries.
+
+mod common;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::{env, fs};
+
+use aluvm::isa::{Bytecode, BytecodeRead, CtrlInstr, ExecStep, Instruction};
+use aluvm::{Core, CoreConfig, CoreExt, Lib, LibId, LibSite, Marshaller, Site, Vm};
+use amplify::confinement::{Confined, U24 as U24MAX};
+use common::parse_field;
+use strict_encoding::StrictDeserialize;
+use zkaluvm::gfa::{Instr, NO_CONTEXT};
+use zkaluvm::{fe256, parse_armored_lib, GfaConfig, GfaCore, RegE};
+
+const USAGE: &str = "Usage: zkaluvm-run [OPTIONS] <LIB>
+
+Runs a zk-AluVM library, provided either in binary or in ASCII armored format, and prints the final
+state of the VM registers.
+
+Options:
+  -i, --input <REG>=<VALUE>   put a field element into a register before the execution; the value
+                              is a decimal number, a hexadecimal number prefixed with `0x`, or a
+                              hexadecimal value with `.fe` suffix
+  -e, --entry <OFFSET>        offset of the entry point in the library code (0 by default)
+  -f, --field <FIELD>         field used by the VM: `25519` (default), `secp`, `stark`, or a
+                              hexadecimal field order prefixed with `0x`
+  -c, --complexity <LIMIT>    limit on the execution complexity
+  -n, --no-halt               continue the execution after failures
+  -t, --trace                 print each executed instruction
+  -h, --help                  print this help message";
+
+struct Args {
+    lib: PathBuf,
+    inputs: Vec<(RegE, fe256)>,
+    entry: u16,
+    field: GfaConfig,
+    config: CoreConfig,
+    trace: bool,
+}
+
+fn parse_input(s: &str) -> Result<(RegE, fe256), String> {
+    let (reg, val) = s
+        .split_once('=')
+        .ok_or_else(|| format!("input `{s}` must have `<REG>=<VALUE>` form"))?;
+    let reg = RegE::from_str(reg.trim()).map_err(|err| err.to_string())?;
+    let val = fe256::parse_literal(val.trim()).map_err(|err| err.to_string())?;
+    Ok((reg, val))
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut lib = None;
+    let mut inputs = vec![];
+    let mut entry = 0;
+    let mut field = GfaConfig::default();
+    let mut config = CoreConfig::default();
+    let mut trace = false;
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or_else(|| format!("missing {name}"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-i" | "--input" => inputs.push(parse_input(&value("input")?)?),
+            "-e" | "--entry" => {
+                let val = value("entry point")?;
+                entry = val
+                    .parse()
+                    .map_err(|_| format!("invalid entry point `{val}`"))?;
+            }
+            "-f" | "--field" => field.field_order = parse_field(&value("field name")?)?,
+            "-c" | "--complexity" => {
+                let val = value("complexity limit")?;
+                let lim = val
+                    .parse()
+                    .map_err(|_| format!("invalid complexity limit `{val}`"))?;
+                config.complexity_lim = Some(lim);
+            }
+            "-n" | "--no-halt" => config.halt = false,
+            "-t" | "--trace" => trace = true,
+            opt if opt.starts_with('-') => return Err(format!("unknown option `{opt}`")),
+            file if lib.is_none() => lib = Some(PathBuf::from(file)),
+            _ => return Err("only a single library can be run".to_owned()),
+        }
+    }
+    let lib = lib.ok_or("no library file")?;
+    Ok(Some(Args {
+        lib,
+        inputs,
+        entry,
+        field,
+        config,
+        trace,
+    }))
+}
+
+fn load_lib(path: &PathBuf) -> Result<Lib, String> {
+    let data = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let err = |err: String| format!("{}: invalid library: {err}", path.display());
+    if data.starts_with(b"-----BEGIN") {
+        let s = String::from_utf8(data).map_err(|e| err(e.to_string()))?;
+        parse_armored_lib(&s).map_err(|e| err(e.to_string()))
+    } else {
+        let data = Confined::<Vec<u8>, 0, U24MAX>::try_from(data).map_err(|e| err(e.to_string()))?;
+        Lib::from_strict_serialized::<U24MAX>(data).map_err(|e| err(e.to_string()))
+    }
+}
+
+/// Sets `CK` to a failed state, returning whether the execution must halt.
+fn fail_ck(site: Site<LibId>, core: &mut Core<LibId, GfaCore>) -> bool {
+    Instr::<LibId>::Ctrl(CtrlInstr::FailCk).exec(site, core, &NO_CONTEXT) == ExecStep::Stop
+}
+
+/// Executes library code instruction by instruction, printing each of them, following the same
+/// rules as [`Lib::exec`].
+///
+/// Calls of external libraries are not supported and fail `CK`.
+fn trace(lib: &Lib, entry: u16, core: &mut Core<LibId, GfaCore>) {
+    let lib_id = lib.lib_id();
+    let mut marshaller = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+    let mut pos = entry;
+    let mut skip = false;
+    loop {
+        if marshaller.seek(pos).is_err() {
+            println!("halting: jump to non-existing offset {pos:04X}");
+            fail_ck(Site::new(lib_id, pos), core);
+            return;
+        }
+        if skip && Instr::<LibId>::decode_instr(&mut marshaller).is_err() {
+            println!("halting: unable to decode instruction at {pos:04X}");
+            return;
+        }
+
+        (pos, skip) = loop {
+            if marshaller.is_eof() {
+                return;
+            }
+            let at = marshaller.pos();
+            let site = Site::new(lib_id, at);
+            let Ok(instr) = Instr::<LibId>::decode_instr(&mut marshaller) else {
+                println!("halting: unable to decode instruction at {at:04X}");
+                return;
+            };
+
+            let step = instr.exec(site, core, &NO_CONTEXT);
+            let overflow = !core.acc_complexity(instr.complexity());
+            let halt = if overflow || step == ExecStep::Fail { fail_ck(site, core) || overflow } else { false };
+
+            let regs = instr
+                .dst_regs()
+                .into_iter()
+                .map(|reg| match core.cx.get(reg) {
+                    Some(val) => format!("{reg} {val}"),
+                    None => format!("{reg} ~"),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("{at:04X}: {: <32} ; CO {}, CK {}; {regs}", instr.to_string(), core.co(), core.ck());
+
+            if overflow {
+                println!("halting: complexity limit is reached");
+                return;
+            }
+            match step {
+                ExecStep::Stop => return,
+                ExecStep::Fail if halt => {
+                    println!("halting: CK is failed");
+                    return;
+                }
+                ExecStep::Fail | ExecStep::Next => {}
+                ExecStep::Jump(new_pos) => {
+                    if marshaller.seek(new_pos).is_err() {
+                        println!("halting: jump to non-existing offset {new_pos:04X}");
+                        fail_ck(site, core);
+                        return;
+                    }
+                }
+                ExecStep::Call(target) | ExecStep::Ret(target) if target.prog_id != lib_id => {
+                    println!("halting: external library {} is not available", target.prog_id);
+                    fail_ck(site, core);
+                    return;
+                }
+                ExecStep::Call(target) => break (target.offset, false),
+                ExecStep::Ret(target) => break (target.offset, true),
+            }
+        };
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let lib = load_lib(&args.lib)?;
+    println!("; library {}", lib.lib_id());
+
+    let mut vm = Vm::<Instr<LibId>>::with(args.config, args.field);
+    for (reg, val) in args.inputs {
+        if val.to_u256() >= args.field.field_order {
+            return Err(format!("input value {val} for {reg} is not a canonical field element"));
+        }
+        vm.core.cx.set(reg, val);
+    }
+
+    if args.trace {
+        trace(&lib, args.entry, &mut vm.core);
+    } else {
+        let lib_id = lib.lib_id();
+        vm.exec(LibSite::new(lib_id, args.entry), &NO_CONTEXT, |id| if id == lib_id { Some(&lib) } else { None });
+    }
+
+    println!("{:?}", vm.core);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+#![cfg_attr(coverage_nightly, feature(coverage_attribute), coverage(off))]
+
+//! Command-line assembler for zk-AluVM programs.
+
+mod common;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{env, fs};
+
+use amplify::num::u256;
+use common::parse_field;
+use zkaluvm::gfa::{AsmConfig, AsmLimit, Assembly, FieldInstr, Instr, IsaSpec};
+use zkaluvm::{armor_lib, serialize_lib, FIELD_ORDER_25519};
+
+const USAGE: &str = "Usage: zkas [OPTIONS] <FILE>...
+
+Assembles zk-AluVM programs into a library. Multiple files are linked into a single library in the
+order they are given, sharing the same label namespace.
+
+Options:
+  -o, --output <FILE>   write the assembled library to the file
+  -a, --armor           write the library in ASCII armored format
+  -f, --field <FIELD>   field used to check canonicality of the constants and to select the code
+                        with `field = \"...\"` conditions: `25519` (default), `secp`, `stark`, or
+                        a hexadecimal field order prefixed with `0x`
+  -c, --cfg <OPT>       set configuration option for the conditional assembly, either a flag
+                        `NAME` or a key-value pair `KEY=VALUE`; may be repeated
+      --isa-spec        print the machine-readable GFA256 ISA specification in JSON format and
+                        exit
+  -h, --help            print this help message";
+
+struct Args {
+    files: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    armor: bool,
+    field_order: u256,
+    options: Vec<String>,
+    isa_spec: bool,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = Args {
+        files: vec![],
+        output: None,
+        armor: false,
+        field_order: FIELD_ORDER_25519,
+        options: vec![],
+        isa_spec: false,
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-a" | "--armor" => args.armor = true,
+            "--isa-spec" => args.isa_spec = true,
+            "-o" | "--output" => {
+                let path = iter.next().ok_or("missing output file name")?;
+                args.output = Some(PathBuf::from(path));
+            }
+            "-f" | "--field" => {
+                let field = iter.next().ok_or("missing field name")?;
+                args.field_order = parse_field(&field)?;
+            }
+            "-c" | "--cfg" => {
+                let opt = iter.next().ok_or("missing configuration option")?;
+                args.options.push(opt);
+            }
+            opt if opt.starts_with('-') => return Err(format!("unknown option `{opt}`")),
+            file => args.files.push(PathBuf::from(file)),
+        }
+    }
+    if args.files.is_empty() && !args.isa_spec {
+        return Err("no input files".to_owned());
+    }
+    Ok(Some(args))
+}
+
+/// Source file linked into the program.
+struct Source {
+    path: PathBuf,
+    first_line: usize,
+}
+
+/// Resolves line in the linked source into the file name and the line number in the file.
+fn locate(sources: &[Source], line: usize) -> String {
+    let src = sources
+        .iter()
+        .rev()
+        .find(|src| src.first_line <= line)
+        .expect("line numbers start from 1");
+    format!("{}:{}", src.path.display(), line - src.first_line + 1)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    if args.isa_spec {
+        let spec = serde_json::to_string_pretty(&IsaSpec::current()).map_err(|err| err.to_string())?;
+        println!("{spec}");
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    let mut sources = vec![];
+    for path in args.files {
+        let source = fs::read_to_string(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+        sources.push(Source {
+            path,
+            first_line: text.lines().count() + 1,
+        });
+        text.push_str(&source);
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+
+    let mut config = AsmConfig::with_field(args.field_order);
+    for opt in &args.options {
+        match opt.split_once('=') {
+            Some((key, value)) => config.set_value(key.trim(), value.trim().trim_matches('"')),
+            None => config.set_flag(opt.trim()),
+        }
+    }
+
+    let asm =
+        Assembly::parse_with(&text, &config).map_err(|err| format!("{}: {}", locate(&sources, err.line), err.kind))?;
+
+    let mut errors = vec![];
+    for limit in asm.violated_limits() {
+        let val = limit.measure(&asm.code);
+        errors.push(match limit {
+            AsmLimit::Size { max, inclusive } => {
+                let op = if inclusive { "<=" } else { "<" };
+                format!("program code size is {val} bytes, which violates `.assert_size {op} {max}`")
+            }
+            AsmLimit::Complexity { max, inclusive } => {
+                let op = if inclusive { "<=" } else { "<" };
+                format!("program complexity is {val}, which violates `.assert_complexity {op} {max}`")
+            }
+        });
+    }
+    for decl in &asm.data {
+        for value in decl
+            .values()
+            .iter()
+            .filter(|value| value.to_u256() >= args.field_order)
+        {
+            errors.push(format!(
+                "data `{}` value {value} is not a canonical element of the field with order {:#x}",
+                decl.name(),
+                args.field_order
+            ));
+        }
+    }
+    for (no, instr) in asm.code.iter().enumerate() {
+        if let Instr::Gfa(FieldInstr::PutD { data, .. }) = instr {
+            if data.to_u256() >= args.field_order {
+                let line = asm
+                    .source_map
+                    .get(no)
+                    .expect("source map covers all instructions")
+                    .line;
+                errors.push(format!(
+                    "{}: constant {data} is not a canonical element of the field with order {:#x}",
+                    locate(&sources, line as usize),
+                    args.field_order
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    let lib = asm.assemble().map_err(|err| err.to_string())?;
+    println!("{}", lib.lib_id());
+
+    if let Some(output) = args.output {
+        let data = if args.armor { armor_lib(&lib).into_bytes() } else { serialize_lib(&lib) };
+        fs::write(&output, data).map_err(|err| format!("{}: {err}", output.display()))?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
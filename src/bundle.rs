@@ -0,0 +1,457 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Bundles of several libraries shipped together with their dependency graph and entry points.
+
+use alloc::boxed::Box;
+use alloc::collections::{btree_map, BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::CtrlInstr;
+use aluvm::{Lib, LibId, LibSite};
+
+use crate::gfa::Instr;
+use crate::{validate_lib, CurveError, GfaConfig, LibCache, LoadError};
+
+/// Version of the library bundle format produced by this crate.
+pub const BUNDLE_VERSION: u8 = 1;
+
+/// Errors reading a library bundle.
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum BundleError {
+    /// invalid CBOR encoding of the library bundle: {0}
+    Decode(String),
+
+    /// I/O error writing the library bundle: {0}
+    Encode(String),
+
+    /// unsupported library bundle version {0}.
+    UnsupportedVersion(u8),
+
+    /// library {0} is invalid: {1}
+    InvalidLib(LibId, Box<LoadError>),
+
+    /// invalid curve parameters in the field configuration: {0}
+    #[from]
+    InvalidCurve(CurveError),
+
+    /// library {0} is present in the bundle more than once.
+    DuplicateLib(LibId),
+
+    /// dependencies of library {0} in the bundle graph don't match its library segment.
+    GraphMismatch(LibId),
+
+    /// library {0} depends on library {1}, which is not present in the bundle.
+    MissingDependency(LibId, LibId),
+
+    /// library {0} refers to {1}, which is outside of the code segment of the referenced library.
+    ExternalOutOfCode(LibId, LibSite),
+
+    /// entry point '{0}' refers to {1}, which is not present in the bundle.
+    EntryOutOfBundle(String, LibSite),
+}
+
+/// Artifact shipping several libraries together with their dependency graph and named entry
+/// points, such that a complete suite of programs can be distributed as a single file.
+///
+/// The bundle is serialized with CBOR. Reading a bundle validates all the libraries against the
+/// bundled configuration and checks that all the references to external libraries are satisfied
+/// by the bundle itself; the libraries can then be executed with the resolver returned by
+/// [`LibBundle::cache`].
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::isa::CtrlInstr;
+/// # use aluvm::{CoreConfig, Lib, LibId, LibSite, Site, Vm};
+/// # use zkaluvm::gfa::{Instr, NO_CONTEXT};
+/// # use zkaluvm::{zk_aluasm, GfaConfig, LibBundle};
+/// let callee = Lib::assemble(&zk_aluasm! { mul E1, E1; ret; }).unwrap();
+/// let mut code = zk_aluasm! { put E1, 7; };
+/// code.push(
+///     CtrlInstr::Call {
+///         site: Site::new(callee.lib_id(), 0),
+///     }
+///     .into(),
+/// );
+/// let caller = Lib::assemble(&code).unwrap();
+///
+/// let mut bundle = LibBundle::new(GfaConfig::default());
+/// bundle.add_lib(callee);
+/// let entry = bundle.add_lib(caller);
+/// bundle
+///     .entry_points
+///     .insert("square".into(), LibSite::new(entry, 0));
+///
+/// let bundle = LibBundle::from_cbor(&bundle.to_cbor()).unwrap();
+/// let cache = bundle.cache();
+/// let mut vm = Vm::<Instr<LibId>>::with(CoreConfig::default(), bundle.config);
+/// let site = bundle.entry("square").unwrap();
+/// assert!(vm.exec(site, &NO_CONTEXT, cache.resolver()).is_ok());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibBundle {
+    /// Version of the bundle format.
+    pub version: u8,
+    /// Configuration of the field the libraries run in.
+    pub config: GfaConfig,
+    /// Libraries in the bundle.
+    pub libs: Vec<Lib>,
+    /// Dependency graph, mapping each library to the libraries it refers to.
+    pub deps: BTreeMap<LibId, BTreeSet<LibId>>,
+    /// Named entry points into the libraries.
+    pub entry_points: BTreeMap<String, LibSite>,
+}
+
+impl LibBundle {
+    /// Constructs an empty bundle.
+    pub fn new(config: GfaConfig) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            config,
+            libs: none!(),
+            deps: none!(),
+            entry_points: none!(),
+        }
+    }
+
+    /// Adds a library to the bundle, recording its dependencies, and returns the library id.
+    ///
+    /// Libraries which are already present in the bundle are not added again.
+    pub fn add_lib(&mut self, lib: Lib) -> LibId {
+        let id = lib.lib_id();
+        if let btree_map::Entry::Vacant(entry) = self.deps.entry(id) {
+            entry.insert(lib.libs.iter().copied().collect());
+            self.libs.push(lib);
+        }
+        id
+    }
+
+    /// Returns a library from the bundle.
+    pub fn lib(&self, id: LibId) -> Option<&Lib> { self.libs.iter().find(|lib| lib.lib_id() == id) }
+
+    /// Returns a named entry point.
+    pub fn entry(&self, name: &str) -> Option<LibSite> { self.entry_points.get(name).copied() }
+
+    /// Resolves the library and all its transitive dependencies, ordered such that each library
+    /// follows all the libraries it depends on.
+    pub fn resolve(&self, id: LibId) -> Result<Vec<LibId>, BundleError> {
+        fn visit(
+            bundle: &LibBundle,
+            from: Option<LibId>,
+            id: LibId,
+            order: &mut Vec<LibId>,
+        ) -> Result<(), BundleError> {
+            if order.contains(&id) {
+                return Ok(());
+            }
+            let Some(deps) = bundle.deps.get(&id) else {
+                return Err(BundleError::MissingDependency(from.unwrap_or(id), id));
+            };
+            for dep in deps {
+                visit(bundle, Some(id), *dep, order)?;
+            }
+            order.push(id);
+            Ok(())
+        }
+
+        let mut order = Vec::new();
+        visit(self, None, id, &mut order)?;
+        Ok(order)
+    }
+
+    /// Checks the bundle version and validates the field configuration with
+    /// [`GfaConfig::validate`], each library with [`validate_lib`], the dependency graph against
+    /// the library segments, and that all the external references and the entry points are
+    /// resolved within the bundle.
+    pub fn validate(&self) -> Result<(), BundleError> {
+        if self.version != BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(self.version));
+        }
+        self.config.validate()?;
+
+        let mut ids = BTreeSet::new();
+        for lib in &self.libs {
+            let id = lib.lib_id();
+            if !ids.insert(id) {
+                return Err(BundleError::DuplicateLib(id));
+            }
+            validate_lib(lib, self.config).map_err(|err| BundleError::InvalidLib(id, Box::new(err)))?;
+            if !self
+                .deps
+                .get(&id)
+                .is_some_and(|deps| lib.libs.iter().eq(deps))
+            {
+                return Err(BundleError::GraphMismatch(id));
+            }
+        }
+        if let Some(id) = self.deps.keys().find(|id| !ids.contains(*id)) {
+            return Err(BundleError::GraphMismatch(*id));
+        }
+
+        for lib in &self.libs {
+            let id = lib.lib_id();
+            if let Some(dep) = lib.libs.iter().find(|dep| !ids.contains(*dep)) {
+                return Err(BundleError::MissingDependency(id, *dep));
+            }
+            // The code was already verified, so it is always decodable
+            let Ok(code) = lib.disassemble::<Instr<LibId>>() else {
+                continue;
+            };
+            for instr in code {
+                let (Instr::Ctrl(CtrlInstr::Call { site }) | Instr::Ctrl(CtrlInstr::Exec { site })) = instr else {
+                    continue;
+                };
+                let site = LibSite::new(site.prog_id, site.offset);
+                if !self.contains_site(site) {
+                    return Err(BundleError::ExternalOutOfCode(id, site));
+                }
+            }
+        }
+
+        if let Some((name, site)) = self
+            .entry_points
+            .iter()
+            .find(|(_, site)| !self.contains_site(**site))
+        {
+            return Err(BundleError::EntryOutOfBundle(name.clone(), *site));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the site points inside the code segment of a library in the bundle.
+    fn contains_site(&self, site: LibSite) -> bool {
+        self.lib(site.lib_id)
+            .is_some_and(|lib| (site.offset as usize) < lib.code.len())
+    }
+
+    /// Constructs a cache of all the bundled libraries, which can be used as a library resolver
+    /// for the VM.
+    pub fn cache(&self) -> LibCache<'static> {
+        let mut cache = LibCache::new();
+        for lib in &self.libs {
+            cache.register(lib.clone());
+        }
+        cache
+    }
+
+    /// Serializes the bundle into CBOR.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        ciborium::into_writer(self, &mut data).expect("in-memory serialization");
+        data
+    }
+
+    /// Deserializes the bundle from CBOR and [`validate`](Self::validate)s it.
+    pub fn from_cbor(data: &[u8]) -> Result<Self, BundleError> {
+        let bundle: Self = ciborium::from_reader(data).map_err(|e| BundleError::Decode(e.to_string()))?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    /// Writes the bundle in CBOR encoding.
+    #[cfg(feature = "std")]
+    pub fn write(&self, writer: impl std::io::Write) -> Result<(), BundleError> {
+        ciborium::into_writer(self, writer).map_err(|e| BundleError::Encode(e.to_string()))
+    }
+
+    /// Reads the bundle in CBOR encoding and [`validate`](Self::validate)s it.
+    #[cfg(feature = "std")]
+    pub fn read(reader: impl std::io::Read) -> Result<Self, BundleError> {
+        let bundle: Self = ciborium::from_reader(reader).map_err(|e| BundleError::Decode(e.to_string()))?;
+        bundle.validate()?;
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::isa::CtrlInstr;
+    use aluvm::{CoreExt, Site, Vm};
+    use amplify::num::u256;
+
+    use super::*;
+    use crate::gfa::{FieldInstr, NO_CONTEXT};
+    use crate::{fe256, RegE, FIELD_ORDER_25519, FIELD_ORDER_GOLDILOCKS};
+
+    fn libs() -> (Lib, Lib, Lib) {
+        let leaf = Lib::assemble::<Instr<LibId>>(&[
+            FieldInstr::Mul {
+                dst_src: RegE::E1,
+                src: RegE::E1,
+            }
+            .into(),
+            CtrlInstr::Ret.into(),
+        ])
+        .unwrap();
+        let middle = Lib::assemble::<Instr<LibId>>(&[
+            CtrlInstr::Call {
+                site: Site::new(leaf.lib_id(), 0),
+            }
+            .into(),
+            CtrlInstr::Call {
+                site: Site::new(leaf.lib_id(), 0),
+            }
+            .into(),
+            CtrlInstr::Ret.into(),
+        ])
+        .unwrap();
+        let root = Lib::assemble::<Instr<LibId>>(&[
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(0x10000000000000000u128),
+            }
+            .into(),
+            CtrlInstr::Call {
+                site: Site::new(middle.lib_id(), 0),
+            }
+            .into(),
+        ])
+        .unwrap();
+        (root, middle, leaf)
+    }
+
+    fn bundle() -> LibBundle {
+        let (root, middle, leaf) = libs();
+        let mut bundle = LibBundle::new(GfaConfig::default());
+        let root = bundle.add_lib(root);
+        bundle.add_lib(middle);
+        bundle.add_lib(leaf);
+        bundle
+            .entry_points
+            .insert(s!("main"), LibSite::new(root, 0));
+        bundle
+    }
+
+    #[test]
+    fn roundtrip() {
+        let bundle = bundle();
+        let data = bundle.to_cbor();
+        assert_eq!(LibBundle::from_cbor(&data).unwrap(), bundle);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_write() {
+        let bundle = bundle();
+        let mut file = Vec::new();
+        bundle.write(&mut file).unwrap();
+        assert_eq!(file, bundle.to_cbor());
+        assert_eq!(LibBundle::read(file.as_slice()).unwrap(), bundle);
+    }
+
+    #[test]
+    fn exec() {
+        let bundle = bundle();
+        let cache = bundle.cache();
+        let mut vm = Vm::<Instr<LibId>>::with(default!(), bundle.config);
+        let site = bundle.entry("main").unwrap();
+        assert!(vm.exec(site, &NO_CONTEXT, cache.resolver()).is_ok());
+        // (2^64)^4 = 2^256, which is reduced once by the field order exceeding 2^255
+        let expected = u256::ZERO.wrapping_sub(FIELD_ORDER_25519);
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(expected)));
+    }
+
+    #[test]
+    fn resolve() {
+        let (root, middle, leaf) = libs();
+        let bundle = bundle();
+        assert_eq!(bundle.resolve(root.lib_id()).unwrap(), vec![leaf.lib_id(), middle.lib_id(), root.lib_id()]);
+        assert_eq!(bundle.resolve(leaf.lib_id()).unwrap(), vec![leaf.lib_id()]);
+
+        let mut partial = LibBundle::new(GfaConfig::default());
+        partial.add_lib(root.clone());
+        partial.add_lib(middle.clone());
+        assert!(matches!(
+            partial.resolve(root.lib_id()),
+            Err(BundleError::MissingDependency(from, to)) if from == middle.lib_id() && to == leaf.lib_id()
+        ));
+        assert!(matches!(
+            LibBundle::from_cbor(&partial.to_cbor()),
+            Err(BundleError::MissingDependency(from, to)) if from == middle.lib_id() && to == leaf.lib_id()
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut bundle = bundle();
+        bundle.version = BUNDLE_VERSION + 1;
+        assert!(matches!(bundle.validate(), Err(BundleError::UnsupportedVersion(v)) if v == BUNDLE_VERSION + 1));
+
+        let mut bundle = self::bundle();
+        bundle.config.field_order = FIELD_ORDER_GOLDILOCKS;
+        assert!(matches!(
+            bundle.validate(),
+            Err(BundleError::InvalidLib(_, err)) if matches!(*err, LoadError::InvalidCode(_))
+        ));
+
+        let mut bundle = self::bundle();
+        let lib = bundle.libs[2].clone();
+        bundle.libs.push(lib.clone());
+        assert!(matches!(bundle.validate(), Err(BundleError::DuplicateLib(id)) if id == lib.lib_id()));
+
+        let mut bundle = self::bundle();
+        bundle
+            .deps
+            .get_mut(&lib.lib_id())
+            .unwrap()
+            .insert(bundle.libs[0].lib_id());
+        assert!(matches!(bundle.validate(), Err(BundleError::GraphMismatch(id)) if id == lib.lib_id()));
+
+        let mut bundle = self::bundle();
+        bundle.libs.pop();
+        assert!(matches!(bundle.validate(), Err(BundleError::GraphMismatch(id)) if id == lib.lib_id()));
+
+        let mut bundle = self::bundle();
+        let site = LibSite::new(lib.lib_id(), 0x100);
+        bundle.entry_points.insert(s!("out"), site);
+        assert!(matches!(
+            bundle.validate(),
+            Err(BundleError::EntryOutOfBundle(name, s)) if name == "out" && s == site
+        ));
+
+        assert!(matches!(LibBundle::from_cbor(b"zk-aluvm"), Err(BundleError::Decode(_))));
+    }
+
+    #[test]
+    fn external_out_of_code() {
+        let (_, _, leaf) = libs();
+        let caller = Lib::assemble::<Instr<LibId>>(&[CtrlInstr::Call {
+            site: Site::new(leaf.lib_id(), 0x100),
+        }
+        .into()])
+        .unwrap();
+        let mut bundle = LibBundle::new(GfaConfig::default());
+        let caller = bundle.add_lib(caller);
+        bundle.add_lib(leaf.clone());
+        assert!(matches!(
+            bundle.validate(),
+            Err(BundleError::ExternalOutOfCode(id, site)) if id == caller && site == LibSite::new(leaf.lib_id(), 0x100)
+        ));
+    }
+}
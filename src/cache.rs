@@ -0,0 +1,211 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Library resolver shared between multiple program executions.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{self, Debug, Formatter};
+
+use aluvm::{Lib, LibId};
+
+/// Cache of libraries, which can be used as a library resolver for the VM.
+///
+/// Libraries can be pre-registered with [`LibCache::register`]; libraries which are not known to
+/// the cache may be loaded lazily on their first use with a loader provided to
+/// [`LibCache::with_loader`]. Lazily loaded libraries are kept in the cache for subsequent
+/// executions.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibId, LibSite, Vm};
+/// # use zkaluvm::gfa::{Instr, NO_CONTEXT};
+/// # use zkaluvm::{zk_aluasm, LibCache};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let lib_id = lib.lib_id();
+///
+/// let mut cache = LibCache::new();
+/// cache.register(lib);
+///
+/// let mut vm = Vm::<Instr<LibId>>::new();
+/// assert!(vm
+///     .exec(LibSite::new(lib_id, 0), &NO_CONTEXT, cache.resolver())
+///     .is_ok());
+/// ```
+#[derive(Default)]
+pub struct LibCache<'loader> {
+    libs: RefCell<BTreeMap<LibId, Rc<Lib>>>,
+    loader: Option<Box<dyn Fn(LibId) -> Option<Lib> + 'loader>>,
+}
+
+impl Debug for LibCache<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LibCache")
+            .field("libs", &self.libs.borrow().keys())
+            .field("loader", &self.loader.is_some())
+            .finish()
+    }
+}
+
+impl<'loader> LibCache<'loader> {
+    /// Constructs an empty cache without a loader.
+    pub fn new() -> Self { Self::default() }
+
+    /// Constructs an empty cache, which loads unknown libraries with the `loader`.
+    ///
+    /// Libraries returned by the loader are ignored if their id doesn't match the requested one.
+    pub fn with_loader(loader: impl Fn(LibId) -> Option<Lib> + 'loader) -> Self {
+        Self {
+            libs: default!(),
+            loader: Some(Box::new(loader)),
+        }
+    }
+
+    /// Registers a library, returning its id.
+    pub fn register(&mut self, lib: Lib) -> LibId {
+        let id = lib.lib_id();
+        self.libs.get_mut().insert(id, Rc::new(lib));
+        id
+    }
+
+    /// Checks whether a library is present in the cache, without trying to load it.
+    pub fn contains(&self, id: LibId) -> bool { self.libs.borrow().contains_key(&id) }
+
+    /// Returns the number of the libraries in the cache.
+    pub fn len(&self) -> usize { self.libs.borrow().len() }
+
+    /// Checks whether the cache is empty.
+    pub fn is_empty(&self) -> bool { self.libs.borrow().is_empty() }
+
+    /// Removes a library from the cache, returning it if it was present.
+    pub fn remove(&mut self, id: LibId) -> Option<Rc<Lib>> { self.libs.get_mut().remove(&id) }
+
+    /// Resolves a library by its id, loading it if the library is not yet in the cache.
+    pub fn resolve(&self, id: LibId) -> Option<Rc<Lib>> {
+        if let Some(lib) = self.libs.borrow().get(&id) {
+            return Some(lib.clone());
+        }
+        let lib = (self.loader.as_ref()?)(id)?;
+        if lib.lib_id() != id {
+            return None;
+        }
+        let lib = Rc::new(lib);
+        self.libs.borrow_mut().insert(id, lib.clone());
+        Some(lib)
+    }
+
+    /// Returns library resolver for use in [`aluvm::Vm::exec`].
+    pub fn resolver(&self) -> impl Fn(LibId) -> Option<Rc<Lib>> + '_ { |id| self.resolve(id) }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use core::cell::Cell;
+
+    use aluvm::isa::CtrlInstr;
+    use aluvm::{CoreExt, LibSite, Site, Vm};
+
+    use super::*;
+    use crate::gfa::{FieldInstr, Instr, NO_CONTEXT};
+    use crate::{fe256, RegE};
+
+    fn libs() -> (Lib, Lib) {
+        let callee = Lib::assemble::<Instr<LibId>>(&[
+            FieldInstr::Mul {
+                dst_src: RegE::E1,
+                src: RegE::E1,
+            }
+            .into(),
+            CtrlInstr::Ret.into(),
+        ])
+        .unwrap();
+        let caller = Lib::assemble::<Instr<LibId>>(&[
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(3u8),
+            }
+            .into(),
+            CtrlInstr::Call {
+                site: Site::new(callee.lib_id(), 0),
+            }
+            .into(),
+        ])
+        .unwrap();
+        (caller, callee)
+    }
+
+    #[test]
+    fn registered() {
+        let (caller, callee) = libs();
+        let mut cache = LibCache::new();
+        let entry = cache.register(caller);
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert!(!vm
+            .exec(LibSite::new(entry, 0), &NO_CONTEXT, cache.resolver())
+            .is_ok());
+
+        let callee_id = cache.register(callee);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(callee_id));
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert!(vm
+            .exec(LibSite::new(entry, 0), &NO_CONTEXT, cache.resolver())
+            .is_ok());
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(9u8)));
+
+        assert!(cache.remove(callee_id).is_some());
+        assert!(!cache.contains(callee_id));
+    }
+
+    #[test]
+    fn lazy() {
+        let (caller, callee) = libs();
+        let (caller_id, callee_id) = (caller.lib_id(), callee.lib_id());
+        let loads = Cell::new(0);
+        let cache = LibCache::with_loader(|id| {
+            loads.set(loads.get() + 1);
+            [&caller, &callee]
+                .into_iter()
+                .find(|lib| lib.lib_id() == id)
+                .cloned()
+        });
+        assert!(cache.is_empty());
+        for _ in 0..2 {
+            let mut vm = Vm::<Instr<LibId>>::new();
+            assert!(vm
+                .exec(LibSite::new(caller_id, 0), &NO_CONTEXT, cache.resolver())
+                .is_ok());
+        }
+        assert_eq!(loads.get(), 2);
+        assert!(cache.contains(callee_id));
+        drop(cache);
+
+        let cache = LibCache::with_loader(|_| Some(caller.clone()));
+        assert!(cache.resolve(callee_id).is_none());
+        assert!(cache.is_empty());
+    }
+}
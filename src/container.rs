@@ -0,0 +1,246 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Self-describing container format for deployable zk-AluVM programs.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::Lib;
+
+use crate::gfa::SourceMap;
+use crate::{validate_lib, CurveError, GfaConfig, IoSpec, LoadError};
+
+/// Version of the program container format produced by this crate.
+pub const CONTAINER_VERSION: u8 = 1;
+
+/// Errors reading a program container.
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ContainerError {
+    /// invalid CBOR encoding of the program container: {0}
+    Decode(String),
+
+    /// I/O error writing the program container: {0}
+    Encode(String),
+
+    /// unsupported program container version {0}.
+    UnsupportedVersion(u8),
+
+    /// {0}
+    #[from]
+    InvalidLib(LoadError),
+
+    /// source map refers to offset {0:04X}, which is outside of the code segment.
+    SourceMapOutOfCode(u16),
+
+    /// invalid curve parameters in the field configuration: {0}
+    #[from]
+    InvalidCurve(CurveError),
+}
+
+/// Deployable program artifact, bundling the library (code and data segments together with the
+/// ISA extensions it requires) with the field configuration, the calling convention and,
+/// optionally, the source map of the program.
+///
+/// The container is serialized with CBOR, so a program can be shipped as a single self-describing
+/// file. Reading a container validates the library against the bundled configuration.
+///
+/// # Example
+///
+/// ```
+/// # use zkaluvm::gfa::Assembly;
+/// # use zkaluvm::{GfaConfig, IoSpec, IoType, ProgramContainer, RegE};
+/// let asm = Assembly::parse("put E1, 7\nmul E1, E1\n").unwrap();
+/// let mut io = IoSpec::new();
+/// io.output("square", RegE::E1, IoType::Fe).unwrap();
+///
+/// let mut container = ProgramContainer::new(asm.assemble().unwrap(), GfaConfig::default());
+/// container.io = io;
+/// container.source_map = Some(asm.source_map);
+///
+/// let data = container.to_cbor();
+/// assert_eq!(ProgramContainer::from_cbor(&data).unwrap(), container);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramContainer {
+    /// Version of the container format.
+    pub version: u8,
+    /// Program library.
+    pub lib: Lib,
+    /// Configuration of the field the program runs in.
+    pub config: GfaConfig,
+    /// Calling convention of the program.
+    pub io: IoSpec,
+    /// Source map of the program, if available.
+    pub source_map: Option<SourceMap>,
+}
+
+impl ProgramContainer {
+    /// Constructs container for a library, with no declared inputs and outputs and no source map.
+    pub fn new(lib: Lib, config: GfaConfig) -> Self {
+        Self {
+            version: CONTAINER_VERSION,
+            lib,
+            config,
+            io: IoSpec::new(),
+            source_map: None,
+        }
+    }
+
+    /// Checks the container version, validates the library with [`validate_lib`] and the field
+    /// configuration with [`GfaConfig::validate`], and checks that the source map points inside
+    /// the code segment.
+    pub fn validate(&self) -> Result<(), ContainerError> {
+        if self.version != CONTAINER_VERSION {
+            return Err(ContainerError::UnsupportedVersion(self.version));
+        }
+        validate_lib(&self.lib, self.config)?;
+        self.config.validate()?;
+        if let Some(loc) = self
+            .source_map
+            .iter()
+            .flat_map(SourceMap::iter)
+            .find(|loc| loc.pos as usize >= self.lib.code.len())
+        {
+            return Err(ContainerError::SourceMapOutOfCode(loc.pos));
+        }
+        Ok(())
+    }
+
+    /// Serializes the container into CBOR.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        ciborium::into_writer(self, &mut data).expect("in-memory serialization");
+        data
+    }
+
+    /// Deserializes the container from CBOR and [`validate`](Self::validate)s it.
+    pub fn from_cbor(data: &[u8]) -> Result<Self, ContainerError> {
+        let container: Self = ciborium::from_reader(data).map_err(|e| ContainerError::Decode(e.to_string()))?;
+        container.validate()?;
+        Ok(container)
+    }
+
+    /// Writes the container in CBOR encoding.
+    #[cfg(feature = "std")]
+    pub fn write(&self, writer: impl std::io::Write) -> Result<(), ContainerError> {
+        ciborium::into_writer(self, writer).map_err(|e| ContainerError::Encode(e.to_string()))
+    }
+
+    /// Reads the container in CBOR encoding and [`validate`](Self::validate)s it.
+    #[cfg(feature = "std")]
+    pub fn read(reader: impl std::io::Read) -> Result<Self, ContainerError> {
+        let container: Self = ciborium::from_reader(reader).map_err(|e| ContainerError::Decode(e.to_string()))?;
+        container.validate()?;
+        Ok(container)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::{Assembly, FieldInstr, Instr, SourceLoc};
+    use crate::{fe256, IoType, RegE, FIELD_ORDER_GOLDILOCKS};
+
+    fn container() -> ProgramContainer {
+        let asm = Assembly::parse(
+            "put E1, 7
+             put E2, 0x10000000000000000
+             mul E1, E2
+             ",
+        )
+        .unwrap();
+        let mut io = IoSpec::new();
+        io.output("product", RegE::E1, IoType::Fe).unwrap();
+        let mut container = ProgramContainer::new(asm.assemble().unwrap(), GfaConfig::default());
+        container.io = io;
+        container.source_map = Some(asm.source_map);
+        container
+    }
+
+    #[test]
+    fn roundtrip() {
+        let container = container();
+        let data = container.to_cbor();
+        assert_eq!(ProgramContainer::from_cbor(&data).unwrap(), container);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_write() {
+        let container = container();
+        let mut file = Vec::new();
+        container.write(&mut file).unwrap();
+        assert_eq!(file, container.to_cbor());
+        assert_eq!(ProgramContainer::read(file.as_slice()).unwrap(), container);
+    }
+
+    #[test]
+    fn invalid_cbor() {
+        let data = container().to_cbor();
+        assert!(matches!(ProgramContainer::from_cbor(&data[..data.len() - 1]), Err(ContainerError::Decode(_))));
+        assert!(matches!(ProgramContainer::from_cbor(b"zk-aluvm"), Err(ContainerError::Decode(_))));
+    }
+
+    #[test]
+    fn version() {
+        let mut container = container();
+        container.version = CONTAINER_VERSION + 1;
+        let data = container.to_cbor();
+        assert!(matches!(
+            ProgramContainer::from_cbor(&data),
+            Err(ContainerError::UnsupportedVersion(v)) if v == CONTAINER_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn invalid_lib() {
+        let mut container = container();
+        // The value put into E2 doesn't fit Goldilocks field
+        container.config.field_order = FIELD_ORDER_GOLDILOCKS;
+        assert!(matches!(
+            ProgramContainer::from_cbor(&container.to_cbor()),
+            Err(ContainerError::InvalidLib(LoadError::InvalidCode(_)))
+        ));
+    }
+
+    #[test]
+    fn source_map_out_of_code() {
+        let lib = Lib::assemble(&[Instr::<LibId>::Gfa(FieldInstr::put(RegE::E1, fe256::from(7u8)))]).unwrap();
+        let mut container = ProgramContainer::new(lib, GfaConfig::default());
+        let mut source_map = SourceMap::new();
+        source_map.push(SourceLoc { pos: 0, line: 1 }).unwrap();
+        source_map.push(SourceLoc { pos: 0x100, line: 2 }).unwrap();
+        container.source_map = Some(source_map);
+        assert!(matches!(
+            ProgramContainer::from_cbor(&container.to_cbor()),
+            Err(ContainerError::SourceMapOutOfCode(0x100))
+        ));
+    }
+}
@@ -20,12 +20,17 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
 use core::fmt::{self, Debug, Formatter};
+use core::str::FromStr;
 
 use aluvm::{CoreExt, NoExt, Register, Supercore};
 use amplify::num::{u256, u4};
 
-use crate::fe256;
+use super::solinas::Reduction;
+use crate::gfa::IsaGroups;
+use crate::{fe256, CurveError, CurveParams, FailurePolicy, SolinasPrime, LIB_NAME_FINITE_FIELD};
 
 /// Field order for the group used in the Curve25519 elliptic curve construction.
 pub const FIELD_ORDER_25519: u256 =
@@ -35,28 +40,229 @@ pub const FIELD_ORDER_STARK: u256 = u256::from_inner([1, 0, 17, 0x0800_0000_0000
 /// Field order for the group used in SECP256K1 elliptic curve construction.
 pub const FIELD_ORDER_SECP: u256 =
     u256::from_inner([0xFFFF_FFFE_FFFF_FC2E, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF]);
+/// Order of the Goldilocks field (`2^64 - 2^32 + 1`) used by Plonky2 and Plonky3 provers.
+pub const FIELD_ORDER_GOLDILOCKS: u256 = u256::from_inner([0xFFFF_FFFF_0000_0001, 0, 0, 0]);
+/// Order of the BabyBear field (`2^31 - 2^27 + 1`) used by Plonky3 and RISC Zero provers.
+pub const FIELD_ORDER_BABYBEAR: u256 = u256::from_inner([0x7800_0001, 0, 0, 0]);
 
-impl Default for GfaConfig {
-    fn default() -> Self {
-        Self {
-            field_order: FIELD_ORDER_25519,
+/// Fields with predefined orders.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum FieldPreset {
+    /// Field used in the Curve25519 elliptic curve construction (see [`FIELD_ORDER_25519`]).
+    #[display("25519")]
+    #[cfg_attr(feature = "serde", serde(rename = "25519"))]
+    Curve25519,
+
+    /// Field used in the SECP256K1 elliptic curve construction (see [`FIELD_ORDER_SECP`]).
+    #[display("secp")]
+    Secp,
+
+    /// Field used in the "Stark" elliptic curve construction (see [`FIELD_ORDER_STARK`]).
+    #[display("stark")]
+    Stark,
+
+    /// Goldilocks field (see [`FIELD_ORDER_GOLDILOCKS`]).
+    #[display("goldilocks")]
+    Goldilocks,
+
+    /// BabyBear field (see [`FIELD_ORDER_BABYBEAR`]).
+    #[display("babybear")]
+    BabyBear,
+}
+
+impl FieldPreset {
+    /// Enumeration of all field presets.
+    pub const ALL: [Self; 5] = [
+        FieldPreset::Curve25519,
+        FieldPreset::Secp,
+        FieldPreset::Stark,
+        FieldPreset::Goldilocks,
+        FieldPreset::BabyBear,
+    ];
+
+    /// Returns the field order.
+    pub const fn field_order(self) -> u256 {
+        match self {
+            FieldPreset::Curve25519 => FIELD_ORDER_25519,
+            FieldPreset::Secp => FIELD_ORDER_SECP,
+            FieldPreset::Stark => FIELD_ORDER_STARK,
+            FieldPreset::Goldilocks => FIELD_ORDER_GOLDILOCKS,
+            FieldPreset::BabyBear => FIELD_ORDER_BABYBEAR,
         }
     }
+
+    /// Returns the special form of the field order, if it is known.
+    pub const fn solinas(self) -> Option<SolinasPrime> {
+        match self {
+            FieldPreset::Stark => Some(SolinasPrime::STARK),
+            FieldPreset::Goldilocks => Some(SolinasPrime::GOLDILOCKS),
+            FieldPreset::BabyBear => Some(SolinasPrime::BABYBEAR),
+            FieldPreset::Curve25519 | FieldPreset::Secp => None,
+        }
+    }
+}
+
+impl FromStr for FieldPreset {
+    type Err = ParseFieldPresetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FieldPreset::ALL
+            .into_iter()
+            .find(|preset| preset.to_string() == s)
+            .ok_or_else(|| ParseFieldPresetError(s.to_owned()))
+    }
+}
+
+/// Error parsing field preset name.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("unknown field preset `{0}`.")]
+pub struct ParseFieldPresetError(pub String);
+
+/// Parses field order given either by the field name (`25519`, `secp`, `stark`, `goldilocks` or
+/// `babybear`) or by a hexadecimal value prefixed with `0x`.
+#[cfg(any(feature = "wasm", feature = "python"))]
+pub(crate) fn parse_field_order(s: &str) -> Option<u256> {
+    match FieldPreset::from_str(s) {
+        Ok(preset) => Some(preset.field_order()),
+        Err(_) => s
+            .strip_prefix("0x")
+            .and_then(|hex| fe256::from_str(&format!("{hex}.fe")).ok())
+            .map(|fe| fe.to_u256()),
+    }
+}
+
+impl Default for GfaConfig {
+    fn default() -> Self { Self::new(FIELD_ORDER_25519) }
 }
 
 /// An extension of AluVM core for the GFA256 ISA.
-#[derive(Copy, Clone, Eq, PartialEq)]
+///
+/// With the `zeroize` feature, the register values are overwritten with zeros when the registers
+/// are cleared, the core is reset or dropped, such that secret field elements don't persist in
+/// freed or reused memory. In this case the core is not `Copy`, since implicit copies would leave
+/// the values in memory which is never scrubbed.
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
 pub struct GfaCore {
     /// Used field order.
     pub(super) fq: u256,
-    pub(super) e: [Option<fe256>; 16],
+    /// Maximum number of instructions which can be executed.
+    pub(super) step_lim: Option<u64>,
+    /// Whether the field arithmetic runs in constant time.
+    pub(super) ct: bool,
+    /// Reduction routine for the special form of the field order, if any.
+    pub(super) reduction: Option<Reduction>,
+    /// Parameters of the elliptic curve over the field, if any.
+    pub(super) curve: Option<CurveParams>,
+    /// Actions taken on failures of instructions, per instruction class.
+    pub(super) policy: FailurePolicy,
+    /// Instruction groups supported by the core.
+    pub(super) isa: IsaGroups,
+    /// Number of executed instructions.
+    pub(super) steps: u64,
+    /// Number of executed instructions from the GFA256 ISA extension.
+    pub(super) gfa_steps: u64,
+    /// Values of the `E` registers; values of the registers which are not set are zero.
+    pub(super) e: [fe256; 16],
+    /// Bit mask of the `E` registers which are set.
+    pub(super) e_set: u16,
 }
 
 /// Configuration for initializing the zk-AluVM core (GFA256 ISA extension).
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
 pub struct GfaConfig {
     /// The order of the group for the core.
     pub field_order: u256,
+    /// Maximum number of instructions the VM can execute, if any.
+    ///
+    /// Unlike the complexity limit, the step limit halts the program once exceeded even if the VM
+    /// is not configured to halt on failures, bounding loops made of instructions with low
+    /// complexity.
+    pub step_lim: Option<u64>,
+    /// Whether the field arithmetic runs in constant time.
+    ///
+    /// In the constant-time mode the microcode uses data-independent branching and memory access,
+    /// such that the execution time doesn't depend on the values in the registers. This is slower,
+    /// but required for hosts executing programs over secret witnesses. See
+    /// [`GfaCore::is_constant_time`] for the list of the covered operations.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constant_time: bool,
+    /// Special form of the field order, used to synthesize a dedicated reduction routine for the
+    /// multiplication.
+    ///
+    /// If the form doesn't describe [`Self::field_order`], or can't be reduced efficiently (see
+    /// [`SolinasPrime`]), it is ignored and the generic reduction is used. Use
+    /// [`GfaCore::solinas`] to check whether the form is in use.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub solinas: Option<SolinasPrime>,
+    /// Parameters of the elliptic curve over the field, used by the instructions working with the
+    /// curve points.
+    ///
+    /// The parameters are checked with [`GfaConfig::validate`]; a core constructed with invalid
+    /// parameters has no curve (see [`GfaCore::curve`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub curve: Option<CurveParams>,
+    /// Actions taken on failures of instructions, per instruction class.
+    ///
+    /// Allows hosts to mix assertions, which must halt the program, with best-effort computations,
+    /// which may fail without halting it, independently of the `CH` register value.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub failure_policy: FailurePolicy,
+    /// Instruction groups supported by the VM.
+    ///
+    /// Instructions from other groups fail `CK` when executed, and libraries using them are
+    /// rejected by [`validate_lib`](crate::validate_lib).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub isa_groups: IsaGroups,
+}
+
+impl GfaConfig {
+    /// Constructs configuration for a field of the given order, with no step limit, the generic
+    /// reduction routine, no elliptic curve, the default failure policy and all instruction groups
+    /// supported.
+    ///
+    /// Other parameters can be changed with the struct update syntax:
+    ///
+    /// ```
+    /// # use zkaluvm::{GfaConfig, FIELD_ORDER_STARK};
+    /// let config = GfaConfig {
+    ///     constant_time: true,
+    ///     ..GfaConfig::new(FIELD_ORDER_STARK)
+    /// };
+    /// assert_eq!(config.field_order, FIELD_ORDER_STARK);
+    /// ```
+    pub const fn new(field_order: u256) -> Self {
+        Self {
+            field_order,
+            step_lim: None,
+            constant_time: false,
+            solinas: None,
+            curve: None,
+            failure_policy: FailurePolicy::INHERIT,
+            isa_groups: IsaGroups::ALL,
+        }
+    }
+
+    /// Constructs configuration for a field preset, using the reduction routine for the special
+    /// form of the field order, if it is known.
+    pub const fn with_preset(preset: FieldPreset) -> Self {
+        Self {
+            solinas: preset.solinas(),
+            ..Self::new(preset.field_order())
+        }
+    }
+
+    /// Validates the parameters of the elliptic curve, if any, against the field order.
+    pub fn validate(&self) -> Result<(), CurveError> {
+        match self.curve {
+            Some(curve) => curve.validate(self.field_order),
+            None => Ok(()),
+        }
+    }
 }
 
 impl CoreExt for GfaCore {
@@ -67,28 +273,74 @@ impl CoreExt for GfaCore {
     fn with(config: Self::Config) -> Self {
         GfaCore {
             fq: config.field_order,
-            e: [None; 16],
+            step_lim: config.step_lim,
+            ct: config.constant_time,
+            reduction: config
+                .solinas
+                .and_then(|prime| Reduction::synthesize(prime, config.field_order)),
+            curve: config
+                .curve
+                .filter(|curve| curve.validate(config.field_order).is_ok()),
+            policy: config.failure_policy,
+            isa: config.isa_groups,
+            steps: 0,
+            gfa_steps: 0,
+            e: [fe256::ZERO; 16],
+            e_set: 0,
         }
     }
 
     #[inline]
-    fn get(&self, reg: Self::Reg) -> Option<fe256> { self.e[reg as usize] }
+    fn get(&self, reg: Self::Reg) -> Option<fe256> {
+        (self.e_set & (1 << reg as u16) != 0).then_some(self.e[reg as usize])
+    }
 
     #[inline]
-    fn clr(&mut self, reg: Self::Reg) { self.e[reg as usize] = None; }
+    fn clr(&mut self, reg: Self::Reg) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.e[reg as usize]);
+        #[cfg(not(feature = "zeroize"))]
+        {
+            self.e[reg as usize] = fe256::ZERO;
+        }
+        self.e_set &= !(1 << reg as u16);
+    }
 
     #[inline]
     fn put(&mut self, reg: Self::Reg, val: Option<fe256>) {
-        let Some(val) = val else {
-            self.e[reg as usize] = None;
+        // Values outside the field leave the register empty, so that any use of them fails
+        let Some(val) = val.filter(|val| self.is_canonical(*val)) else {
+            self.clr(reg);
             return;
         };
-        assert!(val.to_u256() < self.fq, "value {val} exceeds field order {}", self.fq);
-        self.e[reg as usize] = Some(val);
+        self.e[reg as usize] = val;
+        self.e_set |= 1 << reg as u16;
     }
 
     #[inline]
-    fn reset(&mut self) { self.e = [None; 16]; }
+    fn reset(&mut self) {
+        self.steps = 0;
+        self.gfa_steps = 0;
+        #[cfg(feature = "zeroize")]
+        self.scrub();
+        self.e = [fe256::ZERO; 16];
+        self.e_set = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl GfaCore {
+    fn scrub(&mut self) {
+        for val in &mut self.e {
+            zeroize::Zeroize::zeroize(val);
+        }
+        self.e_set = 0;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for GfaCore {
+    fn drop(&mut self) { self.scrub(); }
 }
 
 impl Supercore<NoExt> for GfaCore {
@@ -106,9 +358,9 @@ impl Debug for GfaCore {
         writeln!(f)?;
         writeln!(f, "{reg}FQ{reset} {val}{:X}{reset}#h", self.fq)?;
         writeln!(f, "{sect}E-regs:{reset}")?;
-        for (no, item) in self.e.iter().enumerate() {
-            write!(f, "{reg}{}{reset} ", RegE::from(u4::with(no as u8)))?;
-            if let Some(e) = item {
+        for no in RegE::ALL {
+            write!(f, "{reg}{no}{reset} ")?;
+            if let Some(e) = self.get(no) {
                 writeln!(f, "{val}{e}{reset}#h")?;
             } else {
                 writeln!(f, "~")?;
@@ -133,6 +385,7 @@ impl Debug for GfaCore {
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display)]
 #[display(uppercase)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u8)]
 pub enum RegE {
     E1 = 0b_0000,
@@ -162,25 +415,8 @@ impl Register for RegE {
 
 impl From<u4> for RegE {
     fn from(val: u4) -> Self {
-        match val {
-            x if x == RegE::E1.to_u4() => RegE::E1,
-            x if x == RegE::E2.to_u4() => RegE::E2,
-            x if x == RegE::E3.to_u4() => RegE::E3,
-            x if x == RegE::E4.to_u4() => RegE::E4,
-            x if x == RegE::E5.to_u4() => RegE::E5,
-            x if x == RegE::E6.to_u4() => RegE::E6,
-            x if x == RegE::E7.to_u4() => RegE::E7,
-            x if x == RegE::E8.to_u4() => RegE::E8,
-            x if x == RegE::EA.to_u4() => RegE::EA,
-            x if x == RegE::EB.to_u4() => RegE::EB,
-            x if x == RegE::EC.to_u4() => RegE::EC,
-            x if x == RegE::ED.to_u4() => RegE::ED,
-            x if x == RegE::EE.to_u4() => RegE::EE,
-            x if x == RegE::EF.to_u4() => RegE::EF,
-            x if x == RegE::EG.to_u4() => RegE::EG,
-            x if x == RegE::EH.to_u4() => RegE::EH,
-            _ => unreachable!(),
-        }
+        // 4-bit values never exceed 15, so the index is always within the bounds
+        RegE::ALL[val.to_u8() as usize & 0x0F]
     }
 }
 
@@ -209,3 +445,151 @@ impl RegE {
     #[inline]
     pub const fn to_u4(self) -> u4 { u4::with(self as u8) }
 }
+
+/// Error converting an index into a register.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("register index {0} is out of range; there are only 16 registers.")]
+pub struct RegOutOfRange(pub u8);
+
+impl TryFrom<u8> for RegE {
+    type Error = RegOutOfRange;
+
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
+        RegE::ALL
+            .get(val as usize)
+            .copied()
+            .ok_or(RegOutOfRange(val))
+    }
+}
+
+/// Error parsing register name.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("unknown register name `{0}`.")]
+pub struct ParseRegError(pub String);
+
+impl FromStr for RegE {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "E1" => RegE::E1,
+            "E2" => RegE::E2,
+            "E3" => RegE::E3,
+            "E4" => RegE::E4,
+            "E5" => RegE::E5,
+            "E6" => RegE::E6,
+            "E7" => RegE::E7,
+            "E8" => RegE::E8,
+            "EA" => RegE::EA,
+            "EB" => RegE::EB,
+            "EC" => RegE::EC,
+            "ED" => RegE::ED,
+            "EE" => RegE::EE,
+            "EF" => RegE::EF,
+            "EG" => RegE::EG,
+            "EH" => RegE::EH,
+            _ => return Err(ParseRegError(s.to_owned())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use super::*;
+    use crate::FailureAction;
+
+    #[test]
+    fn reg_try_from() {
+        for reg in RegE::ALL {
+            assert_eq!(RegE::try_from(reg as u8), Ok(reg));
+            assert_eq!(RegE::from(reg.to_u4()), reg);
+        }
+        assert_eq!(RegE::try_from(16), Err(RegOutOfRange(16)));
+        assert_eq!(RegE::try_from(0xFF), Err(RegOutOfRange(0xFF)));
+    }
+
+    #[test]
+    fn reg_str_roundtrip() {
+        for reg in RegE::ALL {
+            assert_eq!(RegE::from_str(&reg.to_string()), Ok(reg));
+        }
+        assert_eq!(RegE::from_str("EA"), Ok(RegE::EA));
+        assert_eq!(RegE::from_str("ea"), Err(ParseRegError(s!("ea"))));
+        assert_eq!(RegE::from_str("E9"), Err(ParseRegError(s!("E9"))));
+    }
+
+    #[test]
+    fn field_preset_str_roundtrip() {
+        for preset in FieldPreset::ALL {
+            assert_eq!(FieldPreset::from_str(&preset.to_string()), Ok(preset));
+            assert_eq!(GfaConfig::with_preset(preset).field_order, preset.field_order());
+        }
+        assert_eq!(FieldPreset::from_str("Secp"), Err(ParseFieldPresetError(s!("Secp"))));
+    }
+
+    #[test]
+    fn canonical() {
+        let core = GfaCore::with(GfaConfig::new(FIELD_ORDER_BABYBEAR));
+        assert!(core.is_canonical(fe256::ZERO));
+        assert!(core.is_canonical(fe256::from(FIELD_ORDER_BABYBEAR - u256::ONE)));
+        assert!(!core.is_canonical(fe256::from(FIELD_ORDER_BABYBEAR)));
+        assert!(!core.is_canonical(fe256::from(u256::MAX)));
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn scrub() {
+        // Inspects the array backing the registers, which keeps the values of the cleared ones
+        let stored = |core: &GfaCore, reg: RegE| core.e[reg as usize];
+
+        let mut core = GfaCore::with(GfaConfig::default());
+        core.put(RegE::E1, Some(fe256::from(0xDEADu16)));
+        core.put(RegE::E2, Some(fe256::from(0xBEEFu16)));
+        core.clr(RegE::E1);
+        assert_eq!(core.get(RegE::E1), None);
+        assert_eq!(stored(&core, RegE::E1), fe256::ZERO);
+        assert_eq!(core.get(RegE::E2), Some(fe256::from(0xBEEFu16)));
+
+        core.put(RegE::E1, Some(fe256::from(u256::MAX)));
+        assert_eq!(core.get(RegE::E1), None);
+        assert_eq!(stored(&core, RegE::E1), fe256::ZERO);
+
+        core.reset();
+        assert!(RegE::ALL.iter().all(|reg| core.get(*reg).is_none()));
+        assert!(core.e.iter().all(|val| *val == fe256::ZERO));
+    }
+
+    #[test]
+    fn config_strict_roundtrip() {
+        use strict_encoding::{StrictDecode, StrictEncode, StrictReader, StrictWriter};
+
+        let config = GfaConfig {
+            field_order: FIELD_ORDER_STARK,
+            step_lim: Some(1000),
+            constant_time: true,
+            solinas: Some(SolinasPrime::STARK),
+            curve: None,
+            failure_policy: FailurePolicy::uniform(FailureAction::Signal),
+            isa_groups: IsaGroups::ALL,
+        };
+        let data = config
+            .strict_encode(StrictWriter::in_memory::<128>())
+            .unwrap()
+            .unbox()
+            .unconfine();
+        assert_eq!(data.len(), 32 + 1 + 8 + 1 + 1 + 2 + 32 + 1 + 1 + 5 + 2);
+        let mut reader = StrictReader::in_memory::<128>(data);
+        assert_eq!(GfaConfig::strict_decode(&mut reader).unwrap(), config);
+
+        let reg = RegE::EH
+            .strict_encode(StrictWriter::in_memory::<1>())
+            .unwrap()
+            .unbox()
+            .unconfine();
+        assert_eq!(reg, vec![0x0F]);
+    }
+}
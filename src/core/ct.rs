@@ -0,0 +1,221 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Constant-time field arithmetics.
+//!
+//! The functions operate on the 64-bit limbs of the values using only data-independent control
+//! flow: loops have fixed number of iterations, and conditional results are chosen with bit masks
+//! instead of branches.
+
+use core::hint::black_box;
+
+use amplify::num::u256;
+
+type Limbs = [u64; 4];
+
+/// Converts a bit (`0` or `1`) into a mask with all bits set to this bit.
+///
+/// The mask is passed through an optimization barrier; otherwise the compiler may notice that it
+/// can have only two values and turn the selection back into a branch.
+#[inline(always)]
+fn mask(bit: u64) -> u64 { black_box(0u64.wrapping_sub(bit & 1)) }
+
+/// Returns `a` if `mask` has all bits set and `b` if `mask` is zero.
+#[inline(always)]
+fn select(mask: u64, a: Limbs, b: Limbs) -> Limbs {
+    let mut res = [0u64; 4];
+    for ((r, a), b) in res.iter_mut().zip(a).zip(b) {
+        *r = (a & mask) | (b & !mask);
+    }
+    res
+}
+
+/// Adds two values, returning the result and the carry bit.
+#[inline(always)]
+fn adc(a: Limbs, b: Limbs) -> (Limbs, u64) {
+    let mut res = [0u64; 4];
+    let mut carry = 0u64;
+    for ((r, a), b) in res.iter_mut().zip(a).zip(b) {
+        let sum = a as u128 + b as u128 + carry as u128;
+        *r = sum as u64;
+        carry = (sum >> 64) as u64;
+    }
+    (res, carry)
+}
+
+/// Subtracts `b` from `a`, returning the result and the borrow bit.
+#[inline(always)]
+fn sbb(a: Limbs, b: Limbs) -> (Limbs, u64) {
+    let mut res = [0u64; 4];
+    let mut borrow = 0u64;
+    for ((r, a), b) in res.iter_mut().zip(a).zip(b) {
+        let diff = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+        *r = diff as u64;
+        borrow = ((diff >> 64) as u64) & 1;
+    }
+    (res, borrow)
+}
+
+/// Returns `1` if the value is not zero and `0` otherwise.
+#[inline(always)]
+fn is_nonzero(a: Limbs) -> u64 {
+    let acc = a[0] | a[1] | a[2] | a[3];
+    (acc | acc.wrapping_neg()) >> 63
+}
+
+#[inline(always)]
+fn add_mod_limbs(order: Limbs, a: Limbs, b: Limbs) -> Limbs {
+    let (sum, carry) = adc(a, b);
+    let (reduced, borrow) = sbb(sum, order);
+    // The sum must be reduced if it has overflown 256 bits or is not less than the order
+    select(mask(carry | (borrow ^ 1)), reduced, sum)
+}
+
+/// Computes `(a + b) mod order` for `a, b < order`.
+pub(super) fn add_mod(order: u256, a: u256, b: u256) -> u256 {
+    u256::from_inner(add_mod_limbs(order.into_inner(), a.into_inner(), b.into_inner()))
+}
+
+/// Computes `(a * b) mod order` for `a, b < order`, using double-and-add over all 256 bits of `b`.
+pub(super) fn mul_mod(order: u256, a: u256, b: u256) -> u256 {
+    let order = order.into_inner();
+    let a = a.into_inner();
+    let b = b.into_inner();
+    let mut res = [0u64; 4];
+    for i in (0..256).rev() {
+        res = add_mod_limbs(order, res, res);
+        let sum = add_mod_limbs(order, res, a);
+        res = select(mask(b[i / 64] >> (i % 64)), sum, res);
+    }
+    u256::from_inner(res)
+}
+
+/// Computes `a^e mod order` for `a < order`, using square-and-multiply over all 256 bits of `e`.
+pub(super) fn exp_mod(order: u256, a: u256, e: u256) -> u256 {
+    let e = e.into_inner();
+    let mut res = u256::ONE % order;
+    for i in (0..256).rev() {
+        res = mul_mod(order, res, res);
+        let prod = mul_mod(order, res, a);
+        res = u256::from_inner(select(mask(e[i / 64] >> (i % 64)), prod.into_inner(), res.into_inner()));
+    }
+    res
+}
+
+/// Computes `-a mod order` for `a < order`.
+pub(super) fn neg_mod(order: u256, a: u256) -> u256 {
+    let a = a.into_inner();
+    let (neg, _) = sbb(order.into_inner(), a);
+    u256::from_inner(select(mask(is_nonzero(a)), neg, [0u64; 4]))
+}
+
+/// Checks two values for equality.
+pub(super) fn eq(a: u256, b: u256) -> bool {
+    let a = a.into_inner();
+    let b = b.into_inner();
+    is_nonzero([a[0] ^ b[0], a[1] ^ b[1], a[2] ^ b[2], a[3] ^ b[3]]) == 0
+}
+
+/// Checks whether a value fits into the given number of bits.
+pub(super) fn fits(a: u256, bits: usize) -> bool { is_nonzero((a >> bits).into_inner()) == 0 }
+
+/// Checks whether `a` is less than `b`.
+pub(super) fn lt(a: u256, b: u256) -> bool { sbb(a.into_inner(), b.into_inner()).1 == 1 }
+
+/// Returns `a` if the condition holds and `b` otherwise.
+pub(super) fn choose(cond: bool, a: u256, b: u256) -> u256 {
+    u256::from_inner(select(mask(cond as u64), a.into_inner(), b.into_inner()))
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::testing::TestRng;
+    use crate::{FIELD_ORDER_25519, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_SECP, FIELD_ORDER_STARK};
+
+    const ORDERS: [u256; 5] =
+        [FIELD_ORDER_25519, FIELD_ORDER_SECP, FIELD_ORDER_STARK, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_BABYBEAR];
+
+    fn values(rng: &mut TestRng, order: u256) -> [u256; 6] {
+        [
+            u256::ZERO,
+            u256::ONE,
+            order - u256::ONE,
+            order - u256::from(2u8),
+            u256::from_le_bytes(rng.bytes32()) % order,
+            u256::from_le_bytes(rng.bytes32()) % order,
+        ]
+    }
+
+    #[test]
+    fn matches_reference() {
+        let mut rng = TestRng::with(4483);
+        for order in ORDERS {
+            for _ in 0..8 {
+                let vals = values(&mut rng, order);
+                for a in vals {
+                    let neg = if a == u256::ZERO { a } else { order - a };
+                    assert_eq!(neg_mod(order, a), neg);
+                    for b in vals {
+                        let (sum, overflow) = a.overflowing_add(b);
+                        let sum = if overflow || sum >= order { sum.wrapping_sub(order) } else { sum };
+                        assert_eq!(add_mod(order, a, b), sum);
+                        assert_eq!(mul_mod(order, a, b), super::super::microcode::mul_mod_int(order, a, b).0);
+                        assert_eq!(eq(a, b), a == b);
+                        assert_eq!(lt(a, b), a < b);
+                        assert_eq!(choose(true, a, b), a);
+                        assert_eq!(choose(false, a, b), b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exp() {
+        let mut rng = TestRng::with(4504);
+        for order in ORDERS {
+            let vals = values(&mut rng, order);
+            for (a, e) in vals.into_iter().zip(vals.into_iter().rev()) {
+                let mut pow = u256::ONE;
+                for i in (0..e.bits_required()).rev() {
+                    pow = super::super::microcode::mul_mod_int(order, pow, pow).0;
+                    if e.bit(i) {
+                        pow = super::super::microcode::mul_mod_int(order, pow, a).0;
+                    }
+                }
+                assert_eq!(exp_mod(order, a, e), pow);
+            }
+        }
+    }
+
+    #[test]
+    fn fits_bits() {
+        let val = u256::from(0x1_0000u32);
+        assert!(!fits(val, 16));
+        assert!(fits(val, 24));
+        assert!(fits(u256::ZERO, 8));
+        assert!(!fits(u256::MAX, 128));
+    }
+}
@@ -0,0 +1,298 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Elliptic curve parameters.
+
+use alloc::string::{String, ToString};
+
+use amplify::num::{u256, u512};
+use strict_encoding::StrictDumb;
+
+use crate::{fe256, LIB_NAME_FINITE_FIELD};
+
+/// Equation of an elliptic curve over the field of the VM.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, tags = order)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum CurveForm {
+    /// Short Weierstrass curve `y^2 = x^3 + a * x + b`.
+    #[display("weierstrass")]
+    Weierstrass {
+        /// Coefficient `a`.
+        a: fe256,
+        /// Coefficient `b`.
+        b: fe256,
+    },
+
+    /// Twisted Edwards curve `a * x^2 + y^2 = 1 + d * x^2 * y^2`.
+    #[display("edwards")]
+    Edwards {
+        /// Coefficient `a`.
+        a: fe256,
+        /// Coefficient `d`.
+        d: fe256,
+    },
+}
+
+impl StrictDumb for CurveForm {
+    fn strict_dumb() -> Self {
+        CurveForm::Weierstrass {
+            a: fe256::ZERO,
+            b: fe256::from(7u8),
+        }
+    }
+}
+
+/// Errors in elliptic curve parameters.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum CurveError {
+    /// curve parameter {0} is not less than the field order.
+    NonCanonical(fe256),
+
+    /// {0} curve equation is singular.
+    Singular(CurveForm),
+
+    /// curve generator is not a point on the curve.
+    GeneratorOffCurve,
+
+    /// scalar field order of the curve must be greater than one.
+    InvalidScalarOrder,
+
+    /// curve cofactor must not be zero.
+    ZeroCofactor,
+}
+
+/// Parameters of an elliptic curve over the field of the VM.
+///
+/// The parameters are provided in [`GfaConfig::curve`] for the instructions working with the curve
+/// points, so that the choice of the curve lives in the configuration and not in the constants of
+/// the programs.
+///
+/// [`GfaConfig::curve`]: crate::GfaConfig::curve
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CurveParams {
+    /// Curve equation.
+    pub form: CurveForm,
+    /// X coordinate of the generator point.
+    pub gx: fe256,
+    /// Y coordinate of the generator point.
+    pub gy: fe256,
+    /// Cofactor of the subgroup generated by the generator point.
+    pub cofactor: u64,
+    /// Order of the subgroup generated by the generator point, which is the order of the scalar
+    /// field.
+    pub scalar_order: u256,
+}
+
+impl CurveParams {
+    /// Checks that the parameters describe a non-singular curve over the field with the given
+    /// order, and that the generator belongs to the curve.
+    ///
+    /// The order of the generator point is not checked, since it requires curve arithmetic.
+    pub fn validate(&self, field_order: u256) -> Result<(), CurveError> {
+        let (c1, c2) = match self.form {
+            CurveForm::Weierstrass { a, b } => (a, b),
+            CurveForm::Edwards { a, d } => (a, d),
+        };
+        for val in [c1, c2, self.gx, self.gy] {
+            if val.to_u256() >= field_order {
+                return Err(CurveError::NonCanonical(val));
+            }
+        }
+        if self.scalar_order <= u256::ONE {
+            return Err(CurveError::InvalidScalarOrder);
+        }
+        if self.cofactor == 0 {
+            return Err(CurveError::ZeroCofactor);
+        }
+
+        let p = field_order;
+        let (x, y) = (self.gx.to_u256(), self.gy.to_u256());
+        let (x2, y2) = (mul(p, x, x), mul(p, y, y));
+        let on_curve = match self.form {
+            CurveForm::Weierstrass { a, b } => {
+                let (a, b) = (a.to_u256(), b.to_u256());
+                // 4 * a^3 + 27 * b^2 must not be zero
+                let disc =
+                    add(p, mul(p, u256::from(4u8), mul(p, a, mul(p, a, a))), mul(p, u256::from(27u8), mul(p, b, b)));
+                if disc == u256::ZERO {
+                    return Err(CurveError::Singular(self.form));
+                }
+                y2 == add(p, add(p, mul(p, x2, x), mul(p, a, x)), b)
+            }
+            CurveForm::Edwards { a, d } => {
+                if a == fe256::ZERO || d == fe256::ZERO || a == d {
+                    return Err(CurveError::Singular(self.form));
+                }
+                let (a, d) = (a.to_u256(), d.to_u256());
+                add(p, mul(p, a, x2), y2) == add(p, u256::ONE, mul(p, d, mul(p, x2, y2)))
+            }
+        };
+        if !on_curve {
+            return Err(CurveError::GeneratorOffCurve);
+        }
+        Ok(())
+    }
+}
+
+fn reduce(p: u256, val: u512) -> u256 {
+    let limbs = (val % u512::from(p)).into_inner();
+    u256::from_inner([limbs[0], limbs[1], limbs[2], limbs[3]])
+}
+
+fn add(p: u256, a: u256, b: u256) -> u256 { reduce(p, u512::from(a) + u512::from(b)) }
+
+fn mul(p: u256, a: u256, b: u256) -> u256 { reduce(p, u512::from(a) * u512::from(b)) }
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::CoreExt;
+
+    use super::*;
+    use crate::{GfaConfig, GfaCore};
+
+    // 2^255 - 19
+    const P25519: u256 =
+        u256::from_inner([0xFFFF_FFFF_FFFF_FFED, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF, 0x7FFF_FFFF_FFFF_FFFF]);
+    // 2^256 - 2^32 - 977
+    const PSECP: u256 =
+        u256::from_inner([0xFFFF_FFFE_FFFF_FC2F, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF]);
+
+    fn secp256k1() -> CurveParams {
+        CurveParams {
+            form: CurveForm::Weierstrass {
+                a: fe256::ZERO,
+                b: fe256::from(7u8),
+            },
+            gx: fe256::from(u256::from_inner([
+                0x59F2_815B_16F8_1798,
+                0x029B_FCDB_2DCE_28D9,
+                0x55A0_6295_CE87_0B07,
+                0x79BE_667E_F9DC_BBAC,
+            ])),
+            gy: fe256::from(u256::from_inner([
+                0x9C47_D08F_FB10_D4B8,
+                0xFD17_B448_A685_5419,
+                0x5DA4_FBFC_0E11_08A8,
+                0x483A_DA77_26A3_C465,
+            ])),
+            cofactor: 1,
+            scalar_order: u256::from_inner([
+                0xBFD2_5E8C_D036_4141,
+                0xBAAE_DCE6_AF48_A03B,
+                0xFFFF_FFFF_FFFF_FFFE,
+                0xFFFF_FFFF_FFFF_FFFF,
+            ]),
+        }
+    }
+
+    fn ed25519() -> CurveParams {
+        CurveParams {
+            form: CurveForm::Edwards {
+                a: fe256::from(P25519 - u256::ONE),
+                d: fe256::from(u256::from_inner([
+                    0x75EB_4DCA_1359_78A3,
+                    0x0070_0A4D_4141_D8AB,
+                    0x8CC7_4079_7779_E898,
+                    0x5203_6CEE_2B6F_FE73,
+                ])),
+            },
+            gx: fe256::from(u256::from_inner([
+                0xC956_2D60_8F25_D51A,
+                0x692C_C760_9525_A7B2,
+                0xC0A4_E231_FDD6_DC5C,
+                0x2169_36D3_CD6E_53FE,
+            ])),
+            gy: fe256::from(u256::from_inner([
+                0x6666_6666_6666_6658,
+                0x6666_6666_6666_6666,
+                0x6666_6666_6666_6666,
+                0x6666_6666_6666_6666,
+            ])),
+            cofactor: 8,
+            scalar_order: u256::from_inner([0x5812_631A_5CF5_D3ED, 0x14DE_F9DE_A2F7_9CD6, 0, 0x1000_0000_0000_0000]),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(secp256k1().validate(PSECP), Ok(()));
+        assert_eq!(ed25519().validate(P25519), Ok(()));
+    }
+
+    #[test]
+    fn invalid() {
+        let secp = secp256k1();
+        assert_eq!(secp.validate(P25519), Err(CurveError::GeneratorOffCurve));
+        let gy = fe256::from(u256::ONE);
+        assert_eq!(CurveParams { gy, ..secp }.validate(PSECP), Err(CurveError::GeneratorOffCurve));
+        let gx = fe256::from(PSECP);
+        assert_eq!(CurveParams { gx, ..secp }.validate(PSECP), Err(CurveError::NonCanonical(gx)));
+        assert_eq!(CurveParams { cofactor: 0, ..secp }.validate(PSECP), Err(CurveError::ZeroCofactor));
+        assert_eq!(
+            CurveParams {
+                scalar_order: u256::ONE,
+                ..secp
+            }
+            .validate(PSECP),
+            Err(CurveError::InvalidScalarOrder)
+        );
+        let form = CurveForm::Weierstrass {
+            a: fe256::ZERO,
+            b: fe256::ZERO,
+        };
+        assert_eq!(CurveParams { form, ..secp }.validate(PSECP), Err(CurveError::Singular(form)));
+
+        let ed = ed25519();
+        let form = CurveForm::Edwards {
+            a: fe256::from(1u8),
+            d: fe256::from(1u8),
+        };
+        assert_eq!(CurveParams { form, ..ed }.validate(P25519), Err(CurveError::Singular(form)));
+    }
+
+    #[test]
+    fn config() {
+        let config = GfaConfig {
+            field_order: PSECP,
+            curve: Some(secp256k1()),
+            ..GfaConfig::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(GfaCore::with(config).curve(), Some(secp256k1()));
+
+        let config = GfaConfig {
+            curve: Some(secp256k1()),
+            ..GfaConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert_eq!(GfaCore::with(config).curve(), None);
+        assert_eq!(GfaConfig::default().validate(), Ok(()));
+    }
+}
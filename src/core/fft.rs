@@ -0,0 +1,160 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Helpers for FFT-friendly fields.
+
+use amplify::num::u256;
+
+use super::microcode::mul_mod_int;
+use crate::{fe256, GfaCore, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS};
+
+/// Generators of the multiplicative groups of the known FFT-friendly fields.
+const KNOWN_GENERATORS: [(u256, u8); 2] = [(FIELD_ORDER_GOLDILOCKS, 7), (FIELD_ORDER_BABYBEAR, 31)];
+
+/// Number of the smallest field elements tried when searching for a quadratic non-residue.
+const NON_RESIDUE_CANDIDATES: u8 = 255;
+
+impl GfaCore {
+    /// Returns 2-adicity of the field, i.e. the largest `s` such that `2^s` divides `FQ - 1`.
+    ///
+    /// Evaluation domains of FFT have sizes of powers of two up to `2^s`.
+    pub fn two_adicity(&self) -> u32 {
+        let order = self.fq() - u256::ONE;
+        (0..256).find(|bit| order.bit(*bit)).unwrap_or(0) as u32
+    }
+
+    /// Returns a shift for cosets of the FFT evaluation domains.
+    ///
+    /// For Goldilocks and BabyBear fields, returns the commonly used generator of the
+    /// multiplicative group of the field. For other fields, returns the smallest quadratic
+    /// non-residue, which never belongs to an evaluation domain of size below `2^s` (see
+    /// [`Self::two_adicity`]).
+    ///
+    /// # Returns
+    ///
+    /// `None` if no quadratic non-residue was found, which happens if the field order is not a
+    /// prime.
+    pub fn coset_shift(&self) -> Option<fe256> {
+        let order = self.fq();
+        if let Some((_, gen)) = KNOWN_GENERATORS.iter().find(|(fq, _)| *fq == order) {
+            return Some(fe256::from(*gen));
+        }
+        let minus_one = order - u256::ONE;
+        let half = minus_one >> 1;
+        (2..=NON_RESIDUE_CANDIDATES)
+            .map(u256::from)
+            .filter(|z| *z < order)
+            .find(|z| pow_mod(order, *z, half) == minus_one)
+            .map(fe256::from)
+    }
+
+    /// Returns generator of the FFT evaluation domain of size `2^log_size`, i.e. a primitive root
+    /// of unity of this order.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `log_size` exceeds the 2-adicity of the field (see [`Self::two_adicity`]), or if
+    /// the field order is not a prime.
+    pub fn domain_generator(&self, log_size: u32) -> Option<fe256> {
+        let order = self.fq();
+        let s = self.two_adicity();
+        if log_size > s {
+            return None;
+        }
+        // For a non-residue `z`, `z^((FQ - 1) / 2^s)` is a primitive root of unity of order `2^s`
+        let z = self.coset_shift()?.to_u256();
+        let root = pow_mod(order, z, (order - u256::ONE) >> s as usize);
+        let gen = pow_mod(order, root, u256::ONE << (s - log_size) as usize);
+        // Checking that the order of the generator is exactly `2^log_size`
+        let check = match log_size {
+            0 => gen == u256::ONE,
+            _ => pow_mod(order, gen, u256::ONE << (log_size - 1) as usize) == order - u256::ONE,
+        };
+        check.then_some(fe256::from(gen))
+    }
+}
+
+fn pow_mod(order: u256, base: u256, exp: u256) -> u256 {
+    let mut res = u256::ONE;
+    for bit in (0..256).rev() {
+        res = mul_mod_int(order, res, res).0;
+        if exp.bit(bit) {
+            res = mul_mod_int(order, res, base).0;
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::CoreExt;
+
+    use super::*;
+    use crate::GfaConfig;
+
+    fn core(field_order: u256) -> GfaCore {
+        GfaCore::with(GfaConfig {
+            field_order,
+            ..GfaConfig::default()
+        })
+    }
+
+    #[test]
+    fn two_adicity() {
+        assert_eq!(core(FIELD_ORDER_GOLDILOCKS).two_adicity(), 32);
+        assert_eq!(core(FIELD_ORDER_BABYBEAR).two_adicity(), 27);
+        assert_eq!(core((u256::ONE << 127) - u256::ONE).two_adicity(), 1);
+    }
+
+    #[test]
+    fn coset_shift() {
+        assert_eq!(core(FIELD_ORDER_GOLDILOCKS).coset_shift(), Some(fe256::from(7u8)));
+        assert_eq!(core(FIELD_ORDER_BABYBEAR).coset_shift(), Some(fe256::from(31u8)));
+        let p = (u256::ONE << 127) - u256::ONE;
+        let shift = core(p).coset_shift().unwrap().to_u256();
+        assert_eq!(pow_mod(p, shift, (p - u256::ONE) >> 1), p - u256::ONE);
+        // Not a prime
+        assert_eq!(core(u256::from(1u32 << 20)).coset_shift(), None);
+    }
+
+    #[test]
+    fn domain_generator() {
+        for order in [FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_BABYBEAR, (u256::ONE << 127) - u256::ONE] {
+            let core = core(order);
+            let s = core.two_adicity();
+            for log_size in 0..=s {
+                let gen = core.domain_generator(log_size).unwrap().to_u256();
+                assert_eq!(pow_mod(order, gen, u256::ONE << log_size as usize), u256::ONE);
+                if log_size > 0 {
+                    assert_ne!(pow_mod(order, gen, u256::ONE << (log_size - 1) as usize), u256::ONE);
+                }
+            }
+            assert_eq!(core.domain_generator(s + 1), None);
+        }
+        assert_eq!(
+            core(FIELD_ORDER_GOLDILOCKS).domain_generator(1),
+            Some(fe256::from(FIELD_ORDER_GOLDILOCKS - u256::ONE))
+        );
+    }
+}
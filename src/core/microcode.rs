@@ -24,14 +24,88 @@ use aluvm::regs::Status;
 use aluvm::CoreExt;
 use amplify::num::{u256, u512};
 
-use crate::gfa::Bits;
-use crate::{fe256, GfaCore, RegE};
+use super::ct;
+use crate::gfa::{Bits, IsaGroups};
+use crate::{fe256, CurveParams, FailurePolicy, GfaCore, RegE, SolinasPrime};
 
 /// Microcode for finite field arithmetics.
 impl GfaCore {
     /// Get value of the field order register (`FQ`).
     pub fn fq(&self) -> u256 { self.fq }
 
+    /// Checks whether a value is a canonical element of the field, i.e. it is less than the field
+    /// order.
+    #[inline]
+    pub fn is_canonical(&self, val: fe256) -> bool { val.to_u256() < self.fq }
+
+    /// Checks whether the field arithmetic runs in constant time (see
+    /// [`GfaConfig::constant_time`]).
+    ///
+    /// In the constant-time mode the execution time of `eq`, `fits`, `add`, `mul`, `sqr`, `neg`,
+    /// `inv`, `exp`, `cmov` and `sel` instructions doesn't depend on the values in the registers.
+    /// The mode doesn't hide which registers are set, since this is defined by the program and not
+    /// by the witness, and whether `inv` fails `CK` on zero.
+    ///
+    /// [`GfaConfig::constant_time`]: crate::GfaConfig::constant_time
+    pub fn is_constant_time(&self) -> bool { self.ct }
+
+    /// Returns special form of the field order, if the core uses a reduction routine synthesized
+    /// for it (see [`GfaConfig::solinas`]).
+    ///
+    /// [`GfaConfig::solinas`]: crate::GfaConfig::solinas
+    pub fn solinas(&self) -> Option<SolinasPrime> { self.reduction.map(|reduction| reduction.prime()) }
+
+    /// Returns parameters of the elliptic curve over the field, if they were provided in the
+    /// configuration and passed the validation (see [`GfaConfig::validate`]).
+    ///
+    /// [`GfaConfig::validate`]: crate::GfaConfig::validate
+    pub fn curve(&self) -> Option<CurveParams> { self.curve }
+
+    /// Returns actions taken on failures of instructions, per instruction class.
+    pub fn failure_policy(&self) -> FailurePolicy { self.policy }
+
+    /// Returns instruction groups supported by the core.
+    pub fn isa_groups(&self) -> IsaGroups { self.isa }
+
+    /// Get the limit on the number of executed instructions, if any.
+    pub fn step_lim(&self) -> Option<u64> { self.step_lim }
+
+    /// Get the number of executed instructions, counted since the core construction or the last
+    /// reset.
+    pub fn steps(&self) -> u64 { self.steps }
+
+    /// Get the number of executed instructions from the GFA256 ISA extension.
+    ///
+    /// The rest of the executed instructions (see [`Self::base_steps`]) belong to the base AluVM
+    /// ISA, i.e. are control flow instructions.
+    pub fn gfa_steps(&self) -> u64 { self.gfa_steps }
+
+    /// Get the number of executed instructions from the base AluVM ISA.
+    pub fn base_steps(&self) -> u64 { self.steps - self.gfa_steps }
+
+    /// Set the number of executed instructions, in total and from the GFA256 ISA extension.
+    pub(crate) fn set_steps(&mut self, steps: u64, gfa_steps: u64) {
+        self.steps = steps;
+        self.gfa_steps = gfa_steps.min(steps);
+    }
+
+    /// Accounts for execution of an instruction.
+    ///
+    /// # Returns
+    ///
+    /// `false`, without counting the instruction, if the step limit is already reached.
+    pub(crate) fn count_step(&mut self) -> bool {
+        if matches!(self.step_lim, Some(lim) if self.steps >= lim) {
+            return false;
+        }
+        self.steps = self.steps.saturating_add(1);
+        true
+    }
+
+    /// Accounts for execution of an instruction from the GFA256 ISA extension, which must be
+    /// already counted with [`Self::count_step`].
+    pub(crate) fn count_gfa_step(&mut self) { self.gfa_steps = self.gfa_steps.saturating_add(1); }
+
     /// Test whether the register has a value, returning a status.
     ///
     /// # Register modification
@@ -45,6 +119,21 @@ impl GfaCore {
         }
     }
 
+    /// Put a value into the `dst` register, checking that it is an element of the field.
+    ///
+    /// # Returns
+    ///
+    /// If the value is not less than the field order, clears the `dst` register and returns
+    /// [`Status::Fail`]. Otherwise, returns success.
+    pub fn load(&mut self, dst: RegE, val: fe256) -> Status {
+        if !self.is_canonical(val) {
+            self.clr(dst);
+            return Status::Fail;
+        }
+        self.set(dst, val);
+        Status::Ok
+    }
+
     /// Check whether a register value fits the provided number of bits.
     ///
     /// # Returns
@@ -59,10 +148,55 @@ impl GfaCore {
         let order = self.fq();
         let a = self.get(src)?;
         debug_assert!(a.to_u256() < order);
+        if self.ct {
+            return Some(ct::fits(a.to_u256(), bits.bit_len()));
+        }
         let check = a.to_u256() >> bits.bit_len();
         Some(check == u256::ZERO)
     }
 
+    /// Check whether a register value is less than the value of another register, comparing them
+    /// as integers in the range `0..order`.
+    ///
+    /// # Returns
+    ///
+    /// `None`, if any of the registers contains no value. Otherwise, a boolean value indicating
+    /// whether the value of `src1` is less than the value of `src2`.
+    ///
+    /// # Register modification
+    ///
+    /// No registers are modified, including `CK` and `CO`.
+    pub fn lt(&self, src1: RegE, src2: RegE) -> Option<bool> {
+        let a = self.get(src1)?.to_u256();
+        let b = self.get(src2)?.to_u256();
+        if self.ct {
+            return Some(ct::lt(a, b));
+        }
+        Some(a < b)
+    }
+
+    /// Check whether a register value is less than a bound, comparing them as 256-bit integers.
+    ///
+    /// The bound is not required to be less than the field order; any such bound holds for all the
+    /// register values.
+    ///
+    /// # Returns
+    ///
+    /// `None`, if the register contains no value. Otherwise, a boolean value indicating whether the
+    /// value of `src` is less than `bound`.
+    ///
+    /// # Register modification
+    ///
+    /// No registers are modified, including `CK` and `CO`.
+    pub fn rng(&self, src: RegE, bound: fe256) -> Option<bool> {
+        let a = self.get(src)?.to_u256();
+        let b = bound.to_u256();
+        if self.ct {
+            return Some(ct::lt(a, b));
+        }
+        Some(a < b)
+    }
+
     /// Move a value from the `src` to `dst` register.
     ///
     /// The value of the `src` register is not changed.
@@ -80,12 +214,49 @@ impl GfaCore {
         }
     }
 
+    /// Move a value from the `src` to `dst` register, if the condition holds.
+    ///
+    /// If the condition doesn't hold, the `dst` register is not changed. Otherwise, works as
+    /// [`Self::mov`], clearing `dst` if the `src` register does not have a value.
+    pub fn cmov(&mut self, dst: RegE, src: RegE, cond: bool) {
+        if self.ct {
+            if let (Some(a), Some(b)) = (self.get(src), self.get(dst)) {
+                self.set(dst, fe256::from(ct::choose(cond, a.to_u256(), b.to_u256())));
+                return;
+            }
+        }
+        if cond {
+            self.mov(dst, src);
+        }
+    }
+
+    /// Move a value from the `src_true` register to `dst` if the condition holds, and from the
+    /// `src_false` register otherwise.
+    ///
+    /// If the selected source register does not have a value, sets `dst` to `None`, clearing any
+    /// previous value in it.
+    pub fn sel(&mut self, dst: RegE, src_true: RegE, src_false: RegE, cond: bool) {
+        if self.ct {
+            if let (Some(a), Some(b)) = (self.get(src_true), self.get(src_false)) {
+                self.set(dst, fe256::from(ct::choose(cond, a.to_u256(), b.to_u256())));
+                return;
+            }
+        }
+        self.mov(dst, if cond { src_true } else { src_false });
+    }
+
     /// Checks the equivalence of values in `src1` and `src2`.
     ///
     /// If both registers do not have a value, returns [`Status::Fail`].
     pub fn eqv(&mut self, src1: RegE, src2: RegE) -> Status {
         let a = self.get(src1);
         let b = self.get(src2);
+        if self.ct {
+            return match (a, b) {
+                (Some(a), Some(b)) if ct::eq(a.to_u256(), b.to_u256()) => Status::Ok,
+                _ => Status::Fail,
+            };
+        }
         if a == b && a.is_some() {
             Status::Ok
         } else {
@@ -119,13 +290,12 @@ impl GfaCore {
         let b = b.to_u256();
         debug_assert!(a < order && b < order);
 
-        let (mut res, overflow) = a.overflowing_add(b);
-        if overflow {
-            res += u256::MAX - order;
+        if self.ct {
+            self.set(dst_src, fe256::from(ct::add_mod(order, a, b)));
+            return Status::Ok;
         }
 
-        let res = res % order;
-        self.set(dst_src, fe256::from(res));
+        self.set(dst_src, fe256::from(add_mod_int(order, a, b)));
         Status::Ok
     }
 
@@ -155,6 +325,17 @@ impl GfaCore {
         let b = b.to_u256();
         debug_assert!(a < order && b < order);
 
+        if self.ct {
+            self.set(dst_src, fe256::from(ct::mul_mod(order, a, b)));
+            return Status::Ok;
+        }
+
+        if let Some(reduction) = self.reduction {
+            let res = reduction.reduce(u512::from(a) * u512::from(b));
+            self.set(dst_src, fe256::from(res));
+            return Status::Ok;
+        }
+
         let (res, _) = mul_mod_int(order, a, b);
 
         let res = res % order;
@@ -162,13 +343,48 @@ impl GfaCore {
         Status::Ok
     }
 
+    /// Square a value from the `src` register, storing the result in `dst`.
+    ///
+    /// The square is computed with a dedicated routine, which multiplies each pair of different
+    /// limbs of the value only once, and then is reduced in the same way as by
+    /// [`GfaCore::mul_mod`].
+    ///
+    /// # Returns
+    ///
+    /// If the `src` register does not have a value, returns [`Status::Fail`].
+    /// Otherwise, returns success.
+    #[inline]
+    pub fn sqr_mod(&mut self, dst: RegE, src: RegE) -> Status {
+        let order = self.fq();
+
+        let Some(a) = self.get(src) else {
+            return Status::Fail;
+        };
+
+        let a = a.to_u256();
+        debug_assert!(a < order);
+
+        if self.ct {
+            self.set(dst, fe256::from(ct::mul_mod(order, a, a)));
+            return Status::Ok;
+        }
+
+        let sqr = sqr_wide(a);
+        let res = match self.reduction {
+            Some(reduction) => reduction.reduce(sqr),
+            None => u256::from_le_slice(&(sqr % u512::from(order)).to_le_bytes()[..32]).expect(""),
+        };
+        self.set(dst, fe256::from(res));
+        Status::Ok
+    }
+
     /// Negate a value in the `dst_src` register by subtracting it from the field order, stored in
     /// `FQ` register.
     ///
     /// # Returns
     ///
     /// If the `dst_src` register does not have a value, returns [`Status::Fail`].
-    /// Otherwise, returns success.
+    /// Otherwise, returns success. Negation of zero is zero.
     #[inline]
     pub fn neg_mod(&mut self, dst_src: RegE, src: RegE) -> Status {
         let order = self.fq();
@@ -179,13 +395,331 @@ impl GfaCore {
 
         debug_assert!(a.to_u256() < order);
 
-        let res = order - a.to_u256();
-        self.set(dst_src, fe256::from(res));
+        if self.ct {
+            self.set(dst_src, fe256::from(ct::neg_mod(order, a.to_u256())));
+            return Status::Ok;
+        }
+
+        self.set(dst_src, fe256::from(neg_mod_int(order, a.to_u256())));
+        Status::Ok
+    }
+
+    /// Raises a value in the `dst_src` register to the power of the value in the `exp` register
+    /// modulo the field order `FQ`, storing the result back in `dst_src`.
+    ///
+    /// The exponent is taken as an integer, and the power is computed with square-and-multiply;
+    /// zero raised to the power of zero is one.
+    ///
+    /// # Returns
+    ///
+    /// If any of `dst_src` or `exp` registers do not have a value, returns [`Status::Fail`].
+    /// Otherwise, returns success.
+    #[inline]
+    pub fn exp_mod(&mut self, dst_src: RegE, exp: RegE) -> Status {
+        let order = self.fq();
+
+        let Some(a) = self.get(dst_src) else {
+            return Status::Fail;
+        };
+        let Some(e) = self.get(exp) else {
+            return Status::Fail;
+        };
+
+        let a = a.to_u256();
+        let e = e.to_u256();
+        debug_assert!(a < order && e < order);
+
+        if self.ct {
+            self.set(dst_src, fe256::from(ct::exp_mod(order, a, e)));
+            return Status::Ok;
+        }
+
+        self.set(dst_src, fe256::from(self.pow(a, e)));
         Status::Ok
     }
+
+    /// Raises a value in the `dst_src` register to the power of the immediate `exp` value modulo
+    /// the field order `FQ`, storing the result back in `dst_src`.
+    ///
+    /// Since the exponent is defined by the program, the number of multiplications depends only
+    /// on it, and the operation runs in constant time in the constant-time mode; zero raised to
+    /// the power of zero is one.
+    ///
+    /// # Returns
+    ///
+    /// If the `dst_src` register does not have a value, returns [`Status::Fail`]. Otherwise,
+    /// returns success.
+    #[inline]
+    pub fn expi_mod(&mut self, dst_src: RegE, exp: u8) -> Status {
+        let Some(a) = self.get(dst_src) else {
+            return Status::Fail;
+        };
+
+        debug_assert!(a.to_u256() < self.fq());
+
+        self.set(dst_src, fe256::from(self.pow(a.to_u256(), u256::from(exp))));
+        Status::Ok
+    }
+
+    /// Computes the multiplicative inverse of a value in the `src` register modulo the field order
+    /// `FQ`, storing the result in `dst`.
+    ///
+    /// The inverse is computed with the extended Euclidean algorithm, which doesn't require the
+    /// field order to be prime. The number of the algorithm iterations depends on the value, so in
+    /// the constant-time mode the inverse is instead computed as `a^(FQ - 2)` with a constant-time
+    /// exponentiation, which finds the inverse only if the field order is prime.
+    ///
+    /// # Returns
+    ///
+    /// If the `src` register does not have a value, or the value has no inverse (which is the case
+    /// for zero and, with a composite field order, for the values sharing a factor with it),
+    /// returns [`Status::Fail`] leaving `dst` unchanged. In the constant-time mode with a composite
+    /// field order [`Status::Fail`] is returned also if the exponentiation doesn't give the
+    /// inverse. Otherwise, returns success.
+    #[inline]
+    pub fn inv_mod(&mut self, dst: RegE, src: RegE) -> Status {
+        let order = self.fq();
+
+        let Some(a) = self.get(src) else {
+            return Status::Fail;
+        };
+
+        debug_assert!(a.to_u256() < order);
+
+        if self.ct {
+            // Fermat's little theorem; the result is checked, since zero has no inverse
+            let a = a.to_u256();
+            let res = ct::exp_mod(order, a, order.wrapping_sub(u256::from(2u8)));
+            if !ct::eq(ct::mul_mod(order, a, res), u256::ONE % order) {
+                return Status::Fail;
+            }
+            self.set(dst, fe256::from(res));
+            return Status::Ok;
+        }
+
+        match inv_mod_int(order, a.to_u256()) {
+            Some(res) => {
+                self.set(dst, fe256::from(res));
+                Status::Ok
+            }
+            None => Status::Fail,
+        }
+    }
+
+    /// Computes a square root of a value in the `src` register modulo the field order `FQ`,
+    /// storing the result in `dst`.
+    ///
+    /// The root is computed with the Tonelli–Shanks algorithm, which requires the field order to
+    /// be an odd prime; with other orders roots may not be found. Out of the two roots the one
+    /// with the smaller canonical representation is chosen. The number of the algorithm
+    /// iterations depends on the value, so the operation doesn't run in constant time even in the
+    /// constant-time mode.
+    ///
+    /// Unless the field order is 3 modulo 4, the algorithm requires a quadratic non-residue, which
+    /// is searched for among the numbers below the square of the bit length of the order. For a
+    /// prime order, the search usually completes after a few iterations.
+    ///
+    /// # Returns
+    ///
+    /// `None`, if the `src` register contains no value, or if no quadratic non-residue was found
+    /// for the field order (which happens for some composite orders, like squares). Otherwise, a
+    /// boolean value indicating whether the root was found; if it wasn't (the value is a
+    /// quadratic non-residue), `dst` is left unchanged.
+    ///
+    /// # Register modification
+    ///
+    /// Modifies only the `dst` register; `CK` and `CO` are not modified.
+    pub fn sqrt_mod(&mut self, dst: RegE, src: RegE) -> Option<bool> {
+        let a = self.get(src)?;
+        debug_assert!(a.to_u256() < self.fq());
+
+        let Some(res) = self.sqrt(a.to_u256())? else {
+            return Some(false);
+        };
+        self.set(dst, fe256::from(res));
+        Some(true)
+    }
 }
 
-fn mul_mod_int(order: u256, a: u256, b: u256) -> (u256, bool) {
+/// Upper bound on the number of candidates checked when searching for a quadratic non-residue,
+/// reached by the 256-bit field orders (see [`non_residue`]).
+const NON_RESIDUE_LIM: u64 = 256 * 256;
+
+/// Upper bound on the number of modular multiplications performed by [`GfaCore::sqrt_mod`].
+///
+/// Checking a candidate for a quadratic non-residue takes a single reduction of the field order,
+/// which is charged as a multiplication. Tonelli–Shanks runs four exponentiations by exponents
+/// below 2^256, each taking up to two multiplications per bit, and then, for the two-adicity
+/// `s < 256` of the field order, up to `s(s+1)/2 + 2s` squarings and multiplications, followed by
+/// a single multiplication checking the result.
+pub(crate) const SQRT_MUL_LIM: u64 = NON_RESIDUE_LIM + 4 * 2 * 256 + 255 * 256 / 2 + 2 * 255 + 1;
+
+/// Finds a quadratic non-residue modulo an odd order, checking the numbers from 2 up to the square
+/// of the bit length of the order.
+///
+/// Assuming the generalized Riemann hypothesis, the smallest non-residue modulo a prime `p` is
+/// below `2 ln²(p)`, which is less than the bound. The candidates are checked with the Jacobi
+/// symbol, which doesn't require modular exponentiation; if the order is composite, a candidate
+/// with the symbol of -1 is a non-residue modulo the order as well. Composite orders for which
+/// all the candidates have the symbol of 1, like squares, have no candidate found, and the search
+/// returns `None`.
+fn non_residue(order: u256) -> Option<u256> {
+    let lim = (order.bits_required() as u64).pow(2);
+    (2..lim)
+        .map(u256::from)
+        .take_while(|z| *z < order)
+        .find(|z| jacobi(z.low_u64(), order) == -1)
+}
+
+/// Computes the Jacobi symbol `(a/n)` for an odd `n > a`, which is the Legendre symbol if `n` is
+/// a prime.
+fn jacobi(a: u64, n: u256) -> i8 {
+    let mut t = 1i8;
+    // The first step of the reciprocity, reducing the large value to a small one
+    let mut a = a;
+    let lo = n.low_u64();
+    while a % 2 == 0 {
+        a /= 2;
+        if matches!(lo % 8, 3 | 5) {
+            t = -t;
+        }
+    }
+    if a % 4 == 3 && lo % 4 == 3 {
+        t = -t;
+    }
+    let rem = n
+        .into_inner()
+        .iter()
+        .rev()
+        .fold(0u128, |rem, limb| ((rem << 64) | *limb as u128) % a as u128);
+    let (mut a, mut n) = (rem as u64, a);
+
+    while a != 0 {
+        while a % 2 == 0 {
+            a /= 2;
+            if matches!(n % 8, 3 | 5) {
+                t = -t;
+            }
+        }
+        (a, n) = (n, a);
+        if a % 4 == 3 && n % 4 == 3 {
+            t = -t;
+        }
+        a %= n;
+    }
+    if n == 1 {
+        t
+    } else {
+        0
+    }
+}
+
+impl GfaCore {
+    /// Multiplies two canonical values modulo the field order, using the same routine as `mul`.
+    fn mul_raw(&self, a: u256, b: u256) -> u256 {
+        if self.ct {
+            return ct::mul_mod(self.fq, a, b);
+        }
+        match self.reduction {
+            Some(reduction) => reduction.reduce(u512::from(a) * u512::from(b)),
+            None => mul_mod_int(self.fq, a, b).0,
+        }
+    }
+
+    /// Raises a canonical value to the power of `e` with square-and-multiply, branching only on
+    /// the bits of the exponent.
+    fn pow(&self, a: u256, e: u256) -> u256 {
+        let mut res = u256::ONE % self.fq;
+        for i in (0..e.bits_required()).rev() {
+            res = self.mul_raw(res, res);
+            if e.bit(i) {
+                res = self.mul_raw(res, a);
+            }
+        }
+        res
+    }
+
+    /// Finds the smaller square root of a canonical value with the Tonelli–Shanks algorithm.
+    ///
+    /// Returns `None` if no quadratic non-residue was found for the field order, and `Some(None)`
+    /// if the root doesn't exist.
+    fn sqrt(&self, a: u256) -> Option<Option<u256>> {
+        let order = self.fq;
+        if a == u256::ZERO {
+            return Some(Some(u256::ZERO));
+        }
+        if !order.bit(0) {
+            return Some(None);
+        }
+        let one = u256::ONE;
+        let max = order - one;
+        // Euler's criterion
+        let half = max >> 1;
+        if self.pow(a, half) != one {
+            return Some(None);
+        }
+
+        let s = max.trailing_zeros();
+        if s == 1 {
+            // For `p = 3 mod 4` the root is `a^((p+1)/4)`, and no non-residue is needed
+            let r = self.pow(a, (order >> 2) + one);
+            return Some(Some(r.min(order - r)).filter(|_| self.mul_raw(r, r) == a));
+        }
+        let q = max >> s as usize;
+        let z = non_residue(order)?;
+
+        let mut m = s;
+        let mut c = self.pow(z, q);
+        let mut t = self.pow(a, q);
+        let mut r = self.pow(a, (q >> 1) + one);
+        while t != one {
+            let mut i = 0;
+            let mut t2 = t;
+            while t2 != one {
+                t2 = self.mul_raw(t2, t2);
+                i += 1;
+                if i == m {
+                    return Some(None);
+                }
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = self.mul_raw(b, b);
+            }
+            m = i;
+            c = self.mul_raw(b, b);
+            t = self.mul_raw(t, c);
+            r = self.mul_raw(r, b);
+        }
+        // With a composite order the algorithm may produce a wrong value
+        if self.mul_raw(r, r) != a {
+            return Some(None);
+        }
+        Some(Some(r.min(order - r)))
+    }
+}
+
+/// Adds two values below `order` modulo `order`.
+pub(crate) fn add_mod_int(order: u256, a: u256, b: u256) -> u256 {
+    let (res, overflow) = a.overflowing_add(b);
+    // On overflow the sum is above the order, and its reduction `a + b - order` is below 2^256
+    if overflow {
+        res.wrapping_sub(order)
+    } else {
+        res % order
+    }
+}
+
+/// Negates a value below `order` modulo `order`; negation of zero is zero.
+pub(crate) fn neg_mod_int(order: u256, a: u256) -> u256 {
+    if a == u256::ZERO {
+        u256::ZERO
+    } else {
+        order - a
+    }
+}
+
+pub(crate) fn mul_mod_int(order: u256, a: u256, b: u256) -> (u256, bool) {
     let a = u512::from(a);
     let b = u512::from(b);
     let c = a * b;
@@ -193,3 +727,104 @@ fn mul_mod_int(order: u256, a: u256, b: u256) -> (u256, bool) {
     let res = u256::from_le_slice(&(c % o).to_le_bytes()[..32]).expect("");
     (res, c >= o)
 }
+
+/// Squares a value, computing the product of each pair of different limbs once and doubling it.
+pub(super) fn sqr_wide(a: u256) -> u512 {
+    let a = a.into_inner();
+    let mut res = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in i + 1..4 {
+            let t = a[i] as u128 * a[j] as u128 + res[i + j] as u128 + carry;
+            res[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        res[i + 4] = carry as u64;
+    }
+
+    let mut carry = 0u64;
+    for limb in &mut res {
+        let next = *limb >> 63;
+        *limb = (*limb << 1) | carry;
+        carry = next;
+    }
+
+    let mut carry = 0u128;
+    for (i, limb) in a.into_iter().enumerate() {
+        let sqr = limb as u128 * limb as u128;
+        let lo = res[2 * i] as u128 + (sqr as u64) as u128 + carry;
+        res[2 * i] = lo as u64;
+        let hi = res[2 * i + 1] as u128 + (sqr >> 64) + (lo >> 64);
+        res[2 * i + 1] = hi as u64;
+        carry = hi >> 64;
+    }
+    u512::from_inner(res)
+}
+
+/// Computes the inverse of `a < order` modulo `order` with the extended Euclidean algorithm,
+/// returning `None` if the inverse doesn't exist.
+pub(crate) fn inv_mod_int(order: u256, a: u256) -> Option<u256> {
+    // Invariant: `t0 * a = r0` and `t1 * a = r1` modulo the order
+    let (mut r0, mut r1) = (order, a);
+    let (mut t0, mut t1) = (u256::ZERO, u256::ONE % order);
+    while r1 != u256::ZERO {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        let qt = mul_mod_int(order, q % order, t1).0;
+        let t = if t0 >= qt { t0 - qt } else { order - (qt - t0) };
+        (t0, t1) = (t1, t);
+    }
+    (r0 == u256::ONE).then_some(t0)
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use super::*;
+    use crate::testing::TestRng;
+    use crate::FIELD_ORDER_STARK;
+
+    #[test]
+    fn sqr_wide() {
+        let mut rng = TestRng::with(4506);
+        let mut vals = vec![u256::ZERO, u256::ONE, u256::from(u64::MAX), u256::MAX];
+        vals.extend((0..64).map(|_| u256::from_le_bytes(rng.bytes32())));
+        for a in vals {
+            assert_eq!(super::sqr_wide(a), u512::from(a) * u512::from(a));
+        }
+    }
+
+    #[test]
+    fn jacobi() {
+        fn pow(a: u64, e: u64, n: u64) -> u64 {
+            (0..u64::BITS - e.leading_zeros()).rev().fold(1, |acc, bit| {
+                let acc = acc * acc % n;
+                if e >> bit & 1 == 1 { acc * a % n } else { acc }
+            })
+        }
+
+        // Euler's criterion gives the Legendre symbol for prime moduli
+        for n in [3u64, 5, 7, 11, 13, 17, 257, 65537, 48473881] {
+            for a in 1..n.min(200) {
+                let expected = if pow(a, (n - 1) / 2, n) == 1 { 1 } else { -1 };
+                assert_eq!(super::jacobi(a, u256::from(n)), expected, "({a}/{n})");
+            }
+        }
+        assert_eq!(super::jacobi(2, u256::from(15u8)), 1);
+        assert_eq!(super::jacobi(7, u256::from(15u8)), -1);
+        assert_eq!(super::jacobi(3, u256::from(15u8)), 0);
+    }
+
+    #[test]
+    fn non_residue() {
+        // All the primes below 67 are quadratic residues modulo this prime
+        assert_eq!(super::non_residue(u256::from(48473881u32)), Some(u256::from(67u8)));
+        assert_eq!(super::non_residue(FIELD_ORDER_STARK), Some(u256::from(3u8)));
+        assert_eq!(super::non_residue(u256::from(9u8)), None);
+        let p = (u256::ONE << 127) - u256::ONE;
+        assert_eq!(super::non_residue(p * p), None);
+    }
+}
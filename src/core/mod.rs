@@ -22,6 +22,20 @@
 
 #[allow(clippy::module_inception)]
 mod core;
+mod ct;
+mod curve;
+mod fft;
 mod microcode;
+mod policy;
+mod solinas;
 
-pub use self::core::{GfaConfig, GfaCore, RegE, FIELD_ORDER_25519, FIELD_ORDER_SECP, FIELD_ORDER_STARK};
+#[cfg(any(feature = "wasm", feature = "python"))]
+pub(crate) use self::core::parse_field_order;
+pub use self::core::{
+    FieldPreset, GfaConfig, GfaCore, ParseFieldPresetError, ParseRegError, RegE, RegOutOfRange, FIELD_ORDER_25519,
+    FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_SECP, FIELD_ORDER_STARK,
+};
+pub use self::curve::{CurveError, CurveForm, CurveParams};
+pub(crate) use self::microcode::{add_mod_int, inv_mod_int, mul_mod_int, neg_mod_int, SQRT_MUL_LIM};
+pub use self::policy::{FailureAction, FailurePolicy};
+pub use self::solinas::{SolinasPrime, SOLINAS_MAX_TERMS};
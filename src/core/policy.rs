@@ -0,0 +1,137 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Per-class policy for handling instruction failures.
+
+use crate::gfa::InstrClass;
+use crate::LIB_NAME_FINITE_FIELD;
+
+/// Action taken by the VM when an instruction fails.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, tags = repr, into_u8, try_from_u8)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+#[display(lowercase)]
+#[repr(u8)]
+pub enum FailureAction {
+    /// Fail `CK` and halt only if the VM is configured to halt on failures (the `CH` register).
+    #[default]
+    Inherit = 0,
+
+    /// Fail `CK` and halt, regardless of the `CH` register value.
+    Halt = 1,
+
+    /// Fail `CK` and continue with the next instruction, regardless of the `CH` register value.
+    Continue = 2,
+
+    /// Leave `CK` unchanged, set `CO` to the failed state and continue with the next instruction.
+    ///
+    /// Allows programs to detect and handle the failure with `chk CO` or `jif CO` instructions.
+    Signal = 3,
+}
+
+/// Actions taken by the VM on failures of instructions of each [`InstrClass`].
+///
+/// Failures of control flow instructions are always handled according to the `CH` register, since
+/// they implement assertions themselves (`chk CO`, `fail CK`).
+///
+/// The default policy inherits the behavior from the `CH` register for all classes, matching the
+/// VM without the policy.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct FailurePolicy {
+    /// Action on failures of instructions putting, moving or clearing register values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub moves: FailureAction,
+    /// Action on failures of instructions checking register values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub checks: FailureAction,
+    /// Action on failures of field arithmetic instructions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub arithmetic: FailureAction,
+    /// Action on failures of host syscall instructions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub host: FailureAction,
+    /// Action on failures of reserved instructions, including the ones handled by the host.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reserved: FailureAction,
+}
+
+impl FailurePolicy {
+    /// Policy inheriting the behavior from the `CH` register for all instruction classes.
+    pub const INHERIT: Self = Self::uniform(FailureAction::Inherit);
+
+    /// Constructs policy applying the same action to all instruction classes.
+    pub const fn uniform(action: FailureAction) -> Self {
+        Self {
+            moves: action,
+            checks: action,
+            arithmetic: action,
+            host: action,
+            reserved: action,
+        }
+    }
+
+    /// Returns the action taken on failures of instructions of the given class.
+    pub const fn action(&self, class: InstrClass) -> FailureAction {
+        match class {
+            InstrClass::Control => FailureAction::Inherit,
+            InstrClass::Move => self.moves,
+            InstrClass::Check => self.checks,
+            InstrClass::Arithmetic => self.arithmetic,
+            InstrClass::Host => self.host,
+            InstrClass::Reserved => self.reserved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn action() {
+        let policy = FailurePolicy {
+            moves: FailureAction::Halt,
+            checks: FailureAction::Signal,
+            arithmetic: FailureAction::Continue,
+            host: FailureAction::Halt,
+            reserved: FailureAction::Inherit,
+        };
+        assert_eq!(policy.action(InstrClass::Control), FailureAction::Inherit);
+        assert_eq!(policy.action(InstrClass::Move), FailureAction::Halt);
+        assert_eq!(policy.action(InstrClass::Check), FailureAction::Signal);
+        assert_eq!(policy.action(InstrClass::Arithmetic), FailureAction::Continue);
+        assert_eq!(policy.action(InstrClass::Host), FailureAction::Halt);
+        assert_eq!(policy.action(InstrClass::Reserved), FailureAction::Inherit);
+
+        assert_eq!(FailurePolicy::default(), FailurePolicy::INHERIT);
+        assert_eq!(FailurePolicy::uniform(FailureAction::Halt).action(InstrClass::Control), FailureAction::Inherit);
+        assert_eq!(FailureAction::Signal.to_string(), "signal");
+    }
+}
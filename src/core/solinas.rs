@@ -0,0 +1,287 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Reduction modulo special primes.
+
+use amplify::num::{u1024, u256, u512};
+
+use crate::LIB_NAME_FINITE_FIELD;
+
+/// Maximal number of terms in a reduction routine synthesized for a [`SolinasPrime`].
+pub const SOLINAS_MAX_TERMS: usize = 16;
+
+/// Structural description of a special prime modulus of the form `p = 2^k - c` or `p = 2^k + c`
+/// (generalized Mersenne, or Solinas, prime), where `c` is less than `2^(k-1)`.
+///
+/// When provided in [`GfaConfig::solinas`], the core synthesizes a dedicated reduction routine for
+/// the modulus, replacing the generic division after multiplication with a few shifts and
+/// additions. The routine decomposes `c` into signed powers of two (its non-adjacent form), so it
+/// is efficient for sparse `c`, as in the most of the special primes used in practice; if the
+/// decomposition has more than [`SOLINAS_MAX_TERMS`] terms, the generic reduction is used.
+///
+/// [`GfaConfig::solinas`]: crate::GfaConfig::solinas
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct SolinasPrime {
+    /// Exponent of the leading power of two.
+    pub k: u16,
+    /// Absolute value of the difference between the modulus and `2^k`.
+    pub c: u256,
+    /// Whether the modulus is above (`p = 2^k + c`) or below (`p = 2^k - c`) the power of two.
+    pub plus: bool,
+}
+
+impl SolinasPrime {
+    /// Goldilocks prime `2^64 - 2^32 + 1`, matching [`crate::FIELD_ORDER_GOLDILOCKS`].
+    pub const GOLDILOCKS: Self = Self {
+        k: 64,
+        c: u256::from_inner([0xFFFF_FFFF, 0, 0, 0]),
+        plus: false,
+    };
+
+    /// BabyBear prime `2^31 - 2^27 + 1`, matching [`crate::FIELD_ORDER_BABYBEAR`].
+    pub const BABYBEAR: Self = Self {
+        k: 31,
+        c: u256::from_inner([0x07FF_FFFF, 0, 0, 0]),
+        plus: false,
+    };
+
+    /// Stark prime `2^251 + 17 * 2^128 + 1`, matching [`crate::FIELD_ORDER_STARK`].
+    pub const STARK: Self = Self {
+        k: 251,
+        c: u256::from_inner([1, 0, 17, 0]),
+        plus: true,
+    };
+
+    /// Computes the modulus.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the description is invalid: `k` is not within `2..=256` range, `c` is zero or not
+    /// less than `2^(k-1)`, or the modulus doesn't fit 256 bits.
+    pub fn modulus(&self) -> Option<u256> {
+        if !(2..=256).contains(&self.k) || self.c == u256::ZERO {
+            return None;
+        }
+        let k = self.k as usize;
+        if self.c >= u256::ONE << (k - 1) {
+            return None;
+        }
+        match (k, self.plus) {
+            (256, false) => Some(u256::ZERO.wrapping_sub(self.c)),
+            (256, true) => None,
+            (_, false) => Some((u256::ONE << k) - self.c),
+            (_, true) => (u256::ONE << k).checked_add(self.c),
+        }
+    }
+}
+
+/// Reduction routine synthesized for a [`SolinasPrime`].
+///
+/// The routine folds the bits of a value above `2^k` using congruence of `2^k` to a sum of signed
+/// powers of two, until the value fits `k` bits.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) struct Reduction {
+    prime: SolinasPrime,
+    order: u256,
+    /// Terms `±2^e` of the value congruent to `2^k`, where `true` marks the negative terms.
+    terms: [(bool, u16); SOLINAS_MAX_TERMS],
+    len: usize,
+}
+
+impl Reduction {
+    /// Synthesizes reduction routine, if the prime description is valid, matches the field order
+    /// and has a sparse enough form.
+    pub(super) fn synthesize(prime: SolinasPrime, order: u256) -> Option<Self> {
+        if prime.modulus()? != order {
+            return None;
+        }
+        let mut terms = [(false, 0u16); SOLINAS_MAX_TERMS];
+        let mut len = 0;
+        let mut weight = u512::ZERO;
+        let mut c = u512::from(prime.c);
+        let mut e = 0u16;
+        // Computing non-adjacent form of `c`
+        while c != u512::ZERO {
+            if c.low_u64() & 1 == 1 {
+                let neg = c.low_u64() & 2 == 2;
+                if len == SOLINAS_MAX_TERMS {
+                    return None;
+                }
+                // `2^k` is congruent to `c` for `p = 2^k - c`, and to `-c` for `p = 2^k + c`
+                terms[len] = (neg ^ prime.plus, e);
+                len += 1;
+                weight += u512::ONE << e as usize;
+                c = if neg { c + u512::ONE } else { c - u512::ONE };
+            }
+            c >>= 1;
+            e += 1;
+        }
+        // Each folding must at least halve the part of the value above `2^k`
+        if weight >= u512::ONE << (prime.k as usize - 1) {
+            return None;
+        }
+        Some(Reduction {
+            prime,
+            order,
+            terms,
+            len,
+        })
+    }
+
+    /// Returns the prime this routine was synthesized for.
+    pub(super) fn prime(&self) -> SolinasPrime { self.prime }
+
+    /// Reduces a value modulo the prime.
+    pub(super) fn reduce(&self, val: u512) -> u256 {
+        let k = self.prime.k as usize;
+        let mask = (u1024::ONE << k) - u1024::ONE;
+        let mut mag = u1024::from(val);
+        let mut neg = false;
+        while mag >> k != u1024::ZERO {
+            let hi = mag >> k;
+            let mut add = mag & mask;
+            let mut sub = u1024::ZERO;
+            for (minus, e) in &self.terms[..self.len] {
+                if *minus {
+                    sub += hi << *e as usize;
+                } else {
+                    add += hi << *e as usize;
+                }
+            }
+            if add >= sub {
+                mag = add - sub;
+            } else {
+                mag = sub - add;
+                neg = !neg;
+            }
+        }
+        // The value is below `2^k`, which is less than twice the modulus
+        let order = u1024::from(self.order);
+        if mag >= order {
+            mag -= order;
+        }
+        let limbs = mag.as_inner();
+        let res = u256::from_inner([limbs[0], limbs[1], limbs[2], limbs[3]]);
+        if neg && res != u256::ZERO {
+            self.order - res
+        } else {
+            res
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use super::*;
+    use crate::testing::TestRng;
+    use crate::{FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_STARK};
+
+    // 2^127 - 1
+    const MERSENNE_127: SolinasPrime = SolinasPrime {
+        k: 127,
+        c: u256::ONE,
+        plus: false,
+    };
+    // NIST P-256 prime `2^256 - 2^224 + 2^192 + 2^96 - 1`
+    const P256: SolinasPrime = SolinasPrime {
+        k: 256,
+        c: u256::from_inner([1, 0xFFFF_FFFF_0000_0000, 0xFFFF_FFFF_FFFF_FFFF, 0x0000_0000_FFFF_FFFE]),
+        plus: false,
+    };
+
+    #[test]
+    fn modulus() {
+        assert_eq!(SolinasPrime::GOLDILOCKS.modulus(), Some(FIELD_ORDER_GOLDILOCKS));
+        assert_eq!(SolinasPrime::BABYBEAR.modulus(), Some(FIELD_ORDER_BABYBEAR));
+        assert_eq!(SolinasPrime::STARK.modulus(), Some(FIELD_ORDER_STARK));
+        assert_eq!(MERSENNE_127.modulus(), Some((u256::ONE << 127) - u256::ONE));
+        assert_eq!(
+            P256.modulus(),
+            Some(u256::from_inner([0xFFFF_FFFF_FFFF_FFFF, 0x0000_0000_FFFF_FFFF, 0, 0xFFFF_FFFF_0000_0001]))
+        );
+
+        assert_eq!(SolinasPrime { k: 1, ..MERSENNE_127 }.modulus(), None);
+        assert_eq!(SolinasPrime { k: 257, ..MERSENNE_127 }.modulus(), None);
+        assert_eq!(
+            SolinasPrime {
+                c: u256::ZERO,
+                ..MERSENNE_127
+            }
+            .modulus(),
+            None
+        );
+        assert_eq!(
+            SolinasPrime {
+                c: u256::ONE << 126,
+                ..MERSENNE_127
+            }
+            .modulus(),
+            None
+        );
+        assert_eq!(SolinasPrime { plus: true, ..P256 }.modulus(), None);
+    }
+
+    #[test]
+    fn synthesize() {
+        assert!(Reduction::synthesize(SolinasPrime::GOLDILOCKS, FIELD_ORDER_GOLDILOCKS).is_some());
+        assert!(Reduction::synthesize(SolinasPrime::GOLDILOCKS, FIELD_ORDER_BABYBEAR).is_none());
+        assert!(Reduction::synthesize(SolinasPrime { k: 1, ..MERSENNE_127 }, FIELD_ORDER_BABYBEAR).is_none());
+        // Dense `c` has too many terms in its non-adjacent form
+        let dense = SolinasPrime {
+            k: 200,
+            c: u256::from_inner([0x5555_5555_5555_5555, 0, 0, 0]),
+            plus: false,
+        };
+        assert!(Reduction::synthesize(dense, dense.modulus().unwrap()).is_none());
+    }
+
+    #[test]
+    fn reduce() {
+        let mut rng = TestRng::with(4486);
+        for prime in [SolinasPrime::GOLDILOCKS, SolinasPrime::BABYBEAR, SolinasPrime::STARK, MERSENNE_127, P256] {
+            let order = prime.modulus().unwrap();
+            let reduction = Reduction::synthesize(prime, order).unwrap();
+            assert_eq!(reduction.prime(), prime);
+            let max = order - u256::ONE;
+            let mut vals = vec![u256::ZERO, u256::ONE, max];
+            for _ in 0..32 {
+                vals.push(u256::from_le_bytes(rng.bytes32()) % order);
+            }
+            for a in &vals {
+                for b in &vals {
+                    let prod = u512::from(*a) * u512::from(*b);
+                    let expected = prod % u512::from(order);
+                    let limbs = expected.as_inner();
+                    let expected = u256::from_inner([limbs[0], limbs[1], limbs[2], limbs[3]]);
+                    assert_eq!(reduction.reduce(prod), expected, "{a} * {b} mod {order}");
+                }
+            }
+        }
+    }
+}
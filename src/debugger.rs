@@ -0,0 +1,1147 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Step debugger for zk-AluVM programs.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::{Bytecode, BytecodeRead, ExecStep, Instruction};
+use aluvm::regs::Status;
+use aluvm::{CoreConfig, CoreExt, Lib, LibId, LibSite, Marshaller, Site, Vm};
+use amplify::num::u256;
+
+use crate::gfa::{fail_ck, Instr, InstrClass, IsaGroups, NO_CONTEXT};
+use crate::{fe256, ComplexityUsage, CurveParams, ExecStats, FailurePolicy, GfaConfig, RegE, SolinasPrime};
+
+/// Instruction at a specific site of a program.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct InstrSite {
+    /// Site of the instruction.
+    pub site: LibSite,
+    /// Decoded instruction, or `None` if the site doesn't point to a valid instruction of a known
+    /// library.
+    pub instr: Option<Instr<LibId>>,
+}
+
+/// Errors restoring a debugger from a [`VmSnapshot`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SnapshotError {
+    /// value {1} of register {0} is not a canonical element of the field.
+    NonCanonical(RegE, fe256),
+
+    /// call stack exceeds the maximal call stack size.
+    CallStackOverflow,
+
+    /// `CK` register is in a failed state, while the failure counter is zero.
+    InconsistentCk,
+}
+
+/// Complete state of the VM and of the program execution in a [`Debugger`].
+///
+/// Snapshots allow to checkpoint long executions, to move them between machines, or to reproduce
+/// a specific program state exactly (see [`Debugger::snapshot`] and [`Debugger::restore`]).
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct VmSnapshot {
+    /// Configuration of the VM core.
+    pub config: CoreConfig,
+    /// Order of the field used by the VM.
+    pub field_order: u256,
+    /// Maximum number of instructions the VM can execute, if any.
+    pub step_lim: Option<u64>,
+    /// Whether the VM runs field arithmetic in constant time.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constant_time: bool,
+    /// Special form of the field order, if the VM uses a reduction routine synthesized for it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub solinas: Option<SolinasPrime>,
+    /// Parameters of the elliptic curve used by the VM, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub curve: Option<CurveParams>,
+    /// Actions taken by the VM on failures of instructions, per instruction class.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub failure_policy: FailurePolicy,
+    /// Instruction groups supported by the VM.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub isa_groups: IsaGroups,
+    /// Number of instructions executed by the VM.
+    pub steps: u64,
+    /// Number of instructions from the GFA256 ISA extension executed by the VM.
+    pub gfa_steps: u64,
+    /// Value of the `CK` register.
+    #[cfg_attr(feature = "serde", serde(with = "status_serde"))]
+    pub ck: Status,
+    /// Value of the `CO` register.
+    #[cfg_attr(feature = "serde", serde(with = "status_serde"))]
+    pub co: Status,
+    /// Number of `CK` failures.
+    pub cf: u64,
+    /// Accumulated complexity.
+    pub ca: u64,
+    /// Call stack, starting from the outermost call.
+    pub call_stack: Vec<LibSite>,
+    /// Values of initialized `E` registers.
+    pub regs: BTreeMap<RegE, fe256>,
+    /// Site of the instruction to be executed next, or `None` if the program is not running.
+    pub site: Option<LibSite>,
+}
+
+/// Serialization of flag registers as booleans, which are `true` for [`Status::Ok`].
+#[cfg(feature = "serde")]
+mod status_serde {
+    use aluvm::regs::Status;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(status: &Status, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(status.is_ok())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Status, D::Error> {
+        bool::deserialize(deserializer).map(|ok| if ok { Status::Ok } else { Status::Fail })
+    }
+}
+
+/// Filter of the register values triggering a [`Watchpoint`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+pub enum WatchFilter {
+    /// Any write to the register, including clearing it.
+    #[display("any")]
+    Any,
+
+    /// Clearing the register.
+    #[display("cleared")]
+    Cleared,
+
+    /// Writing the provided value to the register.
+    #[display("== {0}")]
+    Eq(fe256),
+
+    /// Writing a value different from the provided one to the register (clearing the register
+    /// doesn't match the filter).
+    #[display("!= {0}")]
+    Ne(fe256),
+}
+
+impl WatchFilter {
+    /// Checks whether a register value after a write matches the filter.
+    pub fn matches(&self, val: Option<fe256>) -> bool {
+        match *self {
+            WatchFilter::Any => true,
+            WatchFilter::Cleared => val.is_none(),
+            WatchFilter::Eq(expected) => val == Some(expected),
+            WatchFilter::Ne(unexpected) => val.is_some() && val != Some(unexpected),
+        }
+    }
+}
+
+/// Action taken by the [`Debugger`] when a [`Watchpoint`] is triggered.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+#[display(lowercase)]
+pub enum WatchAction {
+    /// Pause the program execution, reporting [`StopReason::Watchpoint`].
+    Pause,
+
+    /// Record the write in the watch log (see [`Debugger::watch_log`]) and continue.
+    Record,
+}
+
+/// Watchpoint triggered by instructions writing to a register.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Watchpoint {
+    /// Watched register.
+    pub reg: RegE,
+    /// Filter of the written values.
+    pub filter: WatchFilter,
+    /// Action taken when the watchpoint is triggered.
+    pub action: WatchAction,
+}
+
+/// Register write which has triggered a [`Watchpoint`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WatchHit {
+    /// Site of the instruction which has written to the register.
+    pub site: LibSite,
+    /// The register written to.
+    pub reg: RegE,
+    /// Value of the register after the write, or `None` if it was cleared.
+    pub val: Option<fe256>,
+}
+
+impl Display for WatchHit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.val {
+            Some(val) => write!(f, "{} set to {val} at {}", self.reg, self.site),
+            None => write!(f, "{} cleared at {}", self.reg, self.site),
+        }
+    }
+}
+
+/// Reason for the [`Debugger`] to stop the program execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+pub enum StopReason {
+    /// A single instruction was executed.
+    #[display("step")]
+    Step,
+
+    /// The execution reached a breakpoint.
+    #[display("breakpoint at {0}")]
+    Breakpoint(LibSite),
+
+    /// A register write has triggered a watchpoint with [`WatchAction::Pause`].
+    #[display("watchpoint: {0}")]
+    Watchpoint(WatchHit),
+
+    /// The program has halted with the provided value of `CK`.
+    #[display("halted with CK {0}")]
+    Halted(Status),
+}
+
+/// Interactive debugger executing a program one instruction at a time.
+///
+/// The debugger follows the same execution rules as [`Vm::exec`], with the only exception: calls
+/// of libraries which were not provided to the debugger always fail `CK` and halt the program.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{LibSite, Vm};
+/// # use zkaluvm::gfa::Assembly;
+/// # use zkaluvm::{fe256, Debugger, RegE, StopReason};
+/// let lib = Assembly::parse(
+///     r#"
+///     routine MAIN:
+///         put     E1, 2
+///         mul     E1, E1
+///         mul     E1, E1
+/// "#,
+/// )
+/// .unwrap()
+/// .assemble()
+/// .unwrap();
+/// let lib_id = lib.lib_id();
+///
+/// let mut debugger = Debugger::new(Vm::new(), [lib]);
+/// debugger.start(LibSite::new(lib_id, 0));
+/// debugger.break_at_instr(lib_id, 3);
+/// assert_eq!(debugger.run(), StopReason::Breakpoint(LibSite::new(lib_id, 7)));
+/// assert_eq!(debugger.reg(RegE::E1), Some(fe256::from(4u8)));
+/// debugger.set_reg(RegE::E1, Some(fe256::from(3u8)));
+/// assert_eq!(debugger.run(), StopReason::Halted(aluvm::regs::Status::Ok));
+/// assert_eq!(debugger.reg(RegE::E1), Some(fe256::from(9u8)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Debugger {
+    vm: Vm<Instr<LibId>>,
+    libs: BTreeMap<LibId, Lib>,
+    breakpoints: BTreeSet<LibSite>,
+    watchpoints: Vec<Watchpoint>,
+    /// Register writes which have triggered watchpoints with [`WatchAction::Record`].
+    watch_log: Vec<WatchHit>,
+    /// Site of the instruction to be executed next; `None` when the program is not running.
+    site: Option<LibSite>,
+    /// Instruction which has failed `CK` first during the execution.
+    failure: Option<InstrSite>,
+    /// Instruction at which the program has halted.
+    halt: Option<InstrSite>,
+    /// Complexity accumulated by the VM while being debugged.
+    complexity: u64,
+    /// Statistics of the executed instructions per instruction class.
+    classes: BTreeMap<InstrClass, ExecStats>,
+    /// States before the most recently executed instructions, starting from the oldest one.
+    history: VecDeque<HistoryEntry>,
+    /// Maximal number of states kept in the history.
+    history_depth: usize,
+}
+
+/// State of the VM and the program execution before an instruction execution, used for reverse
+/// debugging.
+#[derive(Clone, Debug)]
+struct HistoryEntry {
+    vm: Vm<Instr<LibId>>,
+    site: LibSite,
+    /// The executed instruction, or `None` if it wasn't decoded.
+    instr: Option<Instr<LibId>>,
+    failure: Option<InstrSite>,
+    halt: Option<InstrSite>,
+    complexity: u64,
+    classes: BTreeMap<InstrClass, ExecStats>,
+    watch_log_len: usize,
+}
+
+impl Debugger {
+    /// Constructs debugger for the VM, which can execute code from the provided libraries.
+    pub fn new(vm: Vm<Instr<LibId>>, libs: impl IntoIterator<Item = Lib>) -> Self {
+        Self {
+            vm,
+            libs: libs.into_iter().map(|lib| (lib.lib_id(), lib)).collect(),
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            watch_log: Vec::new(),
+            site: None,
+            failure: None,
+            halt: None,
+            complexity: 0,
+            classes: BTreeMap::new(),
+            history: VecDeque::new(),
+            history_depth: 0,
+        }
+    }
+
+    /// Returns the debugged VM.
+    pub fn vm(&self) -> &Vm<Instr<LibId>> { &self.vm }
+
+    /// Releases the debugged VM.
+    pub fn into_vm(self) -> Vm<Instr<LibId>> { self.vm }
+
+    /// Starts the program at the entry point, without executing any of its instructions.
+    ///
+    /// The VM registers are not reset, so they can be initialized with the program inputs before
+    /// or after calling this method.
+    ///
+    /// The history of the previously executed instructions is cleared.
+    pub fn start(&mut self, entry_point: LibSite) {
+        self.site = Some(entry_point);
+        self.failure = None;
+        self.halt = None;
+        self.history.clear();
+    }
+
+    /// Returns the site of the instruction to be executed next, or `None` if the program is not
+    /// running.
+    pub fn site(&self) -> Option<LibSite> { self.site }
+
+    /// Returns the instruction which has set `CK` to a failed state since the program start.
+    ///
+    /// If `CK` was failed several times (with the VM configured not to halt on failures), the
+    /// first failure is reported. Failures not caused by an instruction, like calls of unknown
+    /// libraries, report the site of the instruction which was about to be executed.
+    pub fn failure(&self) -> Option<InstrSite> { self.failure }
+
+    /// Returns the instruction at which the program has halted, or `None` if it is still running.
+    pub fn halt_site(&self) -> Option<InstrSite> { self.halt }
+
+    /// Returns the instruction to be executed next, or `None` if the program is not running or the
+    /// instruction can't be decoded.
+    pub fn instr(&self) -> Option<Instr<LibId>> {
+        let site = self.site?;
+        let lib = self.libs.get(&site.lib_id)?;
+        let mut marshaller = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+        marshaller.seek(site.offset).ok()?;
+        Instr::decode_instr(&mut marshaller).ok()
+    }
+
+    /// Adds a breakpoint at the site, returning whether it was not set before.
+    pub fn break_at(&mut self, site: LibSite) -> bool { self.breakpoints.insert(site) }
+
+    /// Adds a breakpoint at the instruction with the provided index in the library code.
+    ///
+    /// Returns the site of the breakpoint, or `None` if the library is not known to the debugger
+    /// or has less instructions.
+    pub fn break_at_instr(&mut self, lib_id: LibId, no: usize) -> Option<LibSite> {
+        let lib = self.libs.get(&lib_id)?;
+        let mut marshaller = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+        let mut offsets = Vec::new();
+        while !marshaller.is_eof() && offsets.len() <= no {
+            offsets.push(marshaller.pos());
+            Instr::<LibId>::decode_instr(&mut marshaller).ok()?;
+        }
+        let site = LibSite::new(lib_id, *offsets.get(no)?);
+        self.breakpoints.insert(site);
+        Some(site)
+    }
+
+    /// Removes a breakpoint, returning whether it was set.
+    pub fn remove_breakpoint(&mut self, site: LibSite) -> bool { self.breakpoints.remove(&site) }
+
+    /// Removes all breakpoints.
+    pub fn clear_breakpoints(&mut self) { self.breakpoints.clear() }
+
+    /// Returns all set breakpoints.
+    pub fn breakpoints(&self) -> impl Iterator<Item = LibSite> + '_ { self.breakpoints.iter().copied() }
+
+    /// Adds a watchpoint, triggered after each instruction writing a value matching the filter
+    /// to the register.
+    ///
+    /// Writes are detected from the destination registers of the executed instructions, so a
+    /// write is reported even if it leaves the register value unchanged.
+    pub fn watch(&mut self, reg: RegE, filter: WatchFilter, action: WatchAction) {
+        self.watchpoints.push(Watchpoint { reg, filter, action });
+    }
+
+    /// Removes all watchpoints on a register, returning whether there were any.
+    pub fn unwatch(&mut self, reg: RegE) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|wp| wp.reg != reg);
+        self.watchpoints.len() != len
+    }
+
+    /// Removes all watchpoints.
+    pub fn clear_watchpoints(&mut self) { self.watchpoints.clear() }
+
+    /// Returns all set watchpoints.
+    pub fn watchpoints(&self) -> &[Watchpoint] { &self.watchpoints }
+
+    /// Returns the register writes recorded by watchpoints with [`WatchAction::Record`], in the
+    /// order of their execution.
+    pub fn watch_log(&self) -> &[WatchHit] { &self.watch_log }
+
+    /// Clears the watch log.
+    pub fn clear_watch_log(&mut self) { self.watch_log.clear() }
+
+    /// Sets the number of the most recently executed instructions which can be stepped back
+    /// with [`Debugger::step_back`].
+    ///
+    /// The history is disabled by default (has zero depth), since each recorded step keeps a copy
+    /// of the VM state. Reducing the depth drops the oldest steps from the history.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history_depth = depth;
+        while self.history.len() > depth {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the maximal number of steps kept in the history.
+    pub fn history_depth(&self) -> usize { self.history_depth }
+
+    /// Returns the number of steps which can be currently stepped back.
+    pub fn history_len(&self) -> usize { self.history.len() }
+
+    /// Clears the history of the executed instructions.
+    pub fn clear_history(&mut self) { self.history.clear() }
+
+    /// Returns the sites of the instructions in the history, starting from the most recently
+    /// executed one.
+    pub fn history_sites(&self) -> impl Iterator<Item = LibSite> + '_ {
+        self.history.iter().rev().map(|entry| entry.site)
+    }
+
+    /// Reverts the last executed instruction, restoring the VM registers, the program site,
+    /// failure and halt sites, complexity usage and the watch log to the state before its
+    /// execution.
+    ///
+    /// Returns `false` if the history is empty. Register values modified with
+    /// [`Debugger::set_reg`] or flags modified with [`Debugger::set_co`] and [`Debugger::set_ck`]
+    /// since the instruction execution are reverted as well.
+    pub fn step_back(&mut self) -> bool {
+        let Some(entry) = self.history.pop_back() else {
+            return false;
+        };
+        self.vm = entry.vm;
+        self.site = Some(entry.site);
+        self.failure = entry.failure;
+        self.halt = entry.halt;
+        self.complexity = entry.complexity;
+        self.classes = entry.classes;
+        self.watch_log.truncate(entry.watch_log_len);
+        true
+    }
+
+    /// Steps back up to `steps` instructions, returning the number of instructions which were
+    /// reverted.
+    pub fn rewind(&mut self, steps: usize) -> usize { (0..steps).take_while(|_| self.step_back()).count() }
+
+    /// Returns the site of the instruction in the history which has most recently written to
+    /// the register, answering where the current register value comes from.
+    ///
+    /// Returns `None` if no instruction in the history has written to the register.
+    pub fn last_write(&self, reg: RegE) -> Option<LibSite> {
+        self.history
+            .iter()
+            .rev()
+            .find(|entry| {
+                entry
+                    .instr
+                    .is_some_and(|instr| instr.dst_regs().contains(&reg))
+            })
+            .map(|entry| entry.site)
+    }
+
+    /// Returns the value of a register.
+    pub fn reg(&self, reg: RegE) -> Option<fe256> { self.vm.core.cx.get(reg) }
+
+    /// Sets the value of a register, or clears it if the value is `None`.
+    ///
+    /// # Panics
+    ///
+    /// If the value is not a canonical element of the field used by the VM.
+    pub fn set_reg(&mut self, reg: RegE, val: Option<fe256>) { self.vm.core.cx.put(reg, val) }
+
+    /// Returns the value of the `CO` register.
+    pub fn co(&self) -> Status { self.vm.core.co() }
+
+    /// Overrides the value of the `CO` register.
+    pub fn set_co(&mut self, co: Status) { self.vm.core.set_co(co) }
+
+    /// Returns the value of the `CK` register.
+    pub fn ck(&self) -> Status { self.vm.core.ck() }
+
+    /// Overrides the value of the `CK` register.
+    ///
+    /// Setting `CK` to a failed state increments the failure counter, but never halts the program.
+    pub fn set_ck(&mut self, ck: Status) {
+        match ck {
+            Status::Ok => self.vm.core.reset_ck(),
+            Status::Fail => {
+                let site = self
+                    .site
+                    .map_or_else(|| Site::new(LibId::default(), 0), exec_site);
+                fail_ck(&mut self.vm.core, site);
+            }
+        }
+    }
+
+    /// Reports the complexity used by the program execution.
+    ///
+    /// The total complexity is accumulated by the VM over all the programs executed with the
+    /// debugger (including the complexity restored from a snapshot), while the breakdown per
+    /// instruction class covers only the instructions executed by the debugger itself.
+    pub fn usage(&self) -> ComplexityUsage {
+        ComplexityUsage {
+            total: self.complexity,
+            limit: self.vm.core.cl(),
+            classes: self.classes.clone(),
+        }
+    }
+
+    /// Captures the complete state of the VM and the program execution.
+    ///
+    /// The accumulated complexity is taken as the complexity of instructions executed by the
+    /// debugger, so the VM must not execute code outside of the debugger for the snapshot to be
+    /// exact.
+    pub fn snapshot(&self) -> VmSnapshot {
+        let core = &self.vm.core;
+        // The core doesn't provide read access to `CH` and the call stack, so they are probed on
+        // a copy of it.
+        let mut probe = core.clone();
+        let halt = fail_ck(&mut probe, Site::new(LibId::default(), 0));
+        let mut call_stack = Vec::with_capacity(core.cp() as usize);
+        while let Some(site) = probe.pop_cs() {
+            call_stack.push(site.into());
+        }
+        call_stack.reverse();
+
+        VmSnapshot {
+            config: CoreConfig {
+                halt,
+                complexity_lim: core.cl(),
+            },
+            field_order: core.cx.fq(),
+            step_lim: core.cx.step_lim(),
+            constant_time: core.cx.is_constant_time(),
+            solinas: core.cx.solinas(),
+            curve: core.cx.curve(),
+            failure_policy: core.cx.failure_policy(),
+            isa_groups: core.cx.isa_groups(),
+            steps: core.cx.steps(),
+            gfa_steps: core.cx.gfa_steps(),
+            ck: core.ck(),
+            co: core.co(),
+            cf: core.cf(),
+            ca: self.complexity,
+            call_stack,
+            regs: RegE::ALL
+                .into_iter()
+                .filter_map(|reg| core.cx.get(reg).map(|val| (reg, val)))
+                .collect(),
+            site: self.site,
+        }
+    }
+
+    /// Restores the state of the VM and the program execution from a snapshot.
+    ///
+    /// Breakpoints, watchpoints, libraries and failure sites are left unchanged, while the history
+    /// of the executed instructions is cleared. Restoring takes
+    /// time proportional to the number of `CK` failures in the snapshot.
+    pub fn restore(&mut self, snapshot: &VmSnapshot) -> Result<(), SnapshotError> {
+        let mut vm = Vm::<Instr<LibId>>::with(snapshot.config, GfaConfig {
+            field_order: snapshot.field_order,
+            step_lim: snapshot.step_lim,
+            constant_time: snapshot.constant_time,
+            solinas: snapshot.solinas,
+            curve: snapshot.curve,
+            failure_policy: snapshot.failure_policy,
+            isa_groups: snapshot.isa_groups,
+        });
+        let core = &mut vm.core;
+        for (reg, val) in &snapshot.regs {
+            if val.to_u256() >= snapshot.field_order {
+                return Err(SnapshotError::NonCanonical(*reg, *val));
+            }
+            core.cx.put(*reg, Some(*val));
+        }
+        for site in &snapshot.call_stack {
+            core.push_cs(exec_site(*site))
+                .ok_or(SnapshotError::CallStackOverflow)?;
+        }
+        core.set_co(snapshot.co);
+        core.acc_complexity(snapshot.ca);
+        core.cx.set_steps(snapshot.steps, snapshot.gfa_steps);
+        for _ in 0..snapshot.cf {
+            fail_ck(core, Site::new(LibId::default(), 0));
+        }
+        if snapshot.ck.is_ok() {
+            core.reset_ck();
+        } else if snapshot.cf == 0 {
+            return Err(SnapshotError::InconsistentCk);
+        }
+
+        self.vm = vm;
+        self.complexity = snapshot.ca;
+        self.site = snapshot.site;
+        self.history.clear();
+        Ok(())
+    }
+
+    /// Executes a single instruction.
+    ///
+    /// Reports [`StopReason::Watchpoint`] instead of [`StopReason::Step`] if the instruction has
+    /// triggered a pausing watchpoint.
+    pub fn step(&mut self) -> StopReason {
+        match self.exec_next(&mut |_, _| {}) {
+            Err(ck) => StopReason::Halted(ck),
+            Ok(Some(hit)) => StopReason::Watchpoint(hit),
+            Ok(None) => StopReason::Step,
+        }
+    }
+
+    /// Executes the program until it reaches a breakpoint, triggers a pausing watchpoint or halts.
+    ///
+    /// At least one instruction is executed, so a breakpoint at the current site is not triggered.
+    pub fn run(&mut self) -> StopReason {
+        loop {
+            match self.exec_next(&mut |_, _| {}) {
+                Err(ck) => return StopReason::Halted(ck),
+                Ok(Some(hit)) => return StopReason::Watchpoint(hit),
+                Ok(None) => {}
+            }
+            match self.site {
+                Some(site) if self.breakpoints.contains(&site) => return StopReason::Breakpoint(site),
+                _ => {}
+            }
+        }
+    }
+
+    /// Executes the program until it halts, ignoring breakpoints and pausing watchpoints, and
+    /// reporting each instruction before its execution to the `observer`.
+    pub(crate) fn run_observed(&mut self, mut observer: impl FnMut(LibSite, &Instr<LibId>)) -> Status {
+        loop {
+            if let Err(ck) = self.exec_next(&mut observer) {
+                return ck;
+            }
+        }
+    }
+
+    /// Executes the next instruction, returning the value of `CK` as an error if the program has
+    /// halted, or the first register write triggering a pausing watchpoint, if any.
+    fn exec_next(&mut self, observer: &mut impl FnMut(LibSite, &Instr<LibId>)) -> Result<Option<WatchHit>, Status> {
+        let Some(site) = self.site else {
+            return Err(self.vm.core.ck());
+        };
+        if self.history_depth > 0 {
+            if self.history.len() == self.history_depth {
+                self.history.pop_front();
+            }
+            self.history.push_back(HistoryEntry {
+                vm: self.vm.clone(),
+                site,
+                instr: None,
+                failure: self.failure,
+                halt: self.halt,
+                complexity: self.complexity,
+                classes: self.classes.clone(),
+                watch_log_len: self.watch_log.len(),
+            });
+        }
+        let ck = self.vm.core.ck();
+        let (instr, next) = self.exec_instr(site, observer);
+        if let Some(entry) = self.history.back_mut() {
+            entry.instr = instr;
+        }
+        if next == Err(Halt::Fail) {
+            fail_ck(&mut self.vm.core, exec_site(site));
+        }
+        if ck.is_ok() && !self.vm.core.ck().is_ok() && self.failure.is_none() {
+            self.failure = Some(InstrSite { site, instr });
+        }
+        let hit = instr.and_then(|instr| self.check_watchpoints(site, &instr));
+        match next {
+            Ok(next) => {
+                self.site = Some(next);
+                Ok(hit)
+            }
+            Err(_) => {
+                self.site = None;
+                self.halt = Some(InstrSite { site, instr });
+                Err(self.vm.core.ck())
+            }
+        }
+    }
+
+    /// Checks watchpoints against the registers written by an executed instruction, recording the
+    /// writes in the watch log and returning the first write triggering a pausing watchpoint.
+    fn check_watchpoints(&mut self, site: LibSite, instr: &Instr<LibId>) -> Option<WatchHit> {
+        if self.watchpoints.is_empty() {
+            return None;
+        }
+        let mut pause = None;
+        for reg in instr.dst_regs() {
+            let hit = WatchHit {
+                site,
+                reg,
+                val: self.vm.core.cx.get(reg),
+            };
+            let mut recorded = false;
+            for wp in &self.watchpoints {
+                if wp.reg != reg || !wp.filter.matches(hit.val) {
+                    continue;
+                }
+                match wp.action {
+                    WatchAction::Pause => pause = pause.or(Some(hit)),
+                    WatchAction::Record if !recorded => {
+                        self.watch_log.push(hit);
+                        recorded = true;
+                    }
+                    WatchAction::Record => {}
+                }
+            }
+        }
+        pause
+    }
+
+    /// Decodes and executes the instruction at the site, returning it together with the site of
+    /// the next instruction to execute.
+    fn exec_instr(
+        &mut self,
+        site: LibSite,
+        observer: &mut impl FnMut(LibSite, &Instr<LibId>),
+    ) -> (Option<Instr<LibId>>, Result<LibSite, Halt>) {
+        let Some(lib) = self.libs.get(&site.lib_id) else {
+            return (None, Err(Halt::Fail));
+        };
+        let mut marshaller = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+        if marshaller.seek(site.offset).is_err() {
+            return (None, Err(Halt::Fail));
+        }
+        let Ok(instr) = Instr::<LibId>::decode_instr(&mut marshaller) else {
+            return (None, Err(Halt::Stop));
+        };
+        let next = marshaller.pos();
+        let code_len = lib.code.len();
+        observer(site, &instr);
+
+        let core = &mut self.vm.core;
+        let step = instr.exec(exec_site(site), core, &NO_CONTEXT);
+        self.complexity = self.complexity.saturating_add(instr.complexity());
+        self.classes
+            .entry(instr.class())
+            .or_default()
+            .add(ExecStats {
+                count: 1,
+                complexity: instr.complexity(),
+            });
+        if !core.acc_complexity(instr.complexity()) {
+            return (Some(instr), Err(Halt::Fail));
+        }
+        let next = match step {
+            ExecStep::Stop => return (Some(instr), Err(Halt::Stop)),
+            ExecStep::Fail => {
+                if fail_ck(core, exec_site(site)) {
+                    return (Some(instr), Err(Halt::Stop));
+                }
+                LibSite::new(site.lib_id, next)
+            }
+            ExecStep::Next => LibSite::new(site.lib_id, next),
+            ExecStep::Jump(pos) if pos as usize >= code_len => return (Some(instr), Err(Halt::Fail)),
+            ExecStep::Jump(pos) => LibSite::new(site.lib_id, pos),
+            ExecStep::Call(target) => target.into(),
+            ExecStep::Ret(caller) => {
+                // Execution continues after the calling instruction.
+                let Some(lib) = self.libs.get(&caller.prog_id) else {
+                    return (Some(instr), Err(Halt::Fail));
+                };
+                let mut marshaller = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+                if marshaller.seek(caller.offset).is_err() {
+                    return (Some(instr), Err(Halt::Fail));
+                }
+                if Instr::<LibId>::decode_instr(&mut marshaller).is_err() {
+                    return (Some(instr), Err(Halt::Stop));
+                }
+                LibSite::new(caller.prog_id, marshaller.pos())
+            }
+        };
+        // The program halts when it reaches the end of the code.
+        if next.lib_id == site.lib_id && next.offset as usize >= code_len {
+            return (Some(instr), Err(Halt::Stop));
+        }
+        (Some(instr), Ok(next))
+    }
+}
+
+/// Way the program execution halts.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Halt {
+    /// Halt leaving `CK` unchanged.
+    Stop,
+    /// Set `CK` to a failed state and halt.
+    Fail,
+}
+
+fn exec_site(site: LibSite) -> Site<LibId> { Site::new(site.lib_id, site.offset) }
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::{format, vec};
+
+    use aluvm::isa::CtrlInstr;
+    use aluvm::CoreConfig;
+
+    use super::*;
+    use crate::gfa::{Assembly, FieldInstr};
+    use crate::RegE::*;
+
+    const SQUARES: &str = r#"
+        routine MAIN:
+            put     E1, 3
+            call    SQUARE
+            call    SQUARE
+            stop
+        routine SQUARE:
+            mul     E1, E1
+            ret
+    "#;
+
+    fn lib(source: &str) -> Lib { Assembly::parse(source).unwrap().assemble().unwrap() }
+
+    #[test]
+    fn same_as_vm() {
+        let lib = lib(SQUARES);
+        let entry = LibSite::new(lib.lib_id(), 0);
+        let mut vm = Vm::<Instr<LibId>>::new();
+        vm.exec(entry, &NO_CONTEXT, |_| Some(&lib));
+
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.start(entry);
+        let mut steps = 1;
+        while debugger.step() == StopReason::Step {
+            steps += 1;
+        }
+        assert_eq!(steps, 11);
+        assert_eq!(debugger.site(), None);
+        assert_eq!(debugger.reg(E1), Some(fe256::from(81u8)));
+        assert_eq!(format!("{:?}", debugger.into_vm().core), format!("{:?}", vm.core));
+    }
+
+    #[test]
+    fn breakpoints() {
+        let lib = lib(SQUARES);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.start(LibSite::new(lib_id, 0));
+        let site = debugger.break_at_instr(lib_id, 6).unwrap();
+        assert_eq!(debugger.break_at_instr(lib_id, 8), None);
+        assert_eq!(debugger.breakpoints().collect::<Vec<_>>(), vec![site]);
+
+        assert_eq!(debugger.run(), StopReason::Breakpoint(site));
+        assert_eq!(debugger.instr(), Some(Instr::Gfa(FieldInstr::Mul { dst_src: E1, src: E1 })));
+        assert_eq!(debugger.reg(E1), Some(fe256::from(3u8)));
+        assert_eq!(debugger.run(), StopReason::Breakpoint(site));
+        assert_eq!(debugger.reg(E1), Some(fe256::from(9u8)));
+        debugger.set_reg(E1, Some(fe256::from(2u8)));
+        assert!(debugger.remove_breakpoint(site));
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(debugger.reg(E1), Some(fe256::from(4u8)));
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+    }
+
+    #[test]
+    fn flags() {
+        let lib = lib(r#"
+            routine MAIN:
+                put     E1, 1
+                put     E2, 2
+                eq      E1, E2
+                chk     CO
+                put     E3, 3
+        "#);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        let chk = debugger.break_at_instr(lib_id, 4).unwrap();
+
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(debugger.run(), StopReason::Breakpoint(chk));
+        assert_eq!(debugger.co(), Status::Fail);
+        assert_eq!(debugger.failure(), None);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Fail));
+        assert_eq!(debugger.reg(E3), None);
+        let failure = InstrSite {
+            site: chk,
+            instr: Some(Instr::Ctrl(CtrlInstr::ChkCo)),
+        };
+        assert_eq!(debugger.failure(), Some(failure));
+        assert_eq!(debugger.halt_site(), Some(failure));
+
+        debugger.set_ck(Status::Ok);
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(debugger.run(), StopReason::Breakpoint(chk));
+        debugger.set_co(Status::Ok);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(debugger.reg(E3), Some(fe256::from(3u8)));
+        assert_eq!(debugger.failure(), None);
+        assert_eq!(
+            debugger.halt_site().unwrap().instr,
+            Some(Instr::Gfa(FieldInstr::PutD {
+                dst: E3,
+                data: fe256::from(3u8)
+            }))
+        );
+
+        debugger.set_ck(Status::Fail);
+        assert_eq!(debugger.ck(), Status::Fail);
+    }
+
+    #[test]
+    fn unknown_lib() {
+        let mut debugger = Debugger::new(Vm::new(), []);
+        debugger.start(LibSite::new(LibId::default(), 0));
+        assert_eq!(debugger.instr(), None);
+        assert_eq!(debugger.step(), StopReason::Halted(Status::Fail));
+        assert_eq!(debugger.site(), None);
+        assert_eq!(
+            debugger.failure(),
+            Some(InstrSite {
+                site: LibSite::new(LibId::default(), 0),
+                instr: None
+            })
+        );
+    }
+
+    #[test]
+    fn first_failure() {
+        let lib = lib(r#"
+            routine MAIN:
+                put     E1, 1
+                add     E1, E2
+                mul     E1, E3
+                put     E4, 1
+        "#);
+        let lib_id = lib.lib_id();
+        let config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(config, default!()), [lib]);
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Fail));
+        assert_eq!(
+            debugger.failure(),
+            Some(InstrSite {
+                site: LibSite::new(lib_id, 3),
+                instr: Some(Instr::Gfa(FieldInstr::Add { dst_src: E1, src: E2 }))
+            })
+        );
+        assert_eq!(debugger.halt_site().unwrap().site, LibSite::new(lib_id, 7));
+    }
+
+    #[test]
+    fn usage() {
+        let lib = lib(SQUARES);
+        let entry = LibSite::new(lib.lib_id(), 0);
+        let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+        debugger.start(entry);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        let usage = debugger.usage();
+        assert_eq!(usage.limit, None);
+        assert_eq!(usage.remaining(), None);
+        assert_eq!(usage.classes[&InstrClass::Control].count, 8);
+        assert_eq!(usage.classes[&InstrClass::Move].count, 1);
+        assert_eq!(usage.classes[&InstrClass::Arithmetic].count, 2);
+        assert_eq!(
+            usage
+                .classes
+                .values()
+                .map(|stats| stats.complexity)
+                .sum::<u64>(),
+            usage.total
+        );
+        let total = usage.total;
+
+        let config = CoreConfig {
+            halt: true,
+            complexity_lim: Some(total + 5),
+        };
+        let mut debugger = Debugger::new(Vm::with(config, default!()), [lib.clone()]);
+        debugger.start(entry);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(debugger.usage().remaining(), Some(5));
+        assert!(!debugger.usage().is_exceeded());
+
+        let config = CoreConfig {
+            halt: true,
+            complexity_lim: Some(total - 1),
+        };
+        let mut debugger = Debugger::new(Vm::with(config, default!()), [lib]);
+        debugger.start(entry);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Fail));
+        assert_eq!(debugger.usage().remaining(), Some(0));
+        assert!(debugger.usage().is_exceeded());
+    }
+
+    #[test]
+    fn watchpoints() {
+        let lib = lib(r#"
+            routine MAIN:
+                put     E1, 3
+                mul     E1, E1
+                put     E2, 1
+                clr     E1
+                put     E1, 5
+        "#);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.watch(E1, WatchFilter::Eq(fe256::from(9u8)), WatchAction::Pause);
+        debugger.watch(E1, WatchFilter::Any, WatchAction::Record);
+        debugger.watch(E1, WatchFilter::Cleared, WatchAction::Record);
+        assert_eq!(debugger.watchpoints().len(), 3);
+
+        debugger.start(LibSite::new(lib_id, 0));
+        let hit = WatchHit {
+            site: LibSite::new(lib_id, 5),
+            reg: E1,
+            val: Some(fe256::from(9u8)),
+        };
+        assert_eq!(debugger.run(), StopReason::Watchpoint(hit));
+        assert_eq!(format!("{hit}"), format!("E1 set to {} at {}", fe256::from(9u8), hit.site));
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        let log = debugger
+            .watch_log()
+            .iter()
+            .map(|hit| hit.val)
+            .collect::<Vec<_>>();
+        assert_eq!(log, vec![Some(fe256::from(3u8)), Some(fe256::from(9u8)), None, Some(fe256::from(5u8))]);
+
+        debugger.clear_watch_log();
+        assert!(debugger.unwatch(E1));
+        assert!(!debugger.unwatch(E1));
+        debugger.watch(E1, WatchFilter::Ne(fe256::from(3u8)), WatchAction::Pause);
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.step(), StopReason::Watchpoint(hit));
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.step(), StopReason::Halted(Status::Ok));
+        assert!(debugger.watch_log().is_empty());
+        debugger.clear_watchpoints();
+        assert!(debugger.watchpoints().is_empty());
+    }
+
+    #[test]
+    fn reverse() {
+        let lib = lib(SQUARES);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.watch(E1, WatchFilter::Any, WatchAction::Record);
+        debugger.start(LibSite::new(lib_id, 0));
+        assert!(!debugger.step_back());
+        debugger.set_history_depth(4);
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(debugger.history_len(), 4);
+        assert_eq!(debugger.reg(E1), Some(fe256::from(81u8)));
+        let mul = debugger.last_write(E1).unwrap();
+        assert_eq!(debugger.history_sites().nth(2), Some(mul));
+        assert_eq!(debugger.last_write(E2), None);
+
+        assert_eq!(debugger.rewind(3), 3);
+        assert_eq!(debugger.site(), Some(mul));
+        assert_eq!(debugger.halt_site(), None);
+        assert_eq!(debugger.reg(E1), Some(fe256::from(9u8)));
+        assert_eq!(debugger.watch_log().len(), 2);
+        assert_eq!(debugger.instr(), Some(Instr::Gfa(FieldInstr::Mul { dst_src: E1, src: E1 })));
+
+        let usage = debugger.usage();
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(debugger.reg(E1), Some(fe256::from(81u8)));
+        assert_eq!(debugger.watch_log().len(), 3);
+        assert!(debugger.usage().total > usage.total);
+
+        assert_eq!(debugger.rewind(10), 4);
+        assert!(!debugger.step_back());
+        debugger.set_history_depth(0);
+        debugger.start(LibSite::new(lib_id, 0));
+        debugger.step();
+        assert_eq!(debugger.history_len(), 0);
+    }
+
+    #[test]
+    fn snapshot() {
+        let lib = lib(SQUARES);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+        debugger.start(LibSite::new(lib_id, 0));
+        let site = debugger.break_at_instr(lib_id, 6).unwrap();
+        assert_eq!(debugger.run(), StopReason::Breakpoint(site));
+
+        let snapshot = debugger.snapshot();
+        assert_eq!(snapshot.call_stack, vec![LibSite::new(lib_id, 5)]);
+        assert_eq!(snapshot.site, Some(site));
+        assert_eq!(snapshot.regs, bmap! { E1 => fe256::from(3u8) });
+        assert!(snapshot.config.halt);
+
+        let mut restored = Debugger::new(Vm::new(), [lib]);
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        debugger.clear_breakpoints();
+        assert_eq!(debugger.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(restored.run(), StopReason::Halted(Status::Ok));
+        assert_eq!(format!("{:?}", restored.into_vm().core), format!("{:?}", debugger.into_vm().core));
+
+        let mut invalid = snapshot.clone();
+        invalid.regs.insert(E2, fe256::from(u256::MAX));
+        let mut restored = Debugger::new(Vm::new(), []);
+        assert_eq!(restored.restore(&invalid), Err(SnapshotError::NonCanonical(E2, fe256::from(u256::MAX))));
+        let mut invalid = snapshot;
+        invalid.ck = Status::Fail;
+        assert_eq!(restored.restore(&invalid), Err(SnapshotError::InconsistentCk));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_serde() {
+        let lib = lib(SQUARES);
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.start(LibSite::new(lib_id, 0));
+        debugger.break_at_instr(lib_id, 6).unwrap();
+        debugger.run();
+        debugger.set_ck(Status::Fail);
+
+        let snapshot = debugger.snapshot();
+        let data = bincode::serialize(&snapshot).unwrap();
+        assert_eq!(bincode::deserialize::<VmSnapshot>(&data).unwrap(), snapshot);
+    }
+}
@@ -20,6 +20,9 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
 use core::str::FromStr;
 
 use amplify::confinement::TinyBlob;
@@ -33,8 +36,11 @@ use crate::LIB_NAME_FINITE_FIELD;
 /// Element of a Galois finite field.
 ///
 /// Maximum size is 256 bits.
+///
+/// Elements are ordered by their canonical integer representation. The ordering is not a part of
+/// the field arithmetic, and is provided for the use of the elements in sorted collections.
 #[allow(non_camel_case_types)]
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, From)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Display, From)]
 #[display("{0:X}.fe", alt = "{0:064X}.fe")]
 #[derive(StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_FINITE_FIELD)]
@@ -55,6 +61,16 @@ impl fe256 {
     pub const fn to_u256(&self) -> u256 { self.0 }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for fe256 {
+    #[allow(unsafe_code)]
+    fn zeroize(&mut self) {
+        // SAFETY: `fe256` is a plain array of integer limbs without padding, for which the all-zero
+        // bit pattern is a valid value.
+        unsafe { zeroize::zeroize_flat_type(self) }
+    }
+}
+
 impl From<Bytes32> for fe256 {
     fn from(bytes: Bytes32) -> Self { Self::from(bytes.into_inner()) }
 }
@@ -122,6 +138,43 @@ pub enum ParseFeError {
     #[from]
     #[display(inner)]
     Value(hex::Error),
+
+    /// Invalid literal value.
+    #[display("invalid field element literal `{0}`.")]
+    Literal(String),
+}
+
+impl fe256 {
+    /// Parses field element from a literal, which may be either a decimal number, a hexadecimal
+    /// number prefixed with `0x`, or a hexadecimal value with `.fe` suffix. Numbers may contain `_`
+    /// separators.
+    pub fn parse_literal(s: &str) -> Result<Self, ParseFeError> {
+        if s.ends_with(".fe") {
+            return Self::from_str(s);
+        }
+        parse_u256(s)
+            .map(Self)
+            .ok_or_else(|| ParseFeError::Literal(s.to_owned()))
+    }
+}
+
+/// Parses decimal or `0x`-prefixed hexadecimal number, which may contain `_` separators.
+pub(crate) fn parse_u256(s: &str) -> Option<u256> {
+    let (digits, radix) = match s.strip_prefix("0x") {
+        Some(hex) => (hex, 16u8),
+        None => (s, 10u8),
+    };
+    if digits.is_empty() || digits.starts_with('_') {
+        return None;
+    }
+    let mut val = u256::ZERO;
+    for c in digits.chars().filter(|c| *c != '_') {
+        let digit = c.to_digit(radix as u32)?;
+        val = val
+            .checked_mul(u256::from(radix))?
+            .checked_add(u256::from(digit as u8))?;
+    }
+    Some(val)
 }
 
 impl FromStr for fe256 {
@@ -152,6 +205,15 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn parse_literal() {
+        assert_eq!(fe256::parse_literal("1_000").unwrap(), fe256::from(1000u16));
+        assert_eq!(fe256::parse_literal("0xFF").unwrap(), fe256::from(255u8));
+        assert_eq!(fe256::parse_literal("1F.fe").unwrap(), fe256::from(31u8));
+        assert_eq!(fe256::parse_literal("0x").unwrap_err(), ParseFeError::Literal(s!("0x")));
+        assert_eq!(fe256::parse_literal("-1").unwrap_err(), ParseFeError::Literal(s!("-1")));
+    }
+
     #[test]
     fn display_from_str() {
         let s = "0000000000000000000000000000000000000000000000000000000000000000.fe";
@@ -208,6 +270,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn serde() {
         use serde_test::{assert_tokens, Configure, Token};
 
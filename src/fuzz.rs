@@ -0,0 +1,309 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Fuzzing support: [`Arbitrary`] implementations for the instruction set types and entry points
+//! for `cargo-fuzz` targets.
+//!
+//! The generated instructions are always encodable: external sites refer to one of four fixed
+//! library ids, so arbitrary programs don't overflow the library segment. Jump and call targets
+//! are arbitrary and may not point to instruction boundaries.
+
+use alloc::vec::Vec;
+
+use aluvm::isa::{Bytecode, CtrlInstr};
+use aluvm::regs::Status;
+use aluvm::{CoreConfig, Lib, LibId, LibsSeg, Marshaller, Site, Vm};
+use amplify::num::{u2, u3};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::gfa::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{exec_raw, fe256, GfaConfig, RegE};
+
+/// Maximum number of instructions executed by [`fuzz_exec`], bounding programs with loops.
+pub const FUZZ_STEP_LIM: u64 = 0x10000;
+
+impl<'a> Arbitrary<'a> for RegE {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> { u.choose(&RegE::ALL).copied() }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) { (1, Some(1)) }
+}
+
+impl<'a> Arbitrary<'a> for ConstVal {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> { Ok(ConstVal::from(u2::with(u.int_in_range(0..=3)?))) }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) { (1, Some(1)) }
+}
+
+impl<'a> Arbitrary<'a> for Bits {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> { Ok(Bits::from(u3::with(u.int_in_range(0..=7)?))) }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) { (1, Some(1)) }
+}
+
+impl<'a> Arbitrary<'a> for fe256 {
+    /// Generates an arbitrary 256-bit value, which may exceed the field order.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> { <[u8; 32]>::arbitrary(u).map(fe256::from) }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) { (32, Some(32)) }
+}
+
+impl<'a> Arbitrary<'a> for FieldInstr {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=19)? {
+            0 => FieldInstr::Test { src: u.arbitrary()? },
+            1 => FieldInstr::Clr { dst: u.arbitrary()? },
+            2 => FieldInstr::PutD {
+                dst: u.arbitrary()?,
+                data: u.arbitrary()?,
+            },
+            3 => FieldInstr::PutZ { dst: u.arbitrary()? },
+            4 => FieldInstr::PutV {
+                dst: u.arbitrary()?,
+                val: u.arbitrary()?,
+            },
+            5 => FieldInstr::Fits {
+                src: u.arbitrary()?,
+                bits: u.arbitrary()?,
+            },
+            6 => FieldInstr::Mov {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            7 => FieldInstr::Eq {
+                src1: u.arbitrary()?,
+                src2: u.arbitrary()?,
+            },
+            8 => FieldInstr::Neg {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            9 => FieldInstr::Add {
+                dst_src: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            10 => FieldInstr::Mul {
+                dst_src: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            11 => FieldInstr::Inv {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            12 => FieldInstr::Exp {
+                dst_src: u.arbitrary()?,
+                exp: u.arbitrary()?,
+            },
+            13 => FieldInstr::ExpI {
+                dst_src: u.arbitrary()?,
+                exp: u.arbitrary()?,
+            },
+            14 => FieldInstr::Sqr {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            15 => FieldInstr::Sqrt {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            16 => FieldInstr::CMov {
+                dst: u.arbitrary()?,
+                src: u.arbitrary()?,
+            },
+            17 => FieldInstr::Sel {
+                dst: u.arbitrary()?,
+                src_true: u.arbitrary()?,
+                src_false: u.arbitrary()?,
+            },
+            18 => FieldInstr::Lt {
+                src1: u.arbitrary()?,
+                src2: u.arbitrary()?,
+            },
+            _ => FieldInstr::Rng {
+                src: u.arbitrary()?,
+                bound: u.arbitrary()?,
+            },
+        })
+    }
+}
+
+fn arbitrary_ctrl(u: &mut Unstructured) -> Result<CtrlInstr<LibId>> {
+    let site = |u: &mut Unstructured| -> Result<Site<LibId>> {
+        let lib_id = LibId::from([u.int_in_range(0u8..=3)?; 32]);
+        Ok(Site::new(lib_id, u.arbitrary()?))
+    };
+    Ok(match u.int_in_range(0..=16)? {
+        0 => CtrlInstr::Nop,
+        1 => CtrlInstr::ChkCo,
+        2 => CtrlInstr::ChkCk,
+        3 => CtrlInstr::NotCo,
+        4 => CtrlInstr::FailCk,
+        5 => CtrlInstr::RsetCk,
+        6 => CtrlInstr::Jmp { pos: u.arbitrary()? },
+        7 => CtrlInstr::JiOvfl { pos: u.arbitrary()? },
+        8 => CtrlInstr::JiFail { pos: u.arbitrary()? },
+        9 => CtrlInstr::Sh { shift: u.arbitrary()? },
+        10 => CtrlInstr::ShOvfl { shift: u.arbitrary()? },
+        11 => CtrlInstr::ShFail { shift: u.arbitrary()? },
+        12 => CtrlInstr::Exec { site: site(u)? },
+        13 => CtrlInstr::Fn { pos: u.arbitrary()? },
+        14 => CtrlInstr::Call { site: site(u)? },
+        15 => CtrlInstr::Ret,
+        _ => CtrlInstr::Stop,
+    })
+}
+
+fn arbitrary_reserved(u: &mut Unstructured) -> Result<Instr<LibId>> {
+    let reserved = (0..=u8::MAX)
+        .filter(|op| {
+            !CtrlInstr::<LibId>::op_range().contains(op) && !<FieldInstr as Bytecode<LibId>>::op_range().contains(op)
+        })
+        .collect::<Vec<_>>();
+    let opcode = *u.choose(&reserved)?;
+    // Reserved instructions have no operands, so they are decoded from the opcode alone.
+    let libs = LibsSeg::default();
+    let mut reader = Marshaller::with([0u8; 0], [0u8; 0], &libs);
+    Ok(Instr::<LibId>::decode_operands(&mut reader, opcode).expect("reserved instruction has no operands"))
+}
+
+impl<'a> Arbitrary<'a> for Instr<LibId> {
+    /// Generates an arbitrary instruction, which is a field arithmetic instruction in 3/4 of the
+    /// cases, and a control flow or a reserved instruction otherwise.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0u8..=15)? {
+            0..=2 => Instr::Ctrl(arbitrary_ctrl(u)?),
+            3 => arbitrary_reserved(u)?,
+            _ => Instr::Gfa(u.arbitrary()?),
+        })
+    }
+}
+
+/// Fuzzing entry point for the instruction encoding.
+///
+/// Generates an arbitrary program from the input, assembles it into a library and checks that
+/// the library is disassembled back into the same program. Inputs which can't be turned into a
+/// valid library (for instance, exceeding the maximum code segment size) are ignored.
+///
+/// # Panics
+///
+/// If the program doesn't round-trip through the bytecode.
+pub fn fuzz_roundtrip(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    let Ok(code) = Vec::<Instr<LibId>>::arbitrary(&mut u) else {
+        return;
+    };
+    let Ok(lib) = Lib::assemble(&code) else {
+        return;
+    };
+    let disassembled = lib
+        .disassemble::<Instr<LibId>>()
+        .expect("assembled library must be decodable");
+    assert_eq!(disassembled, code, "program doesn't round-trip through the bytecode");
+}
+
+/// Fuzzing entry point for the decoder and the execution loop.
+///
+/// Executes the input as a raw code segment with [`exec_raw`], using the same input as the data
+/// segment. The execution halts on the first failure and is bounded by [`FUZZ_STEP_LIM`]
+/// instructions.
+///
+/// # Returns
+///
+/// Value of the `CK` register at the end of the execution.
+pub fn fuzz_exec(data: &[u8]) -> Status {
+    let core_config = CoreConfig {
+        halt: true,
+        complexity_lim: None,
+    };
+    let config = GfaConfig {
+        step_lim: Some(FUZZ_STEP_LIM),
+        ..GfaConfig::default()
+    };
+    let mut vm = Vm::<Instr<LibId>>::with(core_config, config);
+    exec_raw(data, data, 0, &mut vm)
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::CoreExt;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    fn inputs() -> impl Iterator<Item = Vec<u8>> {
+        let mut rng = TestRng::with(0x4477);
+        (0..256).map(move |len| (0..len * 4).map(|_| rng.next_u64() as u8).collect())
+    }
+
+    #[test]
+    fn roundtrip() {
+        for data in inputs() {
+            fuzz_roundtrip(&data);
+        }
+    }
+
+    #[test]
+    fn exec() {
+        for data in inputs() {
+            fuzz_exec(&data);
+        }
+    }
+
+    #[test]
+    fn exec_bounded() {
+        // `jmp 0` loops forever without the step limit
+        let lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos: 0 })]).unwrap();
+        assert_eq!(fuzz_exec(&lib.code), Status::Fail);
+    }
+
+    #[test]
+    fn exec_program() {
+        let lib = Lib::assemble(&[
+            Instr::<LibId>::Gfa(FieldInstr::PutV {
+                dst: RegE::E1,
+                val: ConstVal::Val1,
+            }),
+            Instr::Gfa(FieldInstr::Add {
+                dst_src: RegE::E1,
+                src: RegE::E1,
+            }),
+        ])
+        .unwrap();
+        let core_config = CoreConfig {
+            halt: true,
+            complexity_lim: None,
+        };
+        let mut vm = Vm::<Instr<LibId>>::with(core_config, GfaConfig::default());
+        assert_eq!(exec_raw(&lib.code, &lib.data, 0, &mut vm), Status::Ok);
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(2u8)));
+        assert_eq!(fuzz_exec(&lib.code), Status::Ok);
+    }
+
+    #[test]
+    fn reserved() {
+        let data = [3u8; 64];
+        let mut u = Unstructured::new(&data);
+        let instr = arbitrary_reserved(&mut u).unwrap();
+        assert!(matches!(instr, Instr::Reserved(_)));
+    }
+}
@@ -0,0 +1,970 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Control- and data-flow analysis of zk-AluVM programs.
+//!
+//! The analysis is intra-procedural: calls of local routines and external libraries are treated
+//! as opaque instructions, which may read and modify any of the registers.
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeInclusive};
+
+use aluvm::isa::{Bytecode, CtrlInstr, GotoTarget, Instruction};
+use aluvm::SiteId;
+
+use super::{FieldInstr, Instr, ECALL_WINDOW};
+use crate::RegE;
+
+/// Program code exceeds maximal size of the code segment.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("program code exceeds maximal size of the code segment.")]
+pub struct CodeOverflow;
+
+/// Successor of an instruction in the control flow graph.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Successor {
+    /// Execution continues with the instruction with the given index.
+    Instr(usize),
+
+    /// Execution of the routine completes (with a `ret`, `stop`, or a failure).
+    Exit,
+
+    /// Execution continues at an unknown location: either a jump points outside of the
+    /// instruction boundaries, or an external library is executed without a return.
+    Unknown,
+}
+
+/// Control flow graph of a program.
+#[derive(Clone, Debug)]
+pub struct ControlFlow<'code, Id: SiteId> {
+    code: &'code [Instr<Id>],
+    offsets: Vec<u16>,
+    succ: Vec<Vec<Successor>>,
+    pred: Vec<BTreeSet<usize>>,
+    calls: Vec<Option<usize>>,
+    entries: BTreeSet<usize>,
+    targets: BTreeSet<usize>,
+}
+
+impl<'code, Id: SiteId> ControlFlow<'code, Id> {
+    /// Analyzes control flow of the program.
+    pub fn analyze(code: &'code [Instr<Id>]) -> Result<Self, CodeOverflow> {
+        let mut offsets = Vec::with_capacity(code.len() + 1);
+        let mut pos = 0u16;
+        offsets.push(pos);
+        for instr in code {
+            pos = pos.checked_add(instr.code_byte_len()).ok_or(CodeOverflow)?;
+            offsets.push(pos);
+        }
+
+        let index = |pos: Option<u16>| match pos.map(|pos| offsets.binary_search(&pos)) {
+            Some(Ok(no)) if no < code.len() => Successor::Instr(no),
+            Some(Ok(_)) => Successor::Exit,
+            _ => Successor::Unknown,
+        };
+        let next = |no: usize| if no + 1 < code.len() { Successor::Instr(no + 1) } else { Successor::Exit };
+
+        let mut succ = Vec::with_capacity(code.len());
+        let mut calls = Vec::with_capacity(code.len());
+        let mut entries = bset![];
+        let mut targets = bset![];
+        if !code.is_empty() {
+            entries.insert(0);
+        }
+        for (no, instr) in code.iter().enumerate() {
+            if instr.is_goto_target() {
+                entries.insert(no);
+                targets.insert(no);
+            }
+            let goto = match instr.clone().local_goto_pos() {
+                GotoTarget::None => None,
+                GotoTarget::Absolute(pos) => Some(index(Some(*pos))),
+                GotoTarget::Relative(shift) => Some(index(offsets[no].checked_add_signed(*shift as i16))),
+            };
+            if let Some(Successor::Instr(target)) = goto {
+                targets.insert(target);
+            }
+            let mut call = None;
+            let mut s = match instr {
+                Instr::Ctrl(ctrl) => match ctrl {
+                    CtrlInstr::Jmp { .. } | CtrlInstr::Sh { .. } => vec![goto.expect("jump instruction")],
+                    CtrlInstr::JiOvfl { .. }
+                    | CtrlInstr::JiFail { .. }
+                    | CtrlInstr::ShOvfl { .. }
+                    | CtrlInstr::ShFail { .. } => vec![next(no), goto.expect("jump instruction")],
+                    CtrlInstr::Fn { .. } => {
+                        if let Some(Successor::Instr(target)) = goto {
+                            entries.insert(target);
+                            call = Some(target);
+                        }
+                        vec![next(no)]
+                    }
+                    CtrlInstr::Call { .. } => vec![next(no)],
+                    CtrlInstr::Exec { .. } => vec![Successor::Unknown],
+                    CtrlInstr::Ret | CtrlInstr::Stop => vec![Successor::Exit],
+                    CtrlInstr::Nop | CtrlInstr::NotCo | CtrlInstr::RsetCk => vec![next(no)],
+                    CtrlInstr::ChkCo | CtrlInstr::ChkCk | CtrlInstr::FailCk => vec![next(no)],
+                },
+                Instr::Gfa(_) => vec![next(no)],
+                Instr::Reserved(_) => vec![],
+            };
+            if may_stop(instr) {
+                s.push(Successor::Exit);
+            }
+            s.sort();
+            s.dedup();
+            succ.push(s);
+            calls.push(call);
+        }
+
+        let mut pred = vec![BTreeSet::new(); code.len()];
+        for (no, s) in succ.iter().enumerate() {
+            for target in s {
+                if let Successor::Instr(target) = target {
+                    pred[*target].insert(no);
+                }
+            }
+        }
+
+        Ok(Self {
+            code,
+            offsets,
+            succ,
+            pred,
+            calls,
+            entries,
+            targets,
+        })
+    }
+
+    /// Returns the analyzed program code.
+    pub fn code(&self) -> &'code [Instr<Id>] { self.code }
+
+    /// Returns the number of instructions in the program.
+    pub fn len(&self) -> usize { self.code.len() }
+
+    /// Detects whether the program has no instructions.
+    pub fn is_empty(&self) -> bool { self.code.is_empty() }
+
+    /// Returns offset of an instruction in the code segment.
+    ///
+    /// For an index equal to the number of instructions returns the code segment size.
+    ///
+    /// # Panics
+    ///
+    /// If the index exceeds the number of instructions.
+    pub fn offset(&self, no: usize) -> u16 { self.offsets[no] }
+
+    /// Returns offsets of all instructions in the code segment, followed by the code segment size.
+    pub fn offsets(&self) -> &[u16] { &self.offsets }
+
+    /// Finds an instruction starting at a given offset in the code segment.
+    pub fn index_at(&self, pos: u16) -> Option<usize> {
+        self.offsets
+            .binary_search(&pos)
+            .ok()
+            .filter(|no| *no < self.code.len())
+    }
+
+    /// Returns successors of an instruction.
+    pub fn successors(&self, no: usize) -> &[Successor] { &self.succ[no] }
+
+    /// Returns indexes of the instructions which may pass execution to the given one, not
+    /// counting calls of local routines.
+    pub fn predecessors(&self, no: usize) -> &BTreeSet<usize> { &self.pred[no] }
+
+    /// Returns the index of a local routine called by an instruction.
+    pub fn call_target(&self, no: usize) -> Option<usize> { self.calls[no] }
+
+    /// Returns indexes of the instructions from which execution may start: the first instruction,
+    /// targets of local routine calls and `nop` instructions marking routine entry points.
+    pub fn entry_points(&self) -> &BTreeSet<usize> { &self.entries }
+
+    /// Returns indexes of the instructions which can be reached by local jumps and calls,
+    /// including `nop` instructions marking routine entry points.
+    pub fn goto_targets(&self) -> &BTreeSet<usize> { &self.targets }
+
+    /// Splits the program into basic blocks: maximal sequences of instructions which are entered
+    /// only through the first of them, and which pass execution only to the next instruction in
+    /// the sequence (or stop the program).
+    ///
+    /// Calls of local routines don't terminate a basic block.
+    pub fn basic_blocks(&self) -> Vec<Range<usize>> {
+        let mut leaders = self.targets.clone();
+        leaders.extend(self.entries.iter().copied());
+        for (no, succ) in self.succ.iter().enumerate() {
+            let next = Successor::Instr(no + 1);
+            if !succ.contains(&next) || succ.iter().any(|s| *s != next && *s != Successor::Exit) {
+                leaders.insert(no + 1);
+            }
+        }
+        let mut blocks = Vec::with_capacity(leaders.len());
+        let mut start = 0;
+        for end in leaders
+            .into_iter()
+            .filter(|no| *no > 0 && *no <= self.code.len())
+        {
+            blocks.push(start..end);
+            start = end;
+        }
+        if start < self.code.len() {
+            blocks.push(start..self.code.len());
+        }
+        blocks
+    }
+
+    /// Returns indexes of all instructions which can be reached from the given one, including
+    /// code of the called local routines.
+    pub fn reachable_from(&self, start: usize) -> BTreeSet<usize> {
+        let mut visited = BTreeSet::new();
+        let mut queue = vec![start];
+        while let Some(no) = queue.pop() {
+            if no >= self.code.len() || !visited.insert(no) {
+                continue;
+            }
+            for s in &self.succ[no] {
+                if let Successor::Instr(next) = s {
+                    queue.push(*next);
+                }
+            }
+            if let Some(target) = self.calls[no] {
+                queue.push(target);
+            }
+        }
+        visited
+    }
+
+    /// Returns indexes of instructions which can't be reached from any of the entry points.
+    pub fn unreachable(&self) -> BTreeSet<usize> {
+        let reachable = self
+            .entries
+            .iter()
+            .flat_map(|no| self.reachable_from(*no))
+            .collect::<BTreeSet<_>>();
+        (0..self.code.len())
+            .filter(|no| !reachable.contains(no))
+            .collect()
+    }
+}
+
+/// Checks whether an instruction may stop the program execution (assuming halting on `CK`
+/// failures).
+pub fn may_stop<Id: SiteId>(instr: &Instr<Id>) -> bool {
+    matches!(
+        instr,
+        Instr::Ctrl(CtrlInstr::ChkCo | CtrlInstr::ChkCk | CtrlInstr::FailCk | CtrlInstr::Ret | CtrlInstr::Stop)
+            | Instr::Gfa(
+                FieldInstr::Fits { .. }
+                    | FieldInstr::Lt { .. }
+                    | FieldInstr::Rng { .. }
+                    | FieldInstr::Neg { .. }
+                    | FieldInstr::Add { .. }
+                    | FieldInstr::Mul { .. }
+                    | FieldInstr::Ecall { .. }
+                    | FieldInstr::Inv { .. }
+                    | FieldInstr::Exp { .. }
+                    | FieldInstr::ExpI { .. }
+                    | FieldInstr::Sqr { .. }
+                    | FieldInstr::Sqrt { .. }
+            )
+            | Instr::Reserved(_)
+    )
+}
+
+/// Checks whether an instruction calls other routines, which may read and modify any registers.
+fn is_call<Id: SiteId>(instr: &Instr<Id>) -> bool {
+    matches!(instr, Instr::Ctrl(CtrlInstr::Fn { .. } | CtrlInstr::Call { .. } | CtrlInstr::Exec { .. }))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+struct RegMask(u16);
+
+impl RegMask {
+    const ALL: Self = RegMask(u16::MAX);
+
+    fn contains(self, reg: RegE) -> bool { self.0 & (1 << reg as u8) != 0 }
+    fn insert(&mut self, reg: RegE) { self.0 |= 1 << reg as u8; }
+    fn remove(&mut self, reg: RegE) { self.0 &= !(1 << reg as u8); }
+    fn union(self, other: Self) -> Self { RegMask(self.0 | other.0) }
+    fn intersection(self, other: Self) -> Self { RegMask(self.0 & other.0) }
+    fn to_set(self) -> BTreeSet<RegE> {
+        RegE::ALL
+            .into_iter()
+            .filter(|reg| self.contains(*reg))
+            .collect()
+    }
+}
+
+impl FromIterator<RegE> for RegMask {
+    fn from_iter<T: IntoIterator<Item = RegE>>(iter: T) -> Self {
+        let mut mask = RegMask::default();
+        for reg in iter {
+            mask.insert(reg);
+        }
+        mask
+    }
+}
+
+/// Registers liveness analysis.
+///
+/// A register is live at some point of the program if its value may be read afterwards.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Liveness {
+    before: Vec<RegMask>,
+    after: Vec<RegMask>,
+}
+
+impl Liveness {
+    /// Performs backwards liveness analysis of the program.
+    ///
+    /// `live_out` provides the registers which are read by the host or the caller once the
+    /// program stops or returns. Calls of other routines are considered to read all the
+    /// registers.
+    pub fn analyze<Id: SiteId>(cfg: &ControlFlow<Id>, live_out: &BTreeSet<RegE>) -> Self {
+        let exit = live_out.iter().copied().collect::<RegMask>();
+        let len = cfg.len();
+        let mut before = vec![RegMask::default(); len];
+        let mut after = vec![RegMask::default(); len];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for no in (0..len).rev() {
+                let instr = &cfg.code()[no];
+                let mut live = if is_call(instr) { RegMask::ALL } else { RegMask::default() };
+                for s in cfg.successors(no) {
+                    live = live.union(match s {
+                        Successor::Instr(next) => before[*next],
+                        Successor::Exit => exit,
+                        Successor::Unknown => RegMask::ALL,
+                    });
+                }
+                let mut live_in = live;
+                for reg in instr.dst_regs() {
+                    live_in.remove(reg);
+                }
+                for reg in instr.src_regs() {
+                    live_in.insert(reg);
+                }
+                if live_in != before[no] || live != after[no] {
+                    before[no] = live_in;
+                    after[no] = live;
+                    changed = true;
+                }
+            }
+        }
+        Self { before, after }
+    }
+
+    /// Returns registers live before execution of an instruction.
+    pub fn live_before(&self, no: usize) -> BTreeSet<RegE> { self.before[no].to_set() }
+
+    /// Returns registers live after execution of an instruction.
+    pub fn live_after(&self, no: usize) -> BTreeSet<RegE> { self.after[no].to_set() }
+
+    /// Checks whether a register is live after execution of an instruction.
+    pub fn is_live_after(&self, no: usize, reg: RegE) -> bool { self.after[no].contains(reg) }
+
+    /// Checks whether a register is live before execution of an instruction.
+    pub fn is_live_before(&self, no: usize, reg: RegE) -> bool { self.before[no].contains(reg) }
+
+    /// Returns ranges of instruction indexes, during which the register is live (either before
+    /// or after the instruction execution).
+    pub fn live_ranges(&self, reg: RegE) -> Vec<RangeInclusive<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for no in 0..self.before.len() {
+            let live = self.before[no].contains(reg) || self.after[no].contains(reg);
+            match (live, start) {
+                (true, None) => start = Some(no),
+                (false, Some(s)) => {
+                    ranges.push(s..=no - 1);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            ranges.push(s..=self.before.len() - 1);
+        }
+        ranges
+    }
+}
+
+/// Analysis of registers which are definitely initialized (i.e. contain a value).
+///
+/// No registers are considered initialized at the program entry points and after calls of other
+/// routines.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Initialization {
+    before: Vec<RegMask>,
+}
+
+impl Initialization {
+    /// Performs forward analysis of initialized registers.
+    pub fn analyze<Id: SiteId>(cfg: &ControlFlow<Id>) -> Self {
+        let len = cfg.len();
+        let mut before = vec![RegMask::ALL; len];
+        for entry in cfg.entry_points() {
+            before[*entry] = RegMask::default();
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for no in 0..len {
+                let instr = &cfg.code()[no];
+                let mut after = before[no];
+                match instr {
+                    Instr::Gfa(FieldInstr::Clr { dst }) => after.remove(*dst),
+                    Instr::Gfa(FieldInstr::Mov { dst, src } | FieldInstr::CMov { dst, src })
+                        if !after.contains(*src) =>
+                    {
+                        after.remove(*dst)
+                    }
+                    // The destination is initialized only if it was initialized before the move
+                    Instr::Gfa(FieldInstr::CMov { .. }) => {}
+                    Instr::Gfa(FieldInstr::Sel { dst, src_true, src_false })
+                        if !after.contains(*src_true) || !after.contains(*src_false) =>
+                    {
+                        after.remove(*dst)
+                    }
+                    // The host may clear any register from the window
+                    Instr::Gfa(FieldInstr::Ecall { .. }) => ECALL_WINDOW.into_iter().for_each(|reg| after.remove(reg)),
+                    Instr::Gfa(gfa) => {
+                        for reg in Instruction::<Id>::dst_regs(gfa) {
+                            after.insert(reg);
+                        }
+                    }
+                    _ if is_call(instr) => after = RegMask::default(),
+                    _ => {}
+                }
+                for s in cfg.successors(no) {
+                    let Successor::Instr(next) = *s else { continue };
+                    let new = before[next].intersection(after);
+                    if new != before[next] {
+                        before[next] = new;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        Self { before }
+    }
+
+    /// Returns registers which are definitely initialized before execution of an instruction.
+    pub fn init_before(&self, no: usize) -> BTreeSet<RegE> { self.before[no].to_set() }
+
+    /// Checks whether a register is definitely initialized before execution of an instruction.
+    pub fn is_init_before(&self, no: usize, reg: RegE) -> bool { self.before[no].contains(reg) }
+}
+
+/// Definition of a register value.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Def {
+    /// The value is defined outside of the routine: by the caller of the routine, or by the host.
+    Entry,
+
+    /// The value is defined by the instruction with the given index. For instructions calling
+    /// other routines this means that the value may be modified by the called routine.
+    Instr(usize),
+}
+
+/// Def-use chains of the program registers, computed with reaching definitions analysis.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DefUse {
+    reaching: Vec<[BTreeSet<Def>; 16]>,
+    uses: Vec<BTreeSet<(usize, RegE)>>,
+}
+
+impl DefUse {
+    /// Performs reaching definitions analysis, constructing def-use chains.
+    pub fn analyze<Id: SiteId>(cfg: &ControlFlow<Id>) -> Self {
+        let len = cfg.len();
+        let empty = || core::array::from_fn::<BTreeSet<Def>, 16, _>(|_| BTreeSet::new());
+        let mut reaching = vec![empty(); len];
+        for entry in cfg.entry_points() {
+            reaching[*entry] = core::array::from_fn(|_| bset![Def::Entry]);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for no in 0..len {
+                let instr = &cfg.code()[no];
+                let mut out = reaching[no].clone();
+                let defs = if is_call(instr) { RegE::ALL.into_iter().collect() } else { instr.dst_regs() };
+                for reg in defs {
+                    out[reg as usize] = bset![Def::Instr(no)];
+                }
+                for s in cfg.successors(no) {
+                    let Successor::Instr(next) = *s else { continue };
+                    for (set, defs) in reaching[next].iter_mut().zip(&out) {
+                        for def in defs {
+                            changed |= set.insert(*def);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut uses = vec![BTreeSet::new(); len];
+        for (no, instr) in cfg.code().iter().enumerate() {
+            for reg in instr.src_regs() {
+                for def in &reaching[no][reg as usize] {
+                    if let Def::Instr(def) = def {
+                        uses[*def].insert((no, reg));
+                    }
+                }
+            }
+        }
+
+        Self { reaching, uses }
+    }
+
+    /// Returns definitions of the register value which may reach the instruction.
+    pub fn reaching_defs(&self, no: usize, reg: RegE) -> &BTreeSet<Def> { &self.reaching[no][reg as usize] }
+
+    /// Returns instructions (and the registers they read) which may use values defined by the
+    /// given instruction.
+    pub fn uses(&self, def: usize) -> &BTreeSet<(usize, RegE)> { &self.uses[def] }
+}
+
+/// Security level of a value in the [`Taint`] analysis.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Display)]
+#[display(lowercase)]
+pub enum Level {
+    /// The value doesn't depend on secret inputs.
+    #[default]
+    Public,
+
+    /// The value may depend on secret inputs.
+    Secret,
+}
+
+impl Level {
+    fn join(self, other: Self) -> Self { self.max(other) }
+}
+
+impl From<bool> for Level {
+    fn from(secret: bool) -> Self {
+        if secret {
+            Level::Secret
+        } else {
+            Level::Public
+        }
+    }
+}
+
+/// Security levels of the registers at some point of the program.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct TaintState {
+    regs: RegMask,
+    co: Level,
+    ck: Level,
+}
+
+impl TaintState {
+    /// Returns the security level of a register.
+    pub fn reg(&self, reg: RegE) -> Level { self.regs.contains(reg).into() }
+
+    /// Returns the security level of the `CO` register.
+    pub fn co(&self) -> Level { self.co }
+
+    /// Returns the security level of the `CK` register.
+    pub fn ck(&self) -> Level { self.ck }
+
+    /// Returns registers which may hold values depending on secret inputs.
+    pub fn secret_regs(&self) -> BTreeSet<RegE> { self.regs.to_set() }
+
+    fn is_public(&self) -> bool { *self == Self::default() }
+
+    fn join(self, other: Self) -> Self {
+        TaintState {
+            regs: self.regs.union(other.regs),
+            co: self.co.join(other.co),
+            ck: self.ck.join(other.ck),
+        }
+    }
+
+    fn set(&mut self, reg: RegE, level: Level) {
+        match level {
+            Level::Public => self.regs.remove(reg),
+            Level::Secret => self.regs.insert(reg),
+        }
+    }
+}
+
+/// Instruction whose effect on the control flow is driven by a secret value.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum SecretFlow {
+    /// Conditional jump on a `CO` or `CK` value depending on secret inputs.
+    #[display("instruction #{0} jumps depending on a secret value")]
+    Jump(usize),
+
+    /// Check of a `CO` or `CK` value depending on secret inputs, which may stop the program.
+    #[display("instruction #{0} checks a secret value")]
+    Check(usize),
+}
+
+impl SecretFlow {
+    /// Index of the instruction in the program.
+    pub fn no(self) -> usize {
+        match self {
+            SecretFlow::Jump(no) | SecretFlow::Check(no) => no,
+        }
+    }
+}
+
+/// Taint analysis, tracking which registers may hold values depending on secret inputs.
+///
+/// Secrets propagate through data flow: the result of an operation is secret if any of its
+/// operands is secret, and `CO` becomes secret when it is set from a comparison of secret values.
+/// Secret `CO` also taints `CK` when checked with `chk CO`. Implicit flows, where a register value
+/// depends on a secret because of a secret-driven branch, are not tracked beyond reporting the
+/// branch itself as a [`SecretFlow`].
+///
+/// Each entry point of the program starts with the same set of secret registers. Calls of other
+/// routines are considered to propagate a secret, if there is any, to all the registers.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Taint {
+    before: Vec<TaintState>,
+    flows: Vec<SecretFlow>,
+}
+
+impl Taint {
+    /// Performs forward taint analysis, starting with the `secrets` registers holding secret
+    /// values.
+    pub fn analyze<Id: SiteId>(cfg: &ControlFlow<Id>, secrets: &BTreeSet<RegE>) -> Self {
+        let len = cfg.len();
+        let entry = TaintState {
+            regs: secrets.iter().copied().collect(),
+            ..default!()
+        };
+        let mut before = vec![TaintState::default(); len];
+        for no in cfg.entry_points() {
+            before[*no] = entry;
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for no in 0..len {
+                let after = Self::transfer(&cfg.code()[no], before[no]);
+                for s in cfg.successors(no) {
+                    let Successor::Instr(next) = *s else { continue };
+                    let new = before[next].join(after);
+                    if new != before[next] {
+                        before[next] = new;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let flows = cfg
+            .code()
+            .iter()
+            .zip(&before)
+            .enumerate()
+            .filter_map(|(no, (instr, state))| match instr {
+                Instr::Ctrl(CtrlInstr::JiOvfl { .. } | CtrlInstr::ShOvfl { .. }) if state.co == Level::Secret => {
+                    Some(SecretFlow::Jump(no))
+                }
+                Instr::Ctrl(CtrlInstr::JiFail { .. } | CtrlInstr::ShFail { .. }) if state.ck == Level::Secret => {
+                    Some(SecretFlow::Jump(no))
+                }
+                Instr::Ctrl(CtrlInstr::ChkCo) if state.co == Level::Secret => Some(SecretFlow::Check(no)),
+                Instr::Ctrl(CtrlInstr::ChkCk) if state.ck == Level::Secret => Some(SecretFlow::Check(no)),
+                _ => None,
+            })
+            .collect();
+
+        Self { before, flows }
+    }
+
+    fn transfer<Id: SiteId>(instr: &Instr<Id>, mut state: TaintState) -> TaintState {
+        let level = |state: &TaintState, reg: RegE| state.reg(reg);
+        match instr {
+            Instr::Gfa(FieldInstr::Test { src } | FieldInstr::Fits { src, .. } | FieldInstr::Rng { src, .. }) => {
+                state.co = level(&state, *src)
+            }
+            Instr::Gfa(FieldInstr::Eq { src1, src2 } | FieldInstr::Lt { src1, src2 }) => {
+                state.co = level(&state, *src1).join(level(&state, *src2))
+            }
+            Instr::Gfa(
+                FieldInstr::Clr { dst }
+                | FieldInstr::PutD { dst, .. }
+                | FieldInstr::PutZ { dst }
+                | FieldInstr::PutV { dst, .. },
+            ) => state.set(*dst, Level::Public),
+            Instr::Gfa(FieldInstr::Mov { dst, src } | FieldInstr::Neg { dst, src } | FieldInstr::Sqr { dst, src }) => {
+                state.set(*dst, level(&state, *src))
+            }
+            Instr::Gfa(
+                FieldInstr::Add { dst_src, src }
+                | FieldInstr::Mul { dst_src, src }
+                | FieldInstr::Exp { dst_src, exp: src },
+            ) => state.set(*dst_src, level(&state, *dst_src).join(level(&state, *src))),
+            Instr::Gfa(FieldInstr::Sqrt { dst, src }) => {
+                // The root is written only for quadratic residues, which is revealed through `CO`
+                state.set(*dst, level(&state, *dst).join(level(&state, *src)));
+                state.co = level(&state, *src);
+            }
+            Instr::Gfa(FieldInstr::CMov { dst, src }) => {
+                state.set(*dst, level(&state, *dst).join(level(&state, *src)).join(state.co))
+            }
+            Instr::Gfa(FieldInstr::Sel { dst, src_true, src_false }) => {
+                state.set(*dst, level(&state, *src_true).join(level(&state, *src_false)).join(state.co))
+            }
+            Instr::Gfa(FieldInstr::Inv { dst, src }) => {
+                // Inverse fails on zero, revealing whether the value is zero
+                state.set(*dst, level(&state, *src));
+                state.ck = state.ck.join(level(&state, *src));
+            }
+            Instr::Gfa(FieldInstr::Ecall { .. }) => {
+                // The host may compute the results and its failure from any register of the window
+                let window = ECALL_WINDOW
+                    .into_iter()
+                    .fold(Level::Public, |acc, reg| acc.join(level(&state, reg)));
+                ECALL_WINDOW
+                    .into_iter()
+                    .for_each(|reg| state.set(reg, window));
+                state.ck = state.ck.join(window);
+            }
+            Instr::Ctrl(CtrlInstr::ChkCo) => state.ck = state.ck.join(state.co),
+            Instr::Ctrl(CtrlInstr::FailCk) => state.ck = Level::Public,
+            Instr::Ctrl(CtrlInstr::RsetCk) => {
+                state.co = state.ck;
+                state.ck = Level::Public;
+            }
+            _ if is_call(instr) && !state.is_public() => {
+                state = TaintState {
+                    regs: RegMask::ALL,
+                    co: Level::Secret,
+                    ck: Level::Secret,
+                }
+            }
+            _ => {}
+        }
+        state
+    }
+
+    /// Returns security levels of the registers before execution of an instruction.
+    pub fn state_before(&self, no: usize) -> TaintState { self.before[no] }
+
+    /// Returns instructions whose effect on the control flow depends on secret values.
+    pub fn secret_flows(&self) -> &[SecretFlow] { &self.flows }
+
+    /// Detects whether any secret value influences the control flow of the program, either with a
+    /// conditional jump or with a check which may stop the program.
+    pub fn influences_control_flow(&self) -> bool { !self.flows.is_empty() }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::RegE::*;
+
+    fn program() -> Vec<Instr<LibId>> {
+        zk_aluasm! {
+            put     E1, 0;          // 0 @0
+            put     E2, 1;          // 1 @2
+            add     E1, E2;         // 2 @6
+            fits    E1, 8.bits;     // 3 @8
+            jif     CO, 6;          // 4 @10
+            mov     EA, E1;         // 5 @13
+            ret;                    // 6 @15
+            nop;                    // 7 @16
+            call    16;             // 8 @17
+            clr     E1;             // 9 @20
+        }
+    }
+
+    #[test]
+    fn cfg() {
+        let code = program();
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        assert_eq!(cfg.len(), 10);
+        assert_eq!(cfg.offset(4), 10);
+        assert_eq!(cfg.offset(10), 22);
+        assert_eq!(cfg.index_at(13), Some(5));
+        assert_eq!(cfg.index_at(14), None);
+        assert_eq!(cfg.successors(2), &[Successor::Instr(3), Successor::Exit]);
+        assert_eq!(cfg.successors(4), &[Successor::Instr(2), Successor::Instr(5)]);
+        assert_eq!(cfg.successors(6), &[Successor::Exit]);
+        assert_eq!(cfg.successors(9), &[Successor::Exit]);
+        assert_eq!(cfg.predecessors(2), &bset![1, 4]);
+        assert_eq!(cfg.call_target(8), Some(7));
+        assert_eq!(cfg.entry_points(), &bset![0, 7]);
+        assert_eq!(cfg.goto_targets(), &bset![2, 7]);
+        assert_eq!(cfg.reachable_from(0), bset![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(cfg.reachable_from(7), bset![7, 8, 9]);
+        assert_eq!(cfg.basic_blocks(), vec![0..2, 2..5, 5..7, 7..10]);
+        assert!(cfg.unreachable().is_empty());
+    }
+
+    #[test]
+    fn liveness() {
+        let code = program();
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let live = Liveness::analyze(&cfg, &bset![EA]);
+        assert_eq!(live.live_before(0), bset![EA]);
+        assert_eq!(live.live_after(1), bset![E1, E2, EA]);
+        assert_eq!(live.live_after(4), bset![E1, E2, EA]);
+        assert_eq!(live.live_after(5), bset![EA]);
+        assert!(live.is_live_after(8, E1));
+        assert!(!live.is_live_after(9, E1));
+        assert_eq!(live.live_ranges(E1), vec![0..=5, 7..=8]);
+        assert_eq!(live.live_ranges(E2), vec![1..=4, 7..=8]);
+    }
+
+    #[test]
+    fn initialization() {
+        let code = program();
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let init = Initialization::analyze(&cfg);
+        assert_eq!(init.init_before(0), bset![]);
+        assert_eq!(init.init_before(2), bset![E1, E2]);
+        assert_eq!(init.init_before(6), bset![E1, E2, EA]);
+        assert_eq!(init.init_before(8), bset![]);
+        assert!(!init.is_init_before(9, E1));
+    }
+
+    #[test]
+    fn def_use() {
+        let code = program();
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let du = DefUse::analyze(&cfg);
+        assert_eq!(du.reaching_defs(2, E1), &bset![Def::Instr(0), Def::Instr(2)]);
+        assert_eq!(du.reaching_defs(2, E2), &bset![Def::Instr(1)]);
+        assert_eq!(du.reaching_defs(2, EA), &bset![Def::Entry]);
+        assert_eq!(du.reaching_defs(9, E1), &bset![Def::Instr(8)]);
+        assert_eq!(du.uses(0), &bset![(2, E1)]);
+        assert_eq!(du.uses(2), &bset![(2, E1), (3, E1), (5, E1)]);
+        assert_eq!(du.uses(5), &bset![]);
+    }
+
+    #[test]
+    fn taint() {
+        let code = program();
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let taint = Taint::analyze(&cfg, &bset![E2]);
+        assert_eq!(taint.state_before(0).secret_regs(), bset![E2]);
+        assert_eq!(taint.state_before(2).secret_regs(), bset![]);
+        assert_eq!(taint.state_before(7).secret_regs(), bset![E2]);
+        assert_eq!(taint.state_before(9).secret_regs(), RegE::ALL.into_iter().collect());
+        assert!(!taint.influences_control_flow());
+
+        let taint = Taint::analyze(&cfg, &bset![E1, EA]);
+        assert_eq!(taint.state_before(3).reg(E1), Level::Public);
+        assert_eq!(taint.state_before(4).co(), Level::Public);
+        assert_eq!(taint.state_before(6).reg(EA), Level::Public);
+        assert!(!taint.influences_control_flow());
+
+        let code = zk_aluasm! {
+            mov     E1, EA;
+            add     E1, EB;
+            fits    E1, 8.bits;
+            jif     CO, 0;
+            eq      E1, E2;
+            chk     CO;
+            jif     CK, 0;
+            mov     CO, CK;
+            chk     CO;
+        };
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let taint = Taint::analyze(&cfg, &bset![EB]);
+        assert_eq!(taint.state_before(2).secret_regs(), bset![E1, EB]);
+        assert_eq!(taint.state_before(3).co(), Level::Secret);
+        assert_eq!(taint.state_before(6).ck(), Level::Secret);
+        assert_eq!(taint.state_before(8).co(), Level::Secret);
+        assert_eq!(taint.state_before(8).ck(), Level::Public);
+        assert_eq!(taint.secret_flows(), &[
+            SecretFlow::Jump(3),
+            SecretFlow::Check(5),
+            SecretFlow::Jump(6),
+            SecretFlow::Check(8)
+        ]);
+        assert_eq!(taint.secret_flows()[1].to_string(), "instruction #5 checks a secret value");
+        assert!(taint.influences_control_flow());
+
+        let code = zk_aluasm! {
+            inv     E1, EB;
+            chk     CK;
+        };
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let taint = Taint::analyze(&cfg, &bset![EB]);
+        assert_eq!(taint.state_before(1).secret_regs(), bset![E1, EB]);
+        assert_eq!(taint.state_before(1).ck(), Level::Secret);
+        assert_eq!(taint.secret_flows(), &[SecretFlow::Check(1)]);
+    }
+
+    #[test]
+    fn cmov() {
+        let code = zk_aluasm! {
+            put     E1, 1;
+            put     E2, 2;
+            eq      EA, EB;
+            cmov    E1, E2;
+            cmov    E3, E1;
+            cmov    E1, EC;
+            ret;
+        };
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let init = Initialization::analyze(&cfg);
+        assert_eq!(init.init_before(4), bset![E1, E2]);
+        assert_eq!(init.init_before(5), bset![E1, E2]);
+        assert_eq!(init.init_before(6), bset![E2]);
+
+        let taint = Taint::analyze(&cfg, &bset![EA]);
+        assert_eq!(taint.state_before(3).co(), Level::Secret);
+        assert_eq!(taint.state_before(4).secret_regs(), bset![E1, EA]);
+        assert!(!taint.influences_control_flow());
+    }
+
+    #[test]
+    fn sel() {
+        let code = zk_aluasm! {
+            put     E1, 1;
+            put     E2, 2;
+            eq      EA, EB;
+            sel     E3, E1, E2;
+            sel     E4, E1, EC;
+            sel     E2, E3, E3;
+            ret;
+        };
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        let init = Initialization::analyze(&cfg);
+        assert_eq!(init.init_before(4), bset![E1, E2, E3]);
+        assert_eq!(init.init_before(5), bset![E1, E2, E3]);
+        assert_eq!(init.init_before(6), bset![E1, E2, E3]);
+
+        let taint = Taint::analyze(&cfg, &bset![EA]);
+        assert_eq!(taint.state_before(4).secret_regs(), bset![E3, EA]);
+        assert_eq!(taint.state_before(6).secret_regs(), bset![E2, E3, E4, EA]);
+        assert!(!taint.influences_control_flow());
+    }
+}
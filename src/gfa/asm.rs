@@ -0,0 +1,1366 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Textual assembler for zk-AluVM programs.
+//!
+//! The syntax of a single instruction matches its [`Display`] representation, such that any
+//! instruction can be printed and parsed back. A program consists of lines, each containing a
+//! single instruction, a label or a directive:
+//!
+//! ```text
+//! ; comments start with `;` or `//` and last until the end of the line
+//! .assert_size        < 1024      ; directives start with a dot
+//! .assert_complexity  <= 10000000
+//!
+//! routine START:                  ; emits `nop` which can be used as a goto target
+//!     put     E1, 0
+//!     put     E2, 1
+//! again:                          ; a label without any code
+//!     add     E1, E2
+//!     fits    E1, 8.bits
+//!     jif     CO, again
+//!     ret
+//! ```
+//!
+//! Labels resolve into the byte offset of the instruction following them and can be used as
+//! operands of `jmp`, `jif` and `call` instructions.
+//!
+//! Repeated instruction sequences can be defined as macros, which are invoked like instructions
+//! and expanded at assembly time:
+//!
+//! ```text
+//! macro assert_eq(a, b) {
+//!     eq      a, b
+//!     chk     CO
+//! }
+//! macro square_sum(dst, src) {    ; dst += src * src
+//!     mov     %sq, src            ; `%` marks a temporary register
+//!     mul     %sq, src
+//!     add     dst, %sq
+//! }
+//!     square_sum  E1, E2
+//!     assert_eq   E1, EA
+//! ```
+//!
+//! Macros must be defined before they are invoked and may invoke other macros. The expansion is
+//! hygienic: labels defined inside a macro are unique to each expansion, and temporaries are
+//! allocated from the local registers `E8`..`E1` which are not used by the macro arguments, its
+//! body or any of the enclosing macro expansions.
+//!
+//! Parts of the code may be assembled conditionally, depending on the [`AsmConfig`] provided to
+//! [`Assembly::parse_with`]. Conditions follow the syntax of Rust `cfg` attributes and may be
+//! given either for a block of lines or for a single line:
+//!
+//! ```text
+//! .cfg field = "secp"
+//!     put     E1, 0x10
+//! .else
+//!     put     E1, 0x20
+//! .endcfg
+//! #[cfg(any(debug, not(field = "stark")))] chk CK
+//! ```
+//!
+//! Values of `put` and bounds of `rng` instructions may be given by constant expressions, which
+//! are evaluated at assembly time with unsigned 256-bit integer semantics (see [`eval_const`]). If
+//! the assembler is configured for a specific field with [`AsmConfig::with_field`], the field order
+//! is available as the constant `P`:
+//!
+//! ```text
+//!     put     E1, (1 << 64) - 1       ; assembled into `put E1, ffff_ffff_ffff_ffff#h`
+//!     put     E2, P - 5
+//!     rng     E1, 1000 * 1000 * 1000
+//! ```
+//!
+//! When the field is known, the assembler rejects `put` instructions with constants which are not
+//! canonical elements of the field, since they would fail `CK` at runtime. For programs assembled
+//! without a field, such instructions can be found with [`Assembly::non_canonical`].
+//!
+//! Values may be placed into the data segment explicitly with `.const` and `.table` directives,
+//! which declare a named field element or a named sequence of field elements. The declared values
+//! are placed at the beginning of the data segment in the order of declaration (see
+//! [`Assembly::assemble`]), and `put` instructions referencing them by name read them from there.
+//! The names can also be used in constant expressions:
+//!
+//! ```text
+//! .const  GEN = 0x07
+//! .table  POWERS = [1, GEN, GEN * GEN, GEN * GEN * GEN]
+//!     put     E1, GEN                 ; reads the value of `GEN` from the data segment
+//!     put     E2, POWERS[2]           ; reads the third element of the table
+//!     put     E3, GEN + 1             ; a constant expression
+//! ```
+//!
+//! Along with the code, the assembler produces a [`SourceMap`], linking each instruction to the
+//! source line it originates from.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::str::FromStr;
+
+use aluvm::isa::{Bytecode, CtrlInstr, GotoTarget, Instruction, ReservedInstr};
+use aluvm::{AssemblerError, Lib, LibId, LibsSeg, MarshallError, Marshaller, Site, SiteId};
+use amplify::num::u256;
+
+use super::cexpr::eval_const_with;
+use super::preproc::{preprocess, Line};
+use super::{
+    AsmConfig, AsmLimit, Bits, ConstExprError, ConstVal, FieldInstr, Instr, IsaGroups, ParseBitsError, SourceLoc,
+    SourceMap,
+};
+use crate::fe::parse_u256;
+use crate::{fe256, ParseRegError, RegE};
+
+/// Errors parsing a single instruction from its textual representation.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ParseInstrError {
+    /// empty instruction.
+    Empty,
+
+    /// unknown instruction mnemonic `{0}`.
+    UnknownMnemonic(String),
+
+    /// instruction `{0}` can't take {1} operand(s).
+    OperandCount(String, usize),
+
+    /// {0}
+    #[from]
+    InvalidReg(ParseRegError),
+
+    /// {0}
+    #[from]
+    InvalidBits(ParseBitsError),
+
+    /// invalid field element value `{0}`.
+    InvalidValue(String),
+
+    /// invalid control register `{0}`; only `CO` and `CK` are allowed here.
+    InvalidFlag(String),
+
+    /// invalid jump target `{0}`.
+    InvalidTarget(String),
+
+    /// invalid reserved instruction op code `{0}`.
+    InvalidOpcode(String),
+
+    /// invalid host syscall identifier `{0}`; it must be a decimal number from 0 to 255.
+    InvalidSyscall(String),
+
+    /// invalid exponent `{0}`; it must be a register or a decimal number from 0 to 255.
+    InvalidExponent(String),
+}
+
+/// Errors assembling a program from its textual representation.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("line {line}: {kind}")]
+pub struct AsmError {
+    /// Line number in the source text, starting from 1.
+    pub line: usize,
+    /// Details of the error.
+    pub kind: AsmErrorKind,
+}
+
+/// Details of an [`AsmError`].
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum AsmErrorKind {
+    /// {0}
+    #[from]
+    Instr(ParseInstrError),
+
+    /// unknown directive `{0}`.
+    UnknownDirective(String),
+
+    /// invalid directive argument `{0}`.
+    InvalidDirective(String),
+
+    /// label `{0}` is defined more than once.
+    DuplicateLabel(String),
+
+    /// label `{0}` is not defined.
+    UnknownLabel(String),
+
+    /// invalid label name `{0}`.
+    InvalidLabel(String),
+
+    /// program code exceeds maximal size of the code segment.
+    CodeOverflow,
+
+    /// invalid macro definition `{0}`.
+    InvalidMacro(String),
+
+    /// macro `{0}` is defined more than once.
+    DuplicateMacro(String),
+
+    /// macro `{0}` definition is not terminated with `}}`.
+    UnterminatedMacro(String),
+
+    /// macro `{0}` can't take {1} argument(s).
+    MacroArgCount(String, usize),
+
+    /// macro `{0}` is invoked recursively.
+    MacroRecursion(String),
+
+    /// no registers left for temporaries of macro `{0}`.
+    NoTempRegs(String),
+
+    /// invalid configuration predicate `{0}`.
+    InvalidCfg(String),
+
+    /// unbalanced `.cfg`, `.else` and `.endcfg` directives.
+    UnbalancedCfg,
+
+    /// {0}
+    #[from]
+    ConstExpr(ConstExprError),
+
+    /// constant `{0}` is defined more than once.
+    DuplicateConst(String),
+
+    /// value {0} is not a canonical element of the field the program is assembled for.
+    NonCanonical(fe256),
+}
+
+/// Instruction putting a constant which is not a canonical element of a field into a register.
+///
+/// Such instructions fail `CK` in the VM configured for the field, while they may succeed for
+/// fields of a larger order, making the program behavior field-dependent.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display("line {line}: value {value} is not a canonical field element")]
+pub struct NonCanonicalPut {
+    /// Index of the instruction in the program.
+    pub no: usize,
+    /// Source line of the instruction.
+    pub line: u32,
+    /// Non-canonical value.
+    pub value: fe256,
+}
+
+/// Named data placed into the data segment with `.const` and `.table` directives.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DataDecl {
+    /// A single field element, declared with `.const NAME = <value>` directive.
+    Const {
+        /** Name of the constant */
+        name: String,
+        /** Value of the constant */
+        value: fe256,
+    },
+
+    /// A sequence of field elements placed next to each other, declared with
+    /// `.table NAME = [<value>, ...]` directive.
+    Table {
+        /** Name of the table */
+        name: String,
+        /** Table elements */
+        values: Vec<fe256>,
+    },
+}
+
+impl DataDecl {
+    /// Returns the name of the constant or table.
+    pub fn name(&self) -> &str {
+        match self {
+            DataDecl::Const { name, .. } | DataDecl::Table { name, .. } => name,
+        }
+    }
+
+    /// Returns the values placed into the data segment.
+    pub fn values(&self) -> &[fe256] {
+        match self {
+            DataDecl::Const { value, .. } => core::slice::from_ref(value),
+            DataDecl::Table { values, .. } => values,
+        }
+    }
+}
+
+impl Display for DataDecl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DataDecl::Const { name, value } => write!(f, ".const {name} = {value}"),
+            DataDecl::Table { name, values } => {
+                write!(f, ".table {name} = [")?;
+                for (no, value) in values.iter().enumerate() {
+                    if no > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Display::fmt(value, f)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// Program assembled from a textual source.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Assembly {
+    /// Program instructions.
+    pub code: Vec<Instr<LibId>>,
+    /// Limits declared with `.assert_size` and `.assert_complexity` directives.
+    pub limits: Vec<AsmLimit>,
+    /// Constants and tables declared with `.const` and `.table` directives, in the order of their
+    /// declaration.
+    pub data: Vec<DataDecl>,
+    /// Labels defined in the program, with the offsets of the instructions they point to.
+    pub labels: BTreeMap<String, u16>,
+    /// Source map, linking each of the program instructions to the source line it was parsed
+    /// from.
+    pub source_map: SourceMap,
+}
+
+impl Assembly {
+    /// Parses program source text, resolving labels into code offsets.
+    ///
+    /// NB: This does not check limits declared by the program; use [`Self::check_limits`] for
+    /// that.
+    pub fn parse(source: &str) -> Result<Self, AsmError> { Self::parse_with(source, &AsmConfig::new()) }
+
+    /// Parses program source text, resolving labels into code offsets and selecting the code for
+    /// the conditional assembly according to the provided configuration.
+    ///
+    /// NB: This does not check limits declared by the program; use [`Self::check_limits`] for
+    /// that.
+    pub fn parse_with(source: &str, config: &AsmConfig) -> Result<Self, AsmError> {
+        let mut asm = Assembly::default();
+        let mut refs = Vec::<(usize, usize, String)>::new();
+        let mut offset = 0u16;
+        let mut consts = BTreeMap::new();
+        let mut tables = BTreeMap::new();
+        if let Some(order) = config.field_order() {
+            consts.insert(s!("P"), order);
+        }
+
+        for Line { no: line_no, text } in preprocess(source, config)? {
+            let err = |kind: AsmErrorKind| AsmError { line: line_no, kind };
+
+            let line = text.as_str();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(directive) = line.strip_prefix('.') {
+                let decl = match directive.split_once(char::is_whitespace) {
+                    Some(("const", arg)) => parse_const(arg.trim(), &consts, &tables).map_err(err)?,
+                    Some(("table", arg)) => parse_table(arg.trim(), &consts, &tables).map_err(err)?,
+                    _ => {
+                        asm.limits.push(parse_directive(directive).map_err(err)?);
+                        continue;
+                    }
+                };
+                let name = decl.name().to_owned();
+                if consts.contains_key(&name) || tables.contains_key(&name) {
+                    return Err(err(AsmErrorKind::DuplicateConst(name)));
+                }
+                match &decl {
+                    DataDecl::Const { value, .. } => consts.insert(name, value.to_u256()),
+                    DataDecl::Table { values, .. } => {
+                        tables.insert(name, values.iter().map(fe256::to_u256).collect());
+                        None
+                    }
+                };
+                asm.data.push(decl);
+                continue;
+            }
+
+            if let Some(label) = line.strip_suffix(':') {
+                let (label, nop) = match label.split_once(char::is_whitespace) {
+                    Some(("routine" | "proc" | "label" | "loop", name)) => (name.trim(), true),
+                    Some(_) => return Err(err(AsmErrorKind::InvalidLabel(label.to_owned()))),
+                    None => (label, false),
+                };
+                if !is_ident(label) {
+                    return Err(err(AsmErrorKind::InvalidLabel(label.to_owned())));
+                }
+                if asm.labels.insert(label.to_owned(), offset).is_some() {
+                    return Err(err(AsmErrorKind::DuplicateLabel(label.to_owned())));
+                }
+                if nop {
+                    asm.push_loc(offset, line_no).map_err(err)?;
+                    asm.code.push(Instr::Ctrl(CtrlInstr::Nop));
+                    offset = next_offset(offset, &CtrlInstr::<LibId>::Nop).map_err(err)?;
+                }
+                continue;
+            }
+
+            let instr = match Instr::<LibId>::from_str(line) {
+                Ok(instr) => instr,
+                Err(ParseInstrError::InvalidTarget(target)) if is_ident(&target) => {
+                    let template = line.replacen(&target, "0", 1);
+                    let instr = Instr::<LibId>::from_str(&template).map_err(|e| err(e.into()))?;
+                    refs.push((line_no, asm.code.len(), target));
+                    instr
+                }
+                Err(ParseInstrError::InvalidValue(value)) => {
+                    let invalid = || err(ParseInstrError::InvalidValue(value.clone()).into());
+                    let template = line.rsplit_once(',').map(|(head, _)| format!("{head}, 0"));
+                    // Constant expressions are allowed for the bound of `rng` and for the value of `put`
+                    let (reg, rng) = match template.map(|t| Instr::<LibId>::from_str(&t)) {
+                        Some(Ok(Instr::Gfa(FieldInstr::PutZ { dst }))) => (dst, false),
+                        Some(Ok(Instr::Gfa(FieldInstr::Rng { src, .. }))) => (src, true),
+                        _ => return Err(invalid()),
+                    };
+                    let val = match eval_const_with(&value, &consts, &tables) {
+                        Ok(val) => fe256::from(val),
+                        Err(ConstExprError::Syntax(_)) => return Err(invalid()),
+                        Err(e) => return Err(err(e.into())),
+                    };
+                    if rng {
+                        Instr::Gfa(FieldInstr::Rng { src: reg, bound: val })
+                    } else if asm.is_data_ref(&value) {
+                        Instr::Gfa(FieldInstr::PutD { dst: reg, data: val })
+                    } else {
+                        Instr::Gfa(FieldInstr::put(reg, val))
+                    }
+                }
+                Err(e) => return Err(err(e.into())),
+            };
+            if let (Some(order), Some(value)) = (config.field_order(), put_value(&instr)) {
+                if value.to_u256() >= order {
+                    return Err(err(AsmErrorKind::NonCanonical(value)));
+                }
+            }
+            asm.push_loc(offset, line_no).map_err(err)?;
+            offset = next_offset(offset, &instr).map_err(err)?;
+            asm.code.push(instr);
+        }
+
+        for (line, no, label) in refs {
+            let Some(pos) = asm.labels.get(&label).copied() else {
+                return Err(AsmError {
+                    line,
+                    kind: AsmErrorKind::UnknownLabel(label),
+                });
+            };
+            if let GotoTarget::Absolute(goto) = asm.code[no].local_goto_pos() {
+                *goto = pos;
+            }
+        }
+
+        Ok(asm)
+    }
+
+    /// Returns the list of limits declared by the program which are violated by its code.
+    pub fn violated_limits(&self) -> Vec<AsmLimit> {
+        self.limits
+            .iter()
+            .filter(|limit| !limit.is_satisfied(&self.code))
+            .copied()
+            .collect()
+    }
+
+    /// Checks all limits declared by the program.
+    ///
+    /// # Panics
+    ///
+    /// If any of the limits is violated.
+    pub fn check_limits(&self) {
+        for limit in &self.limits {
+            limit.check(&self.code);
+        }
+    }
+
+    /// Returns instructions putting constants which are not canonical elements of the field with
+    /// the given order.
+    ///
+    /// Programs parsed with [`AsmConfig::with_field`] are rejected if they contain such
+    /// instructions; this allows to detect them in the programs parsed without a field, or to
+    /// check a program against other fields it may be executed with.
+    pub fn non_canonical(&self, field_order: u256) -> Vec<NonCanonicalPut> {
+        self.code
+            .iter()
+            .enumerate()
+            .filter_map(|(no, instr)| {
+                let value = put_value(instr)?;
+                (value.to_u256() >= field_order).then(|| NonCanonicalPut {
+                    no,
+                    line: self
+                        .source_map
+                        .get(no)
+                        .map(|loc| loc.line)
+                        .unwrap_or_default(),
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns program instructions, discarding other assembly information.
+    pub fn into_code(self) -> Vec<Instr<LibId>> { self.code }
+
+    /// Assembles the program into a library.
+    ///
+    /// Unlike [`Lib::assemble`], this places the values declared with `.const` and `.table`
+    /// directives at the beginning of the data segment, in the order of their declaration. The
+    /// data used by the instructions follow; a value already present in the segment is never
+    /// repeated.
+    pub fn assemble(&self) -> Result<Lib, AssemblerError> {
+        let libs = LibsSeg::try_from_iter(self.code.iter().filter_map(Instr::external_ref))?;
+
+        let mut data = Vec::<u8>::new();
+        for decl in &self.data {
+            let bytes = decl
+                .values()
+                .iter()
+                .flat_map(|value| value.to_u256().to_le_bytes())
+                .collect::<Vec<_>>();
+            if data.windows(bytes.len()).any(|window| window == bytes) {
+                continue;
+            }
+            if data.len() + bytes.len() > u16::MAX as usize {
+                return Err(MarshallError::DataNotFittingSegment.into());
+            }
+            data.extend(bytes);
+        }
+
+        let mut writer = Marshaller::with(Vec::new(), data, &libs);
+        for instr in &self.code {
+            instr.encode_instr(&mut writer)?;
+        }
+        let (code, data) = writer.finish();
+
+        Ok(Lib {
+            isae: self
+                .code
+                .iter()
+                .filter_map(Instr::group)
+                .collect::<IsaGroups>()
+                .isa_ext(),
+            libs,
+            code,
+            data,
+        })
+    }
+
+    /// Detects whether a `put` instruction value is a direct reference to a constant or a table
+    /// element, which must be read from the data segment.
+    fn is_data_ref(&self, value: &str) -> bool {
+        let (name, index) = match value.strip_suffix(']').and_then(|v| v.split_once('[')) {
+            Some((name, index)) => (name.trim(), Some(index)),
+            None => (value, None),
+        };
+        let mut depth = 0usize;
+        let balanced = index.unwrap_or_default().chars().all(|c| {
+            match c {
+                '[' => depth += 1,
+                ']' if depth == 0 => return false,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            true
+        });
+        balanced
+            && self.data.iter().any(|decl| match decl {
+                DataDecl::Const { name: n, .. } => index.is_none() && n == name,
+                DataDecl::Table { name: n, .. } => index.is_some() && n == name,
+            })
+    }
+
+    fn push_loc(&mut self, pos: u16, line: usize) -> Result<(), AsmErrorKind> {
+        let line = u32::try_from(line).map_err(|_| AsmErrorKind::CodeOverflow)?;
+        self.source_map
+            .push(SourceLoc { pos, line })
+            .map_err(|_| AsmErrorKind::CodeOverflow)
+    }
+}
+
+/// Returns the constant put into a register by the instruction, if the constant doesn't depend on
+/// the field.
+fn put_value(instr: &Instr<LibId>) -> Option<fe256> {
+    match instr {
+        Instr::Gfa(FieldInstr::PutD { data, .. }) => Some(*data),
+        Instr::Gfa(FieldInstr::PutV { val, .. }) => val.to_fe256(),
+        _ => None,
+    }
+}
+
+impl FromStr for Assembly {
+    type Err = AsmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> { Self::parse(s) }
+}
+
+/// Mnemonics of all instructions known to the assembler.
+pub(super) const MNEMONICS: [&str; 28] = [
+    "nop", "chk", "not", "fail", "ret", "stop", "halt", "jmp", "jif", "call", "test", "clr", "put", "fits", "mov",
+    "eq", "neg", "add", "mul", "ecall", "inv", "exp", "sqr", "sqrt", "cmov", "sel", "lt", "rng",
+];
+
+fn next_offset<I: Bytecode<LibId>>(offset: u16, instr: &I) -> Result<u16, AsmErrorKind> {
+    offset
+        .checked_add(instr.code_byte_len())
+        .ok_or(AsmErrorKind::CodeOverflow)
+}
+
+pub(super) fn strip_comment(line: &str) -> &str {
+    let end = [line.find(';'), line.find("//")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+pub(super) fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_const(
+    arg: &str,
+    consts: &BTreeMap<String, u256>,
+    tables: &BTreeMap<String, Vec<u256>>,
+) -> Result<DataDecl, AsmErrorKind> {
+    let (name, value) = parse_decl(arg)?;
+    let value = fe256::from(eval_const_with(value, consts, tables)?);
+    Ok(DataDecl::Const { name, value })
+}
+
+fn parse_table(
+    arg: &str,
+    consts: &BTreeMap<String, u256>,
+    tables: &BTreeMap<String, Vec<u256>>,
+) -> Result<DataDecl, AsmErrorKind> {
+    let invalid = || AsmErrorKind::InvalidDirective(arg.to_owned());
+    let (name, list) = parse_decl(arg)?;
+    let list = list
+        .strip_prefix('[')
+        .and_then(|list| list.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let list = list.trim().strip_suffix(',').unwrap_or(list);
+    if list.trim().is_empty() {
+        return Err(invalid());
+    }
+    let values = list
+        .split(',')
+        .map(|value| eval_const_with(value, consts, tables).map(fe256::from))
+        .collect::<Result<_, _>>()?;
+    Ok(DataDecl::Table { name, values })
+}
+
+fn parse_decl(arg: &str) -> Result<(String, &str), AsmErrorKind> {
+    let invalid = || AsmErrorKind::InvalidDirective(arg.to_owned());
+    let (name, value) = arg.split_once('=').ok_or_else(invalid)?;
+    let name = name.trim();
+    if !is_ident(name) {
+        return Err(invalid());
+    }
+    Ok((name.to_owned(), value.trim()))
+}
+
+fn parse_directive(directive: &str) -> Result<AsmLimit, AsmErrorKind> {
+    let (name, arg) = directive
+        .split_once(char::is_whitespace)
+        .unwrap_or((directive, ""));
+    let arg = arg.trim();
+    let invalid = || AsmErrorKind::InvalidDirective(arg.to_owned());
+    let (inclusive, max) = if let Some(max) = arg.strip_prefix("<=") {
+        (true, max)
+    } else if let Some(max) = arg.strip_prefix('<') {
+        (false, max)
+    } else {
+        return Err(invalid());
+    };
+    let max = parse_u256(max.trim())
+        .filter(|max| *max <= u256::from(u64::MAX))
+        .ok_or_else(invalid)?
+        .low_u64();
+    match name {
+        "assert_size" => Ok(AsmLimit::Size { max, inclusive }),
+        "assert_complexity" => Ok(AsmLimit::Complexity { max, inclusive }),
+        _ => Err(AsmErrorKind::UnknownDirective(name.to_owned())),
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer, allowing `_` as a digit separator.
+fn parse_put(dst: RegE, s: &str) -> Result<FieldInstr, ParseInstrError> {
+    if let Ok(val) = ConstVal::from_str(s) {
+        return Ok(FieldInstr::PutV { dst, val });
+    }
+    let data = fe256::parse_literal(s).map_err(|_| ParseInstrError::InvalidValue(s.to_owned()))?;
+    if data == fe256::ZERO && !s.ends_with(".fe") {
+        return Ok(FieldInstr::PutZ { dst });
+    }
+    Ok(FieldInstr::PutD { dst, data })
+}
+
+fn parse_flag(s: &str) -> Result<bool, ParseInstrError> {
+    match s {
+        "CO" => Ok(true),
+        "CK" => Ok(false),
+        _ => Err(ParseInstrError::InvalidFlag(s.to_owned())),
+    }
+}
+
+enum Target<Id: SiteId> {
+    Pos(u16),
+    Shift(i8),
+    Site(Site<Id>),
+}
+
+fn parse_target<Id: SiteId>(s: &str) -> Result<Target<Id>, ParseInstrError> {
+    let err = || ParseInstrError::InvalidTarget(s.to_owned());
+    if let Some((id, pos)) = s.rsplit_once('@') {
+        let prog_id = Id::from_str(id).map_err(|_| err())?;
+        let offset = pos.parse().map_err(|_| err())?;
+        return Ok(Target::Site(Site::new(prog_id, offset)));
+    }
+    if s.starts_with(['+', '-']) {
+        return s.parse().map(Target::Shift).map_err(|_| err());
+    }
+    parse_u256(s)
+        .filter(|pos| *pos <= u256::from(u16::MAX))
+        .map(|pos| Target::Pos(pos.low_u64() as u16))
+        .ok_or_else(err)
+}
+
+fn parse_reserved(s: &str) -> Result<ReservedInstr, ParseInstrError> {
+    let err = || ParseInstrError::InvalidOpcode(s.to_owned());
+    let code = s
+        .strip_suffix(".h")
+        .and_then(|code| code.strip_prefix("0x"))
+        .ok_or_else(err)?;
+    let opcode = u8::from_str_radix(code, 16).map_err(|_| err())?;
+    let libs = LibsSeg::default();
+    let mut reader = Marshaller::with([0u8; 0], [0u8; 0], &libs);
+    match Instr::<LibId>::decode_operands(&mut reader, opcode) {
+        Ok(Instr::Reserved(instr)) => Ok(instr),
+        _ => Err(err()),
+    }
+}
+
+impl<Id: SiteId> FromStr for Instr<Id> {
+    type Err = ParseInstrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (mnemonic, ops) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let ops = ops
+            .split(',')
+            .map(str::trim)
+            .filter(|op| !op.is_empty())
+            .collect::<Vec<_>>();
+        let count_err = || ParseInstrError::OperandCount(mnemonic.to_owned(), ops.len());
+
+        let instr: Instr<Id> = match (mnemonic, ops.as_slice()) {
+            ("", _) => return Err(ParseInstrError::Empty),
+
+            ("nop", []) => CtrlInstr::Nop.into(),
+            ("chk", [flag]) => {
+                if parse_flag(flag)? {
+                    CtrlInstr::ChkCo.into()
+                } else {
+                    CtrlInstr::ChkCk.into()
+                }
+            }
+            ("not", ["CO"]) => CtrlInstr::NotCo.into(),
+            ("fail", ["CK"]) => CtrlInstr::FailCk.into(),
+            ("not" | "fail", [flag]) => return Err(ParseInstrError::InvalidFlag(flag.to_string())),
+            ("mov", ["CO", "CK"]) => CtrlInstr::RsetCk.into(),
+            ("ret", []) => CtrlInstr::Ret.into(),
+            ("stop", []) => CtrlInstr::Stop.into(),
+            ("halt", []) => ReservedInstr::default().into(),
+            ("halt", [code]) => parse_reserved(code)?.into(),
+            ("jmp", [target]) => match parse_target(target)? {
+                Target::Pos(pos) => CtrlInstr::Jmp { pos },
+                Target::Shift(shift) => CtrlInstr::Sh { shift },
+                Target::Site(site) => CtrlInstr::Exec { site },
+            }
+            .into(),
+            ("jif", [flag, target]) => match (parse_flag(flag)?, parse_target::<Id>(target)?) {
+                (true, Target::Pos(pos)) => CtrlInstr::JiOvfl { pos },
+                (false, Target::Pos(pos)) => CtrlInstr::JiFail { pos },
+                (true, Target::Shift(shift)) => CtrlInstr::ShOvfl { shift },
+                (false, Target::Shift(shift)) => CtrlInstr::ShFail { shift },
+                (_, Target::Site(_)) => return Err(ParseInstrError::InvalidTarget(target.to_string())),
+            }
+            .into(),
+            ("call", [target]) => match parse_target(target)? {
+                Target::Pos(pos) => CtrlInstr::Fn { pos },
+                Target::Site(site) => CtrlInstr::Call { site },
+                Target::Shift(_) => return Err(ParseInstrError::InvalidTarget(target.to_string())),
+            }
+            .into(),
+
+            ("test", [src]) => FieldInstr::Test {
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("clr", [dst]) => FieldInstr::Clr {
+                dst: RegE::from_str(dst)?,
+            }
+            .into(),
+            ("put", [dst, val]) => parse_put(RegE::from_str(dst)?, val)?.into(),
+            ("fits", [src, bits]) => FieldInstr::Fits {
+                src: RegE::from_str(src)?,
+                bits: Bits::from_str(bits)?,
+            }
+            .into(),
+            ("mov", [dst, src]) => FieldInstr::Mov {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("eq", [src1, src2]) => FieldInstr::Eq {
+                src1: RegE::from_str(src1)?,
+                src2: RegE::from_str(src2)?,
+            }
+            .into(),
+            ("lt", [src1, src2]) => FieldInstr::Lt {
+                src1: RegE::from_str(src1)?,
+                src2: RegE::from_str(src2)?,
+            }
+            .into(),
+            ("rng", [src, bound]) => FieldInstr::Rng {
+                src: RegE::from_str(src)?,
+                bound: fe256::parse_literal(bound).map_err(|_| ParseInstrError::InvalidValue(bound.to_string()))?,
+            }
+            .into(),
+            ("neg", [dst, src]) => FieldInstr::Neg {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("add", [dst_src, src]) => FieldInstr::Add {
+                dst_src: RegE::from_str(dst_src)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("mul", [dst_src, src]) => FieldInstr::Mul {
+                dst_src: RegE::from_str(dst_src)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("inv", [dst, src]) => FieldInstr::Inv {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("exp", [dst_src, exp]) if exp.starts_with(|c: char| c.is_ascii_digit()) => FieldInstr::ExpI {
+                dst_src: RegE::from_str(dst_src)?,
+                exp: u8::from_str(exp).map_err(|_| ParseInstrError::InvalidExponent(exp.to_string()))?,
+            }
+            .into(),
+            ("exp", [dst_src, exp]) => FieldInstr::Exp {
+                dst_src: RegE::from_str(dst_src)?,
+                exp: RegE::from_str(exp)?,
+            }
+            .into(),
+            ("sqr", [dst, src]) => FieldInstr::Sqr {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("sqrt", [dst, src]) => FieldInstr::Sqrt {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("cmov", [dst, src]) => FieldInstr::CMov {
+                dst: RegE::from_str(dst)?,
+                src: RegE::from_str(src)?,
+            }
+            .into(),
+            ("sel", [dst, src_true, src_false]) => FieldInstr::Sel {
+                dst: RegE::from_str(dst)?,
+                src_true: RegE::from_str(src_true)?,
+                src_false: RegE::from_str(src_false)?,
+            }
+            .into(),
+            ("ecall", [id]) => FieldInstr::Ecall {
+                id: u8::from_str(id).map_err(|_| ParseInstrError::InvalidSyscall(id.to_string()))?,
+            }
+            .into(),
+
+            (mnemonic, _) if MNEMONICS.contains(&mnemonic) => return Err(count_err()),
+            (mnemonic, _) => return Err(ParseInstrError::UnknownMnemonic(mnemonic.to_owned())),
+        };
+        Ok(instr)
+    }
+}
+
+impl Display for Assembly {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for decl in &self.data {
+            writeln!(f, "{decl}")?;
+        }
+        for limit in &self.limits {
+            match *limit {
+                AsmLimit::Size { max, inclusive } => {
+                    writeln!(f, ".assert_size {} {max}", if inclusive { "<=" } else { "<" })?
+                }
+                AsmLimit::Complexity { max, inclusive } => {
+                    writeln!(f, ".assert_complexity {} {max}", if inclusive { "<=" } else { "<" })?
+                }
+            }
+        }
+        for instr in &self.code {
+            writeln!(f, "{instr}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use amplify::num::{u2, u3};
+
+    use super::*;
+
+    const LIB_ID: &str = "5iMb1eHJ-bN5BOe6-9RvBjYL-jF1ELjj-VV7c8Bm-WvFen1Q";
+
+    fn roundtrip(instr: impl Into<Instr<LibId>>) {
+        let instr = instr.into();
+        let s = instr.to_string();
+        assert_eq!(Instr::<LibId>::from_str(&s).unwrap(), instr, "instruction `{s}`");
+    }
+
+    #[test]
+    fn field_instr_roundtrip() {
+        for reg1 in RegE::ALL {
+            roundtrip(FieldInstr::Test { src: reg1 });
+            roundtrip(FieldInstr::Clr { dst: reg1 });
+            roundtrip(FieldInstr::PutZ { dst: reg1 });
+            roundtrip(FieldInstr::PutD {
+                dst: reg1,
+                data: fe256::from(0xdeadcafe1badbeef_u64),
+            });
+            roundtrip(FieldInstr::PutD {
+                dst: reg1,
+                data: fe256::ZERO,
+            });
+            roundtrip(FieldInstr::PutD {
+                dst: reg1,
+                data: fe256::from(1u8),
+            });
+            for bound in [fe256::ZERO, fe256::from(1_000_000_000u32), fe256::from(u256::MAX)] {
+                roundtrip(FieldInstr::Rng { src: reg1, bound });
+            }
+            for val in 0..4 {
+                roundtrip(FieldInstr::PutV {
+                    dst: reg1,
+                    val: ConstVal::from(u2::with(val)),
+                });
+            }
+            for bits in 0..8 {
+                roundtrip(FieldInstr::Fits {
+                    src: reg1,
+                    bits: Bits::from(u3::with(bits)),
+                });
+            }
+            for reg2 in RegE::ALL {
+                roundtrip(FieldInstr::Mov { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::Eq { src1: reg1, src2: reg2 });
+                roundtrip(FieldInstr::Lt { src1: reg1, src2: reg2 });
+                roundtrip(FieldInstr::Neg { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::Inv { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::Sqr { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::Sqrt { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::CMov { dst: reg1, src: reg2 });
+                roundtrip(FieldInstr::Sel {
+                    dst: reg1,
+                    src_true: reg2,
+                    src_false: reg1,
+                });
+                roundtrip(FieldInstr::Add {
+                    dst_src: reg1,
+                    src: reg2,
+                });
+                roundtrip(FieldInstr::Mul {
+                    dst_src: reg1,
+                    src: reg2,
+                });
+                roundtrip(FieldInstr::Exp {
+                    dst_src: reg1,
+                    exp: reg2,
+                });
+            }
+            for exp in [0, 1, 5, u8::MAX] {
+                roundtrip(FieldInstr::ExpI { dst_src: reg1, exp });
+            }
+        }
+        for id in [0, 1, 0x80, 0xFF] {
+            roundtrip(FieldInstr::Ecall { id });
+        }
+    }
+
+    #[test]
+    fn ctrl_instr_roundtrip() {
+        let site = Site::new(LibId::from_str(LIB_ID).unwrap(), 42);
+        for instr in [
+            CtrlInstr::Nop,
+            CtrlInstr::ChkCo,
+            CtrlInstr::ChkCk,
+            CtrlInstr::NotCo,
+            CtrlInstr::FailCk,
+            CtrlInstr::RsetCk,
+            CtrlInstr::Jmp { pos: 12 },
+            CtrlInstr::JiOvfl { pos: 0xFFFF },
+            CtrlInstr::JiFail { pos: 0 },
+            CtrlInstr::Sh { shift: 5 },
+            CtrlInstr::ShOvfl { shift: -5 },
+            CtrlInstr::ShFail { shift: -128 },
+            CtrlInstr::Exec { site },
+            CtrlInstr::Fn { pos: 1 },
+            CtrlInstr::Call { site },
+            CtrlInstr::Ret,
+            CtrlInstr::Stop,
+        ] {
+            roundtrip(instr);
+        }
+        roundtrip(ReservedInstr::default());
+        assert_eq!(Instr::<LibId>::from_str("halt").unwrap(), Instr::Reserved(ReservedInstr::default()));
+        let reserved = Instr::<LibId>::from_str("halt 0x7F.h").unwrap();
+        assert_eq!(Bytecode::<LibId>::opcode_byte(&reserved), 0x7F);
+        roundtrip(reserved);
+    }
+
+    #[test]
+    fn put_values() {
+        let put = |s: &str| Instr::<LibId>::from_str(s).unwrap();
+        assert_eq!(put("put E1, 0"), FieldInstr::PutZ { dst: RegE::E1 }.into());
+        assert_eq!(
+            put("put E1, 1"),
+            FieldInstr::PutV {
+                dst: RegE::E1,
+                val: ConstVal::Val1
+            }
+            .into()
+        );
+        assert_eq!(
+            put("put E1, 1_000"),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(1000u16)
+            }
+            .into()
+        );
+        assert_eq!(
+            put("put E1, 0x1F"),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(31u8)
+            }
+            .into()
+        );
+        assert_eq!(
+            put("put E1, 1F.fe"),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(31u8)
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn instr_errors() {
+        let err = |s: &str| Instr::<LibId>::from_str(s).unwrap_err();
+        assert_eq!(err(""), ParseInstrError::Empty);
+        assert_eq!(err("foo E1"), ParseInstrError::UnknownMnemonic(s!("foo")));
+        assert_eq!(err("add E1"), ParseInstrError::OperandCount(s!("add"), 1));
+        assert_eq!(err("add E1, E9"), ParseInstrError::InvalidReg(ParseRegError(s!("E9"))));
+        assert_eq!(err("fits E1, 7.bits"), ParseInstrError::InvalidBits(ParseBitsError(s!("7.bits"))));
+        assert_eq!(err("put E1, x"), ParseInstrError::InvalidValue(s!("x")));
+        assert_eq!(err("chk CH"), ParseInstrError::InvalidFlag(s!("CH")));
+        assert_eq!(err("jmp START"), ParseInstrError::InvalidTarget(s!("START")));
+        assert_eq!(err("call +2"), ParseInstrError::InvalidTarget(s!("+2")));
+        assert_eq!(err("halt 0x40.h"), ParseInstrError::InvalidOpcode(s!("0x40.h")));
+        assert_eq!(err("ecall 256"), ParseInstrError::InvalidSyscall(s!("256")));
+        assert_eq!(err("ecall E1"), ParseInstrError::InvalidSyscall(s!("E1")));
+        assert_eq!(err("exp E1, 256"), ParseInstrError::InvalidExponent(s!("256")));
+        assert_eq!(err("ecall"), ParseInstrError::OperandCount(s!("ecall"), 0));
+    }
+
+    #[test]
+    fn program() {
+        let asm = Assembly::parse(
+            r#"
+            ; Test program
+            .assert_size < 1024
+            .assert_complexity <= 100_000_000
+
+            routine START:
+                put     E1, 0       ; initialize
+                put     E2, 1;
+            again:
+                add     E1, E2      // increment
+                fits    E1, 8.bits
+                jif     CO, again
+                call    START
+                jmp     end
+            end:
+                ret
+            "#,
+        )
+        .unwrap();
+        assert_eq!(asm.labels, bmap! { s!("START") => 0, s!("again") => 5, s!("end") => 18 });
+        assert_eq!(asm.limits, vec![
+            AsmLimit::Size {
+                max: 1024,
+                inclusive: false
+            },
+            AsmLimit::Complexity {
+                max: 100_000_000,
+                inclusive: true
+            }
+        ]);
+        assert_eq!(asm.code, vec![
+            CtrlInstr::Nop.into(),
+            FieldInstr::PutZ { dst: RegE::E1 }.into(),
+            FieldInstr::PutV {
+                dst: RegE::E2,
+                val: ConstVal::Val1
+            }
+            .into(),
+            FieldInstr::Add {
+                dst_src: RegE::E1,
+                src: RegE::E2
+            }
+            .into(),
+            FieldInstr::Fits {
+                src: RegE::E1,
+                bits: Bits::Bits8
+            }
+            .into(),
+            CtrlInstr::JiOvfl { pos: 5 }.into(),
+            CtrlInstr::Fn { pos: 0 }.into(),
+            CtrlInstr::Jmp { pos: 18 }.into(),
+            CtrlInstr::Ret.into(),
+        ]);
+        assert!(asm.violated_limits().is_empty());
+        asm.check_limits();
+
+        let reparsed = Assembly::from_str(&asm.to_string()).unwrap();
+        assert_eq!(reparsed.code, asm.code);
+        assert_eq!(reparsed.limits, asm.limits);
+    }
+
+    #[test]
+    fn program_errors() {
+        let err = |s: &str| Assembly::parse(s).unwrap_err();
+        assert_eq!(err("nop\nfoo"), AsmError {
+            line: 2,
+            kind: AsmErrorKind::Instr(ParseInstrError::UnknownMnemonic(s!("foo")))
+        });
+        assert_eq!(err("jmp start").kind, AsmErrorKind::UnknownLabel(s!("start")));
+        assert_eq!(err("a:\na:").kind, AsmErrorKind::DuplicateLabel(s!("a")));
+        assert_eq!(err("1a:").kind, AsmErrorKind::InvalidLabel(s!("1a")));
+        assert_eq!(err("func a:").kind, AsmErrorKind::InvalidLabel(s!("func a")));
+        assert_eq!(err(".assert_depth < 5").kind, AsmErrorKind::UnknownDirective(s!("assert_depth")));
+        assert_eq!(err(".assert_size 5").kind, AsmErrorKind::InvalidDirective(s!("5")));
+        assert_eq!(err("nop\n.assert_size < x\n").to_string(), "line 2: invalid directive argument `< x`.");
+
+        let asm = Assembly::parse(".assert_size < 2\nput E1, 0").unwrap();
+        assert_eq!(asm.violated_limits(), vec![AsmLimit::Size {
+            max: 2,
+            inclusive: false
+        }]);
+    }
+
+    #[test]
+    fn const_exprs() {
+        let source = r#"
+            put     E1, (1 << 64) - 1
+            put     E2, P - 5
+            put     E3, 2 * (3 + 4)
+            put     E4, P - P
+            put     E5, 0x10 | 1 << 8
+        "#;
+        let config = AsmConfig::with_field(crate::FIELD_ORDER_SECP);
+        let asm = Assembly::parse_with(source, &config).unwrap();
+        assert_eq!(asm.code, vec![
+            FieldInstr::PutV {
+                dst: RegE::E1,
+                val: ConstVal::ValU64Max
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E2,
+                data: fe256::from(crate::FIELD_ORDER_SECP - u256::from(5u8))
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E3,
+                data: fe256::from(14u8)
+            }
+            .into(),
+            FieldInstr::PutZ { dst: RegE::E4 }.into(),
+            FieldInstr::PutD {
+                dst: RegE::E5,
+                data: fe256::from(0x110u16)
+            }
+            .into(),
+        ]);
+
+        let err = |s: &str| Assembly::parse_with(s, &config).unwrap_err();
+        assert_eq!(Assembly::parse("put E2, P - 5").unwrap_err(), AsmError {
+            line: 1,
+            kind: AsmErrorKind::ConstExpr(ConstExprError::UnknownConst(s!("P")))
+        });
+        assert_eq!(err("put E1, 1 - 2").kind, AsmErrorKind::ConstExpr(ConstExprError::Overflow(s!("1 - 2"))));
+        assert_eq!(err("put E1, P / 0").kind, AsmErrorKind::ConstExpr(ConstExprError::DivZero(s!("P / 0"))));
+        assert_eq!(err("put E1, (1 +").kind, AsmErrorKind::Instr(ParseInstrError::InvalidValue(s!("(1 +"))));
+        assert_eq!(
+            err("add E1, 1 + 1").kind,
+            AsmErrorKind::Instr(ParseInstrError::InvalidReg(ParseRegError(s!("1 + 1"))))
+        );
+    }
+
+    #[test]
+    fn data_decls() {
+        let source = r#"
+            .const  GEN = 0x07
+            .table  POWERS = [1, GEN, GEN * GEN, GEN * GEN * GEN,]
+            .const  ONE = POWERS[0]
+                put     E1, GEN
+                put     E2, POWERS[2]
+                put     E3, GEN + 1
+                put     E4, ONE
+                put     E5, POWERS[POWERS[0]] - 6
+        "#;
+        let asm = Assembly::parse(source).unwrap();
+        let fe = |val: u16| fe256::from(val);
+        assert_eq!(asm.data, vec![
+            DataDecl::Const {
+                name: s!("GEN"),
+                value: fe(7)
+            },
+            DataDecl::Table {
+                name: s!("POWERS"),
+                values: vec![fe(1), fe(7), fe(49), fe(343)]
+            },
+            DataDecl::Const {
+                name: s!("ONE"),
+                value: fe(1)
+            },
+        ]);
+        assert_eq!(asm.code, vec![
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe(7)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E2,
+                data: fe(49)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E3,
+                data: fe(8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E4,
+                data: fe(1)
+            }
+            .into(),
+            FieldInstr::PutV {
+                dst: RegE::E5,
+                val: ConstVal::Val1
+            }
+            .into(),
+        ]);
+
+        let lib = asm.assemble().unwrap();
+        let data = [7u16, 1, 7, 49, 343, 8]
+            .into_iter()
+            .flat_map(|val| u256::from(val).to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(lib.data.as_slice(), data.as_slice());
+        assert_eq!(lib.disassemble::<Instr<LibId>>().unwrap(), asm.code);
+
+        let reparsed = Assembly::from_str(&asm.to_string()).unwrap();
+        assert_eq!(reparsed, Assembly {
+            source_map: reparsed.source_map.clone(),
+            ..asm
+        });
+    }
+
+    #[test]
+    fn data_decl_errors() {
+        let err = |s: &str| Assembly::parse(s).unwrap_err().kind;
+        assert_eq!(err(".const A = 1\n.table A = [1]"), AsmErrorKind::DuplicateConst(s!("A")));
+        assert_eq!(err(".const A = 1 + B"), AsmErrorKind::ConstExpr(ConstExprError::UnknownConst(s!("B"))));
+        assert_eq!(err(".const 1A = 1"), AsmErrorKind::InvalidDirective(s!("1A = 1")));
+        assert_eq!(err(".const A"), AsmErrorKind::InvalidDirective(s!("A")));
+        assert_eq!(err(".table T = []"), AsmErrorKind::InvalidDirective(s!("T = []")));
+        assert_eq!(err(".table T = 1, 2"), AsmErrorKind::InvalidDirective(s!("T = 1, 2")));
+        assert_eq!(
+            err(".table T = [1]\nput E1, T[1]"),
+            AsmErrorKind::ConstExpr(ConstExprError::OutOfBounds(s!("T"), u256::ONE))
+        );
+        let config = AsmConfig::with_field(crate::FIELD_ORDER_SECP);
+        assert_eq!(
+            Assembly::parse_with(".const P = 1", &config)
+                .unwrap_err()
+                .kind,
+            AsmErrorKind::DuplicateConst(s!("P"))
+        );
+    }
+
+    #[test]
+    fn non_canonical() {
+        let source = r#"
+            put     E1, 0xFFFFFFFF00000000
+            put     E2, 0xFFFFFFFF00000001
+            put     E3, 0xFFFFFFFFFFFFFFFF
+            put     E4, P
+        "#;
+        let config = AsmConfig::with_field(crate::FIELD_ORDER_GOLDILOCKS);
+        assert_eq!(Assembly::parse_with(source, &config).unwrap_err(), AsmError {
+            line: 3,
+            kind: AsmErrorKind::NonCanonical(fe256::from(crate::FIELD_ORDER_GOLDILOCKS))
+        });
+
+        let asm = Assembly::parse(&source.replace('P', "0")).unwrap();
+        assert_eq!(asm.non_canonical(crate::FIELD_ORDER_SECP), vec![]);
+        assert_eq!(asm.non_canonical(crate::FIELD_ORDER_GOLDILOCKS), vec![
+            NonCanonicalPut {
+                no: 1,
+                line: 3,
+                value: fe256::from(crate::FIELD_ORDER_GOLDILOCKS)
+            },
+            NonCanonicalPut {
+                no: 2,
+                line: 4,
+                value: fe256::from(u64::MAX)
+            },
+        ]);
+        assert_eq!(
+            asm.non_canonical(crate::FIELD_ORDER_BABYBEAR)
+                .into_iter()
+                .map(|put| put.no)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,461 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Programmatic construction of zk-AluVM programs.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::{Bytecode, CtrlInstr};
+use aluvm::{Site, SiteId};
+use amplify::num::u256;
+
+use super::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Errors detected by the [`ProgramBuilder`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum BuildError {
+    /// register {0} is read before it gets initialized.
+    Uninitialized(RegE),
+
+    /// value {0} doesn't fit into the finite field.
+    ValueOverflow(fe256),
+
+    /// program code exceeds maximal size of the code segment.
+    CodeOverflow,
+}
+
+/// Builder constructing programs from Rust code without a use of assembly macros.
+///
+/// The builder keeps track of the registers which were initialized by the previous instructions,
+/// and returns an error on attempts to read a register which doesn't have a value. The tracking is
+/// linear and doesn't follow jumps: for registers initialized by a code from a different branch or
+/// passed as routine arguments use [`ProgramBuilder::assume_init`].
+///
+/// Following zk-AluVM ABI, calls of other routines make registers `E1`-`E8` uninitialized.
+///
+/// # Example
+///
+/// ```
+/// # use zkaluvm::gfa::{Bits, BuildError, ProgramBuilder};
+/// # use zkaluvm::RegE::*;
+/// # use aluvm::LibId;
+/// # fn main() -> Result<(), BuildError> {
+/// let mut builder = ProgramBuilder::<LibId>::new();
+/// builder
+///     .put(E1, 2u8)?
+///     .put(E2, 3u8)?
+///     .add(E1, E2)?
+///     .fits(E1, Bits::Bits64)?
+///     .ret()?;
+/// assert_eq!(builder.add(E1, E3).err(), Some(BuildError::Uninitialized(E3)));
+/// let code = builder.finish();
+/// assert_eq!(code.len(), 5);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProgramBuilder<Id: SiteId> {
+    config: GfaConfig,
+    code: Vec<Instr<Id>>,
+    offset: u16,
+    init: [bool; 16],
+}
+
+impl<Id: SiteId> Default for ProgramBuilder<Id> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<Id: SiteId> ProgramBuilder<Id> {
+    /// Creates a builder for a VM with the default configuration.
+    pub fn new() -> Self { Self::with(GfaConfig::default()) }
+
+    /// Creates a builder for a VM with a specific configuration.
+    pub fn with(config: GfaConfig) -> Self {
+        Self {
+            config,
+            code: Vec::new(),
+            offset: 0,
+            init: [false; 16],
+        }
+    }
+
+    /// Returns the offset of the next instruction in the code segment, which can be used as a jump
+    /// target.
+    pub fn offset(&self) -> u16 { self.offset }
+
+    /// Returns the instructions added so far.
+    pub fn code(&self) -> &[Instr<Id>] { &self.code }
+
+    /// Checks whether a register is known to contain a value at the current position of the code.
+    pub fn is_init(&self, reg: RegE) -> bool { self.init[reg as usize] }
+
+    /// Marks register as initialized, for instance, when it holds a routine argument.
+    pub fn assume_init(&mut self, reg: RegE) -> &mut Self {
+        self.init[reg as usize] = true;
+        self
+    }
+
+    /// Completes the construction, returning the program code.
+    pub fn finish(self) -> Vec<Instr<Id>> { self.code }
+
+    /// Adds an arbitrary instruction to the program, checking its register use.
+    pub fn push(&mut self, instr: impl Into<Instr<Id>>) -> Result<&mut Self, BuildError> {
+        let instr = instr.into();
+        let (reads, writes, clears) = match instr {
+            Instr::Gfa(instr) => match instr {
+                FieldInstr::Test { .. } => (None, None, None),
+                FieldInstr::Clr { dst } => (None, None, Some(dst)),
+                FieldInstr::PutD { dst, data } => {
+                    if data.to_u256() >= self.config.field_order {
+                        return Err(BuildError::ValueOverflow(data));
+                    }
+                    (None, Some(dst), None)
+                }
+                FieldInstr::PutZ { dst } | FieldInstr::PutV { dst, .. } => (None, Some(dst), None),
+                FieldInstr::Fits { src, .. } | FieldInstr::Rng { src, .. } => (Some([src, src]), None, None),
+                FieldInstr::Mov { dst, src }
+                | FieldInstr::Neg { dst, src }
+                | FieldInstr::Inv { dst, src }
+                | FieldInstr::Sqr { dst, src }
+                | FieldInstr::Sqrt { dst, src } => (Some([src, src]), Some(dst), None),
+                FieldInstr::Eq { src1, src2 } | FieldInstr::Lt { src1, src2 } => (Some([src1, src2]), None, None),
+                FieldInstr::Add { dst_src, src }
+                | FieldInstr::Mul { dst_src, src }
+                | FieldInstr::Exp { dst_src, exp: src } => (Some([dst_src, src]), None, None),
+                FieldInstr::ExpI { dst_src, .. } => (Some([dst_src, dst_src]), None, None),
+                // The destination keeps its value if the move doesn't happen
+                FieldInstr::CMov { dst, src } => (Some([dst, src]), None, None),
+                FieldInstr::Sel { dst, src_true, src_false } => (Some([src_true, src_false]), Some(dst), None),
+                FieldInstr::Ecall { .. } => (None, None, None),
+            },
+            _ => (None, None, None),
+        };
+        if let Some(reg) = reads.into_iter().flatten().find(|reg| !self.is_init(*reg)) {
+            return Err(BuildError::Uninitialized(reg));
+        }
+        self.offset = self
+            .offset
+            .checked_add(instr.code_byte_len())
+            .ok_or(BuildError::CodeOverflow)?;
+        if let Some(reg) = writes {
+            self.init[reg as usize] = true;
+        }
+        if let Some(reg) = clears {
+            self.init[reg as usize] = false;
+        }
+        // Host syscalls may write or clear any register from their window, which is the same as the
+        // registers used for routine arguments and results
+        if let Instr::Ctrl(CtrlInstr::Fn { .. } | CtrlInstr::Call { .. } | CtrlInstr::Exec { .. })
+        | Instr::Gfa(FieldInstr::Ecall { .. }) = instr
+        {
+            self.init[..8].fill(false);
+        }
+        self.code.push(instr);
+        Ok(self)
+    }
+
+    /// Adds `nop` instruction.
+    pub fn nop(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Nop) }
+
+    /// Adds `chk CO` instruction.
+    pub fn chk_co(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::ChkCo) }
+
+    /// Adds `chk CK` instruction.
+    pub fn chk_ck(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::ChkCk) }
+
+    /// Adds `not CO` instruction.
+    pub fn not_co(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::NotCo) }
+
+    /// Adds `fail CK` instruction.
+    pub fn fail_ck(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::FailCk) }
+
+    /// Adds `mov CO, CK` instruction.
+    pub fn reset_ck(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::RsetCk) }
+
+    /// Adds unconditional jump to the code offset `pos`.
+    pub fn jmp(&mut self, pos: u16) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Jmp { pos }) }
+
+    /// Adds jump to the code offset `pos` taken if `CO` is in a failed state.
+    pub fn jif_co(&mut self, pos: u16) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::JiOvfl { pos }) }
+
+    /// Adds jump to the code offset `pos` taken if `CK` is in a failed state.
+    pub fn jif_ck(&mut self, pos: u16) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::JiFail { pos }) }
+
+    /// Adds call of a routine located at the code offset `pos`.
+    pub fn call(&mut self, pos: u16) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Fn { pos }) }
+
+    /// Adds call of an external routine.
+    pub fn call_site(&mut self, site: Site<Id>) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Call { site }) }
+
+    /// Adds `ret` instruction.
+    pub fn ret(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Ret) }
+
+    /// Adds `stop` instruction.
+    pub fn stop(&mut self) -> Result<&mut Self, BuildError> { self.push(CtrlInstr::Stop) }
+
+    /// Adds `test` instruction.
+    pub fn test(&mut self, src: RegE) -> Result<&mut Self, BuildError> { self.push(FieldInstr::Test { src }) }
+
+    /// Adds `clr` instruction.
+    pub fn clr(&mut self, dst: RegE) -> Result<&mut Self, BuildError> { self.push(FieldInstr::Clr { dst }) }
+
+    /// Adds instruction putting a value into a register, selecting the most compact encoding for
+    /// it.
+    pub fn put(&mut self, dst: RegE, val: impl Into<fe256>) -> Result<&mut Self, BuildError> {
+        let instr = put_instr(dst, val.into(), self.config.field_order);
+        self.push(instr)
+    }
+
+    /// Adds `fits` instruction.
+    pub fn fits(&mut self, src: RegE, bits: Bits) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Fits { src, bits })
+    }
+
+    /// Adds `mov` instruction.
+    pub fn mov(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Mov { dst, src })
+    }
+
+    /// Adds `eq` instruction.
+    pub fn eq(&mut self, src1: RegE, src2: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Eq { src1, src2 })
+    }
+
+    /// Adds `neg` instruction.
+    pub fn neg(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Neg { dst, src })
+    }
+
+    /// Adds `add` instruction.
+    pub fn add(&mut self, dst_src: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Add { dst_src, src })
+    }
+
+    /// Adds `mul` instruction.
+    pub fn mul(&mut self, dst_src: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Mul { dst_src, src })
+    }
+
+    /// Adds `inv` instruction.
+    pub fn inv(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Inv { dst, src })
+    }
+
+    /// Adds `exp` instruction.
+    pub fn exp(&mut self, dst_src: RegE, exp: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Exp { dst_src, exp })
+    }
+
+    /// Adds `exp` instruction with an immediate exponent.
+    pub fn expi(&mut self, dst_src: RegE, exp: u8) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::ExpI { dst_src, exp })
+    }
+
+    /// Adds `sqr` instruction.
+    pub fn sqr(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Sqr { dst, src })
+    }
+
+    /// Adds `sqrt` instruction.
+    pub fn sqrt(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Sqrt { dst, src })
+    }
+
+    /// Adds `cmov` instruction.
+    pub fn cmov(&mut self, dst: RegE, src: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::CMov { dst, src })
+    }
+
+    /// Adds `sel` instruction.
+    pub fn sel(&mut self, dst: RegE, src_true: RegE, src_false: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Sel { dst, src_true, src_false })
+    }
+
+    /// Adds `lt` instruction.
+    pub fn lt(&mut self, src1: RegE, src2: RegE) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Lt { src1, src2 })
+    }
+
+    /// Adds `rng` instruction.
+    pub fn rng(&mut self, src: RegE, bound: impl Into<fe256>) -> Result<&mut Self, BuildError> {
+        self.push(FieldInstr::Rng { src, bound: bound.into() })
+    }
+}
+
+/// Constructs an instruction putting a value into a register using the most compact encoding.
+pub(super) fn put_instr(dst: RegE, data: fe256, field_order: u256) -> FieldInstr {
+    let val = data.to_u256();
+    if val == u256::ZERO {
+        FieldInstr::PutZ { dst }
+    } else if let Some(val) = [ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max]
+        .into_iter()
+        .find(|c| c.to_fe256() == Some(data))
+    {
+        FieldInstr::PutV { dst, val }
+    } else if val.checked_add(u256::ONE) == Some(field_order) {
+        FieldInstr::PutV {
+            dst,
+            val: ConstVal::ValFeMAX,
+        }
+    } else {
+        FieldInstr::PutD { dst, data }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::RegE::*;
+
+    #[test]
+    fn build() {
+        let mut builder = ProgramBuilder::<LibId>::new();
+        builder
+            .put(E1, 0u8)
+            .unwrap()
+            .put(E2, 1u8)
+            .unwrap()
+            .put(E3, u64::MAX)
+            .unwrap()
+            .put(E4, 5u8)
+            .unwrap();
+        let start = builder.offset();
+        builder
+            .add(E1, E2)
+            .unwrap()
+            .mul(E3, E4)
+            .unwrap()
+            .fits(E1, Bits::Bits8)
+            .unwrap()
+            .jif_co(start)
+            .unwrap()
+            .ret()
+            .unwrap();
+        assert_eq!(builder.finish(), vec![
+            FieldInstr::PutZ { dst: E1 }.into(),
+            FieldInstr::PutV {
+                dst: E2,
+                val: ConstVal::Val1
+            }
+            .into(),
+            FieldInstr::PutV {
+                dst: E3,
+                val: ConstVal::ValU64Max
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: E4,
+                data: fe256::from(5u8)
+            }
+            .into(),
+            FieldInstr::Add { dst_src: E1, src: E2 }.into(),
+            FieldInstr::Mul { dst_src: E3, src: E4 }.into(),
+            FieldInstr::Fits {
+                src: E1,
+                bits: Bits::Bits8
+            }
+            .into(),
+            CtrlInstr::JiOvfl { pos: 10 }.into(),
+            CtrlInstr::Ret.into(),
+        ]);
+    }
+
+    #[test]
+    fn put_encoding() {
+        let fq = GfaConfig::default().field_order;
+        let mut builder = ProgramBuilder::<LibId>::new();
+        builder
+            .put(E1, u128::MAX)
+            .unwrap()
+            .put(E2, fq - u256::ONE)
+            .unwrap()
+            .put(E3, 2u8)
+            .unwrap();
+        assert_eq!(builder.code(), &[
+            FieldInstr::PutV {
+                dst: E1,
+                val: ConstVal::ValU128Max
+            }
+            .into(),
+            FieldInstr::PutV {
+                dst: E2,
+                val: ConstVal::ValFeMAX
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: E3,
+                data: fe256::from(2u8)
+            }
+            .into(),
+        ]);
+        assert_eq!(builder.put(E4, fq).err(), Some(BuildError::ValueOverflow(fe256::from(fq))));
+    }
+
+    #[test]
+    fn uninitialized() {
+        let mut builder = ProgramBuilder::<LibId>::new();
+        assert_eq!(builder.test(E1).unwrap().fits(E1, Bits::Bits8).err(), Some(BuildError::Uninitialized(E1)));
+        builder.put(E1, 1u8).unwrap();
+        assert_eq!(builder.add(E1, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.mov(E2, EA).err(), Some(BuildError::Uninitialized(EA)));
+        builder
+            .assume_init(EA)
+            .mov(E2, EA)
+            .unwrap()
+            .add(E1, E2)
+            .unwrap();
+        builder.clr(E2).unwrap();
+        assert_eq!(builder.neg(E3, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.inv(E3, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.exp(E1, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.expi(E2, 5).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.sqr(E3, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.sqrt(E3, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.cmov(E1, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.cmov(E3, EA).err(), Some(BuildError::Uninitialized(E3)));
+        assert_eq!(builder.sel(E3, EA, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.sel(E3, E2, EA).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.lt(EA, E2).err(), Some(BuildError::Uninitialized(E2)));
+        assert_eq!(builder.rng(E2, 10u8).err(), Some(BuildError::Uninitialized(E2)));
+        builder.call(0).unwrap();
+        assert!(!builder.is_init(E1));
+        assert!(builder.is_init(EA));
+        assert_eq!(builder.code().len(), 6);
+    }
+
+    #[test]
+    fn overflow() {
+        let mut builder = ProgramBuilder::<LibId>::new();
+        for _ in 0..u16::MAX {
+            builder.nop().unwrap();
+        }
+        assert_eq!(builder.nop().err(), Some(BuildError::CodeOverflow));
+    }
+}
@@ -34,7 +34,7 @@ impl FieldInstr {
     /// The initial value of the instruction op codes.
     pub const START: u8 = 64;
     /// The ending value of the instruction op codes.
-    pub const END: u8 = Self::MUL;
+    pub const END: u8 = Self::RNG;
 
     pub const SET: u8 = Self::START + 0;
     pub const TEST: u8 = Self::START + 0;
@@ -49,6 +49,16 @@ impl FieldInstr {
     pub const NEG: u8 = Self::START + 3;
     pub const ADD: u8 = Self::START + 4;
     pub const MUL: u8 = Self::START + 5;
+    pub const ECALL: u8 = Self::START + 6;
+    pub const INV: u8 = Self::START + 7;
+    pub const EXP: u8 = Self::START + 8;
+    pub const EXPI: u8 = Self::START + 9;
+    pub const SQR: u8 = Self::START + 10;
+    pub const SQRT: u8 = Self::START + 11;
+    pub const CMOV: u8 = Self::START + 12;
+    pub const SEL: u8 = Self::START + 13;
+    pub const LT: u8 = Self::START + 14;
+    pub const RNG: u8 = Self::START + 15;
 }
 
 const SUB_TEST: u8 = 0b_0000;
@@ -76,6 +86,16 @@ impl<Id: SiteId> Bytecode<Id> for FieldInstr {
             FieldInstr::Neg { .. } => Self::NEG,
             FieldInstr::Add { .. } => Self::ADD,
             FieldInstr::Mul { .. } => Self::MUL,
+            FieldInstr::Ecall { .. } => Self::ECALL,
+            FieldInstr::Inv { .. } => Self::INV,
+            FieldInstr::Exp { .. } => Self::EXP,
+            FieldInstr::ExpI { .. } => Self::EXPI,
+            FieldInstr::Sqr { .. } => Self::SQR,
+            FieldInstr::Sqrt { .. } => Self::SQRT,
+            FieldInstr::CMov { .. } => Self::CMOV,
+            FieldInstr::Sel { .. } => Self::SEL,
+            FieldInstr::Lt { .. } => Self::LT,
+            FieldInstr::Rng { .. } => Self::RNG,
         }
     }
 
@@ -92,6 +112,16 @@ impl<Id: SiteId> Bytecode<Id> for FieldInstr {
             FieldInstr::Neg { dst: _, src: _ } => 1,
             FieldInstr::Add { dst_src: _, src: _ } => 1,
             FieldInstr::Mul { dst_src: _, src: _ } => 1,
+            FieldInstr::Ecall { id: _ } => 1,
+            FieldInstr::Inv { dst: _, src: _ } => 1,
+            FieldInstr::Exp { dst_src: _, exp: _ } => 1,
+            FieldInstr::ExpI { dst_src: _, exp: _ } => 2,
+            FieldInstr::Sqr { dst: _, src: _ } => 1,
+            FieldInstr::Sqrt { dst: _, src: _ } => 1,
+            FieldInstr::CMov { dst: _, src: _ } => 1,
+            FieldInstr::Sel { dst: _, src_true: _, src_false: _ } => 2,
+            FieldInstr::Lt { src1: _, src2: _ } => 1,
+            FieldInstr::Rng { src: _, bound: _ } => 3,
         };
         arg_len + 1
     }
@@ -148,6 +178,41 @@ impl<Id: SiteId> Bytecode<Id> for FieldInstr {
                 writer.write_4bits(dst_src.to_u4())?;
                 writer.write_4bits(src.to_u4())?;
             }
+            FieldInstr::Ecall { id } => {
+                writer.write_byte(id)?;
+            }
+            FieldInstr::Inv { dst, src } => {
+                writer.write_4bits(dst.to_u4())?;
+                writer.write_4bits(src.to_u4())?;
+            }
+            FieldInstr::Exp { dst_src, exp } => {
+                writer.write_4bits(dst_src.to_u4())?;
+                writer.write_4bits(exp.to_u4())?;
+            }
+            FieldInstr::ExpI { dst_src, exp } => {
+                writer.write_4bits(dst_src.to_u4())?;
+                writer.write_4bits(u4::ZERO)?;
+                writer.write_byte(exp)?;
+            }
+            FieldInstr::Sqr { dst, src } | FieldInstr::Sqrt { dst, src } | FieldInstr::CMov { dst, src } => {
+                writer.write_4bits(dst.to_u4())?;
+                writer.write_4bits(src.to_u4())?;
+            }
+            FieldInstr::Sel { dst, src_true, src_false } => {
+                writer.write_4bits(dst.to_u4())?;
+                writer.write_4bits(src_true.to_u4())?;
+                writer.write_4bits(src_false.to_u4())?;
+                writer.write_4bits(u4::ZERO)?;
+            }
+            FieldInstr::Lt { src1, src2 } => {
+                writer.write_4bits(src1.to_u4())?;
+                writer.write_4bits(src2.to_u4())?;
+            }
+            FieldInstr::Rng { src, bound } => {
+                writer.write_4bits(src.to_u4())?;
+                writer.write_4bits(u4::ZERO)?;
+                writer.write_fixed(bound.to_u256().to_le_bytes())?;
+            }
         }
         Ok(())
     }
@@ -183,12 +248,13 @@ impl<Id: SiteId> Bytecode<Id> for FieldInstr {
                         let dst = RegE::from(reader.read_4bits()?);
                         FieldInstr::PutV { dst, val }
                     }
-                    x if x & MASK_FITS == TEST_FITS => {
+                    // All the remaining sub-operation codes have the `fits` bit set
+                    _ => {
+                        debug_assert_eq!(sub & MASK_FITS, TEST_FITS);
                         let bits = Bits::from(u3::with(sub & !MASK_FITS));
                         let src = RegE::from(reader.read_4bits()?);
                         FieldInstr::Fits { src, bits }
                     }
-                    _ => unreachable!(),
                 }
             }
             Self::MOV => {
@@ -216,7 +282,71 @@ impl<Id: SiteId> Bytecode<Id> for FieldInstr {
                 let src = RegE::from(reader.read_4bits()?);
                 FieldInstr::Mul { dst_src, src }
             }
-            _ => unreachable!(),
+            Self::ECALL => {
+                let id = reader.read_byte()?;
+                FieldInstr::Ecall { id }
+            }
+            Self::INV => {
+                let dst = RegE::from(reader.read_4bits()?);
+                let src = RegE::from(reader.read_4bits()?);
+                FieldInstr::Inv { dst, src }
+            }
+            Self::EXP => {
+                let dst_src = RegE::from(reader.read_4bits()?);
+                let exp = RegE::from(reader.read_4bits()?);
+                FieldInstr::Exp { dst_src, exp }
+            }
+            Self::EXPI => {
+                let dst_src = RegE::from(reader.read_4bits()?);
+                // Non-zero padding is rejected to keep the encoding unique
+                if reader.read_4bits()? != u4::ZERO {
+                    return Err(CodeEofError);
+                }
+                let exp = reader.read_byte()?;
+                FieldInstr::ExpI { dst_src, exp }
+            }
+            Self::SQR => {
+                let dst = RegE::from(reader.read_4bits()?);
+                let src = RegE::from(reader.read_4bits()?);
+                FieldInstr::Sqr { dst, src }
+            }
+            Self::SQRT => {
+                let dst = RegE::from(reader.read_4bits()?);
+                let src = RegE::from(reader.read_4bits()?);
+                FieldInstr::Sqrt { dst, src }
+            }
+            Self::CMOV => {
+                let dst = RegE::from(reader.read_4bits()?);
+                let src = RegE::from(reader.read_4bits()?);
+                FieldInstr::CMov { dst, src }
+            }
+            Self::SEL => {
+                let dst = RegE::from(reader.read_4bits()?);
+                let src_true = RegE::from(reader.read_4bits()?);
+                let src_false = RegE::from(reader.read_4bits()?);
+                // Non-zero padding is rejected to keep the encoding unique
+                if reader.read_4bits()? != u4::ZERO {
+                    return Err(CodeEofError);
+                }
+                FieldInstr::Sel { dst, src_true, src_false }
+            }
+            Self::LT => {
+                let src1 = RegE::from(reader.read_4bits()?);
+                let src2 = RegE::from(reader.read_4bits()?);
+                FieldInstr::Lt { src1, src2 }
+            }
+            Self::RNG => {
+                let src = RegE::from(reader.read_4bits()?);
+                // Non-zero padding is rejected to keep the encoding unique
+                if reader.read_4bits()? != u4::ZERO {
+                    return Err(CodeEofError);
+                }
+                let bound = reader.read_fixed(|d: [u8; 32]| fe256::from(u256::from_le_bytes(d)))?;
+                FieldInstr::Rng { src, bound }
+            }
+            // The opcode is outside of the GFA256 range. The trait doesn't allow returning a more
+            // specific error, and failing the decoding is preferred to a panic.
+            _ => return Err(CodeEofError),
         })
     }
 }
@@ -277,33 +407,17 @@ impl<Id: SiteId> Bytecode<Id> for Instr<Id> {
 #[cfg(test)]
 mod test {
     #![cfg_attr(coverage_nightly, coverage(off))]
-    use core::str::FromStr;
+    use alloc::vec::Vec;
 
     use aluvm::{LibId, LibsSeg, Marshaller};
     use amplify::confinement::SmallBlob;
 
     use super::*;
+    use crate::testing::{self, random_code, TestRng};
     use crate::RegE;
 
-    const LIB_ID: &str = "5iMb1eHJ-bN5BOe6-9RvBjYL-jF1ELjj-VV7c8Bm-WvFen1Q";
-
     fn roundtrip(instr: impl Into<Instr<LibId>>, bytecode: impl AsRef<[u8]>, dataseg: Option<&[u8]>) -> SmallBlob {
-        let instr = instr.into();
-        let mut libs = LibsSeg::new();
-        libs.push(LibId::from_str(LIB_ID).unwrap()).unwrap();
-        let mut marshaller = Marshaller::new(&libs);
-        instr.encode_instr(&mut marshaller).unwrap();
-        let (code, data) = marshaller.finish();
-        assert_eq!(code.as_slice(), bytecode.as_ref());
-        if let Some(d) = dataseg {
-            assert_eq!(data.as_slice(), d);
-        } else {
-            assert!(data.is_empty());
-        }
-        let mut marshaller = Marshaller::with(code, data, &libs);
-        let decoded = Instr::<LibId>::decode_instr(&mut marshaller).unwrap();
-        assert_eq!(decoded, instr);
-        marshaller.into_code_data().1
+        testing::roundtrip(instr.into(), bytecode, dataseg)
     }
 
     #[test]
@@ -408,6 +522,16 @@ mod test {
         }
     }
 
+    #[test]
+    fn fits_128_bits() {
+        // Bit dimension code 7 used to be decoded as 96 bits
+        let instr = Instr::<LibId>::Gfa(FieldInstr::Fits {
+            src: RegE::E1,
+            bits: Bits::Bits128,
+        });
+        roundtrip(instr, [64, 0b_0000_1111], None);
+    }
+
     #[test]
     fn mov() {
         for reg1 in RegE::ALL {
@@ -499,6 +623,180 @@ mod test {
         }
     }
 
+    #[test]
+    fn ecall() {
+        for id in 0..=u8::MAX {
+            let instr = Instr::<LibId>::Gfa(FieldInstr::Ecall { id });
+            roundtrip(instr, [FieldInstr::ECALL, id], None);
+
+            assert_eq!(instr.code_byte_len(), 2);
+            assert_eq!(instr.opcode_byte(), FieldInstr::ECALL);
+            assert_eq!(instr.external_ref(), None);
+        }
+    }
+
+    #[test]
+    fn inv() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::Inv { dst: reg1, src: reg2 });
+                let opcode = FieldInstr::INV;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::INV);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn exp() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::Exp {
+                    dst_src: reg1,
+                    exp: reg2,
+                });
+                let opcode = FieldInstr::EXP;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::EXP);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn expi() {
+        for reg in RegE::ALL {
+            for exp in 0..=u8::MAX {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::ExpI { dst_src: reg, exp });
+                roundtrip(instr, [FieldInstr::EXPI, reg.to_u4().to_u8(), exp], None);
+
+                assert_eq!(instr.code_byte_len(), 3);
+                assert_eq!(instr.opcode_byte(), FieldInstr::EXPI);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn sqr() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::Sqr { dst: reg1, src: reg2 });
+                let opcode = FieldInstr::SQR;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::SQR);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn sqrt() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::Sqrt { dst: reg1, src: reg2 });
+                let opcode = FieldInstr::SQRT;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::SQRT);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn cmov() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::CMov { dst: reg1, src: reg2 });
+                let opcode = FieldInstr::CMOV;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::CMOV);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn sel() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                for reg3 in RegE::ALL {
+                    let instr = Instr::<LibId>::Gfa(FieldInstr::Sel {
+                        dst: reg1,
+                        src_true: reg2,
+                        src_false: reg3,
+                    });
+                    let opcode = FieldInstr::SEL;
+                    let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                    roundtrip(instr, [opcode, regs, reg3.to_u4().to_u8()], None);
+
+                    assert_eq!(instr.code_byte_len(), 3);
+                    assert_eq!(instr.opcode_byte(), FieldInstr::SEL);
+                    assert_eq!(instr.external_ref(), None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lt() {
+        for reg1 in RegE::ALL {
+            for reg2 in RegE::ALL {
+                let instr = Instr::<LibId>::Gfa(FieldInstr::Lt { src1: reg1, src2: reg2 });
+                let opcode = FieldInstr::LT;
+                let regs = reg2.to_u4().to_u8() << 4 | reg1.to_u4().to_u8();
+
+                roundtrip(instr, [opcode, regs], None);
+
+                assert_eq!(instr.code_byte_len(), 2);
+                assert_eq!(instr.opcode_byte(), FieldInstr::LT);
+                assert_eq!(instr.external_ref(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn rng() {
+        for reg in RegE::ALL {
+            let val = u256::from(1_000_000_000u64);
+            let data = val.to_le_bytes();
+
+            let instr = Instr::<LibId>::Gfa(FieldInstr::Rng {
+                src: reg,
+                bound: fe256::from(val),
+            });
+            let opcode = FieldInstr::RNG;
+
+            roundtrip(instr, [opcode, reg.to_u4().to_u8(), 0, 0], Some(&data[..]));
+
+            assert_eq!(instr.code_byte_len(), 4);
+            assert_eq!(instr.opcode_byte(), FieldInstr::RNG);
+            assert_eq!(instr.external_ref(), None);
+        }
+    }
+
     #[test]
     fn reserved() {
         let instr = Instr::<LibId>::Reserved(default!());
@@ -508,4 +806,54 @@ mod test {
         assert_eq!(instr.opcode_byte(), 0xFF);
         assert_eq!(instr.external_ref(), None);
     }
+
+    #[test]
+    fn malformed() {
+        let libs = LibsSeg::default();
+        for opcode in 0..=u8::MAX {
+            for byte in 0..=u8::MAX {
+                for data in [&[][..], &[0xFF; 32][..]] {
+                    let mut reader = Marshaller::with([opcode, byte], data, &libs);
+                    let _ = Instr::<LibId>::decode_instr(&mut reader);
+                }
+            }
+        }
+
+        let mut reader = Marshaller::with([0u8; 2], [0u8; 0], &libs);
+        assert_eq!(<FieldInstr as Bytecode<LibId>>::decode_operands(&mut reader, 0), Err(CodeEofError));
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = TestRng::with(0);
+        for len in [0, 1, 16, 256] {
+            testing::roundtrip_code(&random_code::<Instr<LibId>>(&mut rng, len));
+        }
+    }
+
+    #[test]
+    fn decode_coverage() {
+        let coverage = testing::decode_coverage::<FieldInstr>(0..=0xFF);
+        assert_eq!(coverage.opcodes().collect::<Vec<_>>(), (FieldInstr::START..=FieldInstr::END).collect::<Vec<_>>());
+        // `put` from the data segment is rejected without the data segment offset
+        assert_eq!(coverage.decoded[&FieldInstr::SET], 2 * 0x100 - RegE::ALL.len());
+        // The immediate exponent doesn't fit the operand byte, and the padding must be zero
+        assert_eq!(coverage.decoded[&FieldInstr::EXPI], RegE::ALL.len());
+        // The third register of `sel` doesn't fit the operand byte, and the padding must be zero
+        assert_eq!(coverage.decoded[&FieldInstr::SEL], 0x100);
+        // `rng` requires the data segment offset, and the padding must be zero
+        assert_eq!(coverage.decoded[&FieldInstr::RNG], RegE::ALL.len());
+        assert!(coverage
+            .decoded
+            .iter()
+            .filter(|(opcode, _)| {
+                ![FieldInstr::SET, FieldInstr::EXPI, FieldInstr::SEL, FieldInstr::RNG].contains(opcode)
+            })
+            .all(|(_, count)| *count == 2 * 0x100));
+
+        // Control flow instructions are not covered since AluVM decodes library references outside
+        // of the library segment into the default library id.
+        let coverage = testing::decode_coverage::<Instr<LibId>>(FieldInstr::START..=0xFF);
+        assert_eq!(coverage.opcodes().count(), 0x100 - FieldInstr::START as usize);
+    }
 }
@@ -0,0 +1,308 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Constant expressions evaluated at assembly time.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::{self, Vec};
+use core::iter::Peekable;
+use core::str::FromStr;
+
+use amplify::num::u256;
+
+use crate::fe::parse_u256;
+use crate::fe256;
+
+/// Errors evaluating constant expressions.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ConstExprError {
+    /// invalid constant expression `{0}`.
+    Syntax(String),
+
+    /// unknown constant `{0}`.
+    UnknownConst(String),
+
+    /// arithmetic overflow in constant expression `{0}`.
+    Overflow(String),
+
+    /// division by zero in constant expression `{0}`.
+    DivZero(String),
+
+    /// index {1} is out of bounds of table `{0}`.
+    OutOfBounds(String, u256),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Token<'s> {
+    Num(u256),
+    Ident(&'s str),
+    Op(Op),
+    Open,
+    Close,
+    OpenIndex,
+    CloseIndex,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Shl,
+    Shr,
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    /// Operator precedence, following Rust rules.
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Mul | Op::Div | Op::Rem => 5,
+            Op::Add | Op::Sub => 4,
+            Op::Shl | Op::Shr => 3,
+            Op::And => 2,
+            Op::Xor => 1,
+            Op::Or => 0,
+        }
+    }
+
+    fn apply(self, a: u256, b: u256) -> Option<u256> {
+        let shift = |b: u256| (b < u256::from(256u16)).then(|| b.low_u32() as usize);
+        match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => a.checked_div(b),
+            Op::Rem => a.checked_rem(b),
+            Op::Shl => {
+                let bits = shift(b)?;
+                let res = a << bits;
+                (res >> bits == a).then_some(res)
+            }
+            Op::Shr => Some(a >> shift(b)?),
+            Op::And => Some(a & b),
+            Op::Or => Some(a | b),
+            Op::Xor => Some(a ^ b),
+        }
+    }
+}
+
+/// Evaluates a constant expression with unsigned 256-bit integer semantics.
+///
+/// Expressions consist of integer literals (decimal, `0x`-prefixed hexadecimal or hexadecimal with
+/// `.fe` suffix, all allowing `_` separators), named constants from the `consts` map, parentheses
+/// and binary operators `*`, `/`, `%`, `+`, `-`, `<<`, `>>`, `&`, `^`, `|`, which have the same
+/// precedence as in Rust.
+///
+/// Overflows, underflows, shifts by 256 bits or more and divisions by zero are reported as errors;
+/// the arithmetic never wraps.
+pub fn eval_const(expr: &str, consts: &BTreeMap<String, u256>) -> Result<u256, ConstExprError> {
+    eval_const_with(expr, consts, &BTreeMap::new())
+}
+
+/// Evaluates a constant expression, which may also access elements of the provided tables with
+/// `NAME[index]` syntax, where the index is a constant expression itself.
+pub(super) fn eval_const_with(
+    expr: &str,
+    consts: &BTreeMap<String, u256>,
+    tables: &BTreeMap<String, Vec<u256>>,
+) -> Result<u256, ConstExprError> {
+    let expr = expr.trim();
+    let tokens = tokenize(expr).ok_or_else(|| ConstExprError::Syntax(expr.to_owned()))?;
+    let mut eval = Evaluator {
+        expr,
+        consts,
+        tables,
+        tokens: tokens.into_iter().peekable(),
+    };
+    let val = eval.binary(0)?;
+    if eval.tokens.next().is_some() {
+        return Err(ConstExprError::Syntax(expr.to_owned()));
+    }
+    Ok(val)
+}
+
+fn tokenize(s: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = match c {
+            '(' => (Token::Open, 1),
+            ')' => (Token::Close, 1),
+            '[' => (Token::OpenIndex, 1),
+            ']' => (Token::CloseIndex, 1),
+            '+' => (Token::Op(Op::Add), 1),
+            '-' => (Token::Op(Op::Sub), 1),
+            '*' => (Token::Op(Op::Mul), 1),
+            '/' => (Token::Op(Op::Div), 1),
+            '%' => (Token::Op(Op::Rem), 1),
+            '&' => (Token::Op(Op::And), 1),
+            '|' => (Token::Op(Op::Or), 1),
+            '^' => (Token::Op(Op::Xor), 1),
+            '<' if rest.starts_with("<<") => (Token::Op(Op::Shl), 2),
+            '>' if rest.starts_with(">>") => (Token::Op(Op::Shr), 2),
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                if rest[end..].starts_with(".fe") {
+                    let lit = &rest[..end + 3];
+                    (Token::Num(fe256::from_str(lit).ok()?.to_u256()), lit.len())
+                } else if c.is_ascii_digit() {
+                    (Token::Num(parse_u256(&rest[..end])?), end)
+                } else {
+                    (Token::Ident(&rest[..end]), end)
+                }
+            }
+            _ => return None,
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Some(tokens)
+}
+
+struct Evaluator<'s, 'c> {
+    expr: &'s str,
+    consts: &'c BTreeMap<String, u256>,
+    tables: &'c BTreeMap<String, Vec<u256>>,
+    tokens: Peekable<vec::IntoIter<Token<'s>>>,
+}
+
+impl Evaluator<'_, '_> {
+    /// Evaluates a sequence of binary operations using precedence climbing.
+    fn binary(&mut self, min_precedence: u8) -> Result<u256, ConstExprError> {
+        let mut lhs = self.primary()?;
+        while let Some(Token::Op(op)) = self.tokens.peek().copied() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.tokens.next();
+            let rhs = self.binary(op.precedence() + 1)?;
+            lhs = op.apply(lhs, rhs).ok_or_else(|| match op {
+                Op::Div | Op::Rem => ConstExprError::DivZero(self.expr.to_owned()),
+                _ => ConstExprError::Overflow(self.expr.to_owned()),
+            })?;
+        }
+        Ok(lhs)
+    }
+
+    fn primary(&mut self) -> Result<u256, ConstExprError> {
+        match self.tokens.next() {
+            Some(Token::Num(num)) => Ok(num),
+            Some(Token::Ident(name)) if self.tokens.peek() == Some(&Token::OpenIndex) => {
+                self.tokens.next();
+                let index = self.binary(0)?;
+                if self.tokens.next() != Some(Token::CloseIndex) {
+                    return Err(ConstExprError::Syntax(self.expr.to_owned()));
+                }
+                let table = self
+                    .tables
+                    .get(name)
+                    .ok_or_else(|| ConstExprError::UnknownConst(name.to_owned()))?;
+                table
+                    .get(index.low_u64() as usize)
+                    .filter(|_| index < u256::from(table.len() as u64))
+                    .copied()
+                    .ok_or_else(|| ConstExprError::OutOfBounds(name.to_owned(), index))
+            }
+            Some(Token::Ident(name)) => self
+                .consts
+                .get(name)
+                .copied()
+                .ok_or_else(|| ConstExprError::UnknownConst(name.to_owned())),
+            Some(Token::Open) => {
+                let val = self.binary(0)?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(val),
+                    _ => Err(ConstExprError::Syntax(self.expr.to_owned())),
+                }
+            }
+            _ => Err(ConstExprError::Syntax(self.expr.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use super::*;
+
+    fn eval(expr: &str) -> Result<u256, ConstExprError> {
+        let mut consts = BTreeMap::new();
+        consts.insert(s!("P"), u256::from(101u8));
+        eval_const(expr, &consts)
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(u256::from(7u8)));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(u256::from(9u8)));
+        assert_eq!(eval("(1 << 64) - 1"), Ok(u256::from(u64::MAX)));
+        assert_eq!(eval("P - 5"), Ok(u256::from(96u8)));
+        assert_eq!(eval("17 / 5 + 17 % 5"), Ok(u256::from(5u8)));
+        assert_eq!(eval("1 << 4 | 1 << 2 & 0xff ^ 0x0f"), Ok(u256::from(0x1bu8)));
+        assert_eq!(eval("0x1_00 >> 4"), Ok(u256::from(16u8)));
+        assert_eq!(eval("ff.fe + 1"), Ok(u256::from(256u16)));
+        assert_eq!(eval("10 - 3 - 2"), Ok(u256::from(5u8)));
+        assert_eq!(eval("(1 << 255) - 1 + (1 << 255)"), Ok(u256::MAX));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval("0 - 1"), Err(ConstExprError::Overflow(s!("0 - 1"))));
+        assert_eq!(eval("1 << 256"), Err(ConstExprError::Overflow(s!("1 << 256"))));
+        assert_eq!(eval("3 << 255"), Err(ConstExprError::Overflow(s!("3 << 255"))));
+        assert_eq!(eval("1 / (P - 101)"), Err(ConstExprError::DivZero(s!("1 / (P - 101)"))));
+        assert_eq!(eval("Q + 1"), Err(ConstExprError::UnknownConst(s!("Q"))));
+        assert_eq!(eval("(1 + 2"), Err(ConstExprError::Syntax(s!("(1 + 2"))));
+        assert_eq!(eval("1 2"), Err(ConstExprError::Syntax(s!("1 2"))));
+        assert_eq!(eval("1 + !2"), Err(ConstExprError::Syntax(s!("1 + !2"))));
+        assert_eq!(eval(""), Err(ConstExprError::Syntax(s!(""))));
+    }
+
+    #[test]
+    fn tables() {
+        let consts = BTreeMap::new();
+        let mut tables = BTreeMap::new();
+        tables.insert(s!("T"), vec![u256::from(10u8), u256::from(20u8), u256::from(30u8)]);
+        let eval = |expr: &str| eval_const_with(expr, &consts, &tables);
+        assert_eq!(eval("T[0] + T[2]"), Ok(u256::from(40u8)));
+        assert_eq!(eval("T[T[0] / 5 - 1]"), Ok(u256::from(20u8)));
+        assert_eq!(eval("T[3]"), Err(ConstExprError::OutOfBounds(s!("T"), u256::from(3u8))));
+        assert_eq!(eval("T[1 << 64]"), Err(ConstExprError::OutOfBounds(s!("T"), u256::ONE << 64usize)));
+        assert_eq!(eval("U[0]"), Err(ConstExprError::UnknownConst(s!("U"))));
+        assert_eq!(eval("T"), Err(ConstExprError::UnknownConst(s!("T"))));
+        assert_eq!(eval("T[1"), Err(ConstExprError::Syntax(s!("T[1"))));
+        assert_eq!(eval("[1]"), Err(ConstExprError::Syntax(s!("[1]"))));
+    }
+}
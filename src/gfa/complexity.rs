@@ -0,0 +1,273 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use aluvm::isa::{CodeEofError, CtrlInstr, Instruction};
+use aluvm::{Lib, LibId};
+
+use super::analysis::{CodeOverflow, ControlFlow, Successor};
+use super::{FieldInstr, Instr};
+
+/// Errors estimating complexity of a library.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ComplexityError {
+    /// library code can't be decoded into zk-AluVM instructions.
+    #[from(CodeEofError)]
+    Decode,
+
+    /// library code exceeds maximal size of the code segment.
+    #[from(CodeOverflow)]
+    CodeOverflow,
+}
+
+/// Complexity of a basic block of a program.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BlockComplexity {
+    /// Range of indexes of the instructions forming the block.
+    pub instrs: Range<usize>,
+    /// Offset of the first instruction of the block in the code segment.
+    pub pos: u16,
+    /// Sum of complexities of the block instructions, not including complexity of the called
+    /// routines.
+    pub complexity: u64,
+}
+
+/// Report on the computational complexity of a library.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ComplexityReport {
+    /// Complexity of each of the basic blocks of the library code.
+    pub blocks: Vec<BlockComplexity>,
+    /// Worst-case complexity of the execution starting from each of the library entry points
+    /// (the beginning of the code, targets of the local calls and routines marked with `nop`).
+    ///
+    /// `None` values indicate that the complexity is unbounded (see [`Self::worst_case`]).
+    pub entry_points: BTreeMap<u16, Option<u64>>,
+    /// Worst-case complexity of the execution starting from the beginning of the code.
+    ///
+    /// The complexity is unbounded (`None`) if the execution may enter a loop, a recursive call,
+    /// a routine from an external library, a host syscall charging its own complexity or a jump
+    /// outside of the instruction boundaries.
+    pub worst_case: Option<u64>,
+    /// Sum of complexities of all library instructions.
+    pub total: u64,
+}
+
+/// Estimates computational complexity of a library code, allowing hosts to set a rational limit
+/// for the complexity of the execution (see [`aluvm::CoreConfig::complexity_lim`]).
+///
+/// The worst-case complexity is computed over all execution paths using the control flow of the
+/// program and [`Instruction::complexity`] values of the instructions, matching the complexity
+/// accumulated by the VM.
+pub fn estimate_complexity(lib: &Lib) -> Result<ComplexityReport, ComplexityError> {
+    let code = lib.disassemble::<Instr<LibId>>()?;
+    let cfg = ControlFlow::analyze(&code)?;
+
+    let blocks = cfg
+        .basic_blocks()
+        .into_iter()
+        .map(|instrs| BlockComplexity {
+            pos: cfg.offset(instrs.start),
+            complexity: code[instrs.clone()]
+                .iter()
+                .map(Instruction::complexity)
+                .sum(),
+            instrs,
+        })
+        .collect();
+
+    let worst = worst_case(&cfg);
+    let entry_points = cfg
+        .entry_points()
+        .iter()
+        .map(|no| (cfg.offset(*no), worst[*no]))
+        .collect();
+
+    Ok(ComplexityReport {
+        blocks,
+        entry_points,
+        worst_case: worst.first().copied().unwrap_or(Some(0)),
+        total: code.iter().map(Instruction::complexity).sum(),
+    })
+}
+
+/// Computes worst-case complexity of the execution starting at each of the instructions.
+fn worst_case(cfg: &ControlFlow<LibId>) -> Vec<Option<u64>> {
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    enum State {
+        New,
+        Visiting,
+        Done(Option<u64>),
+    }
+
+    let code = cfg.code();
+    let deps = |no: usize| {
+        let mut deps = cfg
+            .successors(no)
+            .iter()
+            .filter_map(|s| if let Successor::Instr(next) = s { Some(*next) } else { None })
+            .collect::<Vec<_>>();
+        deps.extend(cfg.call_target(no));
+        deps
+    };
+
+    let mut state = vec![State::New; code.len()];
+    for start in 0..code.len() {
+        let mut stack = vec![start];
+        while let Some(&no) = stack.last() {
+            match state[no] {
+                State::New => {
+                    state[no] = State::Visiting;
+                    for dep in deps(no) {
+                        match state[dep] {
+                            State::New => stack.push(dep),
+                            // Loop or recursion
+                            State::Visiting => state[dep] = State::Done(None),
+                            State::Done(_) => {}
+                        }
+                    }
+                }
+                State::Visiting => {
+                    stack.pop();
+                    let unbounded = matches!(
+                        code[no],
+                        Instr::Ctrl(CtrlInstr::Call { .. } | CtrlInstr::Exec { .. })
+                            | Instr::Gfa(FieldInstr::Ecall { .. })
+                    ) || cfg.successors(no).contains(&Successor::Unknown);
+                    let mut cost = Some(code[no].complexity());
+                    if unbounded {
+                        cost = None;
+                    }
+                    if let Some(callee) = cfg.call_target(no) {
+                        cost = add(cost, value(state[callee]));
+                    }
+                    let next = cfg
+                        .successors(no)
+                        .iter()
+                        .map(|s| match s {
+                            Successor::Instr(next) => value(state[*next]),
+                            Successor::Exit => Some(0),
+                            Successor::Unknown => None,
+                        })
+                        .try_fold(0u64, |max, val| val.map(|val| max.max(val)));
+                    state[no] = State::Done(add(cost, next));
+                }
+                State::Done(_) => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    fn value(state: State) -> Option<u64> {
+        match state {
+            State::Done(val) => val,
+            // A dependency still being visited means a loop
+            State::New | State::Visiting => None,
+        }
+    }
+    fn add(a: Option<u64>, b: Option<u64>) -> Option<u64> { a?.checked_add(b?) }
+
+    state.into_iter().map(value).collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+
+    fn estimate(code: &[Instr<LibId>]) -> ComplexityReport {
+        let lib = Lib::assemble(code).unwrap();
+        estimate_complexity(&lib).unwrap()
+    }
+
+    #[test]
+    fn linear() {
+        let code = zk_aluasm! {
+            put     E1, 0;          // 0 @0
+            put     E2, 1;          // 1 @2
+            fits    E1, 8.bits;     // 2 @6
+            jif     CO, 13;         // 3 @8
+            add     E1, E2;         // 4 @11
+            mul     E1, E2;         // 5 @13
+            ret;                    // 6 @15
+        };
+        let report = estimate(&code);
+        let complexity = |no: usize| code[no].complexity();
+        let total = (0..7).map(complexity).sum::<u64>();
+        assert_eq!(report.total, total);
+        assert_eq!(report.worst_case, Some(total));
+        assert_eq!(report.blocks.len(), 3);
+        assert_eq!(report.blocks[2], BlockComplexity {
+            instrs: 5..7,
+            pos: 13,
+            complexity: complexity(5) + complexity(6),
+        });
+        assert_eq!(report.entry_points, bmap! { 0 => Some(total) });
+    }
+
+    #[test]
+    fn calls() {
+        let code = zk_aluasm! {
+            call    7;              // 0 @0
+            call    7;              // 1 @3
+            stop;                   // 2 @6
+            nop;                    // 3 @7
+            add     E1, E2;         // 4 @8
+            ret;                    // 5 @10
+        };
+        let report = estimate(&code);
+        let complexity = |no: usize| code[no].complexity();
+        let routine = complexity(3) + complexity(4) + complexity(5);
+        assert_eq!(report.worst_case, Some(complexity(0) + complexity(1) + complexity(2) + routine * 2));
+        assert_eq!(report.entry_points, bmap! { 0 => report.worst_case, 7 => Some(routine) });
+    }
+
+    #[test]
+    fn unbounded() {
+        let code = zk_aluasm! {
+            put     E1, 0;          // 0 @0
+            nop;                    // 1 @2
+            add     E1, E1;         // 2 @3
+            jmp     2;              // 3 @5
+        };
+        let report = estimate(&code);
+        assert_eq!(report.worst_case, None);
+        assert_eq!(report.entry_points, bmap! { 0 => None, 2 => None });
+
+        let code = zk_aluasm! {
+            call    0;
+        };
+        assert_eq!(estimate(&code).worst_case, None);
+
+        let code = zk_aluasm! {
+            ecall   1;
+        };
+        assert_eq!(estimate(&code).worst_case, None);
+    }
+}
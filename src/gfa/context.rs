@@ -0,0 +1,351 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Host context of the instruction execution.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use aluvm::regs::Status;
+use aluvm::CoreExt;
+use amplify::num::u256;
+
+use crate::{fe256, GfaCore, RegE};
+
+/// Registers accessible to the host syscall handlers (see [`GfaContext::ecall`]).
+///
+/// Following the zk-AluVM ABI (see [`RegE`]), syscalls receive their arguments and return their
+/// results in the same registers as routine calls.
+pub const ECALL_WINDOW: [RegE; 8] = [
+    RegE::EA,
+    RegE::EB,
+    RegE::EC,
+    RegE::ED,
+    RegE::EE,
+    RegE::EF,
+    RegE::EG,
+    RegE::EH,
+];
+
+/// Errors writing registers from a host syscall handler.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum EcallError {
+    /// register {0} is outside of the host syscall register window.
+    OutOfWindow(RegE),
+
+    /// value {0} is not an element of the field.
+    NonCanonical(fe256),
+}
+
+/// Register window provided to a host syscall handler (see [`GfaContext::ecall`]).
+///
+/// The handler reads the registers from [`ECALL_WINDOW`] and writes results back to them; the
+/// writes are buffered and applied to the core only if the handler succeeds.
+#[derive(Clone, Debug)]
+pub struct EcallFrame<'core> {
+    core: &'core GfaCore,
+    writes: BTreeMap<RegE, Option<fe256>>,
+    complexity: u64,
+}
+
+impl<'core> EcallFrame<'core> {
+    pub(crate) fn new(core: &'core GfaCore) -> Self {
+        Self {
+            core,
+            writes: none!(),
+            complexity: 0,
+        }
+    }
+
+    /// Returns the order of the field the VM operates on.
+    pub fn fq(&self) -> u256 { self.core.fq() }
+
+    /// Returns the value of a register from the window, including the values written by the
+    /// handler.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the register has no value or is outside of the window.
+    pub fn get(&self, reg: RegE) -> Option<fe256> {
+        if !ECALL_WINDOW.contains(&reg) {
+            return None;
+        }
+        match self.writes.get(&reg) {
+            Some(val) => *val,
+            None => self.core.get(reg),
+        }
+    }
+
+    /// Writes a value to a register from the window.
+    ///
+    /// # Errors
+    ///
+    /// If the register is outside of the window, or the value is not less than the field order.
+    pub fn set(&mut self, reg: RegE, val: fe256) -> Result<(), EcallError> {
+        if !self.core.is_canonical(val) {
+            return Err(EcallError::NonCanonical(val));
+        }
+        self.write(reg, Some(val))
+    }
+
+    /// Clears a register from the window.
+    ///
+    /// # Errors
+    ///
+    /// If the register is outside of the window.
+    pub fn clr(&mut self, reg: RegE) -> Result<(), EcallError> { self.write(reg, None) }
+
+    /// Charges the computational complexity of the syscall, which is added to the complexity of
+    /// the `ecall` instruction.
+    pub fn charge(&mut self, complexity: u64) { self.complexity = self.complexity.saturating_add(complexity); }
+
+    /// Returns the complexity charged by the handler so far.
+    pub fn complexity(&self) -> u64 { self.complexity }
+
+    fn write(&mut self, reg: RegE, val: Option<fe256>) -> Result<(), EcallError> {
+        if !ECALL_WINDOW.contains(&reg) {
+            return Err(EcallError::OutOfWindow(reg));
+        }
+        self.writes.insert(reg, val);
+        Ok(())
+    }
+
+    /// Returns the buffered register writes and the charged complexity.
+    pub(crate) fn into_parts(self) -> (BTreeMap<RegE, Option<fe256>>, u64) { (self.writes, self.complexity) }
+}
+
+/// Context provided by the host to the execution of zk-AluVM instructions.
+///
+/// The context is passed by reference to [`aluvm::Vm::exec`] and to each of the executed
+/// instructions, allowing hosts to provide oracles, witness sources and configuration to the
+/// instructions. All the methods have default implementations, so hosts implement only the ones
+/// they need; the unit type `()` is the no-op context (see [`NO_CONTEXT`]).
+///
+/// The context is accessible through a shared reference, so hosts which need to record data during
+/// the execution should use interior mutability.
+pub trait GfaContext {
+    /// Handles execution of a reserved instruction, allowing hosts to emulate opcodes introduced by
+    /// newer ISA revisions, or to record them.
+    ///
+    /// Reserved instructions are decoded as a single opcode byte, so the emulated instructions
+    /// can't have operands; the handler may access the registers of the core instead.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the opcode is not handled, in which case the instruction fails `CK`, as it does
+    /// without a handler. Otherwise, the status of the emulated instruction, with
+    /// [`Status::Fail`] failing `CK`.
+    fn trap(&self, _opcode: u8, _core: &mut GfaCore) -> Option<Status> { None }
+
+    /// Handles a host syscall made with `ecall` instruction.
+    ///
+    /// The handler accesses the registers through the `frame`, which also accounts for the
+    /// complexity of the syscall. The default implementation doesn't handle any syscalls.
+    ///
+    /// # Returns
+    ///
+    /// Status of the syscall: with [`Status::Ok`] the register writes are applied to the core;
+    /// with [`Status::Fail`] they are discarded, and the instruction fails `CK`.
+    fn ecall(&self, _id: u8, _frame: &mut EcallFrame) -> Status { Status::Fail }
+}
+
+impl GfaContext for () {}
+
+/// No-op execution context, for hosts which don't provide any.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibSite, Vm};
+/// # use zkaluvm::gfa::{Instr, NO_CONTEXT};
+/// # use zkaluvm::zk_aluasm;
+/// let lib = Lib::assemble::<Instr<_>>(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut vm = Vm::<Instr<_>>::new();
+/// let status = vm.exec(LibSite::new(lib.lib_id(), 0), &NO_CONTEXT, |_| Some(&lib));
+/// assert!(status.is_ok());
+/// ```
+pub const NO_CONTEXT: &dyn GfaContext = &();
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    use aluvm::isa::{Bytecode, ReservedInstr};
+    use aluvm::{CoreConfig, Lib, LibId, LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Instr;
+    use crate::{fe256, GfaConfig, RegE};
+
+    struct Host;
+
+    impl GfaContext for Host {}
+
+    /// Emulates the default reserved opcode as doubling of `E1`, recording all trapped opcodes.
+    #[derive(Default)]
+    struct Emulator(RefCell<Vec<u8>>);
+
+    impl GfaContext for Emulator {
+        fn trap(&self, opcode: u8, core: &mut GfaCore) -> Option<Status> {
+            self.0.borrow_mut().push(opcode);
+            (opcode == Bytecode::<LibId>::opcode_byte(&ReservedInstr::default()))
+                .then(|| core.add_mod(RegE::E1, RegE::E1))
+        }
+    }
+
+    /// Host syscalls: `1` copies `EA` to `EB`; `2` clears `EA` and fails; `3` writes `E1`; `4`
+    /// charges the maximal complexity.
+    struct Syscalls;
+
+    impl GfaContext for Syscalls {
+        fn ecall(&self, id: u8, frame: &mut EcallFrame) -> Status {
+            match id {
+                1 => {
+                    frame.charge(1000);
+                    let Some(val) = frame.get(RegE::EA) else {
+                        return Status::Fail;
+                    };
+                    frame.set(RegE::EB, val).unwrap();
+                    assert_eq!(frame.get(RegE::EB), Some(val));
+                    Status::Ok
+                }
+                2 => {
+                    frame.clr(RegE::EA).unwrap();
+                    assert_eq!(frame.get(RegE::EA), None);
+                    Status::Fail
+                }
+                3 => match frame.set(RegE::E1, fe256::ZERO) {
+                    Ok(()) => Status::Ok,
+                    Err(_) => Status::Fail,
+                },
+                4 => {
+                    frame.charge(u64::MAX);
+                    Status::Ok
+                }
+                _ => Status::Fail,
+            }
+        }
+    }
+
+    #[test]
+    fn host_context() {
+        let lib = Lib::assemble::<Instr<LibId>>(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let host: &dyn GfaContext = &Host;
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &host, |_| Some(&lib));
+        assert!(status.is_ok());
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(49u8)));
+    }
+
+    #[test]
+    fn trap() {
+        let reserved = Instr::Reserved(ReservedInstr::default());
+        let lib = Lib::assemble::<Instr<LibId>>(&[
+            zk_aluasm! { put E1, 7; }[0],
+            reserved,
+            reserved,
+            zk_aluasm! { put E2, 1; }[0],
+        ])
+        .unwrap();
+        let site = LibSite::new(lib.lib_id(), 0);
+
+        let emulator = Emulator::default();
+        let host: &dyn GfaContext = &emulator;
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert!(vm.exec(site, &host, |_| Some(&lib)).is_ok());
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(28u8)));
+        assert_eq!(vm.core.cx.get(RegE::E2), Some(fe256::from(1u8)));
+        assert_eq!(emulator.0.borrow().as_slice(), &[0xFF, 0xFF]);
+
+        // Failures of the emulated instructions fail CK, as do reserved instructions without a
+        // handler
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let lib = Lib::assemble::<Instr<LibId>>(&[reserved]).unwrap();
+        let site = LibSite::new(lib.lib_id(), 0);
+        assert!(!vm.exec(site, &host, |_| Some(&lib)).is_ok());
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert!(!vm.exec(site, &NO_CONTEXT, |_| Some(&lib)).is_ok());
+        assert_eq!(emulator.0.borrow().len(), 3);
+    }
+
+    #[test]
+    fn ecall() {
+        let run = |code: &[Instr<LibId>], host: &dyn GfaContext, complexity_lim: Option<u64>| {
+            let lib = Lib::assemble::<Instr<LibId>>(code).unwrap();
+            let mut vm = Vm::<Instr<LibId>>::with(
+                CoreConfig {
+                    halt: true,
+                    complexity_lim,
+                },
+                default!(),
+            );
+            let status = vm.exec(LibSite::new(lib.lib_id(), 0), &host, |_| Some(&lib));
+            (status, vm)
+        };
+        let seven = Some(fe256::from(7u8));
+
+        let (status, vm) = run(&zk_aluasm! { put EA, 7; ecall 1; }, &Syscalls, None);
+        assert!(status.is_ok());
+        assert_eq!(vm.core.cx.get(RegE::EB), seven);
+
+        // Writes of failed syscalls are discarded
+        let (status, vm) = run(&zk_aluasm! { put EA, 7; ecall 2; }, &Syscalls, None);
+        assert!(!status.is_ok());
+        assert_eq!(vm.core.cx.get(RegE::EA), seven);
+
+        let (status, _) = run(&zk_aluasm! { ecall 3; }, &Syscalls, None);
+        assert!(!status.is_ok());
+        let (status, _) = run(&zk_aluasm! { ecall 1; }, NO_CONTEXT, None);
+        assert!(!status.is_ok());
+
+        // The charged complexity is checked against the limit
+        let (status, _) = run(&zk_aluasm! { put EA, 7; ecall 1; }, &Syscalls, Some(1_000_000));
+        assert!(status.is_ok());
+        let (status, vm) = run(&zk_aluasm! { ecall 4; put E1, 7; }, &Syscalls, Some(u64::MAX));
+        assert!(!status.is_ok());
+        assert_eq!(vm.core.cx.get(RegE::E1), None);
+    }
+
+    #[test]
+    fn ecall_frame() {
+        let mut core = GfaCore::with(GfaConfig::default());
+        core.set(RegE::EH, fe256::from(1u8));
+        core.set(RegE::E8, fe256::from(2u8));
+        let mut frame = EcallFrame::new(&core);
+        assert_eq!(frame.fq(), core.fq());
+        assert_eq!(frame.get(RegE::EH), Some(fe256::from(1u8)));
+        assert_eq!(frame.get(RegE::E8), None);
+        assert_eq!(frame.set(RegE::E8, fe256::ZERO), Err(EcallError::OutOfWindow(RegE::E8)));
+        assert_eq!(frame.clr(RegE::E8), Err(EcallError::OutOfWindow(RegE::E8)));
+        let fq = fe256::from(core.fq());
+        assert_eq!(frame.set(RegE::EA, fq), Err(EcallError::NonCanonical(fq)));
+        frame.charge(10);
+        frame.charge(u64::MAX);
+        assert_eq!(frame.complexity(), u64::MAX);
+        frame.clr(RegE::EH).unwrap();
+        assert_eq!(frame.into_parts(), (bmap! { RegE::EH => None }, u64::MAX));
+    }
+}
@@ -0,0 +1,424 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Decompilation of straight-line GFA256 code into arithmetic expressions.
+//!
+//! This is the inverse of [`ExprCompiler`](super::ExprCompiler): it reconstructs a graph of the
+//! arithmetic operations performed by a program, such that the value of each register at the end
+//! of the program can be read as an expression over the register values at its start.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::CtrlInstr;
+use aluvm::SiteId;
+use amplify::num::u256;
+
+use super::{Expr, FieldInstr, Instr};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Errors decompiling a program.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum DecompileError {
+    /// instruction #{0} transfers control or is not a field arithmetic instruction; only
+    /// straight-line code can be decompiled.
+    NotStraightLine(usize),
+
+    /// instruction #{no} reads register {reg}, which was cleared by a preceding instruction.
+    Cleared {
+        /** Index of the instruction in the program */
+        no: usize,
+        /** Register cleared by a preceding instruction */
+        reg: RegE,
+    },
+
+    /// instruction #{0} performs an operation which has no representation in the expression graph.
+    Unsupported(usize),
+}
+
+/// Identifier of a node in an [`ExprGraph`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display("n{0}")]
+pub struct NodeId(pub(super) usize);
+
+impl NodeId {
+    /// Index of the node in [`ExprGraph::nodes`].
+    pub const fn index(self) -> usize { self.0 }
+}
+
+/// Node of an [`ExprGraph`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum ExprNode {
+    /// Value of a register at the start of the program.
+    #[display("{0}")]
+    Input(RegE),
+
+    /// Constant field element.
+    #[display("{0}")]
+    Const(fe256),
+
+    /// Sum of two nodes.
+    #[display("{0} + {1}")]
+    Add(NodeId, NodeId),
+
+    /// Product of two nodes.
+    #[display("{0} * {1}")]
+    Mul(NodeId, NodeId),
+
+    /// Negation of a node.
+    #[display("-{0}")]
+    Neg(NodeId),
+}
+
+/// Directed acyclic graph of arithmetic operations performed by a program.
+///
+/// Leaves of the graph are constants and register values at the start of the program. Each value
+/// is represented by a single node, so a subexpression computed once and used several times is
+/// shared by all the nodes using it. Nodes are topologically ordered: operands always precede the
+/// operations using them.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::{decompile, Expr, ExprCompiler};
+/// # use zkaluvm::GfaConfig;
+/// # use zkaluvm::RegE::*;
+/// let mut compiler = ExprCompiler::<LibId>::new();
+/// compiler.assign(E3, Expr::mul(EA, EB) + 5u8);
+/// let code = compiler.compile().unwrap();
+///
+/// let graph = decompile(&code, GfaConfig::default()).unwrap();
+/// assert_eq!(graph.expr(E3).unwrap().to_string(), "((EA * EB) + 5.fe)");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ExprGraph {
+    nodes: Vec<ExprNode>,
+    /// Final values of the registers modified by the program; `None` for the cleared registers.
+    outputs: BTreeMap<RegE, Option<NodeId>>,
+}
+
+impl ExprGraph {
+    /// Returns all nodes of the graph, in topological order.
+    pub fn nodes(&self) -> &[ExprNode] { &self.nodes }
+
+    /// Returns a node of the graph.
+    ///
+    /// # Panics
+    ///
+    /// If the node doesn't belong to the graph.
+    pub fn node(&self, id: NodeId) -> ExprNode { self.nodes[id.0] }
+
+    /// Returns final values of all registers modified by the program.
+    ///
+    /// Registers which are cleared by the program have `None` as their value.
+    pub fn outputs(&self) -> impl Iterator<Item = (RegE, Option<NodeId>)> + '_ {
+        self.outputs.iter().map(|(reg, id)| (*reg, *id))
+    }
+
+    /// Returns the node holding the final value of the register.
+    ///
+    /// For registers which are not modified by the program this is their input value, if the
+    /// program reads it. Returns `None` for cleared registers and registers which are neither
+    /// read nor modified.
+    pub fn output(&self, reg: RegE) -> Option<NodeId> {
+        match self.outputs.get(&reg) {
+            Some(id) => *id,
+            None => self.find(ExprNode::Input(reg)),
+        }
+    }
+
+    /// Expands the final value of the register into an expression over the register values at the
+    /// start of the program.
+    ///
+    /// Shared subexpressions are duplicated in the expanded expression.
+    pub fn expr(&self, reg: RegE) -> Option<Expr> { self.output(reg).map(|id| self.expand(id)) }
+
+    /// Expands a node into an expression over the register values at the start of the program.
+    ///
+    /// # Panics
+    ///
+    /// If the node doesn't belong to the graph.
+    pub fn expand(&self, id: NodeId) -> Expr {
+        match self.node(id) {
+            ExprNode::Input(reg) => Expr::Reg(reg),
+            ExprNode::Const(val) => Expr::Const(val),
+            ExprNode::Add(a, b) => Expr::add(self.expand(a), self.expand(b)),
+            ExprNode::Mul(a, b) => Expr::mul(self.expand(a), self.expand(b)),
+            ExprNode::Neg(a) => Expr::neg(self.expand(a)),
+        }
+    }
+
+    pub(super) fn find(&self, node: ExprNode) -> Option<NodeId> {
+        self.nodes.iter().position(|n| *n == node).map(NodeId)
+    }
+
+    pub(super) fn insert(&mut self, node: ExprNode) -> NodeId {
+        self.find(node).unwrap_or_else(|| {
+            self.nodes.push(node);
+            NodeId(self.nodes.len() - 1)
+        })
+    }
+
+    /// Inserts the nodes raising `base` to the power of `exp` with square-and-multiply, returning
+    /// the node of the result.
+    pub(super) fn insert_power(&mut self, base: NodeId, exp: u8) -> NodeId {
+        if exp == 0 {
+            return self.insert(ExprNode::Const(fe256::from(1u8)));
+        }
+        let mut acc = base;
+        for bit in (0..u8::BITS - 1 - exp.leading_zeros()).rev() {
+            acc = self.insert(ExprNode::Mul(acc, acc));
+            if (exp >> bit) & 1 == 1 {
+                acc = self.insert(ExprNode::Mul(acc, base));
+            }
+        }
+        acc
+    }
+}
+
+impl Display for ExprGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (no, node) in self.nodes.iter().enumerate() {
+            writeln!(f, "{} = {node}", NodeId(no))?;
+        }
+        for (reg, id) in &self.outputs {
+            match id {
+                Some(id) => writeln!(f, "{reg} = {id}")?,
+                None => writeln!(f, "{reg} = _")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reconstructs the graph of arithmetic operations performed by straight-line code.
+///
+/// Instructions which only affect the `CO` and `CK` registers (like `eq`, `fits` or `chk`) do not
+/// change register values and are skipped; any instruction transferring control results in
+/// [`DecompileError::NotStraightLine`], and field operations other than addition, multiplication
+/// and negation (like `inv`), as well as moves and selections depending on `CO`, result in
+/// [`DecompileError::Unsupported`].
+pub fn decompile<Id: SiteId>(code: &[Instr<Id>], config: GfaConfig) -> Result<ExprGraph, DecompileError> {
+    let mut graph = ExprGraph::default();
+    let read = |graph: &mut ExprGraph, no: usize, reg: RegE| match graph.outputs.get(&reg) {
+        Some(Some(id)) => Ok(*id),
+        Some(None) => Err(DecompileError::Cleared { no, reg }),
+        None => Ok(graph.insert(ExprNode::Input(reg))),
+    };
+
+    for (no, instr) in code.iter().enumerate() {
+        let instr = match instr {
+            Instr::Gfa(instr) => *instr,
+            Instr::Ctrl(
+                CtrlInstr::Nop
+                | CtrlInstr::ChkCo
+                | CtrlInstr::ChkCk
+                | CtrlInstr::NotCo
+                | CtrlInstr::FailCk
+                | CtrlInstr::RsetCk,
+            ) => continue,
+            _ => return Err(DecompileError::NotStraightLine(no)),
+        };
+        let (dst, node) = match instr {
+            FieldInstr::Test { .. }
+            | FieldInstr::Fits { .. }
+            | FieldInstr::Eq { .. }
+            | FieldInstr::Lt { .. }
+            | FieldInstr::Rng { .. } => continue,
+            FieldInstr::Ecall { .. } => return Err(DecompileError::NotStraightLine(no)),
+            FieldInstr::Inv { .. }
+            | FieldInstr::Exp { .. }
+            | FieldInstr::Sqrt { .. }
+            | FieldInstr::CMov { .. }
+            | FieldInstr::Sel { .. } => return Err(DecompileError::Unsupported(no)),
+            FieldInstr::Clr { dst } => {
+                graph.outputs.insert(dst, None);
+                continue;
+            }
+            FieldInstr::PutD { dst, data } => (dst, ExprNode::Const(data)),
+            FieldInstr::PutZ { dst } => (dst, ExprNode::Const(fe256::ZERO)),
+            FieldInstr::PutV { dst, val } => {
+                let val = val
+                    .to_fe256()
+                    .unwrap_or_else(|| (config.field_order - u256::ONE).into());
+                (dst, ExprNode::Const(val))
+            }
+            FieldInstr::Mov { dst, src } => {
+                let src = read(&mut graph, no, src)?;
+                graph.outputs.insert(dst, Some(src));
+                continue;
+            }
+            FieldInstr::ExpI { dst_src, exp } => {
+                let base = read(&mut graph, no, dst_src)?;
+                let id = graph.insert_power(base, exp);
+                graph.outputs.insert(dst_src, Some(id));
+                continue;
+            }
+            FieldInstr::Neg { dst, src } => (dst, ExprNode::Neg(read(&mut graph, no, src)?)),
+            FieldInstr::Add { dst_src, src } => {
+                (dst_src, ExprNode::Add(read(&mut graph, no, dst_src)?, read(&mut graph, no, src)?))
+            }
+            FieldInstr::Mul { dst_src, src } => {
+                (dst_src, ExprNode::Mul(read(&mut graph, no, dst_src)?, read(&mut graph, no, src)?))
+            }
+            FieldInstr::Sqr { dst, src } => {
+                let src = read(&mut graph, no, src)?;
+                (dst, ExprNode::Mul(src, src))
+            }
+        };
+        let id = graph.insert(node);
+        graph.outputs.insert(dst, Some(id));
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::{Assembly, ExprCompiler};
+    use crate::RegE::*;
+
+    fn parse(source: &str) -> Vec<Instr<LibId>> { Assembly::parse(source).unwrap().code }
+
+    #[test]
+    fn shared() {
+        let code = parse(
+            r#"
+            routine MAIN:
+                mov     E1, EA
+                mul     E1, EB
+                put     E2, 5
+                add     E2, E1
+                mov     E3, E1
+                neg     E3, E3
+                eq      E2, E3
+                chk     CO
+                clr     EA
+        "#,
+        );
+        let graph = decompile(&code, GfaConfig::default()).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "\
+n0 = EA
+n1 = EB
+n2 = n0 * n1
+n3 = 5.fe
+n4 = n3 + n2
+n5 = -n2
+E1 = n2
+E2 = n4
+E3 = n5
+EA = _
+"
+        );
+        assert_eq!(graph.expr(E2).unwrap().to_string(), "(5.fe + (EA * EB))");
+        assert_eq!(graph.expr(E3).unwrap().to_string(), "-(EA * EB)");
+        assert_eq!(graph.expr(EB), Some(Expr::Reg(EB)));
+        assert_eq!(graph.expr(EA), None);
+        assert_eq!(graph.expr(E4), None);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let expr = (Expr::mul(EA, EB) + Expr::mul(EA, EB)) * EC - 7u8;
+        let mut compiler = ExprCompiler::<LibId>::new();
+        compiler.assign(E1, expr.clone());
+        let code = compiler.compile().unwrap();
+        let graph = decompile(&code, GfaConfig::default()).unwrap();
+        assert_eq!(graph.expr(E1).unwrap().simplify(GfaConfig::default()), expr.simplify(GfaConfig::default()));
+    }
+
+    #[test]
+    fn power() {
+        let graph = decompile(&parse("exp EA, 5\nmov E1, EA\nexp E1, 1\nexp E2, 0"), GfaConfig::default()).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "\
+n0 = EA
+n1 = n0 * n0
+n2 = n1 * n1
+n3 = n2 * n0
+n4 = E2
+n5 = 1.fe
+E1 = n3
+E2 = n5
+EA = n3
+"
+        );
+    }
+
+    #[test]
+    fn square() {
+        let graph = decompile(&parse("sqr E1, EA\nmov E2, EA\nmul E2, EA\nsqr E1, E1"), GfaConfig::default()).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "\
+n0 = EA
+n1 = n0 * n0
+n2 = n1 * n1
+E1 = n2
+E2 = n1
+"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let code = parse(
+            r#"
+            routine MAIN:
+                put     E1, 1
+                jif     CO, MAIN
+        "#,
+        );
+        assert_eq!(decompile(&code, GfaConfig::default()).unwrap_err(), DecompileError::NotStraightLine(2));
+
+        let code = parse(
+            r#"
+            routine MAIN:
+                clr     E1
+                add     E2, E1
+        "#,
+        );
+        assert_eq!(decompile(&code, GfaConfig::default()).unwrap_err(), DecompileError::Cleared { no: 2, reg: E1 });
+
+        let code = parse("put E1, 2\ninv E2, E1");
+        assert_eq!(decompile(&code, GfaConfig::default()).unwrap_err(), DecompileError::Unsupported(1));
+
+        let code = parse("eq E1, E2\ncmov E1, E2");
+        assert_eq!(decompile(&code, GfaConfig::default()).unwrap_err(), DecompileError::Unsupported(1));
+
+        let code = parse("eq E1, E2\nsel E3, E1, E2");
+        assert_eq!(decompile(&code, GfaConfig::default()).unwrap_err(), DecompileError::Unsupported(1));
+    }
+}
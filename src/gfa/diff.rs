@@ -0,0 +1,316 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Semantic comparison of zk-AluVM programs.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use aluvm::isa::{GotoTarget, Instruction};
+use aluvm::{Lib, LibId};
+
+use super::analysis::ControlFlow;
+use super::Instr;
+
+/// Errors comparing libraries.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum DiffError {
+    /// the code of the old library can't be decoded.
+    OldCode,
+
+    /// the code of the new library can't be decoded.
+    NewCode,
+}
+
+/// Location of an instruction within a program.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display("{pos:04X}")]
+pub struct InstrLoc {
+    /// Index of the instruction in the program.
+    pub no: usize,
+    /// Offset of the instruction in the code segment.
+    pub pos: u16,
+}
+
+/// Instruction-level change between two versions of a program.
+#[derive(Clone, PartialEq, Eq, Debug, Display)]
+pub enum Change {
+    /// Instruction present only in the new version of the program.
+    #[display("+ {new}: {instr}")]
+    Inserted {
+        /** Location of the instruction in the new program */
+        new: InstrLoc,
+        /** Inserted instruction */
+        instr: Instr<LibId>,
+    },
+
+    /// Instruction present only in the old version of the program.
+    #[display("- {old}: {instr}")]
+    Deleted {
+        /** Location of the instruction in the old program */
+        old: InstrLoc,
+        /** Deleted instruction */
+        instr: Instr<LibId>,
+    },
+
+    /// Instruction replaced with another one, or a jump retargeted to a different instruction.
+    #[display("~ {old} -> {new}: {from} => {to}")]
+    Modified {
+        /** Location of the instruction in the old program */
+        old: InstrLoc,
+        /** Location of the instruction in the new program */
+        new: InstrLoc,
+        /** Instruction in the old program */
+        from: Instr<LibId>,
+        /** Instruction in the new program */
+        to: Instr<LibId>,
+    },
+}
+
+/// Compares two versions of a library, returning instruction-level changes in the order of their
+/// appearance in the code.
+///
+/// The comparison is resilient to the offset shifts: jumps and calls are compared by the
+/// instructions they point to rather than by their raw offsets, so inserting or removing code
+/// doesn't make the unchanged jumps around it appear modified. A jump is reported as modified only
+/// if it is retargeted to an instruction which doesn't correspond to its old target.
+pub fn diff(old: &Lib, new: &Lib) -> Result<Vec<Change>, DiffError> {
+    let old = old
+        .disassemble::<Instr<LibId>>()
+        .map_err(|_| DiffError::OldCode)?;
+    let new = new
+        .disassemble::<Instr<LibId>>()
+        .map_err(|_| DiffError::NewCode)?;
+    let old_cfg = ControlFlow::analyze(&old).map_err(|_| DiffError::OldCode)?;
+    let new_cfg = ControlFlow::analyze(&new).map_err(|_| DiffError::NewCode)?;
+
+    let old_keys = old.iter().map(masked).collect::<Vec<_>>();
+    let new_keys = new.iter().map(masked).collect::<Vec<_>>();
+    let matches = lcs(&old_keys, &new_keys);
+
+    let mut mapping = vec![None; old.len()];
+    for (o, n) in &matches {
+        mapping[*o] = Some(*n);
+    }
+
+    let loc = |cfg: &ControlFlow<LibId>, no: usize| InstrLoc {
+        no,
+        pos: cfg.offset(no),
+    };
+    let mut changes = Vec::new();
+    let (mut o, mut n) = (0usize, 0usize);
+    for (mo, mn) in matches.into_iter().chain([(old.len(), new.len())]) {
+        let (dels, ins) = (mo - o, mn - n);
+        for i in 0..dels.max(ins) {
+            changes.push(match (i < dels, i < ins) {
+                (true, true) => Change::Modified {
+                    old: loc(&old_cfg, o + i),
+                    new: loc(&new_cfg, n + i),
+                    from: old[o + i],
+                    to: new[n + i],
+                },
+                (true, false) => Change::Deleted {
+                    old: loc(&old_cfg, o + i),
+                    instr: old[o + i],
+                },
+                (false, _) => Change::Inserted {
+                    new: loc(&new_cfg, n + i),
+                    instr: new[n + i],
+                },
+            });
+        }
+        if mo == old.len() {
+            break;
+        }
+        let retargeted = match (target(&old_cfg, mo), target(&new_cfg, mn)) {
+            (Some(Some(to)), Some(Some(tn))) => mapping[to] != Some(tn),
+            (None, None) => false,
+            _ => old[mo] != new[mn],
+        };
+        if retargeted {
+            changes.push(Change::Modified {
+                old: loc(&old_cfg, mo),
+                new: loc(&new_cfg, mn),
+                from: old[mo],
+                to: new[mn],
+            });
+        }
+        (o, n) = (mo + 1, mn + 1);
+    }
+    Ok(changes)
+}
+
+/// Replaces local goto targets with zeros, such that jumps can be matched regardless of the offsets
+/// they point to.
+fn masked(instr: &Instr<LibId>) -> Instr<LibId> {
+    let mut instr = *instr;
+    match instr.local_goto_pos() {
+        GotoTarget::None => {}
+        GotoTarget::Absolute(pos) => *pos = 0,
+        GotoTarget::Relative(shift) => *shift = 0,
+    }
+    instr
+}
+
+/// Resolves index of the instruction a local goto points to. Returns `None` for instructions
+/// without local goto, and `Some(None)` if the goto doesn't point to an instruction boundary.
+fn target(cfg: &ControlFlow<LibId>, no: usize) -> Option<Option<usize>> {
+    let mut instr = cfg.code()[no];
+    let pos = match instr.local_goto_pos() {
+        GotoTarget::None => return None,
+        GotoTarget::Absolute(pos) => Some(*pos),
+        GotoTarget::Relative(shift) => cfg.offset(no).checked_add_signed(*shift as i16),
+    };
+    Some(pos.and_then(|pos| cfg.index_at(pos)))
+}
+
+/// Computes the longest common subsequence of two sequences with Myers' algorithm, returning
+/// pairs of indexes of the matching elements.
+fn lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    'outer: for d in 0..=max {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { v[idx + 1] } else { v[idx - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'outer;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, snapshot) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| snapshot[(k + d) as usize];
+        let (px, py) = if d == 0 {
+            (0, 0)
+        } else {
+            let k = x - y;
+            let pk = if k == -d || (k != d && get(k - 1) < get(k + 1)) { k + 1 } else { k - 1 };
+            (get(pk), get(pk) - pk)
+        };
+        while x > px && y > py {
+            x -= 1;
+            y -= 1;
+            pairs.push((x as usize, y as usize));
+        }
+        (x, y) = (px, py);
+    }
+    pairs.reverse();
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use aluvm::isa::CtrlInstr;
+
+    use super::*;
+    use crate::gfa::Assembly;
+
+    fn lib(source: &str) -> Lib { Assembly::parse(source).unwrap().assemble().unwrap() }
+
+    const OLD: &str = r#"
+        routine START:
+            put     E1, 0
+            put     E2, 1
+        again:
+            add     E1, E2
+            fits    E1, 8.bits
+            jif     CO, again
+            call    START
+            ret
+    "#;
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(diff(&lib(OLD), &lib(OLD)).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn shifted() {
+        let new = lib(r#"
+            routine START:
+                put     E1, 0
+                put     E2, 5
+                put     E3, 1
+            again:
+                add     E1, E2
+                jif     CO, again
+                call    START
+                ret
+        "#);
+        let changes = diff(&lib(OLD), &new).unwrap();
+        assert_eq!(changes.iter().map(Change::to_string).collect::<Vec<_>>(), vec![
+            s!("~ 0003 -> 0003: put     E2, 1 => put     E2, 5.fe"),
+            s!("+ 0007: put     E3, 1"),
+            s!("- 0007: fits    E1, 8.bits"),
+        ]);
+    }
+
+    #[test]
+    fn retargeted() {
+        let new = lib(r#"
+            routine START:
+                put     E1, 0
+                put     E2, 1
+            again:
+                add     E1, E2
+                fits    E1, 8.bits
+                jif     CO, START
+                call    START
+                ret
+        "#);
+        let changes = diff(&lib(OLD), &new).unwrap();
+        assert_eq!(changes, vec![Change::Modified {
+            old: InstrLoc { no: 5, pos: 9 },
+            new: InstrLoc { no: 5, pos: 9 },
+            from: CtrlInstr::JiOvfl { pos: 5 }.into(),
+            to: CtrlInstr::JiOvfl { pos: 0 }.into(),
+        }]);
+    }
+
+    #[test]
+    fn sequences() {
+        assert_eq!(lcs(b"abcabba", b"cbabac"), vec![(2, 0), (3, 2), (4, 3), (6, 4)]);
+        assert_eq!(lcs(b"", b"abc"), vec![]);
+        assert_eq!(lcs(b"abc", b"abc"), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+}
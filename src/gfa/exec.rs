@@ -22,22 +22,23 @@
 
 use alloc::collections::BTreeSet;
 
-use aluvm::isa::{ExecStep, GotoTarget, Instruction};
+use aluvm::isa::{Bytecode, CtrlInstr, ExecStep, GotoTarget, Instruction};
 use aluvm::regs::Status;
 use aluvm::{Core, CoreExt, Site, SiteId, Supercore};
 use amplify::num::u256;
 
-use super::{FieldInstr, Instr, ISA_GFA256};
-use crate::{fe256, GfaCore, RegE};
+use super::{EcallFrame, FieldInstr, GfaContext, Instr, ECALL_WINDOW, ISA_GFA256};
+use crate::core::SQRT_MUL_LIM;
+use crate::{fe256, FailureAction, GfaCore, RegE};
 
 impl<Id: SiteId> Instruction<Id> for FieldInstr {
     const ISA_EXT: &'static [&'static str] = &[ISA_GFA256];
     type Core = GfaCore;
-    type Context<'ctx> = ();
+    type Context<'ctx> = &'ctx dyn GfaContext;
 
     fn is_goto_target(&self) -> bool { false }
 
-    fn local_goto_pos(&mut self) -> GotoTarget { GotoTarget::None }
+    fn local_goto_pos(&mut self) -> GotoTarget<'_> { GotoTarget::None }
 
     fn remote_goto_pos(&mut self) -> Option<&mut Site<Id>> { None }
 
@@ -48,14 +49,27 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
             | FieldInstr::PutZ { dst: _ }
             | FieldInstr::PutV { dst: _, val: _ } => none!(),
 
-            FieldInstr::Eq { src1, src2 } => bset![src1, src2],
+            FieldInstr::Eq { src1, src2 } | FieldInstr::Lt { src1, src2 } => bset![src1, src2],
 
             FieldInstr::Test { src }
             | FieldInstr::Fits { src, bits: _ }
+            | FieldInstr::Rng { src, bound: _ }
             | FieldInstr::Mov { dst: _, src }
-            | FieldInstr::Neg { dst: _, src } => bset![src],
+            | FieldInstr::Neg { dst: _, src }
+            | FieldInstr::Inv { dst: _, src }
+            | FieldInstr::Sqr { dst: _, src }
+            | FieldInstr::Sqrt { dst: _, src } => bset![src],
+
+            // The previous value of `dst` is retained if the move doesn't happen
+            FieldInstr::CMov { dst, src } => bset![src, dst],
+            FieldInstr::Sel { dst: _, src_true, src_false } => bset![src_true, src_false],
+
+            FieldInstr::ExpI { dst_src, exp: _ } => bset![dst_src],
 
             FieldInstr::Add { dst_src, src } | FieldInstr::Mul { dst_src, src } => bset![src, dst_src],
+            FieldInstr::Exp { dst_src, exp } => bset![exp, dst_src],
+
+            FieldInstr::Ecall { id: _ } => ECALL_WINDOW.into_iter().collect(),
         }
     }
 
@@ -65,21 +79,35 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
             | FieldInstr::PutD { dst, data: _ }
             | FieldInstr::PutZ { dst }
             | FieldInstr::PutV { dst, val: _ }
-            | FieldInstr::Mov { dst, src: _ } => bset![dst],
+            | FieldInstr::Mov { dst, src: _ }
+            | FieldInstr::CMov { dst, src: _ }
+            | FieldInstr::Sel { dst, src_true: _, src_false: _ } => bset![dst],
 
             FieldInstr::Eq { src1: _, src2: _ }
+            | FieldInstr::Lt { src1: _, src2: _ }
+            | FieldInstr::Rng { src: _, bound: _ }
             | FieldInstr::Test { src: _ }
             | FieldInstr::Fits { src: _, bits: _ } => none!(),
 
             FieldInstr::Neg { dst, src: _ }
             | FieldInstr::Add { dst_src: dst, src: _ }
-            | FieldInstr::Mul { dst_src: dst, src: _ } => bset![dst],
+            | FieldInstr::Mul { dst_src: dst, src: _ }
+            | FieldInstr::Inv { dst, src: _ }
+            | FieldInstr::Exp { dst_src: dst, exp: _ }
+            | FieldInstr::ExpI { dst_src: dst, exp: _ }
+            | FieldInstr::Sqr { dst, src: _ }
+            | FieldInstr::Sqrt { dst, src: _ } => bset![dst],
+
+            FieldInstr::Ecall { id: _ } => ECALL_WINDOW.into_iter().collect(),
         }
     }
 
     fn op_data_bytes(&self) -> u16 {
         match self {
-            FieldInstr::PutV { dst: _, val: _ } | FieldInstr::Fits { src: _, bits: _ } => 1,
+            FieldInstr::PutV { dst: _, val: _ }
+            | FieldInstr::Fits { src: _, bits: _ }
+            | FieldInstr::Ecall { id: _ }
+            | FieldInstr::ExpI { dst_src: _, exp: _ } => 1,
 
             FieldInstr::Test { src: _ }
             | FieldInstr::Clr { dst: _ }
@@ -89,13 +117,21 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
             | FieldInstr::Eq { src1: _, src2: _ }
             | FieldInstr::Neg { dst: _, src: _ }
             | FieldInstr::Add { dst_src: _, src: _ }
-            | FieldInstr::Mul { dst_src: _, src: _ } => 0,
+            | FieldInstr::Mul { dst_src: _, src: _ }
+            | FieldInstr::Inv { dst: _, src: _ }
+            | FieldInstr::Exp { dst_src: _, exp: _ }
+            | FieldInstr::Sqr { dst: _, src: _ }
+            | FieldInstr::Sqrt { dst: _, src: _ }
+            | FieldInstr::CMov { dst: _, src: _ }
+            | FieldInstr::Sel { dst: _, src_true: _, src_false: _ }
+            | FieldInstr::Lt { src1: _, src2: _ }
+            | FieldInstr::Rng { src: _, bound: _ } => 0,
         }
     }
 
     fn ext_data_bytes(&self) -> u16 {
         match self {
-            FieldInstr::PutD { dst: _, data: _ } => 32,
+            FieldInstr::PutD { dst: _, data: _ } | FieldInstr::Rng { src: _, bound: _ } => 32,
 
             FieldInstr::Test { src: _ }
             | FieldInstr::Clr { dst: _ }
@@ -106,7 +142,16 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
             | FieldInstr::Eq { src1: _, src2: _ }
             | FieldInstr::Neg { dst: _, src: _ }
             | FieldInstr::Add { dst_src: _, src: _ }
-            | FieldInstr::Mul { dst_src: _, src: _ } => 0,
+            | FieldInstr::Mul { dst_src: _, src: _ }
+            | FieldInstr::Ecall { id: _ }
+            | FieldInstr::Inv { dst: _, src: _ }
+            | FieldInstr::Exp { dst_src: _, exp: _ }
+            | FieldInstr::ExpI { dst_src: _, exp: _ }
+            | FieldInstr::Sqr { dst: _, src: _ }
+            | FieldInstr::Sqrt { dst: _, src: _ }
+            | FieldInstr::CMov { dst: _, src: _ }
+            | FieldInstr::Sel { dst: _, src_true: _, src_false: _ }
+            | FieldInstr::Lt { src1: _, src2: _ } => 0,
         }
     }
 
@@ -119,19 +164,52 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
             | FieldInstr::PutV { dst: _, val: _ }
             | FieldInstr::PutD { dst: _, data: _ }
             | FieldInstr::Mov { dst: _, src: _ }
-            | FieldInstr::Eq { src1: _, src2: _ } => base,
+            | FieldInstr::CMov { dst: _, src: _ }
+            | FieldInstr::Sel { dst: _, src_true: _, src_false: _ }
+            | FieldInstr::Eq { src1: _, src2: _ }
+            | FieldInstr::Lt { src1: _, src2: _ }
+            | FieldInstr::Rng { src: _, bound: _ } => base,
+
+            FieldInstr::Ecall { id: _ } => {
+                // The register window is accessed by the host, which charges the complexity itself.
+                Instruction::<Id>::op_data_bytes(self) as u64 * 8 * 1000
+            }
 
             FieldInstr::Fits { src: _, bits: _ }
             | FieldInstr::Neg { dst: _, src: _ }
             | FieldInstr::Add { dst_src: _, src: _ }
-            | FieldInstr::Mul { dst_src: _, src: _ } => {
+            | FieldInstr::Mul { dst_src: _, src: _ }
+            | FieldInstr::Sqr { dst: _, src: _ } => {
                 // Double the default complexity since each instruction performs two operations.
                 base * 2
             }
+
+            FieldInstr::Inv { dst: _, src: _ } => {
+                // The extended Euclidean algorithm runs up to a few iterations per bit of the value.
+                base * 256
+            }
+
+            FieldInstr::Exp { dst_src: _, exp: _ } => {
+                // Square-and-multiply performs up to two multiplications per bit of the exponent.
+                base * 2 * 512
+            }
+
+            FieldInstr::ExpI { dst_src: _, exp: _ } => {
+                // Square-and-multiply performs up to two multiplications per bit of the exponent.
+                base * 2 * 16
+            }
+
+            FieldInstr::Sqrt { dst: _, src: _ } => {
+                // Tonelli–Shanks searches for a quadratic non-residue and performs a few
+                // exponentiations, followed by a number of squarings quadratic in the two-adicity of
+                // the field order. The complexity doesn't depend on the configured field, so the worst
+                // case is charged.
+                base * 2 * SQRT_MUL_LIM
+            }
         }
     }
 
-    fn exec(&self, _: Site<Id>, core: &mut Core<Id, GfaCore>, _: &Self::Context<'_>) -> ExecStep<Site<Id>> {
+    fn exec(&self, site: Site<Id>, core: &mut Core<Id, GfaCore>, context: &Self::Context<'_>) -> ExecStep<Site<Id>> {
         let res = match *self {
             FieldInstr::Test { src } => {
                 let res = core.cx.test(src);
@@ -142,25 +220,28 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
                 core.cx.clr(dst);
                 Status::Ok
             }
-            FieldInstr::PutD { dst, data } => {
-                core.cx.set(dst, data);
-                Status::Ok
-            }
-            FieldInstr::PutZ { dst } => {
-                core.cx.set(dst, fe256::ZERO);
-                Status::Ok
-            }
+            FieldInstr::PutD { dst, data } => core.cx.load(dst, data),
+            FieldInstr::PutZ { dst } => core.cx.load(dst, fe256::ZERO),
             FieldInstr::PutV { dst, val } => {
                 let val = val
                     .to_fe256()
                     .unwrap_or_else(|| (core.cx.fq() - u256::ONE).into());
-                core.cx.set(dst, val);
-                Status::Ok
+                core.cx.load(dst, val)
             }
             FieldInstr::Mov { dst, src } => {
                 core.cx.mov(dst, src);
                 Status::Ok
             }
+            FieldInstr::CMov { dst, src } => {
+                let cond = core.co().is_ok();
+                core.cx.cmov(dst, src, cond);
+                Status::Ok
+            }
+            FieldInstr::Sel { dst, src_true, src_false } => {
+                let cond = core.co().is_ok();
+                core.cx.sel(dst, src_true, src_false, cond);
+                Status::Ok
+            }
             FieldInstr::Eq { src1, src2 } => {
                 let res = core.cx.eqv(src1, src2);
                 core.set_co(res);
@@ -178,9 +259,65 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
                     Status::Ok
                 }
             },
+            FieldInstr::Lt { src1, src2 } => match core.cx.lt(src1, src2) {
+                None => Status::Fail,
+                Some(true) => {
+                    core.set_co(Status::Ok);
+                    Status::Ok
+                }
+                Some(false) => {
+                    core.set_co(Status::Fail);
+                    Status::Ok
+                }
+            },
+            FieldInstr::Rng { src, bound } => match core.cx.rng(src, bound) {
+                None => Status::Fail,
+                Some(true) => {
+                    core.set_co(Status::Ok);
+                    Status::Ok
+                }
+                Some(false) => {
+                    core.set_co(Status::Fail);
+                    Status::Ok
+                }
+            },
             FieldInstr::Neg { dst, src } => core.cx.neg_mod(dst, src),
             FieldInstr::Add { dst_src, src } => core.cx.add_mod(dst_src, src),
             FieldInstr::Mul { dst_src, src } => core.cx.mul_mod(dst_src, src),
+            FieldInstr::Inv { dst, src } => core.cx.inv_mod(dst, src),
+            FieldInstr::Exp { dst_src, exp } => core.cx.exp_mod(dst_src, exp),
+            FieldInstr::ExpI { dst_src, exp } => core.cx.expi_mod(dst_src, exp),
+            FieldInstr::Sqr { dst, src } => core.cx.sqr_mod(dst, src),
+            FieldInstr::Sqrt { dst, src } => match core.cx.sqrt_mod(dst, src) {
+                None => Status::Fail,
+                Some(true) => {
+                    core.set_co(Status::Ok);
+                    Status::Ok
+                }
+                Some(false) => {
+                    core.set_co(Status::Fail);
+                    Status::Ok
+                }
+            },
+            FieldInstr::Ecall { id } => {
+                let mut frame = EcallFrame::new(&core.cx);
+                let res = context.ecall(id, &mut frame);
+                let (writes, complexity) = frame.into_parts();
+                // The complexity limit halts the program, as it does for the instruction complexity.
+                if !core.acc_complexity(complexity) {
+                    fail_ck(core, site);
+                    return ExecStep::Stop;
+                }
+                if res == Status::Ok {
+                    for (reg, val) in writes {
+                        match val {
+                            Some(val) => core.cx.set(reg, val),
+                            None => core.cx.clr(reg),
+                        }
+                    }
+                }
+                res
+            }
         };
         if res == Status::Ok {
             ExecStep::Next
@@ -193,7 +330,7 @@ impl<Id: SiteId> Instruction<Id> for FieldInstr {
 impl<Id: SiteId> Instruction<Id> for Instr<Id> {
     const ISA_EXT: &'static [&'static str] = &[ISA_GFA256];
     type Core = GfaCore;
-    type Context<'ctx> = ();
+    type Context<'ctx> = &'ctx dyn GfaContext;
 
     fn is_goto_target(&self) -> bool {
         match self {
@@ -203,7 +340,7 @@ impl<Id: SiteId> Instruction<Id> for Instr<Id> {
         }
     }
 
-    fn local_goto_pos(&mut self) -> GotoTarget {
+    fn local_goto_pos(&mut self) -> GotoTarget<'_> {
         match self {
             Instr::Ctrl(ctrl) => ctrl.local_goto_pos(),
             Instr::Gfa(instr) => Instruction::<Id>::local_goto_pos(instr),
@@ -260,21 +397,116 @@ impl<Id: SiteId> Instruction<Id> for Instr<Id> {
     }
 
     fn exec(&self, site: Site<Id>, core: &mut Core<Id, Self::Core>, context: &Self::Context<'_>) -> ExecStep<Site<Id>> {
-        match self {
+        #[cfg(feature = "tracing")]
+        let ck = core.ck();
+
+        if !core.cx.count_step() {
+            // The step limit halts the program regardless of the `CH` register value.
+            fail_ck(core, site);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(%site, "step limit exceeded");
+            return ExecStep::Stop;
+        }
+
+        let step = match self {
+            // Instructions from the groups not supported by the VM are not executed
+            _ if self
+                .group()
+                .is_some_and(|group| !core.cx.isa_groups().contains(group)) =>
+            {
+                ExecStep::Fail
+            }
             Instr::Ctrl(instr) => {
                 let mut subcore = core.subcore();
-                let step = instr.exec(site, &mut subcore, context);
+                let step = instr.exec(site, &mut subcore, &());
                 core.merge_subcore(subcore);
                 step
             }
-            Instr::Gfa(instr) => instr.exec(site, core, context),
-            Instr::Reserved(instr) => {
-                let mut subcore = core.subcore();
-                let step = instr.exec(site, &mut subcore, context);
-                core.merge_subcore(subcore);
-                step
+            Instr::Gfa(instr) => {
+                core.cx.count_gfa_step();
+                instr.exec(site, core, context)
             }
+            Instr::Reserved(instr) => match context.trap(Bytecode::<Id>::opcode_byte(instr), &mut core.cx) {
+                Some(Status::Ok) => ExecStep::Next,
+                Some(Status::Fail) => ExecStep::Fail,
+                None => {
+                    let mut subcore = core.subcore();
+                    let step = instr.exec(site, &mut subcore, &());
+                    core.merge_subcore(subcore);
+                    step
+                }
+            },
+        };
+        let step = match step {
+            ExecStep::Fail => apply_policy(core.cx.failure_policy().action(self.class()), core, site),
+            step => step,
+        };
+
+        #[cfg(feature = "tracing")]
+        trace_exec(self, site, core, ck, &step);
+
+        step
+    }
+}
+
+/// Handles an instruction failure according to the action from the [`FailurePolicy`].
+///
+/// [`FailurePolicy`]: crate::FailurePolicy
+fn apply_policy<Id: SiteId>(action: FailureAction, core: &mut Core<Id, GfaCore>, site: Site<Id>) -> ExecStep<Site<Id>> {
+    match action {
+        FailureAction::Inherit => ExecStep::Fail,
+        FailureAction::Halt => {
+            fail_ck(core, site);
+            ExecStep::Stop
+        }
+        FailureAction::Continue => {
+            fail_ck(core, site);
+            ExecStep::Next
         }
+        FailureAction::Signal => {
+            core.set_co(Status::Fail);
+            ExecStep::Next
+        }
+    }
+}
+
+/// Fails `CK`, returning whether the core halts on failures.
+///
+/// The core doesn't provide direct access to `CK` failures, so `fail CK` instruction is executed
+/// instead; it is executed by the control flow subcore, so it is not counted as an executed
+/// instruction.
+pub(crate) fn fail_ck<Id: SiteId>(core: &mut Core<Id, GfaCore>, site: Site<Id>) -> bool {
+    let mut subcore = core.subcore();
+    let step = CtrlInstr::<Id>::FailCk.exec(site, &mut subcore, &());
+    core.merge_subcore(subcore);
+    step == ExecStep::Stop
+}
+
+/// Emits `tracing` events describing the execution of an instruction.
+///
+/// `ck` is the value of the `CK` register before the instruction execution.
+#[cfg(feature = "tracing")]
+fn trace_exec<Id: SiteId>(
+    instr: &Instr<Id>,
+    site: Site<Id>,
+    core: &Core<Id, GfaCore>,
+    ck: Status,
+    step: &ExecStep<Site<Id>>,
+) {
+    tracing::trace!(%site, %instr, co = %core.co(), ck = %core.ck(), "instruction executed");
+    // Failed instructions leave their destination registers unchanged.
+    for reg in instr
+        .dst_regs()
+        .into_iter()
+        .filter(|_| *step != ExecStep::Fail)
+    {
+        match core.cx.get(reg) {
+            Some(value) => tracing::trace!(%site, %reg, %value, "register written"),
+            None => tracing::trace!(%site, %reg, "register cleared"),
+        }
+    }
+    if *step == ExecStep::Fail || (ck.is_ok() && !core.ck().is_ok()) {
+        tracing::debug!(%site, %instr, "CK failed");
     }
 }
 
@@ -285,7 +517,7 @@ mod test {
     use aluvm::LibId;
 
     use super::*;
-    use crate::gfa::{Bits, ConstVal};
+    use crate::gfa::{Bits, ConstVal, NO_CONTEXT};
 
     #[test]
     fn test() {
@@ -438,6 +670,46 @@ mod test {
         assert_eq!(instr.complexity(), instr.base_complexity());
     }
 
+    #[test]
+    fn lt() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Lt {
+            src1: RegE::E1,
+            src2: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::E1, RegE::EA]);
+        assert_eq!(instr.dst_regs(), none!());
+        assert_eq!(instr.src_reg_bytes(), 64);
+        assert_eq!(instr.dst_reg_bytes(), 0);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 512000);
+        assert_eq!(instr.complexity(), instr.base_complexity());
+    }
+
+    #[test]
+    fn rng() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Rng {
+            src: RegE::E1,
+            bound: fe256::from(1_000_000_000u64),
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::E1]);
+        assert_eq!(instr.dst_regs(), none!());
+        assert_eq!(instr.src_reg_bytes(), 32);
+        assert_eq!(instr.dst_reg_bytes(), 0);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 32);
+        assert_eq!(instr.base_complexity(), 768000);
+        assert_eq!(instr.complexity(), instr.base_complexity());
+    }
+
     #[test]
     fn neg() {
         let mut instr = Instr::<LibId>::Gfa(FieldInstr::Neg {
@@ -498,6 +770,170 @@ mod test {
         assert_eq!(instr.complexity(), instr.base_complexity() * 2);
     }
 
+    #[test]
+    fn ecall() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Ecall { id: 1 });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), ECALL_WINDOW.into_iter().collect());
+        assert_eq!(instr.src_regs(), ECALL_WINDOW.into_iter().collect());
+        assert_eq!(instr.dst_regs(), ECALL_WINDOW.into_iter().collect());
+        assert_eq!(instr.src_reg_bytes(), 256);
+        assert_eq!(instr.dst_reg_bytes(), 256);
+        assert_eq!(instr.op_data_bytes(), 1);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 4104000);
+        assert_eq!(instr.complexity(), 8000);
+    }
+
+    #[test]
+    fn inv() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Inv {
+            dst: RegE::E1,
+            src: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::EA]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 32);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 512000);
+        assert_eq!(instr.complexity(), instr.base_complexity() * 256);
+    }
+
+    #[test]
+    fn exp() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Exp {
+            dst_src: RegE::E1,
+            exp: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::EA, RegE::E1]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 64);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 768000);
+        assert_eq!(instr.complexity(), instr.base_complexity() * 1024);
+    }
+
+    #[test]
+    fn expi() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::ExpI {
+            dst_src: RegE::E1,
+            exp: 5,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::E1]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 32);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 1);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 520000);
+        assert_eq!(instr.complexity(), instr.base_complexity() * 32);
+    }
+
+    #[test]
+    fn sqr() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Sqr {
+            dst: RegE::E1,
+            src: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::EA]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 32);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 512000);
+        assert_eq!(instr.complexity(), instr.base_complexity() * 2);
+
+        let mul = Instr::<LibId>::Gfa(FieldInstr::Mul {
+            dst_src: RegE::E1,
+            src: RegE::EA,
+        });
+        assert!(instr.complexity() < mul.complexity());
+    }
+
+    #[test]
+    fn sqrt() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Sqrt {
+            dst: RegE::E1,
+            src: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::EA]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 32);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 512000);
+        assert_eq!(instr.complexity(), instr.base_complexity() * 2 * 100735);
+    }
+
+    #[test]
+    fn cmov() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::CMov {
+            dst: RegE::E1,
+            src: RegE::EA,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::E1, RegE::EA]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 64);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 768000);
+        assert_eq!(instr.complexity(), instr.base_complexity());
+    }
+
+    #[test]
+    fn sel() {
+        let mut instr = Instr::<LibId>::Gfa(FieldInstr::Sel {
+            dst: RegE::E1,
+            src_true: RegE::EA,
+            src_false: RegE::EB,
+        });
+        assert_eq!(instr.is_goto_target(), false);
+        assert_eq!(instr.local_goto_pos(), GotoTarget::None);
+        assert_eq!(instr.remote_goto_pos(), None);
+        assert_eq!(instr.regs(), instr.src_regs().union(&instr.dst_regs()).copied().collect());
+        assert_eq!(instr.src_regs(), bset![RegE::EA, RegE::EB]);
+        assert_eq!(instr.dst_regs(), bset![RegE::E1]);
+        assert_eq!(instr.src_reg_bytes(), 64);
+        assert_eq!(instr.dst_reg_bytes(), 32);
+        assert_eq!(instr.op_data_bytes(), 0);
+        assert_eq!(instr.ext_data_bytes(), 0);
+        assert_eq!(instr.base_complexity(), 768000);
+        assert_eq!(instr.complexity(), instr.base_complexity());
+    }
+
     #[test]
     fn reserved() {
         let mut instr = Instr::<LibId>::Reserved(default!());
@@ -514,4 +950,115 @@ mod test {
         assert_eq!(instr.base_complexity(), 0);
         assert_eq!(instr.complexity(), u64::MAX);
     }
+
+    #[test]
+    fn step_limit() {
+        use aluvm::{CoreConfig, LibSite, Vm};
+
+        use crate::gfa::Assembly;
+        use crate::GfaConfig;
+
+        let lib = Assembly::parse(
+            r#"
+            routine LOOP:
+                put     E1, 1
+                jmp     LOOP
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let lib_id = lib.lib_id();
+        let config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut vm = Vm::<Instr<LibId>>::with(config, GfaConfig {
+            step_lim: Some(100),
+            ..default!()
+        });
+        let res = vm.exec(LibSite::new(lib_id, 0), &NO_CONTEXT, |id| (id == lib_id).then_some(&lib));
+        assert_eq!(res, Status::Fail);
+        assert_eq!(vm.core.cx.steps(), 100);
+        assert_eq!(vm.core.cx.gfa_steps(), 33);
+        assert_eq!(vm.core.cx.base_steps(), 67);
+        assert_eq!(vm.core.cx.step_lim(), Some(100));
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(1u8)));
+
+        vm.core.reset();
+        assert_eq!(vm.core.cx.steps(), 0);
+        assert_eq!(vm.core.cx.gfa_steps(), 0);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing() {
+        use alloc::string::String;
+        use alloc::sync::Arc;
+        use alloc::vec::Vec;
+        use alloc::{format, vec};
+        use core::fmt::Debug;
+        use std::sync::Mutex;
+
+        use aluvm::{Core, CoreConfig};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        #[derive(Clone, Default)]
+        struct Collector(Arc<Mutex<Vec<String>>>);
+
+        struct Fields(String);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                match field.name() {
+                    "message" => self.0.insert_str(0, &format!("{value:?}")),
+                    "reg" => self.0.push_str(&format!(" {value:?}")),
+                    _ => {}
+                }
+            }
+        }
+
+        impl Subscriber for Collector {
+            fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+            fn new_span(&self, _: &Attributes<'_>) -> Id { Id::from_u64(1) }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields(String::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let collector = Collector::default();
+        let site = Site::new(LibId::default(), 0);
+        let mut core = Core::<LibId, GfaCore>::with(CoreConfig::default(), default!());
+        tracing::subscriber::with_default(collector.clone(), || {
+            for instr in [
+                FieldInstr::PutV {
+                    dst: RegE::E1,
+                    val: ConstVal::Val1,
+                },
+                FieldInstr::Clr { dst: RegE::E1 },
+                FieldInstr::Add {
+                    dst_src: RegE::E1,
+                    src: RegE::E2,
+                },
+            ] {
+                Instr::<LibId>::Gfa(instr).exec(site, &mut core, &NO_CONTEXT);
+            }
+        });
+        assert_eq!(*collector.0.lock().unwrap(), vec![
+            s!("instruction executed"),
+            s!("register written E1"),
+            s!("instruction executed"),
+            s!("register cleared E1"),
+            s!("instruction executed"),
+            s!("CK failed"),
+        ]);
+    }
 }
@@ -0,0 +1,569 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Arithmetic expressions compiled into GFA256 instructions.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops;
+
+use aluvm::{CoreExt, SiteId};
+
+use super::{BuildError, Instr, ProgramBuilder};
+use crate::{fe256, GfaConfig, GfaCore, RegE};
+
+/// Arithmetic expression over finite field elements.
+///
+/// Expressions are constructed from register values and constants with `+`, `-`, `*` and unary
+/// `-` operators, or with the corresponding constructor methods, and are compiled into a sequence
+/// of instructions with [`ExprCompiler`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Expr {
+    /// Value of a register.
+    Reg(RegE),
+
+    /// Constant field element.
+    Const(fe256),
+
+    /// Sum of two expressions.
+    Add(Box<Expr>, Box<Expr>),
+
+    /// Product of two expressions.
+    Mul(Box<Expr>, Box<Expr>),
+
+    /// Negation of an expression.
+    Neg(Box<Expr>),
+}
+
+impl Expr {
+    /// Constructs expression taking a value of a register.
+    pub fn reg(reg: RegE) -> Self { Self::Reg(reg) }
+
+    /// Constructs a constant expression.
+    pub fn val(val: impl Into<fe256>) -> Self { Self::Const(val.into()) }
+
+    /// Constructs a sum of two expressions.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(a: impl Into<Expr>, b: impl Into<Expr>) -> Self { Self::Add(Box::new(a.into()), Box::new(b.into())) }
+
+    /// Constructs a difference of two expressions.
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(a: impl Into<Expr>, b: impl Into<Expr>) -> Self { Self::add(a, Self::neg(b)) }
+
+    /// Constructs a product of two expressions.
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(a: impl Into<Expr>, b: impl Into<Expr>) -> Self { Self::Mul(Box::new(a.into()), Box::new(b.into())) }
+
+    /// Constructs a negation of an expression.
+    #[allow(clippy::should_implement_trait)]
+    pub fn neg(a: impl Into<Expr>) -> Self { Self::Neg(Box::new(a.into())) }
+
+    /// Checks whether the expression reads a value of a register.
+    pub fn reads(&self, reg: RegE) -> bool {
+        match self {
+            Expr::Reg(r) => *r == reg,
+            Expr::Const(_) => false,
+            Expr::Add(a, b) | Expr::Mul(a, b) => a.reads(reg) || b.reads(reg),
+            Expr::Neg(a) => a.reads(reg),
+        }
+    }
+
+    fn regs(&self, f: &mut impl FnMut(RegE)) {
+        match self {
+            Expr::Reg(r) => f(*r),
+            Expr::Const(_) => {}
+            Expr::Add(a, b) | Expr::Mul(a, b) => {
+                a.regs(f);
+                b.regs(f);
+            }
+            Expr::Neg(a) => a.regs(f),
+        }
+    }
+
+    /// Folds constant subexpressions and removes operations with neutral elements.
+    ///
+    /// Constants are folded using the same microcode which is used by the VM, such that the
+    /// simplified expression always evaluates to the same value as the original one.
+    pub fn simplify(self, config: GfaConfig) -> Self {
+        let fold = |a: fe256, b: Option<fe256>, op: fn(&mut GfaCore) -> aluvm::regs::Status| {
+            let mut core = GfaCore::with(config);
+            core.set(RegE::E1, a);
+            if let Some(b) = b {
+                core.set(RegE::E2, b);
+            }
+            op(&mut core);
+            core.get(RegE::E1).expect("registers are initialized")
+        };
+        match self {
+            Expr::Reg(_) | Expr::Const(_) => self,
+            Expr::Add(a, b) => match (a.simplify(config), b.simplify(config)) {
+                (Expr::Const(a), Expr::Const(b)) => {
+                    Expr::Const(fold(a, Some(b), |core| core.add_mod(RegE::E1, RegE::E2)))
+                }
+                (Expr::Const(fe256::ZERO), x) | (x, Expr::Const(fe256::ZERO)) => x,
+                (a, b) => Expr::add(a, b),
+            },
+            Expr::Mul(a, b) => match (a.simplify(config), b.simplify(config)) {
+                (Expr::Const(a), Expr::Const(b)) => {
+                    Expr::Const(fold(a, Some(b), |core| core.mul_mod(RegE::E1, RegE::E2)))
+                }
+                (Expr::Const(fe256::ZERO), _) | (_, Expr::Const(fe256::ZERO)) => Expr::Const(fe256::ZERO),
+                (Expr::Const(one), x) | (x, Expr::Const(one)) if one == fe256::from(1u8) => x,
+                (a, b) => Expr::mul(a, b),
+            },
+            Expr::Neg(a) => match a.simplify(config) {
+                // Negation of zero is not folded, since the VM doesn't handle it as a regular field
+                // operation.
+                Expr::Const(a) if a != fe256::ZERO => {
+                    Expr::Const(fold(a, None, |core| core.neg_mod(RegE::E1, RegE::E1)))
+                }
+                Expr::Neg(x) => *x,
+                a => Expr::neg(a),
+            },
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Reg(reg) => Display::fmt(reg, f),
+            Expr::Const(val) => Display::fmt(val, f),
+            Expr::Add(a, b) => write!(f, "({a} + {b})"),
+            Expr::Mul(a, b) => write!(f, "({a} * {b})"),
+            Expr::Neg(a) => write!(f, "-{a}"),
+        }
+    }
+}
+
+impl From<RegE> for Expr {
+    fn from(reg: RegE) -> Self { Expr::Reg(reg) }
+}
+
+impl From<fe256> for Expr {
+    fn from(val: fe256) -> Self { Expr::Const(val) }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty),+) => {$(
+        impl From<$ty> for Expr {
+            fn from(val: $ty) -> Self { Expr::Const(fe256::from(val)) }
+        }
+    )+};
+}
+impl_from_int!(u8, u16, u32, u64, u128);
+
+impl<T: Into<Expr>> ops::Add<T> for Expr {
+    type Output = Expr;
+    fn add(self, rhs: T) -> Self::Output { Expr::add(self, rhs) }
+}
+
+impl<T: Into<Expr>> ops::Sub<T> for Expr {
+    type Output = Expr;
+    fn sub(self, rhs: T) -> Self::Output { Expr::sub(self, rhs) }
+}
+
+impl<T: Into<Expr>> ops::Mul<T> for Expr {
+    type Output = Expr;
+    fn mul(self, rhs: T) -> Self::Output { Expr::mul(self, rhs) }
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+    fn neg(self) -> Self::Output { Expr::neg(self) }
+}
+
+/// Errors compiling expressions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ExprError {
+    /// {0}
+    #[from]
+    Build(BuildError),
+
+    /// not enough free registers to compile the expression.
+    ///
+    /// Since zk-AluVM has no memory which could be used to spill the intermediate values, the
+    /// expression must be split into several statements using intermediary destination
+    /// registers.
+    OutOfRegisters,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Stmt {
+    Assign(RegE, Expr),
+    AssertEq(Expr, Expr),
+}
+
+/// Compiler of arithmetic expressions into GFA256 instructions.
+///
+/// The compiler simplifies expressions, computes repeated subexpressions only once, and allocates
+/// registers for intermediate values. Registers used by the statements as inputs or outputs are
+/// never used for intermediate values.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::{Expr, ExprCompiler};
+/// # use zkaluvm::RegE::*;
+/// let mut compiler = ExprCompiler::<LibId>::new();
+/// compiler
+///     .assign(E3, Expr::mul(EA, EB) + 5u8)
+///     .assert_eq(Expr::mul(E3, 2u8) - 10u8, Expr::mul(EA, EB) * 2u8);
+/// let code = compiler.compile().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExprCompiler<Id: SiteId> {
+    builder: ProgramBuilder<Id>,
+    config: GfaConfig,
+    stmts: Vec<Stmt>,
+}
+
+impl<Id: SiteId> Default for ExprCompiler<Id> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<Id: SiteId> ExprCompiler<Id> {
+    /// Creates compiler for a VM with the default configuration.
+    pub fn new() -> Self { Self::with(GfaConfig::default()) }
+
+    /// Creates compiler for a VM with a specific configuration.
+    pub fn with(config: GfaConfig) -> Self { Self::with_builder(ProgramBuilder::with(config), config) }
+
+    /// Creates compiler appending the code to an existing program.
+    ///
+    /// Registers, which are initialized by the existing code, are not used for intermediate values.
+    pub fn with_builder(builder: ProgramBuilder<Id>, config: GfaConfig) -> Self {
+        Self {
+            builder,
+            config,
+            stmts: Vec::new(),
+        }
+    }
+
+    /// Adds a statement computing the expression and putting the result into `dst` register.
+    pub fn assign(&mut self, dst: RegE, expr: impl Into<Expr>) -> &mut Self {
+        self.stmts.push(Stmt::Assign(dst, expr.into()));
+        self
+    }
+
+    /// Adds a statement failing the program if the two expressions are not equal.
+    pub fn assert_eq(&mut self, lhs: impl Into<Expr>, rhs: impl Into<Expr>) -> &mut Self {
+        self.stmts.push(Stmt::AssertEq(lhs.into(), rhs.into()));
+        self
+    }
+
+    /// Compiles all statements, returning a builder which can be used to extend the program.
+    pub fn compile_into(self) -> Result<ProgramBuilder<Id>, ExprError> {
+        let mut reserved = [false; 16];
+        for reg in RegE::ALL {
+            reserved[reg as usize] = self.builder.is_init(reg);
+        }
+        for stmt in &self.stmts {
+            match stmt {
+                Stmt::Assign(dst, expr) => {
+                    reserved[*dst as usize] = true;
+                    expr.regs(&mut |reg| reserved[reg as usize] = true);
+                }
+                Stmt::AssertEq(lhs, rhs) => {
+                    lhs.regs(&mut |reg| reserved[reg as usize] = true);
+                    rhs.regs(&mut |reg| reserved[reg as usize] = true);
+                }
+            }
+        }
+        let mut gen = Codegen {
+            builder: self.builder,
+            pool: RegE::ALL.map(|reg| !reserved[reg as usize]),
+            free: RegE::ALL
+                .into_iter()
+                .rev()
+                .filter(|reg| !reserved[*reg as usize])
+                .collect(),
+            shared: Vec::new(),
+            memo: Vec::new(),
+            dst: None,
+        };
+
+        for stmt in self.stmts {
+            match stmt {
+                Stmt::Assign(dst, expr) => {
+                    let expr = expr.simplify(self.config);
+                    gen.prepare(&[&expr]);
+                    let mut hint = if expr.reads(dst) { None } else { Some(dst) };
+                    gen.dst = hint;
+                    let res = gen.expr(&expr, &mut hint)?;
+                    if res.reg != dst {
+                        gen.builder.mov(dst, res.reg)?;
+                    }
+                    gen.release(res);
+                }
+                Stmt::AssertEq(lhs, rhs) => {
+                    let lhs = lhs.simplify(self.config);
+                    let rhs = rhs.simplify(self.config);
+                    gen.prepare(&[&lhs, &rhs]);
+                    gen.dst = None;
+                    let a = gen.expr(&lhs, &mut None)?;
+                    let b = gen.expr(&rhs, &mut None)?;
+                    gen.builder.eq(a.reg, b.reg)?.chk_co()?;
+                    gen.release(a);
+                    gen.release(b);
+                }
+            }
+            gen.finish_stmt();
+        }
+        Ok(gen.builder)
+    }
+
+    /// Compiles all statements into the program code.
+    pub fn compile(self) -> Result<Vec<Instr<Id>>, ExprError> { self.compile_into().map(ProgramBuilder::finish) }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Operand {
+    reg: RegE,
+    /// Whether the value in the register may be overwritten.
+    owned: bool,
+}
+
+struct Codegen<Id: SiteId> {
+    builder: ProgramBuilder<Id>,
+    /// Registers which can be used for intermediate values.
+    pool: [bool; 16],
+    /// Free registers, in the reverse order of their preference.
+    free: Vec<RegE>,
+    /// Subexpressions which are used more than once in the current statement.
+    shared: Vec<Expr>,
+    /// Registers holding values of already computed shared subexpressions.
+    memo: Vec<(Expr, RegE)>,
+    /// Destination register of the current statement, which can be used for intermediate values.
+    dst: Option<RegE>,
+}
+
+impl<Id: SiteId> Codegen<Id> {
+    fn prepare(&mut self, exprs: &[&Expr]) {
+        fn count<'e>(expr: &'e Expr, seen: &mut Vec<&'e Expr>, shared: &mut Vec<Expr>) {
+            if matches!(expr, Expr::Reg(_)) {
+                return;
+            }
+            if seen.contains(&expr) {
+                if !shared.contains(expr) {
+                    shared.push(expr.clone());
+                }
+                return;
+            }
+            seen.push(expr);
+            match expr {
+                Expr::Add(a, b) | Expr::Mul(a, b) => {
+                    count(a, seen, shared);
+                    count(b, seen, shared);
+                }
+                Expr::Neg(a) => count(a, seen, shared),
+                Expr::Reg(_) | Expr::Const(_) => {}
+            }
+        }
+        let mut seen = Vec::new();
+        for expr in exprs {
+            count(expr, &mut seen, &mut self.shared);
+        }
+        for reg in RegE::ALL {
+            if exprs.iter().any(|expr| expr.reads(reg)) {
+                self.builder.assume_init(reg);
+            }
+        }
+    }
+
+    fn finish_stmt(&mut self) {
+        for (_, reg) in core::mem::take(&mut self.memo) {
+            self.release(Operand { reg, owned: true });
+        }
+        self.shared.clear();
+    }
+
+    fn alloc(&mut self, hint: &mut Option<RegE>) -> Result<RegE, ExprError> {
+        hint.take()
+            .or_else(|| self.free.pop())
+            .ok_or(ExprError::OutOfRegisters)
+    }
+
+    fn release(&mut self, op: Operand) {
+        if op.owned && self.pool[op.reg as usize] && !self.free.contains(&op.reg) {
+            self.free.push(op.reg);
+            self.free.sort_by(|a, b| b.cmp(a));
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, hint: &mut Option<RegE>) -> Result<Operand, ExprError> {
+        if !self.shared.contains(expr) {
+            return self.compute(expr, hint);
+        }
+        if let Some((_, reg)) = self.memo.iter().find(|(e, _)| e == expr) {
+            return Ok(Operand {
+                reg: *reg,
+                owned: false,
+            });
+        }
+        let res = self.compute(expr, &mut None)?;
+        self.memo.push((expr.clone(), res.reg));
+        Ok(Operand {
+            reg: res.reg,
+            owned: false,
+        })
+    }
+
+    fn compute(&mut self, expr: &Expr, hint: &mut Option<RegE>) -> Result<Operand, ExprError> {
+        Ok(match expr {
+            Expr::Reg(reg) => Operand {
+                reg: *reg,
+                owned: false,
+            },
+            Expr::Const(val) => {
+                let reg = self.alloc(hint)?;
+                self.builder.put(reg, *val)?;
+                Operand { reg, owned: true }
+            }
+            Expr::Neg(a) => {
+                let a = self.expr(a, hint)?;
+                let reg = if a.owned { a.reg } else { self.alloc(hint)? };
+                self.builder.neg(reg, a.reg)?;
+                Operand { reg, owned: true }
+            }
+            Expr::Add(a, b) | Expr::Mul(a, b) => {
+                let a = self.expr(a, hint)?;
+                let b = self.expr(b, hint)?;
+                let (acc, src) = if a.owned && (!b.owned || Some(b.reg) != self.dst) {
+                    (a, b)
+                } else if b.owned {
+                    (b, a)
+                } else {
+                    let reg = self.alloc(hint)?;
+                    self.builder.mov(reg, a.reg)?;
+                    (Operand { reg, owned: true }, b)
+                };
+                match expr {
+                    Expr::Add(..) => self.builder.add(acc.reg, src.reg)?,
+                    _ => self.builder.mul(acc.reg, src.reg)?,
+                };
+                self.release(src);
+                acc
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::FieldInstr;
+    use crate::RegE::*;
+
+    fn compile(f: impl FnOnce(&mut ExprCompiler<LibId>)) -> Vec<Instr<LibId>> {
+        let mut compiler = ExprCompiler::new();
+        f(&mut compiler);
+        compiler.compile().unwrap()
+    }
+
+    #[test]
+    fn display() {
+        let expr = Expr::mul(EA, EB) + 5u8 - E1;
+        assert_eq!(expr.to_string(), "(((EA * EB) + 5.fe) + -E1)");
+    }
+
+    #[test]
+    fn simplify() {
+        let config = GfaConfig::default();
+        assert_eq!((Expr::val(2u8) * 3u8 + 1u8).simplify(config), Expr::val(7u8));
+        assert_eq!((Expr::reg(E1) * 1u8 + 0u8).simplify(config), Expr::reg(E1));
+        assert_eq!(Expr::mul(E1, 0u8).simplify(config), Expr::val(0u8));
+        assert_eq!((-(-Expr::reg(E1))).simplify(config), Expr::reg(E1));
+        assert_eq!((-Expr::val(0u8)).simplify(config), -Expr::val(0u8));
+        let fq = config.field_order;
+        assert_eq!((-Expr::val(1u8)).simplify(config), Expr::val(fq - amplify::num::u256::ONE));
+    }
+
+    #[test]
+    fn assign_in_place() {
+        let code = compile(|c| {
+            c.assign(E3, Expr::mul(EA, EB) + 5u8);
+        });
+        assert_eq!(code, zk_aluasm! {
+            mov     E3, EA;
+            mul     E3, EB;
+            put     E1, 5;
+            add     E3, E1;
+        });
+    }
+
+    #[test]
+    fn assign_self() {
+        let code = compile(|c| {
+            c.assign(E1, Expr::reg(E1) * E1 + 1u8);
+        });
+        assert_eq!(code, vec![
+            FieldInstr::Mov { dst: E2, src: E1 }.into(),
+            FieldInstr::Mul { dst_src: E2, src: E1 }.into(),
+            FieldInstr::PutV {
+                dst: E3,
+                val: crate::gfa::ConstVal::Val1
+            }
+            .into(),
+            FieldInstr::Add { dst_src: E2, src: E3 }.into(),
+            FieldInstr::Mov { dst: E1, src: E2 }.into(),
+        ]);
+    }
+
+    #[test]
+    fn common_subexpr() {
+        let code = compile(|c| {
+            c.assert_eq(Expr::mul(EA, EB) + EC, Expr::mul(EA, EB) * ED);
+        });
+        assert_eq!(code, zk_aluasm! {
+            mov     E1, EA;
+            mul     E1, EB;
+            mov     E2, E1;
+            add     E2, EC;
+            mov     E3, E1;
+            mul     E3, ED;
+            eq      E2, E3;
+            chk     CO;
+        });
+    }
+
+    #[test]
+    fn out_of_registers() {
+        let mut compiler = ExprCompiler::<LibId>::new();
+        let mut expr = Expr::reg(EA);
+        for reg in RegE::ALL {
+            expr = Expr::mul(expr, reg);
+        }
+        compiler.assign(EH, Expr::val(2u8) * (Expr::val(3u8) + expr));
+        assert_eq!(compiler.compile(), Err(ExprError::OutOfRegisters));
+    }
+}
@@ -20,14 +20,23 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
-use aluvm::isa::{CtrlInstr, ReservedInstr};
-use aluvm::SiteId;
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use core::cmp::Ordering;
+use core::str::FromStr;
+
+use aluvm::isa::{Bytecode, CtrlInstr, ReservedInstr};
+use aluvm::{Site, SiteId};
 use amplify::num::{u2, u3};
 
 use crate::{fe256, RegE};
 
 /// Instruction set, which includes core AluVM control-flow instructions and GFA256 ISA extension
 /// (see [`FieldInstr`]).
+///
+/// Instructions have a canonical ordering, which sorts them by their opcode byte first, and then
+/// by the instruction operands, in the order they are declared. Field arithmetic instructions
+/// sharing the same opcode are ordered as [`FieldInstr`] variants are.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, From)]
 #[display(inner)]
 #[non_exhaustive]
@@ -41,12 +50,18 @@ pub enum Instr<Id: SiteId> {
     Gfa(FieldInstr),
 
     /// Reserved instruction for future use in core `ALU` ISAs.
+    ///
+    /// Fails `CK`, unless it is handled by the host (see [`super::GfaContext::trap`]).
     #[from]
     Reserved(ReservedInstr),
 }
 
 /// Arithmetic instructions for finite fields.
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+///
+/// Instructions are ordered by the order of the variant declaration, which follows their opcodes,
+/// and then by their operands, in the order they are declared. Registers are ordered by their
+/// index, and field elements by their canonical integer representation.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[non_exhaustive]
 pub enum FieldInstr {
     /// Tests if register contains a value and is not set to `None`.
@@ -72,7 +87,8 @@ pub enum FieldInstr {
 
     /// Puts value into a register, replacing the previous value in it if there was any.
     ///
-    /// Does not affect values in the `CO` and `CK` registers.
+    /// Does not affect values in the `CO` register. If the value is not less than the field order,
+    /// clears the register and sets `CK` to [`Status::Fail`].
     #[display("put     {dst}, {data}")]
     PutD {
         /** The destination register */
@@ -93,7 +109,8 @@ pub enum FieldInstr {
     /// Puts `val` value, which is a power of 2, into a register, replacing the previous value in
     /// it if there was any.
     ///
-    /// Does not affect values in the `CO` and `CK` registers.
+    /// Does not affect values in the `CO` register. If the value is not less than the field order,
+    /// clears the register and sets `CK` to [`Status::Fail`].
     #[display("put     {dst}, {val}")]
     PutV {
         /** The destination register */
@@ -188,6 +205,308 @@ pub enum FieldInstr {
         /** The second source register */
         src: RegE,
     },
+
+    /// Invokes the host syscall handler with the given identifier (see
+    /// [`super::GfaContext::ecall`]).
+    ///
+    /// The handler may read and write registers of the call window (see [`super::ECALL_WINDOW`]);
+    /// the written values are applied only if the handler succeeds. The complexity charged by the
+    /// handler is added to the complexity of the instruction; if it exceeds the complexity limit,
+    /// the program halts.
+    ///
+    /// Does not affect values in the `CO` register. If the handler fails, sets `CK` to
+    /// [`Status::Fail`] leaving the registers unchanged; otherwise leaves value in the `CK`
+    /// unchanged.
+    #[display("ecall   {id}")]
+    Ecall {
+        /** The identifier of the host syscall */
+        id: u8,
+    },
+
+    /// Compute multiplicative inverse of `src` value using finite-field arithmetics of the `FQ`
+    /// order, and put result into `dst`.
+    ///
+    /// Does not affect values in the `CO` register.
+    ///
+    /// If `src` is set to `None` or to zero, which has no inverse, sets `CK` to [`Status::Fail`]
+    /// leaving `dst` unchanged; otherwise leaves value in the `CK` unchanged.
+    #[display("inv     {dst}, {src}")]
+    Inv {
+        /** The destination register */
+        dst: RegE,
+        /** The source register */
+        src: RegE,
+    },
+
+    /// Raise `dst_src` value to the power of `exp` value using finite-field arithmetics of the `FQ`
+    /// order, putting the result to `dst_src`.
+    ///
+    /// The exponent is taken as an integer; zero to the power of zero is one.
+    ///
+    /// Does not affect values in the `CO` register.
+    ///
+    /// If either `exp` or `dst_src` (or both) is set to `None`, sets `CK` to [`Status::Fail`];
+    /// otherwise leaves value in the `CK` unchanged.
+    #[display("exp     {dst_src}, {exp}")]
+    Exp {
+        /** The base and the destination register */
+        dst_src: RegE,
+        /** The exponent register */
+        exp: RegE,
+    },
+
+    /// Raise `dst_src` value to the power of the immediate `exp` value using finite-field
+    /// arithmetics of the `FQ` order, putting the result to `dst_src`.
+    ///
+    /// Zero to the power of zero is one.
+    ///
+    /// Does not affect values in the `CO` register.
+    ///
+    /// If `dst_src` is set to `None`, sets `CK` to [`Status::Fail`]; otherwise leaves value in the
+    /// `CK` unchanged.
+    #[display("exp     {dst_src}, {exp}")]
+    ExpI {
+        /** The base and the destination register */
+        dst_src: RegE,
+        /** The exponent */
+        exp: u8,
+    },
+
+    /// Square `src` value using finite-field arithmetics of the `FQ` order, and put result into
+    /// `dst`.
+    ///
+    /// Does not affect values in the `CO` register.
+    ///
+    /// If `src` is set to `None`, sets `CK` to [`Status::Fail`] leaving `dst` unchanged; otherwise
+    /// leaves value in the `CK` unchanged.
+    #[display("sqr     {dst}, {src}")]
+    Sqr {
+        /** The destination register */
+        dst: RegE,
+        /** The source register */
+        src: RegE,
+    },
+
+    /// Compute a square root of `src` value using finite-field arithmetics of the `FQ` order, and
+    /// put the root with the smaller canonical representation into `dst`.
+    ///
+    /// If the root exists, sets `CO` to [`Status::Ok`]; otherwise (the value is a quadratic
+    /// non-residue) sets `CO` to [`Status::Fail`], leaving `dst` unchanged.
+    ///
+    /// For the field orders which are 1 modulo 4 the instruction searches for a quadratic
+    /// non-residue among the numbers below the square of the bit length of the order. If `src` is
+    /// set to `None`, or no non-residue is found, sets `CK` to [`Status::Fail`] leaving `CO` and
+    /// `dst` unchanged; otherwise leaves value in the `CK` unchanged. For a prime order, a
+    /// non-residue always exists within the bound if the generalized Riemann hypothesis holds, while
+    /// for some composite orders, like squares, the search fails.
+    #[display("sqrt    {dst}, {src}")]
+    Sqrt {
+        /** The destination register */
+        dst: RegE,
+        /** The source register */
+        src: RegE,
+    },
+
+    /// Moves (copies) value from `src` to `dst` register if `CO` is set to [`Status::Ok`];
+    /// otherwise leaves `dst` unchanged. If the move happens and `src` has no value, sets `dst` to
+    /// `None`.
+    ///
+    /// Leaves the state of the `src` register unaffected.
+    ///
+    /// Does not affect values in the `CO` and `CK` registers.
+    #[display("cmov    {dst}, {src}")]
+    CMov {
+        /** The destination register */
+        dst: RegE,
+        /** The source register */
+        src: RegE,
+    },
+
+    /// Moves (copies) value from `src_true` register to `dst` if `CO` is set to [`Status::Ok`], and
+    /// from `src_false` register otherwise, overwriting the previous value in `dst`. If the selected
+    /// source has no value, sets `dst` to `None`.
+    ///
+    /// Leaves the state of the source registers unaffected.
+    ///
+    /// Does not affect values in the `CO` and `CK` registers.
+    #[display("sel     {dst}, {src_true}, {src_false}")]
+    Sel {
+        /** The destination register */
+        dst: RegE,
+        /** The source register selected if `CO` is set */
+        src_true: RegE,
+        /** The source register selected if `CO` is not set */
+        src_false: RegE,
+    },
+
+    /// Checks whether the value in `src1` is less than the value in `src2`, comparing them as
+    /// integers in the range from zero to the field order.
+    ///
+    /// Sets `CO` register to [`Status::Ok`] if `src1` is less than `src2`, and to [`Status::Fail`]
+    /// otherwise.
+    ///
+    /// If any of the source registers is set to `None`, sets `CK` to [`Status::Fail`] and leaves
+    /// `CO` unchanged; otherwise leaves value in the `CK` unchanged.
+    #[display("lt      {src1}, {src2}")]
+    Lt {
+        /** The first source register */
+        src1: RegE,
+        /** The second source register */
+        src2: RegE,
+    },
+
+    /// Checks whether the value in `src` is less than `bound`, comparing them as 256-bit integers.
+    ///
+    /// Sets `CO` register to [`Status::Ok`] if `src` is less than `bound`, and to [`Status::Fail`]
+    /// otherwise.
+    ///
+    /// If `src` is set to `None`, sets `CK` to [`Status::Fail`] and leaves `CO` unchanged;
+    /// otherwise leaves value in the `CK` unchanged.
+    #[display("rng     {src}, {bound}")]
+    Rng {
+        /** The source register */
+        src: RegE,
+        /** The exclusive upper bound taken from the data segment */
+        bound: fe256,
+    },
+}
+
+impl<Id: SiteId> Instr<Id> {
+    /// Returns mnemonic of the instruction, as used by the assembler.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instr::Ctrl(ctrl) => match ctrl {
+                CtrlInstr::Nop => "nop",
+                CtrlInstr::ChkCo | CtrlInstr::ChkCk => "chk",
+                CtrlInstr::NotCo => "not",
+                CtrlInstr::FailCk => "fail",
+                CtrlInstr::RsetCk => "mov",
+                CtrlInstr::Jmp { .. } | CtrlInstr::Sh { .. } | CtrlInstr::Exec { .. } => "jmp",
+                CtrlInstr::JiOvfl { .. }
+                | CtrlInstr::JiFail { .. }
+                | CtrlInstr::ShOvfl { .. }
+                | CtrlInstr::ShFail { .. } => "jif",
+                CtrlInstr::Fn { .. } | CtrlInstr::Call { .. } => "call",
+                CtrlInstr::Ret => "ret",
+                CtrlInstr::Stop => "stop",
+            },
+            Instr::Gfa(instr) => instr.info().mnemonic,
+            Instr::Reserved(_) => "halt",
+        }
+    }
+
+    /// Returns class of the instruction.
+    pub fn class(&self) -> InstrClass {
+        match self {
+            Instr::Ctrl(_) => InstrClass::Control,
+            Instr::Gfa(
+                FieldInstr::Clr { .. }
+                | FieldInstr::PutD { .. }
+                | FieldInstr::PutZ { .. }
+                | FieldInstr::PutV { .. }
+                | FieldInstr::Mov { .. }
+                | FieldInstr::CMov { .. }
+                | FieldInstr::Sel { .. },
+            ) => InstrClass::Move,
+            Instr::Gfa(
+                FieldInstr::Test { .. }
+                | FieldInstr::Fits { .. }
+                | FieldInstr::Eq { .. }
+                | FieldInstr::Lt { .. }
+                | FieldInstr::Rng { .. },
+            ) => InstrClass::Check,
+            Instr::Gfa(
+                FieldInstr::Neg { .. }
+                | FieldInstr::Add { .. }
+                | FieldInstr::Mul { .. }
+                | FieldInstr::Inv { .. }
+                | FieldInstr::Exp { .. }
+                | FieldInstr::ExpI { .. }
+                | FieldInstr::Sqr { .. }
+                | FieldInstr::Sqrt { .. },
+            ) => InstrClass::Arithmetic,
+            Instr::Gfa(FieldInstr::Ecall { .. }) => InstrClass::Host,
+            Instr::Reserved(_) => InstrClass::Reserved,
+        }
+    }
+}
+
+/// Class of an instruction, grouping instructions by the kind of the operation they perform.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display(lowercase)]
+pub enum InstrClass {
+    /// Control flow instructions and instructions operating on `CO` and `CK` registers.
+    Control,
+    /// Instructions putting, moving or clearing register values.
+    Move,
+    /// Instructions checking register values and setting `CO`.
+    Check,
+    /// Field arithmetic instructions.
+    Arithmetic,
+    /// Host syscall instructions.
+    Host,
+    /// Reserved instructions.
+    Reserved,
+}
+
+impl<Id: SiteId> PartialOrd for Instr<Id> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<Id: SiteId> Ord for Instr<Id> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let opcode = |instr: &Self| Bytecode::<Id>::opcode_byte(instr);
+        opcode(self)
+            .cmp(&opcode(other))
+            .then_with(|| match (self, other) {
+                (Instr::Ctrl(a), Instr::Ctrl(b)) => ctrl_operands(a).cmp(&ctrl_operands(b)),
+                (Instr::Gfa(a), Instr::Gfa(b)) => a.cmp(b),
+                (Instr::Reserved(a), Instr::Reserved(b)) => a.cmp(b),
+                // Instructions of different kinds never share an opcode
+                (Instr::Ctrl(_), _) => Ordering::Less,
+                (_, Instr::Ctrl(_)) => Ordering::Greater,
+                (Instr::Gfa(_), _) => Ordering::Less,
+                (_, Instr::Gfa(_)) => Ordering::Greater,
+            })
+    }
+}
+
+/// Returns operands of a control flow instruction in a comparable form; the instruction variant is
+/// defined by its opcode.
+fn ctrl_operands<Id: SiteId>(instr: &CtrlInstr<Id>) -> (Option<u16>, Option<i8>, Option<Site<Id>>) {
+    match *instr {
+        CtrlInstr::Jmp { pos } | CtrlInstr::JiOvfl { pos } | CtrlInstr::JiFail { pos } | CtrlInstr::Fn { pos } => {
+            (Some(pos), None, None)
+        }
+        CtrlInstr::Sh { shift } | CtrlInstr::ShOvfl { shift } | CtrlInstr::ShFail { shift } => {
+            (None, Some(shift), None)
+        }
+        CtrlInstr::Exec { site } | CtrlInstr::Call { site } => (None, None, Some(site)),
+        CtrlInstr::Nop
+        | CtrlInstr::ChkCo
+        | CtrlInstr::ChkCk
+        | CtrlInstr::NotCo
+        | CtrlInstr::FailCk
+        | CtrlInstr::RsetCk
+        | CtrlInstr::Ret
+        | CtrlInstr::Stop => (None, None, None),
+    }
+}
+
+impl FieldInstr {
+    /// Constructs an instruction putting a value into a register, using the most compact encoding:
+    /// [`FieldInstr::PutZ`] for zero, [`FieldInstr::PutV`] for the values matching one of
+    /// [`ConstVal`] field-independent constants, and [`FieldInstr::PutD`] otherwise.
+    pub fn put(dst: RegE, val: fe256) -> Self {
+        if val == fe256::ZERO {
+            return FieldInstr::PutZ { dst };
+        }
+        [ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max]
+            .into_iter()
+            .find(|c| c.to_fe256() == Some(val))
+            .map(|val| FieldInstr::PutV { dst, val })
+            .unwrap_or(FieldInstr::PutD { dst, data: val })
+    }
 }
 
 /// A predefined constant field element for a register initialization.
@@ -195,7 +514,7 @@ pub enum FieldInstr {
 /// These constants are used to keep the space and complexity metric of the code low, since reading
 /// a field element from the data segment will take 16 bytes in the code segment; while initializing
 /// with a common constant will take just 2 bits.
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[repr(u8)]
 pub enum ConstVal {
     /// Zero field element.
@@ -218,16 +537,54 @@ pub enum ConstVal {
 
 impl From<u2> for ConstVal {
     fn from(val: u2) -> Self {
-        match val {
-            x if x == ConstVal::Val1.to_u2() => ConstVal::Val1,
-            x if x == ConstVal::ValU64Max.to_u2() => ConstVal::ValU64Max,
-            x if x == ConstVal::ValU128Max.to_u2() => ConstVal::ValU128Max,
-            x if x == ConstVal::ValFeMAX.to_u2() => ConstVal::ValFeMAX,
-            _ => unreachable!(),
+        match val.to_u8() {
+            0 => ConstVal::Val1,
+            1 => ConstVal::ValU64Max,
+            2 => ConstVal::ValU128Max,
+            // 2-bit values never exceed 3
+            _ => ConstVal::ValFeMAX,
         }
     }
 }
 
+/// Error converting a number into a constant value variant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("constant value index {0} is out of range; only indexes from 0 to 3 are allowed.")]
+pub struct ConstValOutOfRange(pub u8);
+
+impl TryFrom<u8> for ConstVal {
+    type Error = ConstValOutOfRange;
+
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
+        Ok(match val {
+            0 => ConstVal::Val1,
+            1 => ConstVal::ValU64Max,
+            2 => ConstVal::ValU128Max,
+            3 => ConstVal::ValFeMAX,
+            _ => return Err(ConstValOutOfRange(val)),
+        })
+    }
+}
+
+/// Error parsing constant field element value.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("unknown constant value `{0}`.")]
+pub struct ParseConstValError(pub String);
+
+impl FromStr for ConstVal {
+    type Err = ParseConstValError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "1" => ConstVal::Val1,
+            "ffff_ffff_ffff_ffff#h" => ConstVal::ValU64Max,
+            "ffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff#h" => ConstVal::ValU128Max,
+            "-1#fe" => ConstVal::ValFeMAX,
+            _ => return Err(ParseConstValError(s.to_owned())),
+        })
+    }
+}
+
 impl ConstVal {
     /// Get a 2-bit representation of the constant value.
     #[inline]
@@ -249,7 +606,7 @@ impl ConstVal {
 
 /// Maximum bit dimension which a register value should fit (used in [`FieldInstr::Fits`]
 /// instruction).
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[repr(u8)]
 pub enum Bits {
     /// 8 bits (a byte).
@@ -287,32 +644,56 @@ pub enum Bits {
 
 impl From<u3> for Bits {
     fn from(val: u3) -> Self {
-        match val {
-            x if x == Bits::Bits8.to_u3() => Bits::Bits8,
-            x if x == Bits::Bits16.to_u3() => Bits::Bits16,
-            x if x == Bits::Bits24.to_u3() => Bits::Bits24,
-            x if x == Bits::Bits32.to_u3() => Bits::Bits32,
-            x if x == Bits::Bits48.to_u3() => Bits::Bits48,
-            x if x == Bits::Bits64.to_u3() => Bits::Bits64,
-            x if x == Bits::Bits96.to_u3() => Bits::Bits96,
-            x if x == Bits::Bits128.to_u3() => Bits::Bits96,
-            _ => unreachable!(),
+        match val.to_u8() {
+            0 => Bits::Bits8,
+            1 => Bits::Bits16,
+            2 => Bits::Bits24,
+            3 => Bits::Bits32,
+            4 => Bits::Bits48,
+            5 => Bits::Bits64,
+            6 => Bits::Bits96,
+            // 3-bit values never exceed 7
+            _ => Bits::Bits128,
         }
     }
 }
 
-impl Bits {
-    /// Get a 3-bit representation of the bit dimension variant.
-    #[inline]
-    pub const fn to_u3(self) -> u3 { u3::with(self as u8) }
+/// Error converting a number into a bit dimension variant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("bit dimension index {0} is out of range; only indexes from 0 to 7 are allowed.")]
+pub struct BitsOutOfRange(pub u8);
 
-    /// Construct a dimension variant a bit out of bit length.
-    ///
-    /// # Panics
-    ///
-    /// If there is no enum variant matching the provided bit length.
-    pub fn from_bit_len(len: usize) -> Self {
-        match len {
+/// Error converting a bit length into a bit dimension variant.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("unsupported bit length {0}.")]
+pub struct UnsupportedBitLen(pub usize);
+
+impl TryFrom<u8> for Bits {
+    type Error = BitsOutOfRange;
+
+    /// Converts a 3-bit index of the bit dimension, matching [`Bits::to_u3`], into the enum
+    /// variant. To convert bit length, use [`Bits::try_from`] with `usize` argument.
+    fn try_from(val: u8) -> Result<Self, Self::Error> {
+        Ok(match val {
+            0 => Bits::Bits8,
+            1 => Bits::Bits16,
+            2 => Bits::Bits24,
+            3 => Bits::Bits32,
+            4 => Bits::Bits48,
+            5 => Bits::Bits64,
+            6 => Bits::Bits96,
+            7 => Bits::Bits128,
+            _ => return Err(BitsOutOfRange(val)),
+        })
+    }
+}
+
+impl TryFrom<usize> for Bits {
+    type Error = UnsupportedBitLen;
+
+    /// Converts a bit length into the bit dimension variant.
+    fn try_from(len: usize) -> Result<Self, Self::Error> {
+        Ok(match len {
             8 => Bits::Bits8,
             16 => Bits::Bits16,
             24 => Bits::Bits24,
@@ -321,9 +702,47 @@ impl Bits {
             64 => Bits::Bits64,
             96 => Bits::Bits96,
             128 => Bits::Bits128,
-            invalid => panic!("unsupported bit length {invalid}"),
-        }
+            _ => return Err(UnsupportedBitLen(len)),
+        })
     }
+}
+
+/// Error parsing bit dimension.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(
+    "invalid bit dimension `{0}`; expected `8.bits`, `16.bits`, `24.bits`, `32.bits`, `48.bits`, `64.bits`, `96.bits` \
+     or `128.bits`."
+)]
+pub struct ParseBitsError(pub String);
+
+impl FromStr for Bits {
+    type Err = ParseBitsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "8.bits" => Bits::Bits8,
+            "16.bits" => Bits::Bits16,
+            "24.bits" => Bits::Bits24,
+            "32.bits" => Bits::Bits32,
+            "48.bits" => Bits::Bits48,
+            "64.bits" => Bits::Bits64,
+            "96.bits" => Bits::Bits96,
+            "128.bits" => Bits::Bits128,
+            _ => return Err(ParseBitsError(s.to_owned())),
+        })
+    }
+}
+
+impl Bits {
+    /// Get a 3-bit representation of the bit dimension variant.
+    #[inline]
+    pub const fn to_u3(self) -> u3 { u3::with(self as u8) }
+
+    /// Construct a dimension variant a bit out of bit length.
+    ///
+    /// Returns `None` if there is no enum variant matching the provided bit length; to get an
+    /// error instead use [`Bits::try_from`] with `usize` argument.
+    pub fn from_bit_len(len: usize) -> Option<Self> { Self::try_from(len).ok() }
 
     /// Returns a bit length corresponding to the enum variant.
     pub const fn bit_len(self) -> usize {
@@ -339,3 +758,293 @@ impl Bits {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+mod _serde {
+    use alloc::format;
+
+    use aluvm::isa::{Bytecode, BytecodeRead};
+    use aluvm::{LibId, LibsSeg, Marshaller};
+    use amplify::confinement::SmallBlob;
+    use serde::de::{Error, Unexpected};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    /// Bytecode representation of a single instruction, used by binary serialization formats.
+    #[derive(Serialize, Deserialize)]
+    struct InstrBytecode {
+        code: SmallBlob,
+        data: SmallBlob,
+        libs: LibsSeg,
+    }
+
+    impl InstrBytecode {
+        fn encode<I: Bytecode<LibId>>(instr: &I) -> Result<Self, String> {
+            let libs = LibsSeg::from_iter_checked(instr.external_ref());
+            let mut marshaller = Marshaller::new(&libs);
+            instr
+                .encode_instr(&mut marshaller)
+                .map_err(|e| e.to_string())?;
+            let (code, data) = marshaller.finish();
+            Ok(Self { code, data, libs })
+        }
+
+        fn decode<I: Bytecode<LibId>>(&self) -> Result<I, String> {
+            let mut marshaller = Marshaller::with(&self.code, &self.data, &self.libs);
+            let instr = I::decode_instr(&mut marshaller).map_err(|e| e.to_string())?;
+            if !marshaller.is_eof() {
+                return Err(s!("the instruction bytecode has trailing bytes"));
+            }
+            Ok(instr)
+        }
+    }
+
+    fn parse<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instr<LibId>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Instr::from_str(&s).map_err(|e| D::Error::invalid_value(Unexpected::Str(&s), &e.to_string().as_str()))
+    }
+
+    impl Serialize for Instr<LibId> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+            if serializer.is_human_readable() {
+                self.to_string().serialize(serializer)
+            } else {
+                InstrBytecode::encode(self)
+                    .map_err(serde::ser::Error::custom)?
+                    .serialize(serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Instr<LibId> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de> {
+            if deserializer.is_human_readable() {
+                parse(deserializer)
+            } else {
+                InstrBytecode::deserialize(deserializer)?
+                    .decode()
+                    .map_err(D::Error::custom)
+            }
+        }
+    }
+
+    impl Serialize for FieldInstr {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+            if serializer.is_human_readable() {
+                self.to_string().serialize(serializer)
+            } else {
+                InstrBytecode::encode::<FieldInstr>(self)
+                    .map_err(serde::ser::Error::custom)?
+                    .serialize(serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for FieldInstr {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de> {
+            if deserializer.is_human_readable() {
+                match parse(deserializer)? {
+                    Instr::Gfa(instr) => Ok(instr),
+                    instr => Err(D::Error::custom(format!("`{instr}` is not a field arithmetic instruction"))),
+                }
+            } else {
+                InstrBytecode::deserialize(deserializer)?
+                    .decode::<FieldInstr>()
+                    .map_err(D::Error::custom)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::collections::BTreeSet;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::testing::{random_code, TestRng};
+
+    #[test]
+    fn ordering() {
+        let code: Vec<Instr<LibId>> = vec![
+            CtrlInstr::Jmp { pos: 2 }.into(),
+            FieldInstr::Mul {
+                dst_src: RegE::E1,
+                src: RegE::E2,
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(2u8),
+            }
+            .into(),
+            FieldInstr::Test { src: RegE::E2 }.into(),
+            CtrlInstr::Nop.into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(1u8),
+            }
+            .into(),
+            CtrlInstr::Jmp { pos: 1 }.into(),
+            FieldInstr::Test { src: RegE::E1 }.into(),
+        ];
+        let mut sorted = code.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![
+            CtrlInstr::Nop.into(),
+            CtrlInstr::Jmp { pos: 1 }.into(),
+            CtrlInstr::Jmp { pos: 2 }.into(),
+            FieldInstr::Test { src: RegE::E1 }.into(),
+            FieldInstr::Test { src: RegE::E2 }.into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(1u8),
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(2u8),
+            }
+            .into(),
+            FieldInstr::Mul {
+                dst_src: RegE::E1,
+                src: RegE::E2,
+            }
+            .into(),
+        ]);
+
+        // The ordering is consistent with the opcodes and equality
+        let code = random_code::<Instr<LibId>>(&mut TestRng::with(5), 1000);
+        let mut sorted = code.clone();
+        sorted.sort();
+        for pair in sorted.windows(2) {
+            assert!(Bytecode::<LibId>::opcode_byte(&pair[0]) <= Bytecode::<LibId>::opcode_byte(&pair[1]));
+        }
+        let unique = code.iter().copied().collect::<BTreeSet<_>>();
+        sorted.dedup();
+        assert_eq!(unique.len(), sorted.len());
+        assert!(unique.iter().zip(&sorted).all(|(a, b)| a == b));
+    }
+
+    #[test]
+    fn const_val_str_roundtrip() {
+        for val in [ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max, ConstVal::ValFeMAX] {
+            assert_eq!(ConstVal::from_str(&val.to_string()), Ok(val));
+        }
+        assert_eq!(ConstVal::from_str("0"), Err(ParseConstValError(s!("0"))));
+        assert_eq!(ConstVal::from_str("ffff_ffff#h"), Err(ParseConstValError(s!("ffff_ffff#h"))));
+    }
+
+    #[test]
+    fn const_val_try_from() {
+        for val in [ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max, ConstVal::ValFeMAX] {
+            assert_eq!(ConstVal::try_from(val as u8), Ok(val));
+            assert_eq!(ConstVal::from(val.to_u2()), val);
+        }
+        assert_eq!(ConstVal::try_from(4), Err(ConstValOutOfRange(4)));
+    }
+
+    #[test]
+    fn bits_try_from() {
+        for no in 0..8u8 {
+            let bits = Bits::try_from(no).unwrap();
+            assert_eq!(bits as u8, no);
+            assert_eq!(Bits::from(bits.to_u3()), bits);
+            assert_eq!(Bits::try_from(bits.bit_len()), Ok(bits));
+            assert_eq!(Bits::from_bit_len(bits.bit_len()), Some(bits));
+        }
+        assert_eq!(Bits::try_from(8u8), Err(BitsOutOfRange(8)));
+        assert_eq!(Bits::try_from(7usize), Err(UnsupportedBitLen(7)));
+        assert_eq!(Bits::try_from(256usize), Err(UnsupportedBitLen(256)));
+        assert_eq!(Bits::from_bit_len(7), None);
+        assert_eq!(Bits::from_bit_len(256), None);
+    }
+
+    #[test]
+    fn bits_str_roundtrip() {
+        for bits in [
+            Bits::Bits8,
+            Bits::Bits16,
+            Bits::Bits24,
+            Bits::Bits32,
+            Bits::Bits48,
+            Bits::Bits64,
+            Bits::Bits96,
+            Bits::Bits128,
+        ] {
+            assert_eq!(Bits::from_str(&bits.to_string()), Ok(bits));
+        }
+        assert_eq!(Bits::from_str("48.bits"), Ok(Bits::Bits48));
+        assert_eq!(Bits::from_str("48"), Err(ParseBitsError(s!("48"))));
+        assert_eq!(Bits::from_str("256.bits"), Err(ParseBitsError(s!("256.bits"))));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        use aluvm::LibId;
+        use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Readable, Token};
+
+        let instr = FieldInstr::Add {
+            dst_src: RegE::E1,
+            src: RegE::E2,
+        };
+        assert_tokens(&instr.readable(), &[Token::Str("add     E1, E2")]);
+        assert_tokens(&Instr::<LibId>::from(instr).readable(), &[Token::Str("add     E1, E2")]);
+        assert_tokens(&Instr::<LibId>::from(CtrlInstr::ChkCo).readable(), &[Token::Str("chk     CO")]);
+        assert_de_tokens_error::<Readable<FieldInstr>>(
+            &[Token::Str("chk CO")],
+            "`chk     CO` is not a field arithmetic instruction",
+        );
+        assert_de_tokens_error::<Readable<FieldInstr>>(
+            &[Token::Str("add E1")],
+            "invalid value: string \"add E1\", expected instruction `add` can't take 1 operand(s).",
+        );
+        assert_eq!(bincode::serialize(&instr).unwrap(), bincode::serialize(&Instr::<LibId>::from(instr)).unwrap());
+
+        // Trailing bytes in the code segment are rejected
+        let mut data = bincode::serialize(&instr).unwrap();
+        data[0] += 1;
+        data.insert(8 + 2, 0);
+        let err = bincode::deserialize::<FieldInstr>(&data).unwrap_err();
+        assert_eq!(err.to_string(), "the instruction bytecode has trailing bytes");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        use aluvm::LibId;
+
+        use crate::testing::{random_code, TestRng};
+
+        let mut rng = TestRng::with(4479);
+        for instr in random_code::<Instr<LibId>>(&mut rng, 1000) {
+            let data = bincode::serialize(&instr).unwrap();
+            assert_eq!(bincode::deserialize::<Instr<LibId>>(&data).unwrap(), instr);
+            #[cfg(feature = "testing")]
+            {
+                let json = serde_json::to_string(&instr).unwrap();
+                assert_eq!(serde_json::from_str::<Instr<LibId>>(&json).unwrap(), instr);
+            }
+            if let Instr::Gfa(instr) = instr {
+                let data = bincode::serialize(&instr).unwrap();
+                assert_eq!(bincode::deserialize::<FieldInstr>(&data).unwrap(), instr);
+                #[cfg(feature = "testing")]
+                {
+                    let json = serde_json::to_string(&instr).unwrap();
+                    assert_eq!(serde_json::from_str::<FieldInstr>(&json).unwrap(), instr);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Versioning of the GFA256 ISA extension and negotiation of the instruction groups between
+//! libraries and the VM.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::{Bytecode, BytecodeRead, IsaId};
+use aluvm::{Lib, LibId, Marshaller, SiteId};
+use amplify::confinement::TinyOrdSet;
+
+use super::{FieldInstr, Instr, ISA_GFA256, ISA_GFA256_V2};
+use crate::LIB_NAME_FINITE_FIELD;
+
+/// Group of instructions, which can be enabled or disabled in the VM as a whole.
+///
+/// Each group is introduced in some version of the GFA256 ISA extension (see
+/// [`IsaGroup::version`]); libraries using groups introduced after the first version must declare
+/// [`ISA_GFA256_V2`] ISA extension.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display(lowercase)]
+#[repr(u8)]
+pub enum IsaGroup {
+    /// Control flow instructions and instructions operating on `CO` and `CK` registers.
+    Control = 0,
+    /// Instructions putting, moving, checking values in `E` registers and basic field arithmetic.
+    Field = 1,
+    /// Host syscall instructions, dispatched through [`super::GfaContext::ecall`].
+    Host = 2,
+    /// Field arithmetic beyond the basic operations: multiplicative inverse, exponentiation,
+    /// squaring and square roots.
+    Algebra = 3,
+    /// Data-dependent selection of register values by the `CO` register, replacing branches.
+    Select = 4,
+    /// Comparison of the values by their canonical integer representatives.
+    Compare = 5,
+}
+
+impl IsaGroup {
+    /// All instruction groups, ordered by their bit in [`IsaGroups`].
+    pub const ALL: [Self; 6] =
+        [Self::Control, Self::Field, Self::Host, Self::Algebra, Self::Select, Self::Compare];
+
+    /// Returns version of the GFA256 ISA extension which has introduced the group.
+    pub const fn version(self) -> u8 {
+        match self {
+            IsaGroup::Control | IsaGroup::Field => 1,
+            IsaGroup::Host | IsaGroup::Algebra | IsaGroup::Select | IsaGroup::Compare => 2,
+        }
+    }
+
+    /// Returns the name of the ISA extension which must be declared by libraries using the group.
+    pub const fn isa_ext(self) -> &'static str {
+        match self.version() {
+            1 => ISA_GFA256,
+            _ => ISA_GFA256_V2,
+        }
+    }
+
+    const fn bit(self) -> u16 { 1 << self as u8 }
+}
+
+/// Set of instruction groups.
+///
+/// Used to report the groups required by a library (see [`required_groups`]), and to specify the
+/// groups supported by the VM in [`GfaConfig::isa_groups`].
+///
+/// [`GfaConfig::isa_groups`]: crate::GfaConfig::isa_groups
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct IsaGroups(u16);
+
+impl Default for IsaGroups {
+    fn default() -> Self { Self::ALL }
+}
+
+impl IsaGroups {
+    /// Empty set of groups.
+    pub const NONE: Self = Self(0);
+
+    /// All groups known to this version of zk-AluVM.
+    pub const ALL: Self = Self::with_version(u8::MAX);
+
+    /// Constructs set of groups introduced up to (and including) the given version of the GFA256
+    /// ISA extension.
+    pub const fn with_version(version: u8) -> Self {
+        let mut bits = 0u16;
+        let mut i = 0;
+        while i < IsaGroup::ALL.len() {
+            let group = IsaGroup::ALL[i];
+            if group.version() <= version {
+                bits |= group.bit();
+            }
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    /// Returns a copy of the set with the group added.
+    pub const fn with(self, group: IsaGroup) -> Self { Self(self.0 | group.bit()) }
+
+    /// Returns a copy of the set with the group removed.
+    pub const fn without(self, group: IsaGroup) -> Self { Self(self.0 & !group.bit()) }
+
+    /// Adds a group to the set.
+    pub fn insert(&mut self, group: IsaGroup) { *self = self.with(group); }
+
+    /// Checks whether the set contains the group.
+    pub const fn contains(self, group: IsaGroup) -> bool { self.0 & group.bit() != 0 }
+
+    /// Checks whether the set is empty.
+    pub const fn is_empty(self) -> bool { self.0 == 0 }
+
+    /// Returns groups from this set which are not present in the `other` set.
+    pub const fn difference(self, other: Self) -> Self { Self(self.0 & !other.0) }
+
+    /// Checks whether all groups of this set are present in the `other` set.
+    pub const fn is_subset(self, other: Self) -> bool { self.difference(other).is_empty() }
+
+    /// Iterates over the groups in the set.
+    pub fn iter(self) -> impl Iterator<Item = IsaGroup> {
+        IsaGroup::ALL
+            .into_iter()
+            .filter(move |group| self.contains(*group))
+    }
+
+    /// Returns the highest version of the GFA256 ISA extension among the groups in the set, or
+    /// zero for an empty set.
+    pub fn version(self) -> u8 { self.iter().map(IsaGroup::version).max().unwrap_or_default() }
+
+    /// Returns the set of ISA extensions which must be declared by a library using the groups.
+    ///
+    /// [`ISA_GFA256`] is always present, so libraries using only the groups from the first version
+    /// of the extension are compatible with the VMs not aware of the versioning.
+    pub fn isa_ext(self) -> TinyOrdSet<IsaId> {
+        let mut isae = bset![IsaId::from(ISA_GFA256)];
+        isae.extend(self.iter().map(|group| IsaId::from(group.isa_ext())));
+        TinyOrdSet::from_checked(isae)
+    }
+}
+
+impl FromIterator<IsaGroup> for IsaGroups {
+    fn from_iter<T: IntoIterator<Item = IsaGroup>>(iter: T) -> Self {
+        let mut groups = Self::NONE;
+        groups.extend(iter);
+        groups
+    }
+}
+
+impl Extend<IsaGroup> for IsaGroups {
+    fn extend<T: IntoIterator<Item = IsaGroup>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|group| self.insert(group));
+    }
+}
+
+impl Display for IsaGroups {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names = self
+            .iter()
+            .map(|group| group.to_string())
+            .collect::<Vec<_>>();
+        f.write_str(&names.join(", "))
+    }
+}
+
+impl<Id: SiteId> Instr<Id> {
+    /// Returns the instruction group the instruction belongs to, or `None` for reserved
+    /// instructions.
+    pub fn group(&self) -> Option<IsaGroup> {
+        match self {
+            Instr::Ctrl(_) => Some(IsaGroup::Control),
+            Instr::Gfa(FieldInstr::Ecall { .. }) => Some(IsaGroup::Host),
+            Instr::Gfa(
+                FieldInstr::Inv { .. }
+                | FieldInstr::Exp { .. }
+                | FieldInstr::ExpI { .. }
+                | FieldInstr::Sqr { .. }
+                | FieldInstr::Sqrt { .. },
+            ) => Some(IsaGroup::Algebra),
+            Instr::Gfa(FieldInstr::CMov { .. } | FieldInstr::Sel { .. }) => Some(IsaGroup::Select),
+            Instr::Gfa(FieldInstr::Lt { .. } | FieldInstr::Rng { .. }) => Some(IsaGroup::Compare),
+            Instr::Gfa(_) => Some(IsaGroup::Field),
+            Instr::Reserved(_) => None,
+        }
+    }
+}
+
+/// Reports the instruction groups used by the library code.
+///
+/// Decoding stops at truncated instructions, which are reported by [`verify`](super::verify).
+pub fn required_groups(lib: &Lib) -> IsaGroups {
+    // The data segment is padded, so references outside of it do not interrupt decoding.
+    let mut data = lib.data.to_vec();
+    data.resize(u16::MAX as usize + 32, 0);
+    let mut reader = Marshaller::with(lib.code.as_slice(), data.as_slice(), &lib.libs);
+    let mut groups = IsaGroups::NONE;
+    while !reader.is_eof() {
+        let Ok(instr) = Instr::<LibId>::decode_instr(&mut reader) else {
+            break;
+        };
+        groups.extend(instr.group());
+    }
+    groups
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::isa::ReservedInstr;
+    use aluvm::regs::Status;
+    use aluvm::{CoreConfig, CoreExt, LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::{Assembly, NO_CONTEXT};
+    use crate::{GfaConfig, RegE};
+
+    #[test]
+    fn groups() {
+        assert_eq!(IsaGroups::default(), IsaGroups::ALL);
+        assert_eq!(IsaGroups::with_version(2), IsaGroups::ALL);
+        assert_eq!(
+            IsaGroups::with_version(1),
+            IsaGroups::ALL
+                .without(IsaGroup::Host)
+                .without(IsaGroup::Algebra)
+                .without(IsaGroup::Select)
+                .without(IsaGroup::Compare)
+        );
+        assert_eq!(IsaGroups::with_version(0), IsaGroups::NONE);
+        assert_eq!(IsaGroups::ALL.version(), 2);
+        assert_eq!(IsaGroups::with_version(1).version(), 1);
+        assert_eq!(IsaGroups::NONE.version(), 0);
+
+        let groups = IsaGroups::ALL.without(IsaGroup::Field);
+        assert!(groups.contains(IsaGroup::Control));
+        assert!(!groups.contains(IsaGroup::Field));
+        assert!(groups.is_subset(IsaGroups::ALL));
+        assert!(!IsaGroups::ALL.is_subset(groups));
+        assert_eq!(IsaGroups::ALL.difference(groups), IsaGroups::NONE.with(IsaGroup::Field));
+        assert_eq!(IsaGroups::ALL.to_string(), "control, field, host, algebra, select, compare");
+        assert_eq!(IsaGroups::ALL.iter().collect::<IsaGroups>(), IsaGroups::ALL);
+        assert_eq!(IsaGroups::with_version(1).to_string(), "control, field");
+        assert_eq!(IsaGroups::NONE.to_string(), "");
+        assert_eq!(IsaGroups::with_version(1).isa_ext(), TinyOrdSet::from_checked(bset![IsaId::from(ISA_GFA256)]));
+        assert_eq!(
+            IsaGroups::ALL.isa_ext(),
+            TinyOrdSet::from_checked(bset![IsaId::from(ISA_GFA256), IsaId::from(ISA_GFA256_V2)])
+        );
+    }
+
+    #[test]
+    fn required() {
+        let lib = Assembly::parse("put E1, 7\nret")
+            .unwrap()
+            .assemble()
+            .unwrap();
+        assert_eq!(lib.isae, IsaGroups::with_version(1).isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::with_version(1));
+
+        let lib = Assembly::parse("ecall 1").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Host));
+
+        let lib = Assembly::parse("inv E1, E2").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Algebra));
+
+        let lib = Assembly::parse("cmov E1, E2").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Select));
+
+        let lib = Assembly::parse("sel E1, E2, E3").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Select));
+
+        let lib = Assembly::parse("lt E1, E2").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Compare));
+
+        let lib = Assembly::parse("rng E1, 1000000000").unwrap().assemble().unwrap();
+        assert_eq!(lib.isae, IsaGroups::ALL.isa_ext());
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Compare));
+
+        let lib = Assembly::parse("ret").unwrap().assemble().unwrap();
+        assert_eq!(required_groups(&lib), IsaGroups::NONE.with(IsaGroup::Control));
+
+        let lib = Lib::assemble(&[Instr::<LibId>::Reserved(ReservedInstr::default())]).unwrap();
+        assert_eq!(required_groups(&lib), IsaGroups::NONE);
+    }
+
+    #[test]
+    fn unsupported() {
+        let lib = Assembly::parse("put E1, 7\nret")
+            .unwrap()
+            .assemble()
+            .unwrap();
+        let lib_id = lib.lib_id();
+        let mut vm = Vm::<Instr<LibId>>::with(CoreConfig::default(), GfaConfig {
+            isa_groups: IsaGroups::ALL.without(IsaGroup::Field),
+            ..GfaConfig::default()
+        });
+        let res = vm.exec(LibSite::new(lib_id, 0), &NO_CONTEXT, |id| (id == lib_id).then_some(&lib));
+        assert_eq!(res, Status::Fail);
+        assert_eq!(vm.core.cx.get(RegE::E1), None);
+    }
+}
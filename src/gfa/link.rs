@@ -0,0 +1,320 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::{CodeEofError, CtrlInstr};
+use aluvm::{AssemblerError, Lib, LibId, LibSite, Site};
+
+use super::analysis::CodeOverflow;
+use super::Instr;
+#[cfg(feature = "cbor")]
+use crate::{BundleError, GfaConfig, LibBundle};
+
+/// Errors linking libraries.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LinkError {
+    /// code of the library {0} can't be decoded into zk-AluVM instructions.
+    Decode(LibId),
+
+    /// linked code exceeds maximal size of the code segment.
+    #[from(CodeOverflow)]
+    CodeOverflow,
+
+    /// unable to assemble the linked library: {0}
+    #[from]
+    Assemble(AssemblerError),
+}
+
+/// Library produced by linking several libraries together.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LinkedLib {
+    /// Library containing code of all the linked libraries.
+    pub lib: Lib,
+    /// Offsets of the code of each of the linked libraries inside the code segment of
+    /// [`Self::lib`].
+    pub offsets: BTreeMap<LibId, u16>,
+}
+
+impl LinkedLib {
+    /// Translates a site in one of the linked libraries into the site inside the linked library.
+    ///
+    /// Returns `None` if the site belongs to a library which was not linked.
+    pub fn site(&self, site: LibSite) -> Option<LibSite> {
+        let base = *self.offsets.get(&site.lib_id)?;
+        Some(LibSite::new(self.lib.lib_id(), base.checked_add(site.offset)?))
+    }
+
+    /// Packs the linked library into a bundle together with the external libraries it refers to.
+    ///
+    /// The bundle is [`validate`](LibBundle::validate)d, such that all the references of the
+    /// linked library to other libraries must be resolved by the provided `externals`, which in
+    /// turn may depend on each other. Entry points can be added to the returned bundle using sites
+    /// translated with [`Self::site`].
+    #[cfg(feature = "cbor")]
+    pub fn bundle(
+        &self,
+        config: GfaConfig,
+        externals: impl IntoIterator<Item = Lib>,
+    ) -> Result<LibBundle, BundleError> {
+        let mut bundle = LibBundle::new(config);
+        for lib in externals {
+            bundle.add_lib(lib);
+        }
+        bundle.add_lib(self.lib.clone());
+        bundle.validate()?;
+        Ok(bundle)
+    }
+}
+
+/// Links several libraries into a single one.
+///
+/// The code of the libraries is concatenated in the order they are provided (duplicated libraries
+/// are linked once). Calls and jumps into any of the linked libraries are resolved into local calls
+/// and jumps, padded with `nop` to keep the code layout intact, while references to other libraries
+/// are kept as external ones. Constants from the data segments are deduplicated across all the
+/// libraries, such that each unique value is stored in the data segment of the linked library once.
+///
+/// The original library sites can be translated into sites within the linked library with
+/// [`LinkedLib::site`].
+///
+/// Linking produces a single library and not a bundle of libraries: each library is linked once,
+/// even if it is provided several times or is a dependency of several other linked libraries, and
+/// calls between the linked libraries become local. With the `cbor` feature, the linked library
+/// and the external libraries it depends on can be packed into a bundle with
+/// `LinkedLib::bundle`.
+pub fn link<'lib>(libs: impl IntoIterator<Item = &'lib Lib>) -> Result<LinkedLib, LinkError> {
+    let mut modules = Vec::new();
+    let mut offsets = BTreeMap::new();
+    let mut base = 0usize;
+    for lib in libs {
+        let id = lib.lib_id();
+        if offsets.contains_key(&id) {
+            continue;
+        }
+        let code = lib
+            .disassemble::<Instr<LibId>>()
+            .map_err(|_: CodeEofError| LinkError::Decode(id))?;
+        let offset = u16::try_from(base).map_err(|_| CodeOverflow)?;
+        offsets.insert(id, offset);
+        base += lib.code.len();
+        modules.push((code, offset));
+    }
+
+    let resolve = |site: Site<LibId>| -> Result<Option<u16>, CodeOverflow> {
+        let Some(base) = offsets.get(&site.prog_id) else {
+            return Ok(None);
+        };
+        base.checked_add(site.offset).map(Some).ok_or(CodeOverflow)
+    };
+
+    let mut linked = Vec::with_capacity(modules.iter().map(|(code, _)| code.len()).sum());
+    for (code, base) in modules {
+        let shift = |pos: u16| pos.checked_add(base).ok_or(CodeOverflow);
+        for instr in code {
+            let instr = match instr {
+                Instr::Ctrl(CtrlInstr::Jmp { pos }) => CtrlInstr::Jmp { pos: shift(pos)? }.into(),
+                Instr::Ctrl(CtrlInstr::JiOvfl { pos }) => CtrlInstr::JiOvfl { pos: shift(pos)? }.into(),
+                Instr::Ctrl(CtrlInstr::JiFail { pos }) => CtrlInstr::JiFail { pos: shift(pos)? }.into(),
+                Instr::Ctrl(CtrlInstr::Fn { pos }) => CtrlInstr::Fn { pos: shift(pos)? }.into(),
+                Instr::Ctrl(CtrlInstr::Exec { site }) => match resolve(site)? {
+                    Some(pos) => {
+                        linked.push(CtrlInstr::Jmp { pos }.into());
+                        CtrlInstr::Nop.into()
+                    }
+                    None => instr,
+                },
+                Instr::Ctrl(CtrlInstr::Call { site }) => match resolve(site)? {
+                    Some(pos) => {
+                        linked.push(CtrlInstr::Fn { pos }.into());
+                        CtrlInstr::Nop.into()
+                    }
+                    None => instr,
+                },
+                _ => instr,
+            };
+            linked.push(instr);
+        }
+    }
+
+    let lib = Lib::assemble(&linked)?;
+    Ok(LinkedLib { lib, offsets })
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{CoreConfig, CoreExt, Vm};
+    use amplify::num::u256;
+
+    use super::*;
+    use crate::gfa::{FieldInstr, NO_CONTEXT};
+    use crate::{fe256, GfaConfig, RegE, FIELD_ORDER_SECP};
+
+    const CONST: u64 = 0xDEAD_BEEF;
+
+    fn put(dst: RegE) -> Instr<LibId> {
+        FieldInstr::PutD {
+            dst,
+            data: fe256::from(u256::from(CONST)),
+        }
+        .into()
+    }
+
+    fn run(lib: &Lib, offset: u16) -> Vm<Instr<LibId>> {
+        let config = CoreConfig {
+            halt: true,
+            complexity_lim: None,
+        };
+        let mut vm = Vm::<Instr<LibId>>::with(config, GfaConfig::new(FIELD_ORDER_SECP));
+        let lib_id = lib.lib_id();
+        let res = vm.exec(LibSite::new(lib_id, offset), &NO_CONTEXT, |id| (id == lib_id).then_some(lib));
+        assert!(res.is_ok());
+        vm
+    }
+
+    #[test]
+    fn link_libs() {
+        let callee = Lib::assemble(&[
+            CtrlInstr::Nop.into(),
+            put(RegE::E2),
+            FieldInstr::Add {
+                dst_src: RegE::E2,
+                src: RegE::E1,
+            }
+            .into(),
+            CtrlInstr::Ret.into(),
+        ])
+        .unwrap();
+        let callee_id = callee.lib_id();
+        let caller = Lib::assemble(&[
+            put(RegE::E1),
+            CtrlInstr::Call {
+                site: Site::new(callee_id, 1),
+            }
+            .into(),
+            CtrlInstr::Exec {
+                site: Site::new(callee_id, 0),
+            }
+            .into(),
+        ])
+        .unwrap();
+        assert_eq!(caller.libs.len(), 1);
+
+        let linked = link([&caller, &callee, &caller]).unwrap();
+        assert_eq!(linked.offsets.len(), 2);
+        assert_eq!(linked.offsets[&caller.lib_id()], 0);
+        assert_eq!(linked.offsets[&callee_id], caller.code.len() as u16);
+        assert_eq!(linked.lib.code.len(), caller.code.len() + callee.code.len());
+        assert_eq!(linked.lib.data.len(), 32);
+        assert!(linked.lib.libs.is_empty());
+        assert_eq!(
+            linked.site(LibSite::new(callee_id, 1)),
+            Some(LibSite::new(linked.lib.lib_id(), caller.code.len() as u16 + 1))
+        );
+        assert_eq!(linked.site(LibSite::new(linked.lib.lib_id(), 0)), None);
+
+        let vm = run(&linked.lib, 0);
+        let expected = fe256::from(u256::from(CONST * 2));
+        assert_eq!(vm.core.cx.get(RegE::E2), Some(expected));
+    }
+
+    #[test]
+    fn shared_dependency() {
+        let common = Lib::assemble(&[put(RegE::E1), CtrlInstr::Ret.into()]).unwrap();
+        let call = |lib: &Lib| -> Instr<LibId> {
+            CtrlInstr::Call {
+                site: Site::new(lib.lib_id(), 0),
+            }
+            .into()
+        };
+        let first = Lib::assemble(&[call(&common), CtrlInstr::Ret.into()]).unwrap();
+        let second = Lib::assemble(&[call(&common), call(&first), CtrlInstr::Ret.into()]).unwrap();
+        assert_eq!(second.libs.len(), 2);
+
+        let linked = link([&second, &first, &common, &common]).unwrap();
+        assert_eq!(linked.offsets.len(), 3);
+        assert_eq!(linked.lib.code.len(), second.code.len() + first.code.len() + common.code.len());
+        assert_eq!(linked.lib.data.len(), 32);
+        assert!(linked.lib.libs.is_empty());
+
+        let vm = run(&linked.lib, 0);
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(u256::from(CONST))));
+    }
+
+    #[test]
+    fn external() {
+        let external = Lib::assemble::<Instr<LibId>>(&[CtrlInstr::Ret.into()]).unwrap();
+        let lib = Lib::assemble::<Instr<LibId>>(&[CtrlInstr::Call {
+            site: Site::new(external.lib_id(), 0),
+        }
+        .into()])
+        .unwrap();
+        let linked = link([&lib]).unwrap();
+        assert_eq!(linked.lib.code, lib.code);
+        assert_eq!(linked.lib.libs, lib.libs);
+    }
+
+    #[test]
+    #[cfg(feature = "cbor")]
+    fn bundle() {
+        let external = Lib::assemble(&[put(RegE::E1), CtrlInstr::Ret.into()]).unwrap();
+        let callee = Lib::assemble::<Instr<LibId>>(&[
+            CtrlInstr::Call {
+                site: Site::new(external.lib_id(), 0),
+            }
+            .into(),
+            CtrlInstr::Ret.into(),
+        ])
+        .unwrap();
+        let caller = Lib::assemble::<Instr<LibId>>(&[
+            CtrlInstr::Call {
+                site: Site::new(callee.lib_id(), 0),
+            }
+            .into(),
+            CtrlInstr::Stop.into(),
+        ])
+        .unwrap();
+        let linked = link([&caller, &callee]).unwrap();
+        let config = GfaConfig::new(FIELD_ORDER_SECP);
+
+        let err = linked.bundle(config, []).unwrap_err();
+        assert!(matches!(err, BundleError::MissingDependency(id, dep)
+            if id == linked.lib.lib_id() && dep == external.lib_id()));
+
+        let mut bundle = linked.bundle(config, [external.clone()]).unwrap();
+        assert_eq!(bundle.libs.len(), 2);
+        assert_eq!(bundle.resolve(linked.lib.lib_id()).unwrap(), vec![external.lib_id(), linked.lib.lib_id()]);
+        let entry = linked.site(LibSite::new(caller.lib_id(), 0)).unwrap();
+        bundle.entry_points.insert(s!("main"), entry);
+        let bundle = LibBundle::from_cbor(&bundle.to_cbor()).unwrap();
+
+        let cache = bundle.cache();
+        let mut vm = Vm::<Instr<LibId>>::with(CoreConfig::default(), bundle.config);
+        assert!(vm.exec(bundle.entry("main").unwrap(), &NO_CONTEXT, cache.resolver()).is_ok());
+        assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(u256::from(CONST))));
+    }
+}
@@ -0,0 +1,205 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Lints detecting suspicious patterns in zk-AluVM programs.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use aluvm::isa::{CtrlInstr, Instruction};
+use aluvm::{CoreConfig, SiteId};
+use amplify::num::u256;
+
+use super::analysis::{ControlFlow, Initialization, Successor};
+use super::{ConstVal, FieldInstr, Instr};
+
+/// Instruction which may fail `CK` without the failure ever being checked by the program.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display("instruction #{no} at offset {pos:04X} may fail CK, which is never checked afterwards")]
+pub struct UncheckedFailure {
+    /// Index of the instruction in the program.
+    pub no: usize,
+    /// Offset of the instruction in the code segment.
+    pub pos: u16,
+}
+
+/// Detects instructions which may fail `CK` while the program never checks the failure.
+///
+/// When a VM is configured not to halt on failures, it continues execution after an instruction
+/// sets `CK` to a failed state, and the failure is noticed by the program only if it later checks
+/// `CK` with `chk CK` or `jif CK`, or moves it into `CO` with `mov CO, CK`. This lint reports
+/// instructions which may fail `CK` (`chk CO`, `ecall`, `inv`, `put` of a value which may not be
+/// an element of the field, and `fits`, `add`, `mul`, `neg`, `exp`, `sqr` and `sqrt` reading
+/// registers which may be uninitialized) if none of these instructions is reachable from them
+/// before the routine completes.
+///
+/// Calls of other routines are assumed to check `CK`. With a halting configuration any failure
+/// stops the program, so nothing is reported.
+pub fn unchecked_failures<Id: SiteId>(cfg: &ControlFlow<Id>, config: &CoreConfig) -> Vec<UncheckedFailure> {
+    if config.halt {
+        return vec![];
+    }
+
+    let code = cfg.code();
+    let init = Initialization::analyze(cfg);
+
+    // Whether `CK` failure existing before the instruction may be checked afterwards.
+    let mut checked = vec![false; code.len()];
+    let after = |checked: &[bool], no: usize| {
+        cfg.successors(no).iter().any(|s| match s {
+            Successor::Instr(next) => checked[*next],
+            Successor::Exit => false,
+            Successor::Unknown => true,
+        })
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for no in (0..code.len()).rev() {
+            let val = match code[no] {
+                Instr::Ctrl(
+                    CtrlInstr::ChkCk
+                    | CtrlInstr::JiFail { .. }
+                    | CtrlInstr::ShFail { .. }
+                    | CtrlInstr::RsetCk
+                    | CtrlInstr::Fn { .. }
+                    | CtrlInstr::Call { .. }
+                    | CtrlInstr::Exec { .. },
+                ) => true,
+                _ => after(&checked, no),
+            };
+            if val != checked[no] {
+                checked[no] = val;
+                changed = true;
+            }
+        }
+    }
+
+    code.iter()
+        .enumerate()
+        .filter(|(no, instr)| {
+            let may_fail = match instr {
+                Instr::Ctrl(CtrlInstr::ChkCo)
+                | Instr::Gfa(FieldInstr::Ecall { .. } | FieldInstr::Inv { .. }) => true,
+                // Zero, unity and the maximal element belong to any field, while other values may
+                // exceed the order of a small field
+                Instr::Gfa(FieldInstr::PutD { dst: _, data }) => data.to_u256() > u256::ONE,
+                Instr::Gfa(FieldInstr::PutV { dst: _, val }) => !matches!(val, ConstVal::Val1 | ConstVal::ValFeMAX),
+                Instr::Gfa(
+                    gfa @ (FieldInstr::Fits { .. }
+                    | FieldInstr::Lt { .. }
+                    | FieldInstr::Rng { .. }
+                    | FieldInstr::Add { .. }
+                    | FieldInstr::Mul { .. }
+                    | FieldInstr::Neg { .. }
+                    | FieldInstr::Exp { .. }
+                    | FieldInstr::ExpI { .. }
+                    | FieldInstr::Sqr { .. }
+                    | FieldInstr::Sqrt { .. }),
+                ) => Instruction::<Id>::src_regs(gfa)
+                    .into_iter()
+                    .any(|reg| !init.is_init_before(*no, reg)),
+                _ => false,
+            };
+            may_fail && !after(&checked, *no)
+        })
+        .map(|(no, _)| UncheckedFailure {
+            no,
+            pos: cfg.offset(no),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use aluvm::LibId;
+
+    use super::*;
+
+    fn lint(code: &[Instr<LibId>]) -> Vec<usize> {
+        let cfg = ControlFlow::analyze(code).unwrap();
+        let config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        unchecked_failures(&cfg, &config)
+            .into_iter()
+            .map(|failure| failure.no)
+            .collect()
+    }
+
+    #[test]
+    fn unchecked() {
+        let code = zk_aluasm! {
+            put     E1, 1;          // 0
+            add     E1, E1;         // 1: can't fail
+            mul     E3, E1;         // 2: checked with `chk CK` below
+            chk     CK;             // 3
+            neg     E4, E5;         // 4: CK is moved to CO
+            mov     CO, CK;         // 5
+            add     E2, E1;         // 6: E2 is uninitialized
+            chk     CO;             // 7: never checked
+            ret;                    // 8
+        };
+        assert_eq!(lint(&code), vec![6, 7]);
+
+        let cfg = ControlFlow::analyze(&code).unwrap();
+        assert!(unchecked_failures(&cfg, &CoreConfig::default()).is_empty());
+        assert_eq!(
+            unchecked_failures(&cfg, &CoreConfig {
+                halt: false,
+                complexity_lim: None
+            })[0]
+                .to_string(),
+            "instruction #6 at offset 000C may fail CK, which is never checked afterwards"
+        );
+    }
+
+    #[test]
+    fn puts() {
+        let code = zk_aluasm! {
+            put     E1, 1;          // 0: unity is an element of any field
+            put     E2, 5;          // 1: may be above the field order
+            put     E3, 0;          // 2
+            ret;                    // 3
+        };
+        assert_eq!(lint(&code), vec![1]);
+    }
+
+    #[test]
+    fn branches() {
+        let code = zk_aluasm! {
+            fits    E1, 8.bits;     // 0 @0
+            jif     CO, 9;          // 1 @2
+            jif     CK, 9;          // 2 @5
+            ret;                    // 3 @8
+            fits    E2, 8.bits;     // 4 @9
+            call    0;              // 5 @11
+            ret;                    // 6 @14
+        };
+        assert_eq!(lint(&code), Vec::<usize>::new());
+    }
+}
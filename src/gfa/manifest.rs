@@ -0,0 +1,128 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use aluvm::isa::Bytecode;
+use aluvm::{IsaId, Lib, LibId, LibsSeg};
+use amplify::confinement::TinyOrdSet;
+
+use super::{estimate_complexity, ComplexityError, Instr};
+use crate::RegE;
+
+/// Description of a library for registries of deployable programs.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Manifest {
+    /// Library identifier.
+    pub lib_id: LibId,
+    /// ISA extensions used by the library.
+    pub isae: TinyOrdSet<IsaId>,
+    /// Libraries called by the library.
+    pub libs: LibsSeg,
+    /// Size of the code segment, in bytes.
+    pub code_size: u16,
+    /// Size of the data segment, in bytes.
+    pub data_size: u16,
+    /// Number of instructions in the library code with each of the opcodes.
+    pub opcodes: BTreeMap<u8, u16>,
+    /// Worst-case complexity of the execution starting from the beginning of the code, if bounded
+    /// (see [`super::ComplexityReport::worst_case`]).
+    pub complexity: Option<u64>,
+    /// Registers which must be set by the caller before running the program.
+    pub inputs: BTreeSet<RegE>,
+    /// Registers holding the program results once it completes.
+    pub outputs: BTreeSet<RegE>,
+}
+
+impl Manifest {
+    /// Produces manifest for a library, with the provided input and output register conventions.
+    pub fn with(
+        lib: &Lib,
+        inputs: impl IntoIterator<Item = RegE>,
+        outputs: impl IntoIterator<Item = RegE>,
+    ) -> Result<Self, ComplexityError> {
+        let report = estimate_complexity(lib)?;
+        let mut opcodes = BTreeMap::<u8, u16>::new();
+        for instr in lib.disassemble::<Instr<LibId>>()? {
+            *opcodes
+                .entry(Bytecode::<LibId>::opcode_byte(&instr))
+                .or_default() += 1;
+        }
+        Ok(Manifest {
+            lib_id: lib.lib_id(),
+            isae: lib.isae.clone(),
+            libs: lib.libs.clone(),
+            code_size: lib.code.len() as u16,
+            data_size: lib.data.len() as u16,
+            opcodes,
+            complexity: report.worst_case,
+            inputs: inputs.into_iter().collect(),
+            outputs: outputs.into_iter().collect(),
+        })
+    }
+
+    /// Returns the number of instructions in the library code.
+    pub fn instr_count(&self) -> usize { self.opcodes.values().map(|count| *count as usize).sum() }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::isa::Instruction;
+
+    use super::*;
+
+    #[test]
+    fn manifest() {
+        let code = zk_aluasm! {
+            put     E2, 1;
+            add     E1, E2;
+            add     E1, E2;
+            mov     EA, E1;
+            ret;
+        };
+        let lib = Lib::assemble(&code).unwrap();
+        let manifest = Manifest::with(&lib, [RegE::E1], [RegE::EA]).unwrap();
+        assert_eq!(manifest.lib_id, lib.lib_id());
+        assert_eq!(manifest.isae, lib.isae);
+        assert!(manifest.libs.is_empty());
+        assert_eq!(manifest.code_size, lib.code.len() as u16);
+        assert_eq!(manifest.data_size, 32);
+        assert_eq!(manifest.instr_count(), 5);
+        assert_eq!(manifest.opcodes.len(), 4);
+        assert_eq!(manifest.opcodes[&Bytecode::<LibId>::opcode_byte(&code[1])], 2);
+        assert_eq!(manifest.complexity, Some(code.iter().map(Instruction::complexity).sum()));
+        assert_eq!(manifest.inputs, bset! { RegE::E1 });
+        assert_eq!(manifest.outputs, bset! { RegE::EA });
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let lib = Lib::assemble(&zk_aluasm! { put E1, 0; ret; }).unwrap();
+        let manifest = Manifest::with(&lib, [], [RegE::E1]).unwrap();
+        let data = bincode::serialize(&manifest).unwrap();
+        assert_eq!(bincode::deserialize::<Manifest>(&data).unwrap(), manifest);
+    }
+}
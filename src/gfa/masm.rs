@@ -20,6 +20,11 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
+use aluvm::isa::{Bytecode, Instruction};
+use aluvm::SiteId;
+
+use super::Instr;
+
 /// Macro compiler for AluVM assembler.
 ///
 /// # Example
@@ -28,7 +33,7 @@
 /// ##![cfg_attr(coverage_nightly, feature(coverage_attribute), coverage(off))]
 /// use aluvm::regs::Status;
 /// use aluvm::{Lib, LibId, LibSite, Vm};
-/// use zkaluvm::gfa::Instr;
+/// use zkaluvm::gfa::{Instr, NO_CONTEXT};
 /// use zkaluvm::zk_aluasm;
 ///
 /// let code = zk_aluasm! {
@@ -48,48 +53,254 @@
 ///
 /// let lib = Lib::assemble::<Instr<LibId>>(&code).unwrap();
 /// let mut vm = Vm::<Instr<LibId>>::new();
-/// match vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib)) {
+/// match vm.exec(LibSite::new(lib.lib_id(), 0), &NO_CONTEXT, |_| Some(&lib)) {
 ///     Status::Ok => println!("success"),
 ///     Status::Fail => println!("failure"),
 /// }
 /// ```
+///
+/// # Size and complexity assertions
+///
+/// Since operands may be runtime expressions, the program is assembled when the code produced by
+/// the macro runs, and its size and complexity are not known to the compiler. Thus, the macro
+/// doesn't support `assert_size!` and `assert_complexity!` directives, rejecting them with a
+/// compile error. Programs which must fail the build once their code segment size (in bytes) or
+/// the total complexity of their instructions exceeds a limit should be written with the
+/// `zk_aluasm_str!` macro from the `zk-aluvm-macros` crate, which assembles the program source
+/// during the compilation and reports violations of its `.assert_size` and `.assert_complexity`
+/// directives as compile errors.
+///
+/// ```compile_fail
+/// # use zkaluvm::zk_aluasm;
+/// let code = zk_aluasm! {
+///     assert_size!(< 1024);
+///     put     E1, 0;
+///     add     E1, E1;
+/// };
+/// ```
+///
+/// # Diagnostics
+///
+/// Programs are validated at compile time: unknown mnemonics, wrong number or form of the
+/// operands and invalid register names produce a compile error naming the offending token and,
+/// where applicable, the expected instruction syntax, like
+/// ``invalid operands `E1` of `add` instruction; expected `add <dst_src>, <src>` ``.
+///
+/// ```compile_fail
+/// # use zkaluvm::zk_aluasm;
+/// let code = zk_aluasm! {
+///     add     E1;
+/// };
+/// ```
+///
+/// ```compile_fail
+/// # use zkaluvm::zk_aluasm;
+/// let code = zk_aluasm! {
+///     mov     E1, E9;
+/// };
+/// ```
+///
+/// # Constant expressions
+///
+/// The value of `put` instruction may be given by an expression over integer literals, evaluated
+/// with unsigned 256-bit integer semantics (see [`crate::gfa::eval_const`]). Since the macro
+/// doesn't know the field the program is executed in, the field order constant `P` is not available
+/// here; use the textual assembler configured for a specific field instead.
+///
+/// ```
+/// # use zkaluvm::zk_aluasm;
+/// # use zkaluvm::gfa::{ConstVal, FieldInstr, Instr};
+/// # use zkaluvm::{fe256, RegE};
+/// let code = zk_aluasm! {
+///     put     E1, (1 << 64) - 1;
+///     put     E2, 0x100 * 3 + 1;
+/// };
+/// assert_eq!(
+///     code[0],
+///     Instr::Gfa(FieldInstr::PutV {
+///         dst: RegE::E1,
+///         val: ConstVal::ValU64Max
+///     })
+/// );
+/// assert_eq!(
+///     code[1],
+///     Instr::Gfa(FieldInstr::PutD {
+///         dst: RegE::E2,
+///         data: fe256::from(769u128)
+///     })
+/// );
+/// ```
+///
+/// # Conditional assembly
+///
+/// A block of instructions may be preceded by an attribute, which is applied to the code
+/// assembling the block. This allows to select code paths with `cfg` attributes, for instance
+/// depending on the crate features enabled for a specific field configuration:
+///
+/// ```
+/// # use zkaluvm::zk_aluasm;
+/// let code = zk_aluasm! {
+///     put     E1, 0;
+///     #[cfg(feature = "secp")] {
+///         put     E2, 1;
+///         add     E1, E2;
+///     }
+///     ret;
+/// };
+/// assert_eq!(code.len(), 2);
+/// ```
 #[macro_export]
 macro_rules! zk_aluasm {
     ($( $tt:tt )+) => {{
-        use $crate::instr;
-        #[cfg(not(feature = "std"))]
-        use alloc::vec::Vec;
-
-        let mut code: Vec<$crate::gfa::Instr<$crate::alu::LibId>> = Default::default();
+        let mut code: $crate::__private::Vec<$crate::gfa::Instr<$crate::alu::LibId>> = Default::default();
         #[allow(unreachable_code)] {
-            $crate::alu::aluasm_inner! { code => $( $tt )+ }
+            $crate::zk_aluasm_inner! { code => $( $tt )+ }
         }
         code
     }};
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! zk_aluasm_inner {
+    // end of program
+    { $code:ident => } => { };
+    // assembly directives, which can't be checked by the compiler here
+    { $code:ident => assert_size ! $($tt:tt)* } => {
+        compile_error!(
+            "`assert_size!` can't be checked at compile time by `zk_aluasm!`; use `.assert_size` directive of \
+             `zk_aluasm_str!` macro from `zk-aluvm-macros` crate"
+        );
+    };
+    { $code:ident => assert_complexity ! $($tt:tt)* } => {
+        compile_error!(
+            "`assert_complexity!` can't be checked at compile time by `zk_aluasm!`; use `.assert_complexity` \
+             directive of `zk_aluasm_str!` macro from `zk-aluvm-macros` crate"
+        );
+    };
+    // conditionally assembled code
+    { $code:ident => # [ $attr:meta ] { $($inner:tt)* } $($tt:tt)* } => {
+        #[$attr] {
+            $crate::zk_aluasm_inner! { $code => $( $inner )* }
+        }
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // skipped annotations
+    { $code:ident => offset $_:literal : $($tt:tt)* } => {
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => site $lib:ident @ $_:literal : $($tt:tt)* } => {
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // macro instruction
+    { $code:ident => $masm:ident $label:ident : $($tt:tt)* } => {
+        $code.push($crate::instr!{ $masm $label : });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // no operands
+    { $code:ident => $op:ident ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // operands are all literals
+    { $code:ident => $op:ident $( $arg:literal ),+ ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $( $arg ),+ });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // operands are all idents
+    { $code:ident => $op:ident $( $arg:ident ),+ ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $( $arg ),+ });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // operand is a positive shift
+    { $code:ident => $op:ident + $pos:literal ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op + $pos });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => $op:ident $arg:ident, + $pos:literal ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $arg, + $pos });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // operand is a negative shift
+    { $code:ident => $op:ident - $pos:literal ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op - $pos });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => $op:ident $arg:ident, - $pos:literal ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $arg, - $pos });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // operands are indent followed by a literal
+    { $code:ident => $op:ident $arg:ident, $val:literal ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $arg, $val });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // suffixes
+    { $code:ident => $op:ident $val:literal . $ty:ident ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $val.$ty });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => $op:ident $reg:ident, $val:literal . $ty:ident ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $reg, $val.$ty });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // external constants and variables
+    { $code:ident => $op:ident & $val:ident ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op & $val });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => $op:ident $reg:ident, & $val:ident ; $($tt:tt)* } => {
+        $code.push($crate::instr!{ $op $reg, & $val });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // constant expressions
+    { $code:ident => put $dst:ident, $val:expr ; $($tt:tt)* } => {
+        let val = $crate::gfa::eval_const(stringify!($val), &Default::default())
+            .unwrap_or_else(|err| panic!("invalid `put` instruction: {err}"));
+        $code.push($crate::gfa::FieldInstr::put($crate::zk_aluasm_reg!($dst), $crate::fe256::from(val)).into());
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    { $code:ident => rng $src:ident, $bound:expr ; $($tt:tt)* } => {
+        let bound = $crate::gfa::eval_const(stringify!($bound), &Default::default())
+            .unwrap_or_else(|err| panic!("invalid `rng` instruction: {err}"));
+        $code.push($crate::instr!{ rng $src, bound });
+        $crate::zk_aluasm_inner! { $code => $( $tt )* }
+    };
+    // malformed statements
+    { $code:ident => $op:ident $($tt:tt)* } => {
+        compile_error!(concat!(
+            "invalid syntax of `", stringify!($op), "` instruction; operands must be separated by commas and ",
+            "the instruction must be terminated with `;`"
+        ))
+    };
+    { $code:ident => $tok:tt $($tt:tt)* } => {
+        compile_error!(concat!("expected instruction mnemonic, found `", stringify!($tok), "`"))
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! instr {
     // Test register
     (test $src:ident) => {
         $crate::gfa::FieldInstr::Test {
-            src: $crate::RegE::$src
+            src: $crate::zk_aluasm_reg!($src)
         }.into()
     };
 
     // Clear register
     (clr $dst:ident) => {
         $crate::gfa::FieldInstr::Clr {
-            dst: $crate::RegE::$dst
+            dst: $crate::zk_aluasm_reg!($dst)
         }.into()
     };
 
     // Checks whether a value in a register fits the provided number of bits
     (fits $src:ident, $bits:literal .bits) => {
         $crate::gfa::FieldInstr::Fits {
-            src: $crate::RegE::$src,
-            bits: $crate::gfa::Bits::from_bit_len($bits)
+            src: $crate::zk_aluasm_reg!($src),
+            bits: $crate::gfa::Bits::try_from($bits as usize)
+                .unwrap_or_else(|err| panic!("invalid `fits` instruction: {err}"))
         }.into()
     };
 
@@ -101,8 +312,8 @@ macro_rules! instr {
     // Moving value between regs
     (mov $dst:ident, $src:ident) => {
         $crate::gfa::FieldInstr::Mov {
-            dst: $crate::RegE::$dst,
-            src: $crate::RegE::$src
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
         }.into()
     };
 
@@ -110,11 +321,11 @@ macro_rules! instr {
     (put $dst:ident, $val:literal) => {
         if $val == 0 {
             $crate::gfa::FieldInstr::PutZ {
-                dst: $crate::RegE::$dst
+                dst: $crate::zk_aluasm_reg!($dst)
             }
         } else {
             $crate::gfa::FieldInstr::PutD {
-                dst: $crate::RegE::$dst,
+                dst: $crate::zk_aluasm_reg!($dst),
                 data: $crate::fe256::from($val as u128)
             }
         }.into()
@@ -122,7 +333,7 @@ macro_rules! instr {
 
     (put $dst:ident, $ident:ident) => {
         $crate::gfa::FieldInstr::PutD {
-            dst: $crate::RegE::$dst,
+            dst: $crate::zk_aluasm_reg!($dst),
             data: $crate::fe256::from($ident)
         }.into()
     };
@@ -130,33 +341,415 @@ macro_rules! instr {
     // Equivalence
     (eq $dst:ident, $src:ident) => {
         $crate::gfa::FieldInstr::Eq {
-            src1: $crate::RegE::$dst,
-            src2: $crate::RegE::$src
+            src1: $crate::zk_aluasm_reg!($dst),
+            src2: $crate::zk_aluasm_reg!($src)
+        }.into()
+    };
+    // Less-than comparison
+    (lt $src1:ident, $src2:ident) => {
+        $crate::gfa::FieldInstr::Lt {
+            src1: $crate::zk_aluasm_reg!($src1),
+            src2: $crate::zk_aluasm_reg!($src2)
+        }.into()
+    };
+    // Range check against a bound from the data segment
+    (rng $src:ident, $bound:literal) => {
+        $crate::gfa::FieldInstr::Rng {
+            src: $crate::zk_aluasm_reg!($src),
+            bound: $crate::fe256::from($bound as u128)
+        }.into()
+    };
+    (rng $src:ident, $bound:ident) => {
+        $crate::gfa::FieldInstr::Rng {
+            src: $crate::zk_aluasm_reg!($src),
+            bound: $crate::fe256::from($bound)
         }.into()
     };
     // Modulo-negate
     (neg $dst:ident, $src:ident) => {
         $crate::gfa::FieldInstr::Neg {
-            dst: $crate::RegE::$dst,
-            src: $crate::RegE::$src
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
         }.into()
     };
     // Modulo-add
     (add $dst_src:ident, $src:ident) => {
         $crate::gfa::FieldInstr::Add {
-            dst_src: $crate::RegE::$dst_src,
-            src: $crate::RegE::$src
+            dst_src: $crate::zk_aluasm_reg!($dst_src),
+            src: $crate::zk_aluasm_reg!($src)
         }.into()
     };
     // Modulo-multiply
     (mul $dst_src:ident, $src:ident) => {
         $crate::gfa::FieldInstr::Mul {
-            dst_src: $crate::RegE::$dst_src,
-            src: $crate::RegE::$src
+            dst_src: $crate::zk_aluasm_reg!($dst_src),
+            src: $crate::zk_aluasm_reg!($src)
         }.into()
     };
 
+    // Modulo-invert
+    (inv $dst:ident, $src:ident) => {
+        $crate::gfa::FieldInstr::Inv {
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
+        }.into()
+    };
+    // Modulo-exponentiate
+    (exp $dst_src:ident, $exp:literal) => {
+        $crate::gfa::FieldInstr::ExpI {
+            dst_src: $crate::zk_aluasm_reg!($dst_src),
+            exp: $exp
+        }.into()
+    };
+    (exp $dst_src:ident, $exp:ident) => {
+        $crate::gfa::FieldInstr::Exp {
+            dst_src: $crate::zk_aluasm_reg!($dst_src),
+            exp: $crate::zk_aluasm_reg!($exp)
+        }.into()
+    };
+    // Modulo-square
+    (sqr $dst:ident, $src:ident) => {
+        $crate::gfa::FieldInstr::Sqr {
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
+        }.into()
+    };
+    // Modulo-square root
+    (sqrt $dst:ident, $src:ident) => {
+        $crate::gfa::FieldInstr::Sqrt {
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
+        }.into()
+    };
+    // Moving value between regs if `CO` is set
+    (cmov $dst:ident, $src:ident) => {
+        $crate::gfa::FieldInstr::CMov {
+            dst: $crate::zk_aluasm_reg!($dst),
+            src: $crate::zk_aluasm_reg!($src)
+        }.into()
+    };
+    // Selecting value between two regs by `CO`
+    (sel $dst:ident, $src_true:ident, $src_false:ident) => {
+        $crate::gfa::FieldInstr::Sel {
+            dst: $crate::zk_aluasm_reg!($dst),
+            src_true: $crate::zk_aluasm_reg!($src_true),
+            src_false: $crate::zk_aluasm_reg!($src_false)
+        }.into()
+    };
+
+    // Host syscall
+    (ecall $id:literal) => {
+        $crate::gfa::FieldInstr::Ecall {
+            id: $id
+        }.into()
+    };
+
+    // Operands not matching any of the field arithmetic instruction forms
+    (test $($tt:tt)*) => { $crate::zk_aluasm_error!(test "test <src>" $($tt)*) };
+    (clr $($tt:tt)*) => { $crate::zk_aluasm_error!(clr "clr <dst>" $($tt)*) };
+    (fits $($tt:tt)*) => { $crate::zk_aluasm_error!(fits "fits <src>, <n>.bits" $($tt)*) };
+    (put $($tt:tt)*) => { $crate::zk_aluasm_error!(put "put <dst>, <value>" $($tt)*) };
+    (eq $($tt:tt)*) => { $crate::zk_aluasm_error!(eq "eq <src1>, <src2>" $($tt)*) };
+    (neg $($tt:tt)*) => { $crate::zk_aluasm_error!(neg "neg <dst>, <src>" $($tt)*) };
+    (add $($tt:tt)*) => { $crate::zk_aluasm_error!(add "add <dst_src>, <src>" $($tt)*) };
+    (mul $($tt:tt)*) => { $crate::zk_aluasm_error!(mul "mul <dst_src>, <src>" $($tt)*) };
+    (inv $($tt:tt)*) => { $crate::zk_aluasm_error!(inv "inv <dst>, <src>" $($tt)*) };
+    (exp $($tt:tt)*) => { $crate::zk_aluasm_error!(exp "exp <dst_src>, <exp>|<n>" $($tt)*) };
+    (sqr $($tt:tt)*) => { $crate::zk_aluasm_error!(sqr "sqr <dst>, <src>" $($tt)*) };
+    (sqrt $($tt:tt)*) => { $crate::zk_aluasm_error!(sqrt "sqrt <dst>, <src>" $($tt)*) };
+    (cmov $($tt:tt)*) => { $crate::zk_aluasm_error!(cmov "cmov <dst>, <src>" $($tt)*) };
+    (sel $($tt:tt)*) => { $crate::zk_aluasm_error!(sel "sel <dst>, <src_true>, <src_false>" $($tt)*) };
+    (lt $($tt:tt)*) => { $crate::zk_aluasm_error!(lt "lt <src1>, <src2>" $($tt)*) };
+    (rng $($tt:tt)*) => { $crate::zk_aluasm_error!(rng "rng <src>, <bound>" $($tt)*) };
+    (ecall $($tt:tt)*) => { $crate::zk_aluasm_error!(ecall "ecall <id>" $($tt)*) };
+
+    // Control flow instructions
     { $($tt:tt)+ } => {
+        $crate::zk_aluasm_ctrl! { $( $tt )+ }
+    };
+}
+
+/// A limit on an assembled program, declared by `.assert_size` and `.assert_complexity` directives
+/// of the textual assembly (see [`crate::gfa::Assembly`]), which are checked at compile time by the
+/// `zk_aluasm_str!` macro from the `zk-aluvm-macros` crate.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum AsmLimit {
+    /// Limit on the size of the code segment, in bytes.
+    Size {
+        /** The maximal size of the code segment */
+        max: u64,
+        /** Whether the program may have exactly `max` bytes */
+        inclusive: bool,
+    },
+
+    /// Limit on the sum of complexities of all program instructions.
+    Complexity {
+        /** The maximal total complexity */
+        max: u64,
+        /** Whether the program may have exactly `max` complexity */
+        inclusive: bool,
+    },
+}
+
+impl AsmLimit {
+    /// Measures the value limited by this directive for the provided program code.
+    pub fn measure<Id: SiteId>(&self, code: &[Instr<Id>]) -> u64 {
+        match self {
+            AsmLimit::Size { .. } => code.iter().map(|instr| instr.code_byte_len() as u64).sum(),
+            AsmLimit::Complexity { .. } => code
+                .iter()
+                .fold(0u64, |acc, instr| acc.saturating_add(instr.complexity())),
+        }
+    }
+
+    /// Checks whether the provided program code satisfies the limit.
+    pub fn is_satisfied<Id: SiteId>(&self, code: &[Instr<Id>]) -> bool {
+        let val = self.measure(code);
+        match *self {
+            AsmLimit::Size { max, inclusive } | AsmLimit::Complexity { max, inclusive } => {
+                val < max || (inclusive && val == max)
+            }
+        }
+    }
+
+    /// Checks whether the provided program code satisfies the limit.
+    ///
+    /// # Panics
+    ///
+    /// If the limit is violated.
+    pub fn check<Id: SiteId>(&self, code: &[Instr<Id>]) {
+        if self.is_satisfied(code) {
+            return;
+        }
+        let val = self.measure(code);
+        match *self {
+            AsmLimit::Size { max, inclusive } => {
+                let op = if inclusive { "<=" } else { "<" };
+                panic!("program code size is {val} bytes, which violates `.assert_size {op} {max}`")
+            }
+            AsmLimit::Complexity { max, inclusive } => {
+                let op = if inclusive { "<=" } else { "<" };
+                panic!("program complexity is {val}, which violates `.assert_complexity {op} {max}`")
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! zk_aluasm_ctrl {
+    (@ok $($tt:tt)+) => {
         $crate::gfa::Instr::Ctrl($crate::alu::instr! { $( $tt )+ }).into()
     };
+
+    // Macro instructions
+    (routine $label:ident :) => { $crate::zk_aluasm_ctrl!(@ok routine $label :) };
+    (proc $label:ident :) => { $crate::zk_aluasm_ctrl!(@ok proc $label :) };
+    (label $label:ident :) => { $crate::zk_aluasm_ctrl!(@ok label $label :) };
+    (loop $label:ident :) => { $crate::zk_aluasm_ctrl!(@ok loop $label :) };
+
+    // Control registers
+    (nop) => { $crate::zk_aluasm_ctrl!(@ok nop) };
+    (chk CO) => { $crate::zk_aluasm_ctrl!(@ok chk CO) };
+    (chk CK) => { $crate::zk_aluasm_ctrl!(@ok chk CK) };
+    (not CO) => { $crate::zk_aluasm_ctrl!(@ok not CO) };
+    (fail CK) => { $crate::zk_aluasm_ctrl!(@ok fail CK) };
+    (ret) => { $crate::zk_aluasm_ctrl!(@ok ret) };
+    (stop) => { $crate::zk_aluasm_ctrl!(@ok stop) };
+    (halt) => { $crate::zk_aluasm_ctrl!(@ok halt) };
+
+    // Jumps
+    (jmp $pos:literal) => { $crate::zk_aluasm_ctrl!(@ok jmp $pos) };
+    (jmp $pos:ident) => { $crate::zk_aluasm_ctrl!(@ok jmp $pos) };
+    (jmp + $shift:literal) => { $crate::zk_aluasm_ctrl!(@ok jmp + $shift) };
+    (jmp - $shift:literal) => { $crate::zk_aluasm_ctrl!(@ok jmp - $shift) };
+    (jmp $lib:ident, $pos:literal) => { $crate::zk_aluasm_ctrl!(@ok jmp $lib, $pos) };
+    (jmp $lib:ident, $pos:ident) => { $crate::zk_aluasm_ctrl!(@ok jmp $lib, $pos) };
+    (jif $flag:ident, + $shift:literal) => { $crate::zk_aluasm_ctrl!(@flag $flag @ok jif $flag, + $shift) };
+    (jif $flag:ident, - $shift:literal) => { $crate::zk_aluasm_ctrl!(@flag $flag @ok jif $flag, - $shift) };
+    (jif $flag:ident, $pos:literal) => { $crate::zk_aluasm_ctrl!(@flag $flag @ok jif $flag, $pos) };
+    (jif $flag:ident, $pos:ident) => { $crate::zk_aluasm_ctrl!(@flag $flag @ok jif $flag, $pos) };
+
+    // Calls
+    (call $lib:ident, $pos:literal) => { $crate::zk_aluasm_ctrl!(@ok call $lib, $pos) };
+    (call $lib:ident, $pos:ident) => { $crate::zk_aluasm_ctrl!(@ok call $lib, $pos) };
+    (call $pos:literal) => { $crate::zk_aluasm_ctrl!(@ok call $pos) };
+    (call $pos:ident) => { $crate::zk_aluasm_ctrl!(@ok call $pos) };
+
+    // Control register operands
+    (@flag CO $($tt:tt)+) => { $crate::zk_aluasm_ctrl!($($tt)+) };
+    (@flag CK $($tt:tt)+) => { $crate::zk_aluasm_ctrl!($($tt)+) };
+    (@flag $flag:tt $($tt:tt)+) => {
+        compile_error!(concat!("invalid control register `", stringify!($flag), "`; only `CO` and `CK` are allowed here"))
+    };
+
+    // Operands not matching any of the control flow instruction forms
+    (routine $($tt:tt)*) => { $crate::zk_aluasm_error!(routine "routine <label>:" $($tt)*) };
+    (proc $($tt:tt)*) => { $crate::zk_aluasm_error!(proc "proc <label>:" $($tt)*) };
+    (label $($tt:tt)*) => { $crate::zk_aluasm_error!(label "label <label>:" $($tt)*) };
+    (loop $($tt:tt)*) => { $crate::zk_aluasm_error!(loop "loop <label>:" $($tt)*) };
+    (nop $($tt:tt)*) => { $crate::zk_aluasm_error!(nop "nop" $($tt)*) };
+    (chk $($tt:tt)*) => { $crate::zk_aluasm_error!(chk "chk CO|CK" $($tt)*) };
+    (not $($tt:tt)*) => { $crate::zk_aluasm_error!(not "not CO" $($tt)*) };
+    (fail $($tt:tt)*) => { $crate::zk_aluasm_error!(fail "fail CK" $($tt)*) };
+    (mov $($tt:tt)*) => { $crate::zk_aluasm_error!(mov "mov <dst>, <src>` or `mov CO, CK" $($tt)*) };
+    (ret $($tt:tt)*) => { $crate::zk_aluasm_error!(ret "ret" $($tt)*) };
+    (stop $($tt:tt)*) => { $crate::zk_aluasm_error!(stop "stop" $($tt)*) };
+    (halt $($tt:tt)*) => { $crate::zk_aluasm_error!(halt "halt" $($tt)*) };
+    (jmp $($tt:tt)*) => { $crate::zk_aluasm_error!(jmp "jmp <pos>|+<shift>|-<shift>` or `jmp <lib>, <pos>" $($tt)*) };
+    (jif $($tt:tt)*) => { $crate::zk_aluasm_error!(jif "jif CO|CK, <pos>|+<shift>|-<shift>" $($tt)*) };
+    (call $($tt:tt)*) => { $crate::zk_aluasm_error!(call "call <pos>` or `call <lib>, <pos>" $($tt)*) };
+
+    ($op:tt $($tt:tt)*) => {
+        compile_error!(concat!("unknown instruction mnemonic `", stringify!($op), "`"))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! zk_aluasm_error {
+    ($op:ident $syntax:literal) => {
+        compile_error!(concat!("missing operands of `", stringify!($op), "` instruction; expected `", $syntax, "`"))
+    };
+    ($op:ident $syntax:literal $($tt:tt)+) => {
+        compile_error!(concat!(
+            "invalid operands `", stringify!($($tt)+), "` of `", stringify!($op), "` instruction; expected `", $syntax, "`"
+        ))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! zk_aluasm_reg {
+    (E1) => {
+        $crate::RegE::E1
+    };
+    (E2) => {
+        $crate::RegE::E2
+    };
+    (E3) => {
+        $crate::RegE::E3
+    };
+    (E4) => {
+        $crate::RegE::E4
+    };
+    (E5) => {
+        $crate::RegE::E5
+    };
+    (E6) => {
+        $crate::RegE::E6
+    };
+    (E7) => {
+        $crate::RegE::E7
+    };
+    (E8) => {
+        $crate::RegE::E8
+    };
+    (EA) => {
+        $crate::RegE::EA
+    };
+    (EB) => {
+        $crate::RegE::EB
+    };
+    (EC) => {
+        $crate::RegE::EC
+    };
+    (ED) => {
+        $crate::RegE::ED
+    };
+    (EE) => {
+        $crate::RegE::EE
+    };
+    (EF) => {
+        $crate::RegE::EF
+    };
+    (EG) => {
+        $crate::RegE::EG
+    };
+    (EH) => {
+        $crate::RegE::EH
+    };
+    ($reg:tt) => {
+        compile_error!(concat!("invalid register `", stringify!($reg), "`; expected one of `E1`..`E8` or `EA`..`EH`"))
+    };
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::{ConstVal, FieldInstr};
+    use crate::RegE;
+
+    #[test]
+    fn limits() {
+        let code = zk_aluasm! {
+            put     E1, 0;
+            put     E2, 0;
+            add     E1, E2;
+        };
+        assert_eq!(code.len(), 3);
+
+        let size = AsmLimit::Size {
+            max: 6,
+            inclusive: false,
+        };
+        assert_eq!(size.measure(&code), 6);
+        assert!(!size.is_satisfied(&code));
+        let complexity = AsmLimit::Complexity {
+            max: u64::MAX,
+            inclusive: true,
+        };
+        assert_eq!(complexity.measure(&code), 256_000 * 2 + 768_000 * 2);
+        assert!(complexity.is_satisfied::<LibId>(&code));
+    }
+
+    #[test]
+    fn cfg() {
+        let code = zk_aluasm! {
+            put     E1, 0;
+            #[cfg(test)] {
+                put     E2, 0;
+                add     E1, E2;
+            }
+            #[cfg(not(test))] {
+                mul     E1, E1;
+            }
+            ret;
+        };
+        assert_eq!(code, vec![instr! { put E1, 0 }, instr! { put E2, 0 }, instr! { add E1, E2 }, instr! { ret },]);
+    }
+
+    #[test]
+    fn const_exprs() {
+        let code = zk_aluasm! {
+            put     E1, (1 << 64) - 1;
+            put     E2, 2 * (3 + 4);
+            put     E3, 1 - 1;
+            rng     E3, 1000 * 1000 * 1000;
+        };
+        assert_eq!(code, vec![
+            FieldInstr::PutV {
+                dst: RegE::E1,
+                val: ConstVal::ValU64Max
+            }
+            .into(),
+            instr! { put E2, 14 },
+            instr! { put E3, 0 },
+            instr! { rng E3, 1_000_000_000 },
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid `put` instruction: unknown constant `P`.")]
+    fn const_expr_no_field() {
+        zk_aluasm! {
+            put     E1, P - 1;
+        };
+    }
 }
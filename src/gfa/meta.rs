@@ -0,0 +1,799 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Reflection information about the instructions of the GFA256 ISA extension.
+
+use super::FieldInstr;
+use crate::core::SQRT_MUL_LIM;
+
+/// Kind of instruction operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum OperandKind {
+    /// Field element register ([`crate::RegE`]).
+    Reg,
+    /// Field element stored in the data segment ([`crate::fe256`]).
+    Fe256,
+    /// Constant value ([`super::ConstVal`]).
+    ConstVal,
+    /// Bit dimension ([`super::Bits`]).
+    Bits,
+    /// Byte value.
+    Byte,
+}
+
+/// Way the instruction accesses its operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum OperandRole {
+    /// The operand is read by the instruction.
+    Src,
+    /// The operand is written by the instruction.
+    Dst,
+    /// The operand is both read and written by the instruction.
+    DstSrc,
+    /// The operand is an immediate value.
+    Imm,
+}
+
+/// Information about an instruction operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct OperandInfo {
+    /// Name of the operand, matching the name of the instruction field.
+    pub name: &'static str,
+    /// Kind of the operand.
+    pub kind: OperandKind,
+    /// Way the instruction accesses the operand.
+    pub role: OperandRole,
+}
+
+/// Field of the instruction bytecode encoding.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum EncodingField {
+    /// Bits with a fixed value, identifying the instruction (opcode or sub-opcode).
+    Fixed {
+        /// Number of bits.
+        bits: u8,
+        /// Value of the bits.
+        value: u8,
+    },
+    /// Operand with the given index, encoded in a number of bits.
+    Operand {
+        /// Index of the operand in [`InstrInfo::operands`].
+        index: u8,
+        /// Number of bits.
+        bits: u8,
+    },
+    /// Operand with the given index, stored in the data segment and referenced from the code
+    /// segment by its 16-bit offset.
+    DataRef {
+        /// Index of the operand in [`InstrInfo::operands`].
+        index: u8,
+        /// Number of bytes the operand takes in the data segment.
+        bytes: u16,
+    },
+}
+
+/// Effect of the instruction on the `CO` register.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum CoEffect {
+    /// The instruction doesn't change `CO`.
+    None,
+    /// The instruction sets `CO` to the result of a check.
+    Set,
+}
+
+/// Effect of the instruction on the `CK` register.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum CkEffect {
+    /// The instruction never fails.
+    None,
+    /// The instruction fails `CK` if any of its source registers is not initialized.
+    FailUninit,
+    /// The instruction fails `CK` if the host fails to handle it.
+    FailHost,
+    /// The instruction fails `CK` if any of its source registers is not initialized, or if the
+    /// operation is not defined for the source values (like the inverse of zero).
+    FailDomain,
+    /// The instruction fails `CK` if its immediate value is not an element of the field, i.e. is
+    /// not less than the configured field order.
+    FailRange,
+}
+
+/// Complexity class of the instruction.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub enum ComplexityClass {
+    /// The complexity is equal to the base complexity of the instruction, computed from its
+    /// encoding size.
+    Base,
+    /// The complexity is doubled, since the instruction performs two operations (a check and a
+    /// modular arithmetic operation).
+    Double,
+    /// The complexity is multiplied by 256, since the instruction runs an iterative algorithm
+    /// performing up to a few modular operations per each bit of a 256-bit value.
+    Iterative,
+    /// The complexity is multiplied by 1024, since the instruction performs up to two modular
+    /// multiplications of a doubled complexity per each bit of a 256-bit exponent.
+    SquareMultiply,
+    /// The complexity is multiplied by 32, since the instruction performs up to two modular
+    /// multiplications of a doubled complexity per each bit of an 8-bit exponent.
+    ShortSquareMultiply,
+    /// The complexity is multiplied by 201470, since the instruction runs the Tonelli–Shanks
+    /// algorithm, performing up to 100735 modular multiplications of a doubled complexity for the
+    /// 256-bit field of the largest two-adicity, with each number checked in the search for a
+    /// quadratic non-residue counted as a multiplication.
+    SquareRoot,
+}
+
+/// Cost of proving an instruction with the proof system backends of [`crate::zk`].
+///
+/// The costs are upper bounds: for instance, a multiplication by a constant doesn't produce R1CS
+/// constraints.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct ProofCost {
+    /// Number of R1CS constraints ([`crate::zk::R1cs`]).
+    pub r1cs_constraints: u16,
+    /// Number of PLONK rows ([`crate::zk::PlonkCircuit`]), not counting the rows depending on the
+    /// bit dimension operand.
+    pub plonk_rows: u16,
+    /// Number of PLONK rows per each bit of the bit dimension operand.
+    pub plonk_rows_per_bit: u16,
+    /// Number of AIR trace rows ([`crate::zk::AirTrace`]).
+    pub air_rows: u16,
+}
+
+const fn cost(r1cs_constraints: u16, plonk_rows: u16, plonk_rows_per_bit: u16) -> ProofCost {
+    ProofCost {
+        r1cs_constraints,
+        plonk_rows,
+        plonk_rows_per_bit,
+        air_rows: 1,
+    }
+}
+
+/// Reflection information about an instruction of the GFA256 ISA extension.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct InstrInfo {
+    /// Name of the [`FieldInstr`] variant.
+    pub name: &'static str,
+    /// Instruction mnemonic used by the assembler.
+    pub mnemonic: &'static str,
+    /// Opcode byte.
+    pub opcode: u8,
+    /// Instruction operands, in the order they are written in the assembly.
+    pub operands: &'static [OperandInfo],
+    /// Fields of the instruction encoding following the opcode byte, starting from the least
+    /// significant bits.
+    pub encoding: &'static [EncodingField],
+    /// Size of the instruction in the code segment, in bytes (including the opcode).
+    pub code_bytes: u16,
+    /// Number of bytes the instruction takes in the data segment.
+    pub data_bytes: u16,
+    /// Effect of the instruction on the `CO` register.
+    pub co: CoEffect,
+    /// Effect of the instruction on the `CK` register.
+    pub ck: CkEffect,
+    /// Complexity class of the instruction.
+    pub complexity_class: ComplexityClass,
+    /// Computational complexity of the instruction.
+    ///
+    /// Complexity of a specific instruction may be lower if it uses the same register in
+    /// several operands.
+    pub complexity: u64,
+    /// Cost of proving the instruction.
+    pub proof_cost: ProofCost,
+    /// Short summary of the instruction semantics.
+    pub summary: &'static str,
+}
+
+const fn reg(name: &'static str, role: OperandRole) -> OperandInfo {
+    OperandInfo {
+        name,
+        kind: OperandKind::Reg,
+        role,
+    }
+}
+
+const fn fixed(bits: u8, value: u8) -> EncodingField { EncodingField::Fixed { bits, value } }
+
+const fn operand(index: u8, bits: u8) -> EncodingField { EncodingField::Operand { index, bits } }
+
+const fn complexity(operands: &[OperandInfo], data_bytes: u16, class: ComplexityClass) -> u64 {
+    // Follows `Instruction::base_complexity`: registers are counted by their size, once for
+    // reading and once for writing; immediate values take a byte, and data segment values are
+    // counted twice.
+    let mut bytes = data_bytes as u64 * 2;
+    let mut no = 0;
+    while no < operands.len() {
+        let op = operands[no];
+        bytes += match (op.kind, op.role) {
+            (OperandKind::Reg, OperandRole::DstSrc) => 64,
+            (OperandKind::Reg, _) => 32,
+            (OperandKind::ConstVal | OperandKind::Bits | OperandKind::Byte, _) => 1,
+            (OperandKind::Fe256, _) => 0,
+        };
+        no += 1;
+    }
+    let base = bytes * 8 * 1000;
+    match class {
+        ComplexityClass::Base => base,
+        ComplexityClass::Double => base * 2,
+        ComplexityClass::Iterative => base * 256,
+        ComplexityClass::SquareMultiply => base * 1024,
+        ComplexityClass::ShortSquareMultiply => base * 32,
+        ComplexityClass::SquareRoot => base * 2 * SQRT_MUL_LIM,
+    }
+}
+
+macro_rules! instr_info {
+    (
+        $name:literal, $mnemonic:literal, $opcode:expr,
+        [$($op:expr),* $(,)?], [$($enc:expr),* $(,)?],
+        $code:literal + $data:literal, $co:ident, $ck:ident, $class:ident, $cost:expr, $summary:literal
+    ) => {
+        InstrInfo {
+            name: $name,
+            mnemonic: $mnemonic,
+            opcode: $opcode,
+            operands: &[$($op),*],
+            encoding: &[$($enc),*],
+            code_bytes: $code,
+            data_bytes: $data,
+            co: CoEffect::$co,
+            ck: CkEffect::$ck,
+            complexity_class: ComplexityClass::$class,
+            complexity: complexity(&[$($op),*], $data, ComplexityClass::$class),
+            proof_cost: $cost,
+            summary: $summary,
+        }
+    };
+}
+
+impl FieldInstr {
+    /// Reflection information about all instructions of the ISA extension, in the order of the
+    /// [`FieldInstr`] variants.
+    pub const INFO: &'static [InstrInfo] = &{
+        use OperandRole::*;
+        [
+            instr_info!(
+                "Test",
+                "test",
+                Self::TEST,
+                [reg("src", Src)],
+                [fixed(4, 0b0000), operand(0, 4)],
+                2 + 0,
+                Set,
+                None,
+                Base,
+                cost(0, 0, 0),
+                "Sets `CO` to whether `src` holds a value."
+            ),
+            instr_info!(
+                "Clr",
+                "clr",
+                Self::CLR,
+                [reg("dst", Dst)],
+                [fixed(4, 0b0001), operand(0, 4)],
+                2 + 0,
+                None,
+                None,
+                Base,
+                cost(0, 0, 0),
+                "Sets `dst` to no value."
+            ),
+            instr_info!(
+                "PutD",
+                "put",
+                Self::PUTD,
+                [reg("dst", Dst), OperandInfo {
+                    name: "data",
+                    kind: OperandKind::Fe256,
+                    role: Imm
+                }],
+                [fixed(4, 0b0010), operand(0, 4), EncodingField::DataRef { index: 1, bytes: 32 }],
+                4 + 32,
+                None,
+                FailRange,
+                Base,
+                cost(0, 1, 0),
+                "Puts the field element `data` from the data segment into `dst`."
+            ),
+            instr_info!(
+                "PutZ",
+                "put",
+                Self::PUTZ,
+                [reg("dst", Dst)],
+                [fixed(4, 0b0011), operand(0, 4)],
+                2 + 0,
+                None,
+                None,
+                Base,
+                cost(0, 1, 0),
+                "Puts zero into `dst`."
+            ),
+            instr_info!(
+                "PutV",
+                "put",
+                Self::PUTV,
+                [reg("dst", Dst), OperandInfo {
+                    name: "val",
+                    kind: OperandKind::ConstVal,
+                    role: Imm
+                }],
+                [operand(1, 2), fixed(2, 0b01), operand(0, 4)],
+                2 + 0,
+                None,
+                FailRange,
+                Base,
+                cost(0, 1, 0),
+                "Puts the constant `val` into `dst`."
+            ),
+            instr_info!(
+                "Fits",
+                "fits",
+                Self::FITS,
+                [reg("src", Src), OperandInfo {
+                    name: "bits",
+                    kind: OperandKind::Bits,
+                    role: Imm
+                }],
+                [operand(1, 3), fixed(1, 0b1), operand(0, 4)],
+                2 + 0,
+                Set,
+                FailUninit,
+                Double,
+                cost(0, 3, 1),
+                "Sets `CO` to whether the value of `src` fits into `bits` bits; fails `CK` if `src` holds no value."
+            ),
+            instr_info!(
+                "Mov",
+                "mov",
+                Self::MOV,
+                [reg("dst", Dst), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                None,
+                Base,
+                cost(0, 0, 0),
+                "Copies the value (or absence of value) of `src` into `dst`."
+            ),
+            instr_info!(
+                "Eq",
+                "eq",
+                Self::EQ,
+                [reg("src1", Src), reg("src2", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                Set,
+                None,
+                Base,
+                cost(0, 1, 0),
+                "Sets `CO` to whether `src1` and `src2` hold equal values."
+            ),
+            instr_info!(
+                "Neg",
+                "neg",
+                Self::NEG,
+                [reg("dst", Dst), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailUninit,
+                Double,
+                cost(0, 1, 0),
+                "Puts the additive inverse of `src` into `dst` modulo the field order; fails `CK` if `src` holds no \
+                 value."
+            ),
+            instr_info!(
+                "Add",
+                "add",
+                Self::ADD,
+                [reg("dst_src", DstSrc), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailUninit,
+                Double,
+                cost(0, 1, 0),
+                "Adds `src` to `dst_src` modulo the field order; fails `CK` if either holds no value."
+            ),
+            instr_info!(
+                "Mul",
+                "mul",
+                Self::MUL,
+                [reg("dst_src", DstSrc), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailUninit,
+                Double,
+                cost(1, 1, 0),
+                "Multiplies `dst_src` by `src` modulo the field order; fails `CK` if either holds no value."
+            ),
+            instr_info!(
+                "Ecall",
+                "ecall",
+                Self::ECALL,
+                [OperandInfo {
+                    name: "id",
+                    kind: OperandKind::Byte,
+                    role: Imm
+                }],
+                [operand(0, 8)],
+                2 + 0,
+                None,
+                FailHost,
+                Base,
+                cost(0, 0, 0),
+                "Invokes the host syscall `id` on the registers `EA`-`EH`; fails `CK` if the host fails it."
+            ),
+            instr_info!(
+                "Inv",
+                "inv",
+                Self::INV,
+                [reg("dst", Dst), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailDomain,
+                Iterative,
+                cost(1, 2, 0),
+                "Puts the multiplicative inverse of `src` into `dst` modulo the field order; fails `CK` if `src` \
+                 holds no value or zero."
+            ),
+            instr_info!(
+                "Exp",
+                "exp",
+                Self::EXP,
+                [reg("dst_src", DstSrc), reg("exp", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailUninit,
+                SquareMultiply,
+                cost(0, 0, 0),
+                "Raises `dst_src` to the power of `exp` modulo the field order; fails `CK` if either holds no value."
+            ),
+            instr_info!(
+                "ExpI",
+                "exp",
+                Self::EXPI,
+                [reg("dst_src", DstSrc), OperandInfo {
+                    name: "exp",
+                    kind: OperandKind::Byte,
+                    role: Imm
+                }],
+                [operand(0, 4), fixed(4, 0), operand(1, 8)],
+                3 + 0,
+                None,
+                FailUninit,
+                ShortSquareMultiply,
+                cost(14, 14, 0),
+                "Raises `dst_src` to the power of the immediate `exp` modulo the field order; fails `CK` if \
+                 `dst_src` holds no value."
+            ),
+            instr_info!(
+                "Sqr",
+                "sqr",
+                Self::SQR,
+                [reg("dst", Dst), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                FailUninit,
+                Double,
+                cost(1, 1, 0),
+                "Puts the square of `src` into `dst` modulo the field order; fails `CK` if `src` holds no value."
+            ),
+            instr_info!(
+                "Sqrt",
+                "sqrt",
+                Self::SQRT,
+                [reg("dst", Dst), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                Set,
+                FailDomain,
+                SquareRoot,
+                cost(1, 2, 0),
+                "Puts the smaller square root of `src` into `dst` modulo the field order, setting `CO` to whether \
+                 the root exists; fails `CK` if `src` holds no value or no quadratic non-residue modulo the field \
+                 order is found."
+            ),
+            instr_info!(
+                "CMov",
+                "cmov",
+                Self::CMOV,
+                [reg("dst", DstSrc), reg("src", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                None,
+                None,
+                Base,
+                cost(1, 0, 0),
+                "Copies the value (or absence of value) of `src` into `dst` if `CO` is set, leaving `dst` unchanged \
+                 otherwise."
+            ),
+            instr_info!(
+                "Sel",
+                "sel",
+                Self::SEL,
+                [reg("dst", Dst), reg("src_true", Src), reg("src_false", Src)],
+                [operand(0, 4), operand(1, 4), operand(2, 4), fixed(4, 0)],
+                3 + 0,
+                None,
+                None,
+                Base,
+                cost(1, 0, 0),
+                "Copies the value (or absence of value) of `src_true` into `dst` if `CO` is set, and of `src_false` \
+                 otherwise."
+            ),
+            instr_info!(
+                "Lt",
+                "lt",
+                Self::LT,
+                [reg("src1", Src), reg("src2", Src)],
+                [operand(0, 4), operand(1, 4)],
+                2 + 0,
+                Set,
+                FailUninit,
+                Base,
+                cost(0, 0, 0),
+                "Sets `CO` to whether the value of `src1` is less than the value of `src2`; fails `CK` if any of \
+                 them holds no value."
+            ),
+            instr_info!(
+                "Rng",
+                "rng",
+                Self::RNG,
+                [reg("src", Src), OperandInfo {
+                    name: "bound",
+                    kind: OperandKind::Fe256,
+                    role: Imm
+                }],
+                [operand(0, 4), fixed(4, 0), EncodingField::DataRef { index: 1, bytes: 32 }],
+                4 + 32,
+                Set,
+                FailUninit,
+                Base,
+                cost(0, 0, 0),
+                "Sets `CO` to whether the value of `src` is less than `bound` from the data segment; fails `CK` if \
+                 `src` holds no value."
+            ),
+        ]
+    };
+
+    /// Returns reflection information about the instruction.
+    pub fn info(&self) -> &'static InstrInfo {
+        let index = match self {
+            FieldInstr::Test { .. } => 0,
+            FieldInstr::Clr { .. } => 1,
+            FieldInstr::PutD { .. } => 2,
+            FieldInstr::PutZ { .. } => 3,
+            FieldInstr::PutV { .. } => 4,
+            FieldInstr::Fits { .. } => 5,
+            FieldInstr::Mov { .. } => 6,
+            FieldInstr::Eq { .. } => 7,
+            FieldInstr::Neg { .. } => 8,
+            FieldInstr::Add { .. } => 9,
+            FieldInstr::Mul { .. } => 10,
+            FieldInstr::Ecall { .. } => 11,
+            FieldInstr::Inv { .. } => 12,
+            FieldInstr::Exp { .. } => 13,
+            FieldInstr::ExpI { .. } => 14,
+            FieldInstr::Sqr { .. } => 15,
+            FieldInstr::Sqrt { .. } => 16,
+            FieldInstr::CMov { .. } => 17,
+            FieldInstr::Sel { .. } => 18,
+            FieldInstr::Lt { .. } => 19,
+            FieldInstr::Rng { .. } => 20,
+        };
+        &Self::INFO[index]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+    use alloc::{format, vec};
+
+    use aluvm::isa::{Bytecode, ExecStep, Instruction};
+    use aluvm::{Core, CoreConfig, Lib, LibId, Site};
+
+    use super::*;
+    use crate::gfa::{Bits, ConstVal, Instr, ECALL_WINDOW, NO_CONTEXT};
+    use crate::{fe256, GfaCore, RegE};
+
+    fn samples() -> [FieldInstr; 21] {
+        use RegE::*;
+        [
+            FieldInstr::Test { src: E2 },
+            FieldInstr::Clr { dst: E3 },
+            FieldInstr::PutD {
+                dst: EA,
+                data: fe256::from(0xFEDCu16),
+            },
+            FieldInstr::PutZ { dst: E8 },
+            FieldInstr::PutV {
+                dst: EB,
+                val: ConstVal::ValU128Max,
+            },
+            FieldInstr::Fits {
+                src: EC,
+                bits: Bits::Bits96,
+            },
+            FieldInstr::Mov { dst: E1, src: ED },
+            FieldInstr::Eq { src1: E5, src2: E7 },
+            FieldInstr::Neg { dst: E6, src: EE },
+            FieldInstr::Add { dst_src: E4, src: EF },
+            FieldInstr::Mul { dst_src: EH, src: E1 },
+            FieldInstr::Ecall { id: 0xA5 },
+            FieldInstr::Inv { dst: E7, src: EB },
+            FieldInstr::Exp { dst_src: E2, exp: EC },
+            FieldInstr::ExpI { dst_src: EG, exp: 0x5A },
+            FieldInstr::Sqr { dst: E5, src: EH },
+            FieldInstr::Sqrt { dst: EE, src: E4 },
+            FieldInstr::CMov { dst: EB, src: E6 },
+            FieldInstr::Sel {
+                dst: E3,
+                src_true: EG,
+                src_false: E8,
+            },
+            FieldInstr::Lt { src1: EA, src2: E2 },
+            FieldInstr::Rng {
+                src: EF,
+                bound: fe256::from(1_000_000_000u32),
+            },
+        ]
+    }
+
+    fn operand_value(instr: &FieldInstr, index: u8) -> u8 {
+        let regs = match *instr {
+            FieldInstr::PutV { dst, val } => [dst.to_u4().to_u8(), val.to_u2().to_u8(), 0],
+            FieldInstr::Fits { src, bits } => [src.to_u4().to_u8(), bits.to_u3().to_u8(), 0],
+            FieldInstr::Ecall { id } => [id, 0, 0],
+            FieldInstr::ExpI { dst_src, exp } => [dst_src.to_u4().to_u8(), exp, 0],
+            FieldInstr::Test { src: a }
+            | FieldInstr::Clr { dst: a }
+            | FieldInstr::PutD { dst: a, .. }
+            | FieldInstr::PutZ { dst: a }
+            | FieldInstr::Rng { src: a, .. } => [a.to_u4().to_u8(), 0, 0],
+            FieldInstr::Mov { dst: a, src: b }
+            | FieldInstr::Eq { src1: a, src2: b }
+            | FieldInstr::Lt { src1: a, src2: b }
+            | FieldInstr::Neg { dst: a, src: b }
+            | FieldInstr::Inv { dst: a, src: b }
+            | FieldInstr::Sqr { dst: a, src: b }
+            | FieldInstr::Sqrt { dst: a, src: b }
+            | FieldInstr::CMov { dst: a, src: b }
+            | FieldInstr::Add { dst_src: a, src: b }
+            | FieldInstr::Mul { dst_src: a, src: b }
+            | FieldInstr::Exp { dst_src: a, exp: b } => [a.to_u4().to_u8(), b.to_u4().to_u8(), 0],
+            FieldInstr::Sel { dst, src_true, src_false } => {
+                [dst.to_u4().to_u8(), src_true.to_u4().to_u8(), src_false.to_u4().to_u8()]
+            }
+        };
+        regs[index as usize]
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(FieldInstr::INFO.len(), 21);
+        for instr in samples() {
+            let info = instr.info();
+            assert!(format!("{instr:?}").starts_with(info.name));
+            assert_eq!(instr.to_string().split_whitespace().next(), Some(info.mnemonic));
+            assert_eq!(info.opcode, Bytecode::<LibId>::opcode_byte(&instr));
+            assert_eq!(info.code_bytes, Bytecode::<LibId>::code_byte_len(&instr));
+            assert_eq!(info.data_bytes, Instruction::<LibId>::ext_data_bytes(&instr));
+            assert_eq!(info.complexity, Instruction::<LibId>::complexity(&instr), "{instr}");
+
+            let reg = |role: OperandRole| {
+                info.operands
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, op)| {
+                        op.kind == OperandKind::Reg && (op.role == role || op.role == OperandRole::DstSrc)
+                    })
+                    .map(|(no, _)| RegE::from(amplify::num::u4::with(operand_value(&instr, no as u8))))
+                    .collect()
+            };
+            if let FieldInstr::Ecall { .. } = instr {
+                // Host syscalls access the register window instead of the operands
+                let window = ECALL_WINDOW.into_iter().collect();
+                assert_eq!(Instruction::<LibId>::src_regs(&instr), window);
+                assert_eq!(Instruction::<LibId>::dst_regs(&instr), window);
+                continue;
+            }
+            assert_eq!(Instruction::<LibId>::src_regs(&instr), reg(OperandRole::Src));
+            assert_eq!(Instruction::<LibId>::dst_regs(&instr), reg(OperandRole::Dst));
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        for instr in samples() {
+            let info = instr.info();
+            let lib = Lib::assemble(&[Instr::<LibId>::Gfa(instr)]).unwrap();
+            assert_eq!(lib.data.len(), info.data_bytes as usize);
+
+            let mut bits = 0u32;
+            let mut bit_pos = 0;
+            let mut code = vec![info.opcode];
+            for field in info.encoding {
+                match *field {
+                    EncodingField::Fixed { bits: len, value } => {
+                        bits |= (value as u32) << bit_pos;
+                        bit_pos += len;
+                    }
+                    EncodingField::Operand { index, bits: len } => {
+                        bits |= (operand_value(&instr, index) as u32) << bit_pos;
+                        bit_pos += len;
+                    }
+                    EncodingField::DataRef { .. } => {
+                        assert_eq!(bit_pos % 8, 0);
+                        code.extend(bits.to_le_bytes().into_iter().take(bit_pos as usize / 8));
+                        code.extend([0u8, 0u8]);
+                        bits = 0;
+                        bit_pos = 0;
+                    }
+                }
+            }
+            code.extend(bits.to_le_bytes().into_iter().take(bit_pos as usize / 8));
+            assert_eq!(lib.code.as_slice(), code.as_slice(), "{instr}");
+        }
+    }
+
+    #[test]
+    fn flags() {
+        for instr in samples() {
+            let info = instr.info();
+            let mut core = Core::<LibId, GfaCore>::with(
+                CoreConfig {
+                    halt: false,
+                    complexity_lim: None,
+                },
+                default!(),
+            );
+            let site = Site::new(LibId::from([0u8; 32]), 0);
+            let step = Instruction::<LibId>::exec(&instr, site, &mut core, &NO_CONTEXT);
+            let fails = step == ExecStep::Fail;
+            // The sample immediate values are elements of the default field
+            assert_eq!(fails, !matches!(info.ck, CkEffect::None | CkEffect::FailRange), "{instr}");
+        }
+    }
+}
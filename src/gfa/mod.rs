@@ -27,8 +27,66 @@ mod bytecode;
 mod exec;
 #[macro_use]
 mod masm;
+mod meta;
+mod asm;
+mod builder;
+mod cexpr;
+mod decompile;
+mod complexity;
+mod context;
+mod diff;
+mod expr;
+mod isa;
+mod link;
+mod manifest;
+mod preproc;
+mod pretty;
+mod program;
+mod smt;
+mod spec;
+mod srcmap;
+mod strict;
+mod symbolic;
+mod verify;
+pub mod analysis;
+pub mod lint;
+pub mod opt;
 
-pub use instr::{Bits, ConstVal, FieldInstr, Instr};
+pub use asm::{AsmError, AsmErrorKind, Assembly, DataDecl, NonCanonicalPut, ParseInstrError};
+pub use builder::{BuildError, ProgramBuilder};
+pub use cexpr::{eval_const, ConstExprError};
+pub use complexity::{estimate_complexity, BlockComplexity, ComplexityError, ComplexityReport};
+pub use context::{EcallError, EcallFrame, GfaContext, ECALL_WINDOW, NO_CONTEXT};
+pub use decompile::{decompile, DecompileError, ExprGraph, ExprNode, NodeId};
+pub use diff::{diff, Change, DiffError, InstrLoc};
+pub(crate) use exec::fail_ck;
+pub use expr::{Expr, ExprCompiler, ExprError};
+pub use instr::{
+    Bits, BitsOutOfRange, ConstVal, ConstValOutOfRange, FieldInstr, Instr, InstrClass, ParseBitsError,
+    ParseConstValError, UnsupportedBitLen,
+};
+pub use isa::{required_groups, IsaGroup, IsaGroups};
+pub use link::{link, LinkError, LinkedLib};
+pub use manifest::Manifest;
+pub use masm::AsmLimit;
+pub use meta::{
+    CkEffect, CoEffect, ComplexityClass, EncodingField, InstrInfo, OperandInfo, OperandKind, OperandRole, ProofCost,
+};
+pub use preproc::AsmConfig;
+pub use pretty::{AsmFormatter, AsmStyle};
+pub use program::{Program, Routine};
+pub use smt::{equiv, smtlib};
+pub use spec::{IsaSpec, OperandValue};
+pub use srcmap::{SourceLoc, SourceMap};
+pub use symbolic::{Assumption, Cond, PathEnd, SymPath, SymbolicError, SymbolicExecutor, SymbolicResult};
+pub use verify::{verify, VerifyIssue};
 
 /// AluVM ISA extension name.
 pub const ISA_GFA256: &str = "GFA256";
+
+/// Name of the AluVM ISA extension declared by libraries using instruction groups introduced in
+/// the second version of GFA256 (see [`IsaGroup::version`]).
+pub const ISA_GFA256_V2: &str = "GFA256V2";
+
+/// Latest version of the GFA256 ISA extension supported by the VM.
+pub const ISA_GFA256_VERSION: u8 = 2;
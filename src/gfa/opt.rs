@@ -0,0 +1,490 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Optimization passes over zk-AluVM programs.
+//!
+//! Passes may change the size of the instructions and remove some of them; local jumps (both
+//! absolute and relative) are updated to point to the same instructions as before. Passes return
+//! the original program unmodified if the jumps can't be relocated, for instance, when a jump
+//! points into the middle of an instruction, or a relative jump gets out of the range.
+//!
+//! NB: Since offsets of the instructions change, entry points into the program used by external
+//! callers must be updated as well.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use aluvm::isa::{Bytecode, CtrlInstr, GotoTarget, Instruction};
+use aluvm::{CoreExt, SiteId};
+use amplify::num::u256;
+
+use super::analysis::{ControlFlow, Initialization, Liveness};
+use super::builder::put_instr;
+use super::{ConstVal, FieldInstr, Instr, ECALL_WINDOW};
+use crate::{fe256, GfaConfig, GfaCore, RegE};
+
+/// Applies edits to the program code, relocating local jumps.
+///
+/// Each of the edits corresponds to the instruction of the original code with the same index,
+/// providing its replacement, or `None` if the instruction must be removed.
+fn rewrite<Id: SiteId>(code: &[Instr<Id>], edits: Vec<Option<Instr<Id>>>) -> Option<Vec<Instr<Id>>> {
+    debug_assert_eq!(code.len(), edits.len());
+    let cfg = ControlFlow::analyze(code).ok()?;
+    let old = cfg.offsets();
+    let mut new = Vec::with_capacity(old.len());
+    let mut pos = 0u16;
+    for edit in &edits {
+        new.push(pos);
+        if let Some(instr) = edit {
+            pos = pos.checked_add(instr.code_byte_len())?;
+        }
+    }
+    new.push(pos);
+
+    let mut res = Vec::with_capacity(code.len());
+    for (no, edit) in edits.into_iter().enumerate() {
+        let Some(mut instr) = edit else {
+            continue;
+        };
+        match instr.local_goto_pos() {
+            GotoTarget::None => {}
+            GotoTarget::Absolute(pos) => {
+                let target = old.binary_search(pos).ok()?;
+                *pos = new[target];
+            }
+            GotoTarget::Relative(shift) => {
+                let target = old[no].checked_add_signed(*shift as i16)?;
+                let target = old.binary_search(&target).ok()?;
+                *shift = i8::try_from(new[target] as i32 - new[no] as i32).ok()?;
+            }
+        }
+        res.push(instr);
+    }
+    Some(res)
+}
+
+/// Constant-folding pass.
+///
+/// Tracks registers holding constant values put by `put` instructions, and replaces arithmetic
+/// operations (`add`, `mul`, `neg`, `inv`, `exp` and `sqr`) over them with a single `put` of the
+/// computed result, which has a lower complexity. Values are computed using the same microcode as
+/// used by the VM.
+///
+/// The knowledge about register values is reset at each instruction which can be reached by a
+/// jump, and after calls to other routines.
+pub fn fold_constants<Id: SiteId>(code: &[Instr<Id>], config: GfaConfig) -> Vec<Instr<Id>> {
+    let Ok(cfg) = ControlFlow::analyze(code) else {
+        return code.to_vec();
+    };
+    let targets = cfg.goto_targets();
+    let mut known = [None::<fe256>; 16];
+    let mut edits = Vec::with_capacity(code.len());
+    let mut changed = false;
+
+    for (no, instr) in code.iter().enumerate() {
+        if targets.contains(&no) {
+            known = [None; 16];
+        }
+        let mut edit = *instr;
+        match *instr {
+            Instr::Gfa(gfa) => {
+                let (dst, val) = match gfa {
+                    FieldInstr::PutD { dst, data } => (Some(dst), Some(data)),
+                    FieldInstr::PutZ { dst } => (Some(dst), Some(fe256::ZERO)),
+                    FieldInstr::PutV { dst, val } => (Some(dst), const_val(val, config)),
+                    FieldInstr::Clr { dst } => (Some(dst), None),
+                    FieldInstr::Mov { dst, src } => (Some(dst), known[src as usize]),
+                    // The result depends on `CO`, unless both registers hold the same value
+                    FieldInstr::CMov { dst, src } => {
+                        (Some(dst), known[src as usize].filter(|val| known[dst as usize] == Some(*val)))
+                    }
+                    FieldInstr::Sel { dst, src_true, src_false } => (
+                        Some(dst),
+                        known[src_true as usize].filter(|val| known[src_false as usize] == Some(*val)),
+                    ),
+                    FieldInstr::Add { dst_src, src: _ }
+                    | FieldInstr::Mul { dst_src, src: _ }
+                    | FieldInstr::Exp { dst_src, exp: _ }
+                    | FieldInstr::ExpI { dst_src, exp: _ } => {
+                        (Some(dst_src), eval(gfa, &known, config))
+                    }
+                    FieldInstr::Sqr { dst, src: _ } => (Some(dst), eval(gfa, &known, config)),
+                    // Square root sets `CO`, and is not folded
+                    FieldInstr::Sqrt { dst, src: _ } => (Some(dst), None),
+                    // Inverse of zero fails `CK`, and is not folded
+                    FieldInstr::Inv { dst, src: _ } => (Some(dst), eval(gfa, &known, config)),
+                    FieldInstr::Neg { dst, src: _ } => (Some(dst), eval(gfa, &known, config)),
+                    FieldInstr::Test { .. }
+                    | FieldInstr::Fits { .. }
+                    | FieldInstr::Eq { .. }
+                    | FieldInstr::Lt { .. }
+                    | FieldInstr::Rng { .. } => (None, None),
+                    FieldInstr::Ecall { .. } => {
+                        // The host may write any register from the window
+                        for reg in ECALL_WINDOW {
+                            known[reg as usize] = None;
+                        }
+                        (None, None)
+                    }
+                };
+                if let (Some(dst), Some(val)) = (dst, val) {
+                    if matches!(
+                        gfa,
+                        FieldInstr::Add { .. }
+                            | FieldInstr::Mul { .. }
+                            | FieldInstr::Neg { .. }
+                            | FieldInstr::Inv { .. }
+                            | FieldInstr::Exp { .. }
+                            | FieldInstr::ExpI { .. }
+                            | FieldInstr::Sqr { .. }
+                    ) {
+                        edit = put_instr(dst, val, config.field_order).into();
+                        changed = true;
+                    }
+                }
+                if let Some(dst) = dst {
+                    known[dst as usize] = val;
+                }
+            }
+            Instr::Ctrl(CtrlInstr::Fn { .. } | CtrlInstr::Call { .. } | CtrlInstr::Exec { .. }) => {
+                known = [None; 16];
+            }
+            _ => {}
+        }
+        edits.push(Some(edit));
+    }
+
+    if !changed {
+        return code.to_vec();
+    }
+    rewrite(code, edits).unwrap_or_else(|| code.to_vec())
+}
+
+fn const_val(val: ConstVal, config: GfaConfig) -> Option<fe256> {
+    val.to_fe256()
+        .or_else(|| config.field_order.checked_sub(u256::ONE).map(fe256::from))
+}
+
+/// Evaluates an arithmetic instruction over known register values.
+fn eval(instr: FieldInstr, known: &[Option<fe256>; 16], config: GfaConfig) -> Option<fe256> {
+    let mut core = GfaCore::with(config);
+    for reg in RegE::ALL {
+        match known[reg as usize] {
+            Some(val) if val.to_u256() >= config.field_order => return None,
+            val => core.put(reg, val),
+        }
+    }
+    let (dst, status) = match instr {
+        FieldInstr::Add { dst_src, src } => (dst_src, core.add_mod(dst_src, src)),
+        FieldInstr::Mul { dst_src, src } => (dst_src, core.mul_mod(dst_src, src)),
+        FieldInstr::Neg { dst, src } => (dst, core.neg_mod(dst, src)),
+        FieldInstr::Inv { dst, src } => (dst, core.inv_mod(dst, src)),
+        FieldInstr::Exp { dst_src, exp } => (dst_src, core.exp_mod(dst_src, exp)),
+        FieldInstr::ExpI { dst_src, exp } => (dst_src, core.expi_mod(dst_src, exp)),
+        FieldInstr::Sqr { dst, src } => (dst, core.sqr_mod(dst, src)),
+        _ => return None,
+    };
+    if !status.is_ok() {
+        return None;
+    }
+    core.get(dst)
+}
+
+/// Dead-code elimination pass.
+///
+/// Performs backwards liveness analysis over the program control flow graph and removes
+/// instructions whose results are never read and which can't affect `CO` and `CK` registers:
+/// - `clr` and `mov` instructions writing to a dead register;
+/// - `put` instructions writing to a dead register, if the value is an element of the field with
+///   the order from `config` (otherwise they fail `CK`);
+/// - `add`, `mul`, `neg`, `exp` and `sqr` instructions writing to a dead register, if their source
+///   registers are known to be initialized (otherwise they may fail `CK`).
+///
+/// `live_out` provides the registers which are read by the host or the caller once the program
+/// stops or returns. Calls of other routines are considered to read all the registers.
+///
+/// Instructions for which `keep` returns `true` are never removed, which allows to opt out of the
+/// optimization for the instructions the host considers having side effects.
+pub fn eliminate_dead_code<Id: SiteId>(
+    code: &[Instr<Id>],
+    live_out: &BTreeSet<RegE>,
+    config: GfaConfig,
+    keep: impl Fn(&Instr<Id>) -> bool,
+) -> Vec<Instr<Id>> {
+    let mut code = code.to_vec();
+    loop {
+        let Ok(cfg) = ControlFlow::analyze(&code) else {
+            return code;
+        };
+        let live = Liveness::analyze(&cfg, live_out);
+        let init = Initialization::analyze(&cfg);
+        let mut changed = false;
+        let edits = code
+            .iter()
+            .enumerate()
+            .map(|(no, instr)| {
+                let Instr::Gfa(gfa) = instr else {
+                    return Some(*instr);
+                };
+                let removable = match gfa {
+                    FieldInstr::PutD { dst: _, data } => data.to_u256() < config.field_order,
+                    FieldInstr::PutV { dst: _, val } => {
+                        const_val(*val, config).is_some_and(|val| val.to_u256() < config.field_order)
+                    }
+                    FieldInstr::Clr { .. }
+                    | FieldInstr::PutZ { .. }
+                    | FieldInstr::Mov { .. }
+                    | FieldInstr::CMov { .. }
+                    | FieldInstr::Sel { .. } => true,
+                    FieldInstr::Add { .. }
+                    | FieldInstr::Mul { .. }
+                    | FieldInstr::Neg { .. }
+                    | FieldInstr::Exp { .. }
+                    | FieldInstr::ExpI { .. }
+                    | FieldInstr::Sqr { .. } => {
+                        Instruction::<Id>::src_regs(gfa)
+                            .iter()
+                            .all(|reg| init.is_init_before(no, *reg))
+                    }
+                    // Inverse fails `CK` on zero even if its source register is initialized
+                    FieldInstr::Test { .. }
+                    | FieldInstr::Fits { .. }
+                    | FieldInstr::Eq { .. }
+                    | FieldInstr::Lt { .. }
+                    | FieldInstr::Rng { .. }
+                    | FieldInstr::Ecall { .. }
+                    | FieldInstr::Inv { .. }
+                    | FieldInstr::Sqrt { .. } => false,
+                };
+                let dead = !instr
+                    .dst_regs()
+                    .iter()
+                    .any(|reg| live.is_live_after(no, *reg));
+                if removable && dead && !keep(instr) {
+                    changed = true;
+                    None
+                } else {
+                    Some(*instr)
+                }
+            })
+            .collect();
+        if !changed {
+            return code;
+        }
+        match rewrite(&code, edits) {
+            Some(new) => code = new,
+            None => return code,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+
+    #[test]
+    fn fold() {
+        let code = zk_aluasm! {
+            put     E1, 2;
+            put     E2, 3;
+            mul     E1, E2;
+            add     E1, E2;
+            neg     E3, E1;
+            put     E4, 0;
+            neg     E4, E4;
+            mov     E5, E1;
+            add     E5, EA;
+        };
+        let fq = GfaConfig::default().field_order;
+        let folded = fold_constants(&code, GfaConfig::default());
+        assert_eq!(folded, vec![
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(2u8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E2,
+                data: fe256::from(3u8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(6u8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(9u8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E3,
+                data: fe256::from(fq - u256::from(9u8))
+            }
+            .into(),
+            FieldInstr::PutZ { dst: RegE::E4 }.into(),
+            FieldInstr::PutZ { dst: RegE::E4 }.into(),
+            FieldInstr::Mov {
+                dst: RegE::E5,
+                src: RegE::E1
+            }
+            .into(),
+            FieldInstr::Add {
+                dst_src: RegE::E5,
+                src: RegE::EA
+            }
+            .into(),
+        ]);
+    }
+
+    #[test]
+    fn reset_on_jumps() {
+        let code = zk_aluasm! {
+            put     E1, 2;
+            put     E2, 3;
+            jmp     11;
+            add     E1, E2;
+            call    0;
+            add     E1, E2;
+        };
+        assert_eq!(fold_constants(&code, GfaConfig::default()), code);
+    }
+
+    #[test]
+    fn relocation() {
+        let code = zk_aluasm! {
+            put     E1, 1;
+            add     E1, E1;
+            jif     CO, +5;
+            jmp     13;
+            put     E2, 0;
+            ret;
+        };
+        assert_eq!(fold_constants(&code, GfaConfig::default()), vec![
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(1u8)
+            }
+            .into(),
+            FieldInstr::PutD {
+                dst: RegE::E1,
+                data: fe256::from(2u8)
+            }
+            .into(),
+            CtrlInstr::ShOvfl { shift: 5 }.into(),
+            CtrlInstr::Jmp { pos: 15 }.into(),
+            FieldInstr::PutZ { dst: RegE::E2 }.into(),
+            CtrlInstr::Ret.into(),
+        ]);
+    }
+
+    #[test]
+    fn dce() {
+        let code = zk_aluasm! {
+            put     E1, 2;
+            put     E1, 3;
+            put     E2, 4;
+            mov     E3, E2;
+            add     E2, E1;
+            add     E4, E1;
+            clr     E5;
+            mov     EA, E2;
+            ret;
+        };
+        let config = GfaConfig::default();
+        let live_out = bset![RegE::EA];
+        assert_eq!(eliminate_dead_code(&code, &live_out, config, |_| false), zk_aluasm! {
+            put     E1, 3;
+            put     E2, 4;
+            add     E2, E1;
+            add     E4, E1;
+            mov     EA, E2;
+            ret;
+        });
+        let keep = |instr: &Instr<LibId>| matches!(instr, Instr::Gfa(FieldInstr::Clr { .. }));
+        assert_eq!(eliminate_dead_code(&code, &live_out, config, keep), zk_aluasm! {
+            put     E1, 3;
+            put     E2, 4;
+            add     E2, E1;
+            add     E4, E1;
+            clr     E5;
+            mov     EA, E2;
+            ret;
+        });
+        let all = RegE::ALL.into_iter().collect();
+        assert_eq!(eliminate_dead_code(&code, &all, config, |_| false), zk_aluasm! {
+            put     E1, 3;
+            put     E2, 4;
+            mov     E3, E2;
+            add     E2, E1;
+            add     E4, E1;
+            clr     E5;
+            mov     EA, E2;
+            ret;
+        });
+    }
+
+    #[test]
+    fn dce_loop() {
+        let code = zk_aluasm! {
+            put     E1, 0;
+            put     E2, 1;
+            put     E3, 5;
+            add     E1, E2;
+            eq      E1, E3;
+            jif     CO, 10;
+            mov     E4, E1;
+            stop;
+        };
+        assert_eq!(eliminate_dead_code(&code, &bset![], GfaConfig::default(), |_| false), zk_aluasm! {
+            put     E1, 0;
+            put     E2, 1;
+            put     E3, 5;
+            add     E1, E2;
+            eq      E1, E3;
+            jif     CO, 10;
+            stop;
+        });
+    }
+
+    #[test]
+    fn dce_non_canonical() {
+        let code = zk_aluasm! {
+            put     E1, 5;
+            put     E2, 2;
+            put     E3, 1;
+            ret;
+        };
+        let config = GfaConfig::new(u256::from(3u8));
+        assert_eq!(eliminate_dead_code(&code, &bset![], config, |_| false), zk_aluasm! {
+            put     E1, 5;
+            ret;
+        });
+    }
+}
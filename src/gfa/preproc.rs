@@ -0,0 +1,645 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Preprocessor of the textual assembly, performing conditional assembly and expanding
+//! user-defined macros.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::iter::Peekable;
+use core::str::FromStr;
+
+use amplify::num::u256;
+
+use super::asm::{is_ident, strip_comment, MNEMONICS};
+use super::{AsmError, AsmErrorKind};
+use crate::{
+    RegE, FIELD_ORDER_25519, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_SECP, FIELD_ORDER_STARK,
+};
+
+/// Maximal depth of nested macro invocations.
+const MAX_DEPTH: usize = 64;
+
+/// Registers used for the macro temporaries, in the order of allocation.
+///
+/// These are local registers, which values are not preserved across routine calls.
+const TEMP_REGS: [RegE; 8] = [RegE::E8, RegE::E7, RegE::E6, RegE::E5, RegE::E4, RegE::E3, RegE::E2, RegE::E1];
+
+/// Configuration of the conditional assembly.
+///
+/// The configuration is a set of options, each of which is either a flag (`name`) or a key-value
+/// pair (`key = "value"`), which can be checked by `.cfg` directives and `#[cfg(...)]` attributes
+/// of the assembly source.
+///
+/// If the configuration is constructed for a specific field, the field order is also available to
+/// the constant expressions as `P`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AsmConfig {
+    options: BTreeSet<(String, Option<String>)>,
+    field_order: Option<u256>,
+}
+
+impl AsmConfig {
+    /// Constructs empty configuration.
+    pub fn new() -> Self { Self::default() }
+
+    /// Constructs configuration for a field, setting `field` option to the name of the field preset
+    /// (`25519`, `secp`, `stark`, `goldilocks` or `babybear`), or to a lowercase hexadecimal order
+    /// prefixed with `0x` for other fields.
+    pub fn with_field(field_order: u256) -> Self {
+        let name = match field_order {
+            FIELD_ORDER_25519 => s!("25519"),
+            FIELD_ORDER_SECP => s!("secp"),
+            FIELD_ORDER_STARK => s!("stark"),
+            FIELD_ORDER_GOLDILOCKS => s!("goldilocks"),
+            FIELD_ORDER_BABYBEAR => s!("babybear"),
+            order => format!("{order:#x}"),
+        };
+        let mut config = Self::new();
+        config.set_value("field", name);
+        config.field_order = Some(field_order);
+        config
+    }
+
+    /// Returns the order of the field the program is assembled for, if known.
+    pub fn field_order(&self) -> Option<u256> { self.field_order }
+
+    /// Sets a flag option.
+    pub fn set_flag(&mut self, name: impl ToString) { self.options.insert((name.to_string(), None)); }
+
+    /// Sets a key-value option. A key may have multiple values.
+    pub fn set_value(&mut self, key: impl ToString, value: impl ToString) {
+        self.options
+            .insert((key.to_string(), Some(value.to_string())));
+    }
+
+    /// Checks whether a flag (if `value` is `None`) or a key-value option is set.
+    pub fn is_set(&self, key: &str, value: Option<&str>) -> bool {
+        self.options
+            .contains(&(key.to_owned(), value.map(str::to_owned)))
+    }
+}
+
+/// Source line after the preprocessing.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct Line {
+    /// Number of the source line the code originates from, starting from 1.
+    pub no: usize,
+    /// Line text, with comments removed.
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Macro {
+    params: Vec<String>,
+    body: Vec<String>,
+}
+
+#[derive(Default)]
+struct Preprocessor {
+    macros: BTreeMap<String, Macro>,
+    expansions: usize,
+    lines: Vec<Line>,
+}
+
+/// Preprocesses the source text, removing comments, skipping code excluded by the configuration
+/// and expanding macros.
+pub(super) fn preprocess(source: &str, config: &AsmConfig) -> Result<Vec<Line>, AsmError> {
+    let mut pp = Preprocessor::default();
+    let mut lines = select(source, config)?.into_iter();
+    while let Some((no, text)) = lines.next() {
+        let err = |kind: AsmErrorKind| AsmError { line: no, kind };
+        if let Some(header) = text
+            .strip_prefix("macro")
+            .filter(|h| h.starts_with(char::is_whitespace))
+        {
+            let (name, params) = parse_header(header).map_err(err)?;
+            let mut body = Vec::new();
+            loop {
+                match lines.next() {
+                    None => return Err(err(AsmErrorKind::UnterminatedMacro(name))),
+                    Some((_, "}")) => break,
+                    Some((_, "")) => {}
+                    Some((_, line)) => body.push(line.to_owned()),
+                }
+            }
+            if pp
+                .macros
+                .insert(name.clone(), Macro { params, body })
+                .is_some()
+            {
+                return Err(err(AsmErrorKind::DuplicateMacro(name)));
+            }
+            continue;
+        }
+        pp.expand(no, text, &BTreeSet::new(), 0).map_err(err)?;
+    }
+    Ok(pp.lines)
+}
+
+/// Removes comments and the code excluded by the conditional assembly directives.
+fn select<'s>(source: &'s str, config: &AsmConfig) -> Result<Vec<(usize, &'s str)>, AsmError> {
+    struct Cond {
+        line: usize,
+        active: bool,
+        alt: bool,
+    }
+
+    let mut conds = Vec::<Cond>::new();
+    let mut lines = Vec::new();
+    for (no, line) in source.lines().enumerate() {
+        let no = no + 1;
+        let err = |kind: AsmErrorKind| AsmError { line: no, kind };
+        let mut text = strip_comment(line).trim();
+        let active = conds.iter().all(|cond| cond.active);
+        if let Some(pred) = text
+            .strip_prefix(".cfg")
+            .filter(|p| p.starts_with(char::is_whitespace))
+        {
+            let active = eval_cfg(pred, config).map_err(err)?;
+            conds.push(Cond {
+                line: no,
+                active,
+                alt: false,
+            });
+            continue;
+        }
+        if text == ".else" {
+            match conds.last_mut() {
+                Some(cond) if !cond.alt => {
+                    cond.active = !cond.active;
+                    cond.alt = true;
+                }
+                _ => return Err(err(AsmErrorKind::UnbalancedCfg)),
+            }
+            continue;
+        }
+        if text == ".endcfg" {
+            conds
+                .pop()
+                .ok_or_else(|| err(AsmErrorKind::UnbalancedCfg))?;
+            continue;
+        }
+        if let Some(attr) = text.strip_prefix("#[cfg(") {
+            let (pred, rest) = attr
+                .split_once(")]")
+                .ok_or_else(|| err(AsmErrorKind::InvalidCfg(text.to_owned())))?;
+            if !eval_cfg(pred, config).map_err(err)? {
+                continue;
+            }
+            text = rest.trim();
+        }
+        if active {
+            lines.push((no, text));
+        }
+    }
+    if let Some(cond) = conds.last() {
+        return Err(AsmError {
+            line: cond.line,
+            kind: AsmErrorKind::UnbalancedCfg,
+        });
+    }
+    Ok(lines)
+}
+
+/// Evaluates a configuration predicate, which follows the syntax of Rust `cfg` attribute:
+/// `name`, `key = "value"`, `not(pred)`, `all(pred, ...)` and `any(pred, ...)`.
+fn eval_cfg(pred: &str, config: &AsmConfig) -> Result<bool, AsmErrorKind> {
+    let invalid = || AsmErrorKind::InvalidCfg(pred.trim().to_owned());
+    let mut tokens = cfg_tokens(pred).ok_or_else(invalid)?.into_iter().peekable();
+    let res = eval_pred(&mut tokens, config).ok_or_else(invalid)?;
+    if tokens.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(res)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum CfgToken<'s> {
+    Ident(&'s str),
+    Str(&'s str),
+    Eq,
+    Open,
+    Close,
+    Comma,
+}
+
+fn cfg_tokens(s: &str) -> Option<Vec<CfgToken<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = match c {
+            '=' => (CfgToken::Eq, 1),
+            '(' => (CfgToken::Open, 1),
+            ')' => (CfgToken::Close, 1),
+            ',' => (CfgToken::Comma, 1),
+            '"' => {
+                let end = rest[1..].find('"')? + 1;
+                (CfgToken::Str(&rest[1..end]), end + 1)
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                if !is_ident(&rest[..end]) {
+                    return None;
+                }
+                (CfgToken::Ident(&rest[..end]), end)
+            }
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Some(tokens)
+}
+
+fn eval_pred<'s>(tokens: &mut Peekable<impl Iterator<Item = CfgToken<'s>>>, config: &AsmConfig) -> Option<bool> {
+    let CfgToken::Ident(name) = tokens.next()? else {
+        return None;
+    };
+    match tokens.peek() {
+        Some(CfgToken::Eq) => {
+            tokens.next();
+            let CfgToken::Str(value) = tokens.next()? else {
+                return None;
+            };
+            Some(config.is_set(name, Some(value)))
+        }
+        Some(CfgToken::Open) if matches!(name, "not" | "all" | "any") => {
+            tokens.next();
+            let mut args = Vec::new();
+            loop {
+                if tokens.peek() == Some(&CfgToken::Close) {
+                    tokens.next();
+                    break;
+                }
+                args.push(eval_pred(tokens, config)?);
+                match tokens.next()? {
+                    CfgToken::Comma => {}
+                    CfgToken::Close => break,
+                    _ => return None,
+                }
+            }
+            match name {
+                "not" if args.len() == 1 => Some(!args[0]),
+                "all" => Some(args.iter().all(|arg| *arg)),
+                "any" => Some(args.iter().any(|arg| *arg)),
+                _ => None,
+            }
+        }
+        _ => Some(config.is_set(name, None)),
+    }
+}
+
+fn parse_header(header: &str) -> Result<(String, Vec<String>), AsmErrorKind> {
+    let invalid = || AsmErrorKind::InvalidMacro(header.trim().to_owned());
+    let (name, rest) = header.split_once('(').ok_or_else(invalid)?;
+    let (params, rest) = rest.split_once(')').ok_or_else(invalid)?;
+    let name = name.trim();
+    if rest.trim() != "{" || !is_ident(name) || MNEMONICS.contains(&name) {
+        return Err(invalid());
+    }
+    let params = params
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    let unique = params.iter().collect::<BTreeSet<_>>();
+    if unique.len() != params.len()
+        || params
+            .iter()
+            .any(|p| !is_ident(p) || RegE::from_str(p).is_ok())
+    {
+        return Err(invalid());
+    }
+    Ok((name.to_owned(), params))
+}
+
+impl Preprocessor {
+    /// Expands a line, if it is a macro invocation, or adds it to the output as is.
+    ///
+    /// `reserved` registers are used by the enclosing macro expansions and can't be allocated for
+    /// the temporaries.
+    fn expand(&mut self, no: usize, text: &str, reserved: &BTreeSet<RegE>, depth: usize) -> Result<(), AsmErrorKind> {
+        let (name, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let Some(mac) = self.macros.get(name).cloned() else {
+            self.lines.push(Line {
+                no,
+                text: text.to_owned(),
+            });
+            return Ok(());
+        };
+        if depth >= MAX_DEPTH {
+            return Err(AsmErrorKind::MacroRecursion(name.to_owned()));
+        }
+        let args = args
+            .split(',')
+            .map(str::trim)
+            .filter(|arg| !arg.is_empty())
+            .collect::<Vec<_>>();
+        if args.len() != mac.params.len() {
+            return Err(AsmErrorKind::MacroArgCount(name.to_owned(), args.len()));
+        }
+
+        self.expansions += 1;
+        let mut subst = mac
+            .params
+            .iter()
+            .cloned()
+            .zip(args.iter().map(|arg| arg.to_string()))
+            .collect::<BTreeMap<_, _>>();
+
+        // Labels defined inside the macro are made unique for each expansion
+        for line in &mac.body {
+            if let Some(label) = line.strip_suffix(':') {
+                let label = label.split_whitespace().last().unwrap_or_default();
+                subst.insert(label.to_owned(), format!("__{name}_{}_{label}", self.expansions));
+            }
+        }
+
+        // Temporaries are allocated from the registers not used by the invocation
+        let mut used = reserved.clone();
+        used.extend(args.iter().flat_map(|arg| regs(arg)));
+        used.extend(mac.body.iter().flat_map(|line| regs(line)));
+        let mut free = TEMP_REGS
+            .into_iter()
+            .filter(|reg| !used.contains(reg))
+            .collect::<Vec<_>>()
+            .into_iter();
+        for line in &mac.body {
+            for temp in tokens(line).filter(|token| token.starts_with('%')) {
+                if subst.contains_key(temp) {
+                    continue;
+                }
+                let reg = free
+                    .next()
+                    .ok_or_else(|| AsmErrorKind::NoTempRegs(name.to_owned()))?;
+                used.insert(reg);
+                subst.insert(temp.to_owned(), reg.to_string());
+            }
+        }
+
+        for line in &mac.body {
+            let line = substitute(line, &subst);
+            self.expand(no, &line, &used, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterates over identifier-like tokens of a line, including temporaries prefixed with `%`.
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '%'))
+        .filter(|token| !token.is_empty())
+}
+
+/// Returns registers mentioned in a line.
+fn regs(line: &str) -> impl Iterator<Item = RegE> + '_ { tokens(line).filter_map(|token| RegE::from_str(token).ok()) }
+
+/// Replaces identifiers in a line according to the substitution map.
+///
+/// Identifiers following a dot (like in `8.bits`) are suffixes and are never replaced.
+fn substitute(line: &str, subst: &BTreeMap<String, String>) -> String {
+    let is_token = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '%';
+    let mut res = String::with_capacity(line.len());
+    let mut rest = line;
+    let mut prev = None;
+    while let Some(c) = rest.chars().next() {
+        if !is_token(c) {
+            res.push(c);
+            rest = &rest[c.len_utf8()..];
+            prev = Some(c);
+            continue;
+        }
+        let end = rest.find(|c: char| !is_token(c)).unwrap_or(rest.len());
+        let token = &rest[..end];
+        match subst.get(token) {
+            Some(val) if prev != Some('.') => res.push_str(val),
+            _ => res.push_str(token),
+        }
+        rest = &rest[end..];
+        prev = None;
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::{Assembly, FieldInstr, Instr};
+
+    fn expand(source: &str) -> Vec<String> { expand_with(source, &AsmConfig::new()) }
+
+    fn expand_with(source: &str, config: &AsmConfig) -> Vec<String> {
+        preprocess(source, config)
+            .unwrap()
+            .into_iter()
+            .map(|line| line.text)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn macros() {
+        let source = r#"
+            macro assert_eq(a, b) {
+                eq      a, b
+                chk     CO
+            }
+            macro square_sum(dst, src) {   ; dst += src^2
+                mov     %sq, src
+                mul     %sq, src
+                add     dst, %sq
+            }
+            assert_eq   E1, E2
+            square_sum  E8, E7
+            square_sum  EA, EB
+        "#;
+        assert_eq!(expand(source), vec![
+            "eq      E1, E2",
+            "chk     CO",
+            "mov     E6, E7",
+            "mul     E6, E7",
+            "add     E8, E6",
+            "mov     E8, EB",
+            "mul     E8, EB",
+            "add     EA, E8",
+        ]);
+        let asm = Assembly::parse(source).unwrap();
+        assert_eq!(asm.code.len(), 8);
+        assert_eq!(asm.source_map.line_at(3), Some(12));
+        assert_eq!(
+            asm.code[2],
+            Instr::<LibId>::from(FieldInstr::Mov {
+                dst: RegE::E6,
+                src: RegE::E7
+            })
+        );
+    }
+
+    #[test]
+    fn hygiene() {
+        let source = r#"
+            macro inc(reg) {
+                put     %one, 1
+                add     reg, %one
+            }
+            macro inc2(reg) {
+                mov     %tmp, reg
+                inc     %tmp
+                inc     %tmp
+                mov     reg, %tmp
+            }
+            macro skip_zero(reg) {
+                test    reg
+                jif     CO, done
+                inc     reg
+            done:
+            }
+            inc2        E1
+            skip_zero   E2
+            skip_zero   E3
+        "#;
+        assert_eq!(expand(source), vec![
+            "mov     E8, E1",
+            "put     E7, 1",
+            "add     E8, E7",
+            "put     E7, 1",
+            "add     E8, E7",
+            "mov     E1, E8",
+            "test    E2",
+            "jif     CO, __skip_zero_4_done",
+            "put     E8, 1",
+            "add     E2, E8",
+            "__skip_zero_4_done:",
+            "test    E3",
+            "jif     CO, __skip_zero_6_done",
+            "put     E8, 1",
+            "add     E3, E8",
+            "__skip_zero_6_done:",
+        ]);
+        let asm = Assembly::parse(source).unwrap();
+        assert_eq!(asm.labels.len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| Assembly::parse(s).unwrap_err();
+        assert_eq!(err("macro m(a) {\nnop"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::UnterminatedMacro(s!("m"))
+        });
+        assert_eq!(err("macro add(a) {\n}"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidMacro(s!("add(a) {"))
+        });
+        assert_eq!(err("macro m(E1) {\n}"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidMacro(s!("m(E1) {"))
+        });
+        assert_eq!(err("macro m(a, a) {\n}"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidMacro(s!("m(a, a) {"))
+        });
+        assert_eq!(err("macro m() {\n}\nmacro m() {\n}"), AsmError {
+            line: 3,
+            kind: AsmErrorKind::DuplicateMacro(s!("m"))
+        });
+        assert_eq!(err("macro m(a) {\nnop\n}\nm E1, E2"), AsmError {
+            line: 4,
+            kind: AsmErrorKind::MacroArgCount(s!("m"), 2)
+        });
+        assert_eq!(err("macro m() {\nm\n}\nm"), AsmError {
+            line: 4,
+            kind: AsmErrorKind::MacroRecursion(s!("m"))
+        });
+        assert_eq!(err("macro m() {\nmov %a, %b\nmov %c, %d\nmov %e, %f\nmov %g, %h\nmov %i, E1\n}\nm"), AsmError {
+            line: 8,
+            kind: AsmErrorKind::NoTempRegs(s!("m"))
+        });
+    }
+
+    #[test]
+    fn cfg() {
+        let source = r#"
+            .cfg field = "secp"
+                put     E1, 1
+            .else
+                put     E1, 2
+                .cfg all(test, not(field = "stark"))
+                    put     E2, 2
+                .endcfg
+            .endcfg
+            #[cfg(any(test, field = "stark"))] put E3, 3
+            #[cfg(test)] put E4, 4
+        "#;
+        assert_eq!(expand_with(source, &AsmConfig::with_field(FIELD_ORDER_SECP)), vec!["put     E1, 1"]);
+        assert_eq!(expand_with(source, &AsmConfig::with_field(FIELD_ORDER_STARK)), vec!["put     E1, 2", "put E3, 3"]);
+        let mut config = AsmConfig::with_field(u256::from(17u8));
+        assert!(config.is_set("field", Some("0x11")));
+        config.set_flag("test");
+        assert_eq!(expand_with(source, &config), vec!["put     E1, 2", "put     E2, 2", "put E3, 3", "put E4, 4"]);
+    }
+
+    #[test]
+    fn cfg_errors() {
+        let err = |s: &str| Assembly::parse(s).unwrap_err();
+        assert_eq!(err(".cfg a\n.else\n.else"), AsmError {
+            line: 3,
+            kind: AsmErrorKind::UnbalancedCfg
+        });
+        assert_eq!(err("nop\n.endcfg"), AsmError {
+            line: 2,
+            kind: AsmErrorKind::UnbalancedCfg
+        });
+        assert_eq!(err(".cfg a\n.cfg b\n.endcfg"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::UnbalancedCfg
+        });
+        assert_eq!(err(".cfg not(a, b)"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidCfg(s!("not(a, b)"))
+        });
+        assert_eq!(err(".cfg a = b"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidCfg(s!("a = b"))
+        });
+        assert_eq!(err(".cfg a b"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidCfg(s!("a b"))
+        });
+        assert_eq!(err("#[cfg(a) nop"), AsmError {
+            line: 1,
+            kind: AsmErrorKind::InvalidCfg(s!("#[cfg(a) nop"))
+        });
+    }
+}
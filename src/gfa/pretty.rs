@@ -0,0 +1,288 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Canonical formatting of zk-AluVM programs.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::{Bytecode, CodeEofError, CtrlInstr};
+use aluvm::{Lib, LibId, SiteId};
+
+use super::Instr;
+
+/// Width of the mnemonic column.
+const MNEMONIC_WIDTH: usize = 8;
+
+/// Syntax of the formatted program.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum AsmStyle {
+    /// Syntax of the textual assembler (see [`super::Assembly`]).
+    #[default]
+    Text,
+
+    /// Syntax of the [`crate::zk_aluasm`] macro, with instructions terminated by `;`.
+    Macro,
+}
+
+/// Formatter producing a stable, column-aligned representation of a program.
+///
+/// The output depends only on the program code and the formatter settings, so it can be used to
+/// produce reviewable and deterministic diffs of generated programs:
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::{AsmFormatter, AsmStyle, Instr};
+/// # use zkaluvm::zk_aluasm;
+/// let code: Vec<Instr<LibId>> = zk_aluasm! {
+///     put E1, 0;
+///     fits EA, 8.bits;
+///     ret;
+/// };
+/// let formatter = AsmFormatter {
+///     style: AsmStyle::Macro,
+///     ..AsmFormatter::new()
+/// };
+/// assert_eq!(
+///     formatter.format(&code),
+///     "    put     E1, 0       ;
+///     fits    EA, 8.bits  ;
+///     ret                 ;
+/// "
+/// );
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AsmFormatter {
+    /// Syntax of the output.
+    pub style: AsmStyle,
+    /// Number of spaces each instruction is indented with.
+    pub indent: usize,
+    /// Whether to replace absolute jump and call targets with labels.
+    ///
+    /// Labels are named after the offset they point to, like `L002A`. Since [`crate::zk_aluasm`]
+    /// doesn't support labels, the option is ignored for the [`AsmStyle::Macro`].
+    pub labels: bool,
+    /// Whether to annotate each instruction with a comment containing its offset in the code
+    /// segment.
+    pub offsets: bool,
+}
+
+impl Default for AsmFormatter {
+    fn default() -> Self {
+        Self {
+            style: AsmStyle::Text,
+            indent: 4,
+            labels: true,
+            offsets: false,
+        }
+    }
+}
+
+impl AsmFormatter {
+    /// Constructs formatter with the default settings, producing textual assembler syntax with
+    /// labels and four-space indentation.
+    pub fn new() -> Self { Self::default() }
+
+    /// Formats the program code.
+    pub fn format<Id: SiteId>(&self, code: &[Instr<Id>]) -> String {
+        let mut offsets = Vec::with_capacity(code.len());
+        let mut pos = 0usize;
+        for instr in code {
+            offsets.push(pos);
+            pos += instr.code_byte_len() as usize;
+        }
+
+        let labels = if self.labels && self.style == AsmStyle::Text {
+            code.iter()
+                .filter_map(|instr| target(instr).map(|(_, pos)| pos))
+                .filter(|pos| offsets.binary_search(&(*pos as usize)).is_ok())
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+
+        let lines = code
+            .iter()
+            .map(|instr| match target(instr) {
+                Some((ops, pos)) if labels.contains(&pos) => {
+                    let mnemonic = instr.to_string();
+                    let mnemonic = mnemonic.split_whitespace().next().unwrap_or_default();
+                    column(mnemonic, &format!("{ops}{}", label(pos)))
+                }
+                _ => canonical(&instr.to_string()),
+            })
+            .collect::<Vec<_>>();
+        let width = lines.iter().map(String::len).max().unwrap_or_default() + 2;
+
+        let mut s = String::new();
+        for (line, offset) in lines.into_iter().zip(offsets) {
+            if labels.contains(&(offset as u16)) {
+                s += &format!("{}:\n", label(offset as u16));
+            }
+            let mut line = format!("{:indent$}{line}", "", indent = self.indent);
+            let width = self.indent + width;
+            match (self.style, self.offsets) {
+                (AsmStyle::Text, false) => {}
+                (AsmStyle::Text, true) => line = format!("{line:width$}; 0x{offset:04X}"),
+                (AsmStyle::Macro, false) => line = format!("{line:width$};"),
+                (AsmStyle::Macro, true) => line = format!("{line:width$}; // 0x{offset:04X}"),
+            }
+            s += &line;
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Formats the code of a library.
+    pub fn format_lib(&self, lib: &Lib) -> Result<String, CodeEofError> {
+        let code = lib.disassemble::<Instr<LibId>>()?;
+        Ok(self.format(&code))
+    }
+}
+
+/// Returns operands preceding a local absolute jump or call target, and the target itself.
+fn target<Id: SiteId>(instr: &Instr<Id>) -> Option<(&'static str, u16)> {
+    match *instr {
+        Instr::Ctrl(CtrlInstr::Jmp { pos }) | Instr::Ctrl(CtrlInstr::Fn { pos }) => Some(("", pos)),
+        Instr::Ctrl(CtrlInstr::JiOvfl { pos }) => Some(("CO, ", pos)),
+        Instr::Ctrl(CtrlInstr::JiFail { pos }) => Some(("CK, ", pos)),
+        _ => None,
+    }
+}
+
+fn label(pos: u16) -> String { format!("L{pos:04X}") }
+
+/// Normalizes whitespace in the instruction representation, aligning the operands into a column.
+fn canonical(instr: &str) -> String {
+    let (mnemonic, ops) = instr
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((instr.trim(), ""));
+    column(mnemonic, ops.trim())
+}
+
+fn column(mnemonic: &str, ops: &str) -> String {
+    if ops.is_empty() {
+        return mnemonic.to_owned();
+    }
+    format!("{mnemonic:<MNEMONIC_WIDTH$}{ops}")
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::gfa::Assembly;
+
+    const SOURCE: &str = r#"
+        routine START:
+            put     E1, 0
+            put     E2, 1
+        again:
+            add     E1,E2
+            fits    E1, 8.bits
+            jif     CO, again
+            call    START
+            jmp     +1
+            ret
+    "#;
+
+    #[test]
+    fn text() {
+        let code = Assembly::parse(SOURCE).unwrap().code;
+        let text = AsmFormatter::new().format(&code);
+        assert_eq!(
+            text,
+            "L0000:
+    nop
+    put     E1, 0
+    put     E2, 1
+L0005:
+    add     E1, E2
+    fits    E1, 8.bits
+    jif     CO, L0005
+    call    L0000
+    jmp     +1
+    ret
+"
+        );
+        assert_eq!(Assembly::parse(&text).unwrap().code, code);
+
+        let formatter = AsmFormatter {
+            labels: false,
+            offsets: true,
+            indent: 2,
+            ..AsmFormatter::new()
+        };
+        let text = formatter.format(&code);
+        assert_eq!(
+            text,
+            "  nop                 ; 0x0000
+  put     E1, 0       ; 0x0001
+  put     E2, 1       ; 0x0003
+  add     E1, E2      ; 0x0005
+  fits    E1, 8.bits  ; 0x0007
+  jif     CO, 5       ; 0x0009
+  call    0           ; 0x000C
+  jmp     +1          ; 0x000F
+  ret                 ; 0x0011
+"
+        );
+        assert_eq!(Assembly::parse(&text).unwrap().code, code);
+    }
+
+    #[test]
+    fn macro_style() {
+        let code = Assembly::parse(SOURCE).unwrap().code;
+        let formatter = AsmFormatter {
+            style: AsmStyle::Macro,
+            offsets: true,
+            ..AsmFormatter::new()
+        };
+        assert_eq!(
+            formatter.format(&code),
+            "    nop                 ; // 0x0000
+    put     E1, 0       ; // 0x0001
+    put     E2, 1       ; // 0x0003
+    add     E1, E2      ; // 0x0005
+    fits    E1, 8.bits  ; // 0x0007
+    jif     CO, 5       ; // 0x0009
+    call    0           ; // 0x000C
+    jmp     +1          ; // 0x000F
+    ret                 ; // 0x0011
+"
+        );
+    }
+
+    #[test]
+    fn lib() {
+        let code = Assembly::parse(SOURCE).unwrap().code;
+        let lib = Lib::assemble(&code).unwrap();
+        let formatter = AsmFormatter::new();
+        assert_eq!(formatter.format_lib(&lib).unwrap(), formatter.format(&code));
+        assert_eq!(formatter.format::<LibId>(&[]), "");
+    }
+}
@@ -0,0 +1,276 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Programs and routines as sequences of instructions.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::{Bytecode, GotoTarget, Instruction};
+use aluvm::SiteId;
+
+use super::analysis::CodeOverflow;
+use super::{AsmFormatter, Instr};
+
+/// Sequence of instructions forming a program.
+///
+/// Unlike a plain vector of instructions, the program keeps absolute local jump and call targets
+/// consistent when other programs are appended to it: the targets of the appended code are rebased
+/// by the offset at which the code is placed. Relative jumps and external calls are not changed.
+///
+/// The program is displayed as a formatted listing, with jump targets replaced by labels and each
+/// instruction annotated with its offset (see [`AsmFormatter`]):
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::Program;
+/// # use zkaluvm::zk_aluasm;
+/// let mut program = Program::<LibId>::from(zk_aluasm! { put E1, 0; ret; });
+/// let routine = Program::from(zk_aluasm! { jmp 0; });
+/// assert_eq!(program.append(routine), Ok(3));
+/// assert_eq!(
+///     program.to_string(),
+///     "    put     E1, 0  ; 0x0000
+///     ret            ; 0x0002
+/// L0003:
+///     jmp     L0003  ; 0x0003
+/// "
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Program<Id: SiteId>(Vec<Instr<Id>>);
+
+/// Program fragment which is appended to other programs.
+pub type Routine<Id> = Program<Id>;
+
+impl<Id: SiteId> Default for Program<Id> {
+    fn default() -> Self { Self(vec![]) }
+}
+
+impl<Id: SiteId> Program<Id> {
+    /// Constructs an empty program.
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the number of instructions in the program.
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Checks whether the program has no instructions.
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns the size of the program code in bytes.
+    pub fn code_len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|instr| instr.code_byte_len() as usize)
+            .sum()
+    }
+
+    /// Returns the program instructions.
+    pub fn as_slice(&self) -> &[Instr<Id>] { &self.0 }
+
+    /// Returns the program instructions, consuming the program.
+    pub fn into_code(self) -> Vec<Instr<Id>> { self.0 }
+
+    /// Iterates over the program instructions together with their offsets in the code segment.
+    pub fn offsets(&self) -> impl Iterator<Item = (usize, &Instr<Id>)> {
+        self.0.iter().scan(0usize, |pos, instr| {
+            let offset = *pos;
+            *pos += instr.code_byte_len() as usize;
+            Some((offset, instr))
+        })
+    }
+
+    /// Adds an instruction to the end of the program, without changing its jump targets.
+    pub fn push(&mut self, instr: impl Into<Instr<Id>>) { self.0.push(instr.into()) }
+
+    /// Shifts all absolute local jump and call targets by `base` bytes, as required to place the
+    /// program at the `base` offset of the code segment.
+    ///
+    /// # Errors
+    ///
+    /// If any of the targets exceeds the maximal size of the code segment. The program is not
+    /// changed in this case.
+    pub fn rebase(&mut self, base: u16) -> Result<(), CodeOverflow> {
+        let mut code = self.0.clone();
+        for instr in &mut code {
+            if let GotoTarget::Absolute(pos) = instr.local_goto_pos() {
+                *pos = pos.checked_add(base).ok_or(CodeOverflow)?;
+            }
+        }
+        self.0 = code;
+        Ok(())
+    }
+
+    /// Appends a routine to the end of the program, rebasing its jump and call targets.
+    ///
+    /// # Returns
+    ///
+    /// Offset of the routine in the program code.
+    ///
+    /// # Errors
+    ///
+    /// If the code of the resulting program exceeds the maximal size of the code segment. The
+    /// program is not changed in this case.
+    pub fn append(&mut self, mut routine: Routine<Id>) -> Result<u16, CodeOverflow> {
+        let base = u16::try_from(self.code_len()).map_err(|_| CodeOverflow)?;
+        if self.code_len() + routine.code_len() > u16::MAX as usize {
+            return Err(CodeOverflow);
+        }
+        routine.rebase(base)?;
+        self.0.extend(routine.0);
+        Ok(base)
+    }
+
+    /// Concatenates two programs, rebasing jump and call targets of the second one (see
+    /// [`Self::append`]).
+    pub fn concat(mut self, other: Program<Id>) -> Result<Self, CodeOverflow> {
+        self.append(other)?;
+        Ok(self)
+    }
+}
+
+impl<Id: SiteId> From<Vec<Instr<Id>>> for Program<Id> {
+    fn from(code: Vec<Instr<Id>>) -> Self { Self(code) }
+}
+
+impl<Id: SiteId> From<Program<Id>> for Vec<Instr<Id>> {
+    fn from(program: Program<Id>) -> Self { program.0 }
+}
+
+impl<Id: SiteId> FromIterator<Instr<Id>> for Program<Id> {
+    fn from_iter<T: IntoIterator<Item = Instr<Id>>>(iter: T) -> Self { Self(iter.into_iter().collect()) }
+}
+
+impl<Id: SiteId> AsRef<[Instr<Id>]> for Program<Id> {
+    fn as_ref(&self) -> &[Instr<Id>] { &self.0 }
+}
+
+impl<Id: SiteId> IntoIterator for Program<Id> {
+    type Item = Instr<Id>;
+    type IntoIter = vec::IntoIter<Instr<Id>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl<'a, Id: SiteId> IntoIterator for &'a Program<Id> {
+    type Item = &'a Instr<Id>;
+    type IntoIter = core::slice::Iter<'a, Instr<Id>>;
+
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+
+impl<Id: SiteId> Display for Program<Id> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let formatter = AsmFormatter {
+            offsets: true,
+            ..AsmFormatter::new()
+        };
+        f.write_str(&formatter.format(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use aluvm::isa::CtrlInstr;
+    use aluvm::{LibId, Site};
+
+    use super::*;
+    use crate::gfa::FieldInstr;
+    use crate::RegE;
+
+    fn routine() -> Routine<LibId> {
+        Program::from(vec![
+            CtrlInstr::Nop.into(),
+            FieldInstr::Test { src: RegE::E1 }.into(),
+            CtrlInstr::JiOvfl { pos: 0 }.into(),
+            CtrlInstr::Sh { shift: -3 }.into(),
+            CtrlInstr::Call {
+                site: Site::new(LibId::default(), 0),
+            }
+            .into(),
+            CtrlInstr::Fn { pos: 1 }.into(),
+            CtrlInstr::Ret.into(),
+        ])
+    }
+
+    #[test]
+    fn append() {
+        let mut program = routine();
+        let len = program.code_len();
+        assert_eq!(program.offsets().map(|(pos, _)| pos).last(), Some(len - 1));
+        assert_eq!(program.append(routine()), Ok(len as u16));
+        assert_eq!(program.len(), 14);
+        assert_eq!(program.code_len(), len * 2);
+
+        let code = program.into_code();
+        assert_eq!(code[..7], routine().into_code()[..]);
+        let base = len as u16;
+        assert_eq!(code[9], CtrlInstr::JiOvfl { pos: base }.into());
+        assert_eq!(code[10], CtrlInstr::Sh { shift: -3 }.into());
+        assert_eq!(
+            code[11],
+            CtrlInstr::Call {
+                site: Site::new(LibId::default(), 0)
+            }
+            .into()
+        );
+        assert_eq!(code[12], CtrlInstr::Fn { pos: base + 1 }.into());
+
+        assert_eq!(routine().concat(routine()).unwrap().into_code(), code);
+    }
+
+    #[test]
+    fn overflow() {
+        let mut program = Program::<LibId>::from(vec![CtrlInstr::Jmp { pos: u16::MAX - 1 }.into()]);
+        assert_eq!(program.rebase(2), Err(CodeOverflow));
+        assert_eq!(program.as_slice(), &[CtrlInstr::Jmp { pos: u16::MAX - 1 }.into()]);
+
+        let big = (0..u16::MAX / 3)
+            .map(|_| CtrlInstr::Jmp { pos: 0 }.into())
+            .collect::<Program<LibId>>();
+        let mut program = big.clone();
+        assert_eq!(program.append(Program::from(vec![CtrlInstr::Jmp { pos: 0 }.into()])), Err(CodeOverflow));
+        assert_eq!(program, big);
+    }
+
+    #[test]
+    fn display() {
+        let program = routine();
+        let listing = program.to_string();
+        assert!(listing.starts_with("L0000:\n    nop"));
+        assert_eq!(listing.lines().filter(|line| line.contains("; 0x")).count(), program.len());
+        assert_eq!(
+            (&program).into_iter().collect::<Vec<_>>(),
+            program
+                .clone()
+                .into_iter()
+                .collect::<Vec<_>>()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Export of GFA256 program semantics to SMT-LIB.
+//!
+//! Straight-line programs are [decompiled](super::decompile) into expression graphs, which are
+//! encoded as SMT-LIB v2 formulas over integers modulo the field order. The formulas can be
+//! checked with any external SMT solver supporting non-linear integer arithmetic (`QF_NIA`).
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use aluvm::SiteId;
+use amplify::num::u256;
+
+use super::{decompile, DecompileError, ExprGraph, ExprNode, Instr};
+use crate::{GfaConfig, RegE};
+
+/// Encodes the graph of a program as an SMT-LIB script.
+///
+/// Each program input becomes a constant declared with the name of the register and constrained
+/// to the field, each node of the graph becomes a function named `{prefix}n{index}`, and the final
+/// value of each register modified by the program is defined as a function named `{prefix}{reg}`.
+/// Cleared registers have no definitions.
+pub fn smtlib(graph: &ExprGraph, config: GfaConfig, prefix: &str) -> String {
+    let mut script = String::new();
+    declare_inputs(&mut script, inputs([graph]), config);
+    define_nodes(&mut script, graph, config, prefix);
+    for (reg, id) in graph.outputs() {
+        if let Some(id) = id {
+            writeln!(script, "(define-fun {prefix}{reg} () Int {prefix}{id})").ok();
+        }
+    }
+    script
+}
+
+/// Produces an SMT-LIB query checking equivalence of two straight-line programs.
+///
+/// The query asserts that for some field values in the input registers the programs produce
+/// different values in at least one of the registers modified by any of them (or that one of them
+/// clears a register which the other doesn't). Thus, the programs are equivalent if and only if
+/// the solver reports the query as unsatisfiable (`unsat`).
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::{equiv, Expr, ExprCompiler};
+/// # use zkaluvm::GfaConfig;
+/// # use zkaluvm::RegE::*;
+/// let mut a = ExprCompiler::<LibId>::new();
+/// a.assign(E1, Expr::mul(EA, EB) + EA);
+/// let mut b = ExprCompiler::<LibId>::new();
+/// b.assign(E1, Expr::mul(Expr::Reg(EB) + 1u8, EA));
+///
+/// let query = equiv(&a.compile().unwrap(), &b.compile().unwrap(), GfaConfig::default()).unwrap();
+/// assert!(query.ends_with("(check-sat)\n"));
+/// ```
+pub fn equiv<Id: SiteId>(a: &[Instr<Id>], b: &[Instr<Id>], config: GfaConfig) -> Result<String, DecompileError> {
+    let a = decompile(a, config)?;
+    let b = decompile(b, config)?;
+
+    let mut script = String::new();
+    script.push_str("(set-logic QF_NIA)\n");
+    declare_inputs(&mut script, inputs([&a, &b]), config);
+    define_nodes(&mut script, &a, config, "a_");
+    define_nodes(&mut script, &b, config, "b_");
+
+    let modified = a
+        .outputs()
+        .chain(b.outputs())
+        .map(|(reg, _)| reg)
+        .collect::<BTreeSet<_>>();
+    let output = |graph: &ExprGraph, prefix: &str, reg: RegE| match graph.outputs().find(|(r, _)| *r == reg) {
+        Some((_, Some(id))) => Some(format!("{prefix}{id}")),
+        Some((_, None)) => None,
+        None => Some(reg.to_string()),
+    };
+    let diffs = modified
+        .into_iter()
+        .filter_map(|reg| match (output(&a, "a_", reg), output(&b, "b_", reg)) {
+            (Some(a), Some(b)) => Some(format!("(distinct {a} {b})")),
+            (None, None) => None,
+            _ => Some(s!("true")),
+        })
+        .collect::<Vec<_>>();
+    match diffs.len() {
+        0 => script.push_str("(assert false)\n"),
+        1 => writeln!(script, "(assert {})", diffs[0]).unwrap(),
+        _ => writeln!(script, "(assert (or {}))", diffs.join(" ")).unwrap(),
+    }
+    script.push_str("(check-sat)\n");
+    Ok(script)
+}
+
+fn inputs<'g>(graphs: impl IntoIterator<Item = &'g ExprGraph>) -> BTreeSet<RegE> {
+    graphs
+        .into_iter()
+        .flat_map(|graph| {
+            graph.nodes().iter().filter_map(|node| match node {
+                ExprNode::Input(reg) => Some(*reg),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+fn declare_inputs(script: &mut String, inputs: BTreeSet<RegE>, config: GfaConfig) {
+    let order = numeral(config.field_order);
+    for reg in inputs {
+        writeln!(script, "(declare-const {reg} Int)").ok();
+        writeln!(script, "(assert (and (<= 0 {reg}) (< {reg} {order})))").ok();
+    }
+}
+
+fn define_nodes(script: &mut String, graph: &ExprGraph, config: GfaConfig, prefix: &str) {
+    let order = numeral(config.field_order);
+    for (no, node) in graph.nodes().iter().enumerate() {
+        let term = match node {
+            ExprNode::Input(reg) => reg.to_string(),
+            ExprNode::Const(val) => numeral(val.to_u256()),
+            ExprNode::Add(a, b) => format!("(mod (+ {prefix}{a} {prefix}{b}) {order})"),
+            ExprNode::Mul(a, b) => format!("(mod (* {prefix}{a} {prefix}{b}) {order})"),
+            ExprNode::Neg(a) => format!("(mod (- {prefix}{a}) {order})"),
+        };
+        writeln!(script, "(define-fun {prefix}n{no} () Int {term})").ok();
+    }
+}
+
+/// Formats a number as an SMT-LIB decimal numeral.
+fn numeral(mut val: u256) -> String {
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+    let mut chunks = Vec::new();
+    while val >= u256::from(CHUNK) {
+        chunks.push((val % u256::from(CHUNK)).low_u64());
+        val /= u256::from(CHUNK);
+    }
+    let mut s = val.low_u64().to_string();
+    for chunk in chunks.into_iter().rev() {
+        write!(s, "{chunk:019}").ok();
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::Assembly;
+
+    fn parse(source: &str) -> Vec<Instr<LibId>> { Assembly::parse(source).unwrap().code }
+
+    #[test]
+    fn numerals() {
+        assert_eq!(numeral(u256::ZERO), "0");
+        assert_eq!(numeral(u256::from(10_000_000_000_000_000_000u64)), "10000000000000000000");
+        assert_eq!(numeral(u256::from(10_000_000_000_000_000_001u64)), "10000000000000000001");
+        assert_eq!(
+            numeral(GfaConfig::default().field_order),
+            "65133050195990359925758679067386948167464366374422817272194891004451135422444"
+        );
+    }
+
+    #[test]
+    fn script() {
+        let config = GfaConfig::new(u256::from(101u64));
+        let graph = decompile(&parse("put E1, 5\nmul E1, EA\nneg E2, E1\nclr E3"), config).unwrap();
+        assert_eq!(
+            smtlib(&graph, config, ""),
+            "\
+(declare-const EA Int)
+(assert (and (<= 0 EA) (< EA 101)))
+(define-fun n0 () Int 5)
+(define-fun n1 () Int EA)
+(define-fun n2 () Int (mod (* n0 n1) 101))
+(define-fun n3 () Int (mod (- n2) 101))
+(define-fun E1 () Int n2)
+(define-fun E2 () Int n3)
+"
+        );
+    }
+
+    #[test]
+    fn equivalence() {
+        let config = GfaConfig::new(u256::from(101u64));
+        let a = parse("mov E1, EA\nadd E1, EB");
+        let b = parse("mov E1, EB\nadd E1, EA\nmov EA, EA");
+        assert_eq!(
+            equiv(&a, &b, config).unwrap(),
+            "\
+(set-logic QF_NIA)
+(declare-const EA Int)
+(assert (and (<= 0 EA) (< EA 101)))
+(declare-const EB Int)
+(assert (and (<= 0 EB) (< EB 101)))
+(define-fun a_n0 () Int EA)
+(define-fun a_n1 () Int EB)
+(define-fun a_n2 () Int (mod (+ a_n0 a_n1) 101))
+(define-fun b_n0 () Int EB)
+(define-fun b_n1 () Int EA)
+(define-fun b_n2 () Int (mod (+ b_n0 b_n1) 101))
+(assert (or (distinct a_n2 b_n2) (distinct EA b_n1)))
+(check-sat)
+"
+        );
+
+        let query = equiv(&parse("clr E1"), &parse("put E1, 0"), config).unwrap();
+        assert!(query.ends_with("(assert true)\n(check-sat)\n"));
+        let query = equiv(&parse("clr E1"), &parse("clr E1\nclr E1"), config).unwrap();
+        assert!(query.ends_with("(assert false)\n(check-sat)\n"));
+        assert_eq!(equiv(&parse("stop"), &a, config).unwrap_err(), DecompileError::NotStraightLine(0));
+    }
+}
@@ -0,0 +1,147 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Machine-readable specification of the GFA256 ISA extension.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{Bits, ConstVal, FieldInstr, InstrInfo, ISA_GFA256};
+use crate::RegE;
+
+/// Named value of an instruction operand, together with its encoding.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct OperandValue {
+    /// Value as it is written in the assembly.
+    pub name: String,
+    /// Bits encoding the value in the instruction bytecode.
+    pub code: u8,
+}
+
+/// Machine-readable specification of the GFA256 ISA extension: the instructions with their opcodes,
+/// bytecode encoding, operands, semantics and complexity, and the encoding of the operand values.
+///
+/// The specification is versioned with the crate and is intended for keeping third-party
+/// assemblers, disassemblers and audit tools written in other languages in sync with the ISA. With
+/// the `serde` feature, it can be serialized into JSON, YAML or any other format supported by
+/// `serde`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct IsaSpec {
+    /// Name of the ISA extension.
+    pub isa: &'static str,
+    /// Version of the crate implementing the ISA extension.
+    pub version: &'static str,
+    /// Field element registers.
+    pub registers: Vec<OperandValue>,
+    /// Predefined constants of [`FieldInstr::PutV`] instruction.
+    pub constants: Vec<OperandValue>,
+    /// Bit dimensions of [`FieldInstr::Fits`] instruction.
+    pub bits: Vec<OperandValue>,
+    /// Instructions of the ISA extension.
+    pub instructions: &'static [InstrInfo],
+}
+
+impl IsaSpec {
+    /// Produces specification of the ISA extension implemented by this version of the crate.
+    pub fn current() -> Self {
+        IsaSpec {
+            isa: ISA_GFA256,
+            version: env!("CARGO_PKG_VERSION"),
+            registers: values(
+                RegE::ALL
+                    .into_iter()
+                    .map(|reg| (reg.to_string(), reg.to_u4().to_u8())),
+            ),
+            constants: values(
+                [ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max, ConstVal::ValFeMAX]
+                    .into_iter()
+                    .map(|val| (val.to_string(), val.to_u2().to_u8())),
+            ),
+            bits: values((0u8..8).map(|code| {
+                let bits = Bits::try_from(code).expect("all 3-bit values are valid bit dimensions");
+                (bits.to_string(), code)
+            })),
+            instructions: FieldInstr::INFO,
+        }
+    }
+
+    /// Returns specification of the instruction with the given opcode, if any.
+    pub fn instruction(&self, opcode: u8) -> Option<&'static InstrInfo> {
+        self.instructions.iter().find(|info| info.opcode == opcode)
+    }
+}
+
+fn values(iter: impl IntoIterator<Item = (String, u8)>) -> Vec<OperandValue> {
+    iter.into_iter()
+        .map(|(name, code)| OperandValue { name, code })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+
+    #[test]
+    fn current() {
+        let spec = IsaSpec::current();
+        assert_eq!(spec.isa, "GFA256");
+        assert_eq!(spec.registers.len(), 16);
+        assert_eq!(spec.registers[8], OperandValue {
+            name: s!("EA"),
+            code: 8
+        });
+        assert_eq!(spec.constants[3], OperandValue {
+            name: s!("-1#fe"),
+            code: 3
+        });
+        assert_eq!(spec.bits[7], OperandValue {
+            name: s!("128.bits"),
+            code: 7
+        });
+        assert_eq!(spec.instructions.len(), FieldInstr::INFO.len());
+        assert_eq!(spec.instruction(FieldInstr::MUL).unwrap().mnemonic, "mul");
+        assert_eq!(spec.instruction(0xFF), None);
+        assert!(spec
+            .instructions
+            .iter()
+            .all(|info| !info.summary.is_empty()));
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn json() {
+        let json = serde_json::to_value(IsaSpec::current()).unwrap();
+        assert_eq!(json["isa"], "GFA256");
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        let fits = &json["instructions"][5];
+        assert_eq!(fits["mnemonic"], "fits");
+        assert_eq!(fits["operands"][1]["kind"], "bits");
+        assert_eq!(fits["encoding"][0]["operand"]["bits"], 3);
+        assert_eq!(fits["ck"], "failUninit");
+        assert_eq!(fits["complexityClass"], "double");
+        assert_eq!(fits["proofCost"]["plonkRowsPerBit"], 1);
+    }
+}
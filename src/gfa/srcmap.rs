@@ -0,0 +1,179 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Source maps linking program bytecode to the assembly source text.
+
+use aluvm::isa::Bytecode;
+use aluvm::SiteId;
+use amplify::confinement::{self, SmallVec};
+
+use super::Instr;
+use crate::LIB_NAME_FINITE_FIELD;
+
+/// Location of an instruction in the program bytecode and the assembly source.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Display)]
+#[display("{pos:04X}: line {line}")]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SourceLoc {
+    /// Offset of the instruction in the code segment.
+    pub pos: u16,
+    /// Source line number, starting from 1.
+    pub line: u32,
+}
+
+/// Source map of a program, providing the location of each of the program instructions both in
+/// the code segment and in the assembly source text.
+///
+/// The source map is produced by [`super::Assembly::parse`], and can be serialized alongside the
+/// library compiled from the program, such that debugging and tracing tools can report execution
+/// failures in terms of the original program text.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct SourceMap(SmallVec<SourceLoc>);
+
+impl SourceMap {
+    /// Constructs an empty source map.
+    pub fn new() -> Self { Self::default() }
+
+    /// Constructs a source map for the program code, given the source line of each of the
+    /// instructions.
+    ///
+    /// # Panics
+    ///
+    /// If the number of lines doesn't match the number of instructions.
+    pub fn with<Id: SiteId>(
+        code: &[Instr<Id>],
+        lines: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, confinement::Error> {
+        let mut map = Self::new();
+        let mut lines = lines.into_iter();
+        let mut pos = Some(0u16);
+        for instr in code {
+            let line = lines
+                .next()
+                .expect("source lines must match program instructions");
+            let Some(start) = pos else {
+                return Err(confinement::Error::Oversize {
+                    len: code.len(),
+                    max_len: u16::MAX as usize,
+                });
+            };
+            map.push(SourceLoc { pos: start, line })?;
+            pos = start.checked_add(instr.code_byte_len());
+        }
+        assert!(lines.next().is_none(), "source lines must match program instructions");
+        Ok(map)
+    }
+
+    /// Adds location of the next instruction.
+    ///
+    /// # Panics
+    ///
+    /// If the instruction offset is not greater than the offset of the previously added one.
+    pub fn push(&mut self, loc: SourceLoc) -> Result<(), confinement::Error> {
+        if let Some(last) = self.0.last() {
+            assert!(last.pos < loc.pos, "instruction offsets in a source map must increase");
+        }
+        self.0.push(loc)
+    }
+
+    /// Returns the number of instructions in the source map.
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether the source map is empty.
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns the location of the instruction with a given index.
+    pub fn get(&self, no: usize) -> Option<SourceLoc> { self.0.get(no).copied() }
+
+    /// Finds the index and location of the instruction covering a given offset in the code
+    /// segment.
+    ///
+    /// The offset may point into the middle of the instruction, which allows reporting failures
+    /// for a VM cursor positioned after an executed instruction.
+    pub fn locate(&self, pos: u16) -> Option<(usize, SourceLoc)> {
+        let no = match self.0.binary_search_by_key(&pos, |loc| loc.pos) {
+            Ok(no) => no,
+            Err(0) => return None,
+            Err(no) => no - 1,
+        };
+        Some((no, self.0[no]))
+    }
+
+    /// Returns the source line of the instruction covering a given offset in the code segment.
+    pub fn line_at(&self, pos: u16) -> Option<u32> { self.locate(pos).map(|(_, loc)| loc.line) }
+
+    /// Returns an iterator over the locations of the program instructions.
+    pub fn iter(&self) -> impl Iterator<Item = SourceLoc> + '_ { self.0.iter().copied() }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::gfa::Assembly;
+
+    #[test]
+    fn assembly() {
+        let asm = Assembly::parse(
+            r#"
+            routine START:
+                put     E1, 0
+                put     E2, 1
+
+            again:
+                add     E1, E2
+                jif     CO, again
+                ret
+            "#,
+        )
+        .unwrap();
+        let map = &asm.source_map;
+        assert_eq!(map.len(), asm.code.len());
+        assert_eq!(map.iter().map(|loc| loc.line).collect::<Vec<_>>(), vec![2, 3, 4, 7, 8, 9]);
+        assert_eq!(map.get(3), Some(SourceLoc { pos: 5, line: 7 }));
+        assert_eq!(map.get(6), None);
+        assert_eq!(map.locate(5), Some((3, SourceLoc { pos: 5, line: 7 })));
+        assert_eq!(map.locate(6), Some((3, SourceLoc { pos: 5, line: 7 })));
+        assert_eq!(map.line_at(7), Some(8));
+        assert_eq!(map.line_at(0), Some(2));
+        assert_eq!(SourceMap::new().line_at(0), None);
+        assert_eq!(SourceMap::with(&asm.code, map.iter().map(|loc| loc.line)).unwrap(), *map);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let asm = Assembly::parse("put E1, 0\n\nadd E1, E1").unwrap();
+        let data = bincode::serialize(&asm.source_map).unwrap();
+        let map: SourceMap = bincode::deserialize(&data).unwrap();
+        assert_eq!(map, asm.source_map);
+    }
+}
@@ -0,0 +1,404 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Strict encoding of the instruction set.
+//!
+//! The instruction types and their operands already provide conversions from `u8` with their own
+//! error types, and the control flow instructions are defined in the AluVM crate, so the strict
+//! types are defined by the private mirror types, to which the public types convert.
+
+use alloc::format;
+use alloc::string::ToString;
+
+use aluvm::isa::{Bytecode, CtrlInstr};
+use aluvm::{LibId, LibSite, LibsSeg, Marshaller, Site};
+use strict_encoding::{
+    DecodeError, StrictDecode, StrictDeserialize, StrictDumb, StrictEncode, StrictSerialize, StrictType, TypeName,
+    TypedRead, TypedWrite,
+};
+
+use super::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{fe256, RegE, LIB_NAME_FINITE_FIELD};
+
+macro_rules! strict_mirror {
+    ($ty:ty => $repr:ty, $name:literal) => {
+        impl StrictType for $ty {
+            const STRICT_LIB_NAME: &'static str = LIB_NAME_FINITE_FIELD;
+            fn strict_name() -> Option<TypeName> { Some(tn!($name)) }
+        }
+
+        impl StrictDumb for $ty {
+            fn strict_dumb() -> Self {
+                <$repr>::strict_dumb()
+                    .try_into()
+                    .expect("invalid dumb value")
+            }
+        }
+
+        impl StrictEncode for $ty {
+            fn strict_encode<W: TypedWrite>(&self, writer: W) -> std::io::Result<W> {
+                <$repr>::from(*self).strict_encode(writer)
+            }
+        }
+
+        impl StrictDecode for $ty {
+            fn strict_decode(reader: &mut impl TypedRead) -> Result<Self, DecodeError> {
+                <$repr>::strict_decode(reader)?
+                    .try_into()
+                    .map_err(|err: DecodeError| err)
+            }
+        }
+    };
+}
+
+strict_mirror!(RegE => RegRepr, "RegE");
+strict_mirror!(ConstVal => ConstValRepr, "ConstVal");
+strict_mirror!(Bits => BitsRepr, "Bits");
+strict_mirror!(FieldInstr => FieldInstrRepr, "FieldInstr");
+strict_mirror!(Instr<LibId> => InstrRepr, "Instr");
+
+impl StrictSerialize for FieldInstr {}
+impl StrictDeserialize for FieldInstr {}
+
+impl StrictSerialize for Instr<LibId> {}
+impl StrictDeserialize for Instr<LibId> {}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, rename = "RegE", tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+enum RegRepr {
+    #[default]
+    E1 = 0,
+    E2 = 1,
+    E3 = 2,
+    E4 = 3,
+    E5 = 4,
+    E6 = 5,
+    E7 = 6,
+    E8 = 7,
+    EA = 8,
+    EB = 9,
+    EC = 10,
+    ED = 11,
+    EE = 12,
+    EF = 13,
+    EG = 14,
+    EH = 15,
+}
+
+impl From<RegE> for RegRepr {
+    fn from(reg: RegE) -> Self { RegRepr::try_from(reg as u8).expect("register index is always below 16") }
+}
+
+impl TryFrom<RegRepr> for RegE {
+    type Error = DecodeError;
+    fn try_from(reg: RegRepr) -> Result<Self, Self::Error> {
+        RegE::try_from(u8::from(reg)).map_err(|err| DecodeError::DataIntegrityError(err.to_string()))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, rename = "ConstVal", tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+enum ConstValRepr {
+    #[default]
+    Val1 = 0,
+    ValU64Max = 1,
+    ValU128Max = 2,
+    ValFeMax = 3,
+}
+
+impl From<ConstVal> for ConstValRepr {
+    fn from(val: ConstVal) -> Self { ConstValRepr::try_from(val as u8).expect("constant index is always below 4") }
+}
+
+impl TryFrom<ConstValRepr> for ConstVal {
+    type Error = DecodeError;
+    fn try_from(val: ConstValRepr) -> Result<Self, Self::Error> {
+        ConstVal::try_from(u8::from(val)).map_err(|err| DecodeError::DataIntegrityError(err.to_string()))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, rename = "Bits", tags = repr, into_u8, try_from_u8)]
+#[repr(u8)]
+enum BitsRepr {
+    #[default]
+    Bits8 = 0,
+    Bits16 = 1,
+    Bits24 = 2,
+    Bits32 = 3,
+    Bits48 = 4,
+    Bits64 = 5,
+    Bits96 = 6,
+    Bits128 = 7,
+}
+
+impl From<Bits> for BitsRepr {
+    fn from(bits: Bits) -> Self { BitsRepr::try_from(bits as u8).expect("bit dimension index is always below 8") }
+}
+
+impl TryFrom<BitsRepr> for Bits {
+    type Error = DecodeError;
+    fn try_from(bits: BitsRepr) -> Result<Self, Self::Error> {
+        Bits::try_from(u8::from(bits)).map_err(|err| DecodeError::DataIntegrityError(err.to_string()))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, rename = "FieldInstr", tags = order)]
+enum FieldInstrRepr {
+    Test { src: RegE },
+    Clr { dst: RegE },
+    PutD { dst: RegE, data: fe256 },
+    PutZ { dst: RegE },
+    PutV { dst: RegE, val: ConstVal },
+    Fits { src: RegE, bits: Bits },
+    Mov { dst: RegE, src: RegE },
+    Eq { src1: RegE, src2: RegE },
+    Neg { dst: RegE, src: RegE },
+    Add { dst_src: RegE, src: RegE },
+    Mul { dst_src: RegE, src: RegE },
+    Ecall { id: u8 },
+    Inv { dst: RegE, src: RegE },
+    Exp { dst_src: RegE, exp: RegE },
+    ExpI { dst_src: RegE, exp: u8 },
+    Sqr { dst: RegE, src: RegE },
+    Sqrt { dst: RegE, src: RegE },
+    CMov { dst: RegE, src: RegE },
+    Sel { dst: RegE, src_true: RegE, src_false: RegE },
+    Lt { src1: RegE, src2: RegE },
+    Rng { src: RegE, bound: fe256 },
+}
+
+impl StrictDumb for FieldInstrRepr {
+    fn strict_dumb() -> Self {
+        FieldInstrRepr::Test {
+            src: RegE::strict_dumb(),
+        }
+    }
+}
+
+impl From<FieldInstr> for FieldInstrRepr {
+    fn from(instr: FieldInstr) -> Self {
+        match instr {
+            FieldInstr::Test { src } => FieldInstrRepr::Test { src },
+            FieldInstr::Clr { dst } => FieldInstrRepr::Clr { dst },
+            FieldInstr::PutD { dst, data } => FieldInstrRepr::PutD { dst, data },
+            FieldInstr::PutZ { dst } => FieldInstrRepr::PutZ { dst },
+            FieldInstr::PutV { dst, val } => FieldInstrRepr::PutV { dst, val },
+            FieldInstr::Fits { src, bits } => FieldInstrRepr::Fits { src, bits },
+            FieldInstr::Mov { dst, src } => FieldInstrRepr::Mov { dst, src },
+            FieldInstr::Eq { src1, src2 } => FieldInstrRepr::Eq { src1, src2 },
+            FieldInstr::Neg { dst, src } => FieldInstrRepr::Neg { dst, src },
+            FieldInstr::Add { dst_src, src } => FieldInstrRepr::Add { dst_src, src },
+            FieldInstr::Mul { dst_src, src } => FieldInstrRepr::Mul { dst_src, src },
+            FieldInstr::Ecall { id } => FieldInstrRepr::Ecall { id },
+            FieldInstr::Inv { dst, src } => FieldInstrRepr::Inv { dst, src },
+            FieldInstr::Exp { dst_src, exp } => FieldInstrRepr::Exp { dst_src, exp },
+            FieldInstr::ExpI { dst_src, exp } => FieldInstrRepr::ExpI { dst_src, exp },
+            FieldInstr::Sqr { dst, src } => FieldInstrRepr::Sqr { dst, src },
+            FieldInstr::Sqrt { dst, src } => FieldInstrRepr::Sqrt { dst, src },
+            FieldInstr::CMov { dst, src } => FieldInstrRepr::CMov { dst, src },
+            FieldInstr::Sel { dst, src_true, src_false } => FieldInstrRepr::Sel { dst, src_true, src_false },
+            FieldInstr::Lt { src1, src2 } => FieldInstrRepr::Lt { src1, src2 },
+            FieldInstr::Rng { src, bound } => FieldInstrRepr::Rng { src, bound },
+        }
+    }
+}
+
+impl TryFrom<FieldInstrRepr> for FieldInstr {
+    type Error = DecodeError;
+    fn try_from(instr: FieldInstrRepr) -> Result<Self, Self::Error> {
+        Ok(match instr {
+            FieldInstrRepr::Test { src } => FieldInstr::Test { src },
+            FieldInstrRepr::Clr { dst } => FieldInstr::Clr { dst },
+            FieldInstrRepr::PutD { dst, data } => FieldInstr::PutD { dst, data },
+            FieldInstrRepr::PutZ { dst } => FieldInstr::PutZ { dst },
+            FieldInstrRepr::PutV { dst, val } => FieldInstr::PutV { dst, val },
+            FieldInstrRepr::Fits { src, bits } => FieldInstr::Fits { src, bits },
+            FieldInstrRepr::Mov { dst, src } => FieldInstr::Mov { dst, src },
+            FieldInstrRepr::Eq { src1, src2 } => FieldInstr::Eq { src1, src2 },
+            FieldInstrRepr::Neg { dst, src } => FieldInstr::Neg { dst, src },
+            FieldInstrRepr::Add { dst_src, src } => FieldInstr::Add { dst_src, src },
+            FieldInstrRepr::Mul { dst_src, src } => FieldInstr::Mul { dst_src, src },
+            FieldInstrRepr::Ecall { id } => FieldInstr::Ecall { id },
+            FieldInstrRepr::Inv { dst, src } => FieldInstr::Inv { dst, src },
+            FieldInstrRepr::Exp { dst_src, exp } => FieldInstr::Exp { dst_src, exp },
+            FieldInstrRepr::ExpI { dst_src, exp } => FieldInstr::ExpI { dst_src, exp },
+            FieldInstrRepr::Sqr { dst, src } => FieldInstr::Sqr { dst, src },
+            FieldInstrRepr::Sqrt { dst, src } => FieldInstr::Sqrt { dst, src },
+            FieldInstrRepr::CMov { dst, src } => FieldInstr::CMov { dst, src },
+            FieldInstrRepr::Sel { dst, src_true, src_false } => FieldInstr::Sel { dst, src_true, src_false },
+            FieldInstrRepr::Lt { src1, src2 } => FieldInstr::Lt { src1, src2 },
+            FieldInstrRepr::Rng { src, bound } => FieldInstr::Rng { src, bound },
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_FINITE_FIELD, rename = "Instr", tags = order, dumb = InstrRepr::Nop)]
+enum InstrRepr {
+    Nop,
+    ChkCo,
+    ChkCk,
+    NotCo,
+    FailCk,
+    RsetCk,
+    Jmp { pos: u16 },
+    JiOvfl { pos: u16 },
+    JiFail { pos: u16 },
+    Sh { shift: i8 },
+    ShOvfl { shift: i8 },
+    ShFail { shift: i8 },
+    Exec { site: LibSite },
+    Fn { pos: u16 },
+    Call { site: LibSite },
+    Ret,
+    Stop,
+    Gfa(FieldInstr),
+    Reserved(u8),
+}
+
+impl From<Instr<LibId>> for InstrRepr {
+    fn from(instr: Instr<LibId>) -> Self {
+        match instr {
+            Instr::Ctrl(ctrl) => match ctrl {
+                CtrlInstr::Nop => InstrRepr::Nop,
+                CtrlInstr::ChkCo => InstrRepr::ChkCo,
+                CtrlInstr::ChkCk => InstrRepr::ChkCk,
+                CtrlInstr::NotCo => InstrRepr::NotCo,
+                CtrlInstr::FailCk => InstrRepr::FailCk,
+                CtrlInstr::RsetCk => InstrRepr::RsetCk,
+                CtrlInstr::Jmp { pos } => InstrRepr::Jmp { pos },
+                CtrlInstr::JiOvfl { pos } => InstrRepr::JiOvfl { pos },
+                CtrlInstr::JiFail { pos } => InstrRepr::JiFail { pos },
+                CtrlInstr::Sh { shift } => InstrRepr::Sh { shift },
+                CtrlInstr::ShOvfl { shift } => InstrRepr::ShOvfl { shift },
+                CtrlInstr::ShFail { shift } => InstrRepr::ShFail { shift },
+                CtrlInstr::Exec { site } => InstrRepr::Exec { site: site.into() },
+                CtrlInstr::Fn { pos } => InstrRepr::Fn { pos },
+                CtrlInstr::Call { site } => InstrRepr::Call { site: site.into() },
+                CtrlInstr::Ret => InstrRepr::Ret,
+                CtrlInstr::Stop => InstrRepr::Stop,
+            },
+            Instr::Gfa(instr) => InstrRepr::Gfa(instr),
+            Instr::Reserved(instr) => InstrRepr::Reserved(Bytecode::<LibId>::opcode_byte(&instr)),
+        }
+    }
+}
+
+impl TryFrom<InstrRepr> for Instr<LibId> {
+    type Error = DecodeError;
+    fn try_from(instr: InstrRepr) -> Result<Self, Self::Error> {
+        let site = |site: LibSite| Site::new(site.lib_id, site.offset);
+        Ok(match instr {
+            InstrRepr::Nop => CtrlInstr::Nop.into(),
+            InstrRepr::ChkCo => CtrlInstr::ChkCo.into(),
+            InstrRepr::ChkCk => CtrlInstr::ChkCk.into(),
+            InstrRepr::NotCo => CtrlInstr::NotCo.into(),
+            InstrRepr::FailCk => CtrlInstr::FailCk.into(),
+            InstrRepr::RsetCk => CtrlInstr::RsetCk.into(),
+            InstrRepr::Jmp { pos } => CtrlInstr::Jmp { pos }.into(),
+            InstrRepr::JiOvfl { pos } => CtrlInstr::JiOvfl { pos }.into(),
+            InstrRepr::JiFail { pos } => CtrlInstr::JiFail { pos }.into(),
+            InstrRepr::Sh { shift } => CtrlInstr::Sh { shift }.into(),
+            InstrRepr::ShOvfl { shift } => CtrlInstr::ShOvfl { shift }.into(),
+            InstrRepr::ShFail { shift } => CtrlInstr::ShFail { shift }.into(),
+            InstrRepr::Exec { site: s } => CtrlInstr::Exec { site: site(s) }.into(),
+            InstrRepr::Fn { pos } => CtrlInstr::Fn { pos }.into(),
+            InstrRepr::Call { site: s } => CtrlInstr::Call { site: site(s) }.into(),
+            InstrRepr::Ret => CtrlInstr::Ret.into(),
+            InstrRepr::Stop => CtrlInstr::Stop.into(),
+            InstrRepr::Gfa(instr) => instr.into(),
+            InstrRepr::Reserved(opcode) => {
+                // Reserved instructions have no operands, so they are decoded from the opcode alone.
+                let libs = LibsSeg::default();
+                let mut reader = Marshaller::with([0u8; 0], [0u8; 0], &libs);
+                match Instr::<LibId>::decode_operands(&mut reader, opcode) {
+                    Ok(instr @ Instr::Reserved(_)) => instr,
+                    _ => {
+                        return Err(DecodeError::DataIntegrityError(format!(
+                            "opcode {opcode:#04X} is not a reserved instruction opcode"
+                        )))
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::isa::ReservedInstr;
+    use amplify::confinement::Confined;
+    use strict_encoding::DeserializeError;
+
+    use super::*;
+    use crate::testing::{random_code, TestRng};
+
+    #[test]
+    fn roundtrip() {
+        let mut rng = TestRng::with(0x4469);
+        for instr in random_code::<Instr<LibId>>(&mut rng, 500) {
+            let data = instr.to_strict_serialized::<64>().unwrap();
+            assert_eq!(Instr::<LibId>::from_strict_serialized::<64>(data).unwrap(), instr);
+        }
+        let instr = Instr::<LibId>::Reserved(ReservedInstr::default());
+        let data = instr.to_strict_serialized::<64>().unwrap();
+        assert_eq!(data.as_slice(), &[18, 0xFF]);
+        assert_eq!(Instr::<LibId>::from_strict_serialized::<64>(data).unwrap(), instr);
+    }
+
+    #[test]
+    fn encoding() {
+        let instr = FieldInstr::Fits {
+            src: RegE::EB,
+            bits: Bits::Bits64,
+        };
+        assert_eq!(instr.to_strict_serialized::<64>().unwrap().as_slice(), &[5, 9, 5]);
+        let instr = Instr::<LibId>::from(FieldInstr::put(RegE::E1, fe256::from(5u8)));
+        let data = instr.to_strict_serialized::<64>().unwrap();
+        assert_eq!(data.len(), 3 + 32);
+        assert_eq!(&data[..3], &[17, 2, 0]);
+        assert_eq!(data[3], 5);
+        let instr = Instr::<LibId>::from(CtrlInstr::Jmp { pos: 0x1234 });
+        assert_eq!(instr.to_strict_serialized::<64>().unwrap().as_slice(), &[6, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn invalid() {
+        let err = |data: &[u8]| {
+            Instr::<LibId>::from_strict_serialized::<64>(Confined::from_iter_checked(data.iter().copied())).unwrap_err()
+        };
+        assert!(matches!(err(&[19]), DeserializeError::Decode(DecodeError::UnionTagNotKnown(..))));
+        assert!(matches!(err(&[17, 0, 16]), DeserializeError::Decode(DecodeError::EnumTagNotKnown(..))));
+        assert!(matches!(err(&[18, 0]), DeserializeError::Decode(DecodeError::DataIntegrityError(_))));
+    }
+}
@@ -0,0 +1,925 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Symbolic execution of GFA256 code.
+//!
+//! Registers hold symbolic values: expressions over the register values at the start of the
+//! program, represented by the nodes of a shared [`ExprGraph`]. Each conditional jump and `chk CO`
+//! depending on the outcome of a comparison forks the execution, and each of the resulting paths
+//! records the outcome as a path condition. The resulting paths can be used to extract the
+//! constraints imposed by a program or to prove simple properties of it.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::CtrlInstr;
+use aluvm::regs::Status;
+use aluvm::SiteId;
+use amplify::num::u256;
+
+use super::analysis::ControlFlow;
+use super::{Bits, Expr, ExprGraph, ExprNode, FieldInstr, Instr, NodeId};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Errors of symbolic execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SymbolicError {
+    /// the program code exceeds the maximum code segment size.
+    CodeOverflow,
+
+    /// the number of execution paths exceeds the limit of {0} paths.
+    TooManyPaths(usize),
+}
+
+/// Condition on the symbolic values, on which the execution path depends.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum Cond {
+    /// Register has a value at the start of the program.
+    #[display("{0} is set")]
+    Init(RegE),
+
+    /// Two values are equal.
+    #[display("{0} == {1}")]
+    Eq(NodeId, NodeId),
+
+    /// Value fits into the given number of bits.
+    #[display("{0} fits {1}")]
+    Fits(NodeId, Bits),
+
+    /// The first value is less than the second one.
+    #[display("{0} < {1}")]
+    Lt(NodeId, NodeId),
+
+    /// Value is less than the given bound.
+    #[display("{0} < {1}")]
+    Rng(NodeId, fe256),
+}
+
+/// Path condition: a condition together with its outcome assumed by an execution path.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Assumption {
+    /// The condition.
+    pub cond: Cond,
+    /// Whether the condition holds.
+    pub holds: bool,
+}
+
+impl Assumption {
+    fn negate(self) -> Self {
+        Assumption {
+            cond: self.cond,
+            holds: !self.holds,
+        }
+    }
+}
+
+impl Display for Assumption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.holds {
+            Display::fmt(&self.cond, f)
+        } else {
+            write!(f, "not {}", self.cond)
+        }
+    }
+}
+
+/// The way an execution path ends.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum PathEnd {
+    /// The program halts successfully.
+    #[display("success")]
+    Success,
+
+    /// The program halts with a failure.
+    #[display("failure")]
+    Failure,
+
+    /// The program passes control outside the analyzed code: to another library, to the host or
+    /// inside an instruction; or performs an operation which is not modelled by the analysis (like
+    /// `inv`).
+    #[display("escape")]
+    Escape,
+
+    /// The path exceeds the step limit of the executor.
+    #[display("step limit")]
+    StepLimit,
+}
+
+/// Single execution path of a program.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SymPath {
+    /// Conditions which must hold for the execution to follow this path, in the order in which
+    /// they are met.
+    pub conditions: Vec<Assumption>,
+    /// Final values of the registers modified by the program on this path; `None` for the
+    /// cleared registers.
+    pub regs: BTreeMap<RegE, Option<NodeId>>,
+    /// Indexes of the executed instructions.
+    pub trace: Vec<usize>,
+    /// The way the path ends.
+    pub end: PathEnd,
+}
+
+impl SymPath {
+    /// Checks whether the path assumes the given condition to hold (or not to hold).
+    pub fn assumes(&self, cond: Cond) -> Option<bool> {
+        self.conditions
+            .iter()
+            .find(|a| a.cond == cond)
+            .map(|a| a.holds)
+    }
+}
+
+/// Result of the symbolic execution of a program.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SymbolicResult {
+    /// Graph holding the symbolic values of all the execution paths.
+    pub graph: ExprGraph,
+    /// All the execution paths of the program.
+    pub paths: Vec<SymPath>,
+}
+
+impl SymbolicResult {
+    /// Returns the node holding the final value of the register on the given path.
+    ///
+    /// Returns `None` for cleared registers and registers which are neither read nor modified.
+    pub fn output(&self, path: &SymPath, reg: RegE) -> Option<NodeId> {
+        match path.regs.get(&reg) {
+            Some(id) => *id,
+            None => self.graph.find(ExprNode::Input(reg)),
+        }
+    }
+
+    /// Expands the final value of the register on the given path into an expression over the
+    /// register values at the start of the program.
+    pub fn value(&self, path: &SymPath, reg: RegE) -> Option<Expr> {
+        self.output(path, reg).map(|id| self.graph.expand(id))
+    }
+
+    /// Returns the paths ending with the successful halt of the program.
+    pub fn successes(&self) -> impl Iterator<Item = &SymPath> {
+        self.paths
+            .iter()
+            .filter(|path| path.end == PathEnd::Success)
+    }
+
+    /// Detects whether any of the paths may end with a failure.
+    pub fn may_fail(&self) -> bool { self.paths.iter().any(|path| path.end == PathEnd::Failure) }
+
+    /// Detects whether all the paths were fully analyzed, i.e. none of them escapes the analyzed
+    /// code or exceeds the step limit.
+    pub fn is_complete(&self) -> bool {
+        self.paths
+            .iter()
+            .all(|path| matches!(path.end, PathEnd::Success | PathEnd::Failure))
+    }
+}
+
+impl Display for SymbolicResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.graph, f)?;
+        for (no, path) in self.paths.iter().enumerate() {
+            writeln!(f, "path #{no}: {}", path.end)?;
+            for assumption in &path.conditions {
+                writeln!(f, "  assume {assumption}")?;
+            }
+            for (reg, id) in &path.regs {
+                match id {
+                    Some(id) => writeln!(f, "  {reg} = {id}")?,
+                    None => writeln!(f, "  {reg} = _")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Value of the `CO` register during the symbolic execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Co {
+    Known(Status),
+    /// `CO` is `Ok` if the assumption is true.
+    Cond(Assumption),
+}
+
+#[derive(Clone, Debug)]
+struct State {
+    no: usize,
+    co: Co,
+    conditions: Vec<Assumption>,
+    regs: BTreeMap<RegE, Option<NodeId>>,
+    call_stack: Vec<usize>,
+    trace: Vec<usize>,
+}
+
+impl State {
+    fn assume(&mut self, graph: &ExprGraph, assumption: Assumption) {
+        if let Assumption {
+            cond: Cond::Init(reg),
+            holds: false,
+        } = assumption
+        {
+            for id in self.regs.values_mut() {
+                if id.is_some_and(|id| graph.node(id) == ExprNode::Input(reg)) {
+                    *id = None;
+                }
+            }
+            self.regs.insert(reg, None);
+        }
+        self.conditions.push(assumption);
+    }
+
+    fn end(self, end: PathEnd) -> SymPath {
+        SymPath {
+            conditions: self.conditions,
+            regs: self.regs,
+            trace: self.trace,
+            end,
+        }
+    }
+}
+
+/// Result of a single symbolic execution step.
+enum Step {
+    Next(usize),
+    Fork(Assumption),
+    End(PathEnd),
+}
+
+/// Symbolic executor of GFA256 code.
+///
+/// The executor assumes that the VM runs in the halting mode (`CH` is set), such that any
+/// failure of `CK` halts the program. Registers which are read by arithmetic or comparison
+/// instructions are assumed to have a value at the start of the program (the alternative paths
+/// always end with a failure and are not explored); in contrast, `test` instruction forks the
+/// execution on whether the register is set.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::LibId;
+/// # use zkaluvm::gfa::{Assembly, PathEnd, SymbolicExecutor};
+/// # use zkaluvm::RegE::*;
+/// let code = Assembly::parse(
+///     r#"
+///     routine MAIN:
+///         eq      EA, EB
+///         chk     CO
+///         put     E1, 1
+///         add     E1, EA
+/// "#,
+/// )
+/// .unwrap()
+/// .code;
+///
+/// let result = SymbolicExecutor::default().exec::<LibId>(&code).unwrap();
+/// assert_eq!(result.paths.len(), 2);
+/// let success = result.successes().next().unwrap();
+/// assert_eq!(success.conditions.last().unwrap().to_string(), "n0 == n1");
+/// assert_eq!(result.value(success, E1).unwrap().to_string(), "(1.fe + EA)");
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SymbolicExecutor {
+    /// Configuration of the GFA256 core.
+    pub config: GfaConfig,
+    /// Maximum number of instructions executed on a single path; paths exceeding it end with
+    /// [`PathEnd::StepLimit`].
+    pub max_steps: usize,
+    /// Maximum number of explored paths.
+    pub max_paths: usize,
+}
+
+impl Default for SymbolicExecutor {
+    fn default() -> Self {
+        SymbolicExecutor {
+            config: GfaConfig::default(),
+            max_steps: 1024,
+            max_paths: 256,
+        }
+    }
+}
+
+impl SymbolicExecutor {
+    /// Symbolically executes the program starting from its first instruction.
+    pub fn exec<Id: SiteId>(&self, code: &[Instr<Id>]) -> Result<SymbolicResult, SymbolicError> {
+        let cfg = ControlFlow::analyze(code).map_err(|_| SymbolicError::CodeOverflow)?;
+        let mut graph = ExprGraph::default();
+        let mut paths = Vec::new();
+        let mut pending = Vec::new();
+        if code.is_empty() {
+            paths.push(SymPath {
+                conditions: none!(),
+                regs: none!(),
+                trace: none!(),
+                end: PathEnd::Success,
+            });
+        } else {
+            pending.push(State {
+                no: 0,
+                co: Co::Known(Status::Ok),
+                conditions: none!(),
+                regs: none!(),
+                call_stack: none!(),
+                trace: none!(),
+            });
+        }
+
+        while let Some(mut state) = pending.pop() {
+            loop {
+                if state.trace.len() >= self.max_steps {
+                    paths.push(state.end(PathEnd::StepLimit));
+                    break;
+                }
+                match self.step(&cfg, code, &mut graph, &mut state) {
+                    Step::Next(no) => state.no = no,
+                    Step::Fork(assumption) => {
+                        if paths.len() + pending.len() + 2 > self.max_paths {
+                            return Err(SymbolicError::TooManyPaths(self.max_paths));
+                        }
+                        let mut other = state.clone();
+                        other.assume(&graph, assumption.negate());
+                        pending.push(other);
+                        state.assume(&graph, assumption);
+                    }
+                    Step::End(end) => {
+                        paths.push(state.end(end));
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(SymbolicResult { graph, paths })
+    }
+
+    fn step<Id: SiteId>(
+        &self,
+        cfg: &ControlFlow<Id>,
+        code: &[Instr<Id>],
+        graph: &mut ExprGraph,
+        state: &mut State,
+    ) -> Step {
+        let no = state.no;
+        let next = if no + 1 < code.len() { Step::Next(no + 1) } else { Step::End(PathEnd::Success) };
+        let goto = |pos: Option<u16>| match pos {
+            None => Step::End(PathEnd::Failure),
+            Some(pos) => match cfg.index_at(pos) {
+                Some(target) => Step::Next(target),
+                None if pos >= cfg.offset(code.len()) => Step::End(PathEnd::Failure),
+                None => Step::End(PathEnd::Escape),
+            },
+        };
+        let shift = |shift: i8| cfg.offset(no).checked_add_signed(shift as i16);
+
+        // Resolve the value of `CO`, forking the execution if it depends on an unknown condition.
+        let co = |state: &State| match state.co {
+            Co::Known(status) => Ok(status),
+            Co::Cond(assumption) => match state.conditions.iter().find(|a| a.cond == assumption.cond) {
+                Some(a) if a.holds == assumption.holds => Ok(Status::Ok),
+                Some(_) => Ok(Status::Fail),
+                None => Err(Step::Fork(assumption)),
+            },
+        };
+
+        let instr = match &code[no] {
+            Instr::Ctrl(ctrl) => {
+                if matches!(
+                    ctrl,
+                    CtrlInstr::ChkCo | CtrlInstr::NotCo | CtrlInstr::JiOvfl { .. } | CtrlInstr::ShOvfl { .. }
+                ) {
+                    match co(state) {
+                        Ok(status) => state.co = Co::Known(status),
+                        Err(fork) => return fork,
+                    }
+                }
+                state.trace.push(no);
+                let co_failed = state.co == Co::Known(Status::Fail);
+                return match *ctrl {
+                    CtrlInstr::Nop | CtrlInstr::ChkCk => next,
+                    CtrlInstr::ChkCo if co_failed => Step::End(PathEnd::Failure),
+                    CtrlInstr::ChkCo => next,
+                    CtrlInstr::NotCo => {
+                        state.co = Co::Known(if co_failed { Status::Ok } else { Status::Fail });
+                        next
+                    }
+                    CtrlInstr::FailCk => Step::End(PathEnd::Failure),
+                    CtrlInstr::RsetCk => {
+                        state.co = Co::Known(Status::Ok);
+                        next
+                    }
+                    CtrlInstr::Jmp { pos } => goto(Some(pos)),
+                    CtrlInstr::JiOvfl { pos } if co_failed => goto(Some(pos)),
+                    CtrlInstr::Sh { shift: s } => goto(shift(s)),
+                    CtrlInstr::ShOvfl { shift: s } if co_failed => goto(shift(s)),
+                    CtrlInstr::JiOvfl { .. }
+                    | CtrlInstr::ShOvfl { .. }
+                    | CtrlInstr::JiFail { .. }
+                    | CtrlInstr::ShFail { .. } => next,
+                    CtrlInstr::Fn { pos } => {
+                        state.call_stack.push(no + 1);
+                        goto(Some(pos))
+                    }
+                    CtrlInstr::Ret => match state.call_stack.pop() {
+                        Some(ret) if ret < code.len() => Step::Next(ret),
+                        _ => Step::End(PathEnd::Success),
+                    },
+                    CtrlInstr::Stop => Step::End(PathEnd::Success),
+                    CtrlInstr::Exec { .. } | CtrlInstr::Call { .. } => Step::End(PathEnd::Escape),
+                };
+            }
+            Instr::Gfa(instr) => *instr,
+            Instr::Reserved(_) => {
+                state.trace.push(no);
+                return Step::End(PathEnd::Failure);
+            }
+        };
+
+        // Reads a register value, assuming it is set; `None` if the register is cleared.
+        let read = |graph: &mut ExprGraph, state: &mut State, reg: RegE| {
+            let id = match state.regs.get(&reg) {
+                Some(id) => (*id)?,
+                None => graph.insert(ExprNode::Input(reg)),
+            };
+            if let ExprNode::Input(input) = graph.node(id) {
+                if !state.conditions.iter().any(|a| a.cond == Cond::Init(input)) {
+                    state.conditions.push(Assumption {
+                        cond: Cond::Init(input),
+                        holds: true,
+                    });
+                }
+            }
+            Some(id)
+        };
+
+        let (dst, node) = match instr {
+            FieldInstr::Test { src } => {
+                let id = match state.regs.get(&src) {
+                    Some(id) => *id,
+                    None => Some(graph.insert(ExprNode::Input(src))),
+                };
+                state.co = match id.map(|id| graph.node(id)) {
+                    None => Co::Known(Status::Fail),
+                    Some(ExprNode::Input(input)) => Co::Cond(Assumption {
+                        cond: Cond::Init(input),
+                        holds: true,
+                    }),
+                    Some(_) => Co::Known(Status::Ok),
+                };
+                state.trace.push(no);
+                return next;
+            }
+            FieldInstr::Clr { dst } => {
+                state.regs.insert(dst, None);
+                state.trace.push(no);
+                return next;
+            }
+            // Results of host syscalls and of the operations missing from the expression graph are
+            // not known to the analysis
+            FieldInstr::Ecall { .. } | FieldInstr::Inv { .. } | FieldInstr::Exp { .. } | FieldInstr::Sqrt { .. } => {
+                state.trace.push(no);
+                return Step::End(PathEnd::Escape);
+            }
+            FieldInstr::PutD { dst, data } => (dst, ExprNode::Const(data)),
+            FieldInstr::PutZ { dst } => (dst, ExprNode::Const(fe256::ZERO)),
+            FieldInstr::PutV { dst, val } => {
+                let val = val
+                    .to_fe256()
+                    .unwrap_or_else(|| (self.config.field_order - u256::ONE).into());
+                (dst, ExprNode::Const(val))
+            }
+            FieldInstr::Mov { dst, src } => {
+                let id = match state.regs.get(&src) {
+                    Some(id) => *id,
+                    None => Some(graph.insert(ExprNode::Input(src))),
+                };
+                state.regs.insert(dst, id);
+                state.trace.push(no);
+                return next;
+            }
+            FieldInstr::CMov { dst, src } => {
+                // The paths fork on `CO` as they do on conditional jumps
+                let status = match co(state) {
+                    Ok(status) => status,
+                    Err(fork) => return fork,
+                };
+                state.co = Co::Known(status);
+                if status.is_ok() {
+                    let id = match state.regs.get(&src) {
+                        Some(id) => *id,
+                        None => Some(graph.insert(ExprNode::Input(src))),
+                    };
+                    state.regs.insert(dst, id);
+                }
+                state.trace.push(no);
+                return next;
+            }
+            FieldInstr::Sel { dst, src_true, src_false } => {
+                let status = match co(state) {
+                    Ok(status) => status,
+                    Err(fork) => return fork,
+                };
+                state.co = Co::Known(status);
+                let src = if status.is_ok() { src_true } else { src_false };
+                let id = match state.regs.get(&src) {
+                    Some(id) => *id,
+                    None => Some(graph.insert(ExprNode::Input(src))),
+                };
+                state.regs.insert(dst, id);
+                state.trace.push(no);
+                return next;
+            }
+            FieldInstr::Fits { src, bits } => {
+                state.trace.push(no);
+                let Some(id) = read(graph, state, src) else {
+                    return Step::End(PathEnd::Failure);
+                };
+                state.co = match graph.node(id) {
+                    ExprNode::Const(val) => {
+                        let fits = val.to_u256() >> bits.bit_len() == u256::ZERO;
+                        Co::Known(if fits { Status::Ok } else { Status::Fail })
+                    }
+                    _ => Co::Cond(Assumption {
+                        cond: Cond::Fits(id, bits),
+                        holds: true,
+                    }),
+                };
+                return next;
+            }
+            FieldInstr::Lt { src1, src2 } => {
+                state.trace.push(no);
+                let (Some(a), Some(b)) = (read(graph, state, src1), read(graph, state, src2)) else {
+                    return Step::End(PathEnd::Failure);
+                };
+                state.co = match (graph.node(a), graph.node(b)) {
+                    _ if a == b => Co::Known(Status::Fail),
+                    (ExprNode::Const(x), ExprNode::Const(y)) => {
+                        Co::Known(if x.to_u256() < y.to_u256() { Status::Ok } else { Status::Fail })
+                    }
+                    _ => Co::Cond(Assumption {
+                        cond: Cond::Lt(a, b),
+                        holds: true,
+                    }),
+                };
+                return next;
+            }
+            FieldInstr::Rng { src, bound } => {
+                state.trace.push(no);
+                let Some(id) = read(graph, state, src) else {
+                    return Step::End(PathEnd::Failure);
+                };
+                state.co = match graph.node(id) {
+                    ExprNode::Const(val) => {
+                        Co::Known(if val.to_u256() < bound.to_u256() { Status::Ok } else { Status::Fail })
+                    }
+                    _ => Co::Cond(Assumption {
+                        cond: Cond::Rng(id, bound),
+                        holds: true,
+                    }),
+                };
+                return next;
+            }
+            FieldInstr::Eq { src1, src2 } => {
+                state.trace.push(no);
+                let a = read(graph, state, src1);
+                let b = read(graph, state, src2);
+                state.co = match (a, b) {
+                    (Some(a), Some(b)) if a == b => Co::Known(Status::Ok),
+                    (Some(a), Some(b)) => match (graph.node(a), graph.node(b)) {
+                        (ExprNode::Const(_), ExprNode::Const(_)) => Co::Known(Status::Fail),
+                        _ => Co::Cond(Assumption {
+                            cond: Cond::Eq(a.min(b), a.max(b)),
+                            holds: true,
+                        }),
+                    },
+                    _ => Co::Known(Status::Fail),
+                };
+                return next;
+            }
+            FieldInstr::ExpI { dst_src, exp } => {
+                state.trace.push(no);
+                let Some(base) = read(graph, state, dst_src) else {
+                    return Step::End(PathEnd::Failure);
+                };
+                state.regs.insert(dst_src, Some(graph.insert_power(base, exp)));
+                return next;
+            }
+            FieldInstr::Neg { dst, src } => match read(graph, state, src) {
+                Some(a) => (dst, ExprNode::Neg(a)),
+                None => {
+                    state.trace.push(no);
+                    return Step::End(PathEnd::Failure);
+                }
+            },
+            FieldInstr::Sqr { dst, src } => match read(graph, state, src) {
+                Some(a) => (dst, ExprNode::Mul(a, a)),
+                None => {
+                    state.trace.push(no);
+                    return Step::End(PathEnd::Failure);
+                }
+            },
+            FieldInstr::Add { dst_src, src } | FieldInstr::Mul { dst_src, src } => {
+                match (read(graph, state, dst_src), read(graph, state, src)) {
+                    (Some(a), Some(b)) if matches!(instr, FieldInstr::Add { .. }) => (dst_src, ExprNode::Add(a, b)),
+                    (Some(a), Some(b)) => (dst_src, ExprNode::Mul(a, b)),
+                    _ => {
+                        state.trace.push(no);
+                        return Step::End(PathEnd::Failure);
+                    }
+                }
+            }
+        };
+        let id = graph.insert(node);
+        state.regs.insert(dst, Some(id));
+        state.trace.push(no);
+        next
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::RegE::*;
+
+    fn parse(source: &str) -> Vec<Instr<LibId>> { Assembly::parse(source).unwrap().code }
+
+    fn exec(source: &str) -> SymbolicResult { SymbolicExecutor::default().exec(&parse(source)).unwrap() }
+
+    #[test]
+    fn test_fork() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                test    EA
+                jif     CO, unset
+                mov     E1, EA
+                stop
+            routine unset:
+                put     E1, 0
+        "#,
+        );
+        assert!(result.is_complete());
+        assert!(!result.may_fail());
+        assert_eq!(
+            result.to_string(),
+            "\
+n0 = EA
+n1 = 0.fe
+path #0: success
+  assume EA is set
+  E1 = n0
+path #1: success
+  assume not EA is set
+  E1 = n1
+  EA = _
+"
+        );
+        assert_eq!(result.paths[0].trace, [0, 1, 2, 3, 4]);
+        assert_eq!(result.paths[1].trace, [0, 1, 2, 5, 6]);
+        assert_eq!(result.output(&result.paths[0], EA), Some(NodeId(0)));
+        assert_eq!(result.output(&result.paths[1], EA), None);
+    }
+
+    #[test]
+    fn eq_property() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                eq      EA, EB
+                chk     CO
+                mov     E1, EA
+                neg     E1, E1
+                add     E1, EB
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        let failure = &result.paths[1];
+        assert_eq!(failure.end, PathEnd::Failure);
+        assert_eq!(failure.assumes(Cond::Eq(NodeId(0), NodeId(1))), Some(false));
+
+        let success = result.successes().next().unwrap();
+        assert_eq!(success.assumes(Cond::Init(EA)), Some(true));
+        assert_eq!(success.assumes(Cond::Init(EB)), Some(true));
+        assert_eq!(success.assumes(Cond::Eq(NodeId(0), NodeId(1))), Some(true));
+        assert_eq!(result.value(success, E1).unwrap().to_string(), "(-EA + EB)");
+    }
+
+    #[test]
+    fn const_folding() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                put     E1, 5
+                fits    E1, 8.bits
+                chk     CO
+                put     E2, 5
+                eq      E1, E2
+                chk     CO
+                put     E3, 6
+                eq      E1, E3
+                not     CO
+                chk     CO
+                fits    EA, 16.bits
+        "#,
+        );
+        assert_eq!(result.paths.len(), 1);
+        let path = &result.paths[0];
+        assert_eq!(path.end, PathEnd::Success);
+        assert_eq!(path.conditions, [Assumption {
+            cond: Cond::Init(EA),
+            holds: true
+        }]);
+    }
+
+    #[test]
+    fn cmov() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                put     E1, 1
+                eq      EA, EB
+                cmov    E1, EA
+                cmov    E2, EB
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        assert_eq!(result.successes().count(), 2);
+        let moved = &result.paths[0];
+        assert_eq!(moved.trace, [0, 1, 2, 3, 4]);
+        assert_eq!(moved.assumes(Cond::Eq(NodeId(1), NodeId(2))), Some(true));
+        assert_eq!(result.value(moved, E1).unwrap().to_string(), "EA");
+        assert_eq!(result.value(moved, E2).unwrap().to_string(), "EB");
+        let kept = &result.paths[1];
+        assert_eq!(kept.assumes(Cond::Eq(NodeId(1), NodeId(2))), Some(false));
+        assert_eq!(result.value(kept, E1).unwrap().to_string(), "1.fe");
+        assert_eq!(result.output(kept, E2), None);
+    }
+
+    #[test]
+    fn sel() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                put     E1, 1
+                eq      EA, EB
+                sel     E2, EA, E1
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        assert_eq!(result.successes().count(), 2);
+        let selected = &result.paths[0];
+        assert_eq!(selected.assumes(Cond::Eq(NodeId(1), NodeId(2))), Some(true));
+        assert_eq!(result.value(selected, E2).unwrap().to_string(), "EA");
+        let other = &result.paths[1];
+        assert_eq!(other.assumes(Cond::Eq(NodeId(1), NodeId(2))), Some(false));
+        assert_eq!(result.value(other, E2).unwrap().to_string(), "1.fe");
+    }
+
+    #[test]
+    fn lt() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                put     E1, 1
+                put     E2, 2
+                lt      E1, E2
+                chk     CO
+                lt      EA, E1
+                chk     CO
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        let success = result.successes().next().unwrap();
+        assert_eq!(success.assumes(Cond::Lt(NodeId(2), NodeId(0))), Some(true));
+        assert_eq!(Cond::Lt(NodeId(2), NodeId(0)).to_string(), "n2 < n0");
+    }
+
+    #[test]
+    fn rng() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                put     E1, 5
+                rng     E1, 10
+                chk     CO
+                rng     EA, 1000000000
+                chk     CO
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        let success = result.successes().next().unwrap();
+        let cond = Cond::Rng(NodeId(1), fe256::from(1_000_000_000u32));
+        assert_eq!(success.assumes(cond), Some(true));
+        assert_eq!(cond.to_string(), "n1 < 3B9ACA00.fe");
+    }
+
+    #[test]
+    fn repeated_condition() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                fits    EA, 8.bits
+                jif     CO, +5
+                fits    EA, 8.bits
+                chk     CO
+        "#,
+        );
+        assert_eq!(result.paths.len(), 2);
+        assert_eq!(result.paths[0].end, PathEnd::Success);
+        assert_eq!(result.paths[1].end, PathEnd::Failure);
+        assert_eq!(result.paths[1].conditions[1].to_string(), "not n0 fits 8.bits");
+    }
+
+    #[test]
+    fn calls() {
+        let result = exec(
+            r#"
+            routine MAIN:
+                call    SUB
+                add     E1, E1
+                stop
+            routine SUB:
+                put     E1, 1
+                ret
+        "#,
+        );
+        assert_eq!(result.paths.len(), 1);
+        let path = &result.paths[0];
+        assert_eq!(path.end, PathEnd::Success);
+        assert_eq!(path.trace, [0, 1, 4, 5, 6, 2, 3]);
+        assert_eq!(result.value(path, E1).unwrap().to_string(), "(1.fe + 1.fe)");
+    }
+
+    #[test]
+    fn failures() {
+        let result = exec("halt");
+        assert!(result.may_fail());
+        assert_eq!(result.paths[0].end, PathEnd::Failure);
+
+        let result = exec("clr E1\nadd E1, E2");
+        assert_eq!(result.paths[0].end, PathEnd::Failure);
+
+        let result = exec("fail CK");
+        assert_eq!(result.paths[0].end, PathEnd::Failure);
+    }
+
+    #[test]
+    fn limits() {
+        let code = parse(
+            r#"
+            routine LOOP:
+                jmp     LOOP
+        "#,
+        );
+        let executor = SymbolicExecutor {
+            max_steps: 10,
+            ..default!()
+        };
+        let result = executor.exec(&code).unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(result.paths[0].end, PathEnd::StepLimit);
+        assert_eq!(result.paths[0].trace.len(), 10);
+
+        let code = parse("test EA\nchk CO");
+        let executor = SymbolicExecutor {
+            max_paths: 1,
+            ..default!()
+        };
+        assert_eq!(executor.exec(&code).unwrap_err(), SymbolicError::TooManyPaths(1));
+    }
+
+    #[test]
+    fn empty() {
+        let result = exec("");
+        assert_eq!(result.paths.len(), 1);
+        assert_eq!(result.paths[0].end, PathEnd::Success);
+    }
+}
@@ -0,0 +1,293 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Standalone verification of zk-AluVM bytecode.
+//!
+//! The verifier checks a library received from an untrusted party before it gets accepted for
+//! execution. It walks the code segment instruction by instruction and collects every issue it
+//! finds instead of stopping at the first one, so a caller can report all problems at once.
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::{Bytecode, BytecodeRead, GotoTarget, Instruction};
+use aluvm::{Lib, LibId, Marshaller};
+
+use super::{FieldInstr, Instr};
+use crate::{fe256, GfaConfig};
+
+/// Issue detected by the bytecode verifier.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum VerifyIssue {
+    /// reserved opcode at offset {pos:04X}.
+    ReservedOpcode {
+        /** Offset of the instruction in the code segment */
+        pos: u16,
+    },
+
+    /// incomplete instruction at the end of the code segment at offset {pos:04X}.
+    Truncated {
+        /** Offset of the instruction in the code segment */
+        pos: u16,
+    },
+
+    /// instruction at offset {pos:04X} jumps to offset {target:04X}, which is in the middle of
+    /// another instruction.
+    MidInstructionJump {
+        /** Offset of the jump instruction in the code segment */
+        pos: u16,
+        /** Offset the instruction jumps to */
+        target: u16,
+    },
+
+    /// instruction at offset {pos:04X} jumps to offset {target}, which is outside of the code
+    /// segment.
+    JumpOutOfCode {
+        /** Offset of the jump instruction in the code segment */
+        pos: u16,
+        /** Offset the instruction jumps to */
+        target: i32,
+    },
+
+    /// instruction at offset {pos:04X} references data at offset {offset:04X}, which is outside of
+    /// the data segment.
+    DataOutOfBounds {
+        /** Offset of the instruction in the code segment */
+        pos: u16,
+        /** Offset of the referenced data in the data segment */
+        offset: u16,
+    },
+
+    /// instruction at offset {pos:04X} puts {value}, which is not a canonical field element.
+    NonCanonical {
+        /** Offset of the instruction in the code segment */
+        pos: u16,
+        /** Non-canonical constant */
+        value: fe256,
+    },
+}
+
+impl VerifyIssue {
+    /// Offset of the instruction in the code segment which caused the issue.
+    pub fn pos(&self) -> u16 {
+        match *self {
+            VerifyIssue::ReservedOpcode { pos }
+            | VerifyIssue::Truncated { pos }
+            | VerifyIssue::MidInstructionJump { pos, .. }
+            | VerifyIssue::JumpOutOfCode { pos, .. }
+            | VerifyIssue::DataOutOfBounds { pos, .. }
+            | VerifyIssue::NonCanonical { pos, .. } => pos,
+        }
+    }
+}
+
+/// Size of a field element in the data segment.
+const FE_LEN: usize = 32;
+
+/// Verifies library bytecode before it gets accepted for the execution.
+///
+/// The verifier detects reserved opcodes, jumps landing in the middle of an instruction or outside
+/// of the code segment, references outside of the data segment and constants which are not
+/// canonical elements of the field defined by the `config`. Jumps to the very end of the code
+/// segment are reported as well, since the VM fails `CK` when performing them.
+///
+/// # Returns
+///
+/// All detected issues, ordered by the offset of the instruction causing them.
+pub fn verify(lib: &Lib, config: GfaConfig) -> Result<(), Vec<VerifyIssue>> {
+    let code = lib.code.as_slice();
+    // The data segment is padded, so references outside of it do not interrupt decoding; they are
+    // detected by checking each reference against the original segment.
+    let mut data = lib.data.to_vec();
+    data.resize(u16::MAX as usize + FE_LEN, 0);
+
+    let mut issues = Vec::new();
+    let mut jumps = Vec::new();
+    let mut offsets = BTreeSet::new();
+    let mut reader = Marshaller::with(code, data.as_slice(), &lib.libs);
+    while !reader.is_eof() {
+        let pos = reader.pos();
+        let Ok(mut instr) = Instr::<LibId>::decode_instr(&mut reader) else {
+            issues.push(VerifyIssue::Truncated { pos });
+            break;
+        };
+        offsets.insert(pos);
+        match &instr {
+            Instr::Reserved(_) => issues.push(VerifyIssue::ReservedOpcode { pos }),
+            Instr::Gfa(instr @ (FieldInstr::PutD { .. } | FieldInstr::Rng { .. })) => {
+                // Both are encoded as an opcode byte, an operand byte and a data segment offset.
+                let at = pos as usize + 2;
+                let offset = u16::from_le_bytes([code[at], code[at + 1]]);
+                if offset as usize + FE_LEN > lib.data.len() {
+                    issues.push(VerifyIssue::DataOutOfBounds { pos, offset });
+                } else if let FieldInstr::PutD { data, .. } = instr {
+                    // The bound of `rng` is not required to be a canonical field element
+                    if data.to_u256() >= config.field_order {
+                        issues.push(VerifyIssue::NonCanonical { pos, value: *data });
+                    }
+                }
+            }
+            _ => {}
+        }
+        match instr.local_goto_pos() {
+            GotoTarget::None => {}
+            GotoTarget::Absolute(target) => jumps.push((pos, *target as i32)),
+            GotoTarget::Relative(shift) => jumps.push((pos, pos as i32 + *shift as i32)),
+        }
+    }
+
+    for (pos, target) in jumps {
+        match u16::try_from(target) {
+            Ok(target) if offsets.contains(&target) => {}
+            Ok(target) if (target as usize) < code.len() => {
+                issues.push(VerifyIssue::MidInstructionJump { pos, target })
+            }
+            _ => issues.push(VerifyIssue::JumpOutOfCode { pos, target }),
+        }
+    }
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+    issues.sort_by_key(VerifyIssue::pos);
+    Err(issues)
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use amplify::confinement::SmallBlob;
+    use amplify::num::u256;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::FIELD_ORDER_25519;
+
+    const CONFIG: GfaConfig = GfaConfig::new(FIELD_ORDER_25519);
+
+    fn lib(source: &str) -> Lib { Assembly::parse(source).unwrap().assemble().unwrap() }
+
+    #[test]
+    fn valid() {
+        let lib = lib(r#"
+            routine START:
+                put     E1, 0
+                put     E2, 0x1234567890abcdef1234567890abcdef
+            again:
+                add     E1, E2
+                fits    E1, 8.bits
+                jif     CO, again
+                ret
+        "#);
+        verify(&lib, CONFIG).unwrap();
+    }
+
+    #[test]
+    fn reserved() {
+        let lib = lib(r#"
+            routine START:
+                put     E1, 0
+                halt
+        "#);
+        let issues = verify(&lib, CONFIG).unwrap_err();
+        assert_eq!(issues, vec![VerifyIssue::ReservedOpcode { pos: 3 }]);
+        assert_eq!(issues[0].to_string(), "reserved opcode at offset 0003.");
+    }
+
+    #[test]
+    fn jumps() {
+        let mut lib = lib(r#"
+            routine START:
+                put     E1, 0x1234567890abcdef1234567890abcdef
+                jmp     START
+                jmp     START
+                jmp     START
+        "#);
+        let mut code = lib.code.to_vec();
+        // Retarget the jumps at 0005, 0008 and 000B; the second one jumps to the end of the code.
+        code[6] = 0x03;
+        code[9] = 0x0E;
+        code[12] = 0x20;
+        lib.code = SmallBlob::try_from(code).unwrap();
+        assert_eq!(verify(&lib, CONFIG).unwrap_err(), vec![
+            VerifyIssue::MidInstructionJump { pos: 5, target: 3 },
+            VerifyIssue::JumpOutOfCode { pos: 8, target: 0x0E },
+            VerifyIssue::JumpOutOfCode { pos: 11, target: 0x20 },
+        ]);
+    }
+
+    #[test]
+    fn data() {
+        let mut lib = lib(r#"
+            routine START:
+                put     E1, 0x1234567890abcdef1234567890abcdef
+                put     E2, 0x1234567890abcdef1234567890abcdee
+        "#);
+        let mut data = lib.data.to_vec();
+        data[FE_LEN..].fill(0xFF);
+        data.truncate(FE_LEN * 2 - 1);
+        lib.data = SmallBlob::try_from(data).unwrap();
+        assert_eq!(verify(&lib, CONFIG).unwrap_err(), vec![VerifyIssue::DataOutOfBounds { pos: 5, offset: 0x20 }]);
+
+        let mut data = lib.data.to_vec();
+        data.push(0xFF);
+        lib.data = SmallBlob::try_from(data).unwrap();
+        let issues = verify(&lib, CONFIG).unwrap_err();
+        assert_eq!(issues, vec![VerifyIssue::NonCanonical {
+            pos: 5,
+            value: fe256::from(u256::MAX)
+        }]);
+    }
+
+    #[test]
+    fn rng() {
+        let mut lib = lib(r#"
+            routine START:
+                put     E1, 1
+                rng     E1, 0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+        "#);
+        assert_eq!(verify(&lib, CONFIG), Ok(()));
+
+        let mut data = lib.data.to_vec();
+        data.pop();
+        lib.data = SmallBlob::try_from(data).unwrap();
+        assert_eq!(verify(&lib, CONFIG).unwrap_err(), vec![VerifyIssue::DataOutOfBounds { pos: 3, offset: 0 }]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut lib = lib(r#"
+            routine START:
+                put     E1, 0x1234567890abcdef1234567890abcdef
+        "#);
+        let mut code = lib.code.to_vec();
+        code.pop();
+        lib.code = SmallBlob::try_from(code).unwrap();
+        assert_eq!(verify(&lib, CONFIG).unwrap_err(), vec![VerifyIssue::Truncated { pos: 1 }]);
+    }
+}
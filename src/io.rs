@@ -0,0 +1,352 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Calling conventions for passing typed values to and from zk-AluVM programs.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::CoreExt;
+use amplify::num::u256;
+
+use crate::{fe256, GfaCore, RegE};
+
+/// Errors passing values to or from a program.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum IoError {
+    /// parameter '{0}' is declared more than once.
+    DuplicateName(String),
+
+    /// register {0} is used by more than one parameter.
+    DuplicateReg(RegE),
+
+    /// no value is provided for the input parameter '{0}'.
+    MissingInput(String),
+
+    /// the program has no input parameter named '{0}'.
+    UnknownInput(String),
+
+    /// value provided for the parameter '{name}' must have {expected} type.
+    TypeMismatch {
+        /** Name of the parameter */
+        name: String,
+        /** Type of the parameter */
+        expected: IoType,
+    },
+
+    /// value of the parameter '{0}' exceeds the field order.
+    NonCanonical(String),
+
+    /// the program has not set the output parameter '{0}'.
+    OutputNotSet(String),
+
+    /// value of the output parameter '{name}' doesn't fit {expected} type.
+    OutputOutOfRange {
+        /** Name of the parameter */
+        name: String,
+        /** Type of the parameter */
+        expected: IoType,
+    },
+}
+
+/// Type of a value passed in a register.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+#[display(lowercase)]
+pub enum IoType {
+    /// Field element.
+    Fe,
+    /// Boolean value, represented by zero and one field elements.
+    Bool,
+    /// 64-bit unsigned integer.
+    U64,
+}
+
+/// Value passed in a register.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, From)]
+pub enum IoValue {
+    /// Field element.
+    #[from]
+    #[display("{0}")]
+    Fe(fe256),
+    /// Boolean value.
+    #[from]
+    #[display("{0}")]
+    Bool(bool),
+    /// 64-bit unsigned integer.
+    #[from]
+    #[display("{0}")]
+    U64(u64),
+}
+
+impl IoValue {
+    /// Returns the type of the value.
+    pub fn ty(self) -> IoType {
+        match self {
+            IoValue::Fe(_) => IoType::Fe,
+            IoValue::Bool(_) => IoType::Bool,
+            IoValue::U64(_) => IoType::U64,
+        }
+    }
+
+    /// Converts the value into a field element.
+    pub fn to_fe256(self) -> fe256 {
+        match self {
+            IoValue::Fe(val) => val,
+            IoValue::Bool(val) => fe256::from(val as u8),
+            IoValue::U64(val) => fe256::from(val),
+        }
+    }
+
+    /// Interprets a field element as a value of the given type, if it fits the type.
+    pub fn from_fe256(val: fe256, ty: IoType) -> Option<Self> {
+        let val = val.to_u256();
+        match ty {
+            IoType::Fe => Some(IoValue::Fe(val.into())),
+            IoType::Bool if val == u256::ZERO => Some(IoValue::Bool(false)),
+            IoType::Bool if val == u256::ONE => Some(IoValue::Bool(true)),
+            IoType::Bool => None,
+            IoType::U64 if val <= u256::from(u64::MAX) => Some(IoValue::U64(val.low_u64())),
+            IoType::U64 => None,
+        }
+    }
+}
+
+/// Parameter of a program, passed in a register.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct IoParam {
+    /// Name of the parameter.
+    pub name: String,
+    /// Register holding the parameter value.
+    pub reg: RegE,
+    /// Type of the parameter value.
+    pub ty: IoType,
+}
+
+/// Declaration of the program inputs and outputs, describing in which registers the host passes
+/// values to the program and reads its results.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibId, LibSite, Vm};
+/// # use zkaluvm::gfa::{Expr, ExprCompiler, Instr, NO_CONTEXT};
+/// # use zkaluvm::{IoSpec, IoType, IoValue, RegE::*};
+/// let mut compiler = ExprCompiler::new();
+/// compiler.assign(EC, Expr::mul(EA, EB));
+/// let lib = Lib::assemble(&compiler.compile().unwrap()).unwrap();
+///
+/// let mut spec = IoSpec::new();
+/// spec.input("a", EA, IoType::U64).unwrap();
+/// spec.input("b", EB, IoType::U64).unwrap();
+/// spec.output("product", EC, IoType::U64).unwrap();
+///
+/// let mut vm = Vm::<Instr<LibId>>::new();
+/// spec.load(&mut vm.core.cx, [("a", 6u64.into()), ("b", 7u64.into())])
+///     .unwrap();
+/// vm.exec(LibSite::new(lib.lib_id(), 0), &NO_CONTEXT, |_| Some(&lib));
+/// let outputs = spec.extract(&vm.core.cx).unwrap();
+/// assert_eq!(outputs["product"], IoValue::U64(42));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct IoSpec {
+    inputs: Vec<IoParam>,
+    outputs: Vec<IoParam>,
+}
+
+impl IoSpec {
+    /// Constructs specification without parameters.
+    pub fn new() -> Self { Self::default() }
+
+    /// Declares an input parameter.
+    pub fn input(&mut self, name: impl Into<String>, reg: RegE, ty: IoType) -> Result<&mut Self, IoError> {
+        Self::declare(&mut self.inputs, IoParam {
+            name: name.into(),
+            reg,
+            ty,
+        })?;
+        Ok(self)
+    }
+
+    /// Declares an output parameter.
+    ///
+    /// The same register may hold both an input and an output parameter.
+    pub fn output(&mut self, name: impl Into<String>, reg: RegE, ty: IoType) -> Result<&mut Self, IoError> {
+        Self::declare(&mut self.outputs, IoParam {
+            name: name.into(),
+            reg,
+            ty,
+        })?;
+        Ok(self)
+    }
+
+    fn declare(params: &mut Vec<IoParam>, param: IoParam) -> Result<(), IoError> {
+        if params.iter().any(|p| p.name == param.name) {
+            return Err(IoError::DuplicateName(param.name));
+        }
+        if params.iter().any(|p| p.reg == param.reg) {
+            return Err(IoError::DuplicateReg(param.reg));
+        }
+        params.push(param);
+        Ok(())
+    }
+
+    /// Returns declared input parameters.
+    pub fn inputs(&self) -> &[IoParam] { &self.inputs }
+
+    /// Returns declared output parameters.
+    pub fn outputs(&self) -> &[IoParam] { &self.outputs }
+
+    /// Puts values of all the input parameters into their registers.
+    ///
+    /// Other registers are left unchanged. Registers are modified only if all the values are
+    /// valid.
+    pub fn load<'name>(
+        &self,
+        core: &mut GfaCore,
+        values: impl IntoIterator<Item = (&'name str, IoValue)>,
+    ) -> Result<(), IoError> {
+        let mut values = values.into_iter().collect::<BTreeMap<_, _>>();
+        let mut regs = Vec::with_capacity(self.inputs.len());
+        for param in &self.inputs {
+            let value = values
+                .remove(param.name.as_str())
+                .ok_or_else(|| IoError::MissingInput(param.name.clone()))?;
+            if value.ty() != param.ty {
+                return Err(IoError::TypeMismatch {
+                    name: param.name.clone(),
+                    expected: param.ty,
+                });
+            }
+            let val = value.to_fe256();
+            if val.to_u256() >= core.fq() {
+                return Err(IoError::NonCanonical(param.name.clone()));
+            }
+            regs.push((param.reg, val));
+        }
+        if let Some(name) = values.into_keys().next() {
+            return Err(IoError::UnknownInput(name.into()));
+        }
+        for (reg, val) in regs {
+            core.put(reg, Some(val));
+        }
+        Ok(())
+    }
+
+    /// Reads values of all the output parameters from their registers.
+    pub fn extract(&self, core: &GfaCore) -> Result<BTreeMap<String, IoValue>, IoError> {
+        self.outputs
+            .iter()
+            .map(|param| {
+                let val = core
+                    .get(param.reg)
+                    .ok_or_else(|| IoError::OutputNotSet(param.name.clone()))?;
+                let value = IoValue::from_fe256(val, param.ty).ok_or_else(|| IoError::OutputOutOfRange {
+                    name: param.name.clone(),
+                    expected: param.ty,
+                })?;
+                Ok((param.name.clone(), value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::GfaConfig;
+    use crate::RegE::*;
+
+    fn spec() -> IoSpec {
+        let mut spec = IoSpec::new();
+        spec.input("x", EA, IoType::Fe)
+            .unwrap()
+            .input("flag", EB, IoType::Bool)
+            .unwrap()
+            .output("x", EA, IoType::U64)
+            .unwrap()
+            .output("flag", EB, IoType::Bool)
+            .unwrap();
+        spec
+    }
+
+    #[test]
+    fn declare() {
+        let mut spec = spec();
+        assert_eq!(spec.input("x", EC, IoType::Fe).unwrap_err(), IoError::DuplicateName(s!("x")));
+        assert_eq!(spec.output("y", EA, IoType::Fe).unwrap_err(), IoError::DuplicateReg(EA));
+        assert_eq!(spec.inputs().len(), 2);
+        assert_eq!(spec.outputs().len(), 2);
+    }
+
+    #[test]
+    fn load_extract() {
+        let spec = spec();
+        let mut core = GfaCore::with(GfaConfig::default());
+        assert_eq!(spec.extract(&core).unwrap_err(), IoError::OutputNotSet(s!("x")));
+
+        assert_eq!(
+            spec.load(&mut core, [("x", fe256::from(5u8).into())])
+                .unwrap_err(),
+            IoError::MissingInput(s!("flag"))
+        );
+        assert_eq!(
+            spec.load(&mut core, [("x", 5u64.into()), ("flag", true.into())])
+                .unwrap_err(),
+            IoError::TypeMismatch {
+                name: s!("x"),
+                expected: IoType::Fe
+            }
+        );
+        assert_eq!(
+            spec.load(&mut core, [("x", fe256::from(5u8).into()), ("flag", true.into()), ("y", true.into())])
+                .unwrap_err(),
+            IoError::UnknownInput(s!("y"))
+        );
+        assert_eq!(
+            spec.load(&mut core, [("x", fe256::from(u256::MAX).into()), ("flag", true.into())])
+                .unwrap_err(),
+            IoError::NonCanonical(s!("x"))
+        );
+        assert_eq!(core.get(EA), None);
+
+        spec.load(&mut core, [("x", fe256::from(5u8).into()), ("flag", true.into())])
+            .unwrap();
+        assert_eq!(core.get(EA), Some(fe256::from(5u8)));
+        assert_eq!(core.get(EB), Some(fe256::from(1u8)));
+        let outputs = spec.extract(&core).unwrap();
+        assert_eq!(outputs["x"], IoValue::U64(5));
+        assert_eq!(outputs["flag"], IoValue::Bool(true));
+
+        core.put(EB, Some(fe256::from(2u8)));
+        assert_eq!(spec.extract(&core).unwrap_err(), IoError::OutputOutOfRange {
+            name: s!("flag"),
+            expected: IoType::Bool
+        });
+    }
+}
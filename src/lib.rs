@@ -43,19 +43,70 @@ extern crate alloc;
 extern crate amplify;
 #[macro_use]
 extern crate strict_encoding;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
+#[cfg(feature = "armor")]
+mod armored;
+#[cfg(feature = "cbor")]
+mod bundle;
+mod cache;
+#[cfg(feature = "cbor")]
+mod container;
 mod core;
+mod debugger;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+mod io;
+mod load;
+mod profiler;
+mod run;
 #[macro_use]
 pub mod gfa;
+pub mod zk;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 #[cfg(feature = "stl")]
 pub mod zkstl;
 mod fe;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use aluvm as alu;
 pub use aluvm::isa;
+#[cfg(feature = "armor")]
+pub use armored::{armor_lib, parse_armored_lib, ArmorError};
+#[cfg(feature = "cbor")]
+pub use bundle::{BundleError, LibBundle, BUNDLE_VERSION};
+pub use cache::LibCache;
+#[cfg(feature = "cbor")]
+pub use container::{ContainerError, ProgramContainer, CONTAINER_VERSION};
+pub use debugger::{
+    Debugger, InstrSite, SnapshotError, StopReason, VmSnapshot, WatchAction, WatchFilter, WatchHit, Watchpoint,
+};
 pub use fe::{fe256, ParseFeError};
+#[cfg(feature = "arbitrary")]
+pub use fuzz::{fuzz_exec, fuzz_roundtrip};
+pub use io::{IoError, IoParam, IoSpec, IoType, IoValue};
+pub use load::{load_lib_bytes, load_lib_hex, serialize_lib, validate_lib, LoadError, LIB_FILE_EXT};
+#[cfg(feature = "std")]
+pub use load::{load_lib_file, save_lib_file};
+pub use profiler::{ComplexityUsage, ExecStats, Profiler};
+pub use run::{exec_raw, run, run_with, zk_complexity_lim, ExecOutcome, ZkDefault, ZK_MUL_BIT_BUDGET};
+
+pub use self::core::{
+    CurveError, CurveForm, CurveParams, FailureAction, FailurePolicy, FieldPreset, GfaConfig, GfaCore,
+    ParseFieldPresetError, ParseRegError, RegE, RegOutOfRange, SolinasPrime, FIELD_ORDER_25519, FIELD_ORDER_BABYBEAR,
+    FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_SECP, FIELD_ORDER_STARK, SOLINAS_MAX_TERMS,
+};
 
-pub use self::core::{GfaConfig, GfaCore, RegE, FIELD_ORDER_25519, FIELD_ORDER_SECP, FIELD_ORDER_STARK};
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec::Vec;
+}
 
 /// Name for the strict type library.
 pub const LIB_NAME_FINITE_FIELD: &str = "FiniteField";
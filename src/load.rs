@@ -0,0 +1,228 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Loading zk-AluVM libraries produced by external tools.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use aluvm::isa::IsaId;
+use aluvm::Lib;
+use amplify::confinement::{Confined, U24 as U24MAX};
+use amplify::hex::{self, FromHex};
+use strict_encoding::{DeserializeError, StrictDeserialize, StrictSerialize};
+
+use crate::gfa::{required_groups, verify, IsaGroups, VerifyIssue, ISA_GFA256, ISA_GFA256_V2};
+use crate::GfaConfig;
+
+/// Errors loading a library.
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LoadError {
+    /// unable to read the library file: {0}
+    #[cfg(feature = "std")]
+    #[from]
+    Io(std::io::Error),
+
+    /// invalid hexadecimal encoding of the library: {0}
+    #[from]
+    Hex(hex::Error),
+
+    /// invalid library encoding: {0}
+    #[from]
+    Decode(DeserializeError),
+
+    /// invalid ASCII-armored library: {0}
+    #[cfg(feature = "armor")]
+    #[from]
+    Armor(crate::ArmorError),
+
+    /// library requires ISA extensions '{0}', which are not supported by zk-AluVM.
+    UnsupportedIsa(String),
+
+    /// library requires instruction groups '{0}', which are not supported by the VM.
+    UnsupportedGroups(IsaGroups),
+
+    /// library code is invalid: {0}
+    #[from]
+    InvalidCode(VerifyIssue),
+}
+
+/// Checks that a library uses only the zk-AluVM instruction set, that the instruction groups it
+/// requires are supported by the VM (see [`GfaConfig::isa_groups`]), and that its code passes
+/// [`verify`]cation for the field used by the VM.
+pub fn validate_lib(lib: &Lib, config: GfaConfig) -> Result<(), LoadError> {
+    let supported = [IsaId::from(ISA_GFA256), IsaId::from(ISA_GFA256_V2)];
+    if !lib.isae.iter().all(|isa| supported.contains(isa)) {
+        return Err(LoadError::UnsupportedIsa(lib.isae_string()));
+    }
+    let unsupported = required_groups(lib).difference(config.isa_groups);
+    if !unsupported.is_empty() {
+        return Err(LoadError::UnsupportedGroups(unsupported));
+    }
+    verify(lib, config).map_err(|issues| LoadError::InvalidCode(issues[0]))
+}
+
+/// Loads a library from its strict-encoded binary representation, validating it with
+/// [`validate_lib`].
+///
+/// If the `armor` feature is enabled, the library may also be provided in the ASCII-armored form.
+pub fn load_lib_bytes(data: &[u8], config: GfaConfig) -> Result<Lib, LoadError> {
+    #[cfg(feature = "armor")]
+    if data.starts_with(b"-----BEGIN") {
+        let s = String::from_utf8_lossy(data);
+        let lib = crate::parse_armored_lib(&s)?;
+        validate_lib(&lib, config)?;
+        return Ok(lib);
+    }
+    let data =
+        Confined::<Vec<u8>, 0, U24MAX>::try_from(data.to_vec()).map_err(|err| DeserializeError::Decode(err.into()))?;
+    let lib = Lib::from_strict_serialized::<U24MAX>(data)?;
+    validate_lib(&lib, config)?;
+    Ok(lib)
+}
+
+/// Loads a library from a hexadecimal string of its strict-encoded binary representation,
+/// validating it with [`validate_lib`].
+///
+/// Whitespace around the string is ignored.
+pub fn load_lib_hex(s: &str, config: GfaConfig) -> Result<Lib, LoadError> {
+    let data = Vec::<u8>::from_hex(s.trim())?;
+    load_lib_bytes(&data, config)
+}
+
+/// Loads a library from a file, validating it with [`validate_lib`].
+///
+/// The file may contain either the strict-encoded binary representation of the library, or (if
+/// the `armor` feature is enabled) its ASCII-armored form.
+#[cfg(feature = "std")]
+pub fn load_lib_file(path: impl AsRef<Path>, config: GfaConfig) -> Result<Lib, LoadError> {
+    let data = std::fs::read(path)?;
+    load_lib_bytes(&data, config)
+}
+
+/// Extension of AluVM library files.
+pub const LIB_FILE_EXT: &str = "alu";
+
+/// Serializes a library into the strict-encoded binary representation used by AluVM library
+/// (`.alu`) files, which can be read back with [`load_lib_bytes`].
+pub fn serialize_lib(lib: &Lib) -> Vec<u8> {
+    lib.to_strict_serialized::<U24MAX>()
+        .expect("library segments are always below the serialization limit")
+        .release()
+}
+
+/// Writes a library into an AluVM library (`.alu`) file, which can be read back with
+/// [`load_lib_file`].
+#[cfg(feature = "std")]
+pub fn save_lib_file(lib: &Lib, path: impl AsRef<Path>) -> Result<(), LoadError> {
+    std::fs::write(path, serialize_lib(lib))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::format;
+
+    use aluvm::IsaId;
+    use amplify::confinement::TinyOrdSet;
+    use amplify::hex::ToHex;
+
+    use super::*;
+    use crate::gfa::{Assembly, IsaGroup};
+
+    fn lib() -> Lib {
+        Assembly::parse("put E1, 0x1234\nmul E1, E1")
+            .unwrap()
+            .assemble()
+            .unwrap()
+    }
+
+    #[test]
+    fn hex() {
+        let lib = lib();
+        let hex = serialize_lib(&lib).to_hex();
+        let loaded = load_lib_hex(&format!(" {hex}\n"), GfaConfig::default()).unwrap();
+        assert_eq!(loaded, lib);
+
+        assert!(matches!(load_lib_hex("0x00", GfaConfig::default()), Err(LoadError::Hex(_))));
+        assert!(matches!(load_lib_hex(&hex[..hex.len() - 2], GfaConfig::default()), Err(LoadError::Decode(_))));
+    }
+
+    #[test]
+    fn validation() {
+        let mut lib = lib();
+        lib.code.as_mut()[0] = 0xFF;
+        assert!(matches!(validate_lib(&lib, GfaConfig::default()), Err(LoadError::InvalidCode(_))));
+
+        let mut lib = self::lib();
+        lib.isae = TinyOrdSet::from_checked(bset![IsaId::from("ALU128")]);
+        assert_eq!(
+            validate_lib(&lib, GfaConfig::default())
+                .unwrap_err()
+                .to_string(),
+            "library requires ISA extensions 'ALU128', which are not supported by zk-AluVM."
+        );
+
+        let config = GfaConfig {
+            isa_groups: IsaGroups::ALL.without(IsaGroup::Field),
+            ..GfaConfig::default()
+        };
+        assert_eq!(
+            validate_lib(&self::lib(), config).unwrap_err().to_string(),
+            "library requires instruction groups 'field', which are not supported by the VM."
+        );
+
+        let mut lib = self::lib();
+        lib.isae = TinyOrdSet::from_checked(bset![IsaId::from(ISA_GFA256), IsaId::from(ISA_GFA256_V2)]);
+        validate_lib(&lib, GfaConfig::default()).unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file() {
+        let lib = lib();
+        let path = std::env::temp_dir().join(format!("zkaluvm-load-{}.{LIB_FILE_EXT}", lib.lib_id()));
+        save_lib_file(&lib, &path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), lib.to_strict_serialized::<U24MAX>().unwrap().release());
+        assert_eq!(load_lib_file(&path, GfaConfig::default()).unwrap(), lib);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(load_lib_file(&path, GfaConfig::default()), Err(LoadError::Io(_))));
+
+        let dir = std::env::temp_dir()
+            .join("zkaluvm-load-missing")
+            .join("lib.alu");
+        assert!(matches!(save_lib_file(&lib, dir), Err(LoadError::Io(_))));
+    }
+
+    #[test]
+    fn serialize() {
+        let lib = lib();
+        let data = serialize_lib(&lib);
+        assert_eq!(load_lib_bytes(&data, GfaConfig::default()).unwrap(), lib);
+        assert_eq!(Lib::from_strict_serialized::<U24MAX>(Confined::try_from(data).unwrap()).unwrap(), lib);
+    }
+}
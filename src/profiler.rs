@@ -0,0 +1,277 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Execution profiler for zk-AluVM programs.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::Instruction;
+use aluvm::regs::Status;
+use aluvm::{LibId, LibSite};
+
+use crate::gfa::{Instr, InstrClass};
+use crate::Debugger;
+
+/// Execution statistics for a group of instructions.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ExecStats {
+    /// Number of executed instructions.
+    pub count: u64,
+    /// Cumulative complexity of the executed instructions.
+    pub complexity: u64,
+}
+
+impl ExecStats {
+    pub(crate) fn add(&mut self, other: ExecStats) {
+        self.count += other.count;
+        self.complexity += other.complexity;
+    }
+}
+
+/// Complexity used by a program execution, reported by [`Debugger::usage`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ComplexityUsage {
+    /// Total complexity accumulated by the VM.
+    pub total: u64,
+    /// Complexity limit of the VM, if any.
+    pub limit: Option<u64>,
+    /// Statistics of the executed instructions per instruction class.
+    pub classes: BTreeMap<InstrClass, ExecStats>,
+}
+
+impl ComplexityUsage {
+    /// Returns complexity which still can be accumulated before the VM reaches its limit, or
+    /// `None` if the VM has no complexity limit.
+    pub fn remaining(&self) -> Option<u64> { self.limit.map(|limit| limit.saturating_sub(self.total)) }
+
+    /// Detects whether the accumulated complexity has exceeded the limit.
+    pub fn is_exceeded(&self) -> bool { self.limit.is_some_and(|limit| self.total > limit) }
+}
+
+impl Display for ComplexityUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "; total complexity {}", self.total)?;
+        match self.remaining() {
+            Some(remaining) => writeln!(f, ", {remaining} of {} remaining", self.limit.unwrap_or_default())?,
+            None => writeln!(f, ", no limit")?,
+        }
+        writeln!(f, "; {:>12} {:>10}  class", "complexity", "count")?;
+        for (class, stats) in &self.classes {
+            writeln!(f, "  {:>12} {:>10}  {class}", stats.complexity, stats.count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Profiler collecting execution statistics per opcode, per site and per library, over one or
+/// several program executions.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{LibSite, Vm};
+/// # use zkaluvm::gfa::Assembly;
+/// # use zkaluvm::{Debugger, Profiler};
+/// let lib = Assembly::parse(
+///     r#"
+///     routine MAIN:
+///         put     E1, 2
+///         mul     E1, E1
+///         mul     E1, E1
+/// "#,
+/// )
+/// .unwrap()
+/// .assemble()
+/// .unwrap();
+/// let entry = LibSite::new(lib.lib_id(), 0);
+///
+/// let mut profiler = Profiler::new();
+/// let mut debugger = Debugger::new(Vm::new(), [lib]);
+/// debugger.start(entry);
+/// profiler.exec(&mut debugger);
+///
+/// let (mnemonic, stats) = profiler.hot_opcodes()[0];
+/// assert_eq!(mnemonic, "mul");
+/// assert_eq!(stats.count, 2);
+/// println!("{profiler}");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Profiler {
+    runs: u64,
+    opcodes: BTreeMap<&'static str, ExecStats>,
+    sites: BTreeMap<LibSite, ExecStats>,
+    libs: BTreeMap<LibId, ExecStats>,
+}
+
+impl Profiler {
+    /// Constructs profiler with no statistics collected.
+    pub fn new() -> Self { Self::default() }
+
+    /// Runs the program started in the debugger until it halts, collecting statistics on the
+    /// executed instructions.
+    ///
+    /// Debugger breakpoints are ignored.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `CK` register at the end of the program execution.
+    pub fn exec(&mut self, debugger: &mut Debugger) -> Status {
+        self.runs += 1;
+        debugger.run_observed(|site, instr| self.record(site, instr))
+    }
+
+    /// Records execution of a single instruction.
+    pub fn record(&mut self, site: LibSite, instr: &Instr<LibId>) {
+        let stats = ExecStats {
+            count: 1,
+            complexity: instr.complexity(),
+        };
+        self.opcodes.entry(instr.mnemonic()).or_default().add(stats);
+        self.sites.entry(site).or_default().add(stats);
+        self.libs.entry(site.lib_id).or_default().add(stats);
+    }
+
+    /// Merges statistics collected by another profiler.
+    pub fn merge(&mut self, other: &Profiler) {
+        self.runs += other.runs;
+        for (mnemonic, stats) in &other.opcodes {
+            self.opcodes.entry(mnemonic).or_default().add(*stats);
+        }
+        for (site, stats) in &other.sites {
+            self.sites.entry(*site).or_default().add(*stats);
+        }
+        for (lib_id, stats) in &other.libs {
+            self.libs.entry(*lib_id).or_default().add(*stats);
+        }
+    }
+
+    /// Returns the number of profiled program executions.
+    pub fn runs(&self) -> u64 { self.runs }
+
+    /// Returns statistics for all executed instructions.
+    pub fn total(&self) -> ExecStats {
+        let mut total = ExecStats::default();
+        self.libs.values().for_each(|stats| total.add(*stats));
+        total
+    }
+
+    /// Returns statistics per instruction mnemonic, starting from the ones with the highest
+    /// cumulative complexity.
+    pub fn hot_opcodes(&self) -> Vec<(&'static str, ExecStats)> { hot(&self.opcodes) }
+
+    /// Returns statistics per instruction site, starting from the ones with the highest cumulative
+    /// complexity.
+    pub fn hot_sites(&self) -> Vec<(LibSite, ExecStats)> { hot(&self.sites) }
+
+    /// Returns statistics per library, starting from the ones with the highest cumulative
+    /// complexity.
+    pub fn hot_libs(&self) -> Vec<(LibId, ExecStats)> { hot(&self.libs) }
+}
+
+/// Sorts statistics by decreasing complexity and execution count.
+fn hot<K: Copy>(stats: &BTreeMap<K, ExecStats>) -> Vec<(K, ExecStats)> {
+    let mut hot = stats
+        .iter()
+        .map(|(key, stats)| (*key, *stats))
+        .collect::<Vec<_>>();
+    hot.sort_by_key(|(_, stats)| core::cmp::Reverse((stats.complexity, stats.count)));
+    hot
+}
+
+impl Display for Profiler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        writeln!(
+            f,
+            "; {} run(s), {} instruction(s) executed, total complexity {}",
+            self.runs, total.count, total.complexity
+        )?;
+        writeln!(f, "; {:>12} {:>10}  library", "complexity", "count")?;
+        for (lib_id, stats) in self.hot_libs() {
+            writeln!(f, "  {:>12} {:>10}  {lib_id}", stats.complexity, stats.count)?;
+        }
+        writeln!(f, "; {:>12} {:>10}  opcode", "complexity", "count")?;
+        for (mnemonic, stats) in self.hot_opcodes() {
+            writeln!(f, "  {:>12} {:>10}  {mnemonic}", stats.complexity, stats.count)?;
+        }
+        writeln!(f, "; {:>12} {:>10}  site", "complexity", "count")?;
+        for (site, stats) in self.hot_sites() {
+            writeln!(f, "  {:>12} {:>10}  {site}", stats.complexity, stats.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::{Lib, Vm};
+
+    use super::*;
+    use crate::gfa::{Assembly, FieldInstr};
+    use crate::RegE::E1;
+
+    fn lib(source: &str) -> Lib { Assembly::parse(source).unwrap().assemble().unwrap() }
+
+    #[test]
+    fn profile() {
+        let lib = lib(r#"
+            routine MAIN:
+                put     E1, 3
+                call    SQUARE
+                call    SQUARE
+                stop
+            routine SQUARE:
+                mul     E1, E1
+                ret
+        "#);
+        let lib_id = lib.lib_id();
+        let mul = Instr::<LibId>::Gfa(FieldInstr::Mul { dst_src: E1, src: E1 });
+
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        let mut profiler = Profiler::new();
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(profiler.exec(&mut debugger), Status::Ok);
+        let mut other = Profiler::new();
+        debugger.start(LibSite::new(lib_id, 0));
+        assert_eq!(other.exec(&mut debugger), Status::Ok);
+        profiler.merge(&other);
+
+        assert_eq!(profiler.runs(), 2);
+        assert_eq!(profiler.total().count, 22);
+        let (site, stats) = profiler.hot_sites()[0];
+        assert_eq!(site, LibSite::new(lib_id, 13));
+        assert_eq!(stats, ExecStats {
+            count: 4,
+            complexity: 4 * mul.complexity()
+        });
+        let (mnemonic, stats) = profiler.hot_opcodes()[0];
+        assert_eq!(mnemonic, "mul");
+        assert_eq!(stats.count, 4);
+        assert_eq!(profiler.hot_libs(), vec![(lib_id, profiler.total())]);
+    }
+}
@@ -0,0 +1,405 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Python bindings for assembling, running and tracing zk-AluVM programs.
+//!
+//! The [`zkaluvm`] function initializes the `zkaluvm` Python module. A Python extension is
+//! produced by building the crate as a `cdylib` with the `python` feature and the
+//! `pyo3/extension-module` feature enabled (for instance, with `maturin`).
+//!
+//! Field elements are represented by the `Fe256` class, which can be constructed from Python
+//! integers and from the assembly literals, and converted back with `int()`. Registers are
+//! referred to by their names (`"EA"`, `"E1"`, etc.).
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use aluvm::{Lib, LibSite, Vm};
+use amplify::num::u256;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyInt};
+
+use crate::core::parse_field_order;
+use crate::gfa::{AsmConfig, AsmFormatter, Assembly};
+use crate::zk::{ExecTrace, TraceState};
+use crate::{fe256, parse_armored_lib, Debugger, GfaConfig, RegE, StopReason};
+
+fn value_err(err: impl ToString) -> PyErr { PyValueError::new_err(err.to_string()) }
+
+fn parse_reg(name: &str) -> PyResult<RegE> { RegE::from_str(name.trim()).map_err(value_err) }
+
+fn to_int<'py>(py: Python<'py>, val: u256) -> PyResult<Bound<'py, PyAny>> {
+    py.get_type::<PyInt>()
+        .call_method1("from_bytes", (PyBytes::new(py, &val.to_le_bytes()), "little"))
+}
+
+fn regs_dict(regs: &[Option<fe256>; 16]) -> BTreeMap<String, Fe256> {
+    RegE::ALL
+        .into_iter()
+        .zip(regs)
+        .filter_map(|(reg, val)| Some((reg.to_string(), Fe256((*val)?))))
+        .collect()
+}
+
+/// Element of a finite field.
+#[pyclass(module = "zkaluvm", frozen, eq, hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Fe256(pub fe256);
+
+#[pymethods]
+impl Fe256 {
+    /// Constructs a field element from a non-negative Python integer below `2^256`, or from an
+    /// assembly literal (decimal, hexadecimal prefixed with `0x`, or hexadecimal with `.fe`
+    /// suffix).
+    #[new]
+    fn new(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(fe) = value.downcast::<Fe256>() {
+            return Ok(*fe.get());
+        }
+        if let Ok(s) = value.extract::<String>() {
+            return fe256::parse_literal(s.trim()).map(Fe256).map_err(value_err);
+        }
+        let bytes = value.call_method1("to_bytes", (32, "little"))?;
+        let bytes = bytes.downcast::<PyBytes>()?.as_bytes();
+        let bytes = <[u8; 32]>::try_from(bytes).expect("32 bytes are requested");
+        Ok(Fe256(fe256::from(u256::from_le_bytes(bytes))))
+    }
+
+    fn __int__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> { to_int(py, self.0.to_u256()) }
+
+    fn __str__(&self) -> String { self.0.to_string() }
+
+    fn __repr__(&self) -> String { format!("Fe256({})", self.0) }
+}
+
+/// Assembled zk-AluVM program.
+#[pyclass(module = "zkaluvm", frozen)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Program {
+    lib: Lib,
+    config: GfaConfig,
+}
+
+#[pymethods]
+impl Program {
+    /// Assembles a program from its source text for a field given either by its name (`25519`,
+    /// `secp`, `stark`, `goldilocks` or `babybear`) or by a hexadecimal order prefixed with `0x`.
+    #[new]
+    #[pyo3(signature = (source, field = "25519"))]
+    fn new(source: &str, field: &str) -> PyResult<Self> {
+        let field_order = parse_field(field)?;
+        let asm = Assembly::parse_with(source, &AsmConfig::with_field(field_order)).map_err(value_err)?;
+        let lib = asm.assemble().map_err(value_err)?;
+        Ok(Self::with(lib, field_order))
+    }
+
+    /// Loads a program from its ASCII-armored library (see [`crate::armor_lib`]).
+    #[staticmethod]
+    #[pyo3(signature = (armor, field = "25519"))]
+    fn from_armored(armor: &str, field: &str) -> PyResult<Self> {
+        let field_order = parse_field(field)?;
+        let lib = parse_armored_lib(armor).map_err(value_err)?;
+        Ok(Self::with(lib, field_order))
+    }
+
+    /// Identifier of the program library.
+    #[getter]
+    fn lib_id(&self) -> String { self.lib.lib_id().to_string() }
+
+    /// ASCII-armored library of the program.
+    #[getter]
+    fn armored(&self) -> String { crate::armor_lib(&self.lib) }
+
+    /// Order of the field used to run the program.
+    #[getter]
+    fn field_order<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> { to_int(py, self.config.field_order) }
+
+    /// Disassembled program code.
+    fn disassemble(&self) -> PyResult<String> { AsmFormatter::new().format_lib(&self.lib).map_err(value_err) }
+
+    /// Executes the program from its beginning, with the registers initialized from the `inputs`
+    /// dictionary mapping register names to field elements.
+    #[pyo3(signature = (inputs = None))]
+    fn run(&self, inputs: Option<&Bound<'_, PyDict>>) -> PyResult<Outcome> {
+        let mut debugger = self.start(inputs)?;
+        let ck = loop {
+            if let StopReason::Halted(ck) = debugger.run() {
+                break ck;
+            }
+        };
+        Ok(Outcome {
+            ok: ck.is_ok(),
+            co: debugger.co().is_ok(),
+            steps: debugger.vm().core.cx.steps(),
+            complexity: debugger.usage().total,
+            failure: debugger.failure().map(|failure| match failure.instr {
+                Some(instr) => format!("{}: {instr}", failure.site),
+                None => failure.site.to_string(),
+            }),
+            regs: RegE::ALL.map(|reg| debugger.reg(reg)),
+        })
+    }
+
+    /// Executes the program from its beginning like [`Program::run`], recording the state of the
+    /// registers before each of the executed instructions.
+    #[pyo3(signature = (inputs = None))]
+    fn trace(&self, inputs: Option<&Bound<'_, PyDict>>) -> PyResult<Trace> {
+        let mut debugger = self.start(inputs)?;
+        Ok(Trace(ExecTrace::record(&mut debugger)))
+    }
+
+    fn __str__(&self) -> PyResult<String> { self.disassemble() }
+}
+
+impl Program {
+    fn with(lib: Lib, field_order: u256) -> Self {
+        Program {
+            lib,
+            config: GfaConfig::new(field_order),
+        }
+    }
+
+    fn start(&self, inputs: Option<&Bound<'_, PyDict>>) -> PyResult<Debugger> {
+        let mut debugger = Debugger::new(Vm::with(default!(), self.config), [self.lib.clone()]);
+        for (reg, val) in inputs.into_iter().flat_map(|inputs| inputs.iter()) {
+            let reg = parse_reg(&reg.extract::<String>()?)?;
+            let val = Fe256::new(&val)?.0;
+            if val.to_u256() >= self.config.field_order {
+                return Err(value_err(format!("input value {val} for {reg} is not a canonical field element")));
+            }
+            debugger.set_reg(reg, Some(val));
+        }
+        debugger.start(LibSite::new(self.lib.lib_id(), 0));
+        Ok(debugger)
+    }
+}
+
+/// Outcome of a program execution.
+#[pyclass(module = "zkaluvm", frozen)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Outcome {
+    /// Whether the program has completed successfully (`CK` is not failed).
+    #[pyo3(get)]
+    ok: bool,
+    /// Whether the final value of the `CO` register is ok.
+    #[pyo3(get)]
+    co: bool,
+    /// Number of the executed instructions.
+    #[pyo3(get)]
+    steps: u64,
+    /// Complexity consumed by the execution.
+    #[pyo3(get)]
+    complexity: u64,
+    /// Site and the instruction which has failed `CK` first, if any.
+    #[pyo3(get)]
+    failure: Option<String>,
+    regs: [Option<fe256>; 16],
+}
+
+#[pymethods]
+impl Outcome {
+    /// Returns the final value of a register, or `None` if the register is not set.
+    fn reg(&self, name: &str) -> PyResult<Option<Fe256>> { Ok(self.regs[parse_reg(name)? as usize].map(Fe256)) }
+
+    /// Final values of the set registers.
+    #[getter]
+    fn registers(&self) -> BTreeMap<String, Fe256> { regs_dict(&self.regs) }
+
+    fn __bool__(&self) -> bool { self.ok }
+}
+
+/// State of the registers during a traced execution.
+#[pyclass(module = "zkaluvm", frozen)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct State(TraceState);
+
+#[pymethods]
+impl State {
+    /// Whether `CO` register is ok.
+    #[getter]
+    fn co(&self) -> bool { self.0.co.is_ok() }
+
+    /// Whether `CK` register is ok.
+    #[getter]
+    fn ck(&self) -> bool { self.0.ck.is_ok() }
+
+    /// Returns the value of a register, or `None` if the register is not set.
+    fn reg(&self, name: &str) -> PyResult<Option<Fe256>> { Ok(self.0.reg(parse_reg(name)?).map(Fe256)) }
+
+    /// Values of the set registers.
+    #[getter]
+    fn registers(&self) -> BTreeMap<String, Fe256> { regs_dict(&self.0.regs) }
+}
+
+/// Instruction executed during a traced execution.
+#[pyclass(module = "zkaluvm", frozen, get_all)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Step {
+    /// Site of the instruction.
+    site: String,
+    /// The executed instruction, or `None` if the site doesn't point to a valid instruction.
+    instr: Option<String>,
+    /// State of the registers before the instruction execution.
+    before: State,
+}
+
+/// Trace of a program execution.
+#[pyclass(module = "zkaluvm", frozen)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Trace(ExecTrace);
+
+#[pymethods]
+impl Trace {
+    /// Executed instructions, in the order of their execution.
+    #[getter]
+    fn steps(&self) -> Vec<Step> {
+        self.0
+            .steps
+            .iter()
+            .map(|step| Step {
+                site: step.site.to_string(),
+                instr: step.instr.map(|instr| instr.to_string()),
+                before: State(step.before),
+            })
+            .collect()
+    }
+
+    /// State of the registers at the end of the execution.
+    #[getter]
+    fn end(&self) -> State { State(self.0.end) }
+
+    fn __len__(&self) -> usize { self.0.len() }
+}
+
+fn parse_field(s: &str) -> PyResult<u256> {
+    parse_field_order(s).ok_or_else(|| value_err(format!("invalid field `{s}`")))
+}
+
+/// Initializes `zkaluvm` Python module.
+#[pymodule]
+pub fn zkaluvm(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Fe256>()?;
+    m.add_class::<Program>()?;
+    m.add_class::<Outcome>()?;
+    m.add_class::<State>()?;
+    m.add_class::<Step>()?;
+    m.add_class::<Trace>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use pyo3::py_run;
+
+    use super::*;
+
+    fn with_module(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let zk = PyModule::new(py, "zkaluvm").unwrap();
+            zkaluvm(&zk).unwrap();
+            py_run!(py, zk, code);
+        });
+    }
+
+    #[test]
+    fn fe256() {
+        with_module(
+            r#"
+            a = zk.Fe256(2**255 + 7)
+            assert int(a) == 2**255 + 7
+            assert zk.Fe256("0x10") == zk.Fe256(16) == zk.Fe256("10.fe")
+            assert zk.Fe256(zk.Fe256(5)) == zk.Fe256(5)
+            assert len({zk.Fe256(1), zk.Fe256(1), zk.Fe256(2)}) == 2
+            assert repr(zk.Fe256(16)) == "Fe256(10.fe)"
+            for bad in [-1, 2**256, "x"]:
+                try:
+                    zk.Fe256(bad)
+                    assert False
+                except (ValueError, OverflowError):
+                    pass
+            "#,
+        );
+    }
+
+    #[test]
+    fn run() {
+        with_module(
+            r#"
+            program = zk.Program("""
+                routine MAIN:
+                    mov     E1, EA
+                    mul     E1, EB
+                    add     E1, EA
+                    fits    E1, 8.bits
+                    chk     CO
+                    ret
+            """, "stark")
+            assert int(program.field_order) == 2**251 + 17 * 2**128 + 1
+            assert "mul     E1, EB" in program.disassemble()
+            assert zk.Program.from_armored(program.armored, "stark").lib_id == program.lib_id
+
+            outcome = program.run({"EA": 3, "EB": zk.Fe256("0x10")})
+            assert outcome and outcome.ok and outcome.co
+            assert outcome.steps == 7
+            assert outcome.complexity > 0
+            assert outcome.failure is None
+            assert int(outcome.reg("E1")) == 51
+            assert outcome.reg("E2") is None
+            assert {k: int(v) for k, v in outcome.registers.items()} == {"E1": 51, "EA": 3, "EB": 16}
+
+            outcome = program.run({"EA": 3, "EB": 256})
+            assert not outcome
+            assert outcome.failure.endswith("chk     CO")
+
+            trace = program.trace({"EA": 3, "EB": 16})
+            assert len(trace) == 7
+            assert trace.steps[1].instr == "mov     E1, EA"
+            assert trace.steps[2].before.reg("E1") == zk.Fe256(3)
+            assert trace.end.ck and trace.end.co
+            assert int(trace.end.registers["E1"]) == 51
+
+            for args in [("nope",), ("ret", "pallas")]:
+                try:
+                    zk.Program(*args)
+                    assert False
+                except ValueError:
+                    pass
+            try:
+                program.run({"EZ": 1})
+                assert False
+            except ValueError:
+                pass
+            try:
+                program.run({"EA": 2**251 + 17 * 2**128 + 1})
+                assert False
+            except ValueError:
+                pass
+            "#,
+        );
+    }
+}
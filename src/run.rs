@@ -0,0 +1,320 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Convenience API for running zk-AluVM programs.
+
+use aluvm::isa::{Bytecode, BytecodeRead, ExecStep, Instruction};
+use aluvm::regs::Status;
+use aluvm::{AssemblerError, CoreConfig, CoreExt, Lib, LibId, LibSite, LibsSeg, Marshaller, Site, Vm};
+use amplify::num::u256;
+
+use crate::gfa::{fail_ck, FieldInstr, Instr, NO_CONTEXT};
+use crate::{fe256, ComplexityUsage, Debugger, FieldPreset, GfaConfig, InstrSite, Profiler, RegE};
+
+/// Budget of the VMs constructed with [`ZkDefault::zk_default`], measured in bits of the field
+/// elements multiplied during the execution.
+///
+/// The budget corresponds to 2^16 multiplications in a 256-bit field, and grows proportionally for
+/// the smaller fields, whose arithmetic is cheaper to prove.
+pub const ZK_MUL_BIT_BUDGET: u64 = 1 << 24;
+
+/// Computes the complexity limit used by [`ZkDefault::zk_default`] for the field of the given
+/// order: the complexity of the number of multiplications fitting [`ZK_MUL_BIT_BUDGET`].
+pub fn zk_complexity_lim(field_order: u256) -> u64 {
+    let bits = (field_order.bits_required() as u64).max(1);
+    let mul = FieldInstr::Mul {
+        dst_src: RegE::E1,
+        src: RegE::E2,
+    };
+    ZK_MUL_BIT_BUDGET / bits * Instruction::<LibId>::complexity(&mul)
+}
+
+/// Construction of a VM with the configuration suitable for zero-knowledge applications.
+pub trait ZkDefault {
+    /// Constructs a VM for the field preset, which halts on the first failure and has complexity
+    /// limit derived from the field size (see [`zk_complexity_lim`]).
+    fn zk_default(preset: FieldPreset) -> Self;
+}
+
+impl ZkDefault for Vm<Instr<LibId>> {
+    /// # Example
+    ///
+    /// ```
+    /// # use aluvm::{LibId, Vm};
+    /// # use zkaluvm::gfa::Instr;
+    /// # use zkaluvm::{zk_complexity_lim, FieldPreset, ZkDefault, FIELD_ORDER_GOLDILOCKS};
+    /// let vm = Vm::<Instr<LibId>>::zk_default(FieldPreset::Goldilocks);
+    /// assert_eq!(vm.core.cl(), Some(zk_complexity_lim(FIELD_ORDER_GOLDILOCKS)));
+    /// assert_eq!(vm.core.cx.fq(), FIELD_ORDER_GOLDILOCKS);
+    /// ```
+    fn zk_default(preset: FieldPreset) -> Self {
+        let config = CoreConfig {
+            halt: true,
+            complexity_lim: Some(zk_complexity_lim(preset.field_order())),
+        };
+        Vm::with(config, GfaConfig::with_preset(preset))
+    }
+}
+
+/// Outcome of a program execution with [`run`].
+#[derive(Clone, Debug)]
+pub struct ExecOutcome {
+    /// Value of the `CK` register at the end of the execution.
+    pub ck: Status,
+    /// Value of the `CO` register at the end of the execution.
+    pub co: Status,
+    /// Number of the executed instructions.
+    pub steps: u64,
+    /// Complexity consumed by the execution.
+    pub usage: ComplexityUsage,
+    /// Instruction which has failed `CK` first, if any.
+    pub failure: Option<InstrSite>,
+    /// Statistics of the executed instructions.
+    pub profile: Profiler,
+    /// The VM after the execution, holding the final register values.
+    pub vm: Vm<Instr<LibId>>,
+}
+
+impl ExecOutcome {
+    /// Checks whether the program has completed successfully.
+    pub fn is_ok(&self) -> bool { self.ck.is_ok() }
+
+    /// Returns the final value of a register.
+    pub fn reg(&self, reg: RegE) -> Option<fe256> { self.vm.core.cx.get(reg) }
+}
+
+/// Assembles the program code into a library and executes it from the beginning, using the
+/// default VM configuration (halting on the first failure, with no complexity limit).
+///
+/// The program can't call other libraries; such calls fail `CK`.
+///
+/// # Example
+///
+/// ```
+/// # use zkaluvm::gfa::{Expr, ExprCompiler};
+/// # use zkaluvm::{fe256, run, GfaConfig, RegE::*};
+/// let mut compiler = ExprCompiler::new();
+/// compiler.assign(E1, Expr::mul(Expr::Const(fe256::from(3u8)), 7u8));
+///
+/// let outcome = run(&compiler.compile().unwrap(), GfaConfig::default()).unwrap();
+/// assert!(outcome.is_ok());
+/// assert_eq!(outcome.reg(E1), Some(fe256::from(21u8)));
+/// ```
+pub fn run(code: &[Instr<LibId>], config: GfaConfig) -> Result<ExecOutcome, AssemblerError> {
+    run_with(code, CoreConfig::default(), config)
+}
+
+/// Assembles the program code into a library and executes it from the beginning, using the
+/// provided configuration of the VM core.
+///
+/// The program can't call other libraries; such calls fail `CK`.
+pub fn run_with(
+    code: &[Instr<LibId>],
+    core_config: CoreConfig,
+    config: GfaConfig,
+) -> Result<ExecOutcome, AssemblerError> {
+    let lib = Lib::assemble(code)?;
+    let entry = LibSite::new(lib.lib_id(), 0);
+    let mut debugger = Debugger::new(Vm::with(core_config, config), [lib]);
+    let mut profile = Profiler::new();
+    debugger.start(entry);
+    let ck = profile.exec(&mut debugger);
+    let usage = debugger.usage();
+    let failure = debugger.failure();
+    let vm = debugger.into_vm();
+    Ok(ExecOutcome {
+        ck,
+        co: vm.core.co(),
+        steps: vm.core.cx.steps(),
+        usage,
+        failure,
+        profile,
+        vm,
+    })
+}
+
+/// Executes a program directly from its code and data segments, starting at the `entry` offset,
+/// without constructing a library.
+///
+/// This skips computing the library identifier and the checks of the segment sizes, which makes
+/// it suitable for embedded hosts and fuzzers. The program can't call other libraries: any such
+/// call fails `CK` and halts the program. Local calls, jumps and complexity limits are handled in
+/// the same way as by the VM; sites passed to the instructions use the default library id.
+///
+/// # Returns
+///
+/// Value of the `CK` register at the end of the program execution.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::regs::Status;
+/// # use aluvm::{CoreExt, Lib, LibId, Vm};
+/// # use zkaluvm::gfa::Instr;
+/// # use zkaluvm::{exec_raw, fe256, zk_aluasm, RegE};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+///
+/// let mut vm = Vm::<Instr<LibId>>::new();
+/// assert_eq!(exec_raw(&lib.code, &lib.data, 0, &mut vm), Status::Ok);
+/// assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(49u8)));
+/// ```
+pub fn exec_raw(code: &[u8], data: &[u8], entry: u16, vm: &mut Vm<Instr<LibId>>) -> Status {
+    let libs = LibsSeg::default();
+    let core = &mut vm.core;
+    let mut marshaller = Marshaller::with(code, data, &libs);
+    if marshaller.seek(entry).is_err() {
+        fail_ck(core, Site::new(LibId::default(), entry));
+        return core.ck();
+    }
+    while !marshaller.is_eof() {
+        let site = Site::new(LibId::default(), marshaller.pos());
+        let Ok(instr) = Instr::<LibId>::decode_instr(&mut marshaller) else {
+            break;
+        };
+        let step = instr.exec(site, core, &NO_CONTEXT);
+        if !core.acc_complexity(instr.complexity()) {
+            fail_ck(core, site);
+            break;
+        }
+        match step {
+            ExecStep::Stop => break,
+            ExecStep::Fail if fail_ck(core, site) => break,
+            ExecStep::Fail | ExecStep::Next => {}
+            ExecStep::Jump(pos) => {
+                if marshaller.seek(pos).is_err() {
+                    fail_ck(core, site);
+                    break;
+                }
+            }
+            ExecStep::Call(_) => {
+                fail_ck(core, site);
+                break;
+            }
+            ExecStep::Ret(caller) => {
+                // Execution continues after the calling instruction.
+                if marshaller.seek(caller.offset).is_err() {
+                    fail_ck(core, site);
+                    break;
+                }
+                if Instr::<LibId>::decode_instr(&mut marshaller).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    core.ck()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::{format, vec};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::RegE::*;
+
+    #[test]
+    fn zk_default() {
+        let mut lims = vec![];
+        for preset in FieldPreset::ALL {
+            let vm = Vm::<Instr<LibId>>::zk_default(preset);
+            assert_eq!(vm.core.cx.fq(), preset.field_order());
+            assert_eq!(vm.core.cx.solinas(), preset.solinas());
+            assert_eq!(vm.core.cl(), Some(zk_complexity_lim(preset.field_order())));
+            lims.push(vm.core.cl().unwrap());
+        }
+        // Smaller fields get larger budgets
+        assert!(lims[0] < lims[3] && lims[3] < lims[4]);
+
+        // The VM halts on the first failure
+        let lib = Lib::assemble(&Assembly::parse("add E1, E2\nput E3, 1").unwrap().code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::zk_default(FieldPreset::BabyBear);
+        let lib_id = lib.lib_id();
+        let status = vm.exec(LibSite::new(lib_id, 0), &NO_CONTEXT, |id| (id == lib_id).then_some(&lib));
+        assert_eq!(status, Status::Fail);
+        assert_eq!(vm.core.cx.get(E3), None);
+    }
+
+    #[test]
+    fn outcome() {
+        let code = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 5
+                test    E2
+                add     E1, E2
+        "#,
+        )
+        .unwrap()
+        .code;
+        let outcome = run(&code, GfaConfig::default()).unwrap();
+        assert!(!outcome.is_ok());
+        assert_eq!(outcome.ck, Status::Fail);
+        assert_eq!(outcome.co, Status::Fail);
+        assert_eq!(outcome.reg(E1), Some(fe256::from(5u8)));
+        assert_eq!(outcome.steps, 4);
+        assert_eq!(outcome.profile.total().count, 4);
+        assert_eq!(outcome.usage.total, outcome.profile.total().complexity);
+        let failure = outcome.failure.unwrap();
+        assert_eq!(failure.site.offset, 7);
+        assert_eq!(failure.instr, Some(code[3]));
+
+        let config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let outcome = run_with(&code[..3], config, GfaConfig::default()).unwrap();
+        assert!(outcome.is_ok());
+    }
+
+    #[test]
+    fn raw() {
+        let source = r#"
+            routine MAIN:
+                put     E1, 3
+                call    SQUARE
+                call    SQUARE
+                stop
+            routine SQUARE:
+                mul     E1, E1
+                ret
+        "#;
+        let lib = Assembly::parse(source).unwrap().assemble().unwrap();
+        let lib_id = lib.lib_id();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec(LibSite::new(lib_id, 0), &NO_CONTEXT, |id| (id == lib_id).then_some(&lib));
+        let mut raw = Vm::<Instr<LibId>>::new();
+        assert_eq!(exec_raw(&lib.code, &lib.data, 0, &mut raw), status);
+        assert_eq!(raw.core.cx.get(E1), Some(fe256::from(81u8)));
+        assert_eq!(format!("{:?}", raw.core), format!("{:?}", vm.core));
+
+        let mut raw = Vm::<Instr<LibId>>::new();
+        assert_eq!(exec_raw(&lib.code, &lib.data, 0xFF, &mut raw), Status::Fail);
+
+        let code = Assembly::parse("jmp 0x100").unwrap().code;
+        let lib = Lib::assemble(&code).unwrap();
+        let mut raw = Vm::<Instr<LibId>>::new();
+        assert_eq!(exec_raw(&lib.code, &lib.data, 0, &mut raw), Status::Fail);
+    }
+}
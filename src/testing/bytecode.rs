@@ -0,0 +1,165 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ops::RangeInclusive;
+
+use aluvm::isa::{Bytecode, Instruction};
+use aluvm::{Lib, LibId, LibsSeg, Marshaller};
+use amplify::confinement::SmallBlob;
+
+/// Checks that an instruction is encoded into the given bytecode and data segment, and that it is
+/// decoded back into the same instruction.
+///
+/// If `dataseg` is `None`, checks that the instruction doesn't write to the data segment.
+///
+/// # Returns
+///
+/// Data segment produced by the instruction.
+///
+/// # Panics
+///
+/// If any of the checks fails.
+pub fn roundtrip<I>(instr: I, bytecode: impl AsRef<[u8]>, dataseg: Option<&[u8]>) -> SmallBlob
+where I: Bytecode<LibId> + PartialEq + Debug {
+    let libs = LibsSeg::from_iter_checked(instr.external_ref());
+    let mut marshaller = Marshaller::new(&libs);
+    instr.encode_instr(&mut marshaller).unwrap();
+    let (code, data) = marshaller.finish();
+    assert_eq!(code.as_slice(), bytecode.as_ref());
+    assert_eq!(code.len(), instr.code_byte_len() as usize);
+    if let Some(d) = dataseg {
+        assert_eq!(data.as_slice(), d);
+    } else {
+        assert!(data.is_empty());
+    }
+    let mut marshaller = Marshaller::with(code, &data, &libs);
+    let decoded = I::decode_instr(&mut marshaller).unwrap();
+    assert_eq!(decoded, instr);
+    data
+}
+
+/// Checks that a program is assembled into a library with the code segment matching the sizes of
+/// the instructions, and that the library is disassembled back into the same program.
+///
+/// # Returns
+///
+/// Assembled library.
+///
+/// # Panics
+///
+/// If any of the checks fails.
+pub fn roundtrip_code<I>(code: &[I]) -> Lib
+where I: Instruction<LibId> + PartialEq + Debug {
+    let lib = Lib::assemble(code).unwrap();
+    let len = code
+        .iter()
+        .map(|instr| instr.code_byte_len() as usize)
+        .sum::<usize>();
+    assert_eq!(lib.code.len(), len);
+    assert_eq!(lib.disassemble::<I>().unwrap(), code);
+    lib
+}
+
+/// Number of bytes following the opcode and the operand byte in the sequences checked by
+/// [`decode_coverage`]; enough to hold the largest operands of AluVM instructions.
+const DECODE_TAIL_LEN: usize = 3;
+
+/// Statistics of an exhaustive decoding sweep performed by [`decode_coverage`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct DecodeCoverage {
+    /// Number of byte sequences which were decoded, for each opcode.
+    pub decoded: BTreeMap<u8, usize>,
+    /// Number of byte sequences which were rejected by the decoder.
+    pub rejected: usize,
+}
+
+impl DecodeCoverage {
+    /// Returns the total number of the decoded byte sequences.
+    pub fn decoded_count(&self) -> usize { self.decoded.values().sum() }
+
+    /// Iterates over the opcodes for which at least one byte sequence was decoded.
+    pub fn opcodes(&self) -> impl Iterator<Item = u8> + '_ { self.decoded.keys().copied() }
+}
+
+/// Exhaustively checks decoding of all the byte sequences starting with an opcode from the range.
+///
+/// For each opcode, the sweep covers all the values of the following byte (and thus all the
+/// combinations of the 4-bit sub-operation codes and register operands packed into it), each
+/// followed by a zero-filled tail and also truncated after the opcode and after the operand byte.
+/// The data segment of the sweep contains a single zero field element, and the library segment a
+/// single library.
+///
+/// Each sequence must either be rejected by the decoder, or be decoded into an instruction, which
+/// consumes exactly [`Bytecode::code_byte_len`] bytes and is re-encoded into the same bytes,
+/// decoding back into the same instruction.
+///
+/// # Returns
+///
+/// Statistics of the decoded and rejected sequences.
+///
+/// # Panics
+///
+/// If any of the checks fails, with the message detailing the failing byte sequence.
+pub fn decode_coverage<I>(opcodes: RangeInclusive<u8>) -> DecodeCoverage
+where I: Bytecode<LibId> + PartialEq + Debug {
+    let libs = LibsSeg::from_checked(bset![LibId::default()]);
+    let data = [0u8; 32];
+    let mut coverage = DecodeCoverage::default();
+    for opcode in opcodes {
+        let mut sequences = Vec::with_capacity(2 * 0x100 + 1);
+        sequences.push(vec![opcode]);
+        for byte in 0..=0xFF {
+            sequences.push(vec![opcode, byte]);
+            let mut code = vec![opcode, byte];
+            code.extend([0u8; DECODE_TAIL_LEN]);
+            sequences.push(code);
+        }
+        for code in sequences {
+            let mut reader = Marshaller::with(code.as_slice(), data.as_slice(), &libs);
+            let Ok(instr) = I::decode_instr(&mut reader) else {
+                coverage.rejected += 1;
+                continue;
+            };
+            let (pos, bit) = reader.offset();
+            let len = instr.code_byte_len() as usize;
+            assert_eq!(bit.to_u8(), 0, "decoding {code:02X?} into {instr:?} ends in the middle of a byte");
+            assert_eq!(pos as usize, len, "decoding {code:02X?} into {instr:?} consumes wrong number of bytes");
+
+            let mut writer = Marshaller::new(&libs);
+            instr
+                .encode_instr(&mut writer)
+                .unwrap_or_else(|_| panic!("instruction {instr:?} decoded from {code:02X?} can't be encoded"));
+            let (encoded, encoded_data) = writer.finish();
+            assert_eq!(encoded.as_slice(), &code[..len], "instruction {instr:?} is not re-encoded identically");
+
+            let mut reader = Marshaller::with(encoded.as_slice(), encoded_data.as_slice(), &libs);
+            let decoded = I::decode_instr(&mut reader).ok();
+            assert_eq!(decoded.as_ref(), Some(&instr), "re-encoded {code:02X?} decodes into a different instruction");
+            *coverage.decoded.entry(opcode).or_default() += 1;
+        }
+    }
+    coverage
+}
@@ -0,0 +1,645 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Differential testing of the ISA implementation.
+//!
+//! The module provides a generator of random well-formed programs ([`random_program`]) and a
+//! reference interpreter of the field arithmetic instructions ([`ref_exec`]), which is implemented
+//! independently of the VM core using arbitrary-precision integers from [`num_bigint`]. The
+//! [`diff_exec`] and [`diff_selftest`] functions run programs both in the VM and in the reference
+//! interpreter and report all divergences in the resulting state.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::CtrlInstr;
+use aluvm::regs::Status;
+use aluvm::{AssemblerError, CoreConfig, LibId};
+use amplify::num::{u256, u3};
+use num_bigint::BigUint;
+
+use super::TestRng;
+use crate::gfa::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{fe256, run_with, GfaConfig, RegE};
+
+/// Relative weights of the instructions in the programs produced by [`random_program`].
+///
+/// An instruction with zero weight is never generated; at least one of the weights must be
+/// non-zero.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct OpcodeMix {
+    /// Weight of the instructions putting a value into a register (`put`).
+    pub put: u32,
+    /// Weight of the `mov` instruction.
+    pub mov: u32,
+    /// Weight of the `clr` instruction.
+    pub clr: u32,
+    /// Weight of the `test` instruction.
+    pub test: u32,
+    /// Weight of the `eq` instruction.
+    pub eq: u32,
+    /// Weight of the `fits` instruction.
+    pub fits: u32,
+    /// Weight of the `neg` instruction.
+    pub neg: u32,
+    /// Weight of the `add` instruction.
+    pub add: u32,
+    /// Weight of the `mul` instruction.
+    pub mul: u32,
+}
+
+impl Default for OpcodeMix {
+    fn default() -> Self {
+        OpcodeMix {
+            put: 3,
+            mov: 1,
+            clr: 1,
+            test: 1,
+            eq: 1,
+            fits: 1,
+            neg: 2,
+            add: 3,
+            mul: 3,
+        }
+    }
+}
+
+impl OpcodeMix {
+    /// Mix consisting of the arithmetic instructions only (and `put`s providing their operands).
+    pub const ARITHMETIC: Self = OpcodeMix {
+        put: 1,
+        mov: 0,
+        clr: 0,
+        test: 0,
+        eq: 0,
+        fits: 0,
+        neg: 1,
+        add: 2,
+        mul: 2,
+    };
+
+    fn weights(&self) -> [u32; 9] {
+        [self.put, self.mov, self.clr, self.test, self.eq, self.fits, self.neg, self.add, self.mul]
+    }
+
+    fn total(&self) -> u64 { self.weights().iter().map(|w| *w as u64).sum() }
+
+    fn pick(&self, rng: &mut TestRng) -> usize {
+        let mut point = rng.below(self.total());
+        for (no, weight) in self.weights().into_iter().enumerate() {
+            if point < weight as u64 {
+                return no;
+            }
+            point -= weight as u64;
+        }
+        unreachable!("the point is always below the total weight")
+    }
+}
+
+/// Generates a random well-formed program of `len` field arithmetic instructions for the field
+/// with the given order.
+///
+/// All values put into registers are canonical, and all instructions except `test` read only the
+/// registers which were initialized by the previous instructions, so a correct implementation
+/// always completes the generated programs successfully.
+///
+/// # Panics
+///
+/// If all weights of the opcode `mix` are zero or the field order is zero.
+pub fn random_program(rng: &mut TestRng, mix: &OpcodeMix, len: usize, order: u256) -> Vec<Instr<LibId>> {
+    assert!(mix.total() > 0, "opcode mix must have at least one non-zero weight");
+    assert_ne!(order, u256::ZERO, "field order must be non-zero");
+
+    let mut set = [false; 16];
+    let mut code = Vec::with_capacity(len);
+    for _ in 0..len {
+        let regs = RegE::ALL
+            .into_iter()
+            .filter(|reg| set[reg.to_u4().to_u8() as usize])
+            .collect::<Vec<_>>();
+        let any = |rng: &mut TestRng| RegE::ALL[rng.below(16) as usize];
+        let src = |rng: &mut TestRng| regs[rng.below(regs.len() as u64) as usize];
+        let kind = if regs.is_empty() { 0 } else { mix.pick(rng) };
+        let instr = match kind {
+            0 => FieldInstr::put(any(rng), random_value(rng, order)),
+            1 => FieldInstr::Mov {
+                dst: any(rng),
+                src: src(rng),
+            },
+            2 => FieldInstr::Clr { dst: any(rng) },
+            3 => FieldInstr::Test { src: any(rng) },
+            4 => FieldInstr::Eq {
+                src1: src(rng),
+                src2: src(rng),
+            },
+            5 => FieldInstr::Fits {
+                src: src(rng),
+                bits: Bits::from(u3::with(rng.below(8) as u8)),
+            },
+            6 => FieldInstr::Neg {
+                dst: any(rng),
+                src: src(rng),
+            },
+            7 => FieldInstr::Add {
+                dst_src: src(rng),
+                src: src(rng),
+            },
+            _ => FieldInstr::Mul {
+                dst_src: src(rng),
+                src: src(rng),
+            },
+        };
+        match instr {
+            FieldInstr::PutZ { dst }
+            | FieldInstr::PutD { dst, .. }
+            | FieldInstr::PutV { dst, .. }
+            | FieldInstr::Mov { dst, .. }
+            | FieldInstr::Neg { dst, .. } => set[dst.to_u4().to_u8() as usize] = true,
+            FieldInstr::Clr { dst } => set[dst.to_u4().to_u8() as usize] = false,
+            _ => {}
+        }
+        code.push(Instr::Gfa(instr));
+    }
+    code
+}
+
+/// Generates a random canonical field element, preferring the edge cases (zero, one and the
+/// largest field element) in a quarter of the cases.
+fn random_value(rng: &mut TestRng, order: u256) -> fe256 {
+    let val = match rng.below(8) {
+        0 => u256::ZERO,
+        1 => u256::ONE % order,
+        2 => order - u256::ONE,
+        _ => u256::from_le_bytes(rng.bytes32()) % order,
+    };
+    fe256::from(val)
+}
+
+/// State of the registers after a program execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DiffState {
+    /// Value of the `CK` register.
+    pub ck: Status,
+    /// Value of the `CO` register.
+    pub co: Status,
+    /// Values of the field element registers, indexed by the register number.
+    pub regs: [Option<fe256>; 16],
+}
+
+impl Display for DiffState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "CK={}, CO={}", self.ck, self.co)?;
+        for (reg, val) in RegE::ALL.into_iter().zip(self.regs) {
+            if let Some(val) = val {
+                write!(f, ", {reg}={val}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors running differential tests.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum DiffError {
+    /// instruction `{0}` is not supported by the reference interpreter.
+    Unsupported(Instr<LibId>),
+
+    /// unable to assemble the program: {0}
+    #[from]
+    Assembler(AssemblerError),
+}
+
+/// Divergence of the VM from the reference interpreter.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Divergence {
+    /// Program which has caused the divergence.
+    pub code: Vec<Instr<LibId>>,
+    /// State of the VM after the program execution.
+    pub vm: DiffState,
+    /// State of the reference interpreter after the program execution.
+    pub reference: DiffState,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "VM diverges from the reference interpreter on the program:")?;
+        for instr in &self.code {
+            writeln!(f, "    {instr}")?;
+        }
+        if self.vm.ck != self.reference.ck {
+            writeln!(f, "CK: VM {}, reference {}", self.vm.ck, self.reference.ck)?;
+        }
+        if self.vm.co != self.reference.co {
+            writeln!(f, "CO: VM {}, reference {}", self.vm.co, self.reference.co)?;
+        }
+        for (reg, (vm, reference)) in RegE::ALL
+            .into_iter()
+            .zip(self.vm.regs.into_iter().zip(self.reference.regs))
+        {
+            if vm != reference {
+                let show = |val: Option<fe256>| val.map(|v| v.to_string()).unwrap_or_else(|| s!("~"));
+                writeln!(f, "{reg}: VM {}, reference {}", show(vm), show(reference))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_big(val: fe256) -> BigUint { BigUint::from_bytes_le(&val.to_u256().to_le_bytes()) }
+
+fn from_big(val: &BigUint) -> fe256 {
+    let mut buf = [0u8; 32];
+    let bytes = val.to_bytes_le();
+    buf[..bytes.len()].copy_from_slice(&bytes);
+    fe256::from(u256::from_le_bytes(buf))
+}
+
+/// Finds the smaller square root of `a` modulo an odd prime `order` with the Tonelli–Shanks
+/// algorithm.
+fn sqrt_big(a: &BigUint, order: &BigUint) -> Option<BigUint> {
+    let one = BigUint::from(1u8);
+    if a.bits() == 0 {
+        return Some(a.clone());
+    }
+    if !order.bit(0) {
+        return None;
+    }
+    let max = order - &one;
+    let half = &max >> 1;
+    if a.modpow(&half, order) != one {
+        return None;
+    }
+    let s = max.trailing_zeros()?;
+    let q = &max >> s;
+    let z = (2u8..=u8::MAX)
+        .map(BigUint::from)
+        .take_while(|z| z < order)
+        .find(|z| z.modpow(&half, order) == max)?;
+
+    let mut m = s;
+    let mut c = z.modpow(&q, order);
+    let mut t = a.modpow(&q, order);
+    let mut r = a.modpow(&((&q + &one) >> 1), order);
+    while t != one {
+        let i = (1..m).find(|i| t.modpow(&(&one << i), order) == one)?;
+        let b = c.modpow(&(&one << (m - i - 1)), order);
+        m = i;
+        c = &b * &b % order;
+        t = t * &c % order;
+        r = r * b % order;
+    }
+    if &r * &r % order != *a {
+        return None;
+    }
+    let neg = order - &r;
+    Some(r.min(neg))
+}
+
+/// Executes a program with the reference interpreter for the field of the given order.
+///
+/// The interpreter starts with all registers unset, and halts on the first instruction failing
+/// `CK`. It supports all field arithmetic instructions, but only `nop` and `stop` control flow
+/// instructions.
+pub fn ref_exec(code: &[Instr<LibId>], order: u256) -> Result<DiffState, DiffError> {
+    let order = BigUint::from_bytes_le(&order.to_le_bytes());
+    let mut regs: [Option<BigUint>; 16] = Default::default();
+    let mut co = Status::Ok;
+    let mut ck = Status::Ok;
+    let idx = |reg: RegE| reg.to_u4().to_u8() as usize;
+
+    for instr in code {
+        let instr = match instr {
+            Instr::Gfa(instr) => instr,
+            Instr::Ctrl(CtrlInstr::Nop) => continue,
+            Instr::Ctrl(CtrlInstr::Stop) => break,
+            _ => return Err(DiffError::Unsupported(*instr)),
+        };
+        let put = |regs: &mut [Option<BigUint>; 16], dst: RegE, val: BigUint| {
+            if val < order {
+                regs[idx(dst)] = Some(val);
+                Status::Ok
+            } else {
+                regs[idx(dst)] = None;
+                Status::Fail
+            }
+        };
+        let status = match *instr {
+            FieldInstr::Test { src } => {
+                co = if regs[idx(src)].is_some() { Status::Ok } else { Status::Fail };
+                Status::Ok
+            }
+            FieldInstr::Clr { dst } => {
+                regs[idx(dst)] = None;
+                Status::Ok
+            }
+            FieldInstr::PutD { dst, data } => put(&mut regs, dst, to_big(data)),
+            FieldInstr::PutZ { dst } => put(&mut regs, dst, BigUint::ZERO),
+            FieldInstr::PutV { dst, val } => {
+                let val = match val {
+                    ConstVal::Val1 => BigUint::from(1u8),
+                    ConstVal::ValU64Max => BigUint::from(u64::MAX),
+                    ConstVal::ValU128Max => BigUint::from(u128::MAX),
+                    ConstVal::ValFeMAX => &order - 1u8,
+                };
+                put(&mut regs, dst, val)
+            }
+            FieldInstr::Mov { dst, src } => {
+                regs[idx(dst)] = regs[idx(src)].clone();
+                Status::Ok
+            }
+            FieldInstr::CMov { dst, src } => {
+                if co == Status::Ok {
+                    regs[idx(dst)] = regs[idx(src)].clone();
+                }
+                Status::Ok
+            }
+            FieldInstr::Sel { dst, src_true, src_false } => {
+                let src = if co == Status::Ok { src_true } else { src_false };
+                regs[idx(dst)] = regs[idx(src)].clone();
+                Status::Ok
+            }
+            FieldInstr::Eq { src1, src2 } => {
+                co = match (&regs[idx(src1)], &regs[idx(src2)]) {
+                    (Some(a), Some(b)) if a == b => Status::Ok,
+                    _ => Status::Fail,
+                };
+                Status::Ok
+            }
+            FieldInstr::Fits { src, bits } => match &regs[idx(src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    co = if a.bits() <= bits.bit_len() as u64 { Status::Ok } else { Status::Fail };
+                    Status::Ok
+                }
+            },
+            FieldInstr::Lt { src1, src2 } => match (&regs[idx(src1)], &regs[idx(src2)]) {
+                (Some(a), Some(b)) => {
+                    co = if a < b { Status::Ok } else { Status::Fail };
+                    Status::Ok
+                }
+                _ => Status::Fail,
+            },
+            FieldInstr::Rng { src, bound } => match &regs[idx(src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    co = if *a < to_big(bound) { Status::Ok } else { Status::Fail };
+                    Status::Ok
+                }
+            },
+            FieldInstr::Neg { dst, src } => match &regs[idx(src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    regs[idx(dst)] = Some((&order - a) % &order);
+                    Status::Ok
+                }
+            },
+            FieldInstr::Add { dst_src, src } => match (&regs[idx(dst_src)], &regs[idx(src)]) {
+                (Some(a), Some(b)) => {
+                    regs[idx(dst_src)] = Some((a + b) % &order);
+                    Status::Ok
+                }
+                _ => Status::Fail,
+            },
+            FieldInstr::Mul { dst_src, src } => match (&regs[idx(dst_src)], &regs[idx(src)]) {
+                (Some(a), Some(b)) => {
+                    regs[idx(dst_src)] = Some((a * b) % &order);
+                    Status::Ok
+                }
+                _ => Status::Fail,
+            },
+            FieldInstr::Inv { dst, src } => match regs[idx(src)].as_ref().and_then(|a| a.modinv(&order)) {
+                None => Status::Fail,
+                Some(inv) => {
+                    regs[idx(dst)] = Some(inv);
+                    Status::Ok
+                }
+            },
+            FieldInstr::Exp { dst_src, exp } => match (&regs[idx(dst_src)], &regs[idx(exp)]) {
+                (Some(a), Some(e)) => {
+                    regs[idx(dst_src)] = Some(a.modpow(e, &order));
+                    Status::Ok
+                }
+                _ => Status::Fail,
+            },
+            FieldInstr::ExpI { dst_src, exp } => match &regs[idx(dst_src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    regs[idx(dst_src)] = Some(a.modpow(&BigUint::from(exp), &order));
+                    Status::Ok
+                }
+            },
+            FieldInstr::Sqr { dst, src } => match &regs[idx(src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    regs[idx(dst)] = Some((a * a) % &order);
+                    Status::Ok
+                }
+            },
+            FieldInstr::Sqrt { dst, src } => match &regs[idx(src)] {
+                None => Status::Fail,
+                Some(a) => {
+                    match sqrt_big(a, &order) {
+                        Some(root) => {
+                            regs[idx(dst)] = Some(root);
+                            co = Status::Ok;
+                        }
+                        None => co = Status::Fail,
+                    }
+                    Status::Ok
+                }
+            },
+            // The interpreter has no host, matching the VM without the execution context
+            FieldInstr::Ecall { .. } => Status::Fail,
+        };
+        if status == Status::Fail {
+            ck = Status::Fail;
+            break;
+        }
+    }
+
+    Ok(DiffState {
+        ck,
+        co,
+        regs: regs.map(|val| val.as_ref().map(from_big)),
+    })
+}
+
+/// Runs a program both in the VM with the given configuration and in the reference interpreter,
+/// returning the divergence of their final states, if any.
+///
+/// The VM halts on the first failure, like the reference interpreter does. The configuration step
+/// limit and the alternative arithmetic modes (constant-time execution, Solinas reduction) are
+/// applied to the VM only, so they are checked against the plain reference arithmetic.
+pub fn diff_exec(code: &[Instr<LibId>], config: GfaConfig) -> Result<Option<Divergence>, DiffError> {
+    let reference = ref_exec(code, config.field_order)?;
+    let core_config = CoreConfig {
+        halt: true,
+        complexity_lim: None,
+    };
+    let outcome = run_with(code, core_config, config)?;
+    let vm = DiffState {
+        ck: outcome.ck,
+        co: outcome.co,
+        regs: RegE::ALL.map(|reg| outcome.reg(reg)),
+    };
+    Ok((vm != reference).then(|| Divergence {
+        code: code.to_vec(),
+        vm,
+        reference,
+    }))
+}
+
+/// Runs a differential self-test of the ISA: generates `runs` random programs of `len`
+/// instructions with the given opcode mix from the `seed`, and runs each of them with
+/// [`diff_exec`].
+///
+/// # Returns
+///
+/// All found divergences; an empty vector if the VM matches the reference interpreter.
+pub fn diff_selftest(seed: u64, runs: usize, len: usize, mix: &OpcodeMix, config: GfaConfig) -> Vec<Divergence> {
+    let mut rng = TestRng::with(seed);
+    (0..runs)
+        .filter_map(|_| {
+            let code = random_program(&mut rng, mix, len, config.field_order);
+            diff_exec(&code, config).expect("generated programs are always supported and assemble")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::{
+        SolinasPrime, FIELD_ORDER_25519, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_SECP,
+        FIELD_ORDER_STARK,
+    };
+
+    #[test]
+    fn selftest() {
+        for order in
+            [FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_BABYBEAR, FIELD_ORDER_STARK, FIELD_ORDER_25519, FIELD_ORDER_SECP]
+        {
+            let divergences = diff_selftest(order.low_u64(), 64, 48, &OpcodeMix::default(), GfaConfig::new(order));
+            assert!(divergences.is_empty(), "{}", divergences[0]);
+        }
+    }
+
+    #[test]
+    fn selftest_modes() {
+        let mut ct = GfaConfig::new(FIELD_ORDER_GOLDILOCKS);
+        ct.constant_time = true;
+        assert!(diff_selftest(1, 32, 48, &OpcodeMix::ARITHMETIC, ct).is_empty());
+
+        let mut solinas = GfaConfig::new(FIELD_ORDER_STARK);
+        solinas.solinas = Some(SolinasPrime::STARK);
+        assert!(diff_selftest(2, 32, 48, &OpcodeMix::ARITHMETIC, solinas).is_empty());
+    }
+
+    #[test]
+    fn generator() {
+        let mut rng = TestRng::with(7);
+        let mix = OpcodeMix {
+            put: 1,
+            mov: 0,
+            clr: 0,
+            test: 0,
+            eq: 0,
+            fits: 0,
+            neg: 0,
+            add: 0,
+            mul: 1,
+        };
+        let code = random_program(&mut rng, &mix, 100, FIELD_ORDER_GOLDILOCKS);
+        assert_eq!(code.len(), 100);
+        assert!(matches!(
+            code[0],
+            Instr::Gfa(FieldInstr::PutZ { .. } | FieldInstr::PutV { .. } | FieldInstr::PutD { .. })
+        ));
+        assert!(code.iter().all(|instr| matches!(
+            instr,
+            Instr::Gfa(
+                FieldInstr::PutZ { .. } | FieldInstr::PutV { .. } | FieldInstr::PutD { .. } | FieldInstr::Mul { .. }
+            )
+        )));
+        assert_eq!(code, random_program(&mut TestRng::with(7), &mix, 100, FIELD_ORDER_GOLDILOCKS));
+    }
+
+    #[test]
+    fn reference() {
+        let code = [
+            Instr::Gfa(FieldInstr::PutV {
+                dst: RegE::E1,
+                val: ConstVal::ValFeMAX,
+            }),
+            Instr::Gfa(FieldInstr::Neg {
+                dst: RegE::E2,
+                src: RegE::E1,
+            }),
+            Instr::Gfa(FieldInstr::Add {
+                dst_src: RegE::E2,
+                src: RegE::E2,
+            }),
+            Instr::Gfa(FieldInstr::Test { src: RegE::E3 }),
+            Instr::Gfa(FieldInstr::Mul {
+                dst_src: RegE::E3,
+                src: RegE::E1,
+            }),
+            Instr::Gfa(FieldInstr::PutZ { dst: RegE::E4 }),
+        ];
+        let state = ref_exec(&code, FIELD_ORDER_GOLDILOCKS).unwrap();
+        assert_eq!(state.ck, Status::Fail);
+        assert_eq!(state.co, Status::Fail);
+        assert_eq!(state.regs[0], Some(fe256::from(FIELD_ORDER_GOLDILOCKS - u256::ONE)));
+        assert_eq!(state.regs[1], Some(fe256::from(2u8)));
+        assert_eq!(state.regs[3], None);
+        assert_eq!(diff_exec(&code, GfaConfig::new(FIELD_ORDER_GOLDILOCKS)).unwrap(), None);
+    }
+
+    #[test]
+    fn divergence() {
+        let code = [Instr::Gfa(FieldInstr::PutD {
+            dst: RegE::E1,
+            data: fe256::from(u256::from(u64::MAX)),
+        })];
+        // Simulates a VM which puts the value without checking it against the field order
+        let reference = ref_exec(&code, FIELD_ORDER_BABYBEAR).unwrap();
+        let vm = ref_exec(&code, FIELD_ORDER_SECP).unwrap();
+        let divergence = Divergence {
+            code: code.to_vec(),
+            vm,
+            reference,
+        };
+        let msg = divergence.to_string();
+        assert!(msg.contains("CK: VM ok, reference fail"));
+        assert!(msg.contains("E1: VM FFFFFFFFFFFFFFFF.fe, reference ~"));
+    }
+
+    #[test]
+    fn unsupported() {
+        let code = [Instr::Ctrl(CtrlInstr::Ret)];
+        assert_eq!(ref_exec(&code, FIELD_ORDER_GOLDILOCKS), Err(DiffError::Unsupported(Instr::Ctrl(CtrlInstr::Ret))));
+    }
+}
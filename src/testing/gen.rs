@@ -0,0 +1,215 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::vec::Vec;
+
+use aluvm::isa::CtrlInstr;
+use aluvm::{LibId, Site};
+use amplify::num::{u2, u256, u3};
+
+use crate::gfa::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{fe256, RegE};
+
+/// Deterministic pseudo-random number generator used to produce test instructions.
+///
+/// The generator is not cryptographically secure and must be used for testing purposes only.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TestRng(u64);
+
+impl TestRng {
+    /// Constructs generator from a seed.
+    pub fn with(seed: u64) -> Self { TestRng(seed) }
+
+    /// Returns next pseudo-random 64-bit value (using SplitMix64 algorithm).
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns pseudo-random value in range `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// If `bound` is zero.
+    pub fn below(&mut self, bound: u64) -> u64 { self.next_u64() % bound }
+
+    /// Returns pseudo-random 32 bytes.
+    pub fn bytes32(&mut self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Values which can be generated randomly for property testing.
+///
+/// ISA extenders can implement the trait for their own instructions, and then use
+/// [`random_code`] together with [`super::roundtrip_code`] to test the encoding.
+pub trait RandomInstr: Sized {
+    /// Generates random valid value.
+    fn random(rng: &mut TestRng) -> Self;
+}
+
+impl RandomInstr for RegE {
+    fn random(rng: &mut TestRng) -> Self { RegE::ALL[rng.below(16) as usize] }
+}
+
+impl RandomInstr for FieldInstr {
+    fn random(rng: &mut TestRng) -> Self {
+        let reg = |rng: &mut TestRng| RegE::random(rng);
+        match rng.below(21) {
+            0 => FieldInstr::Test { src: reg(rng) },
+            1 => FieldInstr::Clr { dst: reg(rng) },
+            2 => FieldInstr::PutD {
+                dst: reg(rng),
+                data: fe256::from(u256::from_le_bytes(rng.bytes32())),
+            },
+            3 => FieldInstr::PutZ { dst: reg(rng) },
+            4 => FieldInstr::PutV {
+                dst: reg(rng),
+                val: ConstVal::from(u2::with(rng.below(4) as u8)),
+            },
+            5 => FieldInstr::Fits {
+                src: reg(rng),
+                bits: Bits::from(u3::with(rng.below(8) as u8)),
+            },
+            6 => FieldInstr::Mov {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            7 => FieldInstr::Eq {
+                src1: reg(rng),
+                src2: reg(rng),
+            },
+            8 => FieldInstr::Neg {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            9 => FieldInstr::Add {
+                dst_src: reg(rng),
+                src: reg(rng),
+            },
+            10 => FieldInstr::Mul {
+                dst_src: reg(rng),
+                src: reg(rng),
+            },
+            11 => FieldInstr::Inv {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            12 => FieldInstr::Exp {
+                dst_src: reg(rng),
+                exp: reg(rng),
+            },
+            13 => FieldInstr::ExpI {
+                dst_src: reg(rng),
+                exp: rng.below(0x100) as u8,
+            },
+            14 => FieldInstr::Sqr {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            15 => FieldInstr::Sqrt {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            16 => FieldInstr::CMov {
+                dst: reg(rng),
+                src: reg(rng),
+            },
+            17 => FieldInstr::Sel {
+                dst: reg(rng),
+                src_true: reg(rng),
+                src_false: reg(rng),
+            },
+            18 => FieldInstr::Lt {
+                src1: reg(rng),
+                src2: reg(rng),
+            },
+            19 => FieldInstr::Rng {
+                src: reg(rng),
+                bound: fe256::from(u256::from_le_bytes(rng.bytes32())),
+            },
+            _ => FieldInstr::Ecall {
+                id: rng.below(0x100) as u8,
+            },
+        }
+    }
+}
+
+impl RandomInstr for CtrlInstr<LibId> {
+    /// Generates random control flow instruction.
+    ///
+    /// NB: jump and call targets are arbitrary and may not point to instruction boundaries;
+    /// external calls and jumps refer to one of four fixed library ids.
+    fn random(rng: &mut TestRng) -> Self {
+        let pos = rng.next_u64() as u16;
+        let shift = rng.next_u64() as i8;
+        // External sites refer a small set of libraries, so the library segment doesn't overflow
+        let lib_id = LibId::from([rng.below(4) as u8; 32]);
+        match rng.below(17) {
+            0 => CtrlInstr::Nop,
+            1 => CtrlInstr::ChkCo,
+            2 => CtrlInstr::ChkCk,
+            3 => CtrlInstr::NotCo,
+            4 => CtrlInstr::FailCk,
+            5 => CtrlInstr::RsetCk,
+            6 => CtrlInstr::Jmp { pos },
+            7 => CtrlInstr::JiOvfl { pos },
+            8 => CtrlInstr::JiFail { pos },
+            9 => CtrlInstr::Sh { shift },
+            10 => CtrlInstr::ShOvfl { shift },
+            11 => CtrlInstr::ShFail { shift },
+            12 => CtrlInstr::Exec {
+                site: Site::new(lib_id, pos),
+            },
+            13 => CtrlInstr::Fn { pos },
+            14 => CtrlInstr::Call {
+                site: Site::new(lib_id, pos),
+            },
+            15 => CtrlInstr::Ret,
+            _ => CtrlInstr::Stop,
+        }
+    }
+}
+
+impl RandomInstr for Instr<LibId> {
+    /// Generates random instruction, which is a field arithmetic instruction in 3/4 of the cases
+    /// and a control flow instruction otherwise.
+    fn random(rng: &mut TestRng) -> Self {
+        if rng.below(4) == 0 {
+            Instr::Ctrl(CtrlInstr::random(rng))
+        } else {
+            Instr::Gfa(FieldInstr::random(rng))
+        }
+    }
+}
+
+/// Generates a sequence of `len` random instructions.
+pub fn random_code<I: RandomInstr>(rng: &mut TestRng, len: usize) -> Vec<I> {
+    (0..len).map(|_| I::random(rng)).collect()
+}
@@ -0,0 +1,54 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Utilities for testing zk-AluVM programs and ISA extensions.
+//!
+//! The module provides:
+//! - encode/decode round-trip checks for instructions and programs, usable with any instruction set
+//!   extension ([`roundtrip`], [`roundtrip_code`]), and an exhaustive check of decoding of the
+//!   opcode space ([`decode_coverage`]);
+//! - deterministic generators of random valid instruction sequences ([`TestRng`], [`RandomInstr`],
+//!   [`random_code`]);
+//! - [`proptest`] strategies generating valid instructions, canonical field elements and
+//!   well-formed straight-line programs ([`straight_line`] and others);
+//! - golden test vectors, allowing to pin ISA semantics with TOML or JSON fixture files
+//!   ([`TestVectors`]);
+//! - differential testing of the ISA against a reference interpreter over arbitrary-precision
+//!   integers, running it on random well-formed programs ([`diff_selftest`], [`random_program`]).
+
+mod bytecode;
+#[cfg(feature = "testing")]
+mod diff;
+mod gen;
+#[cfg(feature = "testing")]
+mod strategy;
+#[cfg(feature = "testing")]
+mod vectors;
+
+pub use bytecode::{decode_coverage, roundtrip, roundtrip_code, DecodeCoverage};
+#[cfg(feature = "testing")]
+pub use diff::{diff_exec, diff_selftest, random_program, ref_exec, DiffError, DiffState, Divergence, OpcodeMix};
+pub use gen::{random_code, RandomInstr, TestRng};
+#[cfg(feature = "testing")]
+pub use strategy::{bits, const_val, ctrl_instr, fe256_canonical, field_instr, instr, reg, straight_line};
+#[cfg(feature = "testing")]
+pub use vectors::{Expected, TestProgram, TestVector, TestVectors, VectorError, VectorFailure};
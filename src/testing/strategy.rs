@@ -0,0 +1,241 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! [`proptest`] strategies generating zk-AluVM instructions and programs.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use aluvm::isa::CtrlInstr;
+use aluvm::{LibId, Site};
+use amplify::num::u256;
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use crate::gfa::{Bits, ConstVal, FieldInstr, Instr};
+use crate::{fe256, RegE};
+
+/// Strategy generating registers.
+pub fn reg() -> impl Strategy<Value = RegE> { proptest::sample::select(RegE::ALL.as_slice()) }
+
+/// Strategy generating field-independent constants.
+pub fn const_val() -> impl Strategy<Value = ConstVal> {
+    proptest::sample::select([ConstVal::Val1, ConstVal::ValU64Max, ConstVal::ValU128Max, ConstVal::ValFeMAX].as_slice())
+}
+
+/// Strategy generating bit dimensions for the `fits` instruction.
+pub fn bits() -> impl Strategy<Value = Bits> {
+    proptest::sample::select(
+        [
+            Bits::Bits8,
+            Bits::Bits16,
+            Bits::Bits24,
+            Bits::Bits32,
+            Bits::Bits48,
+            Bits::Bits64,
+            Bits::Bits96,
+            Bits::Bits128,
+        ]
+        .as_slice(),
+    )
+}
+
+/// Strategy generating canonical elements of the field with the given order, i.e. values below the
+/// order.
+///
+/// Along with uniformly distributed values, zero, one and the maximal element of the field are
+/// generated with increased probability.
+///
+/// # Panics
+///
+/// If the field order is less than two.
+pub fn fe256_canonical(order: u256) -> impl Strategy<Value = fe256> {
+    assert!(order > u256::ONE, "field order must be at least two");
+    prop_oneof![
+        1 => Just(fe256::ZERO),
+        1 => Just(fe256::from(1u8)),
+        1 => Just(fe256::from(order - u256::ONE)),
+        7 => any::<[u8; 32]>().prop_map(move |bytes| fe256::from(u256::from_le_bytes(bytes) % order)),
+    ]
+}
+
+/// Strategy generating valid field arithmetic instructions for the field with the given order.
+///
+/// Values put into registers by [`FieldInstr::PutD`] are always canonical.
+pub fn field_instr(order: u256) -> impl Strategy<Value = FieldInstr> {
+    prop_oneof![
+        reg().prop_map(|src| FieldInstr::Test { src }),
+        reg().prop_map(|dst| FieldInstr::Clr { dst }),
+        (reg(), fe256_canonical(order)).prop_map(|(dst, data)| FieldInstr::PutD { dst, data }),
+        reg().prop_map(|dst| FieldInstr::PutZ { dst }),
+        (reg(), const_val()).prop_map(|(dst, val)| FieldInstr::PutV { dst, val }),
+        (reg(), bits()).prop_map(|(src, bits)| FieldInstr::Fits { src, bits }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::Mov { dst, src }),
+        (reg(), reg()).prop_map(|(src1, src2)| FieldInstr::Eq { src1, src2 }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::Neg { dst, src }),
+        (reg(), reg()).prop_map(|(dst_src, src)| FieldInstr::Add { dst_src, src }),
+        (reg(), reg()).prop_map(|(dst_src, src)| FieldInstr::Mul { dst_src, src }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::Inv { dst, src }),
+        (reg(), reg()).prop_map(|(dst_src, exp)| FieldInstr::Exp { dst_src, exp }),
+        (reg(), any::<u8>()).prop_map(|(dst_src, exp)| FieldInstr::ExpI { dst_src, exp }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::Sqr { dst, src }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::Sqrt { dst, src }),
+        (reg(), reg()).prop_map(|(dst, src)| FieldInstr::CMov { dst, src }),
+        (reg(), reg(), reg()).prop_map(|(dst, src_true, src_false)| FieldInstr::Sel { dst, src_true, src_false }),
+        (reg(), reg()).prop_map(|(src1, src2)| FieldInstr::Lt { src1, src2 }),
+        (reg(), fe256_canonical(order)).prop_map(|(src, bound)| FieldInstr::Rng { src, bound }),
+    ]
+}
+
+/// Strategy generating control flow instructions.
+///
+/// NB: jump and call targets are arbitrary and may not point to instruction boundaries; external
+/// calls and jumps refer to one of four fixed library ids.
+pub fn ctrl_instr() -> impl Strategy<Value = CtrlInstr<LibId>> {
+    let site = (0u8..4, any::<u16>()).prop_map(|(lib, pos)| Site::new(LibId::from([lib; 32]), pos));
+    prop_oneof![
+        Just(CtrlInstr::Nop),
+        Just(CtrlInstr::ChkCo),
+        Just(CtrlInstr::ChkCk),
+        Just(CtrlInstr::NotCo),
+        Just(CtrlInstr::FailCk),
+        Just(CtrlInstr::RsetCk),
+        any::<u16>().prop_map(|pos| CtrlInstr::Jmp { pos }),
+        any::<u16>().prop_map(|pos| CtrlInstr::JiOvfl { pos }),
+        any::<u16>().prop_map(|pos| CtrlInstr::JiFail { pos }),
+        any::<i8>().prop_map(|shift| CtrlInstr::Sh { shift }),
+        any::<i8>().prop_map(|shift| CtrlInstr::ShOvfl { shift }),
+        any::<i8>().prop_map(|shift| CtrlInstr::ShFail { shift }),
+        site.clone().prop_map(|site| CtrlInstr::Exec { site }),
+        any::<u16>().prop_map(|pos| CtrlInstr::Fn { pos }),
+        site.prop_map(|site| CtrlInstr::Call { site }),
+        Just(CtrlInstr::Ret),
+        Just(CtrlInstr::Stop),
+    ]
+}
+
+/// Strategy generating valid instructions for the field with the given order: field arithmetic
+/// instructions in 3/4 of the cases and control flow instructions otherwise.
+pub fn instr(order: u256) -> impl Strategy<Value = Instr<LibId>> {
+    prop_oneof![
+        3 => field_instr(order).prop_map(Instr::Gfa),
+        1 => ctrl_instr().prop_map(Instr::Ctrl),
+    ]
+}
+
+/// Strategy generating well-formed straight-line programs for the field with the given order.
+///
+/// The programs consist of field arithmetic instructions only and contain no control flow. Each
+/// instruction reads only registers which are set by the previous instructions, and all values put
+/// into registers are canonical, so the generated programs are suitable for checking that
+/// optimizers and exporters preserve the program semantics.
+pub fn straight_line(order: u256, len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<Instr<LibId>>> {
+    let op = (0u8..10, reg(), any::<Index>(), any::<Index>(), fe256_canonical(order), bits());
+    vec(op, len).prop_map(|ops| {
+        let mut set = BTreeSet::<RegE>::new();
+        let mut code = Vec::with_capacity(ops.len());
+        for (kind, dst, a, b, val, bits) in ops {
+            let regs = set.iter().copied().collect::<Vec<_>>();
+            let src = |idx: Index| regs[idx.index(regs.len())];
+            let instr = match kind {
+                _ if regs.is_empty() => FieldInstr::put(dst, val),
+                0 | 1 => FieldInstr::put(dst, val),
+                2 => FieldInstr::Mov { dst, src: src(a) },
+                3 => FieldInstr::Neg { dst, src: src(a) },
+                4 => FieldInstr::Add {
+                    dst_src: src(a),
+                    src: src(b),
+                },
+                5 => FieldInstr::Mul {
+                    dst_src: src(a),
+                    src: src(b),
+                },
+                6 => FieldInstr::Eq {
+                    src1: src(a),
+                    src2: src(b),
+                },
+                7 => FieldInstr::Test { src: src(a) },
+                8 => FieldInstr::Fits { src: src(a), bits },
+                _ => FieldInstr::Clr { dst: src(a) },
+            };
+            match instr {
+                FieldInstr::Clr { dst } => set.remove(&dst),
+                FieldInstr::PutD { dst, .. }
+                | FieldInstr::PutZ { dst }
+                | FieldInstr::PutV { dst, .. }
+                | FieldInstr::Mov { dst, .. }
+                | FieldInstr::Neg { dst, .. } => set.insert(dst),
+                _ => false,
+            };
+            code.push(Instr::Gfa(instr));
+        }
+        code
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::isa::Instruction;
+
+    use super::*;
+    use crate::testing::roundtrip_code;
+    use crate::{FIELD_ORDER_25519, FIELD_ORDER_GOLDILOCKS};
+
+    proptest! {
+        #[test]
+        fn canonical(val in fe256_canonical(FIELD_ORDER_GOLDILOCKS)) {
+            prop_assert!(val.to_u256() < FIELD_ORDER_GOLDILOCKS);
+        }
+
+        #[test]
+        fn instrs(code in vec(instr(FIELD_ORDER_25519), 0..64)) {
+            roundtrip_code(&code);
+            for instr in code {
+                if let Instr::Gfa(FieldInstr::PutD { data, .. }) = instr {
+                    prop_assert!(data.to_u256() < FIELD_ORDER_25519);
+                }
+            }
+        }
+
+        #[test]
+        fn straight_line_programs(code in straight_line(FIELD_ORDER_GOLDILOCKS, 1..64)) {
+            roundtrip_code(&code);
+            let mut set = BTreeSet::new();
+            for instr in code {
+                let Instr::Gfa(instr) = instr else {
+                    panic!("control flow instruction {instr} in a straight-line program");
+                };
+                for reg in Instruction::<LibId>::src_regs(&instr) {
+                    prop_assert!(set.contains(&reg), "{reg} is read before it is set in `{instr}`");
+                }
+                if let FieldInstr::Clr { dst } = instr {
+                    set.remove(&dst);
+                } else {
+                    set.extend(Instruction::<LibId>::dst_regs(&instr));
+                }
+            }
+        }
+    }
+}
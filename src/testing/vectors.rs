@@ -0,0 +1,450 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Golden test vectors.
+//!
+//! Vectors are loaded from TOML or JSON files, with each vector providing a program (either as an
+//! assembly text or as a bytecode), values for the input registers and the expected state of the
+//! registers once the program completes. A TOML file with vectors looks like this:
+//!
+//! ```toml
+//! [[vector]]
+//! name = "addition"
+//! field = "secp"
+//! program.asm = """
+//!     add E1, E2
+//!     ret
+//! """
+//! inputs = { E1 = "2", E2 = "0x3" }
+//! expect = { success = true, co = "ok", ck = "ok", regs = { E1 = "5" }, unset = ["E3"] }
+//! ```
+//!
+//! The same vector in JSON:
+//!
+//! ```json
+//! { "vector": [{
+//!     "name": "addition",
+//!     "program": { "bytecode": { "code": "..." } },
+//!     "inputs": { "E1": "2", "E2": "0x3" },
+//!     "expect": { "regs": { "E1": "5" } }
+//! }] }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use aluvm::isa::{CodeEofError, Instruction};
+use aluvm::regs::Status;
+use aluvm::{CoreConfig, CoreExt, Lib, LibId, LibSite, Vm};
+use amplify::confinement::SmallBlob;
+use amplify::hex::FromHex;
+use amplify::num::u256;
+
+use crate::gfa::{AsmError, Assembly, Instr, NO_CONTEXT};
+use crate::{
+    fe256, GfaConfig, ParseFeError, RegE, FIELD_ORDER_25519, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS,
+    FIELD_ORDER_SECP, FIELD_ORDER_STARK,
+};
+
+/// Errors loading or running test vectors.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum VectorError {
+    /// unable to read test vectors: {0}
+    Io(String),
+
+    /// invalid test vectors: {0}
+    Format(String),
+
+    /// unknown field `{0}`.
+    Field(String),
+
+    /// invalid assembly: {0}
+    #[from]
+    Asm(AsmError),
+
+    /// invalid hex-encoded bytecode `{0}`.
+    Hex(String),
+
+    /// bytecode can't be decoded into zk-AluVM instructions.
+    #[from(CodeEofError)]
+    Decode,
+
+    /// invalid value for the register {0}: {1}
+    Value(RegE, ParseFeError),
+
+    /// value for the register {0} doesn't fit the field order.
+    NonCanonical(RegE),
+
+    /// program execution status is {found}, while {expected} was expected.
+    Result {
+        /** Expected execution status */
+        expected: Status,
+        /** Actual execution status */
+        found: Status,
+    },
+
+    /// `CO` register value is {found}, while {expected} was expected.
+    Co {
+        /** Expected `CO` value */
+        expected: Status,
+        /** Actual `CO` value */
+        found: Status,
+    },
+
+    /// `CK` register value is {found}, while {expected} was expected.
+    Ck {
+        /** Expected `CK` value */
+        expected: Status,
+        /** Actual `CK` value */
+        found: Status,
+    },
+
+    /// register {reg} value is {found:?}, while {expected:?} was expected.
+    Reg {
+        /** Register which value mismatches */
+        reg: RegE,
+        /** Expected register value */
+        expected: Option<fe256>,
+        /** Actual register value */
+        found: Option<fe256>,
+    },
+}
+
+/// Error in one of the vectors.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display("test vector `{name}`: {error}")]
+pub struct VectorFailure {
+    /// Name of the failed vector.
+    pub name: String,
+    /// Details of the failure.
+    pub error: VectorError,
+}
+
+/// Program of a test vector.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestProgram {
+    /// Program assembly text, as accepted by [`Assembly::parse`].
+    Asm(String),
+
+    /// Hex-encoded bytecode.
+    Bytecode {
+        /// Code segment.
+        code: String,
+        /// Data segment.
+        #[serde(default)]
+        data: String,
+    },
+}
+
+impl TestProgram {
+    /// Compiles the program into a library.
+    pub fn compile(&self) -> Result<Lib, VectorError> {
+        match self {
+            TestProgram::Asm(source) => {
+                let asm = Assembly::parse(source)?;
+                asm.assemble()
+                    .map_err(|err| VectorError::Format(err.to_string()))
+            }
+            TestProgram::Bytecode { code, data } => {
+                let blob = |hex: &str| {
+                    Vec::<u8>::from_hex(hex)
+                        .ok()
+                        .and_then(|vec| SmallBlob::try_from(vec).ok())
+                        .ok_or_else(|| VectorError::Hex(hex.to_owned()))
+                };
+                let lib = Lib {
+                    isae: Instr::<LibId>::isa_ext(),
+                    code: blob(code)?,
+                    data: blob(data)?,
+                    libs: none!(),
+                };
+                lib.disassemble::<Instr<LibId>>()?;
+                Ok(lib)
+            }
+        }
+    }
+}
+
+/// Expected results of running a test vector.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Expected {
+    /// Whether the program execution succeeds.
+    pub success: Option<bool>,
+    /// Expected value of `CO` register (`ok` or `fail`).
+    #[serde(with = "status")]
+    pub co: Option<Status>,
+    /// Expected value of `CK` register (`ok` or `fail`).
+    #[serde(with = "status")]
+    pub ck: Option<Status>,
+    /// Expected values of the registers.
+    pub regs: BTreeMap<RegE, String>,
+    /// Registers which are expected to stay uninitialized.
+    pub unset: BTreeSet<RegE>,
+}
+
+/// Golden test vector.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVector {
+    /// Name of the test vector.
+    pub name: String,
+    /// Field order: either `25519`, `secp`, `stark`, `goldilocks`, `babybear` or a hex number
+    /// prefixed with `0x`.
+    #[serde(default = "default_field")]
+    pub field: String,
+    /// Program to run.
+    pub program: TestProgram,
+    /// Offset of the program entry point.
+    #[serde(default)]
+    pub entry: u16,
+    /// Whether to halt the execution on the first `CK` failure.
+    #[serde(default)]
+    pub halt: bool,
+    /// Complexity limit for the execution.
+    #[serde(default)]
+    pub complexity_lim: Option<u64>,
+    /// Values of the input registers, which may be either decimal numbers, hexadecimal numbers
+    /// prefixed with `0x` or hexadecimal values with `.fe` suffix.
+    #[serde(default)]
+    pub inputs: BTreeMap<RegE, String>,
+    /// Expected results of the program execution.
+    #[serde(default)]
+    pub expect: Expected,
+}
+
+fn default_field() -> String { s!("secp") }
+
+impl TestVector {
+    /// Returns the field order used by the vector.
+    pub fn field_order(&self) -> Result<u256, VectorError> {
+        match self.field.as_str() {
+            "25519" => Ok(FIELD_ORDER_25519),
+            "secp" => Ok(FIELD_ORDER_SECP),
+            "stark" => Ok(FIELD_ORDER_STARK),
+            "goldilocks" => Ok(FIELD_ORDER_GOLDILOCKS),
+            "babybear" => Ok(FIELD_ORDER_BABYBEAR),
+            s => s
+                .strip_prefix("0x")
+                .and_then(|hex| fe256::from_str(&format!("{hex}.fe")).ok())
+                .map(|fe| fe.to_u256())
+                .ok_or_else(|| VectorError::Field(s.to_owned())),
+        }
+    }
+
+    /// Runs the vector program, returning the VM in the state after the execution together with
+    /// the execution status.
+    pub fn exec(&self) -> Result<(Vm<Instr<LibId>>, Status), VectorError> {
+        let field_order = self.field_order()?;
+        let lib = self.program.compile()?;
+        let config = CoreConfig {
+            halt: self.halt,
+            complexity_lim: self.complexity_lim,
+        };
+        let mut vm = Vm::<Instr<LibId>>::with(config, GfaConfig::new(field_order));
+        for (reg, value) in &self.inputs {
+            let value = parse_value(*reg, value, field_order)?;
+            vm.core.cx.set(*reg, value);
+        }
+        let lib_id = lib.lib_id();
+        let status = vm.exec(LibSite::new(lib_id, self.entry), &NO_CONTEXT, |id| (id == lib_id).then_some(&lib));
+        Ok((vm, status))
+    }
+
+    /// Runs the vector program and checks its results against the expected ones.
+    pub fn run(&self) -> Result<(), VectorError> {
+        let (vm, status) = self.exec()?;
+        let expect = &self.expect;
+        if let Some(success) = expect.success {
+            let expected = if success { Status::Ok } else { Status::Fail };
+            if status != expected {
+                return Err(VectorError::Result {
+                    expected,
+                    found: status,
+                });
+            }
+        }
+        if let Some(expected) = expect.co {
+            if vm.core.co() != expected {
+                return Err(VectorError::Co {
+                    expected,
+                    found: vm.core.co(),
+                });
+            }
+        }
+        if let Some(expected) = expect.ck {
+            if vm.core.ck() != expected {
+                return Err(VectorError::Ck {
+                    expected,
+                    found: vm.core.ck(),
+                });
+            }
+        }
+        let field_order = vm.core.cx.fq();
+        let expected_regs = expect
+            .regs
+            .iter()
+            .map(|(reg, value)| parse_value(*reg, value, field_order).map(|val| (*reg, Some(val))))
+            .chain(expect.unset.iter().map(|reg| Ok((*reg, None))));
+        for res in expected_regs {
+            let (reg, expected) = res?;
+            let found = vm.core.cx.get(reg);
+            if found != expected {
+                return Err(VectorError::Reg { reg, expected, found });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_value(reg: RegE, value: &str, field_order: u256) -> Result<fe256, VectorError> {
+    let value = fe256::parse_literal(value).map_err(|err| VectorError::Value(reg, err))?;
+    if value.to_u256() >= field_order {
+        return Err(VectorError::NonCanonical(reg));
+    }
+    Ok(value)
+}
+
+/// Collection of golden test vectors.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// Test vectors.
+    #[serde(rename = "vector")]
+    pub vectors: Vec<TestVector>,
+}
+
+impl TestVectors {
+    /// Parses test vectors from a JSON text.
+    pub fn from_json(s: &str) -> Result<Self, VectorError> {
+        serde_json::from_str(s).map_err(|err| VectorError::Format(err.to_string()))
+    }
+
+    /// Parses test vectors from a TOML text.
+    pub fn from_toml(s: &str) -> Result<Self, VectorError> {
+        toml::from_str(s).map_err(|err| VectorError::Format(err.to_string()))
+    }
+
+    /// Loads test vectors from a file, which must have either `.json` or `.toml` extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VectorError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| VectorError::Io(err.to_string()))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&text),
+            Some("toml") => Self::from_toml(&text),
+            _ => Err(VectorError::Io(format!("unknown format of the file `{}`", path.display()))),
+        }
+    }
+
+    /// Runs all the vectors, returning the failures.
+    pub fn run(&self) -> Vec<VectorFailure> {
+        self.vectors
+            .iter()
+            .filter_map(|vector| {
+                vector.run().err().map(|error| VectorFailure {
+                    name: vector.name.clone(),
+                    error,
+                })
+            })
+            .collect()
+    }
+}
+
+mod status {
+    use aluvm::regs::Status;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(status: &Option<Status>, serializer: S) -> Result<S::Ok, S::Error> {
+        match status {
+            Some(status) => serializer.serialize_some(&status.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Status>, D::Error> {
+        match Option::<String>::deserialize(deserializer)?.as_deref() {
+            None => Ok(None),
+            Some("ok") => Ok(Some(Status::Ok)),
+            Some("fail") => Ok(Some(Status::Fail)),
+            Some(other) => Err(serde::de::Error::custom(format!("invalid status `{other}`"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+
+    #[test]
+    fn mismatch() {
+        let vectors = TestVectors::from_toml(
+            r#"
+            [[vector]]
+            name = "mov"
+            program.asm = "mov E2, E1"
+            inputs = { E1 = "1" }
+            expect = { regs = { E2 = "2" } }
+
+            [[vector]]
+            name = "clr"
+            program.asm = "clr E1"
+            inputs = { E1 = "1" }
+            expect = { co = "fail", unset = ["E1"] }
+
+            [[vector]]
+            name = "field"
+            field = "0x10"
+            program.asm = "ret"
+            inputs = { E1 = "16" }
+            "#,
+        )
+        .unwrap();
+        let one = fe256::from(u256::ONE);
+        assert_eq!(vectors.run(), vec![
+            VectorFailure {
+                name: s!("mov"),
+                error: VectorError::Reg {
+                    reg: RegE::E2,
+                    expected: Some(fe256::from(u256::from(2u8))),
+                    found: Some(one),
+                },
+            },
+            VectorFailure {
+                name: s!("clr"),
+                error: VectorError::Co {
+                    expected: Status::Fail,
+                    found: Status::Ok
+                },
+            },
+            VectorFailure {
+                name: s!("field"),
+                error: VectorError::NonCanonical(RegE::E1)
+            },
+        ]);
+        assert!(matches!(TestVectors::from_json("{}"), Err(VectorError::Format(_))));
+    }
+}
@@ -0,0 +1,259 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! JavaScript bindings for running zk-AluVM programs in browsers and other WebAssembly hosts.
+//!
+//! The bindings expose assembling programs (from the assembly source or from the ASCII-armored
+//! library), executing them with the provided register values and inspecting the final state of
+//! the registers. Field elements and registers are passed as strings using the assembly syntax.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use aluvm::{Lib, LibSite, Vm};
+use amplify::num::u256;
+use wasm_bindgen::prelude::*;
+
+use crate::core::parse_field_order;
+use crate::gfa::{AsmConfig, AsmFormatter, Assembly};
+use crate::{fe256, parse_armored_lib, Debugger, GfaConfig, RegE, StopReason};
+
+/// Assembled zk-AluVM program.
+#[wasm_bindgen(js_name = Program)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WasmProgram {
+    lib: Lib,
+    config: GfaConfig,
+}
+
+#[wasm_bindgen(js_class = Program)]
+impl WasmProgram {
+    /// Assembles a program from its source text for a field given either by its name (`25519`,
+    /// `secp`, `stark`, `goldilocks` or `babybear`) or by a hexadecimal order prefixed with `0x`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str, field: &str) -> Result<WasmProgram, JsError> {
+        Self::assemble(source, field).map_err(|err| JsError::new(&err))
+    }
+
+    /// Loads a program from its ASCII-armored library (see [`crate::armor_lib`]).
+    #[wasm_bindgen(js_name = fromArmored)]
+    pub fn from_armored(armor: &str, field: &str) -> Result<WasmProgram, JsError> {
+        Self::load_armored(armor, field).map_err(|err| JsError::new(&err))
+    }
+
+    /// Identifier of the program library.
+    #[wasm_bindgen(getter, js_name = libId)]
+    pub fn lib_id(&self) -> String { self.lib.lib_id().to_string() }
+
+    /// ASCII-armored library of the program.
+    #[wasm_bindgen(getter)]
+    pub fn armored(&self) -> String { crate::armor_lib(&self.lib) }
+
+    /// Disassembled program code.
+    pub fn disassemble(&self) -> Result<String, JsError> {
+        AsmFormatter::new()
+            .format_lib(&self.lib)
+            .map_err(|err| JsError::new(&err.to_string()))
+    }
+
+    /// Executes the program from its beginning, with the registers initialized with the `inputs`
+    /// given as `REG=VALUE` strings (for instance, `EA=0x1234`).
+    pub fn run(&self, inputs: Vec<String>) -> Result<WasmOutcome, JsError> {
+        self.exec(&inputs).map_err(|err| JsError::new(&err))
+    }
+}
+
+impl WasmProgram {
+    fn assemble(source: &str, field: &str) -> Result<Self, String> {
+        let field_order = parse_field(field)?;
+        let asm = Assembly::parse_with(source, &AsmConfig::with_field(field_order)).map_err(|err| err.to_string())?;
+        let lib = asm.assemble().map_err(|err| err.to_string())?;
+        Ok(Self::with(lib, field_order))
+    }
+
+    fn load_armored(armor: &str, field: &str) -> Result<Self, String> {
+        let field_order = parse_field(field)?;
+        let lib = parse_armored_lib(armor).map_err(|err| err.to_string())?;
+        Ok(Self::with(lib, field_order))
+    }
+
+    fn with(lib: Lib, field_order: u256) -> Self {
+        WasmProgram {
+            lib,
+            config: GfaConfig::new(field_order),
+        }
+    }
+
+    fn exec(&self, inputs: &[String]) -> Result<WasmOutcome, String> {
+        let mut debugger = Debugger::new(Vm::with(default!(), self.config), [self.lib.clone()]);
+        for input in inputs {
+            let (reg, val) = input
+                .split_once('=')
+                .ok_or_else(|| format!("input `{input}` must have `REG=VALUE` form"))?;
+            let reg = RegE::from_str(reg.trim()).map_err(|err| err.to_string())?;
+            let val = fe256::parse_literal(val.trim()).map_err(|err| err.to_string())?;
+            if val.to_u256() >= self.config.field_order {
+                return Err(format!("input value {val} for {reg} is not a canonical field element"));
+            }
+            debugger.set_reg(reg, Some(val));
+        }
+        debugger.start(LibSite::new(self.lib.lib_id(), 0));
+        let ck = loop {
+            if let StopReason::Halted(ck) = debugger.run() {
+                break ck;
+            }
+        };
+        Ok(WasmOutcome {
+            ok: ck.is_ok(),
+            co: debugger.co().is_ok(),
+            steps: debugger.vm().core.cx.steps(),
+            complexity: debugger.usage().total,
+            failure: debugger.failure().map(|failure| match failure.instr {
+                Some(instr) => format!("{}: {instr}", failure.site),
+                None => failure.site.to_string(),
+            }),
+            regs: RegE::ALL.map(|reg| debugger.reg(reg)),
+        })
+    }
+}
+
+/// Outcome of a program execution.
+#[wasm_bindgen(js_name = Outcome)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WasmOutcome {
+    ok: bool,
+    co: bool,
+    steps: u64,
+    complexity: u64,
+    failure: Option<String>,
+    regs: [Option<fe256>; 16],
+}
+
+#[wasm_bindgen(js_class = Outcome)]
+impl WasmOutcome {
+    /// Whether the program has completed successfully (`CK` is not failed).
+    #[wasm_bindgen(getter)]
+    pub fn ok(&self) -> bool { self.ok }
+
+    /// Whether the final value of the `CO` register is ok.
+    #[wasm_bindgen(getter)]
+    pub fn co(&self) -> bool { self.co }
+
+    /// Number of the executed instructions.
+    #[wasm_bindgen(getter)]
+    pub fn steps(&self) -> u64 { self.steps }
+
+    /// Complexity consumed by the execution.
+    #[wasm_bindgen(getter)]
+    pub fn complexity(&self) -> u64 { self.complexity }
+
+    /// Site and the instruction which has failed `CK` first, if any.
+    #[wasm_bindgen(getter)]
+    pub fn failure(&self) -> Option<String> { self.failure.clone() }
+
+    /// Returns the final value of a register given by its name, or `undefined` if the register is
+    /// not set.
+    pub fn reg(&self, name: &str) -> Result<Option<String>, JsError> {
+        self.reg_value(name).map_err(|err| JsError::new(&err))
+    }
+
+    /// Returns final values of all set registers as `REG=VALUE` strings.
+    pub fn registers(&self) -> Vec<String> {
+        RegE::ALL
+            .into_iter()
+            .zip(self.regs)
+            .filter_map(|(reg, val)| Some(format!("{reg}={}", val?)))
+            .collect()
+    }
+}
+
+impl WasmOutcome {
+    fn reg_value(&self, name: &str) -> Result<Option<String>, String> {
+        let reg = RegE::from_str(name.trim()).map_err(|err| err.to_string())?;
+        Ok(self.regs[reg as usize].map(|val| val.to_string()))
+    }
+}
+
+fn parse_field(s: &str) -> Result<u256, String> { parse_field_order(s).ok_or_else(|| format!("invalid field `{s}`")) }
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+
+    const SOURCE: &str = r#"
+        routine MAIN:
+            mov     E1, EA
+            mul     E1, EB
+            add     E1, EA
+            fits    E1, 8.bits
+            chk     CO
+            ret
+    "#;
+
+    #[test]
+    fn run() {
+        let program = WasmProgram::assemble(SOURCE, "stark").unwrap();
+        let outcome = program.exec(&[s!("EA=3"), s!(" EB = 0x10 ")]).unwrap();
+        assert!(outcome.ok());
+        assert!(outcome.co());
+        assert_eq!(outcome.steps(), 7);
+        assert!(outcome.complexity() > 0);
+        assert_eq!(outcome.failure(), None);
+        assert_eq!(outcome.reg_value("E1").unwrap(), Some(fe256::from(51u8).to_string()));
+        assert_eq!(outcome.reg_value("E2").unwrap(), None);
+        assert!(outcome.reg_value("E9").is_err());
+        assert_eq!(outcome.registers(), vec![
+            format!("E1={}", fe256::from(51u8)),
+            format!("EA={}", fe256::from(3u8)),
+            format!("EB={}", fe256::from(16u8)),
+        ]);
+
+        let outcome = program.exec(&[s!("EA=3"), s!("EB=0x100")]).unwrap();
+        assert!(!outcome.ok());
+        assert!(outcome.failure().unwrap().ends_with("chk     CO"));
+    }
+
+    #[test]
+    fn inputs() {
+        let program = WasmProgram::assemble(SOURCE, "babybear").unwrap();
+        assert!(program.exec(&[s!("EA")]).is_err());
+        assert!(program.exec(&[s!("EZ=1")]).is_err());
+        assert!(program.exec(&[s!("EA=x")]).is_err());
+        assert!(program.exec(&[s!("EA=0x78000001")]).is_err());
+    }
+
+    #[test]
+    fn load() {
+        let program = WasmProgram::assemble(SOURCE, "25519").unwrap();
+        let loaded = WasmProgram::load_armored(&program.armored(), "25519").unwrap();
+        assert_eq!(loaded, program);
+        assert_eq!(loaded.lib_id(), program.lib.lib_id().to_string());
+        assert!(program.disassemble().unwrap().contains("mul     E1, EB"));
+
+        assert!(WasmProgram::assemble("nope", "25519").is_err());
+        assert!(WasmProgram::assemble(SOURCE, "pallas").is_err());
+        assert_eq!(parse_field("0x11"), Ok(u256::from(17u8)));
+    }
+}
@@ -0,0 +1,720 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Algebraic execution traces (AIR) for STARK provers.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::{self, Display, Formatter};
+use core::ops::{Add, Mul, Sub};
+
+use aluvm::LibId;
+use amplify::num::u256;
+
+use super::{field, ExecTrace, TraceState};
+use crate::gfa::{FieldInstr, Instr};
+use crate::{fe256, RegE};
+
+/// Column holding the value of the `E` register with the provided number.
+const fn val(reg: usize) -> usize { reg }
+/// Column holding `1` if the `E` register with the provided number is initialized.
+const fn init(reg: usize) -> usize { 16 + reg }
+const CO: usize = 32;
+const CK: usize = 33;
+/// Number of the columns holding the register state, which precede the instruction columns.
+pub(super) const STATE_COLUMNS: usize = CK + 1;
+const SEL_CLR: usize = 34;
+const SEL_PUT: usize = 35;
+const SEL_MOV: usize = 36;
+const SEL_NEG: usize = 37;
+const SEL_ADD: usize = 38;
+const SEL_MUL: usize = 39;
+const SEL_EQ: usize = 40;
+const SEL_TEST: usize = 41;
+const SEL_FITS: usize = 42;
+const SEL_INV: usize = 43;
+const SEL_EXP: usize = 44;
+const SEL_SQR: usize = 45;
+const SEL_SQRT: usize = 46;
+const SEL_CMOV: usize = 47;
+const SEL_SEL: usize = 48;
+const SEL_LT: usize = 49;
+const SEL_RNG: usize = 50;
+const SEL_CTRL: usize = 51;
+/// Column selecting the destination (or the first) operand register.
+const fn dst(reg: usize) -> usize { 52 + reg }
+/// Column selecting the source (or the second) operand register.
+const fn src(reg: usize) -> usize { 68 + reg }
+const IMM: usize = 84;
+const DST: usize = 85;
+const SRC: usize = 86;
+const RES: usize = 87;
+const WR: usize = 88;
+const CL: usize = 89;
+pub(super) const COLUMNS: usize = 90;
+
+const SELECTORS: [(usize, &str); 18] = [
+    (SEL_CLR, "s_clr"),
+    (SEL_PUT, "s_put"),
+    (SEL_MOV, "s_mov"),
+    (SEL_NEG, "s_neg"),
+    (SEL_ADD, "s_add"),
+    (SEL_MUL, "s_mul"),
+    (SEL_EQ, "s_eq"),
+    (SEL_TEST, "s_test"),
+    (SEL_FITS, "s_fits"),
+    (SEL_INV, "s_inv"),
+    (SEL_EXP, "s_exp"),
+    (SEL_SQR, "s_sqr"),
+    (SEL_SQRT, "s_sqrt"),
+    (SEL_CMOV, "s_cmov"),
+    (SEL_SEL, "s_sel"),
+    (SEL_LT, "s_lt"),
+    (SEL_RNG, "s_rng"),
+    (SEL_CTRL, "s_ctrl"),
+];
+
+/// Polynomial expression over the columns of two consecutive rows of an [`AirTrace`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AirExpr {
+    /// Value of a column in the current row.
+    Cur(usize),
+    /// Value of a column in the next row.
+    Next(usize),
+    /// Constant.
+    Const(fe256),
+    /// Sum of two expressions.
+    Add(Box<AirExpr>, Box<AirExpr>),
+    /// Difference of two expressions.
+    Sub(Box<AirExpr>, Box<AirExpr>),
+    /// Product of two expressions.
+    Mul(Box<AirExpr>, Box<AirExpr>),
+}
+
+impl AirExpr {
+    fn one() -> Self { AirExpr::Const(fe256::from(1u8)) }
+
+    fn sum(items: impl IntoIterator<Item = AirExpr>) -> Self {
+        items
+            .into_iter()
+            .reduce(|acc, item| acc + item)
+            .unwrap_or(AirExpr::Const(fe256::ZERO))
+    }
+
+    /// Returns the degree of the expression as a polynomial over the trace columns.
+    pub fn degree(&self) -> usize {
+        match self {
+            AirExpr::Cur(_) | AirExpr::Next(_) => 1,
+            AirExpr::Const(_) => 0,
+            AirExpr::Add(a, b) | AirExpr::Sub(a, b) => a.degree().max(b.degree()),
+            AirExpr::Mul(a, b) => a.degree() + b.degree(),
+        }
+    }
+
+    /// Evaluates the expression over two consecutive rows.
+    pub fn eval(&self, cur: &[fe256], next: &[fe256], field_order: u256) -> fe256 {
+        match self {
+            AirExpr::Cur(col) => cur[*col],
+            AirExpr::Next(col) => next[*col],
+            AirExpr::Const(val) => *val,
+            AirExpr::Add(a, b) => {
+                field::add(field_order, a.eval(cur, next, field_order), b.eval(cur, next, field_order))
+            }
+            AirExpr::Sub(a, b) => field::add(
+                field_order,
+                a.eval(cur, next, field_order),
+                field::neg(field_order, b.eval(cur, next, field_order)),
+            ),
+            AirExpr::Mul(a, b) => {
+                field::mul(field_order, a.eval(cur, next, field_order), b.eval(cur, next, field_order))
+            }
+        }
+    }
+
+    fn describe(&self, columns: &[String]) -> String {
+        match self {
+            AirExpr::Cur(col) => columns[*col].clone(),
+            AirExpr::Next(col) => format!("{}'", columns[*col]),
+            AirExpr::Const(val) => val.to_string(),
+            AirExpr::Add(a, b) => format!("({} + {})", a.describe(columns), b.describe(columns)),
+            AirExpr::Sub(a, b) => format!("({} - {})", a.describe(columns), b.describe(columns)),
+            AirExpr::Mul(a, b) => format!("{} * {}", a.describe(columns), b.describe(columns)),
+        }
+    }
+}
+
+impl Add for AirExpr {
+    type Output = AirExpr;
+    fn add(self, rhs: Self) -> Self::Output { AirExpr::Add(Box::new(self), Box::new(rhs)) }
+}
+
+impl Sub for AirExpr {
+    type Output = AirExpr;
+    fn sub(self, rhs: Self) -> Self::Output { AirExpr::Sub(Box::new(self), Box::new(rhs)) }
+}
+
+impl Mul for AirExpr {
+    type Output = AirExpr;
+    fn mul(self, rhs: Self) -> Self::Output { AirExpr::Mul(Box::new(self), Box::new(rhs)) }
+}
+
+/// Transition constraint of an [`AirTrace`], which must evaluate to zero over each pair of
+/// consecutive rows of the trace.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AirConstraint {
+    /// Name of the constraint.
+    pub name: String,
+    /// Constraint polynomial.
+    pub expr: AirExpr,
+    /// Human-readable description of the constraint polynomial, using column names.
+    pub description: String,
+}
+
+impl AirConstraint {
+    /// Returns the degree of the constraint polynomial, as required for Winterfell's
+    /// `TransitionConstraintDegree`.
+    pub fn degree(&self) -> usize { self.expr.degree() }
+}
+
+impl Display for AirConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{}: {} = 0", self.name, self.description) }
+}
+
+/// Algebraic execution trace of a program, suitable for STARK provers.
+///
+/// Each row of the trace describes the state of the registers before an instruction execution
+/// together with the executed instruction; the last row holds the state at the end of the
+/// execution, with all the instruction columns set to zero. The trace has the following columns:
+/// - `e1`..`eh`: values of the `E` registers (zero for uninitialized registers);
+/// - `i1`..`ih`: `1` for initialized `E` registers;
+/// - `co`, `ck`: `1` for the `CO` and `CK` registers in the [`Status::Ok`](aluvm::regs::Status)
+///   state;
+/// - `s_clr`..`s_ctrl`: one-hot selectors of the executed operation;
+/// - `d1`..`dh`, `r1`..`rh`: one-hot selectors of the destination (first) and source (second)
+///   operand registers;
+/// - `imm`: immediate operand (the value written by `put`, the bit length checked by `fits`, the
+///   bound checked by `rng` or the exponent of `exp`);
+/// - `dst`, `src`: values of the operand registers;
+/// - `res`: value written to the destination register;
+/// - `wr`, `cl`: `1` if the instruction has written a value to the destination register or has
+///   cleared it.
+///
+/// The transition constraints (see [`AirTrace::constraints`]) enforce the arithmetics of the
+/// executed operations and the updates of the register file. Values of the `CO` and `CK` flags
+/// are only constrained to be booleans. The result of `exp` is constrained only to be written to
+/// the destination register, since exponentiation has no single-row constraint of a bounded
+/// degree; the result of `sqrt` is constrained to be a square root of the source, but not to be
+/// the smaller one. `cmov` is constrained to write or clear the destination register exactly
+/// when `CO` is set. For `sel`, the source operand is the register chosen by `CO`; since the
+/// operand registers are not bound to the program, the choice itself is not constrained.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AirTrace {
+    /// Order of the field over which the trace is defined.
+    pub field_order: u256,
+    /// Names of the trace columns.
+    pub columns: Vec<String>,
+    /// Rows of the trace.
+    pub rows: Vec<Vec<fe256>>,
+    /// Transition constraints.
+    pub constraints: Vec<AirConstraint>,
+}
+
+impl AirTrace {
+    /// Constructs the algebraic trace of an execution.
+    pub fn from_trace(trace: &ExecTrace) -> Self {
+        let order = trace.field_order;
+        let mut rows = Vec::with_capacity(trace.len() + 1);
+        for (no, step) in trace.steps.iter().enumerate() {
+            let mut row = state_row(&step.before);
+            if let Some(instr) = step.instr {
+                fill_instr(&mut row, instr, &step.before, trace.after(no), order);
+            }
+            rows.push(row);
+        }
+        rows.push(state_row(&trace.end));
+
+        let columns = column_names();
+        let constraints = constraints()
+            .into_iter()
+            .map(|(name, expr)| AirConstraint {
+                description: expr.describe(&columns),
+                name,
+                expr,
+            })
+            .collect();
+        AirTrace {
+            field_order: order,
+            columns,
+            rows,
+            constraints,
+        }
+    }
+
+    /// Returns the number of the rows.
+    pub fn len(&self) -> usize { self.rows.len() }
+
+    /// Checks whether the trace has no rows.
+    pub fn is_empty(&self) -> bool { self.rows.is_empty() }
+
+    /// Returns the maximal degree of the transition constraints.
+    pub fn max_degree(&self) -> usize {
+        self.constraints
+            .iter()
+            .map(AirConstraint::degree)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Pads the trace to a power of two number of rows (and at least `min_len` rows) by
+    /// repeating its last row, as required by STARK provers.
+    ///
+    /// Repeated final rows don't execute any instruction, so the padded trace satisfies the same
+    /// constraints.
+    pub fn pad(&mut self, min_len: usize) {
+        let Some(last) = self.rows.last().cloned() else {
+            return;
+        };
+        let len = self.rows.len().max(min_len).next_power_of_two();
+        self.rows.resize(len, last);
+    }
+
+    /// Returns the trace in the column-major form used by Winterfell's `TraceTable`.
+    pub fn to_columns(&self) -> Vec<Vec<fe256>> {
+        (0..self.columns.len())
+            .map(|col| self.rows.iter().map(|row| row[col]).collect())
+            .collect()
+    }
+
+    /// Finds the first violated transition constraint, returning the index of the row to which
+    /// the constraint was applied, together with the constraint.
+    pub fn violation(&self) -> Option<(usize, &AirConstraint)> {
+        self.rows.windows(2).enumerate().find_map(|(no, rows)| {
+            self.constraints
+                .iter()
+                .find(|c| c.expr.eval(&rows[0], &rows[1], self.field_order) != fe256::ZERO)
+                .map(|c| (no, c))
+        })
+    }
+}
+
+fn column_names() -> Vec<String> {
+    let mut names = vec![String::new(); COLUMNS];
+    for reg in RegE::ALL {
+        let no = reg as usize;
+        let name = reg.to_string()[1..].to_lowercase();
+        names[val(no)] = format!("e{name}");
+        names[init(no)] = format!("i{name}");
+        names[dst(no)] = format!("d{name}");
+        names[src(no)] = format!("r{name}");
+    }
+    for (col, name) in
+        [(CO, "co"), (CK, "ck"), (IMM, "imm"), (DST, "dst"), (SRC, "src"), (RES, "res"), (WR, "wr"), (CL, "cl")]
+            .into_iter()
+            .chain(SELECTORS)
+    {
+        names[col] = name.to_string();
+    }
+    names
+}
+
+fn flag(ok: bool) -> fe256 {
+    if ok {
+        fe256::from(1u8)
+    } else {
+        fe256::ZERO
+    }
+}
+
+fn state_row(state: &TraceState) -> Vec<fe256> {
+    let mut row = vec![fe256::ZERO; COLUMNS];
+    for (no, val) in state.regs.iter().enumerate() {
+        row[self::val(no)] = val.unwrap_or(fe256::ZERO);
+        row[init(no)] = flag(val.is_some());
+    }
+    row[CO] = flag(state.co.is_ok());
+    row[CK] = flag(state.ck.is_ok());
+    row
+}
+
+fn fill_instr(row: &mut [fe256], instr: Instr<LibId>, before: &TraceState, after: &TraceState, order: u256) {
+    let one = fe256::from(1u8);
+    let instr = match instr {
+        Instr::Gfa(instr) => instr,
+        Instr::Ctrl(_) | Instr::Reserved(_) => {
+            row[SEL_CTRL] = one;
+            return;
+        }
+    };
+    let (sel, d, r) = match instr {
+        FieldInstr::Clr { dst } => (SEL_CLR, Some(dst), None),
+        FieldInstr::PutD { dst, data: _ } | FieldInstr::PutZ { dst } | FieldInstr::PutV { dst, val: _ } => {
+            (SEL_PUT, Some(dst), None)
+        }
+        FieldInstr::Mov { dst, src } => (SEL_MOV, Some(dst), Some(src)),
+        FieldInstr::Neg { dst, src } => (SEL_NEG, Some(dst), Some(src)),
+        FieldInstr::Add { dst_src, src } => (SEL_ADD, Some(dst_src), Some(src)),
+        FieldInstr::Mul { dst_src, src } => (SEL_MUL, Some(dst_src), Some(src)),
+        FieldInstr::Inv { dst, src } => (SEL_INV, Some(dst), Some(src)),
+        FieldInstr::Exp { dst_src, exp } => (SEL_EXP, Some(dst_src), Some(exp)),
+        FieldInstr::Sqr { dst, src } => (SEL_SQR, Some(dst), Some(src)),
+        FieldInstr::Sqrt { dst, src } => (SEL_SQRT, Some(dst), Some(src)),
+        FieldInstr::CMov { dst, src } => (SEL_CMOV, Some(dst), Some(src)),
+        FieldInstr::Sel { dst, src_true, src_false } => {
+            (SEL_SEL, Some(dst), Some(if before.co.is_ok() { src_true } else { src_false }))
+        }
+        FieldInstr::ExpI { dst_src, exp } => {
+            row[IMM] = fe256::from(exp);
+            (SEL_EXP, Some(dst_src), None)
+        }
+        FieldInstr::Eq { src1, src2 } => (SEL_EQ, Some(src1), Some(src2)),
+        FieldInstr::Lt { src1, src2 } => (SEL_LT, Some(src1), Some(src2)),
+        FieldInstr::Rng { src, bound } => {
+            row[IMM] = bound;
+            (SEL_RNG, Some(src), None)
+        }
+        FieldInstr::Test { src } => (SEL_TEST, Some(src), None),
+        FieldInstr::Fits { src, bits } => {
+            row[IMM] = fe256::from(bits.bit_len() as u64);
+            (SEL_FITS, Some(src), None)
+        }
+        // Host syscalls are not constrained, as are the reserved instructions handled by the host
+        FieldInstr::Ecall { .. } => {
+            row[SEL_CTRL] = one;
+            return;
+        }
+    };
+    row[sel] = one;
+    if let Some(r) = r {
+        row[src(r as usize)] = one;
+        row[SRC] = before.reg(r).unwrap_or(fe256::ZERO);
+    }
+    let Some(d) = d else { return };
+    row[dst(d as usize)] = one;
+    row[DST] = before.reg(d).unwrap_or(fe256::ZERO);
+
+    let written = match instr {
+        FieldInstr::PutD { .. } | FieldInstr::PutZ { .. } | FieldInstr::PutV { .. } => true,
+        FieldInstr::Mov { src, .. } | FieldInstr::Neg { src, .. } | FieldInstr::Sqr { src, .. } => {
+            before.reg(src).is_some()
+        }
+        FieldInstr::ExpI { dst_src, .. } => before.reg(dst_src).is_some(),
+        FieldInstr::Add { dst_src, src } | FieldInstr::Mul { dst_src, src } | FieldInstr::Exp { dst_src, exp: src } => {
+            before.reg(dst_src).is_some() && before.reg(src).is_some()
+        }
+        FieldInstr::Inv { dst, src } => match (before.reg(src), after.reg(dst)) {
+            (Some(a), Some(b)) => field::mul(order, a, b) == one,
+            _ => false,
+        },
+        FieldInstr::Sqrt { dst, src } => match (before.reg(src), after.reg(dst)) {
+            (Some(a), Some(b)) => field::mul(order, b, b) == a,
+            _ => false,
+        },
+        FieldInstr::CMov { src, .. } => before.co.is_ok() && before.reg(src).is_some(),
+        FieldInstr::Sel { .. } => r.is_some_and(|r| before.reg(r).is_some()),
+        _ => false,
+    };
+    let cleared = match instr {
+        FieldInstr::Clr { .. } => true,
+        FieldInstr::Mov { src, .. } => before.reg(src).is_none(),
+        FieldInstr::CMov { src, .. } => before.co.is_ok() && before.reg(src).is_none(),
+        FieldInstr::Sel { .. } => r.is_some_and(|r| before.reg(r).is_none()),
+        _ => false,
+    };
+    if written {
+        row[RES] = after.reg(d).unwrap_or(fe256::ZERO);
+        row[WR] = one;
+        if sel == SEL_PUT {
+            row[IMM] = row[RES];
+        }
+    }
+    if cleared {
+        row[CL] = one;
+    }
+}
+
+pub(super) fn constraints() -> Vec<(String, AirExpr)> {
+    use AirExpr::{Cur, Next};
+
+    let one = AirExpr::one;
+    let boolean = |col: usize| Cur(col) * (Cur(col) - one());
+    let mut constraints = Vec::new();
+
+    for (col, name) in [(CO, "co"), (CK, "ck"), (WR, "wr"), (CL, "cl")]
+        .into_iter()
+        .chain(SELECTORS)
+    {
+        constraints.push((format!("{name}_bool"), boolean(col)));
+    }
+    let selectors = AirExpr::sum(SELECTORS.map(|(col, _)| Cur(col)));
+    constraints.push(("sel_one_hot".to_string(), selectors.clone() * (selectors - one())));
+    for (name, sel) in [("d", dst as fn(usize) -> usize), ("r", src)] {
+        for no in 0..16 {
+            constraints.push((format!("{name}{no}_bool"), boolean(sel(no))));
+        }
+        let sum = AirExpr::sum((0..16).map(|no| Cur(sel(no))));
+        constraints.push((format!("{name}_one_hot"), sum.clone() * (sum - one())));
+    }
+    for (col, sel, name) in [(DST, dst as fn(usize) -> usize, "dst"), (SRC, src, "src")] {
+        let operand = AirExpr::sum((0..16).map(|no| Cur(sel(no)) * Cur(val(no))));
+        constraints.push((format!("{name}_value"), Cur(col) - operand));
+    }
+
+    constraints.push((
+        "wr_writers".to_string(),
+        Cur(WR)
+            * (one()
+                - Cur(SEL_PUT)
+                - Cur(SEL_MOV)
+                - Cur(SEL_NEG)
+                - Cur(SEL_ADD)
+                - Cur(SEL_MUL)
+                - Cur(SEL_INV)
+                - Cur(SEL_EXP)
+                - Cur(SEL_SQR)
+                - Cur(SEL_SQRT)
+                - Cur(SEL_CMOV)
+                - Cur(SEL_SEL)),
+    ));
+    constraints.push((
+        "cl_clearers".to_string(),
+        Cur(CL) * (one() - Cur(SEL_CLR) - Cur(SEL_MOV) - Cur(SEL_CMOV) - Cur(SEL_SEL)),
+    ));
+    constraints.push(("put".to_string(), Cur(SEL_PUT) * (Cur(RES) - Cur(IMM))));
+    constraints.push(("mov".to_string(), Cur(SEL_MOV) * Cur(WR) * (Cur(RES) - Cur(SRC))));
+    constraints.push(("cmov".to_string(), Cur(SEL_CMOV) * Cur(WR) * (Cur(RES) - Cur(SRC))));
+    constraints.push(("cmov_co".to_string(), Cur(SEL_CMOV) * (Cur(CO) - Cur(WR) - Cur(CL))));
+    constraints.push(("sel".to_string(), Cur(SEL_SEL) * Cur(WR) * (Cur(RES) - Cur(SRC))));
+    constraints.push(("sel_wr".to_string(), Cur(SEL_SEL) * (one() - Cur(WR) - Cur(CL))));
+    constraints.push(("neg".to_string(), Cur(SEL_NEG) * Cur(WR) * (Cur(RES) + Cur(SRC))));
+    constraints.push(("add".to_string(), Cur(SEL_ADD) * Cur(WR) * (Cur(RES) - Cur(DST) - Cur(SRC))));
+    constraints.push(("mul".to_string(), Cur(SEL_MUL) * (Cur(WR) * Cur(RES) - Cur(DST) * Cur(SRC))));
+    constraints.push(("sqr".to_string(), Cur(SEL_SQR) * (Cur(WR) * Cur(RES) - Cur(SRC) * Cur(SRC))));
+    constraints.push(("sqrt".to_string(), Cur(SEL_SQRT) * (Cur(WR) * Cur(SRC) - Cur(RES) * Cur(RES))));
+    constraints.push(("inv".to_string(), Cur(SEL_INV) * (Cur(RES) * Cur(SRC) - Cur(WR))));
+
+    for reg in RegE::ALL {
+        let no = reg as usize;
+        let name = reg.to_string().to_lowercase();
+        let written = Cur(WR) * (Cur(RES) - Cur(val(no))) - Cur(CL) * Cur(val(no));
+        constraints.push((format!("{name}_update"), Next(val(no)) - Cur(val(no)) - Cur(dst(no)) * written));
+        let init = Cur(WR) * (one() - Cur(self::init(no))) - Cur(CL) * Cur(self::init(no));
+        constraints.push((format!("{name}_init"), Next(self::init(no)) - Cur(self::init(no)) - Cur(dst(no)) * init));
+    }
+    constraints
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::{Debugger, GfaConfig, FIELD_ORDER_STARK};
+
+    fn trace(source: &str) -> AirTrace {
+        let lib = Assembly::parse(source).unwrap().assemble().unwrap();
+        let entry = LibSite::new(lib.lib_id(), 0);
+        let vm = Vm::<Instr<LibId>>::with(
+            aluvm::CoreConfig {
+                halt: false,
+                complexity_lim: None,
+            },
+            GfaConfig::new(FIELD_ORDER_STARK),
+        );
+        let mut debugger = Debugger::new(vm, [lib]);
+        debugger.start(entry);
+        AirTrace::from_trace(&ExecTrace::record(&mut debugger))
+    }
+
+    #[test]
+    fn satisfied() {
+        let mut air = trace(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                put     E2, 1
+                neg     E2, E2
+                mov     E3, E1
+                mul     E3, E1
+                add     E3, E1
+                neg     E4, E3
+                eq      E3, E4
+                fits    E3, 8.bits
+                add     E5, E1
+                mov     E1, E6
+                clr     E2
+                test    E1
+        "#,
+        );
+        assert_eq!(air.len(), 15);
+        assert_eq!(air.columns.len(), COLUMNS);
+        assert_eq!(air.columns[val(0)], "e1");
+        assert_eq!(air.columns[init(15)], "ih");
+        assert_eq!(air.max_degree(), 3);
+        assert_eq!(air.violation(), None);
+
+        let last = air.rows[14].clone();
+        assert_eq!(last[val(2)], fe256::from(12u8));
+        assert_eq!(last[init(0)], fe256::ZERO);
+        assert_eq!(last[CK], fe256::ZERO);
+
+        air.pad(8);
+        assert_eq!(air.len(), 16);
+        assert_eq!(air.violation(), None);
+        let columns = air.to_columns();
+        assert_eq!(columns.len(), COLUMNS);
+        assert_eq!(columns[val(2)][15], fe256::from(12u8));
+    }
+
+    #[test]
+    fn violation() {
+        let mut air = trace(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                mul     E1, E1
+        "#,
+        );
+        air.rows[2][val(0)] = fe256::from(10u8);
+        let (row, constraint) = air.violation().unwrap();
+        assert_eq!(row, 1);
+        assert_eq!(constraint.name, "e1_update");
+        assert_eq!(constraint.to_string(), "e1_update: ((e1' - e1) - d1 * (wr * (res - e1) - cl * e1)) = 0");
+
+        let mut air = trace("put E1, 3\nmul E1, E1");
+        air.rows[1][RES] = fe256::from(10u8);
+        assert_eq!(air.violation().unwrap().1.name, "mul");
+    }
+
+    #[test]
+    fn inv() {
+        let mut air = trace("put E1, 3\ninv E2, E1\nput E3, 0\ninv E4, E3");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[1][WR], fe256::from(1u8));
+        assert_eq!(air.rows[3][WR], fe256::ZERO);
+        assert_eq!(air.rows[4][init(3)], fe256::ZERO);
+
+        air.rows[1][RES] = fe256::from(10u8);
+        air.rows[2][val(1)] = fe256::from(10u8);
+        assert_eq!(air.violation().unwrap().1.name, "inv");
+    }
+
+    #[test]
+    fn exp() {
+        let mut air = trace("put E1, 3\nput E2, 5\nexp E1, E2");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[2][SEL_EXP], fe256::from(1u8));
+        assert_eq!(air.rows[2][WR], fe256::from(1u8));
+        assert_eq!(air.rows[3][val(0)], fe256::from(243u8));
+
+        air.rows[3][val(0)] = fe256::from(10u8);
+        assert_eq!(air.violation().unwrap().1.name, "e1_update");
+    }
+
+    #[test]
+    fn sqr() {
+        let mut air = trace("put E1, 7\nsqr E2, E1\nsqr E3, E4");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[1][SEL_SQR], fe256::from(1u8));
+        assert_eq!(air.rows[1][RES], fe256::from(49u8));
+        assert_eq!(air.rows[2][WR], fe256::ZERO);
+        assert_eq!(air.rows[3][val(1)], fe256::from(49u8));
+
+        air.rows[1][RES] = fe256::from(50u8);
+        air.rows[2][val(1)] = fe256::from(50u8);
+        assert_eq!(air.violation().unwrap().1.name, "sqr");
+    }
+
+    #[test]
+    fn sqrt() {
+        let mut air = trace("put E1, 9\nsqrt E2, E1\nsqrt E3, E4");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[1][SEL_SQRT], fe256::from(1u8));
+        assert_eq!(air.rows[1][RES], fe256::from(3u8));
+        assert_eq!(air.rows[2][WR], fe256::ZERO);
+        assert_eq!(air.rows[3][val(1)], fe256::from(3u8));
+
+        air.rows[1][RES] = fe256::from(4u8);
+        air.rows[2][val(1)] = fe256::from(4u8);
+        assert_eq!(air.violation().unwrap().1.name, "sqrt");
+    }
+
+    #[test]
+    fn cmov() {
+        let mut air = trace("put E1, 3\nput E2, 5\neq E1, E2\ncmov E1, E2\nnot CO\ncmov E1, E2\ncmov E2, E3");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[3][SEL_CMOV], fe256::from(1u8));
+        assert_eq!(air.rows[3][WR], fe256::ZERO);
+        assert_eq!(air.rows[4][val(0)], fe256::from(3u8));
+        assert_eq!(air.rows[5][WR], fe256::from(1u8));
+        assert_eq!(air.rows[6][val(0)], fe256::from(5u8));
+        assert_eq!(air.rows[6][CL], fe256::from(1u8));
+        assert_eq!(air.rows[7][init(1)], fe256::ZERO);
+
+        air.rows[3][WR] = fe256::from(1u8);
+        air.rows[3][RES] = fe256::from(5u8);
+        air.rows[4][val(0)] = fe256::from(5u8);
+        assert_eq!(air.violation().unwrap().1.name, "cmov_co");
+    }
+
+    #[test]
+    fn sel() {
+        let mut air = trace("put E1, 3\nput E2, 5\neq E1, E2\nsel E3, E1, E2\nnot CO\nsel E4, E1, E2\nsel E5, E6, E1");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[3][SEL_SEL], fe256::from(1u8));
+        assert_eq!(air.rows[3][src(1)], fe256::from(1u8));
+        assert_eq!(air.rows[4][val(2)], fe256::from(5u8));
+        assert_eq!(air.rows[5][src(0)], fe256::from(1u8));
+        assert_eq!(air.rows[6][val(3)], fe256::from(3u8));
+        assert_eq!(air.rows[6][CL], fe256::from(1u8));
+        assert_eq!(air.rows[7][init(4)], fe256::ZERO);
+
+        air.rows[3][WR] = fe256::ZERO;
+        air.rows[4][val(2)] = fe256::ZERO;
+        air.rows[4][init(2)] = fe256::ZERO;
+        assert_eq!(air.violation().unwrap().1.name, "sel_wr");
+    }
+
+    #[test]
+    fn lt() {
+        let air = trace("put E1, 3\nput E2, 5\nlt E1, E2");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[2][SEL_LT], fe256::from(1u8));
+        assert_eq!(air.rows[2][DST], fe256::from(3u8));
+        assert_eq!(air.rows[2][SRC], fe256::from(5u8));
+        assert_eq!(air.rows[3][CO], fe256::from(1u8));
+    }
+
+    #[test]
+    fn rng() {
+        let air = trace("put E1, 1000000000\nrng E1, 1000000000");
+        assert_eq!(air.violation(), None);
+        assert_eq!(air.rows[1][SEL_RNG], fe256::from(1u8));
+        assert_eq!(air.rows[1][DST], fe256::from(1_000_000_000u32));
+        assert_eq!(air.rows[1][IMM], fe256::from(1_000_000_000u32));
+        assert_eq!(air.rows[2][CO], fe256::ZERO);
+    }
+}
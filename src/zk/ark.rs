@@ -0,0 +1,260 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Adapter of [`R1cs`] to arkworks constraint synthesizers.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use aluvm::SiteId;
+use amplify::num::u256;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+
+use super::{LinComb, R1cs, WitnessError};
+use crate::gfa::{DecompileError, Instr};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Errors constructing an [`ArkCircuit`].
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ArkError {
+    /// the program is defined over a field of order {0:#x}, which doesn't match the field of the
+    /// arkworks circuit.
+    FieldMismatch(u256),
+
+    /// the program can't be compiled into constraints: {0}
+    #[from]
+    Decompile(DecompileError),
+
+    /// unable to compute the witness: {0}
+    #[from]
+    Witness(WitnessError),
+}
+
+/// Converts a field element into an element of the arkworks field `F`, reducing it modulo the
+/// order of `F`.
+pub fn to_ark<F: PrimeField>(val: fe256) -> F { F::from_le_bytes_mod_order(&val.to_u256().to_le_bytes()) }
+
+/// Circuit for arkworks provers (like Groth16 or Marlin) constraining the arithmetics of a GFA
+/// program.
+///
+/// The public inputs of the circuit are the final values of the registers modified by the program
+/// (see [`R1cs::outputs`]). A circuit without inputs is used for the setup of the proof system;
+/// the prover uses the circuit with the register values at the start of the program provided via
+/// [`ArkCircuit::with_inputs`].
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify;
+/// # use ark_bn254::Fr;
+/// # use ark_ff::{BigInteger, PrimeField};
+/// # use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+/// # use amplify::num::u256;
+/// # use zkaluvm::zk::ArkCircuit;
+/// # use zkaluvm::{fe256, zk_aluasm, GfaConfig, RegE::*};
+/// let field_order = u256::from_le_slice(&Fr::MODULUS.to_bytes_le()).unwrap();
+/// let config = GfaConfig {
+///     field_order,
+///     step_lim: None,
+///     ..GfaConfig::default()
+/// };
+/// let code = zk_aluasm! { mov E1, EA; mul E1, EB; };
+/// let circuit = ArkCircuit::<Fr>::compile(&code, config)
+///     .unwrap()
+///     .with_inputs(&bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8) })
+///     .unwrap();
+/// assert_eq!(circuit.public_inputs(), Some(vec![Fr::from(12u8)]));
+///
+/// let cs = ConstraintSystem::new_ref();
+/// circuit.generate_constraints(cs.clone()).unwrap();
+/// assert!(cs.is_satisfied().unwrap());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArkCircuit<F: PrimeField> {
+    r1cs: R1cs,
+    witness: Option<Vec<fe256>>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: PrimeField> ArkCircuit<F> {
+    /// Constructs the circuit for a constraint system, checking that it is defined over the field
+    /// `F`.
+    pub fn new(r1cs: R1cs) -> Result<Self, ArkError> {
+        let modulus = F::MODULUS.to_bytes_le();
+        if u256::from_le_slice(&modulus).ok() != Some(r1cs.field_order) {
+            return Err(ArkError::FieldMismatch(r1cs.field_order));
+        }
+        Ok(ArkCircuit {
+            r1cs,
+            witness: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Compiles straight-line program code into the circuit (see [`R1cs::compile`]).
+    pub fn compile<Id: SiteId>(code: &[Instr<Id>], config: GfaConfig) -> Result<Self, ArkError> {
+        Self::new(R1cs::compile(code, config)?)
+    }
+
+    /// Assigns the witness, computing it from the register values at the start of the program.
+    pub fn with_inputs(mut self, inputs: &BTreeMap<RegE, fe256>) -> Result<Self, ArkError> {
+        self.witness = Some(self.r1cs.witness(inputs)?);
+        Ok(self)
+    }
+
+    /// Returns the constraint system of the circuit.
+    pub fn r1cs(&self) -> &R1cs { &self.r1cs }
+
+    /// Returns the public inputs for the proof verification, if the witness is assigned.
+    pub fn public_inputs(&self) -> Option<Vec<F>> {
+        let witness = self.witness.as_ref()?;
+        Some(
+            witness[1..self.r1cs.num_public()]
+                .iter()
+                .copied()
+                .map(to_ark)
+                .collect(),
+        )
+    }
+
+    fn value(&self, var: usize) -> Result<F, SynthesisError> {
+        self.witness
+            .as_ref()
+            .map(|witness| to_ark(witness[var]))
+            .ok_or(SynthesisError::AssignmentMissing)
+    }
+}
+
+fn lin_comb<F: PrimeField>(lc: &LinComb, vars: &[Variable]) -> LinearCombination<F> {
+    LinearCombination(
+        lc.iter()
+            .map(|(var, coeff)| (to_ark(*coeff), vars[*var]))
+            .collect(),
+    )
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for ArkCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let num_public = self.r1cs.num_public();
+        let mut vars = Vec::with_capacity(self.r1cs.num_vars);
+        vars.push(Variable::One);
+        for var in 1..self.r1cs.num_vars {
+            vars.push(if var < num_public {
+                cs.new_input_variable(|| self.value(var))?
+            } else {
+                cs.new_witness_variable(|| self.value(var))?
+            });
+        }
+        for ((a, b), c) in self.r1cs.a.iter().zip(&self.r1cs.b).zip(&self.r1cs.c) {
+            cs.enforce_constraint(lin_comb(a, &vars), lin_comb(b, &vars), lin_comb(c, &vars))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::RegE::*;
+
+    fn config() -> GfaConfig {
+        GfaConfig::new(u256::from_le_slice(&Fr::MODULUS.to_bytes_le()).unwrap())
+    }
+
+    fn code() -> Vec<Instr<aluvm::LibId>> {
+        Assembly::parse(
+            r#"
+            routine MAIN:
+                mov     E1, EA
+                mul     E1, EB
+                neg     E2, EA
+                mul     E2, E1
+                add     E1, E2
+        "#,
+        )
+        .unwrap()
+        .code
+    }
+
+    #[test]
+    fn synthesize() {
+        let circuit = ArkCircuit::<Fr>::compile(&code(), config())
+            .unwrap()
+            .with_inputs(&bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8) })
+            .unwrap();
+        // E2 = -3 * 12 = -36, E1 = 12 - 36 = -24
+        let outputs = circuit.public_inputs().unwrap();
+        assert_eq!(outputs, vec![-Fr::from(24u8), -Fr::from(36u8)]);
+
+        let cs = ConstraintSystem::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_instance_variables(), circuit.r1cs().num_public());
+        assert_eq!(cs.num_constraints(), circuit.r1cs().num_constraints());
+
+        let mut wrong = circuit.clone();
+        wrong.witness.as_mut().unwrap()[1] = fe256::from(1u8);
+        let cs = ConstraintSystem::new_ref();
+        wrong.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn setup() {
+        let circuit = ArkCircuit::<Fr>::compile(&code(), config()).unwrap();
+        assert_eq!(circuit.public_inputs(), None);
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.num_constraints(), circuit.r1cs().num_constraints());
+
+        let cs = ConstraintSystem::new_ref();
+        assert_eq!(circuit.generate_constraints(cs), Err(SynthesisError::AssignmentMissing));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            ArkCircuit::<Fr>::compile(&code(), GfaConfig::default()).unwrap_err(),
+            ArkError::FieldMismatch(GfaConfig::default().field_order)
+        );
+        let circuit = ArkCircuit::<Fr>::compile(&code(), config()).unwrap();
+        assert_eq!(
+            circuit
+                .with_inputs(&bmap! { EA => fe256::from(3u8) })
+                .unwrap_err(),
+            ArkError::Witness(WitnessError::MissingInput(EB))
+        );
+    }
+}
@@ -0,0 +1,313 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Export of executions into a Cairo-like memory and trace layout.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::isa::Bytecode;
+use aluvm::regs::Status;
+use aluvm::{LibId, LibSite, LibsSeg, Marshaller};
+use amplify::num::u256;
+
+use super::{ExecTrace, TraceState};
+use crate::gfa::{FieldInstr, Instr};
+use crate::{fe256, RegE, FIELD_ORDER_STARK};
+
+/// Number of memory cells in a register frame.
+pub const CAIRO_FRAME: u64 = 19;
+
+/// Errors exporting an execution into the Cairo layout.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum CairoError {
+    /// the execution is performed over a field of order {0:#x}, while Cairo requires the STARK
+    /// field.
+    FieldMismatch(u256),
+}
+
+/// Values of the Cairo registers for a single executed instruction.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CairoEntry {
+    /// Address of the instruction word in the program segment.
+    pub pc: u64,
+    /// Address of the first free cell, where the frame with the registers after the instruction
+    /// execution is written.
+    pub ap: u64,
+    /// Address of the frame with the registers before the instruction execution.
+    pub fp: u64,
+}
+
+/// Execution lowered into the relocated memory and register trace of a Cairo-like machine.
+///
+/// The memory is a write-once array of STARK field elements, addressed from `1`, consisting of
+/// two segments:
+/// - the program segment, holding a word for each executed instruction (in the order of the first
+///   execution), which is the big-endian integer of the instruction bytecode; `put` and `rng`
+///   instructions reading a value from the data segment are followed by a cell with the immediate
+///   value, like in Cairo;
+/// - the execution segment, holding a sequence of register frames of [`CAIRO_FRAME`] cells: the
+///   values of `E1`..`EH` registers (`0` for the registers without a value), a bit mask of the
+///   registers holding a value (bit `n` for the register `n`), and the values of `CO` and `CK` (`0`
+///   for [`Status::Ok`] and `1` for [`Status::Fail`]).
+///
+/// The first frame holds the state at the start of the execution; each executed instruction
+/// appends the frame with the state after its execution. For each instruction, the trace provides
+/// the `pc` pointing to the instruction word, the `fp` pointing to the frame before its execution
+/// and the `ap` pointing to the frame after it.
+///
+/// [`CairoTrace::trace_bytes`] and [`CairoTrace::memory_bytes`] convert the layout into the binary
+/// files produced by `cairo-run --trace_file --memory_file`.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{CoreConfig, Lib, LibSite, Vm};
+/// # use zkaluvm::zk::{CairoTrace, ExecTrace, CAIRO_FRAME};
+/// # use zkaluvm::{fe256, zk_aluasm, Debugger, GfaConfig, FIELD_ORDER_STARK};
+/// let config = GfaConfig {
+///     field_order: FIELD_ORDER_STARK,
+///     step_lim: None,
+///     ..GfaConfig::default()
+/// };
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::with(CoreConfig::default(), config), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+///
+/// let cairo = CairoTrace::from_trace(&ExecTrace::record(&mut debugger)).unwrap();
+/// // Two instruction words, and an immediate value of the `put` instruction.
+/// assert_eq!(cairo.program_len, 3);
+/// assert_eq!(cairo.entries.len(), 2);
+/// assert_eq!(cairo.memory_at(cairo.entries[1].ap), Some(fe256::from(49u8)));
+/// assert_eq!(cairo.memory.len() as u64, 3 + 3 * CAIRO_FRAME);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CairoTrace {
+    /// Number of the cells in the program segment.
+    pub program_len: u64,
+    /// Memory cells, starting from the address `1`.
+    pub memory: Vec<fe256>,
+    /// Register values for each of the executed instructions.
+    pub entries: Vec<CairoEntry>,
+}
+
+impl CairoTrace {
+    /// Lowers an execution trace into the Cairo layout.
+    ///
+    /// # Errors
+    ///
+    /// If the execution is not performed over the STARK field.
+    pub fn from_trace(trace: &ExecTrace) -> Result<Self, CairoError> {
+        if trace.field_order != FIELD_ORDER_STARK {
+            return Err(CairoError::FieldMismatch(trace.field_order));
+        }
+
+        let mut memory = Vec::new();
+        let mut sites = BTreeMap::<LibSite, u64>::new();
+        for step in &trace.steps {
+            if sites.contains_key(&step.site) {
+                continue;
+            }
+            sites.insert(step.site, memory.len() as u64 + 1);
+            let Some(instr) = step.instr else {
+                memory.push(fe256::ZERO);
+                continue;
+            };
+            memory.push(instr_word(&instr));
+            if let Instr::Gfa(FieldInstr::PutD { data, .. } | FieldInstr::Rng { bound: data, .. }) = instr {
+                memory.push(data);
+            }
+        }
+        let program_len = memory.len() as u64;
+
+        let start = trace.steps.first().map_or(&trace.end, |step| &step.before);
+        push_frame(&mut memory, start);
+        let mut entries = Vec::with_capacity(trace.len());
+        for (no, step) in trace.steps.iter().enumerate() {
+            let fp = program_len + 1 + no as u64 * CAIRO_FRAME;
+            entries.push(CairoEntry {
+                pc: sites[&step.site],
+                ap: fp + CAIRO_FRAME,
+                fp,
+            });
+            push_frame(&mut memory, trace.after(no));
+        }
+
+        Ok(CairoTrace {
+            program_len,
+            memory,
+            entries,
+        })
+    }
+
+    /// Returns the value of a memory cell.
+    pub fn memory_at(&self, addr: u64) -> Option<fe256> {
+        let index = usize::try_from(addr.checked_sub(1)?).ok()?;
+        self.memory.get(index).copied()
+    }
+
+    /// Returns the register frame starting at an address.
+    pub fn frame(&self, addr: u64) -> Option<&[fe256]> {
+        let start = usize::try_from(addr.checked_sub(1)?).ok()?;
+        self.memory.get(start..start + CAIRO_FRAME as usize)
+    }
+
+    /// Encodes the register trace in the binary format of the Cairo trace file: a sequence of
+    /// `ap`, `fp` and `pc` values of each entry, as little-endian 64-bit integers.
+    pub fn trace_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.entries.len() * 24);
+        for entry in &self.entries {
+            data.extend(entry.ap.to_le_bytes());
+            data.extend(entry.fp.to_le_bytes());
+            data.extend(entry.pc.to_le_bytes());
+        }
+        data
+    }
+
+    /// Encodes the memory in the binary format of the Cairo memory file: a sequence of addresses
+    /// as little-endian 64-bit integers, each followed by the cell value as a 32-byte
+    /// little-endian integer.
+    pub fn memory_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.memory.len() * 40);
+        for (index, val) in self.memory.iter().enumerate() {
+            data.extend((index as u64 + 1).to_le_bytes());
+            data.extend(val.to_u256().to_le_bytes());
+        }
+        data
+    }
+}
+
+fn instr_word(instr: &Instr<LibId>) -> fe256 {
+    let libs = LibsSeg::try_from_iter(instr.external_ref()).expect("single library reference");
+    let mut writer = Marshaller::with(Vec::new(), Vec::new(), &libs);
+    instr
+        .encode_instr(&mut writer)
+        .expect("a single instruction always fits the segments");
+    let (code, _) = writer.finish();
+    let mut bytes = [0u8; 32];
+    bytes[32 - code.len()..].copy_from_slice(code.as_slice());
+    fe256::from(u256::from_be_bytes(bytes))
+}
+
+fn push_frame(memory: &mut Vec<fe256>, state: &TraceState) {
+    let mut mask = 0u16;
+    for reg in RegE::ALL {
+        let val = state.reg(reg);
+        if val.is_some() {
+            mask |= 1 << reg as u8;
+        }
+        memory.push(val.unwrap_or(fe256::ZERO));
+    }
+    let status = |status: Status| fe256::from(u8::from(status == Status::Fail));
+    memory.push(fe256::from(mask));
+    memory.push(status(state.co));
+    memory.push(status(state.ck));
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{CoreConfig, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::{Debugger, GfaConfig};
+
+    fn run(field_order: u256) -> ExecTrace {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 100
+                put     E2, 0
+            again:
+                add     E2, E1
+                fits    E2, 8.bits
+                not     CO
+                jif     CO, again
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let config = GfaConfig::new(field_order);
+        let core_config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(core_config, config), [lib.clone()]);
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        ExecTrace::record(&mut debugger)
+    }
+
+    #[test]
+    fn layout() {
+        let trace = run(FIELD_ORDER_STARK);
+        let cairo = CairoTrace::from_trace(&trace).unwrap();
+        // nop, put with immediate, put zero, add, fits, not, jif.
+        assert_eq!(cairo.program_len, 8);
+        assert_eq!(cairo.entries.len(), trace.len());
+        assert_eq!(cairo.memory.len() as u64, cairo.program_len + (trace.len() as u64 + 1) * CAIRO_FRAME);
+        assert_eq!(cairo.memory_at(3), Some(fe256::from(100u8)));
+        assert_eq!(cairo.memory_at(0), None);
+
+        // The loop body is executed three times, sharing the program words.
+        let pcs = cairo
+            .entries
+            .iter()
+            .map(|entry| entry.pc)
+            .collect::<Vec<_>>();
+        assert_eq!(pcs.iter().filter(|pc| **pc == pcs[4]).count(), 3);
+
+        for (no, entry) in cairo.entries.iter().enumerate() {
+            assert_eq!(entry.ap, entry.fp + CAIRO_FRAME);
+            if let Some(next) = cairo.entries.get(no + 1) {
+                assert_eq!(next.fp, entry.ap);
+            }
+        }
+        let first = cairo.frame(cairo.entries[0].fp).unwrap();
+        assert_eq!(first, &[fe256::ZERO; CAIRO_FRAME as usize]);
+        let last = cairo.frame(cairo.entries.last().unwrap().ap).unwrap();
+        assert_eq!(last[RegE::E2 as usize], fe256::from(300u16));
+        assert_eq!(last[16], fe256::from(0b11u8));
+        assert_eq!(last[17], fe256::ZERO);
+        assert_eq!(last[18], fe256::ZERO);
+
+        let trace_bytes = cairo.trace_bytes();
+        assert_eq!(trace_bytes.len(), cairo.entries.len() * 24);
+        assert_eq!(&trace_bytes[..8], &cairo.entries[0].ap.to_le_bytes());
+        let memory_bytes = cairo.memory_bytes();
+        assert_eq!(memory_bytes.len(), cairo.memory.len() * 40);
+        assert_eq!(&memory_bytes[80..88], &3u64.to_le_bytes());
+        assert_eq!(memory_bytes[88], 100);
+    }
+
+    #[test]
+    fn field_mismatch() {
+        let trace = run(crate::FIELD_ORDER_SECP);
+        assert_eq!(CairoTrace::from_trace(&trace), Err(CairoError::FieldMismatch(crate::FIELD_ORDER_SECP)));
+    }
+}
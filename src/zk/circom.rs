@@ -0,0 +1,270 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Export of witnesses in the binary `.wtns` and the symbol `.sym` formats of circom, consumed by
+//! snarkjs.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use amplify::num::u256;
+
+use super::R1cs;
+use crate::fe256;
+
+const WTNS_MAGIC: [u8; 4] = *b"wtns";
+const WTNS_VERSION: u32 = 2;
+const SECTION_HEADER: u32 = 1;
+const SECTION_WITNESS: u32 = 2;
+/// Length of the field elements in bytes.
+const N8: u32 = 32;
+
+/// Errors parsing circom `.wtns` files.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum WtnsError {
+    /// the data are not a circom witness file.
+    Magic,
+
+    /// unsupported version {0} of the witness file format.
+    Version(u32),
+
+    /// the witness file is truncated.
+    Truncated,
+
+    /// the witness file lacks the section {0}.
+    NoSection(u32),
+
+    /// field elements of {0} bytes are not supported.
+    ElementSize(u32),
+
+    /// the witness section size doesn't match the number of the witness values.
+    SectionSize,
+
+    /// witness value #{0} is not less than the field order.
+    NonCanonical(usize),
+}
+
+/// Encodes witness values in the binary `.wtns` format (version 2) of circom.
+///
+/// The file consists of a header section with the field order and the number of the witness
+/// values, followed by the witness section with the values in little-endian byte order.
+pub fn wtns_encode(field_order: u256, witness: &[fe256]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(64 + witness.len() * N8 as usize);
+    data.extend(WTNS_MAGIC);
+    data.extend(WTNS_VERSION.to_le_bytes());
+    data.extend(2u32.to_le_bytes());
+
+    data.extend(SECTION_HEADER.to_le_bytes());
+    data.extend((4u64 + N8 as u64 + 4).to_le_bytes());
+    data.extend(N8.to_le_bytes());
+    data.extend(field_order.to_le_bytes());
+    data.extend((witness.len() as u32).to_le_bytes());
+
+    data.extend(SECTION_WITNESS.to_le_bytes());
+    data.extend((witness.len() as u64 * N8 as u64).to_le_bytes());
+    for val in witness {
+        data.extend(val.to_u256().to_le_bytes());
+    }
+    data
+}
+
+/// Decodes a circom `.wtns` file, returning the field order and the witness values.
+pub fn wtns_decode(data: &[u8]) -> Result<(u256, Vec<fe256>), WtnsError> {
+    let mut reader = Reader(data);
+    if reader.take(4)? != WTNS_MAGIC {
+        return Err(WtnsError::Magic);
+    }
+    let version = reader.u32()?;
+    if version != WTNS_VERSION {
+        return Err(WtnsError::Version(version));
+    }
+    let sections = reader.u32()?;
+
+    let mut header = None;
+    let mut values = None;
+    for _ in 0..sections {
+        let ty = reader.u32()?;
+        let len = usize::try_from(reader.u64()?).map_err(|_| WtnsError::Truncated)?;
+        let section = reader.take(len)?;
+        match ty {
+            SECTION_HEADER => header = Some(section),
+            SECTION_WITNESS => values = Some(section),
+            _ => {}
+        }
+    }
+
+    let mut header = Reader(header.ok_or(WtnsError::NoSection(SECTION_HEADER))?);
+    let n8 = header.u32()?;
+    if n8 != N8 {
+        return Err(WtnsError::ElementSize(n8));
+    }
+    let field_order = u256::from_le_slice(header.take(N8 as usize)?).expect("fixed length");
+    let count = header.u32()? as usize;
+
+    let values = values.ok_or(WtnsError::NoSection(SECTION_WITNESS))?;
+    if values.len() != count * N8 as usize {
+        return Err(WtnsError::SectionSize);
+    }
+    let witness = values
+        .chunks(N8 as usize)
+        .enumerate()
+        .map(|(no, chunk)| {
+            let val = u256::from_le_slice(chunk).expect("fixed length");
+            if val >= field_order {
+                return Err(WtnsError::NonCanonical(no));
+            }
+            Ok(fe256::from(val))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((field_order, witness))
+}
+
+struct Reader<'data>(&'data [u8]);
+
+impl<'data> Reader<'data> {
+    fn take(&mut self, len: usize) -> Result<&'data [u8], WtnsError> {
+        if self.0.len() < len {
+            return Err(WtnsError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, WtnsError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("fixed length")))
+    }
+
+    fn u64(&mut self) -> Result<u64, WtnsError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("fixed length")))
+    }
+}
+
+impl R1cs {
+    /// Returns the circom signal name of a witness variable.
+    ///
+    /// Outputs are named after the register with an `_out` suffix (like `main.e1_out`), inputs
+    /// with an `_in` suffix (like `main.ea_in`), and intermediate products are elements of the
+    /// `main.aux` array. The constant `1` is named `main.one`.
+    pub fn var_name(&self, var: usize) -> String {
+        let reg_name = |reg: &crate::RegE| reg.to_string().to_lowercase();
+        if var == 0 {
+            return s!("main.one");
+        }
+        if let Some((reg, _)) = self.outputs.iter().find(|(_, v)| **v == var) {
+            return format!("main.{}_out", reg_name(reg));
+        }
+        if let Some((reg, _)) = self.inputs.iter().find(|(_, v)| **v == var) {
+            return format!("main.{}_in", reg_name(reg));
+        }
+        format!("main.aux[{}]", var - self.num_public() - self.inputs.len())
+    }
+
+    /// Encodes the witness in the binary `.wtns` format of circom.
+    ///
+    /// The layout of the variables of the constraint system matches the circom wire order: the
+    /// constant `1`, public outputs, then inputs and intermediate signals.
+    pub fn to_wtns(&self, witness: &[fe256]) -> Vec<u8> { wtns_encode(self.field_order, witness) }
+
+    /// Produces the circom `.sym` file, naming the witness variables (see [`R1cs::var_name`]).
+    ///
+    /// Each line has the form `label,wire,component,name`; all signals belong to the `main`
+    /// component.
+    pub fn to_sym(&self) -> String {
+        let mut sym = String::new();
+        for var in 1..self.num_vars {
+            writeln!(sym, "{var},{var},0,{}", self.var_name(var)).expect("writing to string");
+        }
+        sym
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::GfaConfig;
+    use crate::RegE::*;
+
+    fn r1cs() -> R1cs {
+        let code = Assembly::parse(
+            r#"
+            routine MAIN:
+                mov     E1, EA
+                mul     E1, EB
+                mul     E1, EA
+                add     E1, EB
+        "#,
+        )
+        .unwrap()
+        .code;
+        R1cs::compile(&code, GfaConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn wtns() {
+        let r1cs = r1cs();
+        let witness = r1cs
+            .witness(&bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8) })
+            .unwrap();
+        let data = r1cs.to_wtns(&witness);
+        assert_eq!(&data[..12], b"wtns\x02\x00\x00\x00\x02\x00\x00\x00");
+        assert_eq!(data.len(), 12 + 12 + 40 + 12 + 32 * witness.len());
+        assert_eq!(&data[data.len() - 32 * witness.len()..][..32], &[&[1u8][..], &[0u8; 31]].concat()[..]);
+        assert_eq!(wtns_decode(&data), Ok((r1cs.field_order, witness)));
+
+        assert_eq!(wtns_decode(b"wtnx"), Err(WtnsError::Magic));
+        assert_eq!(wtns_decode(&data[..data.len() - 1]), Err(WtnsError::Truncated));
+        let mut wrong = data.clone();
+        wrong[4] = 1;
+        assert_eq!(wtns_decode(&wrong), Err(WtnsError::Version(1)));
+        let mut wrong = data.clone();
+        wrong.truncate(12 + 12 + 40);
+        wrong[8] = 1;
+        assert_eq!(wtns_decode(&wrong), Err(WtnsError::NoSection(2)));
+        let mut wrong = data;
+        let last = wrong.len() - 1;
+        wrong[last] = 0xFF;
+        assert_eq!(wtns_decode(&wrong), Err(WtnsError::NonCanonical(r1cs.num_vars - 1)));
+    }
+
+    #[test]
+    fn sym() {
+        let r1cs = r1cs();
+        let sym = r1cs.to_sym();
+        let lines = sym.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), r1cs.num_vars - 1);
+        assert_eq!(lines[0], "1,1,0,main.e1_out");
+        assert!(lines.contains(&format!("{0},{0},0,main.ea_in", r1cs.inputs[&EA]).as_str()));
+        assert!(lines.contains(&format!("{0},{0},0,main.eb_in", r1cs.inputs[&EB]).as_str()));
+        assert!(lines
+            .last()
+            .unwrap()
+            .ends_with(&format!(",0,main.aux[{}]", r1cs.num_vars - r1cs.num_public() - r1cs.inputs.len() - 1)));
+        assert_eq!(r1cs.var_name(0), "main.one");
+    }
+}
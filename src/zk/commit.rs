@@ -0,0 +1,325 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Succinct commitments to execution traces.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use aluvm::regs::Status;
+use aluvm::LibSite;
+use amplify::Bytes32;
+use sha2::{Digest, Sha256};
+
+use super::{ExecTrace, TraceState, TraceStep};
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+const COMMITMENT_TAG: &[u8] = b"urn:ubideco:zk-aluvm:trace-commitment#2025";
+
+/// Commitment to a program execution: a Merkle root over the executed steps together with the
+/// program entry point, the register state at the start and at the end of the execution.
+///
+/// The leaves of the binary Merkle tree are SHA-256 hashes of the executed steps (the step
+/// index, the site and the register state before the step), prefixed with the `0x00` byte; inner
+/// nodes hash their children prefixed with the `0x01` byte. A node without a pair at its level is
+/// moved to the next level unchanged.
+///
+/// A commitment allows referencing an execution without shipping the whole trace: a verifier
+/// either recomputes the commitment from the trace with [`TraceCommitment::verify`], or
+/// spot-checks single steps opened with [`TraceCommitment::open`].
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibSite, Vm};
+/// # use zkaluvm::zk::{ExecTrace, TraceCommitment};
+/// # use zkaluvm::{fe256, zk_aluasm, Debugger, RegE};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+/// let trace = ExecTrace::record(&mut debugger);
+///
+/// let commitment = TraceCommitment::commit(&trace);
+/// assert_eq!(commitment.outputs.reg(RegE::E1), Some(fe256::from(49u8)));
+/// assert!(commitment.verify(&trace));
+///
+/// let opening = commitment.open(&trace, 1).unwrap();
+/// assert!(commitment.verify_opening(&opening));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TraceCommitment {
+    /// Site of the first executed instruction, or `None` if no instructions were executed.
+    pub entry: Option<LibSite>,
+    /// Register state at the start of the execution.
+    pub inputs: TraceState,
+    /// Register state at the end of the execution.
+    pub outputs: TraceState,
+    /// Number of the executed steps.
+    pub steps: u64,
+    /// Merkle root over the executed steps.
+    pub root: Bytes32,
+}
+
+/// Single step of an execution, opened against a [`TraceCommitment`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StepOpening {
+    /// Index of the step in the trace.
+    pub index: u64,
+    /// The executed step.
+    pub step: TraceStep,
+    /// Hashes of the sibling nodes from the leaf up to the root, skipping the levels where the
+    /// node doesn't have a pair.
+    pub path: Vec<Bytes32>,
+}
+
+impl TraceCommitment {
+    /// Computes the commitment to an execution trace.
+    pub fn commit(trace: &ExecTrace) -> Self {
+        let leaves = leaves(trace);
+        let root = levels(leaves)
+            .last()
+            .and_then(|level| level.first().copied())
+            .unwrap_or_else(|| Bytes32::from_byte_array([0u8; 32]));
+        TraceCommitment {
+            entry: trace.steps.first().map(|step| step.site),
+            inputs: trace.steps.first().map_or(trace.end, |step| step.before),
+            outputs: trace.end,
+            steps: trace.len() as u64,
+            root,
+        }
+    }
+
+    /// Verifies that the commitment matches an execution trace by recomputing it.
+    pub fn verify(&self, trace: &ExecTrace) -> bool { *self == Self::commit(trace) }
+
+    /// Returns a single identifier of the commitment, hashing all of its data.
+    pub fn id(&self) -> Bytes32 {
+        let mut hasher = Sha256::new_with_prefix(COMMITMENT_TAG);
+        match self.entry {
+            None => hasher.update([0u8]),
+            Some(site) => {
+                hasher.update([1u8]);
+                hasher.update(site.lib_id.to_byte_array());
+                hasher.update(site.offset.to_le_bytes());
+            }
+        }
+        hash_state(&mut hasher, &self.inputs);
+        hash_state(&mut hasher, &self.outputs);
+        hasher.update(self.steps.to_le_bytes());
+        hasher.update(self.root.to_byte_array());
+        finish(hasher)
+    }
+
+    /// Opens a single step of the trace, producing the Merkle path to the commitment root.
+    ///
+    /// Returns `None` if the index is out of the trace bounds.
+    pub fn open(&self, trace: &ExecTrace, index: usize) -> Option<StepOpening> {
+        let step = *trace.steps.get(index)?;
+        let mut path = Vec::new();
+        let mut pos = index;
+        let levels = levels(leaves(trace));
+        for level in &levels[..levels.len() - 1] {
+            if let Some(sibling) = level.get(pos ^ 1) {
+                path.push(*sibling);
+            }
+            pos /= 2;
+        }
+        Some(StepOpening {
+            index: index as u64,
+            step,
+            path,
+        })
+    }
+
+    /// Verifies that an opened step belongs to the committed trace.
+    pub fn verify_opening(&self, opening: &StepOpening) -> bool {
+        if opening.index >= self.steps {
+            return false;
+        }
+        let mut node = leaf(opening.index, &opening.step);
+        let mut path = opening.path.iter();
+        let (mut pos, mut len) = (opening.index, self.steps);
+        while len > 1 {
+            let sibling = pos ^ 1;
+            if sibling < len {
+                let Some(hash) = path.next() else {
+                    return false;
+                };
+                node = if pos % 2 == 0 { node_hash(node, *hash) } else { node_hash(*hash, node) };
+            }
+            pos /= 2;
+            len = len.div_ceil(2);
+        }
+        path.next().is_none() && node == self.root
+    }
+}
+
+fn finish(hasher: Sha256) -> Bytes32 {
+    let hash: [u8; 32] = hasher.finalize().into();
+    Bytes32::from_byte_array(hash)
+}
+
+fn hash_state(hasher: &mut Sha256, state: &TraceState) {
+    for val in state.regs {
+        match val {
+            None => hasher.update([0u8]),
+            Some(val) => {
+                hasher.update([1u8]);
+                hasher.update(val.to_u256().to_le_bytes());
+            }
+        }
+    }
+    let status = |status: Status| u8::from(status == Status::Fail);
+    hasher.update([status(state.co), status(state.ck)]);
+}
+
+fn leaf(index: u64, step: &TraceStep) -> Bytes32 {
+    let mut hasher = Sha256::new_with_prefix([LEAF_TAG]);
+    hasher.update(index.to_le_bytes());
+    hasher.update(step.site.lib_id.to_byte_array());
+    hasher.update(step.site.offset.to_le_bytes());
+    hash_state(&mut hasher, &step.before);
+    finish(hasher)
+}
+
+fn node_hash(left: Bytes32, right: Bytes32) -> Bytes32 {
+    let mut hasher = Sha256::new_with_prefix([NODE_TAG]);
+    hasher.update(left.to_byte_array());
+    hasher.update(right.to_byte_array());
+    finish(hasher)
+}
+
+fn leaves(trace: &ExecTrace) -> Vec<Bytes32> {
+    trace
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| leaf(index as u64, step))
+        .collect()
+}
+
+/// Builds all levels of the Merkle tree, from the leaves up to the root.
+fn levels(leaves: Vec<Bytes32>) -> Vec<Vec<Bytes32>> {
+    let mut levels = vec![leaves];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let level = levels.last().expect("non-empty");
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(*left, *right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::Vm;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::{fe256, Debugger, RegE};
+
+    fn trace(ea: u8) -> ExecTrace {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                mov     E2, EA
+                mul     E2, E1
+                add     E2, E1
+                neg     E3, E2
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+        debugger.set_reg(RegE::EA, Some(fe256::from(ea)));
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        ExecTrace::record(&mut debugger)
+    }
+
+    #[test]
+    fn commitment() {
+        let trace = trace(5);
+        let commitment = TraceCommitment::commit(&trace);
+        assert_eq!(commitment.steps, 6);
+        assert_eq!(commitment.inputs.reg(RegE::EA), Some(fe256::from(5u8)));
+        assert_eq!(commitment.outputs.reg(RegE::E2), Some(fe256::from(18u8)));
+        assert!(commitment.verify(&trace));
+        assert_eq!(commitment, TraceCommitment::commit(&trace));
+
+        let other = TraceCommitment::commit(&self::trace(6));
+        assert_ne!(other.root, commitment.root);
+        assert_ne!(other.id(), commitment.id());
+
+        let mut tampered = trace.clone();
+        tampered.steps[3].before.regs[1] = Some(fe256::from(1u8));
+        assert!(!commitment.verify(&tampered));
+        assert_eq!(TraceCommitment::commit(&tampered).outputs, commitment.outputs);
+    }
+
+    #[test]
+    fn openings() {
+        let trace = trace(5);
+        let commitment = TraceCommitment::commit(&trace);
+        for index in 0..trace.len() {
+            let opening = commitment.open(&trace, index).unwrap();
+            assert!(commitment.verify_opening(&opening));
+        }
+        assert_eq!(commitment.open(&trace, 6), None);
+
+        let opening = commitment.open(&trace, 4).unwrap();
+        // 6 leaves: the fifth one is paired at the first level, and moved up at the second one.
+        assert_eq!(opening.path.len(), 2);
+        let mut wrong = opening.clone();
+        wrong.step.before.regs[0] = None;
+        assert!(!commitment.verify_opening(&wrong));
+        let mut wrong = opening.clone();
+        wrong.index = 5;
+        assert!(!commitment.verify_opening(&wrong));
+        let mut wrong = opening;
+        wrong.path.pop();
+        assert!(!commitment.verify_opening(&wrong));
+    }
+
+    #[test]
+    fn empty() {
+        let trace = ExecTrace {
+            field_order: crate::FIELD_ORDER_SECP,
+            steps: vec![],
+            end: trace(5).end,
+        };
+        let commitment = TraceCommitment::commit(&trace);
+        assert_eq!(commitment.entry, None);
+        assert_eq!(commitment.root, Bytes32::from_byte_array([0u8; 32]));
+        assert!(commitment.verify(&trace));
+    }
+}
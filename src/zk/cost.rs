@@ -0,0 +1,200 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Estimation of the proving cost of programs.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use aluvm::isa::Instruction;
+use aluvm::SiteId;
+
+use super::air::COLUMNS;
+use crate::gfa::{FieldInstr, Instr};
+use crate::RegE;
+
+/// Estimated cost of proving a program (or a single instruction) with each of the proof system
+/// backends.
+///
+/// The estimate is computed from the [`ProofCost`](crate::gfa::ProofCost) table of the
+/// instructions, and is an upper bound for straight-line code: backends may fold some of the
+/// operations (like multiplications by constants) into linear combinations.
+///
+/// # Example
+///
+/// ```
+/// # use zkaluvm::zk::CostEstimate;
+/// # use zkaluvm::zk_aluasm;
+/// let squares = CostEstimate::of(&zk_aluasm! { mov E2, E1; mul E2, E2; mul E2, E2; });
+/// let sum = CostEstimate::of(&zk_aluasm! { mov E2, E1; add E2, E2; add E2, E2; });
+/// assert_eq!(squares.r1cs_constraints, 3);
+/// assert_eq!(sum.r1cs_constraints, 1);
+/// assert_eq!(squares.plonk_rows, sum.plonk_rows);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct CostEstimate {
+    /// Number of the instructions, by their mnemonic.
+    pub ops: BTreeMap<String, usize>,
+    /// Number of R1CS constraints ([`R1cs`](super::R1cs)), including the constraints for the
+    /// program outputs.
+    pub r1cs_constraints: usize,
+    /// Number of PLONK rows ([`PlonkCircuit`](super::PlonkCircuit)), including the rows for the
+    /// program inputs.
+    pub plonk_rows: usize,
+    /// Number of AIR trace rows ([`AirTrace`](super::AirTrace)), including the final state row.
+    pub air_rows: usize,
+    /// Number of AIR trace columns, which is the same for all programs.
+    pub air_columns: usize,
+    /// Number of the folding steps ([`StepCircuit`](super::StepCircuit)).
+    pub folding_steps: usize,
+}
+
+impl CostEstimate {
+    /// Estimates the cost of a single instruction, not including the costs of the program inputs,
+    /// outputs and the final state.
+    pub fn instr<Id: SiteId>(instr: &Instr<Id>) -> Self {
+        let mut estimate = CostEstimate {
+            air_columns: COLUMNS,
+            ..default!()
+        };
+        estimate.add(instr);
+        estimate
+    }
+
+    /// Estimates the cost of a program, assuming straight-line execution of its code.
+    pub fn of<Id: SiteId>(code: &[Instr<Id>]) -> Self {
+        let mut estimate = CostEstimate {
+            air_columns: COLUMNS,
+            air_rows: 1,
+            ..default!()
+        };
+        let mut written = [false; 16];
+        let mut read = [false; 16];
+        for instr in code {
+            estimate.add(instr);
+            for reg in instr.src_regs() {
+                if !written[reg as usize] && !read[reg as usize] {
+                    read[reg as usize] = true;
+                    // Registers initialized before the program start are assigned to input rows.
+                    estimate.plonk_rows += 1;
+                }
+            }
+            for reg in instr.dst_regs() {
+                written[reg as usize] = true;
+            }
+        }
+        // Each register modified by the program is constrained as an output.
+        estimate.r1cs_constraints += RegE::ALL
+            .into_iter()
+            .filter(|reg| written[*reg as usize])
+            .count();
+        estimate
+    }
+
+    fn add<Id: SiteId>(&mut self, instr: &Instr<Id>) {
+        *self.ops.entry(instr.mnemonic().to_string()).or_default() += 1;
+        self.air_rows += 1;
+        self.folding_steps += 1;
+        let Instr::Gfa(instr) = instr else {
+            return;
+        };
+        let cost = instr.info().proof_cost;
+        let bits = match instr {
+            FieldInstr::Fits { bits, .. } => bits.bit_len(),
+            _ => 0,
+        };
+        self.r1cs_constraints += cost.r1cs_constraints as usize;
+        self.plonk_rows += cost.plonk_rows as usize + cost.plonk_rows_per_bit as usize * bits;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{CoreConfig, LibId, LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::zk::{AirTrace, ExecTrace, PlonkCircuit, R1cs};
+    use crate::{fe256, Debugger, GfaConfig, FIELD_ORDER_STARK};
+
+    const PROGRAM: &str = r#"
+        routine MAIN:
+            mov     E1, EA
+            mul     E1, EB
+            put     E2, 5
+            add     E1, E2
+            neg     E3, E1
+            eq      E1, E3
+            fits    E1, 16.bits
+    "#;
+
+    #[test]
+    fn table() {
+        for info in FieldInstr::INFO {
+            assert_eq!(info.proof_cost.air_rows, 1);
+            let r1cs = match info.name {
+                "Mul" | "Inv" | "Sqr" | "Sqrt" | "CMov" | "Sel" => 1,
+                "ExpI" => 14,
+                _ => 0,
+            };
+            assert_eq!(info.proof_cost.r1cs_constraints, r1cs);
+        }
+        let fits = CostEstimate::instr(&Instr::<LibId>::Gfa(FieldInstr::Fits {
+            src: RegE::E1,
+            bits: crate::gfa::Bits::Bits8,
+        }));
+        assert_eq!(fits.plonk_rows, 11);
+        assert_eq!(fits.ops, bmap! { s!("fits") => 1 });
+    }
+
+    #[test]
+    fn matches_backends() {
+        let lib = Assembly::parse(PROGRAM).unwrap();
+        let estimate = CostEstimate::of(&lib.code);
+        assert_eq!(estimate.ops["mul"], 1);
+        assert_eq!(estimate.ops["nop"], 1);
+
+        let config = GfaConfig::new(FIELD_ORDER_STARK);
+        let r1cs = R1cs::compile(&lib.code, config).unwrap();
+        assert_eq!(estimate.r1cs_constraints, r1cs.num_constraints());
+
+        let lib = lib.assemble().unwrap();
+        let core_config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(core_config, config), [lib.clone()]);
+        debugger.set_reg(RegE::EA, Some(fe256::from(30u8)));
+        debugger.set_reg(RegE::EB, Some(fe256::from(40u8)));
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        let trace = ExecTrace::record(&mut debugger);
+        assert_eq!(estimate.folding_steps, trace.len());
+        let air = AirTrace::from_trace(&trace);
+        assert_eq!(estimate.air_rows, air.len());
+        assert_eq!(estimate.air_columns, air.columns.len());
+        let plonk = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(estimate.plonk_rows, plonk.rows.len());
+    }
+}
@@ -0,0 +1,42 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Arithmetics over the field used by the VM, running the same microcode as the VM itself.
+
+use amplify::num::u256;
+
+use crate::core::{add_mod_int, inv_mod_int, mul_mod_int, neg_mod_int};
+use crate::fe256;
+
+pub(super) fn add(order: u256, a: fe256, b: fe256) -> fe256 {
+    fe256::from(add_mod_int(order, a.to_u256(), b.to_u256()))
+}
+
+pub(super) fn mul(order: u256, a: fe256, b: fe256) -> fe256 {
+    fe256::from(mul_mod_int(order, a.to_u256(), b.to_u256()).0)
+}
+
+pub(super) fn neg(order: u256, a: fe256) -> fe256 { fe256::from(neg_mod_int(order, a.to_u256())) }
+
+/// Computes the multiplicative inverse, returning `None` if it doesn't exist, which is the case for
+/// zero and, if the order is not a prime, for the values sharing a factor with it.
+pub(super) fn inv(order: u256, a: fe256) -> Option<fe256> { inv_mod_int(order, a.to_u256()).map(fe256::from) }
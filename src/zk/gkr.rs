@@ -0,0 +1,373 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Layered arithmetic circuits for GKR and sumcheck-based provers.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use aluvm::SiteId;
+use amplify::num::u256;
+
+use super::{field, WitnessError};
+use crate::gfa::{decompile, DecompileError, ExprGraph, ExprNode, Instr};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Operation of a [`GkrGate`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(lowercase)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum GkrOp {
+    /// Sum of the two input wires.
+    Add,
+    /// Product of the two input wires.
+    Mul,
+}
+
+/// Fan-in-2 gate of a layer, reading two wires of the previous layer.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct GkrGate {
+    /// Operation of the gate.
+    pub op: GkrOp,
+    /// Index of the left input wire in the previous layer.
+    pub left: usize,
+    /// Index of the right input wire in the previous layer.
+    pub right: usize,
+}
+
+/// Wire of the input layer.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(inner)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum GkrInput {
+    /// Value of a register at the start of the program.
+    Reg(RegE),
+    /// Constant field element.
+    Const(fe256),
+}
+
+/// Value carried between the layers while constructing the circuit.
+#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
+enum Wire {
+    Zero,
+    MinusOne,
+    Node(usize),
+}
+
+/// Layered arithmetic circuit computing the arithmetics of a straight-line program.
+///
+/// The input layer holds the register values at the start of the program and the constants.
+/// Each next layer consists of fan-in-2 addition and multiplication gates reading the wires of
+/// the previous layer only, as required by GKR-style provers. Values used by the layers above
+/// the next one are relayed by adding a constant zero wire, which is present in every layer;
+/// negations are multiplications by a constant `-1`. The final values of the registers modified
+/// by the program are wires of the last layer.
+///
+/// The wiring predicates `add_i(z, x, y)` and `mul_i(z, x, y)` of each layer are given by
+/// [`GkrCircuit::wiring`].
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify;
+/// # use zkaluvm::zk::{GkrCircuit, GkrOp};
+/// # use zkaluvm::{fe256, zk_aluasm, GfaConfig, RegE::*};
+/// let code = zk_aluasm! {
+///     mov     E1, EA;
+///     mul     E1, EB;
+///     add     E1, EA;
+/// };
+/// let circuit = GkrCircuit::compile(&code, GfaConfig::default()).unwrap();
+/// assert_eq!(circuit.depth(), 2);
+/// assert_eq!(circuit.wiring(1, GkrOp::Mul).len(), 1);
+///
+/// let values = circuit
+///     .evaluate(&bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8) })
+///     .unwrap();
+/// assert_eq!(circuit.output_values(&values)[&E1], fe256::from(15u8));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct GkrCircuit {
+    /// Order of the field over which the circuit is defined.
+    pub field_order: u256,
+    /// Wires of the input layer.
+    pub inputs: Vec<GkrInput>,
+    /// Gate layers, starting from the one reading the input layer.
+    pub layers: Vec<Vec<GkrGate>>,
+    /// Wires of the last layer (or of the input layer, if there are no gate layers) holding the
+    /// final values of the registers modified by the program.
+    pub outputs: BTreeMap<RegE, usize>,
+}
+
+impl GkrCircuit {
+    /// Decompiles straight-line code and constructs the layered circuit for its arithmetics.
+    pub fn compile<Id: SiteId>(code: &[Instr<Id>], config: GfaConfig) -> Result<Self, DecompileError> {
+        decompile(code, config).map(|graph| Self::from_graph(&graph, config))
+    }
+
+    /// Constructs the layered circuit for a graph of arithmetic operations, with the final values
+    /// of the registers modified by the program as outputs.
+    pub fn from_graph(graph: &ExprGraph, config: GfaConfig) -> Self {
+        let order = config.field_order;
+        let nodes = graph.nodes();
+        let operands = |id: usize| match nodes[id] {
+            ExprNode::Input(_) | ExprNode::Const(_) => None,
+            ExprNode::Add(a, b) | ExprNode::Mul(a, b) => Some((Wire::Node(a.index()), Wire::Node(b.index()))),
+            ExprNode::Neg(a) => Some((Wire::Node(a.index()), Wire::MinusOne)),
+        };
+        let mut depths = Vec::with_capacity(nodes.len());
+        for id in 0..nodes.len() {
+            let depth = match operands(id) {
+                None => 0,
+                Some((a, b)) => 1 + wire_depth(&depths, a).max(wire_depth(&depths, b)),
+            };
+            depths.push(depth);
+        }
+
+        let outputs = graph
+            .outputs()
+            .filter_map(|(reg, id)| Some((reg, Wire::Node(id?.index()))))
+            .collect::<BTreeMap<_, _>>();
+        let depth = outputs
+            .values()
+            .map(|wire| wire_depth(&depths, *wire))
+            .max()
+            .unwrap_or_default();
+
+        // Wires required at each layer, from the last one down to the inputs.
+        let mut needed = vec![BTreeSet::new(); depth + 1];
+        needed[depth].extend(outputs.values().copied());
+        for layer in (1..=depth).rev() {
+            let wires = needed[layer].clone();
+            for wire in wires {
+                let (a, b) = match wire {
+                    Wire::Node(id) if depths[id] == layer => operands(id).expect("gate node"),
+                    _ => (wire, Wire::Zero),
+                };
+                needed[layer - 1].insert(a);
+                needed[layer - 1].insert(b);
+            }
+        }
+
+        let index = |wires: &BTreeSet<Wire>, wire: Wire| wires.iter().position(|w| *w == wire).expect("needed wire");
+        let inputs = needed[0]
+            .iter()
+            .map(|wire| match *wire {
+                Wire::Zero => GkrInput::Const(fe256::ZERO),
+                Wire::MinusOne => GkrInput::Const(field::neg(order, fe256::from(1u8))),
+                Wire::Node(id) => match nodes[id] {
+                    ExprNode::Input(reg) => GkrInput::Reg(reg),
+                    ExprNode::Const(val) => GkrInput::Const(val),
+                    _ => unreachable!("gate nodes have non-zero depth"),
+                },
+            })
+            .collect();
+        let layers = (1..=depth)
+            .map(|layer| {
+                needed[layer]
+                    .iter()
+                    .map(|wire| {
+                        let (op, a, b) = match *wire {
+                            Wire::Node(id) if depths[id] == layer => {
+                                let (a, b) = operands(id).expect("gate node");
+                                let op = if matches!(nodes[id], ExprNode::Add(..)) { GkrOp::Add } else { GkrOp::Mul };
+                                (op, a, b)
+                            }
+                            _ => (GkrOp::Add, *wire, Wire::Zero),
+                        };
+                        GkrGate {
+                            op,
+                            left: index(&needed[layer - 1], a),
+                            right: index(&needed[layer - 1], b),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let outputs = outputs
+            .into_iter()
+            .map(|(reg, wire)| (reg, index(&needed[depth], wire)))
+            .collect();
+
+        GkrCircuit {
+            field_order: order,
+            inputs,
+            layers,
+            outputs,
+        }
+    }
+
+    /// Returns the number of the gate layers.
+    pub fn depth(&self) -> usize { self.layers.len() }
+
+    /// Returns the wiring predicate of a gate layer (numbered from `1`) for an operation, as a
+    /// list of `(z, x, y)` triples for which the predicate is `1`: the gate `z` of the layer
+    /// applies the operation to the wires `x` and `y` of the previous layer.
+    ///
+    /// # Panics
+    ///
+    /// If the layer number is zero or exceeds the circuit depth.
+    pub fn wiring(&self, layer: usize, op: GkrOp) -> Vec<(usize, usize, usize)> {
+        assert!(layer > 0 && layer <= self.depth(), "layer {layer} is not a gate layer");
+        self.layers[layer - 1]
+            .iter()
+            .enumerate()
+            .filter(|(_, gate)| gate.op == op)
+            .map(|(z, gate)| (z, gate.left, gate.right))
+            .collect()
+    }
+
+    /// Evaluates the circuit, returning the values of the wires of all layers, starting from the
+    /// input layer.
+    pub fn evaluate(&self, inputs: &BTreeMap<RegE, fe256>) -> Result<Vec<Vec<fe256>>, WitnessError> {
+        let mut values = Vec::with_capacity(self.layers.len() + 1);
+        let input_layer = self
+            .inputs
+            .iter()
+            .map(|input| match *input {
+                GkrInput::Const(val) => Ok(val),
+                GkrInput::Reg(reg) => {
+                    let val = *inputs.get(&reg).ok_or(WitnessError::MissingInput(reg))?;
+                    if val.to_u256() >= self.field_order {
+                        return Err(WitnessError::NonCanonical(reg, val));
+                    }
+                    Ok(val)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        values.push(input_layer);
+        for layer in &self.layers {
+            let prev = values.last().expect("input layer");
+            let next = layer
+                .iter()
+                .map(|gate| {
+                    let (a, b) = (prev[gate.left], prev[gate.right]);
+                    match gate.op {
+                        GkrOp::Add => field::add(self.field_order, a, b),
+                        GkrOp::Mul => field::mul(self.field_order, a, b),
+                    }
+                })
+                .collect();
+            values.push(next);
+        }
+        Ok(values)
+    }
+
+    /// Returns the final values of the registers from the values of the circuit wires.
+    pub fn output_values(&self, values: &[Vec<fe256>]) -> BTreeMap<RegE, fe256> {
+        let last = values.last().expect("input layer");
+        self.outputs
+            .iter()
+            .map(|(reg, wire)| (*reg, last[*wire]))
+            .collect()
+    }
+}
+
+fn wire_depth(depths: &[usize], wire: Wire) -> usize {
+    match wire {
+        Wire::Zero | Wire::MinusOne => 0,
+        Wire::Node(id) => depths[id],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::zk::R1cs;
+    use crate::RegE::*;
+
+    const PROGRAM: &str = r#"
+        routine MAIN:
+            mov     E1, EA
+            mul     E1, EB
+            mul     E1, EB
+            neg     E2, EA
+            add     E2, E1
+            put     E3, 7
+            add     E3, EC
+            mov     E4, EA
+            clr     E5
+    "#;
+
+    #[test]
+    fn layers() {
+        let code = Assembly::parse(PROGRAM).unwrap().code;
+        let config = GfaConfig::default();
+        let circuit = GkrCircuit::compile(&code, config).unwrap();
+        // E2 = -EA + EA * EB * EB
+        assert_eq!(circuit.depth(), 3);
+        assert_eq!(circuit.outputs.keys().copied().collect::<Vec<_>>(), vec![E1, E2, E3, E4]);
+        assert!(circuit.inputs.contains(&GkrInput::Const(fe256::ZERO)));
+        assert!(circuit.inputs.contains(&GkrInput::Reg(EC)));
+        for (no, layer) in circuit.layers.iter().enumerate() {
+            let prev = if no == 0 { circuit.inputs.len() } else { circuit.layers[no - 1].len() };
+            assert!(layer
+                .iter()
+                .all(|gate| gate.left < prev && gate.right < prev));
+        }
+        assert_eq!(circuit.wiring(3, GkrOp::Add).len() + circuit.wiring(3, GkrOp::Mul).len(), circuit.layers[2].len());
+
+        let inputs = bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8), EC => fe256::from(5u8) };
+        let values = circuit.evaluate(&inputs).unwrap();
+        assert_eq!(values.len(), 4);
+        let outputs = circuit.output_values(&values);
+        assert_eq!(outputs, bmap! {
+            E1 => fe256::from(48u8),
+            E2 => fe256::from(45u8),
+            E3 => fe256::from(12u8),
+            E4 => fe256::from(3u8),
+        });
+
+        // The layered circuit computes the same outputs as the constraint system.
+        let r1cs = R1cs::compile(&code, config).unwrap();
+        let witness = r1cs.witness(&inputs).unwrap();
+        for (reg, var) in &r1cs.outputs {
+            assert_eq!(outputs[reg], witness[*var]);
+        }
+
+        assert_eq!(circuit.evaluate(&bmap! { EA => fe256::from(3u8) }), Err(WitnessError::MissingInput(EB)));
+    }
+
+    #[test]
+    fn inputs_only() {
+        let code = Assembly::parse("mov E1, EA").unwrap().code;
+        let circuit = GkrCircuit::compile(&code, GfaConfig::default()).unwrap();
+        assert_eq!(circuit.depth(), 0);
+        let values = circuit.evaluate(&bmap! { EA => fe256::from(3u8) }).unwrap();
+        assert_eq!(circuit.output_values(&values), bmap! { E1 => fe256::from(3u8) });
+    }
+
+    #[test]
+    #[should_panic(expected = "layer 0 is not a gate layer")]
+    fn input_wiring() {
+        let code = Assembly::parse("mov E1, EA").unwrap().code;
+        GkrCircuit::compile(&code, GfaConfig::default())
+            .unwrap()
+            .wiring(0, GkrOp::Add);
+    }
+}
@@ -0,0 +1,306 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Adapter of [`PlonkCircuit`] to halo2 proving system.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use amplify::num::u256;
+use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::pasta::group::ff::PrimeField;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector};
+use halo2_proofs::poly::Rotation;
+
+use super::{PlonkCircuit, PlonkColumn, PlonkError, PlonkGate};
+use crate::fe256;
+
+/// Converts a field element into the field of the proof system.
+///
+/// The value is reduced modulo the order of the proof system field.
+pub fn to_field<F: PrimeField>(val: fe256) -> F {
+    val.to_u256()
+        .to_be_bytes()
+        .iter()
+        .fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(*byte as u64))
+}
+
+/// Columns and selectors of a [`Halo2Circuit`].
+#[derive(Clone, Debug)]
+pub struct Halo2Config {
+    advice: [Column<Advice>; 4],
+    k: Column<Fixed>,
+    instance: Column<Instance>,
+    selectors: [Selector; PlonkGate::ALL.len()],
+}
+
+/// halo2 circuit proving a program execution lowered into a [`PlonkCircuit`].
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{CoreConfig, Lib, LibSite, Vm};
+/// # use halo2_proofs::dev::MockProver;
+/// # use halo2_proofs::pasta::Fp;
+/// # use zkaluvm::zk::{ExecTrace, Halo2Circuit, PlonkCircuit};
+/// # use zkaluvm::{zk_aluasm, Debugger, GfaConfig};
+/// # use amplify::num::u256;
+/// // Order of the Pallas base field.
+/// let field_order = u256::from_be_bytes([
+///     0x40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x22, 0x46, 0x98, 0xfc, 0x09, 0x4c,
+///     0xf9, 0x1b, 0x99, 0x2d, 0x30, 0xed, 0, 0, 0, 0x01,
+/// ]);
+/// let config = GfaConfig {
+///     field_order,
+///     step_lim: None,
+///     ..GfaConfig::default()
+/// };
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; fits E1, 8.bits; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::with(CoreConfig::default(), config), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+///
+/// let plonk = PlonkCircuit::from_trace(&ExecTrace::record(&mut debugger)).unwrap();
+/// let circuit = Halo2Circuit::<Fp>::new(plonk).unwrap();
+/// let prover = MockProver::run(circuit.min_k(), &circuit, vec![circuit.instance()]).unwrap();
+/// assert_eq!(prover.verify(), Ok(()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Halo2Circuit<F: PrimeField> {
+    plonk: PlonkCircuit,
+    known: bool,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: PrimeField> Halo2Circuit<F> {
+    /// Constructs the circuit, checking that the field of the lowered execution matches the field
+    /// of the proof system.
+    pub fn new(plonk: PlonkCircuit) -> Result<Self, PlonkError> {
+        let order = plonk.field_order;
+        // An order congruent to zero which fits into the bit size of the field is the modulus itself.
+        if order < u256::from(2u8)
+            || 256 - order.leading_zeros() > F::NUM_BITS
+            || to_field::<F>(fe256::from(order - u256::ONE)) + F::ONE != F::ZERO
+        {
+            return Err(PlonkError::FieldMismatch(order));
+        }
+        Ok(Halo2Circuit {
+            plonk,
+            known: true,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the lowered execution.
+    pub fn plonk(&self) -> &PlonkCircuit { &self.plonk }
+
+    /// Returns the values of the instance column.
+    pub fn instance(&self) -> Vec<F> { self.plonk.instance().into_iter().map(to_field).collect() }
+
+    /// Returns the minimal `k` parameter (logarithm of the number of rows) of the circuit.
+    pub fn min_k(&self) -> u32 {
+        // Rows reserved by halo2 for blinding factors.
+        const RESERVED: usize = 10;
+        let rows = (self.plonk.rows.len() + RESERVED).max(self.plonk.public.len() + RESERVED);
+        rows.next_power_of_two().trailing_zeros().max(4)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for Halo2Circuit<F> {
+    type Config = Halo2Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Halo2Circuit {
+            plonk: self.plonk.clone(),
+            known: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 4].map(|_| meta.advice_column());
+        for col in advice {
+            meta.enable_equality(col);
+        }
+        let k = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        let selectors = [(); PlonkGate::ALL.len()].map(|_| meta.selector());
+
+        for (gate, selector) in PlonkGate::ALL.into_iter().zip(selectors) {
+            meta.create_gate("gate", |meta| {
+                let q = meta.query_selector(selector);
+                let [a, b, c, d] = advice.map(|col| meta.query_advice(col, Rotation::cur()));
+                let k = meta.query_fixed(k);
+                let one = Expression::Constant(F::ONE);
+                let constraints = match gate {
+                    PlonkGate::Const => vec![c - k],
+                    PlonkGate::Add => vec![a + b - c],
+                    PlonkGate::Mul => vec![a * b - c],
+                    PlonkGate::Neg => vec![a + c],
+                    PlonkGate::Eq => {
+                        let diff = a - b;
+                        vec![diff.clone() * d + c.clone() - one, diff * c]
+                    }
+                    PlonkGate::Lin => vec![a + k * b - c],
+                    PlonkGate::Bit => vec![a + k * b.clone() - c, b.clone() * (b - one)],
+                    PlonkGate::Fits => vec![a.clone() * d + c.clone() - one, a * c],
+                };
+                constraints.into_iter().map(move |expr| q.clone() * expr)
+            });
+        }
+
+        Halo2Config {
+            advice,
+            k,
+            instance,
+            selectors,
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let plonk = &self.plonk;
+        let cells = layouter.assign_region(
+            || "execution",
+            |mut region| {
+                let mut cells = Vec::<[Cell; 4]>::with_capacity(plonk.rows.len());
+                for (offset, row) in plonk.rows.iter().enumerate() {
+                    if let Some(gate) = row.gate {
+                        let no = PlonkGate::ALL
+                            .iter()
+                            .position(|g| *g == gate)
+                            .expect("all gates are listed");
+                        config.selectors[no].enable(&mut region, offset)?;
+                    }
+                    region.assign_fixed(|| "k", config.k, offset, || Value::known(to_field::<F>(row.k)))?;
+                    let mut assigned = Vec::with_capacity(4);
+                    for (col, column) in [PlonkColumn::A, PlonkColumn::B, PlonkColumn::C, PlonkColumn::D]
+                        .into_iter()
+                        .zip(config.advice)
+                    {
+                        let val = row.get(col);
+                        let value = if self.known { Value::known(to_field::<F>(val)) } else { Value::unknown() };
+                        assigned.push(
+                            region
+                                .assign_advice(|| "advice", column, offset, || value)?
+                                .cell(),
+                        );
+                    }
+                    cells.push([assigned[0], assigned[1], assigned[2], assigned[3]]);
+                }
+                let cell = |cell: super::PlonkCell| cells[cell.row][cell.col as usize];
+                for (a, b) in &plonk.copies {
+                    region.constrain_equal(cell(*a), cell(*b))?;
+                }
+                Ok(cells)
+            },
+        )?;
+        for (no, public) in plonk.public.iter().enumerate() {
+            layouter.constrain_instance(cells[public.row][public.col as usize], config.instance, no)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{CoreConfig, LibSite, Vm};
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pasta::Fp;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::zk::ExecTrace;
+    use crate::{Debugger, GfaConfig, RegE, FIELD_ORDER_STARK};
+
+    fn pallas() -> u256 {
+        u256::from_be_bytes([
+            0x40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x22, 0x46, 0x98, 0xfc, 0x09, 0x4c, 0xf9, 0x1b, 0x99,
+            0x2d, 0x30, 0xed, 0, 0, 0, 0x01,
+        ])
+    }
+
+    fn circuit(field_order: u256) -> PlonkCircuit {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                mov     E1, EA
+                mul     E1, EB
+                put     E2, 5
+                add     E1, E2
+                neg     E3, E1
+                eq      E1, E3
+                fits    E1, 16.bits
+                fits    E3, 8.bits
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let config = GfaConfig::new(field_order);
+        let core_config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(core_config, config), [lib.clone()]);
+        debugger.set_reg(RegE::EA, Some(fe256::from(30u8)));
+        debugger.set_reg(RegE::EB, Some(fe256::from(40u8)));
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        PlonkCircuit::from_trace(&ExecTrace::record(&mut debugger)).unwrap()
+    }
+
+    #[test]
+    fn mock_prover() {
+        let circuit = Halo2Circuit::<Fp>::new(circuit(pallas())).unwrap();
+        let instance = circuit.instance();
+        assert!(instance.contains(&Fp::from(1205)));
+        assert!(instance.contains(&-Fp::from(1205)));
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![instance.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut wrong = instance;
+        wrong[0] += Fp::from(1);
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![wrong]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let mut plonk = circuit.plonk().clone();
+        let mul = plonk
+            .rows
+            .iter()
+            .position(|row| row.gate == Some(PlonkGate::Mul))
+            .unwrap();
+        plonk.rows[mul].c = fe256::from(1u8);
+        let tampered = Halo2Circuit::<Fp>::new(plonk).unwrap();
+        let prover = MockProver::run(tampered.min_k(), &tampered, vec![circuit.instance()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn field_mismatch() {
+        assert_eq!(
+            Halo2Circuit::<Fp>::new(circuit(FIELD_ORDER_STARK)).unwrap_err(),
+            PlonkError::FieldMismatch(FIELD_ORDER_STARK)
+        );
+    }
+}
@@ -0,0 +1,75 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Backends exporting zk-AluVM programs and their executions to proof systems.
+
+mod air;
+#[cfg(feature = "ark")]
+mod ark;
+mod cairo;
+mod circom;
+mod commit;
+mod cost;
+mod field;
+mod gkr;
+#[cfg(feature = "halo2")]
+mod halo2;
+mod nova;
+mod plonk;
+#[cfg(feature = "plonky3")]
+mod plonky3;
+mod r1cs;
+mod trace;
+mod witness;
+
+use alloc::string::{String, ToString};
+
+pub use air::{AirConstraint, AirExpr, AirTrace};
+#[cfg(feature = "ark")]
+pub use ark::{to_ark, ArkCircuit, ArkError};
+pub use cairo::{CairoEntry, CairoError, CairoTrace, CAIRO_FRAME};
+pub use circom::{wtns_decode, wtns_encode, WtnsError};
+pub use commit::{StepOpening, TraceCommitment};
+pub use cost::CostEstimate;
+pub use gkr::{GkrCircuit, GkrGate, GkrInput, GkrOp};
+#[cfg(feature = "halo2")]
+pub use halo2::{to_field, Halo2Circuit, Halo2Config};
+pub use nova::{state_to_z, verify_ivc, z_to_state, NovaError, StepCircuit, StepWitness, STEP_ADVICE, STEP_ARITY};
+pub use plonk::{PlonkCell, PlonkCircuit, PlonkColumn, PlonkError, PlonkGate, PlonkRow};
+#[cfg(feature = "plonky3")]
+pub use plonky3::{check_field, from_p3, state_to_p3, to_p3, trace_matrix, P3Air, P3Error};
+pub use r1cs::{LinComb, R1cs};
+pub use trace::{ExecTrace, TraceState, TraceStep};
+pub use witness::{Witness, WitnessLayout, WitnessSlot};
+
+use crate::{fe256, RegE};
+
+/// Errors generating a witness for a program execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum WitnessError {
+    /// value of the input register {0} is not provided.
+    MissingInput(RegE),
+
+    /// value {1} of the input register {0} is not a canonical element of the field.
+    NonCanonical(RegE, fe256),
+}
@@ -0,0 +1,371 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Step circuits for folding schemes, proving executions one instruction at a time.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use aluvm::regs::Status;
+use amplify::num::u256;
+
+use super::air::{self, COLUMNS, STATE_COLUMNS};
+use super::{field, r1cs, AirExpr, AirTrace, ExecTrace, LinComb, R1cs, TraceState};
+use crate::fe256;
+
+/// Errors checking steps of an incrementally verifiable computation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum NovaError {
+    /// no steps are provided.
+    NoSteps,
+
+    /// step state has {0} elements instead of {STEP_ARITY}.
+    Arity(usize),
+
+    /// step advice has {0} elements instead of {STEP_ADVICE}.
+    Advice(usize),
+
+    /// step state element {0} is not a valid flag.
+    InvalidFlag(usize),
+
+    /// input state of step {0} doesn't match the output state of the previous step.
+    Chain(usize),
+
+    /// step {0} doesn't satisfy the step circuit.
+    Unsatisfied(usize),
+}
+
+/// Number of the field elements in the state passed between the steps.
+pub const STEP_ARITY: usize = STATE_COLUMNS;
+
+/// Number of the field elements in the non-deterministic advice of a step.
+pub const STEP_ADVICE: usize = COLUMNS - STATE_COLUMNS;
+
+/// Serializes the register state into the state vector passed between steps.
+///
+/// The vector holds the values of `E1`..`EH` registers (`0` for the registers without a value),
+/// followed by `1` or `0` for each of the registers holding or not holding a value, and by the
+/// `CO` and `CK` flags (`1` for [`Status::Ok`]).
+pub fn state_to_z(state: &TraceState) -> Vec<fe256> {
+    let flag = |ok: bool| fe256::from(u8::from(ok));
+    let mut z = Vec::with_capacity(STEP_ARITY);
+    z.extend(state.regs.iter().map(|val| val.unwrap_or(fe256::ZERO)));
+    z.extend(state.regs.iter().map(|val| flag(val.is_some())));
+    z.push(flag(state.co.is_ok()));
+    z.push(flag(state.ck.is_ok()));
+    z
+}
+
+/// Deserializes the register state from a state vector produced by [`state_to_z`].
+pub fn z_to_state(z: &[fe256]) -> Result<TraceState, NovaError> {
+    if z.len() != STEP_ARITY {
+        return Err(NovaError::Arity(z.len()));
+    }
+    let flag = |index: usize| match z[index] {
+        val if val == fe256::ZERO => Ok(false),
+        val if val == fe256::from(1u8) => Ok(true),
+        _ => Err(NovaError::InvalidFlag(index)),
+    };
+    let status = |ok: bool| if ok { Status::Ok } else { Status::Fail };
+    let mut regs = [None; 16];
+    for (no, reg) in regs.iter_mut().enumerate() {
+        if flag(16 + no)? {
+            *reg = Some(z[no]);
+        }
+    }
+    Ok(TraceState {
+        regs,
+        co: status(flag(32)?),
+        ck: status(flag(33)?),
+    })
+}
+
+/// Inputs of a single step of the step circuit.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct StepWitness {
+    /// State before the instruction execution.
+    pub z_in: Vec<fe256>,
+    /// The decoded instruction with its operand values (the instruction columns of the
+    /// [`AirTrace`] row).
+    pub advice: Vec<fe256>,
+    /// State after the instruction execution.
+    pub z_out: Vec<fe256>,
+}
+
+impl StepWitness {
+    /// Splits an execution trace into the inputs of the step circuit, one for each executed
+    /// instruction.
+    pub fn from_trace(trace: &ExecTrace) -> Vec<Self> {
+        let air = AirTrace::from_trace(trace);
+        air.rows
+            .windows(2)
+            .map(|rows| StepWitness {
+                z_in: rows[0][..STATE_COLUMNS].to_vec(),
+                advice: rows[0][STATE_COLUMNS..].to_vec(),
+                z_out: rows[1][..STATE_COLUMNS].to_vec(),
+            })
+            .collect()
+    }
+}
+
+/// Fixed relation between the VM states before and after execution of a single instruction,
+/// suitable as the step circuit `F(z_i, w_i) = z_{i+1}` of folding schemes like Nova and
+/// SuperNova.
+///
+/// The relation is the transition constraints of the [`AirTrace`], lowered into a rank-1
+/// constraint system ([`R1cs`]). The variables of the witness vector are laid out as:
+/// - variable `0` is the constant `1`;
+/// - next [`STEP_ARITY`] variables are the input state `z_i` (see [`state_to_z`]);
+/// - next [`STEP_ADVICE`] variables are the advice `w_i`: the instruction decoded into one-hot
+///   selectors of the operation and of the operand registers, together with the operand values;
+/// - next [`STEP_ARITY`] variables are the output state `z_{i+1}`;
+/// - the rest are intermediate products.
+///
+/// The same circuit proves any instruction, so a long execution is proven by folding the steps
+/// produced by [`StepWitness::from_trace`].
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibSite, Vm};
+/// # use zkaluvm::zk::{verify_ivc, ExecTrace, StepCircuit, StepWitness};
+/// # use zkaluvm::{fe256, zk_aluasm, Debugger, RegE};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+/// let trace = ExecTrace::record(&mut debugger);
+///
+/// let circuit = StepCircuit::new(trace.field_order);
+/// let steps = StepWitness::from_trace(&trace);
+/// let state = verify_ivc(&circuit, &steps).unwrap();
+/// assert_eq!(state.reg(RegE::E1), Some(fe256::from(49u8)));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct StepCircuit {
+    /// Constraint system of the relation.
+    ///
+    /// The relation is not compiled from a program, so the register inputs and outputs of the
+    /// constraint system are empty.
+    pub r1cs: R1cs,
+    /// Number of the leading constraints defining intermediate products.
+    products: usize,
+}
+
+impl StepCircuit {
+    /// Constructs the step circuit for a field.
+    pub fn new(field_order: u256) -> Self {
+        let mut r1cs = R1cs {
+            field_order,
+            num_vars: 1 + COLUMNS + STEP_ARITY,
+            inputs: BTreeMap::new(),
+            outputs: BTreeMap::new(),
+            a: Vec::new(),
+            b: Vec::new(),
+            c: Vec::new(),
+        };
+        let checks = air::constraints()
+            .into_iter()
+            .map(|(_, expr)| lower(&mut r1cs, &expr))
+            .collect::<Vec<_>>();
+        let products = r1cs.num_constraints();
+        for lc in checks {
+            r1cs.a.push(lc);
+            r1cs.b.push(LinComb::from([(0, fe256::from(1u8))]));
+            r1cs.c.push(LinComb::new());
+        }
+        StepCircuit { r1cs, products }
+    }
+
+    /// Returns the number of constraints.
+    pub fn num_constraints(&self) -> usize { self.r1cs.num_constraints() }
+
+    /// Computes the witness vector of a step.
+    pub fn witness(&self, step: &StepWitness) -> Result<Vec<fe256>, NovaError> {
+        if step.z_in.len() != STEP_ARITY {
+            return Err(NovaError::Arity(step.z_in.len()));
+        }
+        if step.z_out.len() != STEP_ARITY {
+            return Err(NovaError::Arity(step.z_out.len()));
+        }
+        if step.advice.len() != STEP_ADVICE {
+            return Err(NovaError::Advice(step.advice.len()));
+        }
+        let r1cs = &self.r1cs;
+        let mut witness = Vec::with_capacity(r1cs.num_vars);
+        witness.push(fe256::from(1u8));
+        witness.extend(&step.z_in);
+        witness.extend(&step.advice);
+        witness.extend(&step.z_out);
+        // Each product constraint defines the single variable of its `C` row, in order.
+        for (a, b) in r1cs.a.iter().zip(&r1cs.b).take(self.products) {
+            witness.push(field::mul(r1cs.field_order, r1cs.eval(a, &witness), r1cs.eval(b, &witness)));
+        }
+        Ok(witness)
+    }
+
+    /// Checks whether a witness satisfies all the constraints.
+    pub fn is_satisfied(&self, witness: &[fe256]) -> bool { self.r1cs.is_satisfied(witness) }
+}
+
+/// Checks a chain of steps as an incrementally verifiable computation, returning the final
+/// register state.
+///
+/// Each step must satisfy the step circuit, and its input state must be equal to the output state
+/// of the previous step. This is the relation established by folding all the steps.
+pub fn verify_ivc(circuit: &StepCircuit, steps: &[StepWitness]) -> Result<TraceState, NovaError> {
+    let mut prev: Option<&[fe256]> = None;
+    for (no, step) in steps.iter().enumerate() {
+        if prev.is_some_and(|z| z != step.z_in.as_slice()) {
+            return Err(NovaError::Chain(no));
+        }
+        let witness = circuit.witness(step)?;
+        if !circuit.is_satisfied(&witness) {
+            return Err(NovaError::Unsatisfied(no));
+        }
+        prev = Some(&step.z_out);
+    }
+    z_to_state(prev.ok_or(NovaError::NoSteps)?)
+}
+
+/// Lowers an expression into a linear combination, adding constraints for the products.
+fn lower(r1cs: &mut R1cs, expr: &AirExpr) -> LinComb {
+    let order = r1cs.field_order;
+    match expr {
+        AirExpr::Cur(col) => LinComb::from([(1 + col, fe256::from(1u8))]),
+        AirExpr::Next(col) => LinComb::from([(1 + COLUMNS + col, fe256::from(1u8))]),
+        AirExpr::Const(val) if *val == fe256::ZERO => LinComb::new(),
+        AirExpr::Const(val) => LinComb::from([(0, *val)]),
+        AirExpr::Add(a, b) => {
+            let (a, b) = (lower(r1cs, a), lower(r1cs, b));
+            r1cs::add(order, &a, &b)
+        }
+        AirExpr::Sub(a, b) => {
+            let (a, b) = (lower(r1cs, a), lower(r1cs, b));
+            r1cs::add(order, &a, &r1cs::scale(order, &b, field::neg(order, fe256::from(1u8))))
+        }
+        AirExpr::Mul(a, b) => {
+            let (a, b) = (lower(r1cs, a), lower(r1cs, b));
+            match (r1cs::constant(&a), r1cs::constant(&b)) {
+                (Some(k), _) => r1cs::scale(order, &b, k),
+                (_, Some(k)) => r1cs::scale(order, &a, k),
+                (None, None) => r1cs.product(a, b),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::{Debugger, RegE};
+
+    fn trace() -> ExecTrace {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                put     E2, 1
+                neg     E2, E2
+                mov     E3, E1
+                mul     E3, E1
+                add     E3, E2
+                eq      E3, E1
+                clr     E2
+                mov     E4, E2
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        ExecTrace::record(&mut debugger)
+    }
+
+    #[test]
+    fn steps() {
+        let trace = trace();
+        let circuit = StepCircuit::new(trace.field_order);
+        assert!(circuit.num_constraints() > 0);
+        let r1cs = &circuit.r1cs;
+        assert!(r1cs
+            .a
+            .iter()
+            .chain(&r1cs.b)
+            .all(|lc| lc.keys().all(|var| *var < r1cs.num_vars)));
+
+        let steps = StepWitness::from_trace(&trace);
+        assert_eq!(steps.len(), trace.len());
+        for step in &steps {
+            let witness = circuit.witness(step).unwrap();
+            assert_eq!(witness.len(), circuit.r1cs.num_vars);
+            assert!(circuit.is_satisfied(&witness));
+        }
+        let state = verify_ivc(&circuit, &steps).unwrap();
+        assert_eq!(state, trace.end);
+        assert_eq!(state.reg(RegE::E3), Some(fe256::from(8u8)));
+        assert_eq!(state.reg(RegE::E4), None);
+    }
+
+    #[test]
+    fn tampering() {
+        let trace = trace();
+        let circuit = StepCircuit::new(trace.field_order);
+        let steps = StepWitness::from_trace(&trace);
+
+        let mut wrong = steps.clone();
+        // The `mul` step writes E3.
+        wrong[5].z_out[2] = fe256::from(10u8);
+        wrong[6].z_in[2] = fe256::from(10u8);
+        assert_eq!(verify_ivc(&circuit, &wrong), Err(NovaError::Unsatisfied(5)));
+
+        let mut wrong = steps.clone();
+        wrong[3].z_in[0] = fe256::from(10u8);
+        assert_eq!(verify_ivc(&circuit, &wrong), Err(NovaError::Chain(3)));
+
+        let mut wrong = steps;
+        wrong[1].advice.pop();
+        assert_eq!(verify_ivc(&circuit, &wrong), Err(NovaError::Advice(STEP_ADVICE - 1)));
+        assert_eq!(verify_ivc(&circuit, &[]), Err(NovaError::NoSteps));
+    }
+
+    #[test]
+    fn state() {
+        let trace = trace();
+        let z = state_to_z(&trace.end);
+        assert_eq!(z.len(), STEP_ARITY);
+        assert_eq!(z_to_state(&z), Ok(trace.end));
+        assert_eq!(z_to_state(&z[1..]), Err(NovaError::Arity(STEP_ARITY - 1)));
+        let mut wrong = z;
+        wrong[32] = fe256::from(2u8);
+        assert_eq!(z_to_state(&wrong), Err(NovaError::InvalidFlag(32)));
+    }
+}
@@ -0,0 +1,745 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! PLONKish arithmetization of program executions.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use amplify::num::u256;
+
+use super::{field, ExecTrace};
+use crate::gfa::{FieldInstr, Instr};
+use crate::{fe256, RegE};
+
+/// Errors lowering an execution into a [`PlonkCircuit`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum PlonkError {
+    /// instruction #{0} of the execution has failed `CK`; failed executions can't be proven.
+    Failed(usize),
+
+    /// instruction #{0} of the execution compares uninitialized register {1}.
+    Uninitialized(usize, RegE),
+
+    /// field order {0:x} of the circuit doesn't match the field of the proof system.
+    FieldMismatch(u256),
+
+    /// instruction #{0} of the execution is a host syscall, whose results can't be proven.
+    HostCall(usize),
+
+    /// instruction #{0} of the execution performs an operation which has no PLONK gates.
+    Unsupported(usize),
+
+    /// instruction #{0} of the execution compares values whose difference has no inverse modulo
+    /// the composite field order.
+    NonInvertible(usize),
+}
+
+/// Custom gate of a [`PlonkCircuit`] row, constraining the advice cells `a`, `b`, `c`, `d` and the
+/// fixed cell `k` of the row.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum PlonkGate {
+    /// Constant: `c = k`.
+    #[display("const")]
+    Const,
+
+    /// Addition: `c = a + b`.
+    #[display("add")]
+    Add,
+
+    /// Multiplication: `c = a * b`.
+    #[display("mul")]
+    Mul,
+
+    /// Negation: `c = -a`.
+    #[display("neg")]
+    Neg,
+
+    /// Equality check: `c = 1` if `a = b` and `c = 0` otherwise, with `d` holding the inverse of
+    /// `a - b`.
+    #[display("eq")]
+    Eq,
+
+    /// Linear combination: `c = a + k * b`.
+    #[display("lin")]
+    Lin,
+
+    /// Bit of a decomposition: `c = a + k * b`, where `b` is a boolean.
+    #[display("bit")]
+    Bit,
+
+    /// Zero check of the high part of a decomposed value (see [`PlonkCircuit::from_trace`]):
+    /// `c = 1` if `a = 0` and `c = 0` otherwise, with `d` holding the inverse of `a`.
+    #[display("fits")]
+    Fits,
+}
+
+impl PlonkGate {
+    /// All the gates.
+    pub const ALL: [PlonkGate; 8] = [
+        PlonkGate::Const,
+        PlonkGate::Add,
+        PlonkGate::Mul,
+        PlonkGate::Neg,
+        PlonkGate::Eq,
+        PlonkGate::Lin,
+        PlonkGate::Bit,
+        PlonkGate::Fits,
+    ];
+
+    /// Checks whether the row values satisfy the gate.
+    pub fn is_satisfied(self, row: &PlonkRow, field_order: u256) -> bool {
+        let order = field_order;
+        let one = fe256::from(1u8);
+        let PlonkRow { a, b, c, d, k, .. } = *row;
+        match self {
+            PlonkGate::Const => c == k,
+            PlonkGate::Add => c == field::add(order, a, b),
+            PlonkGate::Mul => c == field::mul(order, a, b),
+            PlonkGate::Neg => field::add(order, a, c) == fe256::ZERO,
+            PlonkGate::Eq => {
+                let diff = field::add(order, a, field::neg(order, b));
+                field::add(order, field::mul(order, diff, d), c) == one && field::mul(order, diff, c) == fe256::ZERO
+            }
+            PlonkGate::Lin => c == field::add(order, a, field::mul(order, k, b)),
+            PlonkGate::Bit => (b == fe256::ZERO || b == one) && PlonkGate::Lin.is_satisfied(row, field_order),
+            PlonkGate::Fits => {
+                field::add(order, field::mul(order, a, d), c) == one && field::mul(order, a, c) == fe256::ZERO
+            }
+        }
+    }
+}
+
+/// Advice column of a [`PlonkCircuit`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display(lowercase)]
+pub enum PlonkColumn {
+    /// First operand.
+    A,
+    /// Second operand.
+    B,
+    /// Result.
+    C,
+    /// Auxiliary value (an inverse).
+    D,
+}
+
+/// Advice cell of a [`PlonkCircuit`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display("{col}[{row}]")]
+pub struct PlonkCell {
+    /// Index of the row.
+    pub row: usize,
+    /// Column of the cell.
+    pub col: PlonkColumn,
+}
+
+impl PlonkCell {
+    const fn new(row: usize, col: PlonkColumn) -> Self { PlonkCell { row, col } }
+}
+
+/// Row of a [`PlonkCircuit`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PlonkRow {
+    /// Gate enabled on the row, if any. Rows without a gate hold values of the registers at the
+    /// start of the program.
+    pub gate: Option<PlonkGate>,
+    /// Value of the `a` advice cell.
+    pub a: fe256,
+    /// Value of the `b` advice cell.
+    pub b: fe256,
+    /// Value of the `c` advice cell.
+    pub c: fe256,
+    /// Value of the `d` advice cell.
+    pub d: fe256,
+    /// Value of the fixed `k` cell.
+    pub k: fe256,
+}
+
+impl PlonkRow {
+    fn new(gate: PlonkGate) -> Self {
+        PlonkRow {
+            gate: Some(gate),
+            a: fe256::ZERO,
+            b: fe256::ZERO,
+            c: fe256::ZERO,
+            d: fe256::ZERO,
+            k: fe256::ZERO,
+        }
+    }
+
+    /// Returns the value of an advice cell of the row.
+    pub fn get(&self, col: PlonkColumn) -> fe256 {
+        match col {
+            PlonkColumn::A => self.a,
+            PlonkColumn::B => self.b,
+            PlonkColumn::C => self.c,
+            PlonkColumn::D => self.d,
+        }
+    }
+}
+
+/// PLONKish circuit with an assigned witness, lowered from a program execution.
+///
+/// The circuit has four advice columns (`a`, `b`, `c`, `d`), one fixed column (`k`), one
+/// instance column and a selector per [`PlonkGate`]. Each arithmetic instruction of the
+/// execution is lowered into a row with the corresponding gate, while register values flow
+/// between the rows through copy constraints. Control flow instructions are not lowered, so the
+/// circuit proves the computation along the executed path only.
+///
+/// The instance column holds the final values of the registers modified by the program (see
+/// [`PlonkCircuit::outputs`]) followed by the `CO` values produced by `eq` and `fits`
+/// instructions, in the order of their execution.
+///
+/// Conditional moves (`cmov` and `sel`) are not lowered, since the circuit has no gate tying the
+/// choice of the source to the `CO` value; executions containing them fail lowering with
+/// [`PlonkError::Unsupported`].
+///
+/// Lowering of `eq` and `fits` instructions computes inverses of the compared differences. If the
+/// field order is not a prime, some of them may have no inverse, in which case lowering fails with
+/// [`PlonkError::NonInvertible`].
+///
+/// `fits` instructions are lowered into a bit decomposition of the low part of the value with a
+/// zero check of the high part. This proves the success of the check; a failed check is not
+/// sound unless the high part is additionally range-checked.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlonkCircuit {
+    /// Order of the field over which the circuit is defined.
+    pub field_order: u256,
+    /// Rows of the circuit.
+    pub rows: Vec<PlonkRow>,
+    /// Pairs of cells which must hold equal values.
+    pub copies: Vec<(PlonkCell, PlonkCell)>,
+    /// Cells exposed in the instance column, in the instance column order.
+    pub public: Vec<PlonkCell>,
+    /// Cells holding the values of the registers at the start of the program.
+    pub inputs: BTreeMap<RegE, PlonkCell>,
+    /// Indexes of the instance column holding the final values of the registers modified by the
+    /// program.
+    pub outputs: BTreeMap<RegE, usize>,
+}
+
+impl PlonkCircuit {
+    /// Lowers a successful execution into a circuit.
+    pub fn from_trace(trace: &ExecTrace) -> Result<Self, PlonkError> {
+        let mut lowering = Lowering {
+            circuit: PlonkCircuit {
+                field_order: trace.field_order,
+                rows: Vec::new(),
+                copies: Vec::new(),
+                public: Vec::new(),
+                inputs: BTreeMap::new(),
+                outputs: BTreeMap::new(),
+            },
+            cells: [None; 16],
+            modified: [false; 16],
+            flags: Vec::new(),
+        };
+        for (no, step) in trace.steps.iter().enumerate() {
+            let after = trace.after(no);
+            if !after.ck.is_ok() {
+                return Err(PlonkError::Failed(no));
+            }
+            match step.instr {
+                Some(Instr::Gfa(instr)) => {
+                    lowering.lower(no, instr, &step.before.regs, &after.regs)?
+                }
+                Some(Instr::Ctrl(_)) => {}
+                Some(Instr::Reserved(_)) | None => return Err(PlonkError::Failed(no)),
+            }
+        }
+
+        let mut circuit = lowering.circuit;
+        for reg in RegE::ALL {
+            if let (true, Some(cell)) = (lowering.modified[reg as usize], lowering.cells[reg as usize]) {
+                circuit.outputs.insert(reg, circuit.public.len());
+                circuit.public.push(cell);
+            }
+        }
+        circuit.public.extend(lowering.flags);
+        Ok(circuit)
+    }
+
+    /// Returns the value of a cell.
+    ///
+    /// # Panics
+    ///
+    /// If the cell row is out of the circuit bounds.
+    pub fn value(&self, cell: PlonkCell) -> fe256 { self.rows[cell.row].get(cell.col) }
+
+    /// Returns the values of the instance column.
+    pub fn instance(&self) -> Vec<fe256> { self.public.iter().map(|cell| self.value(*cell)).collect() }
+
+    /// Finds the first row whose gate is not satisfied by the assigned witness.
+    ///
+    /// Copy constraints are checked by [`PlonkCircuit::broken_copy`].
+    pub fn violation(&self) -> Option<usize> {
+        self.rows.iter().position(|row| {
+            row.gate
+                .is_some_and(|gate| !gate.is_satisfied(row, self.field_order))
+        })
+    }
+
+    /// Finds the first copy constraint violated by the assigned witness.
+    pub fn broken_copy(&self) -> Option<(PlonkCell, PlonkCell)> {
+        self.copies
+            .iter()
+            .find(|(a, b)| self.value(*a) != self.value(*b))
+            .copied()
+    }
+}
+
+struct Lowering {
+    circuit: PlonkCircuit,
+    /// Cells holding the current values of the registers.
+    cells: [Option<PlonkCell>; 16],
+    /// Registers written or cleared by the program.
+    modified: [bool; 16],
+    /// Cells holding `CO` values produced by the checks.
+    flags: Vec<PlonkCell>,
+}
+
+impl Lowering {
+    fn push(&mut self, row: PlonkRow) -> usize {
+        self.circuit.rows.push(row);
+        self.circuit.rows.len() - 1
+    }
+
+    fn copy(&mut self, from: PlonkCell, to: PlonkCell) { self.circuit.copies.push((from, to)); }
+
+    /// Returns the cell holding the value of a register, assigning an input row for the
+    /// registers initialized before the program start.
+    fn read(&mut self, reg: RegE, val: fe256) -> PlonkCell {
+        if let Some(cell) = self.cells[reg as usize] {
+            return cell;
+        }
+        let row = self.push(PlonkRow {
+            gate: None,
+            a: val,
+            ..PlonkRow::new(PlonkGate::Const)
+        });
+        let cell = PlonkCell::new(row, PlonkColumn::A);
+        self.circuit.inputs.insert(reg, cell);
+        self.cells[reg as usize] = Some(cell);
+        cell
+    }
+
+    fn write(&mut self, reg: RegE, cell: Option<PlonkCell>) {
+        self.cells[reg as usize] = cell;
+        self.modified[reg as usize] = true;
+    }
+
+    /// Pushes a row with the operands copied from the registers, returning the row index.
+    fn binary(&mut self, gate: PlonkGate, a: (RegE, fe256), b: Option<(RegE, fe256)>, c: fe256, d: fe256) -> usize {
+        let a_cell = self.read(a.0, a.1);
+        let b_cell = b.map(|(reg, val)| self.read(reg, val));
+        let row = self.push(PlonkRow {
+            a: a.1,
+            b: b.map(|(_, val)| val).unwrap_or(fe256::ZERO),
+            c,
+            d,
+            ..PlonkRow::new(gate)
+        });
+        self.copy(a_cell, PlonkCell::new(row, PlonkColumn::A));
+        if let Some(b_cell) = b_cell {
+            self.copy(b_cell, PlonkCell::new(row, PlonkColumn::B));
+        }
+        row
+    }
+
+    /// Pushes a multiplication row with the operands copied from the given cells, returning the
+    /// product and its cell.
+    fn mul(&mut self, a: (fe256, PlonkCell), b: (fe256, PlonkCell)) -> (fe256, PlonkCell) {
+        let c = field::mul(self.circuit.field_order, a.0, b.0);
+        let row = self.push(PlonkRow {
+            a: a.0,
+            b: b.0,
+            c,
+            ..PlonkRow::new(PlonkGate::Mul)
+        });
+        self.copy(a.1, PlonkCell::new(row, PlonkColumn::A));
+        self.copy(b.1, PlonkCell::new(row, PlonkColumn::B));
+        (c, PlonkCell::new(row, PlonkColumn::C))
+    }
+
+    fn lower(
+        &mut self,
+        no: usize,
+        instr: FieldInstr,
+        before: &[Option<fe256>; 16],
+        after: &[Option<fe256>; 16],
+    ) -> Result<(), PlonkError> {
+        let order = self.circuit.field_order;
+        let one = fe256::from(1u8);
+        let val = |reg: RegE| before[reg as usize].ok_or(PlonkError::Uninitialized(no, reg));
+        let res = |reg: RegE| after[reg as usize].unwrap_or(fe256::ZERO);
+        match instr {
+            FieldInstr::Ecall { .. } => return Err(PlonkError::HostCall(no)),
+            // Ordering of the values requires a full-width range check the circuit doesn't have
+            FieldInstr::Exp { .. } | FieldInstr::Lt { .. } | FieldInstr::Rng { .. } => {
+                return Err(PlonkError::Unsupported(no))
+            }
+            // The choice of the source depends on `CO`, which is not tied to any cell of the circuit
+            FieldInstr::CMov { .. } | FieldInstr::Sel { .. } => return Err(PlonkError::Unsupported(no)),
+            FieldInstr::Test { .. } => {}
+            FieldInstr::Clr { dst } => self.write(dst, None),
+            FieldInstr::PutD { dst, .. } | FieldInstr::PutZ { dst } | FieldInstr::PutV { dst, .. } => {
+                let row = self.push(PlonkRow {
+                    c: res(dst),
+                    k: res(dst),
+                    ..PlonkRow::new(PlonkGate::Const)
+                });
+                self.write(dst, Some(PlonkCell::new(row, PlonkColumn::C)));
+            }
+            FieldInstr::Mov { dst, src } => {
+                let cell = before[src as usize].map(|val| self.read(src, val));
+                self.write(dst, cell);
+            }
+            FieldInstr::Neg { dst, src } => {
+                let row =
+                    self.binary(PlonkGate::Neg, (src, val(src)?), None, field::neg(order, val(src)?), fe256::ZERO);
+                self.write(dst, Some(PlonkCell::new(row, PlonkColumn::C)));
+            }
+            FieldInstr::Add { dst_src, src } | FieldInstr::Mul { dst_src, src } => {
+                let gate = if matches!(instr, FieldInstr::Add { .. }) { PlonkGate::Add } else { PlonkGate::Mul };
+                let row =
+                    self.binary(gate, (dst_src, val(dst_src)?), Some((src, val(src)?)), res(dst_src), fe256::ZERO);
+                self.write(dst_src, Some(PlonkCell::new(row, PlonkColumn::C)));
+            }
+            FieldInstr::Sqr { dst, src } => {
+                let a = val(src)?;
+                let row = self.binary(PlonkGate::Mul, (src, a), Some((src, a)), res(dst), fe256::ZERO);
+                self.write(dst, Some(PlonkCell::new(row, PlonkColumn::C)));
+            }
+            FieldInstr::ExpI { dst_src, exp: 0 } => {
+                val(dst_src)?;
+                let row = self.push(PlonkRow {
+                    c: one,
+                    k: one,
+                    ..PlonkRow::new(PlonkGate::Const)
+                });
+                self.write(dst_src, Some(PlonkCell::new(row, PlonkColumn::C)));
+            }
+            FieldInstr::ExpI { dst_src, exp } => {
+                // The exponent is public, so the power is a chain of multiplications
+                let base = val(dst_src)?;
+                let base = (base, self.read(dst_src, base));
+                let mut acc = base;
+                for bit in (0..u8::BITS - 1 - exp.leading_zeros()).rev() {
+                    acc = self.mul(acc, acc);
+                    if (exp >> bit) & 1 == 1 {
+                        acc = self.mul(acc, base);
+                    }
+                }
+                self.write(dst_src, Some(acc.1));
+            }
+            FieldInstr::Inv { dst, src } => {
+                // The inverse is a witness constrained by `src * dst = 1`
+                let a = val(src)?;
+                let src_cell = self.read(src, a);
+                let row = self.push(PlonkRow {
+                    a,
+                    b: res(dst),
+                    c: one,
+                    ..PlonkRow::new(PlonkGate::Mul)
+                });
+                let unit = self.push(PlonkRow {
+                    c: one,
+                    k: one,
+                    ..PlonkRow::new(PlonkGate::Const)
+                });
+                self.copy(src_cell, PlonkCell::new(row, PlonkColumn::A));
+                self.copy(PlonkCell::new(unit, PlonkColumn::C), PlonkCell::new(row, PlonkColumn::C));
+                self.write(dst, Some(PlonkCell::new(row, PlonkColumn::B)));
+            }
+            FieldInstr::Sqrt { dst, src } => {
+                // The root is a witness constrained by `dst * dst = src`; proving that a value is a
+                // non-residue requires gates the circuit doesn't have
+                let a = val(src)?;
+                let root = res(dst);
+                if field::mul(order, root, root) != a {
+                    return Err(PlonkError::Unsupported(no));
+                }
+                let src_cell = self.read(src, a);
+                let row = self.push(PlonkRow {
+                    a: root,
+                    b: root,
+                    c: a,
+                    ..PlonkRow::new(PlonkGate::Mul)
+                });
+                let flag = self.push(PlonkRow {
+                    c: one,
+                    k: one,
+                    ..PlonkRow::new(PlonkGate::Const)
+                });
+                self.copy(PlonkCell::new(row, PlonkColumn::A), PlonkCell::new(row, PlonkColumn::B));
+                self.copy(src_cell, PlonkCell::new(row, PlonkColumn::C));
+                self.write(dst, Some(PlonkCell::new(row, PlonkColumn::A)));
+                self.flags.push(PlonkCell::new(flag, PlonkColumn::C));
+            }
+            FieldInstr::Eq { src1, src2 } => {
+                let (a, b) = (val(src1)?, val(src2)?);
+                let diff = field::add(order, a, field::neg(order, b));
+                let (flag, inv) = match field::inv(order, diff) {
+                    _ if a == b => (one, fe256::ZERO),
+                    Some(inv) => (fe256::ZERO, inv),
+                    None => return Err(PlonkError::NonInvertible(no)),
+                };
+                let row = self.binary(PlonkGate::Eq, (src1, a), Some((src2, b)), flag, inv);
+                self.flags.push(PlonkCell::new(row, PlonkColumn::C));
+            }
+            FieldInstr::Fits { src, bits } => {
+                let val = val(src)?;
+                let src_cell = self.read(src, val);
+                let bits = bits.bit_len();
+                let lo_mask = (u256::ONE << bits) - u256::ONE;
+                let (lo, hi) = (val.to_u256() & lo_mask, val.to_u256() >> bits);
+
+                // Running sum of the low bits, starting from a constant zero.
+                let zero = self.push(PlonkRow::new(PlonkGate::Const));
+                let mut acc_cell = PlonkCell::new(zero, PlonkColumn::C);
+                let mut acc = u256::ZERO;
+                for bit in 0..bits {
+                    let weight = u256::ONE << bit;
+                    let b = lo.bit(bit);
+                    let next = if b { acc | weight } else { acc };
+                    let row = self.push(PlonkRow {
+                        a: fe256::from(acc),
+                        b: if b { one } else { fe256::ZERO },
+                        c: fe256::from(next),
+                        k: fe256::from(weight),
+                        ..PlonkRow::new(PlonkGate::Bit)
+                    });
+                    self.copy(acc_cell, PlonkCell::new(row, PlonkColumn::A));
+                    acc_cell = PlonkCell::new(row, PlonkColumn::C);
+                    acc = next;
+                }
+                let lin = self.push(PlonkRow {
+                    a: fe256::from(lo),
+                    b: fe256::from(hi),
+                    c: val,
+                    k: fe256::from(u256::ONE << bits),
+                    ..PlonkRow::new(PlonkGate::Lin)
+                });
+                self.copy(acc_cell, PlonkCell::new(lin, PlonkColumn::A));
+                self.copy(src_cell, PlonkCell::new(lin, PlonkColumn::C));
+                let (flag, inv) = match field::inv(order, fe256::from(hi)) {
+                    _ if hi == u256::ZERO => (one, fe256::ZERO),
+                    Some(inv) => (fe256::ZERO, inv),
+                    None => return Err(PlonkError::NonInvertible(no)),
+                };
+                let row = self.push(PlonkRow {
+                    a: fe256::from(hi),
+                    c: flag,
+                    d: inv,
+                    ..PlonkRow::new(PlonkGate::Fits)
+                });
+                self.copy(PlonkCell::new(lin, PlonkColumn::B), PlonkCell::new(row, PlonkColumn::A));
+                self.flags.push(PlonkCell::new(row, PlonkColumn::C));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use alloc::vec;
+
+    use aluvm::{CoreConfig, LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::RegE::*;
+    use crate::{Debugger, GfaConfig, FIELD_ORDER_25519, FIELD_ORDER_STARK};
+
+    pub(crate) const STARK: GfaConfig = GfaConfig::new(FIELD_ORDER_STARK);
+
+    pub(crate) fn trace(source: &str, config: GfaConfig, inputs: &[(RegE, u64)]) -> ExecTrace {
+        let lib = Assembly::parse(source).unwrap().assemble().unwrap();
+        let entry = LibSite::new(lib.lib_id(), 0);
+        let core_config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(core_config, config), [lib]);
+        for (reg, val) in inputs {
+            debugger.set_reg(*reg, Some(fe256::from(*val)));
+        }
+        debugger.start(entry);
+        ExecTrace::record(&mut debugger)
+    }
+
+    pub(crate) const PROGRAM: &str = r#"
+        routine MAIN:
+            mov     E1, EA
+            mul     E1, EB
+            put     E2, 5
+            add     E1, E2
+            neg     E3, E1
+            eq      E1, E3
+            fits    E1, 8.bits
+            fits    EB, 16.bits
+            clr     E2
+    "#;
+
+    #[test]
+    fn lowering() {
+        let trace = trace(PROGRAM, STARK, &[(EA, 30), (EB, 40)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+        assert_eq!(circuit.inputs.keys().copied().collect::<Vec<_>>(), vec![EA, EB]);
+        assert_eq!(circuit.outputs.keys().copied().collect::<Vec<_>>(), vec![E1, E3]);
+
+        let instance = circuit.instance();
+        let one = fe256::from(1u8);
+        assert_eq!(instance[circuit.outputs[&E1]], fe256::from(1205u16));
+        // `eq` fails, `fits E1, 8.bits` fails, `fits EB, 16.bits` succeeds.
+        assert_eq!(instance[2..], [fe256::ZERO, fe256::ZERO, one]);
+        // Two input rows, `mul`, `put`, `add`, `neg`, `eq` and two `fits` with 8 and 16 bits.
+        assert_eq!(circuit.rows.len(), 2 + 5 + (8 + 3) + (16 + 3));
+    }
+
+    #[test]
+    fn tampering() {
+        let trace = trace(PROGRAM, STARK, &[(EA, 30), (EB, 40)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+
+        let mut tampered = circuit.clone();
+        let mul = tampered
+            .rows
+            .iter()
+            .position(|row| row.gate == Some(PlonkGate::Mul))
+            .unwrap();
+        tampered.rows[mul].c = fe256::from(1u8);
+        assert_eq!(tampered.violation(), Some(mul));
+
+        let mut tampered = circuit;
+        tampered.rows[0].a = fe256::from(31u8);
+        assert!(tampered.broken_copy().is_some());
+    }
+
+    #[test]
+    fn inv() {
+        let trace = trace("inv E1, EA", STARK, &[(EA, 7)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+        // Input row, `mul` row constraining the inverse and the constant row.
+        assert_eq!(circuit.rows.len(), 3);
+        let inv = field::inv(FIELD_ORDER_STARK, fe256::from(7u8)).unwrap();
+        assert_eq!(circuit.instance()[circuit.outputs[&E1]], inv);
+
+        let mut tampered = circuit;
+        tampered.rows[1].b = fe256::from(7u8);
+        assert_eq!(tampered.violation(), Some(1));
+    }
+
+    #[test]
+    fn composite_order() {
+        // The order is even, so inverses can't be computed with Fermat's little theorem
+        let config = GfaConfig::new(FIELD_ORDER_25519);
+        let trace = trace("eq EA, EB\nfits EC, 8.bits", config, &[(EA, 10), (EB, 3), (EC, 0x1FF)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+
+        let trace = self::trace("eq EA, EB", config, &[(EA, 10), (EB, 4)]);
+        assert_eq!(PlonkCircuit::from_trace(&trace), Err(PlonkError::NonInvertible(0)));
+        let trace = self::trace("fits EA, 8.bits", config, &[(EA, 0x200)]);
+        assert_eq!(PlonkCircuit::from_trace(&trace), Err(PlonkError::NonInvertible(0)));
+    }
+
+    #[test]
+    fn expi() {
+        let trace = trace("exp EA, 5\nexp E1, 0", STARK, &[(EA, 3), (E1, 7)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+        // Input row, two squarings and a multiplication, and the constant row.
+        assert_eq!(circuit.rows.len(), 5);
+        assert_eq!(circuit.instance()[circuit.outputs[&EA]], fe256::from(243u8));
+        assert_eq!(circuit.instance()[circuit.outputs[&E1]], fe256::from(1u8));
+
+        let mut tampered = circuit;
+        tampered.rows[2].b = fe256::from(10u8);
+        assert_eq!(tampered.violation(), Some(2));
+    }
+
+    #[test]
+    fn sqr() {
+        let trace = trace("sqr E1, EA\nsqr E1, E1", STARK, &[(EA, 3)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+        // Input row and a multiplication row per squaring.
+        assert_eq!(circuit.rows.len(), 3);
+        assert_eq!(circuit.instance()[circuit.outputs[&E1]], fe256::from(81u8));
+
+        let mut tampered = circuit;
+        tampered.rows[1].b = fe256::from(10u8);
+        assert_eq!(tampered.violation(), Some(1));
+    }
+
+    #[test]
+    fn sqrt() {
+        let trace = trace("sqrt E1, EA", STARK, &[(EA, 9)]);
+        let circuit = PlonkCircuit::from_trace(&trace).unwrap();
+        assert_eq!(circuit.violation(), None);
+        assert_eq!(circuit.broken_copy(), None);
+        // Input row, the root row and the flag row.
+        assert_eq!(circuit.rows.len(), 3);
+        assert_eq!(circuit.instance()[circuit.outputs[&E1]], fe256::from(3u8));
+        assert_eq!(circuit.instance().last(), Some(&fe256::from(1u8)));
+
+        let mut tampered = circuit;
+        tampered.rows[1].a = fe256::from(4u8);
+        assert_eq!(tampered.violation(), Some(1));
+    }
+
+    #[test]
+    fn errors() {
+        let trace = trace("add E1, E2", STARK, &[]);
+        assert_eq!(PlonkCircuit::from_trace(&trace), Err(PlonkError::Failed(0)));
+        let trace = trace_eq();
+        assert_eq!(PlonkCircuit::from_trace(&trace), Err(PlonkError::Uninitialized(0, E1)));
+        let exp = self::trace("put E1, 2\nexp E1, E1", STARK, &[]);
+        assert_eq!(PlonkCircuit::from_trace(&exp), Err(PlonkError::Unsupported(1)));
+        let sqrt = self::trace("put E1, 3\nsqrt E1, E1", STARK, &[]);
+        assert_eq!(PlonkCircuit::from_trace(&sqrt), Err(PlonkError::Unsupported(1)));
+        let lt = self::trace("put E1, 3\nlt E1, E1", STARK, &[]);
+        assert_eq!(PlonkCircuit::from_trace(&lt), Err(PlonkError::Unsupported(1)));
+        let rng = self::trace("put E1, 3\nrng E1, 10", STARK, &[]);
+        assert_eq!(PlonkCircuit::from_trace(&rng), Err(PlonkError::Unsupported(1)));
+        let cmov = self::trace("eq EA, EB\ncmov E1, EA", STARK, &[(EA, 3), (EB, 3)]);
+        assert_eq!(PlonkCircuit::from_trace(&cmov), Err(PlonkError::Unsupported(1)));
+        let sel = self::trace("eq EA, EB\nsel E1, EA, EB", STARK, &[(EA, 3), (EB, 5)]);
+        assert_eq!(PlonkCircuit::from_trace(&sel), Err(PlonkError::Unsupported(1)));
+    }
+
+    fn trace_eq() -> ExecTrace { trace("eq E1, E2", STARK, &[(E2, 1)]) }
+}
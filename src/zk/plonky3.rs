@@ -0,0 +1,330 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Adapters of execution traces to the small fields and trace matrices of Plonky3.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use amplify::num::u256;
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::integers::QuotientMap;
+use p3_field::PrimeField64;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use super::{AirExpr, AirTrace, TraceState};
+use crate::fe256;
+
+/// Errors converting values and traces into Plonky3 field elements.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum P3Error {
+    /// the trace is defined over a field of order {0:#x}, which doesn't match the Plonky3 field of
+    /// order {1:#x}.
+    FieldMismatch(u256, u64),
+
+    /// value {0} is not a canonical element of the Plonky3 field.
+    NonCanonical(fe256),
+}
+
+/// Checks that the field order of the VM configuration matches the Plonky3 field `F`.
+pub fn check_field<F: PrimeField64>(field_order: u256) -> Result<(), P3Error> {
+    if field_order != u256::from(F::ORDER_U64) {
+        return Err(P3Error::FieldMismatch(field_order, F::ORDER_U64));
+    }
+    Ok(())
+}
+
+/// Converts a register value into an element of the Plonky3 field `F`.
+///
+/// # Errors
+///
+/// If the value is not less than the order of `F`.
+pub fn to_p3<F: PrimeField64>(val: fe256) -> Result<F, P3Error> {
+    if val.to_u256() >= u256::from(F::ORDER_U64) {
+        return Err(P3Error::NonCanonical(val));
+    }
+    Ok(F::from_int(val.to_u256().low_u64()))
+}
+
+/// Converts an element of the Plonky3 field `F` into a register value.
+pub fn from_p3<F: PrimeField64>(val: F) -> fe256 { fe256::from(u256::from(val.as_canonical_u64())) }
+
+/// Converts the values of `E`-registers of a trace state into elements of the Plonky3 field `F`.
+pub fn state_to_p3<F: PrimeField64>(state: &TraceState) -> Result<[Option<F>; 16], P3Error> {
+    let mut regs = [None; 16];
+    for (dst, src) in regs.iter_mut().zip(state.regs) {
+        *dst = src.map(to_p3).transpose()?;
+    }
+    Ok(regs)
+}
+
+/// Converts an algebraic trace into the row-major trace matrix of Plonky3, padding it to the
+/// power of two height required by the provers (see [`AirTrace::pad`]).
+pub fn trace_matrix<F: PrimeField64>(air: &AirTrace) -> Result<RowMajorMatrix<F>, P3Error> {
+    check_field::<F>(air.field_order)?;
+    let mut air = air.clone();
+    air.pad(1);
+    let values = air
+        .rows
+        .iter()
+        .flatten()
+        .copied()
+        .map(to_p3)
+        .collect::<Result<Vec<F>, _>>()?;
+    Ok(RowMajorMatrix::new(values, air.columns.len()))
+}
+
+/// Plonky3 AIR enforcing the transition constraints of an [`AirTrace`] over the matrix produced
+/// by [`trace_matrix`].
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{CoreConfig, Lib, LibSite, Vm};
+/// # use p3_goldilocks::Goldilocks;
+/// # use p3_matrix::Matrix;
+/// # use zkaluvm::zk::{trace_matrix, AirTrace, ExecTrace, P3Air};
+/// # use zkaluvm::{zk_aluasm, Debugger, GfaConfig, FIELD_ORDER_GOLDILOCKS};
+/// let config = GfaConfig {
+///     field_order: FIELD_ORDER_GOLDILOCKS,
+///     step_lim: None,
+///     ..GfaConfig::default()
+/// };
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::with(CoreConfig::default(), config), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+///
+/// let air = AirTrace::from_trace(&ExecTrace::record(&mut debugger));
+/// let matrix = trace_matrix::<Goldilocks>(&air).unwrap();
+/// let p3_air = P3Air::<Goldilocks>::new(&air).unwrap();
+/// assert_eq!(matrix.width(), p3_air.width());
+/// assert_eq!(matrix.height(), 4);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct P3Air<F: PrimeField64> {
+    width: usize,
+    constraints: Vec<AirExpr>,
+    _phantom: PhantomData<F>,
+}
+
+impl<F: PrimeField64> P3Air<F> {
+    /// Constructs the AIR from the constraints of an algebraic trace.
+    pub fn new(air: &AirTrace) -> Result<Self, P3Error> {
+        check_field::<F>(air.field_order)?;
+        let constraints = air
+            .constraints
+            .iter()
+            .map(|constraint| constraint.expr.clone())
+            .collect::<Vec<_>>();
+        for expr in &constraints {
+            check_consts::<F>(expr)?;
+        }
+        Ok(P3Air {
+            width: air.columns.len(),
+            constraints,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the number of the trace columns.
+    pub fn width(&self) -> usize { self.width }
+}
+
+fn check_consts<F: PrimeField64>(expr: &AirExpr) -> Result<(), P3Error> {
+    match expr {
+        AirExpr::Cur(_) | AirExpr::Next(_) => Ok(()),
+        AirExpr::Const(val) => to_p3::<F>(*val).map(|_| ()),
+        AirExpr::Add(a, b) | AirExpr::Sub(a, b) | AirExpr::Mul(a, b) => {
+            check_consts::<F>(a)?;
+            check_consts::<F>(b)
+        }
+    }
+}
+
+fn lower<AB: AirBuilder>(expr: &AirExpr, cur: &[AB::Var], next: &[AB::Var]) -> AB::Expr
+where AB::F: PrimeField64 {
+    match expr {
+        AirExpr::Cur(col) => cur[*col].clone().into(),
+        AirExpr::Next(col) => next[*col].clone().into(),
+        AirExpr::Const(val) => AB::Expr::from(AB::F::from_int(val.to_u256().low_u64())),
+        AirExpr::Add(a, b) => lower::<AB>(a, cur, next) + lower::<AB>(b, cur, next),
+        AirExpr::Sub(a, b) => lower::<AB>(a, cur, next) - lower::<AB>(b, cur, next),
+        AirExpr::Mul(a, b) => lower::<AB>(a, cur, next) * lower::<AB>(b, cur, next),
+    }
+}
+
+impl<F: PrimeField64> BaseAir<F> for P3Air<F> {
+    fn width(&self) -> usize { self.width }
+}
+
+impl<F: PrimeField64, AB: AirBuilder<F = F>> Air<AB> for P3Air<F> {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (Some(cur), Some(next)) = (main.row_slice(0), main.row_slice(1)) else {
+            return;
+        };
+        let (cur, next) = (cur.to_vec(), next.to_vec());
+        let mut builder = builder.when_transition();
+        for expr in &self.constraints {
+            builder.assert_zero(lower::<AB>(expr, &cur, &next));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::regs::Status;
+    use aluvm::{CoreConfig, LibSite, Vm};
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeCharacteristicRing;
+    use p3_goldilocks::Goldilocks;
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::zk::ExecTrace;
+    use crate::{Debugger, GfaConfig, RegE, FIELD_ORDER_BABYBEAR, FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_STARK};
+
+    /// Evaluates the constraints over a single pair of rows, counting the violations.
+    struct Checker<F: PrimeField64> {
+        main: RowMajorMatrix<F>,
+        violations: usize,
+    }
+
+    impl<F: PrimeField64> AirBuilder for Checker<F> {
+        type F = F;
+        type Expr = F;
+        type Var = F;
+        type M = RowMajorMatrix<F>;
+
+        fn main(&self) -> Self::M { self.main.clone() }
+        fn is_first_row(&self) -> Self::Expr { F::ZERO }
+        fn is_last_row(&self) -> Self::Expr { F::ZERO }
+        fn is_transition_window(&self, _: usize) -> Self::Expr { F::ONE }
+        fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+            if x.into() != F::ZERO {
+                self.violations += 1;
+            }
+        }
+    }
+
+    fn violations<F: PrimeField64>(air: &P3Air<F>, matrix: &RowMajorMatrix<F>) -> usize {
+        (0..matrix.height() - 1)
+            .map(|row| {
+                let values = [matrix.row_slice(row).unwrap().to_vec(), matrix.row_slice(row + 1).unwrap().to_vec()];
+                let mut checker = Checker {
+                    main: RowMajorMatrix::new(values.concat(), matrix.width()),
+                    violations: 0,
+                };
+                air.eval(&mut checker);
+                checker.violations
+            })
+            .sum()
+    }
+
+    fn trace(field_order: u256) -> AirTrace {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                mov     E3, E1
+                mul     E3, EA
+                add     E3, E1
+                neg     E4, E3
+                eq      E3, E4
+                fits    E3, 8.bits
+                clr     E1
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let config = GfaConfig::new(field_order);
+        let core_config = CoreConfig {
+            halt: false,
+            complexity_lim: None,
+        };
+        let mut debugger = Debugger::new(Vm::with(core_config, config), [lib.clone()]);
+        debugger.set_reg(RegE::EA, Some(fe256::from(5u8)));
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        AirTrace::from_trace(&ExecTrace::record(&mut debugger))
+    }
+
+    fn check<F: PrimeField64>(field_order: u256) {
+        let air = trace(field_order);
+        let matrix = trace_matrix::<F>(&air).unwrap();
+        assert_eq!(matrix.height(), air.len().next_power_of_two());
+        assert_eq!(matrix.width(), air.columns.len());
+        let p3_air = P3Air::<F>::new(&air).unwrap();
+        assert_eq!(violations(&p3_air, &matrix), 0);
+
+        let last = matrix.row_slice(matrix.height() - 1).unwrap().to_vec();
+        let e3 = air.columns.iter().position(|name| name == "e3").unwrap();
+        assert_eq!(last[e3], F::from_u8(18));
+        assert_eq!(from_p3(last[e3]), fe256::from(18u8));
+
+        let mut tampered = matrix.clone();
+        let width = tampered.width();
+        tampered.values[3 * width + e3] = F::from_u8(17);
+        assert!(violations(&p3_air, &tampered) > 0);
+    }
+
+    #[test]
+    fn goldilocks() { check::<Goldilocks>(FIELD_ORDER_GOLDILOCKS); }
+
+    #[test]
+    fn babybear() { check::<BabyBear>(FIELD_ORDER_BABYBEAR); }
+
+    #[test]
+    fn conversion() {
+        let max = fe256::from(FIELD_ORDER_BABYBEAR - u256::ONE);
+        assert_eq!(to_p3::<BabyBear>(max), Ok(-BabyBear::ONE));
+        assert_eq!(from_p3(-BabyBear::ONE), max);
+        assert_eq!(
+            to_p3::<BabyBear>(fe256::from(FIELD_ORDER_BABYBEAR)),
+            Err(P3Error::NonCanonical(fe256::from(FIELD_ORDER_BABYBEAR)))
+        );
+
+        let mut state = TraceState {
+            regs: [None; 16],
+            co: Status::Ok,
+            ck: Status::Ok,
+        };
+        state.regs[2] = Some(fe256::from(7u8));
+        let regs = state_to_p3::<Goldilocks>(&state).unwrap();
+        assert_eq!(regs[2], Some(Goldilocks::from_u8(7)));
+        assert_eq!(regs[0], None);
+    }
+
+    #[test]
+    fn field_mismatch() {
+        let air = trace(FIELD_ORDER_STARK);
+        assert_eq!(
+            trace_matrix::<Goldilocks>(&air).unwrap_err(),
+            P3Error::FieldMismatch(FIELD_ORDER_STARK, FIELD_ORDER_GOLDILOCKS.low_u64())
+        );
+        assert!(P3Air::<BabyBear>::new(&air).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Rank-1 constraint systems (R1CS) for straight-line GFA256 programs.
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use aluvm::SiteId;
+use amplify::num::u256;
+
+use super::{field, WitnessError};
+use crate::gfa::{decompile, DecompileError, ExprGraph, ExprNode, Instr, NodeId};
+use crate::{fe256, GfaConfig, RegE};
+
+/// Linear combination of R1CS variables, mapping variable indexes to their non-zero coefficients.
+pub type LinComb = BTreeMap<usize, fe256>;
+
+/// Rank-1 constraint system equivalent to the arithmetics of a straight-line program.
+///
+/// Each constraint `i` requires `⟨A[i], w⟩ · ⟨B[i], w⟩ = ⟨C[i], w⟩` for the witness vector `w`.
+/// The variables of the witness are laid out as:
+/// - variable `0` is the constant `1`;
+/// - variables `1..=outputs.len()` are the final values of the registers modified by the program
+///   (public inputs of the proof system), in the order of the registers;
+/// - next are the register values at the start of the program, in the order of the registers;
+/// - the rest are intermediate products.
+///
+/// Additions, negations and multiplications by constants are folded into linear combinations, so
+/// the constraints correspond to non-linear multiplications and to the program outputs.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate amplify;
+/// # use zkaluvm::zk::R1cs;
+/// # use zkaluvm::{fe256, zk_aluasm, GfaConfig, RegE::*};
+/// let code = zk_aluasm! {
+///     mov     E1, EA;
+///     mul     E1, EB;
+///     add     E1, EA;
+/// };
+/// let r1cs = R1cs::compile(&code, GfaConfig::default()).unwrap();
+/// // One constraint for the multiplication, and one for the output.
+/// assert_eq!(r1cs.num_constraints(), 2);
+///
+/// let witness = r1cs
+///     .witness(&bmap! { EA => fe256::from(3u8), EB => fe256::from(4u8) })
+///     .unwrap();
+/// assert!(r1cs.is_satisfied(&witness));
+/// assert_eq!(witness[r1cs.outputs[&E1]], fe256::from(15u8));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct R1cs {
+    /// Order of the field over which the constraints are defined.
+    pub field_order: u256,
+    /// Number of variables, including the constant `1`.
+    pub num_vars: usize,
+    /// Variables holding the register values at the start of the program.
+    pub inputs: BTreeMap<RegE, usize>,
+    /// Variables holding the final values of the registers modified by the program.
+    pub outputs: BTreeMap<RegE, usize>,
+    /// Rows of the `A` matrix.
+    pub a: Vec<LinComb>,
+    /// Rows of the `B` matrix.
+    pub b: Vec<LinComb>,
+    /// Rows of the `C` matrix.
+    pub c: Vec<LinComb>,
+}
+
+impl R1cs {
+    /// Decompiles straight-line code and constructs the constraint system for its arithmetics.
+    ///
+    /// Checks of `CO` and `CK` registers (like `eq` or `fits`) are not constrained.
+    pub fn compile<Id: SiteId>(code: &[Instr<Id>], config: GfaConfig) -> Result<Self, DecompileError> {
+        decompile(code, config).map(|graph| Self::from_graph(&graph, config))
+    }
+
+    /// Constructs the constraint system for a graph of arithmetic operations, with the final
+    /// values of the registers modified by the program as outputs.
+    pub fn from_graph(graph: &ExprGraph, config: GfaConfig) -> Self {
+        Self::with_outputs(graph, graph.outputs().filter_map(|(reg, id)| Some((reg, id?))), config)
+    }
+
+    /// Constructs the constraint system for a graph of arithmetic operations, with the provided
+    /// nodes as outputs.
+    ///
+    /// This allows to constrain a single path of a
+    /// [symbolic execution](crate::gfa::SymbolicExecutor), using the final register values of
+    /// the path as outputs.
+    pub fn with_outputs(
+        graph: &ExprGraph,
+        outputs: impl IntoIterator<Item = (RegE, NodeId)>,
+        config: GfaConfig,
+    ) -> Self {
+        let order = config.field_order;
+        let outputs = outputs.into_iter().collect::<BTreeMap<_, _>>();
+        let mut r1cs = R1cs {
+            field_order: order,
+            num_vars: 1 + outputs.len(),
+            inputs: BTreeMap::new(),
+            outputs: BTreeMap::new(),
+            a: Vec::new(),
+            b: Vec::new(),
+            c: Vec::new(),
+        };
+        for node in graph.nodes() {
+            if let ExprNode::Input(reg) = node {
+                r1cs.inputs.insert(*reg, 0);
+            }
+        }
+        for var in r1cs.inputs.values_mut() {
+            *var = r1cs.num_vars;
+            r1cs.num_vars += 1;
+        }
+
+        let mut lcs = Vec::<LinComb>::with_capacity(graph.nodes().len());
+        for node in graph.nodes() {
+            let lc = match *node {
+                ExprNode::Input(reg) => bmap! { r1cs.inputs[&reg] => fe256::from(1u8) },
+                ExprNode::Const(val) => scale(order, &bmap! { 0 => fe256::from(1u8) }, val),
+                ExprNode::Add(a, b) => add(order, &lcs[a.index()], &lcs[b.index()]),
+                ExprNode::Neg(a) => scale(order, &lcs[a.index()], field::neg(order, fe256::from(1u8))),
+                ExprNode::Mul(a, b) => {
+                    let (a, b) = (&lcs[a.index()], &lcs[b.index()]);
+                    match (constant(a), constant(b)) {
+                        (Some(val), _) => scale(order, b, val),
+                        (_, Some(val)) => scale(order, a, val),
+                        (None, None) => r1cs.product(a.clone(), b.clone()),
+                    }
+                }
+            };
+            lcs.push(lc);
+        }
+
+        for (no, (reg, id)) in outputs.into_iter().enumerate() {
+            let var = 1 + no;
+            r1cs.outputs.insert(reg, var);
+            r1cs.push(lcs[id.index()].clone(), bmap! { 0 => fe256::from(1u8) }, var);
+        }
+        r1cs
+    }
+
+    fn push(&mut self, a: LinComb, b: LinComb, var: usize) {
+        self.a.push(a);
+        self.b.push(b);
+        self.c.push(bmap! { var => fe256::from(1u8) });
+    }
+
+    /// Adds a variable constrained to the product of two linear combinations, returning the
+    /// linear combination holding it.
+    pub(super) fn product(&mut self, a: LinComb, b: LinComb) -> LinComb {
+        let var = self.num_vars;
+        self.num_vars += 1;
+        self.push(a, b, var);
+        bmap! { var => fe256::from(1u8) }
+    }
+
+    /// Returns the number of constraints.
+    pub fn num_constraints(&self) -> usize { self.a.len() }
+
+    /// Returns the number of public variables: the constant `1` and the outputs, which precede
+    /// all other variables in the witness.
+    pub fn num_public(&self) -> usize { 1 + self.outputs.len() }
+
+    /// Computes the witness assignment from the register values at the start of the program.
+    pub fn witness(&self, inputs: &BTreeMap<RegE, fe256>) -> Result<Vec<fe256>, WitnessError> {
+        let mut witness = vec![fe256::ZERO; self.num_vars];
+        witness[0] = fe256::from(1u8);
+        for (reg, var) in &self.inputs {
+            let val = *inputs.get(reg).ok_or(WitnessError::MissingInput(*reg))?;
+            if val.to_u256() >= self.field_order {
+                return Err(WitnessError::NonCanonical(*reg, val));
+            }
+            witness[*var] = val;
+        }
+        // Each constraint defines the single variable of its `C` row.
+        for ((a, b), c) in self.a.iter().zip(&self.b).zip(&self.c) {
+            let var = *c.keys().next().expect("single variable");
+            witness[var] = field::mul(self.field_order, self.eval(a, &witness), self.eval(b, &witness));
+        }
+        Ok(witness)
+    }
+
+    /// Checks whether a witness satisfies all the constraints.
+    pub fn is_satisfied(&self, witness: &[fe256]) -> bool {
+        witness.len() == self.num_vars
+            && witness[0] == fe256::from(1u8)
+            && self.a.iter().zip(&self.b).zip(&self.c).all(|((a, b), c)| {
+                field::mul(self.field_order, self.eval(a, witness), self.eval(b, witness)) == self.eval(c, witness)
+            })
+    }
+
+    /// Evaluates a linear combination over a witness.
+    pub fn eval(&self, lc: &LinComb, witness: &[fe256]) -> fe256 {
+        lc.iter().fold(fe256::ZERO, |acc, (var, coeff)| {
+            field::add(self.field_order, acc, field::mul(self.field_order, *coeff, witness[*var]))
+        })
+    }
+}
+
+/// Returns the value of a linear combination which doesn't depend on any variable except the
+/// constant `1`.
+pub(super) fn constant(lc: &LinComb) -> Option<fe256> {
+    match lc.iter().next() {
+        None => Some(fe256::ZERO),
+        Some((0, val)) if lc.len() == 1 => Some(*val),
+        _ => None,
+    }
+}
+
+/// Computes the sum of two linear combinations.
+pub(super) fn add(order: u256, a: &LinComb, b: &LinComb) -> LinComb {
+    let mut sum = a.clone();
+    for (var, coeff) in b {
+        let val = field::add(order, sum.get(var).copied().unwrap_or(fe256::ZERO), *coeff);
+        if val == fe256::ZERO {
+            sum.remove(var);
+        } else {
+            sum.insert(*var, val);
+        }
+    }
+    sum
+}
+
+/// Multiplies a linear combination by a constant.
+pub(super) fn scale(order: u256, lc: &LinComb, factor: fe256) -> LinComb {
+    lc.iter()
+        .map(|(var, coeff)| (*var, field::mul(order, *coeff, factor)))
+        .filter(|(_, coeff)| *coeff != fe256::ZERO)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::LibId;
+
+    use super::*;
+    use crate::gfa::{Assembly, Expr, ExprCompiler, SymbolicExecutor};
+    use crate::RegE::*;
+
+    #[test]
+    fn folding() {
+        let mut compiler = ExprCompiler::<LibId>::new();
+        compiler.assign(E1, (Expr::mul(EA, EB) + EA) * EC - 7u8);
+        compiler.assign(E2, Expr::mul(EA, 3u8) + 1u8);
+        let r1cs = R1cs::compile(&compiler.compile().unwrap(), GfaConfig::default()).unwrap();
+        assert_eq!(r1cs.inputs.keys().copied().collect::<Vec<_>>(), vec![EA, EB, EC]);
+        // Temporary registers used by the compiled code are outputs as well.
+        let outputs = r1cs.outputs.len();
+        assert!(outputs >= 2);
+        // Two non-linear multiplications, while the rest is folded into linear combinations.
+        assert_eq!(r1cs.num_constraints(), 2 + outputs);
+        assert_eq!(r1cs.num_vars, 1 + outputs + 3 + 2);
+        assert_eq!(r1cs.num_public(), 1 + outputs);
+
+        let inputs = bmap! { EA => fe256::from(2u8), EB => fe256::from(5u8), EC => fe256::from(10u8) };
+        let mut witness = r1cs.witness(&inputs).unwrap();
+        assert!(r1cs.is_satisfied(&witness));
+        assert_eq!(witness[r1cs.outputs[&E1]], fe256::from(113u8));
+        assert_eq!(witness[r1cs.outputs[&E2]], fe256::from(7u8));
+
+        witness[r1cs.outputs[&E2]] = fe256::from(8u8);
+        assert!(!r1cs.is_satisfied(&witness));
+        assert!(!r1cs.is_satisfied(&witness[1..]));
+
+        assert_eq!(r1cs.witness(&bmap! { EA => fe256::from(2u8) }), Err(WitnessError::MissingInput(EB)));
+        let mut inputs = inputs;
+        inputs.insert(EC, fe256::from(GfaConfig::default().field_order));
+        assert!(matches!(r1cs.witness(&inputs), Err(WitnessError::NonCanonical(EC, _))));
+    }
+
+    #[test]
+    fn negation() {
+        let code = Assembly::parse(
+            r#"
+            routine MAIN:
+                neg     E1, EA
+                add     E1, EA
+                put     E2, 0
+                mul     E2, EB
+        "#,
+        )
+        .unwrap()
+        .code;
+        let r1cs = R1cs::compile(&code, GfaConfig::default()).unwrap();
+        assert_eq!(r1cs.num_constraints(), 2);
+        assert!(r1cs.a.iter().all(LinComb::is_empty));
+        let witness = r1cs
+            .witness(&bmap! { EA => fe256::from(9u8), EB => fe256::from(4u8) })
+            .unwrap();
+        assert!(r1cs.is_satisfied(&witness));
+        assert_eq!(witness[r1cs.outputs[&E1]], fe256::ZERO);
+        assert_eq!(witness[r1cs.outputs[&E2]], fe256::ZERO);
+    }
+
+    #[test]
+    fn symbolic_path() {
+        let code = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 1
+                eq      EA, E1
+                jif     CO, SQUARE
+                add     EA, EA
+                stop
+            routine SQUARE:
+                mul     EA, EA
+        "#,
+        )
+        .unwrap()
+        .code;
+        let result = SymbolicExecutor::default().exec(&code).unwrap();
+        for path in result.successes() {
+            let outputs = path
+                .regs
+                .iter()
+                .filter_map(|(reg, id)| Some((*reg, (*id)?)));
+            let r1cs = R1cs::with_outputs(&result.graph, outputs, GfaConfig::default());
+            let witness = r1cs.witness(&bmap! { EA => fe256::from(6u8) }).unwrap();
+            assert!(r1cs.is_satisfied(&witness));
+            let ea = witness[r1cs.outputs[&EA]];
+            assert!(ea == fe256::from(12u8) || ea == fe256::from(36u8));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Concrete execution traces.
+
+use alloc::vec::Vec;
+
+use aluvm::regs::Status;
+use aluvm::{LibId, LibSite};
+use amplify::num::u256;
+
+use crate::gfa::Instr;
+use crate::{fe256, Debugger, RegE, StopReason};
+
+/// State of the VM registers before or after an instruction execution.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TraceState {
+    /// Values of the `E` registers, indexed by the register number.
+    pub regs: [Option<fe256>; 16],
+    /// Value of the `CO` register.
+    pub co: Status,
+    /// Value of the `CK` register.
+    pub ck: Status,
+}
+
+impl TraceState {
+    /// Returns the value of a register.
+    pub fn reg(&self, reg: RegE) -> Option<fe256> { self.regs[reg as usize] }
+
+    fn capture(debugger: &Debugger) -> Self {
+        TraceState {
+            regs: RegE::ALL.map(|reg| debugger.reg(reg)),
+            co: debugger.co(),
+            ck: debugger.ck(),
+        }
+    }
+}
+
+/// Single executed instruction of a trace.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TraceStep {
+    /// Site of the instruction.
+    pub site: LibSite,
+    /// The executed instruction, or `None` if the site doesn't point to a valid instruction (in
+    /// which case the execution halts).
+    pub instr: Option<Instr<LibId>>,
+    /// State of the registers before the instruction execution.
+    pub before: TraceState,
+}
+
+/// Trace of a concrete program execution, recording the state of the registers before each of
+/// the executed instructions.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibSite, Vm};
+/// # use zkaluvm::zk::ExecTrace;
+/// # use zkaluvm::{fe256, zk_aluasm, Debugger, RegE};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+///
+/// let trace = ExecTrace::record(&mut debugger);
+/// assert_eq!(trace.len(), 2);
+/// assert_eq!(trace.after(0).reg(RegE::E1), Some(fe256::from(7u8)));
+/// assert_eq!(trace.end.reg(RegE::E1), Some(fe256::from(49u8)));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExecTrace {
+    /// Order of the field used by the VM.
+    pub field_order: u256,
+    /// Executed instructions, in the order of their execution.
+    pub steps: Vec<TraceStep>,
+    /// State of the registers at the end of the execution.
+    pub end: TraceState,
+}
+
+impl ExecTrace {
+    /// Runs the program started in the debugger until it halts, recording all the executed
+    /// instructions.
+    ///
+    /// Debugger breakpoints and watchpoints are ignored.
+    pub fn record(debugger: &mut Debugger) -> Self {
+        let mut steps = Vec::new();
+        while let Some(site) = debugger.site() {
+            steps.push(TraceStep {
+                site,
+                instr: debugger.instr(),
+                before: TraceState::capture(debugger),
+            });
+            if let StopReason::Halted(_) = debugger.step() {
+                break;
+            }
+        }
+        ExecTrace {
+            field_order: debugger.vm().core.cx.fq(),
+            steps,
+            end: TraceState::capture(debugger),
+        }
+    }
+
+    /// Returns the number of the executed instructions.
+    pub fn len(&self) -> usize { self.steps.len() }
+
+    /// Checks whether no instructions were executed.
+    pub fn is_empty(&self) -> bool { self.steps.is_empty() }
+
+    /// Returns the state of the registers after the execution of the step.
+    ///
+    /// # Panics
+    ///
+    /// If the step index is out of the trace bounds.
+    pub fn after(&self, no: usize) -> &TraceState {
+        assert!(no < self.steps.len(), "step {no} is out of the trace bounds");
+        self.steps
+            .get(no + 1)
+            .map_or(&self.end, |step| &step.before)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{Lib, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::RegE::*;
+
+    #[test]
+    fn record() {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                put     E1, 3
+                call    SQUARE
+                test    E2
+                stop
+            routine SQUARE:
+                mul     E1, E1
+                ret
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let lib_id = lib.lib_id();
+        let mut debugger = Debugger::new(Vm::new(), [lib]);
+        debugger.break_at_instr(lib_id, 2);
+        debugger.start(LibSite::new(lib_id, 0));
+        let trace = ExecTrace::record(&mut debugger);
+
+        assert_eq!(trace.len(), 8);
+        assert!(!trace.is_empty());
+        assert_eq!(trace.steps[0].site, LibSite::new(lib_id, 0));
+        assert_eq!(trace.steps[0].before.reg(E1), None);
+        assert_eq!(trace.steps[6].before.co, Status::Ok);
+        assert_eq!(trace.after(6).co, Status::Fail);
+        assert_eq!(trace.end.reg(E1), Some(fe256::from(9u8)));
+        assert_eq!(trace.end.ck, Status::Ok);
+        assert_eq!(debugger.site(), None);
+    }
+
+    #[test]
+    fn empty() {
+        let mut debugger = Debugger::new(Vm::new(), [Lib::assemble::<Instr<LibId>>(&[]).unwrap()]);
+        let trace = ExecTrace::record(&mut debugger);
+        assert!(trace.is_empty());
+        assert_eq!(trace.end.ck, Status::Ok);
+    }
+}
@@ -0,0 +1,270 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Proof-system independent witness vectors of program executions.
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use aluvm::isa::Instruction;
+use amplify::num::u256;
+
+use super::ExecTrace;
+use crate::{fe256, RegE};
+
+/// Meaning of a single value of a [`Witness`] vector.
+#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub enum WitnessSlot {
+    /// The constant `1`.
+    One,
+    /// Value of a register at the end of the execution.
+    Output(RegE),
+    /// Value of a register at the start of the execution.
+    Input(RegE),
+    /// Value written to a register by the instruction executed at a step of the trace.
+    Write {
+        /// Index of the step in the execution trace.
+        step: usize,
+        /// The written register.
+        reg: RegE,
+    },
+}
+
+impl Display for WitnessSlot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = |reg: &RegE| reg.to_string().to_lowercase();
+        match self {
+            WitnessSlot::One => f.write_str("one"),
+            WitnessSlot::Output(reg) => write!(f, "out.{}", name(reg)),
+            WitnessSlot::Input(reg) => write!(f, "in.{}", name(reg)),
+            WitnessSlot::Write { step, reg } => write!(f, "step{step}.{}", name(reg)),
+        }
+    }
+}
+
+/// Layout of a [`Witness`] vector, describing the meaning of each of its values.
+///
+/// The slots are ordered as:
+/// - the constant `1`;
+/// - the registers holding a value at the end of the execution, in the order of the registers;
+/// - the registers holding a value at the start of the execution, in the order of the registers;
+/// - the values written by the executed instructions, in the order of the execution, and for each
+///   instruction in the order of the registers.
+///
+/// The constant and the outputs form the public part of the witness and precede all other values,
+/// like in [`R1cs`](super::R1cs). The layout depends only on the executed instructions and the
+/// sets of the initialized registers, and not on the register values, so executions of the same
+/// program path share the same layout.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct WitnessLayout {
+    /// Order of the field of the witness values.
+    pub field_order: u256,
+    /// Slots of the witness vector.
+    pub slots: Vec<WitnessSlot>,
+}
+
+impl WitnessLayout {
+    /// Constructs the layout of the witness for an execution trace.
+    pub fn from_trace(trace: &ExecTrace) -> Self {
+        let start = trace.steps.first().map_or(&trace.end, |step| &step.before);
+        let mut slots = vec![WitnessSlot::One];
+        slots.extend(
+            RegE::ALL
+                .into_iter()
+                .filter(|reg| trace.end.reg(*reg).is_some())
+                .map(WitnessSlot::Output),
+        );
+        slots.extend(
+            RegE::ALL
+                .into_iter()
+                .filter(|reg| start.reg(*reg).is_some())
+                .map(WitnessSlot::Input),
+        );
+        for (step, item) in trace.steps.iter().enumerate() {
+            let Some(instr) = item.instr else {
+                continue;
+            };
+            let after = trace.after(step);
+            slots.extend(
+                instr
+                    .dst_regs()
+                    .into_iter()
+                    .filter(|reg| after.reg(*reg).is_some())
+                    .map(|reg| WitnessSlot::Write { step, reg }),
+            );
+        }
+        WitnessLayout {
+            field_order: trace.field_order,
+            slots,
+        }
+    }
+
+    /// Returns the number of the witness values.
+    pub fn len(&self) -> usize { self.slots.len() }
+
+    /// Checks whether the layout has no slots.
+    pub fn is_empty(&self) -> bool { self.slots.is_empty() }
+
+    /// Returns the number of the public values: the constant `1` and the outputs.
+    pub fn num_public(&self) -> usize {
+        self.slots
+            .iter()
+            .take_while(|slot| matches!(slot, WitnessSlot::One | WitnessSlot::Output(_)))
+            .count()
+    }
+
+    /// Returns the index of a slot in the witness vector.
+    pub fn index_of(&self, slot: WitnessSlot) -> Option<usize> { self.slots.iter().position(|s| *s == slot) }
+}
+
+/// Flat witness vector of a program execution, together with its layout.
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::{Lib, LibSite, Vm};
+/// # use zkaluvm::zk::{ExecTrace, Witness, WitnessSlot};
+/// # use zkaluvm::{fe256, zk_aluasm, Debugger, RegE};
+/// let lib = Lib::assemble(&zk_aluasm! { put E1, 7; mul E1, E1; }).unwrap();
+/// let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+/// debugger.start(LibSite::new(lib.lib_id(), 0));
+///
+/// let witness = Witness::from_trace(&ExecTrace::record(&mut debugger));
+/// assert_eq!(witness.len(), 4);
+/// assert_eq!(witness.public(), &[fe256::from(1u8), fe256::from(49u8)]);
+/// assert_eq!(
+///     witness.get(WitnessSlot::Write {
+///         step: 0,
+///         reg: RegE::E1
+///     }),
+///     Some(fe256::from(7u8))
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "camelCase"))]
+pub struct Witness {
+    /// Layout of the witness vector.
+    pub layout: WitnessLayout,
+    /// Values of the witness vector.
+    pub values: Vec<fe256>,
+}
+
+impl Witness {
+    /// Generates the witness vector for an execution trace.
+    pub fn from_trace(trace: &ExecTrace) -> Self {
+        let layout = WitnessLayout::from_trace(trace);
+        let start = trace.steps.first().map_or(&trace.end, |step| &step.before);
+        let values = layout
+            .slots
+            .iter()
+            .map(|slot| {
+                let val = match *slot {
+                    WitnessSlot::One => Some(fe256::from(1u8)),
+                    WitnessSlot::Output(reg) => trace.end.reg(reg),
+                    WitnessSlot::Input(reg) => start.reg(reg),
+                    WitnessSlot::Write { step, reg } => trace.after(step).reg(reg),
+                };
+                val.expect("layout includes only initialized registers")
+            })
+            .collect();
+        Witness { layout, values }
+    }
+
+    /// Returns the number of the witness values.
+    pub fn len(&self) -> usize { self.values.len() }
+
+    /// Checks whether the witness has no values.
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+
+    /// Returns the public values: the constant `1` and the outputs.
+    pub fn public(&self) -> &[fe256] { &self.values[..self.layout.num_public()] }
+
+    /// Returns the value of a slot.
+    pub fn get(&self, slot: WitnessSlot) -> Option<fe256> { self.layout.index_of(slot).map(|index| self.values[index]) }
+}
+
+#[cfg(test)]
+mod test {
+    #![cfg_attr(coverage_nightly, coverage(off))]
+
+    use aluvm::{LibSite, Vm};
+
+    use super::*;
+    use crate::gfa::Assembly;
+    use crate::Debugger;
+    use crate::RegE::*;
+
+    fn run(ea: u8) -> Witness {
+        let lib = Assembly::parse(
+            r#"
+            routine MAIN:
+                mov     E1, EA
+                mul     E1, EA
+                put     E2, 5
+                add     E2, E1
+                eq      E1, E2
+                clr     EA
+        "#,
+        )
+        .unwrap()
+        .assemble()
+        .unwrap();
+        let mut debugger = Debugger::new(Vm::new(), [lib.clone()]);
+        debugger.set_reg(EA, Some(fe256::from(ea)));
+        debugger.start(LibSite::new(lib.lib_id(), 0));
+        Witness::from_trace(&ExecTrace::record(&mut debugger))
+    }
+
+    #[test]
+    fn layout() {
+        let witness = run(3);
+        let layout = &witness.layout;
+        // The routine label is compiled into a `nop` at step 0.
+        assert_eq!(layout.slots, vec![
+            WitnessSlot::One,
+            WitnessSlot::Output(E1),
+            WitnessSlot::Output(E2),
+            WitnessSlot::Input(EA),
+            WitnessSlot::Write { step: 1, reg: E1 },
+            WitnessSlot::Write { step: 2, reg: E1 },
+            WitnessSlot::Write { step: 3, reg: E2 },
+            WitnessSlot::Write { step: 4, reg: E2 },
+        ]);
+        assert_eq!(layout.num_public(), 3);
+        assert_eq!(layout.index_of(WitnessSlot::Input(EA)), Some(3));
+        assert_eq!(layout.index_of(WitnessSlot::Input(EB)), None);
+        assert_eq!(layout.slots[6].to_string(), "step3.e2");
+        assert_eq!(layout.slots[1].to_string(), "out.e1");
+
+        assert_eq!(witness.public(), &[fe256::from(1u8), fe256::from(9u8), fe256::from(14u8)]);
+        assert_eq!(witness.get(WitnessSlot::Write { step: 1, reg: E1 }), Some(fe256::from(3u8)));
+        assert_eq!(witness.get(WitnessSlot::Input(EA)), Some(fe256::from(3u8)));
+
+        let other = run(4);
+        assert_eq!(other.layout, witness.layout);
+        assert_ne!(other.values, witness.values);
+    }
+}
@@ -21,27 +21,69 @@
 // the License.
 //! Strict types library generator methods.
 
+use std::io;
+use std::path::Path;
+
+use aluvm::LibId;
 use strict_types::typelib::{CompileError, LibBuilder};
-use strict_types::TypeLib;
+use strict_types::{StlFormat, TypeLib};
 
-use crate::{fe256, LIB_NAME_FINITE_FIELD};
+use crate::gfa::{Bits, ConstVal, Instr};
+use crate::{fe256, GfaConfig, RegE, LIB_NAME_FINITE_FIELD};
 
 /// Strict type id for the lib-old providing data types from this crate.
-pub const LIB_ID_FINITE_FIELD: &str = "stl:Nm0yhN4V-rj3RTrE-tXIS14e-JV1OFTx-zPNhRZU-54QW0tg#report-canal-convert";
+pub const LIB_ID_FINITE_FIELD: &str = "stl:u_RQ_Z1c-Ebf6Srj-kSfkRyq-C~gPH6F-PWHO~hP-HFgUuNI#salary-stock-donor";
 
 #[allow(clippy::result_large_err)]
 fn _finite_field_stl() -> Result<TypeLib, CompileError> {
-    LibBuilder::with(LIB_NAME_FINITE_FIELD, [strict_types::stl::std_stl().to_dependency_types()])
-        .transpile::<fe256>()
-        .compile()
+    LibBuilder::with(LIB_NAME_FINITE_FIELD, [
+        strict_types::stl::std_stl().to_dependency_types(),
+        aluvm::stl::aluvm_stl().to_dependency_types(),
+    ])
+    .transpile::<fe256>()
+    .transpile::<RegE>()
+    .transpile::<Bits>()
+    .transpile::<ConstVal>()
+    .transpile::<GfaConfig>()
+    .transpile::<Instr<LibId>>()
+    .compile()
 }
 
 /// Generates strict type lib-old providing data types from this crate.
 pub fn finite_field_stl() -> TypeLib { _finite_field_stl().expect("invalid strict type AluVM lib-old") }
 
+/// Writes the strict type library providing data types from this crate into the `dir` directory,
+/// in binary (`.stl`), source (`.sty`) and ASCII-armored (`.sta`) formats.
+///
+/// The files are named `FiniteField@<version>.<ext>`, where the version is the version of this
+/// crate.
+pub fn write_finite_field_stl(dir: impl AsRef<Path>) -> io::Result<()> {
+    let lib = finite_field_stl();
+    let dir = dir.as_ref();
+    for format in [StlFormat::Binary, StlFormat::Source, StlFormat::Armored] {
+        lib.serialize(
+            format,
+            Some(dir),
+            env!("CARGO_PKG_VERSION"),
+            Some(
+                "
+  Description: Finite field types for zk-AluVM
+  Author: Dr Maxim Orlovsky <orlovsky@ubideco.org>
+  Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+                          Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+  License: Apache-2.0",
+            ),
+        )?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     #![cfg_attr(coverage_nightly, coverage(off))]
+    use alloc::format;
+    use alloc::string::ToString;
+
     use super::*;
 
     #[test]
@@ -49,4 +91,59 @@ mod test {
         let lib = finite_field_stl();
         assert_eq!(lib.id().to_string(), LIB_ID_FINITE_FIELD);
     }
+
+    #[test]
+    fn sem_ids() {
+        let lib = finite_field_stl();
+        let expected = [
+            ("Bits", "semid:6uWWlwa0-AgrVaoS-Ki31BCP-GpPF2e5-~bnQ83X-RZuLgZk#network-serial-content"),
+            ("ConstVal", "semid:iajxom4v-9iDEoB0-3RUDGgG-EID9i4X-fbB2nj_-47lX_sA#flute-volcano-episode"),
+            ("CurveForm", "semid:YLeRqVpQ-nyj4p8d-9SIfBww-15hBW_U-eNF36ii-BKHT8jA#torch-friend-lecture"),
+            ("CurveParams", "semid:A~9HUqjo-R3sFJKs-cyTVolc-qGasooC-i8TWTnx-m1Fms8Q#archer-protect-dance"),
+            ("Fe256", "semid:MfGpQOSm-Z_zu1ww-N65HXrJ-DGsIE6a-jBi7xFP-P3WFWXY#palace-mixer-visual"),
+            ("FailureAction", "semid:rZhny3OL-mPo_qcP-UqP92dx-_ZnRvCt-PQX_8bI-~bV7MNU#ranger-indigo-north"),
+            ("FailurePolicy", "semid:G64TMyaz-Jwg1A4B-LD3SbkN-TFishyG-TrHPpFS-46wSYcQ#email-boston-goblin"),
+            ("FieldInstr", "semid:dj_BIU~6-jptszxC-d55O~4a-VhB4pey-mCNeKl_-usIbLPM#actor-sonic-club"),
+            ("GfaConfig", "semid:Xlw~rohb-E3EHA7b-dmJM9qb-XQzYl1P-FlzSGex-b_NhjEk#empty-camel-trick"),
+            ("Instr", "semid:pdPg2zQB-M6ItkVY-tlCO3H9-0lJQa7y-rWQfefz-wTs~F9s#slogan-colony-derby"),
+            ("IsaGroups", "semid:DS7l2imX-hGbcYEE-nFayUfJ-wGSwxly-CQSdWUv-FG5SatU#citrus-mirage-puma"),
+            ("RegE", "semid:ixxhXF~M-51W01JK-YEFzIUy-eVj5XHU-m3SvbZH-gsNihkY#fish-voice-thermos"),
+            ("SolinasPrime", "semid:Z0uLwtgJ-8NsLmPj-ba31dwj-181rHGu-0uvDyF6-zMXSxG0#toyota-serpent-portal"),
+        ];
+        assert_eq!(lib.types.len(), expected.len(), "the library types have changed");
+        for (name, sem_id) in expected {
+            let ty = lib
+                .types
+                .get(&tn!(name))
+                .unwrap_or_else(|| panic!("type {name} is missing"));
+            assert_eq!(ty.sem_id_named(&tn!(name)).to_string(), sem_id, "semantic id of {name} has changed");
+        }
+    }
+
+    #[test]
+    fn write_files() {
+        let dir = std::env::temp_dir().join(format!("zkaluvm-stl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_finite_field_stl(&dir).unwrap();
+        for ext in ["stl", "sty", "sta"] {
+            let path = dir.join(format!("{LIB_NAME_FINITE_FIELD}@{}.{ext}", env!("CARGO_PKG_VERSION")));
+            let data = std::fs::read(&path).unwrap();
+            assert!(!data.is_empty(), "{} is empty", path.display());
+        }
+        let sty =
+            std::fs::read_to_string(dir.join(format!("{LIB_NAME_FINITE_FIELD}@{}.sty", env!("CARGO_PKG_VERSION"))))
+                .unwrap();
+        assert!(sty.contains(LIB_ID_FINITE_FIELD));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(write_finite_field_stl(dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn instr_types() {
+        let lib = finite_field_stl();
+        for name in ["Fe256", "RegE", "ConstVal", "Bits", "GfaConfig", "FieldInstr", "Instr"] {
+            assert!(lib.types.contains_key(&tn!(name)), "type {name} is missing");
+        }
+    }
 }
@@ -0,0 +1,201 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Checks of the constant-time execution mode.
+//!
+//! Timing tests are sensitive to the load of the host, so they are ignored by default; run them
+//! with `cargo test --release --test constant_time -- --ignored`. The harness follows the `dudect`
+//! approach: it measures execution time of an operation for two classes of inputs (fixed low
+//! Hamming weight values and random values), and applies Welch's t-test to the measurements.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use aluvm::regs::Status;
+use aluvm::{CoreConfig, CoreExt};
+use amplify::num::u256;
+use zkaluvm::{fe256, run_with, zk_aluasm, GfaConfig, GfaCore, RegE, FIELD_ORDER_25519, FIELD_ORDER_STARK};
+
+/// Threshold of the t-statistic above which the timing is considered dependent on the input class.
+const T_THRESHOLD: f64 = 10.0;
+const SAMPLES: usize = 20_000;
+
+fn config(constant_time: bool) -> GfaConfig {
+    GfaConfig {
+        constant_time,
+        ..GfaConfig::new(FIELD_ORDER_25519)
+    }
+}
+
+/// Xorshift generator; the quality of randomness is irrelevant for the timing measurements.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn random(&mut self) -> fe256 {
+        let val = u256::from_inner([self.next(), self.next(), self.next(), self.next()]);
+        fe256::from(val % FIELD_ORDER_25519)
+    }
+}
+
+/// Computes Welch's t-statistic for two sets of measurements.
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let stats = |x: &[f64]| {
+        let n = x.len() as f64;
+        let mean = x.iter().sum::<f64>() / n;
+        let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, var, n)
+    };
+    let (ma, va, na) = stats(a);
+    let (mb, vb, nb) = stats(b);
+    (ma - mb) / (va / na + vb / nb).sqrt()
+}
+
+/// Measures an operation over a register with a fixed value and with random values, returning the
+/// t-statistic of the timings.
+fn timing_variance(fixed: fe256, op: impl Fn(&mut GfaCore) -> Status) -> f64 {
+    let mut rng = Rng(4483);
+    let mut core = GfaCore::with(config(true));
+    // Inputs are prepared in advance, so generating them doesn't affect the measurements
+    let inputs = (0..SAMPLES * 2)
+        .map(|_| {
+            let class = (rng.next() & 1) as usize;
+            let val = if class == 0 { fixed } else { rng.random() };
+            (class, rng.random(), val)
+        })
+        .collect::<Vec<_>>();
+    let mut timings = [Vec::with_capacity(SAMPLES), Vec::with_capacity(SAMPLES)];
+    for (class, a, b) in inputs {
+        core.put(RegE::E1, Some(a));
+        core.put(RegE::E2, Some(b));
+        let start = Instant::now();
+        black_box(op(black_box(&mut core)));
+        timings[class].push(start.elapsed().as_nanos() as f64);
+    }
+    // Remove outliers caused by interrupts and context switches
+    for class in &mut timings {
+        class.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        class.truncate(class.len() * 9 / 10);
+    }
+    welch_t(&timings[0], &timings[1]).abs()
+}
+
+#[test]
+#[ignore]
+fn mul_timing() {
+    let t = timing_variance(fe256::from(1u8), |core| core.mul_mod(RegE::E1, RegE::E2));
+    assert!(t < T_THRESHOLD, "timing of mul depends on the operand value (t = {t:.2})");
+}
+
+#[test]
+#[ignore]
+fn add_timing() {
+    let t = timing_variance(fe256::ZERO, |core| core.add_mod(RegE::E1, RegE::E2));
+    assert!(t < T_THRESHOLD, "timing of add depends on the operand value (t = {t:.2})");
+}
+
+#[test]
+#[ignore]
+fn neg_timing() {
+    let t = timing_variance(fe256::ZERO, |core| core.neg_mod(RegE::E1, RegE::E2));
+    assert!(t < T_THRESHOLD, "timing of neg depends on the operand value (t = {t:.2})");
+}
+
+#[test]
+#[ignore]
+fn inv_timing() {
+    let t = timing_variance(fe256::from(1u8), |core| core.inv_mod(RegE::E1, RegE::E2));
+    assert!(t < T_THRESHOLD, "timing of inv depends on the operand value (t = {t:.2})");
+}
+
+#[test]
+#[ignore]
+fn eq_timing() {
+    let t = timing_variance(fe256::from(1u8), |core| core.eqv(RegE::E1, RegE::E2));
+    assert!(t < T_THRESHOLD, "timing of eq depends on the operand value (t = {t:.2})");
+}
+
+#[test]
+fn same_results() {
+    const A: u256 = u256::from_inner([0x1234567890ABCDEF, 0x1234567890ABCDEF, 0, 0]);
+    const B: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    let code = zk_aluasm! {
+        put     E1, A;
+        put     E2, B;
+        mov     E3, E1;
+        mul     E3, E2;
+        add     E3, E2;
+        neg     E4, E3;
+        add     E4, E3;
+        put     E5, 0;
+        eq      E4, E5;
+        chk     CO;
+        fits    E1, 128.bits;
+        chk     CO;
+    };
+    let core_config = CoreConfig {
+        halt: true,
+        complexity_lim: None,
+    };
+    let regular = run_with(&code, core_config, config(false)).unwrap();
+    let constant = run_with(&code, core_config, config(true)).unwrap();
+    assert!(regular.is_ok());
+    assert!(constant.is_ok());
+    assert!(constant.vm.core.cx.is_constant_time());
+    for reg in RegE::ALL {
+        assert_eq!(regular.reg(reg), constant.reg(reg));
+    }
+}
+
+#[test]
+fn same_inverse() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    // The inverse is computed with Fermat's little theorem, which requires a prime order
+    let config = |constant_time| GfaConfig {
+        field_order: FIELD_ORDER_STARK,
+        ..config(constant_time)
+    };
+    let core_config = CoreConfig {
+        halt: false,
+        complexity_lim: None,
+    };
+    let code = zk_aluasm! {
+        put     E1, VAL;
+        inv     E2, E1;
+        put     E3, 0;
+        inv     E4, E3;
+    };
+    let regular = run_with(&code, core_config, config(false)).unwrap();
+    let constant = run_with(&code, core_config, config(true)).unwrap();
+    assert_eq!(regular.ck, Status::Fail);
+    assert_eq!(constant.ck, Status::Fail);
+    assert!(regular.reg(RegE::E2).is_some());
+    for reg in RegE::ALL {
+        assert_eq!(regular.reg(reg), constant.reg(reg));
+    }
+}
@@ -24,11 +24,14 @@ extern crate alloc;
 
 use aluvm::isa::ReservedInstr;
 use aluvm::regs::Status;
-use aluvm::{CoreConfig, CoreExt, Lib, LibId, LibSite, Vm};
+use aluvm::{CoreConfig, CoreExt, LibId, Vm};
 use amplify::default;
 use amplify::num::u256;
-use zkaluvm::gfa::{ConstVal, FieldInstr, Instr};
-use zkaluvm::{fe256, zk_aluasm, RegE};
+use zkaluvm::gfa::{ConstVal, Expr, ExprCompiler, FieldInstr, Instr, ProgramBuilder};
+use zkaluvm::{
+    fe256, run_with, zk_aluasm, FailureAction, FailurePolicy, GfaConfig, RegE, SolinasPrime, FIELD_ORDER_BABYBEAR,
+    FIELD_ORDER_GOLDILOCKS, FIELD_ORDER_STARK,
+};
 
 const CONFIG: CoreConfig = CoreConfig {
     halt: false,
@@ -40,18 +43,9 @@ fn stand(code: Vec<Instr<LibId>>) -> Vm<Instr<LibId>> { stand_check(code, true)
 fn stand_fail(code: Vec<Instr<LibId>>) -> Vm<Instr<LibId>> { stand_check(code, false) }
 
 fn stand_check(code: Vec<Instr<LibId>>, expect: bool) -> Vm<Instr<LibId>> {
-    let lib = Lib::assemble(&code).unwrap();
-    let lib_id = lib.lib_id();
-
-    let mut vm = Vm::<Instr<LibId>>::with(CONFIG, default!());
-    let resolver = |id: LibId| {
-        assert_eq!(id, lib_id);
-        Some(&lib)
-    };
-    let res = vm.exec(LibSite::new(lib_id, 0), &(), resolver).is_ok();
-    assert_eq!(res, expect);
-
-    vm
+    let outcome = run_with(&code, CONFIG, default!()).unwrap();
+    assert_eq!(outcome.is_ok(), expect);
+    outcome.vm
 }
 
 #[test]
@@ -95,6 +89,35 @@ fn putd() {
     assert_eq!(vm.core.co(), Status::Ok);
 }
 
+#[test]
+fn putd_outside_field() {
+    let code = vec![FieldInstr::PutD {
+        dst: RegE::E1,
+        data: fe256::from(u256::MAX),
+    }
+    .into()];
+    let vm = stand_fail(code);
+    assert_eq!(vm.core.cx.get(RegE::E1), None);
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.co(), Status::Ok);
+}
+
+#[test]
+fn putv_outside_field() {
+    let config = GfaConfig {
+        field_order: FIELD_ORDER_BABYBEAR,
+        ..default!()
+    };
+    let code = vec![FieldInstr::PutV {
+        dst: RegE::E1,
+        val: ConstVal::ValU64Max,
+    }
+    .into()];
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert!(!outcome.is_ok());
+    assert_eq!(outcome.reg(RegE::E1), None);
+}
+
 #[test]
 fn putz() {
     let code = zk_aluasm! {
@@ -246,6 +269,133 @@ fn mov() {
     assert_eq!(vm.core.co(), Status::Ok);
 }
 
+#[test]
+fn cmov() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    let code = zk_aluasm! {
+        put     E1, VAL;
+        put     E2, 1;
+        put     E3, 1;
+        put     E4, 1;
+        eq      E1, E2;
+        cmov    E2, E1;
+        cmov    E5, E1;
+        not     CO;
+        cmov    E3, E1;
+        cmov    E4, EH;
+    };
+    for config in [
+        default!(),
+        GfaConfig {
+            constant_time: true,
+            ..default!()
+        },
+    ] {
+        let outcome = run_with(&code, CONFIG, config).unwrap();
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.co, Status::Ok);
+        assert_eq!(outcome.reg(RegE::E1), Some(fe256::from(VAL)));
+        assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(1u8)));
+        assert_eq!(outcome.reg(RegE::E3), Some(fe256::from(VAL)));
+        assert_eq!(outcome.reg(RegE::E4), None);
+        assert_eq!(outcome.reg(RegE::E5), None);
+    }
+}
+
+#[test]
+fn sel() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    let code = zk_aluasm! {
+        put     E1, VAL;
+        put     E2, 1;
+        eq      E1, E1;
+        sel     E3, E1, E2;
+        sel     E4, EH, E2;
+        not     CO;
+        sel     E5, E1, E2;
+        sel     E6, E1, EH;
+    };
+    for config in [
+        default!(),
+        GfaConfig {
+            constant_time: true,
+            ..default!()
+        },
+    ] {
+        let outcome = run_with(&code, CONFIG, config).unwrap();
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.co, Status::Fail);
+        assert_eq!(outcome.reg(RegE::E3), Some(fe256::from(VAL)));
+        assert_eq!(outcome.reg(RegE::E4), None);
+        assert_eq!(outcome.reg(RegE::E5), Some(fe256::from(1u8)));
+        assert_eq!(outcome.reg(RegE::E6), None);
+    }
+}
+
+#[test]
+fn lt() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    let code = zk_aluasm! {
+        put     E1, VAL;
+        put     E2, 1;
+        lt      E2, E1;
+        chk     CO;
+        lt      E1, E2;
+        not     CO;
+        chk     CO;
+        lt      E1, E1;
+        not     CO;
+        chk     CO;
+        lt      E1, EH;
+    };
+    for config in [
+        default!(),
+        GfaConfig {
+            constant_time: true,
+            ..default!()
+        },
+    ] {
+        let outcome = run_with(&code, CONFIG, config).unwrap();
+        // Comparison with an uninitialized register fails `CK` without changing `CO`
+        assert!(!outcome.is_ok());
+        assert_eq!(outcome.co, Status::Ok);
+        assert_eq!(outcome.ck, Status::Fail);
+    }
+}
+
+#[test]
+fn rng() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    let code = zk_aluasm! {
+        put     E1, 999_999_999;
+        rng     E1, 1_000_000_000;
+        chk     CO;
+        put     E2, 1_000_000_000;
+        rng     E2, 1_000_000_000;
+        not     CO;
+        chk     CO;
+        rng     E2, VAL;
+        chk     CO;
+        rng     E1, 0;
+        not     CO;
+        chk     CO;
+        rng     EH, 1;
+    };
+    for config in [
+        default!(),
+        GfaConfig {
+            constant_time: true,
+            ..default!()
+        },
+    ] {
+        let outcome = run_with(&code, CONFIG, config).unwrap();
+        // Range check of an uninitialized register fails `CK` without changing `CO`
+        assert!(!outcome.is_ok());
+        assert_eq!(outcome.co, Status::Ok);
+        assert_eq!(outcome.ck, Status::Fail);
+    }
+}
+
 #[test]
 fn eq() {
     // Cmp equals
@@ -321,6 +471,335 @@ fn neg() {
     assert_eq!(vm.core.cx.get(RegE::EF), None);
     assert_eq!(vm.core.ck(), Status::Fail);
     assert_eq!(vm.core.co(), Status::Ok);
+
+    // Negate zero
+    let vm = stand(zk_aluasm! {
+        put     EF, 0;
+        neg     E1, EF;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::ZERO));
+    assert_eq!(vm.core.ck(), Status::Ok);
+}
+
+#[test]
+fn inv() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    // Inverse multiplied by the value gives one
+    let config = GfaConfig {
+        field_order: FIELD_ORDER_STARK,
+        ..default!()
+    };
+    let code = zk_aluasm! {
+        put     EF, VAL;
+        inv     E1, EF;
+        mul     E1, EF;
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.reg(RegE::EF), Some(fe256::from(VAL)));
+    assert_eq!(outcome.reg(RegE::E1), Some(fe256::from(1u8)));
+
+    // The default order is not prime, so even values have no inverse
+    let vm = stand_fail(zk_aluasm! {
+        put     EF, VAL;
+        inv     E1, EF;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), None);
+    assert_eq!(vm.core.ck(), Status::Fail);
+
+    // Inverse of one, same register
+    let vm = stand(zk_aluasm! {
+        put     EF, 1;
+        inv     EF, EF;
+    });
+    assert_eq!(vm.core.cx.get(RegE::EF), Some(fe256::from(1u8)));
+    assert_eq!(vm.core.ck(), Status::Ok);
+
+    // Inverse of a None
+    let vm = stand_fail(zk_aluasm! {
+        put     E1, 1;
+        inv     E1, EF;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(1u8)));
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.co(), Status::Ok);
+
+    // Inverse of zero
+    let vm = stand_fail(zk_aluasm! {
+        put     EF, 0;
+        inv     E1, EF;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), None);
+    assert_eq!(vm.core.ck(), Status::Fail);
+}
+
+#[test]
+fn exp() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    // Power of a value
+    let vm = stand(zk_aluasm! {
+        put     E1, 3;
+        put     E2, 5;
+        exp     E1, E2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(243u8)));
+    assert_eq!(vm.core.cx.get(RegE::E2), Some(fe256::from(5u8)));
+    assert_eq!(vm.core.ck(), Status::Ok);
+    assert_eq!(vm.core.co(), Status::Ok);
+
+    // Fermat's little theorem: `a^(p-1) = 1` in a prime field
+    let config = GfaConfig {
+        field_order: FIELD_ORDER_STARK,
+        ..default!()
+    };
+    let max = FIELD_ORDER_STARK - u256::ONE;
+    let code = zk_aluasm! {
+        put     EF, VAL;
+        put     E1, max;
+        exp     EF, E1;
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.reg(RegE::EF), Some(fe256::from(1u8)));
+
+    // Zero to the power of zero, same register
+    let vm = stand(zk_aluasm! {
+        put     E1, 0;
+        exp     E1, E1;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(1u8)));
+    assert_eq!(vm.core.ck(), Status::Ok);
+
+    // Exponent is None
+    let vm = stand_fail(zk_aluasm! {
+        put     E1, 3;
+        exp     E1, E2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(3u8)));
+    assert_eq!(vm.core.ck(), Status::Fail);
+}
+
+#[test]
+fn expi() {
+    // Poseidon S-box
+    let vm = stand(zk_aluasm! {
+        put     E1, 3;
+        exp     E1, 5;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(243u8)));
+    assert_eq!(vm.core.ck(), Status::Ok);
+    assert_eq!(vm.core.co(), Status::Ok);
+
+    // Same result as with the exponent in a register
+    let vm = stand(zk_aluasm! {
+        put     E1, 3;
+        put     E2, 255;
+        mov     E3, E1;
+        exp     E1, E2;
+        exp     E3, 255;
+        eq      E1, E3;
+    });
+    assert_eq!(vm.core.ck(), Status::Ok);
+    assert_eq!(vm.core.co(), Status::Ok);
+
+    // Zero power
+    let vm = stand(zk_aluasm! {
+        put     E1, 0;
+        exp     E1, 0;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(1u8)));
+
+    // Power of a None
+    let vm = stand_fail(zk_aluasm! {
+        exp     E1, 2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), None);
+    assert_eq!(vm.core.ck(), Status::Fail);
+}
+
+#[test]
+fn sqr() {
+    const VAL: u256 = u256::from_inner([73864950, 463656, 3456556, 23456657]);
+    // Same result as multiplying a value by itself
+    let mut code = zk_aluasm! {
+        put     E1, VAL;
+        mov     E2, E1;
+        mul     E2, E1;
+        sqr     E3, E1;
+        eq      E2, E3;
+        chk     CO;
+        mov     E4, E5;
+        mul     E4, E5;
+        sqr     E6, E5;
+        eq      E4, E6;
+        chk     CO;
+        sqr     E1, E1;
+    };
+    code.insert(
+        6,
+        FieldInstr::PutV {
+            dst: RegE::E5,
+            val: ConstVal::ValFeMAX,
+        }
+        .into(),
+    );
+    let vm = stand(code.clone());
+    assert_eq!(vm.core.cx.get(RegE::E6), Some(fe256::from(1u8)));
+    assert_eq!(vm.core.cx.get(RegE::E1), vm.core.cx.get(RegE::E3));
+
+    for config in [
+        GfaConfig {
+            constant_time: true,
+            ..default!()
+        },
+        GfaConfig {
+            field_order: FIELD_ORDER_STARK,
+            solinas: Some(SolinasPrime::STARK),
+            ..default!()
+        },
+    ] {
+        let outcome = run_with(&code, CONFIG, config).unwrap();
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.reg(RegE::E6), Some(fe256::from(1u8)));
+    }
+
+    // Square of a None
+    let vm = stand_fail(zk_aluasm! {
+        put     E1, 3;
+        sqr     E1, E2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(3u8)));
+    assert_eq!(vm.core.ck(), Status::Fail);
+}
+
+#[test]
+fn sqrt() {
+    let stark = GfaConfig {
+        field_order: FIELD_ORDER_STARK,
+        ..default!()
+    };
+
+    // Root of a square, the smaller one is chosen
+    let code = zk_aluasm! {
+        put     E1, 9;
+        sqrt    E2, E1;
+        put     E3, 0;
+        sqrt    E4, E3;
+    };
+    let outcome = run_with(&code, CONFIG, stark).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.vm.core.co(), Status::Ok);
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(3u8)));
+    assert_eq!(outcome.reg(RegE::E4), Some(fe256::ZERO));
+
+    // Non-residue
+    let code = zk_aluasm! {
+        put     E1, 3;
+        put     E2, 5;
+        sqrt    E2, E1;
+    };
+    let outcome = run_with(&code, CONFIG, stark).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.vm.core.co(), Status::Fail);
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(5u8)));
+
+    // Roots of squares in the fields of different two-adicity, with the generic and the special
+    // multiplication routines
+    for (order, prime, max) in [
+        (FIELD_ORDER_STARK, SolinasPrime::STARK, u256::from_inner([73864950, 463656, 3456556, 23456657])),
+        (FIELD_ORDER_GOLDILOCKS, SolinasPrime::GOLDILOCKS, u256::from(0xFFFF_FFFF_0000_0000u64)),
+        (FIELD_ORDER_BABYBEAR, SolinasPrime::BABYBEAR, u256::from(0x7800_0000u64)),
+    ] {
+        for val in [u256::from(0xDEADu16), max] {
+            let code = zk_aluasm! {
+                put     E1, val;
+                sqr     E2, E1;
+                sqrt    E3, E2;
+                sqr     E4, E3;
+                eq      E2, E4;
+                chk     CO;
+                neg     E5, E3;
+            };
+            for solinas in [None, Some(prime)] {
+                let config = GfaConfig {
+                    field_order: order,
+                    solinas,
+                    ..default!()
+                };
+                let outcome = run_with(&code, CONFIG, config).unwrap();
+                assert!(outcome.is_ok());
+                let root = outcome.reg(RegE::E3).unwrap().to_u256();
+                assert!(root == val || root == order - val);
+                assert!(root <= outcome.reg(RegE::E5).unwrap().to_u256());
+            }
+        }
+    }
+
+    // Constant-time multiplication
+    let code = zk_aluasm! {
+        put     E1, 0x1234;
+        sqr     E1, E1;
+        sqrt    E2, E1;
+    };
+    let config = GfaConfig {
+        field_order: FIELD_ORDER_BABYBEAR,
+        constant_time: true,
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(0x1234u16)));
+
+    // Prime field where all the primes below 67 are quadratic residues
+    let code = zk_aluasm! {
+        put     E1, 0x1234;
+        sqr     E1, E1;
+        sqrt    E2, E1;
+        put     E3, 67;
+        sqrt    E4, E3;
+    };
+    let config = GfaConfig {
+        field_order: u256::from(48473881u32),
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert!(outcome.is_ok());
+    assert_eq!(outcome.vm.core.co(), Status::Fail);
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(0x1234u16)));
+    assert_eq!(outcome.reg(RegE::E4), None);
+
+    // The Jacobi symbol modulo a square is never -1, so no quadratic non-residue is found
+    let code = zk_aluasm! {
+        put     E1, 1;
+        put     E2, 5;
+        sqrt    E2, E1;
+    };
+    let config = GfaConfig {
+        field_order: u256::from(9u8),
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert_eq!(outcome.vm.core.ck(), Status::Fail);
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(5u8)));
+
+    // The same for a square of a large prime, where the search must stop at the bound instead of
+    // running through the whole field
+    let p = (u256::ONE << 127) - u256::ONE;
+    let config = GfaConfig {
+        field_order: p * p,
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert_eq!(outcome.vm.core.ck(), Status::Fail);
+    assert_eq!(outcome.reg(RegE::E2), Some(fe256::from(5u8)));
+
+    // Root of a None
+    let vm = stand_fail(zk_aluasm! {
+        put     E1, 3;
+        sqrt    E1, E2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(3u8)));
+    assert_eq!(vm.core.ck(), Status::Fail);
 }
 
 #[test]
@@ -362,6 +841,15 @@ fn add() {
     assert_eq!(vm.core.ck(), Status::Ok);
     assert_eq!(vm.core.co(), Status::Ok);
 
+    // Overflow of 256 bits
+    let vm = stand(zk_aluasm! {
+        put     E1, max;
+        put     E2, max;
+        add     E1, E2;
+    });
+    assert_eq!(vm.core.cx.get(RegE::E1), Some(fe256::from(max - ONE)));
+    assert_eq!(vm.core.ck(), Status::Ok);
+
     // none
     let vm = stand_fail(zk_aluasm! {
         put     E1, VAL;
@@ -506,6 +994,57 @@ fn mul() {
     assert_eq!(vm.core.co(), Status::Ok);
 }
 
+#[test]
+fn mul_solinas() {
+    const VAL: u256 = u256::from_inner([73864950, 0, 0, 0]);
+    let mut code = zk_aluasm! {
+        put     E1, VAL;
+        put     E2, VAL;
+        mul     E1, E2;
+        mul     E1, E1;
+        mul     E1, E1;
+        mul     E1, E1;
+        mul     E3, E3;
+    };
+    code.insert(
+        2,
+        FieldInstr::PutV {
+            dst: RegE::E3,
+            val: ConstVal::ValFeMAX,
+        }
+        .into(),
+    );
+    for (order, prime) in [
+        (FIELD_ORDER_GOLDILOCKS, SolinasPrime::GOLDILOCKS),
+        (FIELD_ORDER_BABYBEAR, SolinasPrime::BABYBEAR),
+        (FIELD_ORDER_STARK, SolinasPrime::STARK),
+    ] {
+        let mut config = GfaConfig {
+            field_order: order,
+            ..default!()
+        };
+        let generic = run_with(&code, CONFIG, config).unwrap();
+        config.solinas = Some(prime);
+        let special = run_with(&code, CONFIG, config).unwrap();
+        assert!(generic.is_ok());
+        assert!(special.is_ok());
+        assert_eq!(generic.vm.core.cx.solinas(), None);
+        assert_eq!(special.vm.core.cx.solinas(), Some(prime));
+        for reg in RegE::ALL {
+            assert_eq!(generic.reg(reg), special.reg(reg));
+        }
+    }
+
+    // A form not matching the field order is ignored
+    let config = GfaConfig {
+        field_order: FIELD_ORDER_STARK,
+        solinas: Some(SolinasPrime::GOLDILOCKS),
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert_eq!(outcome.vm.core.cx.solinas(), None);
+}
+
 #[test]
 fn reset() {
     // Increment
@@ -547,3 +1086,83 @@ fn reserved() {
     assert_eq!(vm.core.co(), Status::Ok);
     assert_eq!(vm.core.ck(), Status::Fail);
 }
+
+#[test]
+fn failure_policy() {
+    let code = zk_aluasm! {
+        add     E1, E2;
+        put     E3, 1;
+    };
+    let run = |halt: bool, arithmetic: FailureAction| {
+        let core_config = CoreConfig {
+            halt,
+            complexity_lim: None,
+        };
+        let config = GfaConfig {
+            failure_policy: FailurePolicy {
+                arithmetic,
+                ..default!()
+            },
+            ..default!()
+        };
+        run_with(&code, core_config, config).unwrap().vm
+    };
+    let one = Some(fe256::from(1u8));
+
+    let vm = run(false, FailureAction::Inherit);
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.cx.get(RegE::E3), one);
+    let vm = run(true, FailureAction::Inherit);
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.cx.get(RegE::E3), None);
+
+    let vm = run(false, FailureAction::Halt);
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.cx.get(RegE::E3), None);
+
+    let vm = run(true, FailureAction::Continue);
+    assert_eq!(vm.core.ck(), Status::Fail);
+    assert_eq!(vm.core.cx.get(RegE::E3), one);
+
+    let vm = run(true, FailureAction::Signal);
+    assert_eq!(vm.core.ck(), Status::Ok);
+    assert_eq!(vm.core.co(), Status::Fail);
+    assert_eq!(vm.core.cx.get(RegE::E3), one);
+
+    // Policies for other classes don't apply
+    let config = GfaConfig {
+        failure_policy: FailurePolicy {
+            moves: FailureAction::Signal,
+            checks: FailureAction::Signal,
+            reserved: FailureAction::Signal,
+            ..default!()
+        },
+        ..default!()
+    };
+    let outcome = run_with(&code, CONFIG, config).unwrap();
+    assert_eq!(outcome.ck, Status::Fail);
+}
+
+#[test]
+fn expr() {
+    let mut builder = ProgramBuilder::new();
+    builder
+        .put(RegE::EA, 6u8)
+        .unwrap()
+        .put(RegE::EB, 7u8)
+        .unwrap();
+    let mut compiler = ExprCompiler::with_builder(builder, default!());
+    compiler
+        .assign(RegE::E3, Expr::mul(RegE::EA, RegE::EB) + 5u8)
+        .assert_eq(Expr::reg(RegE::E3) - 47u8, Expr::val(0u8))
+        .assign(RegE::EA, Expr::reg(RegE::EA) * RegE::EA - RegE::EB);
+    let vm = stand(compiler.compile().unwrap());
+    assert_eq!(vm.core.cx.get(RegE::E3), Some(fe256::from(47u8)));
+    assert_eq!(vm.core.cx.get(RegE::EA), Some(fe256::from(29u8)));
+
+    let mut compiler = ExprCompiler::new();
+    compiler
+        .assign(RegE::E1, Expr::val(2u8))
+        .assert_eq(Expr::reg(RegE::E1) * 2u8, Expr::val(5u8));
+    stand_fail(compiler.compile().unwrap());
+}
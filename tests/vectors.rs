@@ -0,0 +1,38 @@
+// AluVM ISA extension for Galois fields
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2024-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2024-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+#![cfg(feature = "testing")]
+
+use zkaluvm::testing::TestVectors;
+
+fn run(path: &str) {
+    let vectors = TestVectors::load(path).unwrap();
+    assert!(!vectors.vectors.is_empty());
+    let failures = vectors.run();
+    assert!(failures.is_empty(), "{failures:#?}");
+}
+
+#[test]
+fn toml() { run("tests/vectors/gfa.toml"); }
+
+#[test]
+fn json() { run("tests/vectors/gfa.json"); }